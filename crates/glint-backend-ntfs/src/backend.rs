@@ -5,10 +5,11 @@
 
 use crate::error::NtfsError;
 use crate::mft::{scan_mft, scan_recursive};
-use crate::usn::{get_journal_state, UsnWatcher};
+use crate::usn::{catch_up, get_journal_state, UsnWatcher};
 use crate::volume::enumerate_ntfs_volumes;
 use glint_core::backend::{
-    ChangeHandler, FileSystemBackend, JournalState, ScanProgress, VolumeInfo, WatchHandle,
+    BackendCapabilities, ChangeHandler, FileSystemBackend, JournalState, ScanProgress, VolumeInfo,
+    WatchHandle,
 };
 use glint_core::types::FileRecord;
 use std::sync::Arc;
@@ -31,12 +32,18 @@ use tracing::{info, warn};
 pub struct NtfsBackend {
     /// Whether to attempt MFT access (requires elevation)
     try_mft: bool,
+
+    /// Whether to skip changes the system makes while maintaining files
+    ignore_maintenance: bool,
 }
 
 impl NtfsBackend {
     /// Create a new NTFS backend.
     pub fn new() -> Self {
-        NtfsBackend { try_mft: true }
+        NtfsBackend {
+            try_mft: true,
+            ignore_maintenance: true,
+        }
     }
 
     /// Create a backend that skips MFT access attempts.
@@ -44,7 +51,18 @@ impl NtfsBackend {
     /// Use this if you know the process doesn't have elevated privileges
     /// to avoid the overhead of failed access attempts.
     pub fn without_mft() -> Self {
-        NtfsBackend { try_mft: false }
+        NtfsBackend {
+            try_mft: false,
+            ..Self::new()
+        }
+    }
+
+    /// Set whether the watcher skips changes the system makes while
+    /// maintaining files, such as defragmentation, deduplication and
+    /// replication. On by default.
+    pub fn ignoring_maintenance(mut self, ignore: bool) -> Self {
+        self.ignore_maintenance = ignore;
+        self
     }
 
     /// Check if we have elevated privileges.
@@ -94,32 +112,65 @@ impl FileSystemBackend for NtfsBackend {
             .map_err(|e| anyhow::anyhow!("{}", e))?;
 
         info!(
-            volume = %volume.mount_point,
+            volume = %volume.display_name(),
             method = if self.try_mft { "MFT" } else { "recursive" },
             "Starting volume scan"
         );
 
+        // Folder volumes walk just their folder when falling back
+        let scan_root = volume.root.as_deref().unwrap_or(&ntfs_info.mount_point);
+
+        let report_method = |method: &'static str| {
+            if let Some(progress) = &progress {
+                progress.on_method(method);
+            }
+        };
+
         let records = if self.try_mft {
             // Try MFT first, fall back to recursive on access denied
+            report_method("mft");
             match scan_mft(&ntfs_info, &volume.id, progress.clone()) {
-                Ok(records) => records,
+                Ok(scan) => {
+                    if !scan.errors.is_clean() {
+                        warn!(
+                            volume = %volume.display_name(),
+                            retries = scan.errors.retries,
+                            recovered = scan.errors.recovered,
+                            skipped = scan.errors.skipped,
+                            last_error = ?scan.errors.last_error,
+                            "MFT scan worked around read errors"
+                        );
+                    }
+                    scan.records
+                }
                 Err(NtfsError::AccessDenied { .. }) => {
                     warn!(
                         volume = %volume.mount_point,
                         "MFT access denied, falling back to recursive scan"
                     );
-                    scan_recursive(&ntfs_info, &volume.id, progress)
+                    report_method("recursive");
+                    scan_recursive(scan_root, &volume.id, progress)
                         .map_err(|e| anyhow::anyhow!("{}", e))?
                 }
                 Err(e) => return Err(anyhow::anyhow!("{}", e)),
             }
         } else {
-            scan_recursive(&ntfs_info, &volume.id, progress)
-                .map_err(|e| anyhow::anyhow!("{}", e))?
+            report_method("recursive");
+            scan_recursive(scan_root, &volume.id, progress).map_err(|e| anyhow::anyhow!("{}", e))?
+        };
+
+        // The MFT covers the whole volume; keep only the folder
+        let records: Vec<FileRecord> = if volume.root.is_some() {
+            records
+                .into_iter()
+                .filter(|r| volume.contains_path(&r.path))
+                .collect()
+        } else {
+            records
         };
 
         info!(
-            volume = %volume.mount_point,
+            volume = %volume.display_name(),
             files = records.iter().filter(|r| !r.is_dir).count(),
             dirs = records.iter().filter(|r| r.is_dir).count(),
             "Scan complete"
@@ -140,13 +191,14 @@ impl FileSystemBackend for NtfsBackend {
             ));
         }
 
-        let device_path = crate::winapi_utils::normalize_volume_path(&volume.mount_point);
+        let device_path = crate::volume::device_path(&volume.mount_point);
 
         let watcher = UsnWatcher::start(
             device_path,
             volume.id.clone(),
             handler,
             volume.journal_state,
+            self.ignore_maintenance,
         )
         .map_err(|e| anyhow::anyhow!("{}", e))?;
 
@@ -156,8 +208,35 @@ impl FileSystemBackend for NtfsBackend {
         Ok(WatchHandle::new(watcher, shutdown_tx))
     }
 
+    fn catch_up(
+        &self,
+        volume: &VolumeInfo,
+        handler: &dyn ChangeHandler,
+    ) -> anyhow::Result<Option<JournalState>> {
+        let Some(state) = &volume.journal_state else {
+            return Ok(None);
+        };
+        if !volume.supports_change_journal {
+            return Err(anyhow::anyhow!(
+                "Volume {} does not support change journal",
+                volume.mount_point
+            ));
+        }
+
+        let device_path = crate::volume::device_path(&volume.mount_point);
+        let state = catch_up(
+            &device_path,
+            &volume.id,
+            state,
+            handler,
+            self.ignore_maintenance,
+        )
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(Some(state))
+    }
+
     fn get_journal_state(&self, volume: &VolumeInfo) -> anyhow::Result<Option<JournalState>> {
-        let device_path = crate::winapi_utils::normalize_volume_path(&volume.mount_point);
+        let device_path = crate::volume::device_path(&volume.mount_point);
 
         match get_journal_state(&device_path) {
             Ok(state) => Ok(Some(state)),
@@ -173,6 +252,20 @@ impl FileSystemBackend for NtfsBackend {
         }
     }
 
+    fn capabilities(&self) -> BackendCapabilities {
+        // Enumerating the MFT gives names, parents and times but not sizes;
+        // walking folders, as scans do without elevation, reads them from
+        // each directory listing
+        let mft = self.try_mft && Self::has_elevated_privileges();
+        BackendCapabilities {
+            supports_fast_scan: mft,
+            supports_change_journal: true,
+            provides_sizes: !mft,
+            provides_timestamps: true,
+            stable_file_ids: true,
+        }
+    }
+
     fn name(&self) -> &'static str {
         "ntfs"
     }
@@ -53,6 +53,11 @@ impl NtfsError {
         use windows::Win32::Foundation::GetLastError;
 
         let code = unsafe { GetLastError().0 };
+        Self::from_code(function, code)
+    }
+
+    /// Create a WinAPI error from a Win32 error code
+    pub fn from_code(function: &str, code: u32) -> Self {
         let message = format_win32_error(code);
 
         // Check for access denied
@@ -75,6 +80,13 @@ impl NtfsError {
             || matches!(self, NtfsError::WinApi { code: 5, .. })
     }
 
+    /// Check if this error indicates the volume's device went away, e.g. a
+    /// drive was unplugged or a network volume dropped
+    pub fn is_device_gone(&self) -> bool {
+        // ERROR_NOT_READY, ERROR_DEV_NOT_EXIST, ERROR_DEVICE_NOT_CONNECTED
+        matches!(self, NtfsError::WinApi { code: 21 | 55 | 1167, .. })
+    }
+
     /// Check if this error indicates the journal needs a rescan
     pub fn requires_rescan(&self) -> bool {
         matches!(
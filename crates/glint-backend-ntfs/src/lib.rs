@@ -13,7 +13,10 @@
 //! - `volume.rs`: Volume enumeration and information
 //! - `mft.rs`: MFT reading and file enumeration
 //! - `usn.rs`: USN Change Journal monitoring
+//! - `usn_record.rs`: Parsing USN records, independent of the platform
 //! - `winapi_utils.rs`: Low-level Windows API wrappers
+//! - `scheduled_task.rs`: Task Scheduler job running `glint watch --once`
+//! - `recycle_bin.rs`: Sending files to the Recycle Bin
 //!
 //! ## Permissions
 //!
@@ -45,6 +48,15 @@ mod stub;
 #[cfg(not(windows))]
 pub use stub::NtfsBackend;
 
+/// USN record parsing shared by MFT enumeration and journal reads
+pub mod usn_record;
+
+/// Task Scheduler alternative to the Windows service
+pub mod scheduled_task;
+
+/// Sending files to the Recycle Bin
+pub mod recycle_bin;
+
 /// Error types specific to the NTFS backend
 pub mod error;
 pub use error::NtfsError;
@@ -21,13 +21,18 @@
 //! capabilities or recursive directory traversal.
 
 use crate::error::NtfsError;
+use crate::usn_record::parse_buffer;
 use crate::volume::NtfsVolumeInfo;
-use crate::winapi_utils::{filetime_to_datetime, open_volume, SafeHandle};
-use glint_core::backend::ScanProgress;
+use crate::winapi_utils::{
+    filetime_to_datetime, is_cloud_placeholder, last_error, open_volume, retry_io, Attempt,
+    IoErrorStats, RetryPolicy, SafeHandle,
+};
+use glint_core::backend::{ScanEstimate, ScanProgress};
 use glint_core::types::{FileId, FileRecord, VolumeId};
 use std::collections::HashMap;
 use std::mem;
 use std::sync::Arc;
+use std::time::Instant;
 use tracing::{debug, info, warn};
 use windows::Win32::System::Ioctl::{FSCTL_ENUM_USN_DATA, FSCTL_GET_NTFS_VOLUME_DATA};
 use windows::Win32::System::IO::DeviceIoControl;
@@ -63,90 +68,89 @@ struct NtfsVolumeData {
     mft_zone_end: u64,
 }
 
-/// USN record structure (version 2)
-#[repr(C)]
-#[derive(Debug)]
-struct UsnRecordV2 {
-    record_length: u32,
-    major_version: u16,
-    minor_version: u16,
-    file_reference_number: u64,
-    parent_file_reference_number: u64,
-    usn: i64,
-    timestamp: i64,
-    reason: u32,
-    source_info: u32,
-    security_id: u32,
-    file_attributes: u32,
-    file_name_length: u16,
-    file_name_offset: u16,
-    // file_name follows (variable length UTF-16)
-}
-
-/// USN record structure (version 3) - uses 128-bit file IDs
-#[repr(C)]
-#[derive(Debug)]
-struct UsnRecordV3 {
-    record_length: u32,
-    major_version: u16,
-    minor_version: u16,
-    file_reference_number: [u8; 16],        // FILE_ID_128
-    parent_file_reference_number: [u8; 16], // FILE_ID_128
-    usn: i64,
-    timestamp: i64,
-    reason: u32,
-    source_info: u32,
-    security_id: u32,
-    file_attributes: u32,
-    file_name_length: u16,
-    file_name_offset: u16,
-    // file_name follows (variable length UTF-16)
-}
+// Layouts the ioctls expect, the same on x64 and ARM64
+const _: () = assert!(mem::size_of::<MftEnumData>() == 32);
+const _: () = assert!(mem::size_of::<NtfsVolumeData>() == 96);
 
 const FILE_ATTRIBUTE_DIRECTORY: u32 = 0x10;
 
+/// Win32 error returned once the enumeration has passed the last record
+const ERROR_HANDLE_EOF: u32 = 38;
+
+/// MFT records passed over when a range of the MFT can't be read
+const UNREADABLE_SKIP: u64 = 1024;
+
+/// Records found by [`scan_mft`], and the read errors it got past.
+pub struct MftScan {
+    pub records: Vec<FileRecord>,
+    pub errors: IoErrorStats,
+}
+
 /// Scan an NTFS volume by reading the MFT.
 ///
-/// Returns all file records found on the volume.
+/// Returns all file records found on the volume. Transient errors are
+/// retried, and MFT ranges that stay unreadable (bad clusters) are skipped
+/// rather than failing the scan; both are counted in [`MftScan::errors`].
 pub fn scan_mft(
     volume_info: &NtfsVolumeInfo,
     volume_id: &VolumeId,
     progress: Option<Arc<dyn ScanProgress>>,
-) -> Result<Vec<FileRecord>, NtfsError> {
+) -> Result<MftScan, NtfsError> {
     let device_path = volume_info.device_path();
     info!(volume = %device_path, "Starting MFT scan");
 
     let handle = open_volume(&device_path)?;
+    let mut errors = IoErrorStats::default();
 
-    // Get NTFS volume data to understand MFT structure
-    let _vol_data = get_ntfs_volume_data(&handle)?;
+    // The MFT size tells how many records the enumeration will go through
+    let vol_data = get_ntfs_volume_data(&handle, &mut errors)?;
+    let mft_records =
+        vol_data.mft_valid_data_length / u64::from(vol_data.bytes_per_file_record_segment.max(1));
 
     // Enumerate all files using FSCTL_ENUM_USN_DATA
-    let records = enumerate_usn_records(&handle, volume_info, volume_id, progress)?;
-
-    Ok(records)
+    let records = enumerate_usn_records(
+        &handle,
+        volume_info,
+        volume_id,
+        progress,
+        mft_records,
+        &mut errors,
+    )?;
+
+    Ok(MftScan { records, errors })
 }
 
 /// Get NTFS volume data.
-fn get_ntfs_volume_data(handle: &SafeHandle) -> Result<NtfsVolumeData, NtfsError> {
+fn get_ntfs_volume_data(
+    handle: &SafeHandle,
+    errors: &mut IoErrorStats,
+) -> Result<NtfsVolumeData, NtfsError> {
     let mut vol_data: NtfsVolumeData = unsafe { mem::zeroed() };
-    let mut bytes_returned = 0u32;
-
-    let result = unsafe {
-        DeviceIoControl(
-            handle.as_raw(),
-            FSCTL_GET_NTFS_VOLUME_DATA,
-            None,
-            0,
-            Some(&mut vol_data as *mut _ as *mut _),
-            mem::size_of::<NtfsVolumeData>() as u32,
-            Some(&mut bytes_returned),
-            None,
-        )
-    };
 
-    if result.is_err() {
-        return Err(NtfsError::from_win32("FSCTL_GET_NTFS_VOLUME_DATA"));
+    let policy = RetryPolicy::without_skipping();
+    let result = retry_io(&policy, errors, "FSCTL_GET_NTFS_VOLUME_DATA", || {
+        let mut bytes_returned = 0u32;
+        let result = unsafe {
+            DeviceIoControl(
+                handle.as_raw(),
+                FSCTL_GET_NTFS_VOLUME_DATA,
+                None,
+                0,
+                Some(&mut vol_data as *mut _ as *mut _),
+                mem::size_of::<NtfsVolumeData>() as u32,
+                Some(&mut bytes_returned),
+                None,
+            )
+        };
+        if result.is_err() {
+            Err(last_error())
+        } else {
+            Ok(())
+        }
+    });
+
+    if let Err(code) = result {
+        return Err(NtfsError::from_code("FSCTL_GET_NTFS_VOLUME_DATA", code));
     }
 
     debug!(
@@ -162,12 +166,15 @@ fn get_ntfs_volume_data(handle: &SafeHandle) -> Result<NtfsVolumeData, NtfsError
 ///
 /// This is the primary enumeration method. It reads through the MFT
 /// using the USN journal infrastructure, which is efficient and works
-/// on all NTFS volumes.
+/// on all NTFS volumes. Records come in MFT order, so the position
+/// against `mft_records` gives the progress estimate.
 fn enumerate_usn_records(
     handle: &SafeHandle,
     volume_info: &NtfsVolumeInfo,
     volume_id: &VolumeId,
     progress: Option<Arc<dyn ScanProgress>>,
+    mft_records: u64,
+    errors: &mut IoErrorStats,
 ) -> Result<Vec<FileRecord>, NtfsError> {
     // Buffer for USN records
     const BUFFER_SIZE: usize = 64 * 1024;
@@ -188,170 +195,121 @@ fn enumerate_usn_records(
     let mut files_scanned = 0u64;
     let mut dirs_scanned = 0u64;
     let mut last_progress_report = 0u64;
+    let started = Instant::now();
 
     info!(volume = %volume_info.mount_point, "Enumerating MFT records");
 
+    let policy = RetryPolicy::default();
     loop {
-        let mut bytes_returned = 0u32;
-
-        let result = unsafe {
-            DeviceIoControl(
-                handle.as_raw(),
-                FSCTL_ENUM_USN_DATA,
-                Some(&enum_data as *const _ as *const _),
-                mem::size_of::<MftEnumData>() as u32,
-                Some(buffer.as_mut_ptr() as *mut _),
-                buffer.len() as u32,
-                Some(&mut bytes_returned),
-                None,
-            )
-        };
-
-        if result.is_err() {
-            // ERROR_HANDLE_EOF (38) means we've reached the end
-            let error = unsafe { windows::Win32::Foundation::GetLastError().0 };
-            if error == 38 {
-                break;
-            }
-            // Check for access denied
-            if error == 5 {
-                return Err(NtfsError::AccessDenied {
-                    operation: "FSCTL_ENUM_USN_DATA".to_string(),
-                });
+        let result = retry_io(&policy, errors, "FSCTL_ENUM_USN_DATA", || {
+            let mut bytes_returned = 0u32;
+            let result = unsafe {
+                DeviceIoControl(
+                    handle.as_raw(),
+                    FSCTL_ENUM_USN_DATA,
+                    Some(&enum_data as *const _ as *const _),
+                    mem::size_of::<MftEnumData>() as u32,
+                    Some(buffer.as_mut_ptr() as *mut _),
+                    buffer.len() as u32,
+                    Some(&mut bytes_returned),
+                    None,
+                )
+            };
+            if result.is_err() {
+                Err(last_error())
+            } else {
+                Ok(bytes_returned)
             }
-            return Err(NtfsError::from_win32("FSCTL_ENUM_USN_DATA"));
-        }
+        });
 
-        if bytes_returned < 8 {
-            break;
-        }
+        let bytes_returned = match result {
+            Ok(Attempt::Done(bytes)) => bytes,
+            Ok(Attempt::Skipped) => {
+                // Carry on after the bad range; records in it are lost
+                enum_data.start_file_reference_number += UNREADABLE_SKIP;
+                continue;
+            }
+            // We've reached the end
+            Err(ERROR_HANDLE_EOF) => break,
+            // Access denied becomes NtfsError::AccessDenied
+            Err(code) => return Err(NtfsError::from_code("FSCTL_ENUM_USN_DATA", code)),
+        };
 
         // First 8 bytes are the next file reference number
-        let next_ref = u64::from_ne_bytes(buffer[0..8].try_into().unwrap());
-
-        // Parse USN records from the buffer
-        let mut offset = 8usize;
-        while offset + 8 <= bytes_returned as usize {
-            // At least need record_length + major_version
-            // Peek at the record length and version
-            let record_length = u32::from_ne_bytes(buffer[offset..offset + 4].try_into().unwrap());
-            let major_version =
-                u16::from_ne_bytes(buffer[offset + 4..offset + 6].try_into().unwrap());
-
-            if record_length == 0 || offset + record_length as usize > bytes_returned as usize {
-                break;
-            }
+        let Some((next_ref, records)) = parse_buffer(&buffer, bytes_returned as usize) else {
+            break;
+        };
 
-            // Parse based on version
-            let (file_ref, parent_ref, timestamp, file_attrs, name_offset, name_len) =
-                if major_version == 2 {
-                    if offset + mem::size_of::<UsnRecordV2>() > bytes_returned as usize {
-                        break;
-                    }
-                    let record =
-                        unsafe { &*(buffer.as_ptr().wrapping_add(offset) as *const UsnRecordV2) };
-                    (
-                        record.file_reference_number,
-                        record.parent_file_reference_number,
-                        record.timestamp,
-                        record.file_attributes,
-                        record.file_name_offset as usize,
-                        record.file_name_length as usize,
-                    )
-                } else if major_version == 3 {
-                    if offset + mem::size_of::<UsnRecordV3>() > bytes_returned as usize {
-                        break;
-                    }
-                    let record =
-                        unsafe { &*(buffer.as_ptr().wrapping_add(offset) as *const UsnRecordV3) };
-                    // FILE_ID_128: use lower 64 bits for compatibility
-                    let file_ref =
-                        u64::from_ne_bytes(record.file_reference_number[0..8].try_into().unwrap());
-                    let parent_ref = u64::from_ne_bytes(
-                        record.parent_file_reference_number[0..8]
-                            .try_into()
-                            .unwrap(),
-                    );
-                    (
-                        file_ref,
-                        parent_ref,
-                        record.timestamp,
-                        record.file_attributes,
-                        record.file_name_offset as usize,
-                        record.file_name_length as usize,
-                    )
-                } else {
-                    // Skip unknown versions
-                    offset += record_length as usize;
-                    continue;
-                };
+        for record in records {
+            let file_ref = record.file_reference_number;
+            let file_attrs = record.file_attributes;
 
             // Debug: dump raw record info for first few
             if raw_records.len() < 5 {
                 debug!(
-                    record_length = record_length,
-                    major_version = major_version,
+                    major_version = record.major_version,
                     file_ref = file_ref,
-                    parent_ref = parent_ref,
-                    name_offset = name_offset,
-                    name_len = name_len,
+                    parent_ref = record.parent_file_reference_number,
+                    name_len = record.name.len(),
                     file_attrs = file_attrs,
                     "Raw USN record fields"
                 );
             }
 
-            if name_len > 0 && offset + name_offset + name_len <= bytes_returned as usize {
-                let name_ptr = buffer.as_ptr().wrapping_add(offset + name_offset) as *const u16;
-                let name_slice = unsafe { std::slice::from_raw_parts(name_ptr, name_len / 2) };
-                let name = String::from_utf16_lossy(name_slice);
-
-                // Extract file ID (lower 48 bits of reference number)
-                let file_id = FileId::new(file_ref & 0x0000FFFFFFFFFFFF);
-                let parent_id = parent_ref & 0x0000FFFFFFFFFFFF;
-
-                let is_dir = (file_attrs & FILE_ATTRIBUTE_DIRECTORY) != 0;
-
-                // Debug: log first few records to see what we're getting
-                if raw_records.len() < 10 {
-                    debug!(
-                        name = %name,
-                        file_id = file_id.as_u64(),
-                        parent_id = parent_id,
-                        attrs = file_attrs,
-                        is_dir = is_dir,
-                        "Sample MFT record"
-                    );
-                }
+            if record.name.is_empty() {
+                continue;
+            }
+
+            // Extract file ID (lower 48 bits of reference number)
+            let file_id = FileId::new(file_ref & 0x0000FFFFFFFFFFFF);
+            let parent_id = record.parent_file_reference_number & 0x0000FFFFFFFFFFFF;
 
-                raw_records.push(RawFileRecord {
-                    file_id,
-                    parent_id: if parent_id == 0 {
-                        None
-                    } else {
-                        Some(FileId::new(parent_id))
-                    },
-                    name,
-                    is_dir,
-                    timestamp,
-                });
-
-                if is_dir {
-                    dirs_scanned += 1;
+            let is_dir = (file_attrs & FILE_ATTRIBUTE_DIRECTORY) != 0;
+
+            // Debug: log first few records to see what we're getting
+            if raw_records.len() < 10 {
+                debug!(
+                    name = %record.name,
+                    file_id = file_id.as_u64(),
+                    parent_id = parent_id,
+                    attrs = file_attrs,
+                    is_dir = is_dir,
+                    "Sample MFT record"
+                );
+            }
+
+            raw_records.push(RawFileRecord {
+                file_id,
+                parent_id: if parent_id == 0 {
+                    None
                 } else {
-                    files_scanned += 1;
-                }
+                    Some(FileId::new(parent_id))
+                },
+                name: record.name,
+                is_dir,
+                cloud: is_cloud_placeholder(file_attrs),
+                timestamp: record.timestamp,
+            });
 
-                // Report progress periodically
-                if let Some(ref p) = progress {
-                    let total = files_scanned + dirs_scanned;
-                    if total - last_progress_report >= 10000 {
-                        p.on_progress(files_scanned, dirs_scanned);
-                        last_progress_report = total;
-                    }
-                }
+            if is_dir {
+                dirs_scanned += 1;
+            } else {
+                files_scanned += 1;
             }
 
-            offset += record_length as usize;
+            // Report progress periodically
+            if let Some(ref p) = progress {
+                let total = files_scanned + dirs_scanned;
+                if total - last_progress_report >= 10000 {
+                    p.on_progress(files_scanned, dirs_scanned);
+                    p.on_estimate(ScanEstimate {
+                        done: file_ref & 0x0000FFFFFFFFFFFF,
+                        total: mft_records,
+                        elapsed: started.elapsed(),
+                    });
+                    last_progress_report = total;
+                }
+            }
         }
 
         // Update starting point for next iteration
@@ -380,6 +338,7 @@ struct RawFileRecord {
     parent_id: Option<FileId>,
     name: String,
     is_dir: bool,
+    cloud: bool,
     timestamp: i64,
 }
 
@@ -437,7 +396,8 @@ fn build_paths(
             path,
             raw.is_dir,
         )
-        .with_modified(filetime_to_datetime(raw.timestamp));
+        .with_modified(filetime_to_datetime(raw.timestamp))
+        .with_cloud(raw.cloud);
 
         result.push(record);
     }
@@ -494,16 +454,18 @@ fn build_single_path(
 
 /// Fallback: scan using recursive directory enumeration.
 ///
-/// This is used when MFT access is denied.
+/// This is used when MFT access is denied. `root` is the volume's mount
+/// point, or the folder of a folder volume.
 pub fn scan_recursive(
-    volume_info: &NtfsVolumeInfo,
+    root: &str,
     volume_id: &VolumeId,
     progress: Option<Arc<dyn ScanProgress>>,
 ) -> Result<Vec<FileRecord>, NtfsError> {
     use std::fs;
+    use std::os::windows::fs::MetadataExt;
 
     info!(
-        volume = %volume_info.mount_point,
+        volume = %root,
         "Falling back to recursive directory scan"
     );
 
@@ -512,7 +474,6 @@ pub fn scan_recursive(
     let mut files_scanned = 0u64;
     let mut dirs_scanned = 0u64;
 
-    let root = &volume_info.mount_point;
     let mut stack = vec![root.to_string()];
 
     while let Some(dir_path) = stack.pop() {
@@ -561,6 +522,10 @@ pub fn scan_recursive(
                 record = record.with_modified(chrono::DateTime::from(modified));
             }
 
+            // Directory listings carry the attributes, so this doesn't
+            // download anything
+            record = record.with_cloud(is_cloud_placeholder(metadata.file_attributes()));
+
             records.push(record);
 
             if is_dir {
@@ -609,7 +574,7 @@ mod tests {
         let result = scan_mft(&vol_info, &volume_id, None);
 
         match result {
-            Ok(records) => {
+            Ok(MftScan { records, .. }) => {
                 println!("Found {} records", records.len());
                 assert!(!records.is_empty());
 
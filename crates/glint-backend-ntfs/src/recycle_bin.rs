@@ -0,0 +1,244 @@
+//! Sending files to the Recycle Bin.
+//!
+//! Files are recycled with the shell's `SHFileOperationW`, so they can be
+//! restored from Explorer afterwards. Its progress and error dialogs are
+//! suppressed; the shell only asks before deleting a file for good because
+//! it is too large for the Recycle Bin. On other platforms recycling always
+//! fails.
+//!
+//! [`restore`] puts a recycled file back. Each recycled file is kept in
+//! `$Recycle.Bin\<user SID>` on its volume as a `$R` file holding the data
+//! and a `$I` file with the same suffix recording where it came from.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[cfg(windows)]
+mod imp {
+    use std::io;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+    use windows::core::PCWSTR;
+    use windows::Win32::UI::Shell::{
+        SHFileOperationW, FOF_ALLOWUNDO, FOF_NOCONFIRMATION, FOF_NOERRORUI, FOF_SILENT,
+        FOF_WANTNUKEWARNING, FO_DELETE, SHFILEOPSTRUCTW,
+    };
+
+    pub fn send(path: &Path) -> io::Result<()> {
+        // The source is a list of paths ending with an empty one
+        let from: Vec<u16> = path.as_os_str().encode_wide().chain([0, 0]).collect();
+        let flags =
+            FOF_ALLOWUNDO | FOF_NOCONFIRMATION | FOF_NOERRORUI | FOF_SILENT | FOF_WANTNUKEWARNING;
+        let mut operation = SHFILEOPSTRUCTW {
+            wFunc: FO_DELETE,
+            pFrom: PCWSTR(from.as_ptr()),
+            fFlags: flags.0 as u16,
+            ..Default::default()
+        };
+        // SAFETY: `from` is double-NUL terminated and outlives the call.
+        let code = unsafe { SHFileOperationW(&mut operation) };
+        if code != 0 {
+            return Err(io::Error::other(format!(
+                "could not move to the Recycle Bin (error {:#x})",
+                code
+            )));
+        }
+        if operation.fAnyOperationsAborted.as_bool() {
+            return Err(io::Error::other("moving to the Recycle Bin was cancelled"));
+        }
+        Ok(())
+    }
+
+    /// Move `from` to `to`, failing with `AlreadyExists` rather than
+    /// replacing whatever is at `to`, in one step.
+    pub fn move_no_replace(from: &Path, to: &Path) -> io::Result<()> {
+        use windows::Win32::Storage::FileSystem::{MoveFileExW, MOVE_FILE_FLAGS};
+
+        let wide =
+            |path: &Path| -> Vec<u16> { path.as_os_str().encode_wide().chain([0]).collect() };
+        let (from, to) = (wide(from), wide(to));
+        // SAFETY: both paths are NUL terminated and outlive the call.
+        // Without MOVEFILE_REPLACE_EXISTING an existing `to` fails the move.
+        unsafe {
+            MoveFileExW(
+                PCWSTR(from.as_ptr()),
+                PCWSTR(to.as_ptr()),
+                MOVE_FILE_FLAGS(0),
+            )
+        }
+        .map_err(io::Error::from)
+    }
+}
+
+#[cfg(not(windows))]
+mod imp {
+    use std::io;
+    use std::path::Path;
+
+    pub fn send(_path: &Path) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the Recycle Bin is only available on Windows",
+        ))
+    }
+
+    /// Move `from` to `to` unless something is at `to`. There is no
+    /// portable rename that won't replace, so this checks first; only
+    /// tests restore files here.
+    pub fn move_no_replace(from: &Path, to: &Path) -> io::Result<()> {
+        if std::fs::symlink_metadata(to).is_ok() {
+            return Err(io::ErrorKind::AlreadyExists.into());
+        }
+        std::fs::rename(from, to)
+    }
+}
+
+/// Move the file at `path` to the Recycle Bin.
+pub fn send(path: &Path) -> io::Result<()> {
+    imp::send(path)
+}
+
+/// Move the file last recycled from `path` back there.
+///
+/// Fails if nothing recycled from `path` is left in the Recycle Bin, or if
+/// something else has taken its place since.
+pub fn restore(path: &Path) -> io::Result<()> {
+    let root = path.ancestors().last().unwrap_or(path);
+    restore_from(&root.join("$Recycle.Bin"), path)
+}
+
+fn restore_from(bin: &Path, path: &Path) -> io::Result<()> {
+    let Some(item) = newest_item(bin, path) else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "not in the Recycle Bin any more",
+        ));
+    };
+    imp::move_no_replace(&item.data, path).map_err(|error| match error.kind() {
+        io::ErrorKind::AlreadyExists => io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "another file has taken its place",
+        ),
+        _ => error,
+    })?;
+    // Without its `$I` file the shell no longer lists the item
+    let _ = fs::remove_file(&item.info);
+    Ok(())
+}
+
+/// A recycled file.
+struct Item {
+    /// The `$I` file recording where it came from
+    info: PathBuf,
+    /// The `$R` file holding its data
+    data: PathBuf,
+    /// FILETIME of when it was recycled
+    deleted: i64,
+}
+
+/// The most recently recycled file from `path` in any folder of `bin` we
+/// can read; other users' folders aren't.
+fn newest_item(bin: &Path, path: &Path) -> Option<Item> {
+    let original = path.to_string_lossy().to_lowercase();
+    fs::read_dir(bin)
+        .ok()?
+        .flatten()
+        .filter_map(|user| fs::read_dir(user.path()).ok())
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.strip_prefix("$I")?.to_string();
+            let (deleted, from) = parse_info(&fs::read(entry.path()).ok()?)?;
+            (from.to_lowercase() == original).then(|| Item {
+                data: entry.path().with_file_name(format!("$R{}", name)),
+                info: entry.path(),
+                deleted,
+            })
+        })
+        .filter(|item| item.data.exists())
+        .max_by_key(|item| item.deleted)
+}
+
+/// Read the deletion time and original path from a `$I` file: version 1
+/// (Windows Vista to 8.1) stores the path in a fixed 260-character field,
+/// version 2 prefixes it with its length.
+fn parse_info(bytes: &[u8]) -> Option<(i64, String)> {
+    let read_u64 = |at: usize| Some(u64::from_le_bytes(bytes.get(at..at + 8)?.try_into().ok()?));
+    let deleted = read_u64(16)? as i64;
+    let name = match read_u64(0)? {
+        1 => bytes.get(24..24 + 520)?,
+        2 => {
+            let len = u32::from_le_bytes(bytes.get(24..28)?.try_into().ok()?) as usize;
+            bytes.get(28..28 + len.checked_mul(2)?)?
+        }
+        _ => return None,
+    };
+    let units: Vec<u16> = name
+        .chunks_exact(2)
+        .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+        .take_while(|&unit| unit != 0)
+        .collect();
+    Some((deleted, String::from_utf16_lossy(&units)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A version 2 `$I` file for `path`.
+    fn info(path: &Path, deleted: i64) -> Vec<u8> {
+        let name: Vec<u16> = path.to_string_lossy().encode_utf16().chain([0]).collect();
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&2u64.to_le_bytes());
+        bytes.extend_from_slice(&5u64.to_le_bytes());
+        bytes.extend_from_slice(&deleted.to_le_bytes());
+        bytes.extend_from_slice(&(name.len() as u32).to_le_bytes());
+        bytes.extend(name.iter().flat_map(|unit| unit.to_le_bytes()));
+        bytes
+    }
+
+    #[test]
+    fn test_parse_info_versions() {
+        let path = Path::new(r"C:\Users\me\report.pdf");
+        assert_eq!(
+            parse_info(&info(path, 42)),
+            Some((42, r"C:\Users\me\report.pdf".to_string()))
+        );
+
+        let mut v1 = info(path, 42);
+        v1[0] = 1;
+        v1.drain(24..28);
+        v1.resize(24 + 520, 0);
+        assert_eq!(parse_info(&v1).unwrap().1, r"C:\Users\me\report.pdf");
+
+        assert!(parse_info(&v1[..100]).is_none());
+    }
+
+    #[test]
+    fn test_restore_newest() {
+        let dir = tempfile::tempdir().unwrap();
+        let bin = dir.path().join("$Recycle.Bin");
+        let user = bin.join("S-1-5-21-1");
+        fs::create_dir_all(&user).unwrap();
+        let path = dir.path().join("notes.txt");
+
+        // Recycled twice; the later one comes back
+        for (suffix, deleted, text) in [("AAA.txt", 1, "old"), ("BBB.txt", 2, "new")] {
+            fs::write(user.join(format!("$I{}", suffix)), info(&path, deleted)).unwrap();
+            fs::write(user.join(format!("$R{}", suffix)), text).unwrap();
+        }
+        restore_from(&bin, &path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert!(!user.join("$IBBB.txt").exists());
+
+        // Not over a file that took its place
+        let error = restore_from(&bin, &path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        fs::remove_file(&path).unwrap();
+        restore_from(&bin, &path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old");
+        let error = restore_from(&bin, &path.with_extension("md")).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,321 @@
+//! Task Scheduler job that keeps the index current without a service.
+//!
+//! Installing the Glint service needs administrator rights. As an
+//! alternative, a per-user Task Scheduler job can run `glint watch --once`
+//! every few minutes or at logon, so the index catches up with the USN
+//! journal without a process running all the time.
+//!
+//! The job is registered through the Task Scheduler COM API. On other
+//! platforms every operation fails and the status is always
+//! [`TaskStatus::NotInstalled`].
+
+use std::fmt;
+use std::path::Path;
+
+/// Name of the job in the Task Scheduler root folder.
+pub const TASK_NAME: &str = "Glint Index Catch-up";
+
+/// Default minutes between runs of an interval job.
+pub const DEFAULT_INTERVAL_MINUTES: u32 = 15;
+
+/// When the job runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskTrigger {
+    /// Every given number of minutes
+    Every(u32),
+    /// Each time the current user logs on
+    AtLogon,
+}
+
+impl TaskTrigger {
+    /// Repetition interval as an ISO 8601 duration (`PT15M`), if any.
+    #[cfg_attr(not(windows), allow(dead_code))]
+    fn interval(&self) -> Option<String> {
+        match self {
+            TaskTrigger::Every(minutes) => Some(format!("PT{}M", (*minutes).max(1))),
+            TaskTrigger::AtLogon => None,
+        }
+    }
+}
+
+impl Default for TaskTrigger {
+    fn default() -> Self {
+        TaskTrigger::Every(DEFAULT_INTERVAL_MINUTES)
+    }
+}
+
+impl fmt::Display for TaskTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskTrigger::Every(1) => write!(f, "every minute"),
+            TaskTrigger::Every(minutes) => write!(f, "every {} minutes", minutes),
+            TaskTrigger::AtLogon => write!(f, "at logon"),
+        }
+    }
+}
+
+/// State of the scheduled job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskStatus {
+    NotInstalled,
+    Ready,
+    Running,
+    Disabled,
+    Unknown,
+}
+
+impl fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskStatus::NotInstalled => write!(f, "Not Installed"),
+            TaskStatus::Ready => write!(f, "Ready"),
+            TaskStatus::Running => write!(f, "Running"),
+            TaskStatus::Disabled => write!(f, "Disabled"),
+            TaskStatus::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::{TaskStatus, TaskTrigger, TASK_NAME};
+    use anyhow::Context;
+    use std::path::Path;
+    use windows::core::{Interface, BSTR, VARIANT};
+    use windows::Win32::Foundation::{RPC_E_CHANGED_MODE, VARIANT_FALSE, VARIANT_TRUE};
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER,
+        COINIT_MULTITHREADED,
+    };
+    use windows::Win32::System::TaskScheduler::{
+        IExecAction, ILogonTrigger, ITaskFolder, ITaskService, ITimeTrigger, TaskScheduler,
+        TASK_ACTION_EXEC, TASK_CREATE_OR_UPDATE, TASK_INSTANCES_IGNORE_NEW,
+        TASK_LOGON_INTERACTIVE_TOKEN, TASK_RUNLEVEL_HIGHEST, TASK_RUNLEVEL_LUA,
+        TASK_STATE_DISABLED, TASK_STATE_QUEUED, TASK_STATE_READY, TASK_STATE_RUNNING,
+        TASK_TRIGGER_LOGON, TASK_TRIGGER_TIME,
+    };
+
+    /// Arguments the job passes to `glint.exe`.
+    const TASK_ARGUMENTS: &str = "watch --once";
+
+    const TASK_DESCRIPTION: &str =
+        "Applies file system changes to the Glint search index (glint watch --once)";
+
+    /// Keeps COM initialized on this thread for as long as it lives.
+    struct ComGuard {
+        owned: bool,
+    }
+
+    impl ComGuard {
+        fn new() -> anyhow::Result<Self> {
+            // SAFETY: paired with CoUninitialize in Drop when we initialized.
+            let hr = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) };
+            if hr == RPC_E_CHANGED_MODE {
+                // Already initialized with another model (e.g. by the GUI)
+                return Ok(ComGuard { owned: false });
+            }
+            hr.ok().context("CoInitializeEx failed")?;
+            Ok(ComGuard { owned: true })
+        }
+    }
+
+    impl Drop for ComGuard {
+        fn drop(&mut self) {
+            if self.owned {
+                unsafe { CoUninitialize() };
+            }
+        }
+    }
+
+    /// Connect to the local Task Scheduler.
+    fn connect() -> anyhow::Result<ITaskService> {
+        // SAFETY: plain COM calls; the interface releases itself on drop.
+        unsafe {
+            let service: ITaskService =
+                CoCreateInstance(&TaskScheduler, None, CLSCTX_INPROC_SERVER)
+                    .context("Task Scheduler is not available")?;
+            let empty = VARIANT::default();
+            service
+                .Connect(&empty, &empty, &empty, &empty)
+                .context("Could not connect to Task Scheduler")?;
+            Ok(service)
+        }
+    }
+
+    /// The Task Scheduler root folder, where the job lives.
+    fn root_folder(service: &ITaskService) -> anyhow::Result<ITaskFolder> {
+        // SAFETY: plain COM call on a connected service.
+        Ok(unsafe { service.GetFolder(&BSTR::from("\\")) }?)
+    }
+
+    pub fn install(exe_path: &Path, trigger: TaskTrigger) -> anyhow::Result<()> {
+        let _com = ComGuard::new()?;
+        let service = connect()?;
+        let folder = root_folder(&service)?;
+        let empty = VARIANT::default();
+
+        // SAFETY: plain COM calls on interfaces obtained above.
+        unsafe {
+            let definition = service.NewTask(0)?;
+
+            definition
+                .RegistrationInfo()?
+                .SetDescription(&BSTR::from(TASK_DESCRIPTION))?;
+
+            // Reading the journal needs admin rights; keep them if we have them
+            let principal = definition.Principal()?;
+            principal.SetLogonType(TASK_LOGON_INTERACTIVE_TOKEN)?;
+            principal.SetRunLevel(if crate::NtfsBackend::has_elevated_privileges() {
+                TASK_RUNLEVEL_HIGHEST
+            } else {
+                TASK_RUNLEVEL_LUA
+            })?;
+
+            let settings = definition.Settings()?;
+            settings.SetStartWhenAvailable(VARIANT_TRUE)?;
+            settings.SetDisallowStartIfOnBatteries(VARIANT_FALSE)?;
+            settings.SetStopIfGoingOnBatteries(VARIANT_FALSE)?;
+            settings.SetMultipleInstances(TASK_INSTANCES_IGNORE_NEW)?;
+            settings.SetExecutionTimeLimit(&BSTR::from("PT1H"))?;
+
+            let user = current_user();
+            let triggers = definition.Triggers()?;
+            match trigger.interval() {
+                Some(interval) => {
+                    let time: ITimeTrigger = triggers.Create(TASK_TRIGGER_TIME)?.cast()?;
+                    let start = chrono::Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+                    time.SetStartBoundary(&BSTR::from(start))?;
+                    // No duration: repeat indefinitely
+                    time.Repetition()?.SetInterval(&BSTR::from(interval))?;
+                }
+                None => {
+                    let logon: ILogonTrigger = triggers.Create(TASK_TRIGGER_LOGON)?.cast()?;
+                    if let Some(user) = &user {
+                        logon.SetUserId(&BSTR::from(user.as_str()))?;
+                    }
+                }
+            }
+
+            let exec: IExecAction = definition.Actions()?.Create(TASK_ACTION_EXEC)?.cast()?;
+            exec.SetPath(&BSTR::from(exe_path.to_string_lossy().as_ref()))?;
+            exec.SetArguments(&BSTR::from(TASK_ARGUMENTS))?;
+
+            let user_id = match &user {
+                Some(user) => VARIANT::from(BSTR::from(user.as_str())),
+                None => VARIANT::default(),
+            };
+            folder
+                .RegisterTaskDefinition(
+                    &BSTR::from(TASK_NAME),
+                    &definition,
+                    TASK_CREATE_OR_UPDATE.0,
+                    &user_id,
+                    &empty,
+                    TASK_LOGON_INTERACTIVE_TOKEN,
+                    &empty,
+                )
+                .context("Could not register the scheduled task")?;
+        }
+
+        tracing::info!(task = TASK_NAME, %trigger, "Installed scheduled task");
+        Ok(())
+    }
+
+    pub fn uninstall() -> anyhow::Result<()> {
+        let _com = ComGuard::new()?;
+        let folder = root_folder(&connect()?)?;
+
+        // SAFETY: plain COM call on the folder obtained above.
+        let task = unsafe { folder.GetTask(&BSTR::from(TASK_NAME)) };
+        if task.is_err() {
+            // Not installed, nothing to do
+            return Ok(());
+        }
+        unsafe { folder.DeleteTask(&BSTR::from(TASK_NAME), 0) }
+            .context("Could not delete the scheduled task")?;
+
+        tracing::info!(task = TASK_NAME, "Removed scheduled task");
+        Ok(())
+    }
+
+    pub fn status() -> TaskStatus {
+        let Ok(_com) = ComGuard::new() else {
+            return TaskStatus::Unknown;
+        };
+        let Ok(folder) = connect().and_then(|service| root_folder(&service)) else {
+            return TaskStatus::Unknown;
+        };
+
+        // SAFETY: plain COM calls on the folder obtained above.
+        unsafe {
+            let Ok(task) = folder.GetTask(&BSTR::from(TASK_NAME)) else {
+                return TaskStatus::NotInstalled;
+            };
+            match task.State() {
+                Ok(TASK_STATE_READY) | Ok(TASK_STATE_QUEUED) => TaskStatus::Ready,
+                Ok(TASK_STATE_RUNNING) => TaskStatus::Running,
+                Ok(TASK_STATE_DISABLED) => TaskStatus::Disabled,
+                _ => TaskStatus::Unknown,
+            }
+        }
+    }
+
+    /// `DOMAIN\user` of the current user, if known.
+    fn current_user() -> Option<String> {
+        let user = std::env::var("USERNAME").ok()?;
+        Some(match std::env::var("USERDOMAIN") {
+            Ok(domain) => format!("{}\\{}", domain, user),
+            Err(_) => user,
+        })
+    }
+}
+
+#[cfg(not(windows))]
+mod imp {
+    use super::{TaskStatus, TaskTrigger};
+    use std::path::Path;
+
+    pub fn install(_exe_path: &Path, _trigger: TaskTrigger) -> anyhow::Result<()> {
+        anyhow::bail!("Scheduled tasks are only available on Windows")
+    }
+
+    pub fn uninstall() -> anyhow::Result<()> {
+        anyhow::bail!("Scheduled tasks are only available on Windows")
+    }
+
+    pub fn status() -> TaskStatus {
+        TaskStatus::NotInstalled
+    }
+}
+
+/// Register (or replace) the job, running `exe_path watch --once`.
+///
+/// Does not need administrator rights. When the caller is elevated the job
+/// runs with highest privileges, which reading the USN journal requires.
+pub fn install(exe_path: &Path, trigger: TaskTrigger) -> anyhow::Result<()> {
+    imp::install(exe_path, trigger)
+}
+
+/// Remove the job. Succeeds if it is not installed.
+pub fn uninstall() -> anyhow::Result<()> {
+    imp::uninstall()
+}
+
+/// Current state of the job.
+pub fn status() -> TaskStatus {
+    imp::status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trigger_interval() {
+        assert_eq!(TaskTrigger::Every(15).interval().as_deref(), Some("PT15M"));
+        assert_eq!(TaskTrigger::Every(0).interval().as_deref(), Some("PT1M"));
+        assert_eq!(TaskTrigger::AtLogon.interval(), None);
+        assert_eq!(TaskTrigger::default().to_string(), "every 15 minutes");
+        assert_eq!(TaskTrigger::AtLogon.to_string(), "at logon");
+    }
+}
@@ -17,6 +17,16 @@ impl NtfsBackend {
     pub fn new() -> Self {
         NtfsBackend
     }
+
+    /// Accepted for parity with the Windows backend; nothing is watched.
+    pub fn ignoring_maintenance(self, _ignore: bool) -> Self {
+        self
+    }
+
+    /// Volume access is never available on non-Windows platforms.
+    pub fn has_elevated_privileges() -> bool {
+        false
+    }
 }
 
 impl Default for NtfsBackend {
@@ -46,6 +56,14 @@ impl FileSystemBackend for NtfsBackend {
         anyhow::bail!("NTFS backend is only available on Windows")
     }
 
+    fn catch_up(
+        &self,
+        _volume: &VolumeInfo,
+        _handler: &dyn ChangeHandler,
+    ) -> anyhow::Result<Option<JournalState>> {
+        anyhow::bail!("NTFS backend is only available on Windows")
+    }
+
     fn get_journal_state(&self, _volume: &VolumeInfo) -> anyhow::Result<Option<JournalState>> {
         anyhow::bail!("NTFS backend is only available on Windows")
     }
@@ -25,7 +25,8 @@
 //! Requires elevated privileges (Administrator or "Perform Volume Maintenance Tasks").
 
 use crate::error::NtfsError;
-use crate::winapi_utils::{open_volume_for_usn, SafeHandle};
+use crate::usn_record::{parse_buffer, UsnRecord};
+use crate::winapi_utils::{is_cloud_placeholder, open_volume_for_usn, SafeHandle};
 use glint_core::backend::{ChangeEvent, ChangeHandler, ChangeKind, JournalState};
 use glint_core::types::{FileId, VolumeId};
 use crossbeam_channel::{Receiver, Sender};
@@ -69,25 +70,9 @@ struct ReadUsnJournalData {
     max_major_version: u16,
 }
 
-/// USN record structure (version 2)
-#[repr(C)]
-#[derive(Debug)]
-struct UsnRecordV2 {
-    record_length: u32,
-    major_version: u16,
-    minor_version: u16,
-    file_reference_number: u64,
-    parent_file_reference_number: u64,
-    usn: i64,
-    timestamp: i64,
-    reason: u32,
-    source_info: u32,
-    security_id: u32,
-    file_attributes: u32,
-    file_name_length: u16,
-    file_name_offset: u16,
-    // file_name follows
-}
+// Layouts the ioctls expect, the same on x64 and ARM64
+const _: () = assert!(mem::size_of::<UsnJournalData>() == 64);
+const _: () = assert!(mem::size_of::<ReadUsnJournalData>() == 48);
 
 // USN reason flags
 const USN_REASON_DATA_OVERWRITE: u32 = 0x00000001;
@@ -97,8 +82,17 @@ const USN_REASON_FILE_CREATE: u32 = 0x00000100;
 const USN_REASON_FILE_DELETE: u32 = 0x00000200;
 const USN_REASON_RENAME_OLD_NAME: u32 = 0x00001000;
 const USN_REASON_RENAME_NEW_NAME: u32 = 0x00002000;
+const USN_REASON_BASIC_INFO_CHANGE: u32 = 0x00008000;
 const USN_REASON_CLOSE: u32 = 0x80000000;
 
+// USN source info flags, set when the system rather than a user or
+// application made the change: data management such as defragmentation or
+// deduplication, auxiliary data, and replication
+const USN_SOURCE_DATA_MANAGEMENT: u32 = 0x00000001;
+const USN_SOURCE_AUXILIARY_DATA: u32 = 0x00000002;
+const USN_SOURCE_REPLICATION_MANAGEMENT: u32 = 0x00000004;
+const USN_SOURCE_CLIENT_REPLICATION_MANAGEMENT: u32 = 0x00000008;
+
 const FILE_ATTRIBUTE_DIRECTORY: u32 = 0x10;
 
 /// Query the USN journal status for a volume.
@@ -176,6 +170,7 @@ pub fn read_usn_records(
         | USN_REASON_FILE_DELETE
         | USN_REASON_RENAME_OLD_NAME
         | USN_REASON_RENAME_NEW_NAME
+        | USN_REASON_BASIC_INFO_CHANGE
         | USN_REASON_CLOSE;
 
     let read_data = ReadUsnJournalData {
@@ -215,57 +210,23 @@ pub fn read_usn_records(
         return Err(NtfsError::from_win32("FSCTL_READ_USN_JOURNAL"));
     }
 
-    if bytes_returned < 8 {
-        // Just the next USN, no records
-        let next_usn = if bytes_returned >= 8 {
-            i64::from_ne_bytes(buffer[0..8].try_into().unwrap())
-        } else {
-            start_usn
-        };
-        return Ok((Vec::new(), next_usn));
-    }
-
     // First 8 bytes are the next USN
-    let next_usn = i64::from_ne_bytes(buffer[0..8].try_into().unwrap());
-
-    // Parse records
-    let mut events = Vec::new();
-    let mut offset = 8usize;
-
-    while offset + mem::size_of::<UsnRecordV2>() <= bytes_returned as usize {
-        let record_ptr = buffer.as_ptr().wrapping_add(offset) as *const UsnRecordV2;
-        let record = unsafe { &*record_ptr };
-
-        if record.record_length == 0 {
-            break;
-        }
-
-        // Extract filename
-        let name_offset = record.file_name_offset as usize;
-        let name_len = record.file_name_length as usize;
-
-        if offset + name_offset + name_len <= bytes_returned as usize {
-            let name_ptr = buffer.as_ptr().wrapping_add(offset + name_offset) as *const u16;
-            let name_slice = unsafe { std::slice::from_raw_parts(name_ptr, name_len / 2) };
-            let name = String::from_utf16_lossy(name_slice);
-
-            // Skip system files
-            if !name.starts_with('$') {
-                let event = parse_usn_record(record, name, volume_id);
-                if let Some(e) = event {
-                    events.push(e);
-                }
-            }
-        }
+    let Some((next_usn, records)) = parse_buffer(&buffer, bytes_returned as usize) else {
+        return Ok((Vec::new(), start_usn));
+    };
 
-        offset += record.record_length as usize;
-    }
+    let events = records
+        // Skip system files
+        .filter(|record| !record.name.is_empty() && !record.name.starts_with('$'))
+        .filter_map(|record| parse_usn_record(record, volume_id))
+        .collect();
 
-    Ok((events, next_usn))
+    Ok((events, next_usn as i64))
 }
 
 /// Parse a USN record into a ChangeEvent.
-fn parse_usn_record(record: &UsnRecordV2, name: String, volume_id: &VolumeId) -> Option<ChangeEvent> {
+fn parse_usn_record(record: UsnRecord, volume_id: &VolumeId) -> Option<ChangeEvent> {
+    let name = record.name;
     let file_id = FileId::new(record.file_reference_number & 0x0000FFFFFFFFFFFF);
     let parent_id = {
         let pid = record.parent_file_reference_number & 0x0000FFFFFFFFFFFF;
@@ -294,11 +255,14 @@ fn parse_usn_record(record: &UsnRecordV2, name: String, volume_id: &VolumeId) ->
     {
         // Data was modified and file closed
         Some(ChangeKind::Modified)
+    } else if record.reason & USN_REASON_BASIC_INFO_CHANGE != 0 && record.reason & USN_REASON_CLOSE != 0 {
+        // Attributes changed, e.g. a cloud file was downloaded or freed up
+        Some(ChangeKind::AttributeChanged)
     } else {
         None
     };
 
-    kind.map(|k| match k {
+    let mut event = match kind? {
         ChangeKind::Created => ChangeEvent::created(
             volume_id.clone(),
             file_id,
@@ -325,8 +289,8 @@ fn parse_usn_record(record: &UsnRecordV2, name: String, volume_id: &VolumeId) ->
             is_dir,
             record.usn,
         ),
-        ChangeKind::Modified => ChangeEvent {
-            kind: ChangeKind::Modified,
+        kind @ (ChangeKind::Modified | ChangeKind::AttributeChanged) => ChangeEvent {
+            kind,
             volume_id: volume_id.clone(),
             file_id,
             parent_id,
@@ -335,9 +299,19 @@ fn parse_usn_record(record: &UsnRecordV2, name: String, volume_id: &VolumeId) ->
             new_parent_id: None,
             is_dir,
             sequence: record.usn,
+            cloud: false,
+            maintenance: false,
         },
         _ => unreachable!(),
-    })
+    };
+    event.cloud = is_cloud_placeholder(record.file_attributes);
+    event.maintenance = record.source_info
+        & (USN_SOURCE_DATA_MANAGEMENT
+            | USN_SOURCE_AUXILIARY_DATA
+            | USN_SOURCE_REPLICATION_MANAGEMENT
+            | USN_SOURCE_CLIENT_REPLICATION_MANAGEMENT)
+        != 0;
+    Some(event)
 }
 
 /// USN journal watcher that monitors for changes.
@@ -352,11 +326,15 @@ pub struct UsnWatcher {
 
 impl UsnWatcher {
     /// Start watching a volume for changes.
+    ///
+    /// With `ignore_maintenance`, changes the system makes while maintaining
+    /// files are not reported (see [`ChangeEvent::is_maintenance_noise`]).
     pub fn start(
         device_path: String,
         volume_id: VolumeId,
         handler: Arc<dyn ChangeHandler>,
         initial_state: Option<JournalState>,
+        ignore_maintenance: bool,
     ) -> Result<Self, NtfsError> {
         let stop_signal = Arc::new(AtomicBool::new(false));
         let stop_signal_clone = stop_signal.clone();
@@ -370,6 +348,7 @@ impl UsnWatcher {
                     volume_id,
                     handler,
                     initial_state,
+                    ignore_maintenance,
                     stop_signal_clone,
                     shutdown_rx,
                 );
@@ -400,12 +379,104 @@ impl Drop for UsnWatcher {
     }
 }
 
+/// Find the USN to resume reading from a saved journal state.
+///
+/// Reports a journal reset and starts from the current position if the
+/// journal was recreated or has dropped records since `state`.
+fn resume_usn(
+    state: &JournalState,
+    journal_data: &UsnJournalData,
+    volume_id: &VolumeId,
+    handler: &dyn ChangeHandler,
+) -> i64 {
+    // Check if journal ID matches
+    if state.journal_id != journal_data.usn_journal_id {
+        warn!(
+            volume = %volume_id,
+            old_id = state.journal_id,
+            new_id = journal_data.usn_journal_id,
+            "Journal ID changed, rescan required"
+        );
+        handler.on_journal_reset(volume_id.clone(), "Journal ID changed".to_string());
+        // Start from current position
+        journal_data.next_usn
+    } else if state.last_usn < journal_data.first_usn {
+        warn!(
+            volume = %volume_id,
+            last = state.last_usn,
+            first_valid = journal_data.first_usn,
+            "Journal truncated, rescan required"
+        );
+        handler.on_journal_reset(
+            volume_id.clone(),
+            "Journal truncated, some changes may have been missed".to_string(),
+        );
+        // Start from current position
+        journal_data.next_usn
+    } else {
+        state.last_usn
+    }
+}
+
+/// Report all changes recorded since `state` and return the new state.
+///
+/// Reads up to the journal position at the time of the call, so changes
+/// made while catching up are left for the next run. With
+/// `ignore_maintenance`, maintenance noise is skipped as by [`UsnWatcher`].
+pub fn catch_up(
+    device_path: &str,
+    volume_id: &VolumeId,
+    state: &JournalState,
+    handler: &dyn ChangeHandler,
+    ignore_maintenance: bool,
+) -> Result<JournalState, NtfsError> {
+    let handle = open_volume_for_usn(device_path)?;
+    let journal_data = query_usn_journal_handle(&handle, device_path)?;
+    let end_usn = journal_data.next_usn;
+
+    let mut current_usn = resume_usn(state, &journal_data, volume_id, handler);
+    let mut changes = 0u64;
+    let mut skipped = 0u64;
+    while current_usn < end_usn {
+        match read_usn_records(&handle, &journal_data, current_usn, volume_id) {
+            Ok((events, next_usn)) => {
+                for event in events.into_iter().filter(|e| e.sequence < end_usn) {
+                    if ignore_maintenance && event.is_maintenance_noise() {
+                        skipped += 1;
+                        continue;
+                    }
+                    handler.on_change(event);
+                    changes += 1;
+                }
+                if next_usn <= current_usn {
+                    break;
+                }
+                current_usn = next_usn;
+            }
+            Err(NtfsError::UsnJournalTruncated { .. }) => {
+                warn!(volume = %volume_id, "Journal truncated while catching up");
+                handler.on_journal_reset(volume_id.clone(), "Journal truncated".to_string());
+                current_usn = end_usn;
+                break;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    info!(volume = %volume_id, changes, skipped, "Caught up with USN journal");
+    Ok(JournalState::new(
+        journal_data.usn_journal_id,
+        current_usn.min(end_usn),
+    ))
+}
+
 /// Main watch loop that polls for USN changes.
 fn watch_loop(
     device_path: String,
     volume_id: VolumeId,
     handler: Arc<dyn ChangeHandler>,
     initial_state: Option<JournalState>,
+    ignore_maintenance: bool,
     stop_signal: Arc<AtomicBool>,
     shutdown_rx: Receiver<()>,
 ) {
@@ -433,38 +504,7 @@ fn watch_loop(
 
     // Determine starting USN
     let mut current_usn = match initial_state {
-        Some(state) => {
-            // Check if journal ID matches
-            if state.journal_id != journal_data.usn_journal_id {
-                warn!(
-                    volume = %volume_id,
-                    old_id = state.journal_id,
-                    new_id = journal_data.usn_journal_id,
-                    "Journal ID changed, rescan required"
-                );
-                handler.on_journal_reset(
-                    volume_id.clone(),
-                    "Journal ID changed".to_string(),
-                );
-                // Start from current position
-                journal_data.next_usn
-            } else if state.last_usn < journal_data.first_usn {
-                warn!(
-                    volume = %volume_id,
-                    last = state.last_usn,
-                    first_valid = journal_data.first_usn,
-                    "Journal truncated, rescan required"
-                );
-                handler.on_journal_reset(
-                    volume_id.clone(),
-                    "Journal truncated, some changes may have been missed".to_string(),
-                );
-                // Start from current position
-                journal_data.next_usn
-            } else {
-                state.last_usn
-            }
-        }
+        Some(state) => resume_usn(&state, &journal_data, &volume_id, handler.as_ref()),
         None => {
             // Start from current position
             journal_data.next_usn
@@ -490,10 +530,20 @@ fn watch_loop(
                         kind = %event.kind,
                         file = %event.name,
                         usn = event.sequence,
+                        maintenance = event.maintenance,
                         "USN change event"
                     );
+                    if ignore_maintenance && event.is_maintenance_noise() {
+                        continue;
+                    }
                     handler.on_change(event);
                 }
+                if next_usn != current_usn {
+                    handler.on_position(
+                        volume_id.clone(),
+                        JournalState::new(journal_data.usn_journal_id, next_usn),
+                    );
+                }
                 current_usn = next_usn;
             }
             Err(NtfsError::UsnJournalTruncated { .. }) => {
@@ -504,6 +554,11 @@ fn watch_loop(
                 );
                 break;
             }
+            Err(e) if e.is_device_gone() => {
+                warn!(volume = %volume_id, error = %e, "Volume went away during watch");
+                handler.on_volume_offline(volume_id.clone(), e.to_string());
+                break;
+            }
             Err(e) => {
                 error!(volume = %volume_id, error = %e, "Error reading USN journal");
                 // Don't exit on transient errors
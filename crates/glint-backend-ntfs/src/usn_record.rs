@@ -0,0 +1,214 @@
+//! Parsing the USN records that `FSCTL_ENUM_USN_DATA` and
+//! `FSCTL_READ_USN_JOURNAL` return.
+//!
+//! Both fill a byte buffer with an 8-byte header (the next file reference
+//! or USN) followed by variable-length records. Fields are read from the
+//! bytes at their documented offsets rather than by casting the buffer to
+//! a struct, so parsing doesn't depend on how the buffer is aligned or on
+//! the target's layout rules: the same code runs on x64 and ARM64 Windows,
+//! and is tested on every platform.
+
+/// Offsets of the fields after the record header in a version 2 record.
+const V2_FIELDS: Fields = Fields {
+    file_reference: 8,
+    id_len: 8,
+    usn: 24,
+};
+
+/// Offsets in a version 3 record, whose file IDs are 128 bits.
+const V3_FIELDS: Fields = Fields {
+    file_reference: 8,
+    id_len: 16,
+    usn: 40,
+};
+
+struct Fields {
+    file_reference: usize,
+    /// Length of each file ID; the parent's follows the file's
+    id_len: usize,
+    /// Offset of the USN, after which the layouts agree
+    usn: usize,
+}
+
+/// One change record (`USN_RECORD_V2` or `USN_RECORD_V3`).
+///
+/// 128-bit file IDs of version 3 records are cut to their low 64 bits,
+/// which hold the NTFS file reference number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsnRecord {
+    pub major_version: u16,
+    pub file_reference_number: u64,
+    pub parent_file_reference_number: u64,
+    pub usn: i64,
+    /// FILETIME of the change
+    pub timestamp: i64,
+    pub reason: u32,
+    pub source_info: u32,
+    pub file_attributes: u32,
+    pub name: String,
+}
+
+impl UsnRecord {
+    /// Parse the record at the start of `bytes`, which must hold all of it.
+    ///
+    /// Returns `None` for versions other than 2 and 3, and for records whose
+    /// name lies outside `bytes`.
+    pub fn parse(bytes: &[u8]) -> Option<UsnRecord> {
+        let major_version = read_u16(bytes, 4)?;
+        let fields = match major_version {
+            2 => V2_FIELDS,
+            3 => V3_FIELDS,
+            _ => return None,
+        };
+        let parent = fields.file_reference + fields.id_len;
+        let usn = fields.usn;
+        let name_length = usize::from(read_u16(bytes, usn + 32)?);
+        let name_offset = usize::from(read_u16(bytes, usn + 34)?);
+        let name = bytes.get(name_offset..name_offset.checked_add(name_length)?)?;
+        let name: Vec<u16> = name
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .collect();
+
+        Some(UsnRecord {
+            major_version,
+            file_reference_number: read_u64(bytes, fields.file_reference)?,
+            parent_file_reference_number: read_u64(bytes, parent)?,
+            usn: read_u64(bytes, usn)? as i64,
+            timestamp: read_u64(bytes, usn + 8)? as i64,
+            reason: read_u32(bytes, usn + 16)?,
+            source_info: read_u32(bytes, usn + 20)?,
+            file_attributes: read_u32(bytes, usn + 28)?,
+            name: String::from_utf16_lossy(&name),
+        })
+    }
+}
+
+/// Split the first `len` bytes of an ioctl output buffer into its header
+/// (the next file reference or USN to ask for) and its records.
+///
+/// Returns `None` if the buffer is too short to hold the header.
+pub fn parse_buffer(buffer: &[u8], len: usize) -> Option<(u64, Records<'_>)> {
+    let buffer = buffer.get(..len)?;
+    let next = read_u64(buffer, 0)?;
+    Some((next, Records { buffer, offset: 8 }))
+}
+
+/// The records of an ioctl output buffer, in order.
+///
+/// Records of unknown versions are skipped; a record running past the end
+/// of the buffer ends the iteration.
+pub struct Records<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+}
+
+impl Iterator for Records<'_> {
+    type Item = UsnRecord;
+
+    fn next(&mut self) -> Option<UsnRecord> {
+        loop {
+            let rest = self.buffer.get(self.offset..)?;
+            let length = read_u32(rest, 0)? as usize;
+            if length == 0 || length > rest.len() {
+                self.offset = self.buffer.len();
+                return None;
+            }
+            self.offset += length;
+            if let Some(record) = UsnRecord::parse(&rest[..length]) {
+                return Some(record);
+            }
+        }
+    }
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn read_u64(bytes: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(bytes.get(at..at + 8)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encode a record the way Windows lays it out, padded to 8 bytes.
+    fn encode(version: u16, file: u64, parent: u64, usn: i64, name: &str) -> Vec<u8> {
+        let id_len = if version == 2 { 8 } else { 16 };
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&0u32.to_le_bytes()); // length, set below
+        bytes.extend_from_slice(&version.to_le_bytes());
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+        for id in [file, parent] {
+            let mut id = id.to_le_bytes().to_vec();
+            id.resize(id_len, 0);
+            bytes.extend_from_slice(&id);
+        }
+        bytes.extend_from_slice(&usn.to_le_bytes());
+        bytes.extend_from_slice(&132_000_000_000_000_000i64.to_le_bytes());
+        bytes.extend_from_slice(&0x8000_0100u32.to_le_bytes()); // create | close
+        bytes.extend_from_slice(&1u32.to_le_bytes()); // source info
+        bytes.extend_from_slice(&0u32.to_le_bytes()); // security id
+        bytes.extend_from_slice(&0x20u32.to_le_bytes()); // archive
+        let name: Vec<u8> = name.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        bytes.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&(bytes.len() as u16 + 2).to_le_bytes());
+        bytes.extend_from_slice(&name);
+        bytes.resize(bytes.len().next_multiple_of(8), 0);
+        let length = bytes.len() as u32;
+        bytes[..4].copy_from_slice(&length.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_parse_versions() {
+        let v2 = UsnRecord::parse(&encode(2, 0x0001_0000_0000_002A, 5, 77, "a.txt")).unwrap();
+        assert_eq!(v2.major_version, 2);
+        assert_eq!(v2.file_reference_number, 0x0001_0000_0000_002A);
+        assert_eq!(v2.parent_file_reference_number, 5);
+        assert_eq!(v2.usn, 77);
+        assert_eq!(v2.reason, 0x8000_0100);
+        assert_eq!(v2.source_info, 1);
+        assert_eq!(v2.file_attributes, 0x20);
+        assert_eq!(v2.name, "a.txt");
+
+        let v3 = UsnRecord::parse(&encode(3, 42, 5, 78, "Résumé.pdf")).unwrap();
+        assert_eq!(
+            (v3.major_version, v3.file_reference_number, v3.usn),
+            (3, 42, 78)
+        );
+        assert_eq!(v3.name, "Résumé.pdf");
+        assert_eq!(v3.timestamp, v2.timestamp);
+
+        assert!(UsnRecord::parse(&encode(4, 1, 5, 1, "x")).is_none());
+    }
+
+    #[test]
+    fn test_parse_buffer_at_any_alignment() {
+        let mut records = 99u64.to_le_bytes().to_vec();
+        records.extend(encode(2, 10, 5, 1, "one"));
+        records.extend(encode(4, 11, 5, 2, "future"));
+        records.extend(encode(3, 12, 10, 3, "three"));
+
+        // The same bytes one past an aligned address parse the same
+        let mut shifted = vec![0u8];
+        shifted.extend_from_slice(&records);
+        for buffer in [&records[..], &shifted[1..]] {
+            let (next, parsed) = parse_buffer(buffer, buffer.len()).unwrap();
+            assert_eq!(next, 99);
+            let names: Vec<String> = parsed.map(|r| r.name).collect();
+            assert_eq!(names, ["one", "three"]);
+        }
+
+        // A record cut off by the returned length ends the records
+        let (_, parsed) = parse_buffer(&records, records.len() - 1).unwrap();
+        assert_eq!(parsed.count(), 1);
+        assert!(parse_buffer(&records, 4).is_none());
+    }
+}
@@ -13,7 +13,7 @@ use windows::core::PCWSTR;
 use windows::Win32::Foundation::MAX_PATH;
 use windows::Win32::Storage::FileSystem::{
     FindFirstVolumeW, FindNextVolumeW, FindVolumeClose, GetDiskFreeSpaceExW, GetDriveTypeW,
-    GetVolumeInformationW, GetVolumePathNamesForVolumeNameW,
+    GetVolumeInformationW, GetVolumeNameForVolumeMountPointW, GetVolumePathNamesForVolumeNameW,
 };
 
 // DRIVE_FIXED constant value (3)
@@ -28,6 +28,9 @@ pub struct NtfsVolumeInfo {
     /// Mount point (e.g., "C:\")
     pub mount_point: String,
 
+    /// Folders the volume is also mounted at (e.g., "C:\Data\")
+    pub mount_folders: Vec<String>,
+
     /// Volume label
     pub label: Option<String>,
 
@@ -51,7 +54,8 @@ impl NtfsVolumeInfo {
 
         let mut info = VolumeInfo::new(id, &self.mount_point, &self.filesystem)
             .with_capacity(self.total_bytes, self.free_bytes)
-            .with_change_journal_support(self.filesystem == "NTFS");
+            .with_change_journal_support(self.filesystem == "NTFS")
+            .with_mount_folders(self.mount_folders.clone());
 
         if let Some(ref label) = self.label {
             info = info.with_label(label);
@@ -60,15 +64,50 @@ impl NtfsVolumeInfo {
         info
     }
 
-    /// Get the device path for this volume (e.g., "\\.\C:")
+    /// Get the device path for this volume (e.g., "\\.\C:", or
+    /// "\\?\Volume{guid}" for a volume mounted in a folder)
     pub fn device_path(&self) -> String {
-        normalize_volume_path(&self.mount_point)
+        if is_drive_root(&self.mount_point) || self.volume_guid.is_empty() {
+            device_path(&self.mount_point)
+        } else {
+            self.volume_guid.trim_end_matches('\\').to_string()
+        }
     }
 }
 
+/// Get the device path for a mount point.
+///
+/// Drive letters open as "\\.\C:". A volume mounted in a folder has no
+/// such name, so it is opened through its volume GUID path instead.
+pub fn device_path(mount_point: &str) -> String {
+    if is_drive_root(mount_point) {
+        return normalize_volume_path(mount_point);
+    }
+    match volume_guid_for_mount_point(mount_point) {
+        Some(guid) => guid.trim_end_matches('\\').to_string(),
+        None => normalize_volume_path(mount_point),
+    }
+}
+
+/// Look up the volume GUID path (e.g., "\\?\Volume{guid}\") of a mount point.
+fn volume_guid_for_mount_point(mount_point: &str) -> Option<String> {
+    // The API wants the trailing backslash
+    let mount_point = format!("{}\\", mount_point.trim_end_matches('\\'));
+    let wide_path = to_wide_string(&mount_point);
+    let mut volume_name = [0u16; MAX_PATH as usize];
+
+    unsafe { GetVolumeNameForVolumeMountPointW(PCWSTR(wide_path.as_ptr()), &mut volume_name) }
+        .ok()?;
+
+    let len = volume_name.iter().position(|&c| c == 0)?;
+    Some(String::from_utf16_lossy(&volume_name[..len]))
+}
+
 /// Enumerate all NTFS volumes on the system.
 ///
-/// Returns information about all fixed NTFS drives.
+/// Returns information about all fixed NTFS drives, including volumes
+/// that are only mounted in a folder and have no drive letter; those use
+/// their mount folder as mount point.
 pub fn enumerate_ntfs_volumes() -> Result<Vec<NtfsVolumeInfo>, NtfsError> {
     let mut volumes = Vec::new();
 
@@ -93,18 +132,28 @@ pub fn enumerate_ntfs_volumes() -> Result<Vec<NtfsVolumeInfo>, NtfsError> {
         );
 
         // Get mount points for this volume
-        if let Some(mount_point) = get_volume_mount_point(&volume_guid) {
+        let mut mount_points = get_volume_mount_points(&volume_guid);
+        if !mount_points.is_empty() {
+            // Prefer a drive letter; the other paths are mount folders
+            let first = mount_points
+                .iter()
+                .position(|p| is_drive_root(p))
+                .unwrap_or(0);
+            let mount_point = mount_points.remove(first);
+
             // Check if it's a fixed drive
             if is_fixed_drive(&mount_point) {
                 // Get volume information
-                if let Some(vol_info) = get_volume_details(&volume_guid, &mount_point) {
+                if let Some(mut vol_info) = get_volume_details(&volume_guid, &mount_point) {
                     if vol_info.filesystem == "NTFS" {
                         debug!(
                             mount_point = %vol_info.mount_point,
+                            mount_folders = ?mount_points,
                             label = ?vol_info.label,
                             serial = %format!("{:08X}", vol_info.serial_number),
                             "Found NTFS volume"
                         );
+                        vol_info.mount_folders = mount_points;
                         volumes.push(vol_info);
                     }
                 }
@@ -128,10 +177,10 @@ pub fn enumerate_ntfs_volumes() -> Result<Vec<NtfsVolumeInfo>, NtfsError> {
     Ok(volumes)
 }
 
-/// Get the first mount point for a volume GUID.
-fn get_volume_mount_point(volume_guid: &str) -> Option<String> {
+/// Get all mount points (drive letters and folders) for a volume GUID.
+fn get_volume_mount_points(volume_guid: &str) -> Vec<String> {
     let wide_guid = to_wide_string(volume_guid);
-    let mut path_names = [0u16; MAX_PATH as usize];
+    let mut path_names = [0u16; 4 * MAX_PATH as usize];
     let mut return_length = 0u32;
 
     let result = unsafe {
@@ -143,17 +192,21 @@ fn get_volume_mount_point(volume_guid: &str) -> Option<String> {
     };
 
     if result.is_err() {
-        return None;
+        return Vec::new();
     }
 
-    // Path names are null-terminated, multi-string format
-    // Get the first path
-    let first_null = path_names.iter().position(|&c| c == 0)?;
-    if first_null == 0 {
-        return None;
-    }
+    // Path names are null-terminated, multi-string format ending in an
+    // empty string
+    path_names
+        .split(|&c| c == 0)
+        .take_while(|name| !name.is_empty())
+        .map(String::from_utf16_lossy)
+        .collect()
+}
 
-    Some(String::from_utf16_lossy(&path_names[..first_null]))
+/// Check if a mount point is a drive root such as "C:\".
+fn is_drive_root(path: &str) -> bool {
+    path.trim_end_matches('\\').len() == 2 && path.as_bytes().get(1) == Some(&b':')
 }
 
 /// Check if a path is on a fixed drive.
@@ -227,6 +280,7 @@ fn get_volume_details(volume_guid: &str, mount_point: &str) -> Option<NtfsVolume
     Some(NtfsVolumeInfo {
         volume_guid: volume_guid.to_string(),
         mount_point: mount_point.to_string(),
+        mount_folders: Vec::new(),
         label,
         serial_number,
         filesystem,
@@ -244,7 +298,8 @@ pub fn get_volume_info(mount_point: &str) -> Result<NtfsVolumeInfo, NtfsError> {
         format!("{}\\", mount_point)
     };
 
-    get_volume_details("", &mount_point).ok_or_else(|| NtfsError::VolumeOpen {
+    let volume_guid = volume_guid_for_mount_point(&mount_point).unwrap_or_default();
+    get_volume_details(&volume_guid, &mount_point).ok_or_else(|| NtfsError::VolumeOpen {
         volume: mount_point,
         reason: "Failed to get volume information".to_string(),
     })
@@ -272,4 +327,12 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_is_drive_root() {
+        assert!(is_drive_root("C:"));
+        assert!(is_drive_root("C:\\"));
+        assert!(!is_drive_root("C:\\Mount\\Data\\"));
+        assert!(!is_drive_root("\\\\?\\Volume{0}\\"));
+    }
 }
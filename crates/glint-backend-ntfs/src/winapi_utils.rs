@@ -7,8 +7,11 @@ use crate::error::NtfsError;
 use std::ffi::OsStr;
 use std::os::windows::ffi::OsStrExt;
 use std::ptr;
+use std::thread;
+use std::time::Duration;
+use tracing::{debug, warn};
 use windows::core::PCWSTR;
-use windows::Win32::Foundation::{CloseHandle, HANDLE, INVALID_HANDLE_VALUE};
+use windows::Win32::Foundation::{CloseHandle, GetLastError, HANDLE, INVALID_HANDLE_VALUE};
 use windows::Win32::Storage::FileSystem::{
     CreateFileW, FILE_ATTRIBUTE_NORMAL, FILE_FLAG_BACKUP_SEMANTICS, FILE_SHARE_DELETE,
     FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
@@ -114,6 +117,182 @@ pub fn open_volume_for_usn(volume_path: &str) -> Result<SafeHandle, NtfsError> {
     }
 }
 
+/// The calling thread's last Win32 error code.
+pub fn last_error() -> u32 {
+    unsafe { GetLastError().0 }
+}
+
+// Win32 errors worth another try: the device is busy or not ready yet
+const ERROR_NOT_READY: u32 = 21;
+const ERROR_SEM_TIMEOUT: u32 = 121;
+const ERROR_BUSY: u32 = 170;
+
+// Win32 errors reading damaged media (bad clusters, corrupt structures)
+const ERROR_CRC: u32 = 23;
+const ERROR_SECTOR_NOT_FOUND: u32 = 27;
+const ERROR_READ_FAULT: u32 = 30;
+const ERROR_IO_DEVICE: u32 = 1117;
+const ERROR_FILE_CORRUPT: u32 = 1392;
+const ERROR_DISK_CORRUPT: u32 = 1393;
+
+/// How a failed call is handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Recovery {
+    /// Try again; the error usually goes away
+    Retry,
+    /// Try again, then skip the data if it still can't be read
+    RetryThenSkip,
+    /// Fail right away (including "errors" like end of data)
+    Fail,
+}
+
+fn recovery_for(code: u32) -> Recovery {
+    match code {
+        ERROR_NOT_READY | ERROR_SEM_TIMEOUT | ERROR_BUSY => Recovery::Retry,
+        ERROR_CRC
+        | ERROR_SECTOR_NOT_FOUND
+        | ERROR_READ_FAULT
+        | ERROR_IO_DEVICE
+        | ERROR_FILE_CORRUPT
+        | ERROR_DISK_CORRUPT => Recovery::RetryThenSkip,
+        _ => Recovery::Fail,
+    }
+}
+
+/// How [`retry_io`] handles errors that may not be permanent.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Attempts after the first failed one
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled for each one after it
+    pub backoff: Duration,
+    /// Let the caller skip data that stays unreadable, instead of failing
+    pub skip_unreadable: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            backoff: Duration::from_millis(100),
+            skip_unreadable: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Retry, but fail rather than skip unreadable data.
+    pub fn without_skipping() -> Self {
+        RetryPolicy {
+            skip_unreadable: false,
+            ..Default::default()
+        }
+    }
+}
+
+/// Read errors a scan recovered from or worked around.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IoErrorStats {
+    /// Failed calls that were tried again
+    pub retries: u64,
+    /// Calls that succeeded after failing at first
+    pub recovered: u64,
+    /// Unreadable ranges that were skipped
+    pub skipped: u64,
+    /// Code of the last error retried or skipped
+    pub last_error: Option<u32>,
+}
+
+impl IoErrorStats {
+    /// Check if every call succeeded the first time.
+    pub fn is_clean(&self) -> bool {
+        self.retries == 0 && self.skipped == 0
+    }
+}
+
+/// Outcome of a call made through [`retry_io`].
+#[derive(Debug)]
+pub enum Attempt<T> {
+    /// The call succeeded, possibly after retries
+    Done(T),
+    /// The data stayed unreadable; the caller should move past it
+    Skipped,
+}
+
+/// Make a Windows API call, retrying transient errors per `policy`.
+///
+/// `call` returns the Win32 error code when it fails. Errors that retrying
+/// can't fix are returned as they are, so callers can still tell apart
+/// codes like end-of-data. Retries and skips are counted in `stats`.
+pub fn retry_io<T>(
+    policy: &RetryPolicy,
+    stats: &mut IoErrorStats,
+    operation: &str,
+    mut call: impl FnMut() -> Result<T, u32>,
+) -> Result<Attempt<T>, u32> {
+    let mut backoff = policy.backoff;
+    let mut attempts = 0;
+    loop {
+        let result = match take_injected_fault() {
+            Some(code) => Err(code),
+            None => call(),
+        };
+        let code = match result {
+            Ok(value) => {
+                if attempts > 0 {
+                    stats.recovered += 1;
+                }
+                return Ok(Attempt::Done(value));
+            }
+            Err(code) => code,
+        };
+
+        let recovery = recovery_for(code);
+        if recovery == Recovery::Fail {
+            return Err(code);
+        }
+        stats.last_error = Some(code);
+
+        if attempts < policy.max_retries {
+            attempts += 1;
+            stats.retries += 1;
+            debug!(operation, code, attempts, "Retrying after I/O error");
+            thread::sleep(backoff);
+            backoff *= 2;
+            continue;
+        }
+        if recovery == Recovery::RetryThenSkip && policy.skip_unreadable {
+            stats.skipped += 1;
+            warn!(operation, code, "Skipping unreadable data");
+            return Ok(Attempt::Skipped);
+        }
+        return Err(code);
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    static INJECTED_FAULTS: std::cell::RefCell<std::collections::VecDeque<u32>> =
+        const { std::cell::RefCell::new(std::collections::VecDeque::new()) };
+}
+
+/// Make the next calls through [`retry_io`] on this thread fail with these
+/// Win32 codes, in order, without reaching the device.
+#[cfg(test)]
+pub fn inject_faults(codes: impl IntoIterator<Item = u32>) {
+    INJECTED_FAULTS.with(|faults| faults.borrow_mut().extend(codes));
+}
+
+#[cfg(test)]
+fn take_injected_fault() -> Option<u32> {
+    INJECTED_FAULTS.with(|faults| faults.borrow_mut().pop_front())
+}
+
+#[cfg(not(test))]
+fn take_injected_fault() -> Option<u32> {
+    None
+}
+
 /// Convert a FILETIME value to a chrono DateTime.
 pub fn filetime_to_datetime(ft: i64) -> chrono::DateTime<chrono::Utc> {
     use chrono::{TimeZone, Utc};
@@ -132,6 +311,23 @@ pub fn filetime_to_datetime(ft: i64) -> chrono::DateTime<chrono::Utc> {
         .unwrap_or_else(Utc::now)
 }
 
+/// Contents are fetched from the cloud when the file is read
+const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x0040_0000;
+/// Contents are fetched from the cloud when the file is opened
+const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x0004_0000;
+/// Contents have been moved to offline storage
+const FILE_ATTRIBUTE_OFFLINE: u32 = 0x1000;
+
+/// Check whether file attributes mark an online-only cloud placeholder,
+/// such as a OneDrive file that isn't downloaded. Reading one downloads it.
+pub fn is_cloud_placeholder(attributes: u32) -> bool {
+    attributes
+        & (FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS
+            | FILE_ATTRIBUTE_RECALL_ON_OPEN
+            | FILE_ATTRIBUTE_OFFLINE)
+        != 0
+}
+
 /// Get the drive letter from a volume path like "\\?\C:" or "C:".
 pub fn extract_drive_letter(path: &str) -> Option<char> {
     // Handle paths like "\\?\C:" or "\\.\C:"
@@ -189,5 +385,72 @@ mod tests {
         assert_eq!(dt.day(), 1);
     }
 
+    #[test]
+    fn test_is_cloud_placeholder() {
+        // Online-only OneDrive file: archive, unpinned, recall on data access
+        assert!(is_cloud_placeholder(0x0052_0020));
+        // Pinned ("always keep on this device") and downloaded
+        assert!(!is_cloud_placeholder(0x0008_0020));
+        assert!(!is_cloud_placeholder(0x10));
+    }
+
+    fn quick_policy() -> RetryPolicy {
+        RetryPolicy {
+            backoff: Duration::ZERO,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_retry_recovers_transient_errors() {
+        let mut stats = IoErrorStats::default();
+        inject_faults([ERROR_NOT_READY, ERROR_BUSY]);
+        let result = retry_io(&quick_policy(), &mut stats, "test", || Ok::<_, u32>(7));
+        assert!(matches!(result, Ok(Attempt::Done(7))));
+        assert_eq!(stats.retries, 2);
+        assert_eq!(stats.recovered, 1);
+        assert_eq!(stats.last_error, Some(ERROR_BUSY));
+
+        // Too many failures in a row give up with the error
+        let mut stats = IoErrorStats::default();
+        inject_faults([ERROR_NOT_READY; 4]);
+        let result = retry_io(&quick_policy(), &mut stats, "test", || Ok::<_, u32>(7));
+        assert!(matches!(result, Err(ERROR_NOT_READY)));
+        assert_eq!(stats.retries, 3);
+        assert_eq!(stats.skipped, 0);
+    }
+
+    #[test]
+    fn test_retry_skips_unreadable_data() {
+        let mut stats = IoErrorStats::default();
+        inject_faults([ERROR_CRC; 4]);
+        let result = retry_io(&quick_policy(), &mut stats, "test", || Ok::<_, u32>(7));
+        assert!(matches!(result, Ok(Attempt::Skipped)));
+        assert_eq!(stats.skipped, 1);
+        assert!(!stats.is_clean());
+
+        let policy = RetryPolicy {
+            backoff: Duration::ZERO,
+            ..RetryPolicy::without_skipping()
+        };
+        let mut stats = IoErrorStats::default();
+        inject_faults([ERROR_CRC; 4]);
+        let result = retry_io(&policy, &mut stats, "test", || Ok::<_, u32>(7));
+        assert!(matches!(result, Err(ERROR_CRC)));
+    }
+
+    #[test]
+    fn test_retry_passes_other_errors_through() {
+        // End of data (ERROR_HANDLE_EOF) and access denied aren't retried
+        for code in [38, 5] {
+            let mut stats = IoErrorStats::default();
+            inject_faults([code]);
+            let result = retry_io(&quick_policy(), &mut stats, "test", || Ok::<_, u32>(7));
+            assert!(matches!(result, Err(c) if c == code));
+            assert!(stats.is_clean());
+            assert_eq!(stats.last_error, None);
+        }
+    }
+
     use chrono::Datelike;
 }
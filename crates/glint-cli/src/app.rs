@@ -1,9 +1,13 @@
 //! Application state management.
 
+use crate::progress::Progress;
 use glint_backend_ntfs::NtfsBackend;
+use glint_core::federation::{AttachedList, Federation};
+use glint_core::manifest::BuildManifest;
 use glint_core::{Config, FileSystemBackend, Index, IndexStore};
 use std::sync::Arc;
-use tracing::info;
+use std::time::Instant;
+use tracing::{info, warn};
 
 /// Shared application state.
 pub struct App {
@@ -23,13 +27,14 @@ pub struct App {
 impl App {
     /// Create a new application instance.
     pub fn new(config: Config) -> anyhow::Result<Self> {
-        let data_dir = config.index_dir()?;
-        let store = IndexStore::new(&data_dir);
-        let index = Arc::new(store.load_or_new());
-        let backend = Arc::new(NtfsBackend::new());
+        let (store, index) = glint_frontend::load_index(&config)?;
+        let backend = Arc::new(
+            NtfsBackend::new().ignoring_maintenance(config.general.ignore_maintenance_changes),
+        );
+        index.set_capabilities(backend.capabilities());
 
         info!(
-            data_dir = %data_dir.display(),
+            index = %store.index_path().display(),
             records = index.len(),
             "Application initialized"
         );
@@ -42,6 +47,17 @@ impl App {
         })
     }
 
+    /// Load the indexes attached to this one, warning about any that
+    /// cannot be loaded.
+    pub fn attached(&self) -> anyhow::Result<Federation> {
+        let list = AttachedList::load(&AttachedList::path_in(&self.config.index_dir()?))?;
+        let (federation, failures) = Federation::open(&list);
+        for (name, e) in failures {
+            eprintln!("⚠ Attached index '{}' is not searched: {}", name, e);
+        }
+        Ok(federation)
+    }
+
     /// Save the current index to disk.
     pub fn save_index(&self) -> anyhow::Result<()> {
         self.store.save(&self.index)?;
@@ -49,42 +65,96 @@ impl App {
     }
 
     /// Rebuild the index from scratch.
-    pub fn rebuild_index(&self, volumes: &[String]) -> anyhow::Result<()> {
-        use glint_core::backend::LoggingProgress;
-
-        self.index.clear();
+    ///
+    /// Volumes or folders given here replace the configured selection.
+    /// Folders are indexed as folder volumes. Each volume's scan is
+    /// reported to `progress`. A manifest of the build is saved with the
+    /// index.
+    pub fn rebuild_index(
+        &self,
+        volumes: &[String],
+        folders: &[String],
+        progress: &Progress,
+    ) -> anyhow::Result<()> {
+        use glint_core::backend::folder_volume;
+
+        self.index.ensure_writable("rebuild the index")?;
+        let start = Instant::now();
 
         let available_volumes = self.backend.list_volumes()?;
+        let explicit = !volumes.is_empty() || !folders.is_empty();
+        let folders = if explicit {
+            folders
+        } else {
+            &self.config.volumes.folders
+        };
 
-        let volumes_to_index: Vec<_> = if volumes.is_empty() {
+        let mut volumes_to_index: Vec<_> = if !explicit {
             available_volumes
-                .into_iter()
+                .iter()
                 .filter(|v| self.config.should_index_volume(&v.mount_point))
+                .cloned()
                 .collect()
         } else {
             available_volumes
-                .into_iter()
+                .iter()
                 .filter(|v| {
+                    let mount = v.mount_point.trim_end_matches(['\\', '/']).to_lowercase();
                     volumes.iter().any(|requested| {
-                        v.mount_point
-                            .to_lowercase()
-                            .starts_with(&requested.to_lowercase())
+                        let requested = requested.trim_end_matches(['\\', '/']).to_lowercase();
+                        // "C" or "C:" picks the drive, not volumes mounted in its folders
+                        mount == requested || (mount.len() <= 2 && mount.starts_with(&requested))
                     })
                 })
+                .cloned()
                 .collect()
         };
 
-        for volume in volumes_to_index {
-            info!(volume = %volume.mount_point, "Indexing volume");
-
-            let progress = Arc::new(LoggingProgress::new(&volume.mount_point));
-            let records = self.backend.full_scan(&volume, Some(progress))?;
-
-            self.index.add_volume_records(&volume, records);
+        for folder in folders {
+            if !std::path::Path::new(folder).is_dir() {
+                anyhow::bail!("Folder not found: {}", folder);
+            }
+            match folder_volume(&available_volumes, folder) {
+                Some(volume) => volumes_to_index.push(volume),
+                None => anyhow::bail!("No indexable volume contains {}", folder),
+            }
         }
 
+        // Scan into a new index, so the old one is kept if a scan fails
+        self.index.rebuild_with(|| {
+            let rebuilt = Index::new();
+            glint_frontend::apply_config(&rebuilt, &self.config);
+            for mut volume in volumes_to_index {
+                info!(volume = %volume.display_name(), "Indexing volume");
+
+                // Note the journal position first, so `watch --once` can pick
+                // up changes made during the scan
+                volume.journal_state =
+                    self.backend.get_journal_state(&volume).unwrap_or_else(|e| {
+                        warn!(volume = %volume.display_name(), error = %e, "No journal state");
+                        None
+                    });
+
+                let records = self
+                    .backend
+                    .full_scan(&volume, Some(progress.scan(volume.display_name())))?;
+
+                rebuilt.add_volume_records(&volume, records);
+            }
+            anyhow::Ok(rebuilt)
+        })?;
+
+        glint_frontend::apply_config(&self.index, &self.config);
         self.save_index()?;
 
+        let mut manifest = BuildManifest::new(&self.index, &self.config, start.elapsed());
+        for (volume, method) in progress.scan_methods() {
+            manifest.set_scan_mode(&volume, method);
+        }
+        if let Err(e) = self.store.save_manifest(&manifest) {
+            warn!(error = %e, "Cannot save index manifest");
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,83 @@
+//! Attach command - search other index files alongside the local one.
+
+use glint_core::federation::{self, AttachedList};
+use glint_core::{Config, IndexStore};
+use std::path::Path;
+
+/// Run the attach command.
+///
+/// Attaches the index at `path` as `name` (default: its folder name), or
+/// with `remove`, detaches the index attached under that name or from that
+/// path. Lists attached indexes when no path is given.
+pub fn run(
+    config: Config,
+    path: Option<String>,
+    name: Option<String>,
+    remove: bool,
+) -> anyhow::Result<()> {
+    let index_dir = config.index_dir()?;
+    let list_path = AttachedList::path_in(&index_dir);
+    let mut list = AttachedList::load(&list_path)?;
+
+    let Some(path) = path else {
+        return show(&list);
+    };
+
+    if remove {
+        let absolute = super::absolute(&path)?;
+        let attached = list
+            .indexes()
+            .iter()
+            .find(|a| same_dir(&a.dir, Path::new(&absolute)));
+        let name = match attached {
+            Some(attached) => attached.name.clone(),
+            None => path,
+        };
+        if !list.detach(&name) {
+            anyhow::bail!("No index is attached as {}", name);
+        }
+        list.save(&list_path)?;
+        println!("Detached {}", name);
+        return Ok(());
+    }
+
+    let dir = federation::index_dir(Path::new(&super::absolute(&path)?))?;
+    if same_dir(&dir, &index_dir) {
+        anyhow::bail!("{} is the local index", dir.display());
+    }
+    let name = match name {
+        Some(name) => name,
+        None => dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .ok_or_else(|| anyhow::anyhow!("Use --name to name the index"))?,
+    };
+    list.attach(&name, &dir)?;
+    list.save(&list_path)?;
+    println!("Attached {} as '{}'", dir.display(), name.trim());
+    Ok(())
+}
+
+/// Whether two paths name the same directory; NTFS ignores case.
+fn same_dir(a: &Path, b: &Path) -> bool {
+    a.to_string_lossy()
+        .eq_ignore_ascii_case(&b.to_string_lossy())
+}
+
+fn show(list: &AttachedList) -> anyhow::Result<()> {
+    if list.is_empty() {
+        println!("Nothing attached. Use 'glint attach <path>' to search another index too.");
+        return Ok(());
+    }
+
+    for attached in list.indexes() {
+        let missing = !IndexStore::new(&attached.dir).exists();
+        println!(
+            "{}  {}{}",
+            attached.name,
+            attached.dir.display(),
+            if missing { " (not found)" } else { "" }
+        );
+    }
+    Ok(())
+}
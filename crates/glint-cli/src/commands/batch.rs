@@ -0,0 +1,114 @@
+//! Batch command - run many queries against one view of the index.
+
+use crate::app::App;
+use crate::OutputFormat;
+use glint_core::ipc::QueryRequest;
+use glint_core::{Config, IndexSnapshot, SearchPage};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+/// Run the batch command.
+///
+/// Reads one query per line from `file`, or from standard input when it is
+/// `-`, and searches every one in the same snapshot of the index, so their
+/// results agree with each other however long the batch takes. Blank
+/// lines and lines starting with `#` are skipped. Queries take `scope:`
+/// tokens, and leave out the `hide_patterns` of the `[ui]` config, like
+/// `glint query`.
+pub fn run(config: Config, file: &str, limit: usize, output: OutputFormat) -> anyhow::Result<()> {
+    let input: Box<dyn BufRead> = match file {
+        "-" => Box::new(io::stdin().lock()),
+        path => Box::new(BufReader::new(File::open(path).map_err(|e| {
+            anyhow::anyhow!("Cannot read queries from {}: {}", path, e)
+        })?)),
+    };
+    let queries = read_queries(input)?;
+
+    let app = App::new(config)?;
+    if app.index.is_empty() {
+        eprintln!("Index is empty. Run 'glint index' first.");
+        return Ok(());
+    }
+    let snapshot = app.index.snapshot();
+    let display_path = |path: &str| app.config.ui.path_format.apply(path);
+
+    let mut answers = Vec::with_capacity(queries.len());
+    for query in &queries {
+        answers.push(search(&app, &snapshot, query, limit)?);
+    }
+
+    match output {
+        OutputFormat::Text => {
+            for (query, page) in queries.iter().zip(&answers) {
+                println!("== {} ({} results)", query, page.total);
+                for result in &page.results {
+                    println!("{}", display_path(&result.record.path));
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let json: Vec<serde_json::Value> = queries
+                .iter()
+                .zip(&answers)
+                .map(|(query, page)| {
+                    let results: Vec<serde_json::Value> = page
+                        .results
+                        .iter()
+                        .map(|r| super::query::result_json(r, false, false, false, &display_path))
+                        .collect();
+                    serde_json::json!({
+                        "query": query,
+                        "total": page.total,
+                        "results": results,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+    }
+    Ok(())
+}
+
+/// The queries in `input`, one per line, without blank and `#` lines.
+fn read_queries(input: impl BufRead) -> anyhow::Result<Vec<String>> {
+    let mut queries = Vec::new();
+    for line in input.lines() {
+        let line = line?;
+        let query = line.trim();
+        if !query.is_empty() && !query.starts_with('#') {
+            queries.push(query.to_string());
+        }
+    }
+    Ok(queries)
+}
+
+/// The first `limit` results of `query` in `snapshot`, best first.
+fn search(
+    app: &App,
+    snapshot: &IndexSnapshot,
+    query: &str,
+    limit: usize,
+) -> anyhow::Result<SearchPage> {
+    let (query, folders) = app.config.scopes.split_query(query)?;
+    let request = QueryRequest {
+        scope: folders,
+        exclude: app.config.ui.hide_patterns.clone(),
+        ..QueryRequest::new(query, limit)
+    };
+    let query = request.to_search_query(&app.index)?;
+    Ok(snapshot.search_ranked(&query, 0, limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_queries() {
+        let input = "*.rs\n\n  # comment\n  report ext:pdf  \n";
+        assert_eq!(
+            read_queries(input.as_bytes()).unwrap(),
+            ["*.rs", "report ext:pdf"]
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! Cleanup command - move or remove old files inside some folders.
+
+use crate::app::App;
+use glint_backend_ntfs::recycle_bin;
+use glint_core::cleanup::{CleanupAction, CleanupPlan};
+use glint_core::clock::{Clock, SystemClock};
+use glint_core::ipc::QueryRequest;
+use glint_core::report::parse_age;
+use glint_core::{Config, SearchFilter};
+use glint_frontend::format::format_size;
+use std::path::PathBuf;
+
+/// Run the cleanup command.
+///
+/// Files inside `within` last modified more than `older_than` ago, and
+/// matching `query` if given, are listed, then moved to `move_to` or
+/// removed once confirmed. Removed files go to the Recycle Bin unless
+/// `permanent` is set.
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+    config: Config,
+    older_than: String,
+    within: Vec<String>,
+    query: Option<String>,
+    move_to: Option<String>,
+    permanent: bool,
+    limit: usize,
+    dry_run: bool,
+    skip_confirm: bool,
+) -> anyhow::Result<()> {
+    let app = App::new(config)?;
+    app.index.ensure_writable("clean up files")?;
+
+    if app.index.is_empty() {
+        eprintln!("Index is empty. Run 'glint index' first.");
+        return Ok(());
+    }
+
+    let cutoff = SystemClock.before(parse_age(&older_than)?);
+    let mut request = QueryRequest::new(query.unwrap_or_default(), limit);
+    request.files_only = true;
+    request.within = within
+        .iter()
+        .map(|dir| super::absolute(dir))
+        .collect::<anyhow::Result<_>>()?;
+    let search = request
+        .to_search_query(&app.index)?
+        .with_filter(SearchFilter::ModifiedBefore(cutoff));
+    let records: Vec<_> = app
+        .index
+        .search_limited(&search, limit)
+        .into_iter()
+        .map(|r| r.record)
+        .collect();
+
+    if records.is_empty() {
+        println!("No files older than {} found.", older_than);
+        return Ok(());
+    }
+
+    let action = match &move_to {
+        Some(folder) => CleanupAction::MoveTo(PathBuf::from(super::absolute(folder)?)),
+        None => CleanupAction::Remove,
+    };
+    let plan = CleanupPlan::build(&records, action);
+    print_plan(&plan);
+
+    let ready = plan.ready_count();
+    println!(
+        "{} files, {}{}",
+        ready,
+        format_size(plan.ready_size()),
+        if records.len() == limit {
+            " (limit reached, use --limit for more)"
+        } else {
+            ""
+        }
+    );
+    if dry_run || ready == 0 {
+        return Ok(());
+    }
+
+    if plan.conflict_count() > 0 {
+        println!(
+            "{} files have conflicts and will be left in place.",
+            plan.conflict_count()
+        );
+    }
+
+    let prompt = match (&move_to, permanent) {
+        (Some(folder), _) => format!("Move {} files to {}?", ready, folder),
+        (None, false) => format!("Move {} files to the Recycle Bin?", ready),
+        (None, true) => format!("Permanently delete {} files?", ready),
+    };
+    if !skip_confirm && !super::confirm(&prompt)? {
+        println!("Cancelled.");
+        return Ok(());
+    }
+
+    let outcome = if permanent {
+        plan.execute(&app.index, |path| std::fs::remove_file(path))
+    } else {
+        plan.execute(&app.index, recycle_bin::send)
+    };
+    for (path, error) in &outcome.failures {
+        eprintln!("⚠ {}: {}", path, error);
+    }
+    app.save_index()?;
+
+    let verb = if move_to.is_some() {
+        "Moved"
+    } else {
+        "Removed"
+    };
+    println!(
+        "{} {} files ({}).",
+        verb,
+        outcome.done,
+        format_size(outcome.bytes)
+    );
+    Ok(())
+}
+
+fn print_plan(plan: &CleanupPlan) {
+    for entry in &plan.entries {
+        let record = &entry.record;
+        let modified = record
+            .modified
+            .map(|t| t.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        match (&entry.conflict, &entry.target) {
+            (Some(reason), _) => println!("✗ {} ({})", record.path, reason),
+            (None, Some(target)) => {
+                println!("  {}  {} → {}", modified, record.path, target.display())
+            }
+            (None, None) => println!(
+                "  {}  {:>10}  {}",
+                modified,
+                format_size(record.size.unwrap_or(0)),
+                record.path
+            ),
+        }
+    }
+    println!();
+}
@@ -6,7 +6,8 @@ use std::io::{self, Write};
 /// Run the clear command.
 pub fn run(config: Config, skip_confirm: bool) -> anyhow::Result<()> {
     let data_dir = config.index_dir()?;
-    let store = IndexStore::new(&data_dir);
+    let store = IndexStore::new(&data_dir).with_read_only(config.general.read_only);
+    store.ensure_writable("clear the index")?;
 
     if !store.exists() {
         println!("No index found. Nothing to clear.");
@@ -0,0 +1,80 @@
+//! Compact command - drop deleted files and long-gone volumes from the index.
+
+use crate::app::App;
+use glint_core::{Config, FileSystemBackend, VolumeId};
+use glint_frontend::format::format_size;
+use std::time::Duration;
+
+/// Run the compact command.
+///
+/// `absent_days` overrides `drop_absent_volumes_after_days` (0 = keep every
+/// volume). The backup of the previous index is deleted too, since it holds
+/// everything compacting drops.
+pub fn run(config: Config, absent_days: Option<u32>, dry_run: bool) -> anyhow::Result<()> {
+    let app = App::new(config)?;
+    if !dry_run {
+        app.index.ensure_writable("compact the index")?;
+    }
+    if app.index.is_empty() {
+        println!("No index found. Run 'glint index' first.");
+        return Ok(());
+    }
+
+    let absent_for = match absent_days {
+        Some(0) => None,
+        Some(days) => Some(Duration::from_secs(u64::from(days) * 86_400)),
+        None => app.config.drop_absent_after(),
+    };
+    let absent = match absent_for {
+        Some(absent_for) => match app.backend.list_volumes() {
+            Ok(present) => app.index.absent_volumes(&present, absent_for),
+            Err(e) => {
+                eprintln!("⚠ Cannot list volumes, keeping all of them ({})", e);
+                Vec::new()
+            }
+        },
+        None => Vec::new(),
+    };
+
+    if dry_run {
+        println!(
+            "Would drop {} deleted-file records",
+            app.index.tombstone_count()
+        );
+        for state in &absent {
+            println!(
+                "Would drop {} ({} entries, last updated {})",
+                state.info.display_name(),
+                state.record_count,
+                state
+                    .last_updated()
+                    .map_or("never".to_string(), |t| t.format("%Y-%m-%d").to_string())
+            );
+        }
+        return Ok(());
+    }
+
+    let before = app.store.disk_usage();
+    let volumes: Vec<VolumeId> = absent.iter().map(|s| s.info.id.clone()).collect();
+    let report = app.index.compact(&volumes);
+    if !report.is_empty() {
+        app.save_index()?;
+    }
+    app.store.remove_spare_files()?;
+    let after = app.store.disk_usage();
+
+    println!("Dropped {} deleted-file records", report.tombstones);
+    for name in &report.volumes {
+        println!("Dropped {}, absent too long", name);
+    }
+    println!(
+        "Index files: {} → {} (saved {})",
+        format_size(before),
+        format_size(after),
+        format_size(before.saturating_sub(after))
+    );
+    if !report.is_empty() {
+        println!("Restart 'glint watch' if it is running, so it doesn't save the old records.");
+    }
+    Ok(())
+}
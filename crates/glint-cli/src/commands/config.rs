@@ -0,0 +1,55 @@
+//! Config command - show the configuration in effect and where it came from.
+
+use crate::ConfigAction;
+use glint_core::layers::{machine_config_path, ConfigLayers};
+use std::path::{Path, PathBuf};
+
+/// Run the config command.
+///
+/// Layers are read again rather than taken from the loaded config, since
+/// the effective config alone doesn't say which file set a value.
+pub fn run(user_path: &Path, set: &[String], action: ConfigAction) -> anyhow::Result<()> {
+    let machine_path = machine_config_path();
+    let layers = ConfigLayers::load(&machine_path, user_path, set)?;
+
+    match action {
+        ConfigAction::Show { origin } => {
+            println!(
+                "# machine: {}",
+                describe(&machine_path, &layers.machine_path)
+            );
+            println!("# user:    {}", describe(user_path, &layers.user_path));
+            for (origin, key) in &layers.ignored {
+                println!(
+                    "# ignored: {} from {} (locked by the machine config)",
+                    key, origin
+                );
+            }
+            println!();
+
+            if origin {
+                let values = layers.values()?;
+                let width = values
+                    .iter()
+                    .map(|(key, value)| key.len() + value.to_string().len() + 3)
+                    .max()
+                    .unwrap_or(0);
+                for (key, value) in values {
+                    let line = format!("{} = {}", key, value);
+                    println!("{:<width$}  # {}", line, layers.origin(&key), width = width);
+                }
+            } else {
+                print!("{}", toml::to_string_pretty(&layers.config)?);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn describe(path: &Path, found: &Option<PathBuf>) -> String {
+    if found.is_some() {
+        path.display().to_string()
+    } else {
+        format!("{} (not found)", path.display())
+    }
+}
@@ -0,0 +1,128 @@
+//! Doctor command - check the index and suggest exclusions.
+
+use crate::app::App;
+use chrono::Local;
+use glint_core::exclude::{Exclusions, PRESETS};
+use glint_core::{Config, FileSystemBackend};
+
+/// Run the doctor command.
+pub fn run(config: Config) -> anyhow::Result<()> {
+    let app = App::new(config)?;
+    let exclude = &app.config.exclude;
+
+    println!("Glint Doctor");
+    println!("============");
+    println!();
+
+    if app.index.is_empty() {
+        println!("⚠ Index is empty. Run 'glint index' to build the index.");
+    } else {
+        println!("✓ Index has {} entries", app.index.len());
+        for vol in app.index.volume_states() {
+            if vol.needs_rescan {
+                println!(
+                    "⚠ {} needs a rescan; run 'glint index --force'",
+                    vol.info.display_name()
+                );
+            }
+        }
+    }
+
+    // Backends without these leave the index to adapt
+    let capabilities = app.index.capabilities();
+    let backend = app.backend.name();
+    if !capabilities.supports_change_journal {
+        println!(
+            "- The {} backend has no change journal; the index is kept current by rebuilding it",
+            backend
+        );
+    }
+    if !capabilities.provides_sizes {
+        println!(
+            "- The {} backend reads no file sizes; size filters are skipped",
+            backend
+        );
+    }
+    if !capabilities.provides_timestamps {
+        println!(
+            "- The {} backend reads no modification times; date filters are skipped",
+            backend
+        );
+    }
+
+    // Exclusions apply as entries are indexed, so changed ones need a rebuild
+    match app.store.load_manifest() {
+        Some(manifest) => {
+            let built = &manifest.exclusions;
+            if built.paths != exclude.paths
+                || built.patterns != exclude.patterns
+                || built.presets != exclude.presets
+            {
+                println!("⚠ Exclusions changed since the last build; run 'glint index --force' to apply them");
+            }
+        }
+        None if !app.index.is_empty() => {
+            println!("⚠ No build manifest; rebuild with 'glint index --force'");
+        }
+        None => {}
+    }
+
+    println!();
+    println!("Exclusion presets:");
+    for preset in PRESETS {
+        let enabled = exclude
+            .presets
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(preset.name));
+        println!(
+            "  {} {:<14} {}",
+            if enabled { "✓" } else { "-" },
+            preset.name,
+            preset.description
+        );
+    }
+
+    println!();
+    let Some(report) = app.store.load_churn() else {
+        println!("No busy folders recorded. 'glint watch' records folders changing more");
+        println!(
+            "than {} times a minute ([exclude] churn_threshold).",
+            exclude.churn_threshold
+        );
+        return Ok(());
+    };
+
+    let exclusions = Exclusions::new(exclude);
+    let busy: Vec<_> = report
+        .folders
+        .iter()
+        .filter(|f| !exclusions.excludes_folder(&f.path.to_lowercase()))
+        .collect();
+    println!(
+        "Busy folders (as of {}, over {} changes a minute):",
+        report
+            .generated_at
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S"),
+        report.threshold
+    );
+    if busy.is_empty() {
+        println!("  ✓ All busy folders are excluded");
+        return Ok(());
+    }
+
+    for folder in &busy {
+        println!(
+            "  ⚠ {} ({} changes a minute, {} in total)",
+            folder.path, folder.per_minute, folder.total
+        );
+        match folder.preset() {
+            Some(preset) => println!("    Enable the preset: presets = [..., \"{}\"]", preset),
+            None => println!("    Exclude it: paths = [..., {:?}]", folder.path),
+        }
+    }
+    println!();
+    println!("Add these under [exclude] in the config file, then run 'glint index --force'.");
+
+    Ok(())
+}
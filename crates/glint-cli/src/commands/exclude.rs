@@ -0,0 +1,286 @@
+//! Exclude command - list, add, remove and test exclusion rules, and prune
+//! what they leave out from the index.
+//!
+//! Rules are the `[exclude]` folders, name patterns and presets. Adding or
+//! removing one only changes the config file; `apply` takes the matching
+//! records out of the existing index so no re-index is needed.
+
+use crate::app::App;
+use crate::ExcludeAction;
+use glint_core::exclude::{count_by_rule, preset, ExcludeRule, Exclusions};
+use glint_core::Config;
+use glint_frontend::format::format_number;
+use std::path::Path;
+
+/// Run the exclude command.
+pub fn run(
+    mut config: Config,
+    config_path: Option<&Path>,
+    action: ExcludeAction,
+) -> anyhow::Result<()> {
+    match action {
+        ExcludeAction::List => list(&config),
+        ExcludeAction::Add {
+            rule,
+            folder,
+            pattern,
+        } => {
+            let rule = parse_rule(&rule, folder, pattern)?;
+            if !config.exclude.add_rule(rule.clone()) {
+                println!("The {} is already excluded", rule);
+                return Ok(());
+            }
+            save(&config, config_path)?;
+            println!("Excluded the {}", rule);
+            println!("Run 'glint exclude apply' to take its files out of the index now.");
+            Ok(())
+        }
+        ExcludeAction::Remove { rule } => {
+            let Some(found) = find_rule(&config, &rule) else {
+                anyhow::bail!("Not an exclusion rule: {} (see 'glint exclude list')", rule);
+            };
+            config.exclude.remove_rule(&found);
+            save(&config, config_path)?;
+            println!("No longer excluding the {}", found);
+            println!("Its files come back with the next 'glint index --force'.");
+            Ok(())
+        }
+        ExcludeAction::Test { path } => {
+            let path = super::absolute(&path)?;
+            match config.exclude.matching_rule(&path) {
+                Some(rule) => println!("{} is excluded by the {}", path, rule),
+                None => println!("{} is not excluded", path),
+            }
+            Ok(())
+        }
+        ExcludeAction::Apply { dry_run } => apply(config, dry_run),
+    }
+}
+
+fn list(config: &Config) -> anyhow::Result<()> {
+    let rules = config.exclude.rules();
+    if rules.is_empty() {
+        println!("Nothing is excluded. Add a rule with 'glint exclude add'.");
+        return Ok(());
+    }
+    for rule in &rules {
+        println!("{:<8} {}", rule.kind(), describe(rule));
+    }
+    Ok(())
+}
+
+/// Take the records the rules leave out of the index and save it.
+fn apply(config: Config, dry_run: bool) -> anyhow::Result<()> {
+    let app = App::new(config)?;
+    if !dry_run {
+        app.index.ensure_writable("remove excluded files")?;
+    }
+    if app.index.is_empty() {
+        println!("No index found. Run 'glint index' first.");
+        return Ok(());
+    }
+
+    let rules = app.config.exclude.rules();
+    let counts = count_by_rule(&app.index, &rules);
+    for (rule, count) in rules.iter().zip(&counts) {
+        println!(
+            "{:>12}  {:<8} {}",
+            format_number(*count),
+            rule.kind(),
+            describe(rule)
+        );
+    }
+    if dry_run {
+        return Ok(());
+    }
+
+    let removed = app
+        .index
+        .remove_excluded(&Exclusions::new(&app.config.exclude));
+    if removed == 0 {
+        println!("Nothing to remove; the index has no excluded files");
+        return Ok(());
+    }
+    app.save_index()?;
+    println!("Removed {} files from the index", format_number(removed));
+    println!("Restart 'glint watch' if it is running, so it doesn't save the old records.");
+    Ok(())
+}
+
+/// The rule `input` adds: a folder with `folder`, a pattern with
+/// `pattern`, and otherwise as [`ExcludeRule::parse`] reads it. Folders
+/// are made absolute.
+fn parse_rule(input: &str, folder: bool, pattern: bool) -> anyhow::Result<ExcludeRule> {
+    let rule = if folder {
+        ExcludeRule::Path(super::absolute(input)?)
+    } else if pattern {
+        ExcludeRule::Pattern(input.trim().to_string())
+    } else {
+        match ExcludeRule::parse(input) {
+            Some(ExcludeRule::Path(path)) => ExcludeRule::Path(super::absolute(&path)?),
+            Some(rule) => rule,
+            None => anyhow::bail!("No rule given"),
+        }
+    };
+    Ok(rule)
+}
+
+/// The rule whose text is `input`, as typed or as an absolute path.
+fn find_rule(config: &Config, input: &str) -> Option<ExcludeRule> {
+    let input = input.trim();
+    let absolute = super::absolute(input).ok();
+    config.exclude.rules().into_iter().find(|rule| {
+        let text = rule.text().trim_end_matches(['\\', '/']);
+        text.eq_ignore_ascii_case(input.trim_end_matches(['\\', '/']))
+            || absolute
+                .as_deref()
+                .is_some_and(|path| text.eq_ignore_ascii_case(path))
+    })
+}
+
+/// A rule's text, with what a preset leaves out.
+fn describe(rule: &ExcludeRule) -> String {
+    match rule {
+        ExcludeRule::Preset(name) => match preset(name) {
+            Some(preset) => format!("{} ({})", name, preset.description),
+            None => format!("{} (unknown preset)", name),
+        },
+        _ => rule.text().to_string(),
+    }
+}
+
+fn save(config: &Config, config_path: Option<&Path>) -> anyhow::Result<()> {
+    match config_path {
+        Some(path) => config.save_to(path)?,
+        None => config.save()?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glint_core::{FileId, FileRecord, Index, IndexStore, SearchQuery, VolumeId, VolumeInfo};
+
+    #[test]
+    fn test_exclude_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("config.toml");
+        let folder = dir.path().join("build").to_string_lossy().into_owned();
+        let add = |config: Config, rule: &str, folder: bool, pattern: bool| {
+            let action = ExcludeAction::Add {
+                rule: rule.to_string(),
+                folder,
+                pattern,
+            };
+            run(config, Some(&config_path), action).unwrap();
+            Config::load_from(&config_path).unwrap()
+        };
+
+        assert_eq!(
+            parse_rule(" *.tmp ", false, false).unwrap(),
+            ExcludeRule::Pattern("*.tmp".into())
+        );
+        assert_eq!(
+            parse_rule("build", false, true).unwrap(),
+            ExcludeRule::Pattern("build".into())
+        );
+        assert_eq!(
+            parse_rule("temp", false, false).unwrap(),
+            ExcludeRule::Preset("temp".into())
+        );
+        assert!(parse_rule("  ", false, false).is_err());
+
+        let mut config = Config::default();
+        config.exclude.presets.clear();
+        let config = add(config, "*.tmp", false, false);
+        let config = add(config, &folder, true, false);
+        let config = add(config, "*.tmp", false, false);
+        assert_eq!(
+            config.exclude.rules(),
+            [
+                ExcludeRule::Path(folder.clone()),
+                ExcludeRule::Pattern("*.tmp".into())
+            ]
+        );
+
+        // Which rule a path falls under, as `glint exclude test` shows it
+        let inside = Path::new(&folder).join("out.bin");
+        assert_eq!(
+            config.exclude.matching_rule(&inside.to_string_lossy()),
+            Some(ExcludeRule::Path(folder.clone()))
+        );
+        assert_eq!(
+            config.exclude.matching_rule("notes.tmp"),
+            Some(ExcludeRule::Pattern("*.tmp".into()))
+        );
+        assert_eq!(config.exclude.matching_rule("notes.txt"), None);
+
+        // Rules are found as listed, in any case and with a trailing separator
+        assert_eq!(
+            find_rule(&config, &format!("{}/", folder.to_uppercase())),
+            Some(ExcludeRule::Path(folder.clone()))
+        );
+        assert_eq!(find_rule(&config, "*.log"), None);
+        let remove = ExcludeAction::Remove {
+            rule: "*.TMP".to_string(),
+        };
+        run(config, Some(&config_path), remove).unwrap();
+        let config = Config::load_from(&config_path).unwrap();
+        assert_eq!(config.exclude.rules(), [ExcludeRule::Path(folder)]);
+        let remove = ExcludeAction::Remove {
+            rule: "*.tmp".to_string(),
+        };
+        assert!(run(config, Some(&config_path), remove).is_err());
+    }
+
+    #[test]
+    fn test_exclude_apply() {
+        let dir = tempfile::tempdir().unwrap();
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        let record = |id: u64, parent: Option<u64>, path: &str, is_dir: bool| {
+            let name = path.rsplit('\\').next().unwrap().to_string();
+            FileRecord::new(
+                FileId::new(id),
+                parent.map(FileId::new),
+                volume.id.clone(),
+                name,
+                path.to_string(),
+                is_dir,
+            )
+        };
+        let index = Index::new();
+        index.add_volume_records(
+            &volume,
+            vec![
+                record(5, None, "C:\\build", true),
+                record(6, Some(5), "C:\\build\\out.bin", false),
+                record(7, None, "C:\\notes.tmp", false),
+                record(8, None, "C:\\notes.txt", false),
+            ],
+        );
+        IndexStore::new(dir.path()).save(&index).unwrap();
+
+        let mut config = Config::default();
+        config.exclude.presets.clear();
+        config.general.index_path = Some(dir.path().to_path_buf());
+        config.exclude.paths = vec!["C:\\build".into()];
+        config.exclude.patterns = vec!["*.tmp".into()];
+        let names = || {
+            let index = IndexStore::new(dir.path()).load().unwrap();
+            let mut names: Vec<String> = index
+                .search(&SearchQuery::substring(""))
+                .into_iter()
+                .map(|r| r.record.name.to_string())
+                .collect();
+            names.sort();
+            names
+        };
+
+        apply(config.clone(), true).unwrap();
+        assert_eq!(names().len(), 4);
+
+        apply(config, false).unwrap();
+        assert_eq!(names(), ["notes.txt"]);
+    }
+}
@@ -0,0 +1,112 @@
+//! Explorer command - add Glint to Explorer's search box as a search connector.
+
+use crate::ExplorerAction;
+use glint_core::opensearch::{self, CONNECTOR_NAME, DEFAULT_EXPLORER_ADDR};
+use glint_core::Config;
+use std::fs;
+use std::net::TcpStream;
+use std::path::Path;
+use std::time::Duration;
+
+/// File name of the OpenSearch description in the data directory.
+const DESCRIPTION_FILE: &str = "glint.osdx";
+
+/// Run the explorer command.
+///
+/// Installing turns on `[server] explorer_listen` and opens the connector's
+/// description, which Windows asks to add under the user's Searches.
+pub fn run(
+    mut config: Config,
+    config_path: Option<&Path>,
+    action: ExplorerAction,
+) -> anyhow::Result<()> {
+    match action {
+        ExplorerAction::Status => status(&config),
+        ExplorerAction::Install => {
+            let addr = match &config.server.explorer_listen {
+                Some(addr) => addr.clone(),
+                None => {
+                    config.server.explorer_listen = Some(DEFAULT_EXPLORER_ADDR.to_string());
+                    save(&config, config_path)?;
+                    DEFAULT_EXPLORER_ADDR.to_string()
+                }
+            };
+            let path = config.index_dir()?.join(DESCRIPTION_FILE);
+            fs::create_dir_all(config.index_dir()?)?;
+            fs::write(&path, opensearch::description(&addr))?;
+
+            if !cfg!(windows) {
+                println!("Wrote {}", path.display());
+                println!(
+                    "Open it on Windows to add the '{}' search connector.",
+                    CONNECTOR_NAME
+                );
+                return Ok(());
+            }
+            std::process::Command::new("explorer.exe")
+                .arg(&path)
+                .status()?;
+            println!(
+                "✓ Opened {}; confirm adding '{}' to finish",
+                path.display(),
+                CONNECTOR_NAME
+            );
+            println!(
+                "Searches typed under Searches > {} are answered on {} while 'glint watch' \
+                 or the service runs; restart it if it is running.",
+                CONNECTOR_NAME, addr
+            );
+            Ok(())
+        }
+        ExplorerAction::Uninstall => {
+            if let Some(connector) = opensearch::connector_path().filter(|p| p.exists()) {
+                fs::remove_file(&connector)?;
+                println!("✓ Removed {}", connector.display());
+            }
+            let _ = fs::remove_file(config.index_dir()?.join(DESCRIPTION_FILE));
+            if config.server.explorer_listen.take().is_some() {
+                save(&config, config_path)?;
+                println!("✓ Turned off [server] explorer_listen");
+            }
+            Ok(())
+        }
+    }
+}
+
+fn status(config: &Config) -> anyhow::Result<()> {
+    let installed = opensearch::connector_path().is_some_and(|p| p.exists());
+    println!(
+        "Search connector: {}",
+        if installed {
+            "Installed"
+        } else {
+            "Not Installed"
+        }
+    );
+    match &config.server.explorer_listen {
+        Some(addr) => {
+            let answering = addr.parse().is_ok_and(|addr| {
+                TcpStream::connect_timeout(&addr, Duration::from_millis(500)).is_ok()
+            });
+            let state = if answering {
+                "answering"
+            } else {
+                "not running"
+            };
+            println!("Endpoint: {} ({})", addr, state);
+        }
+        None => println!("Endpoint: off ([server] explorer_listen is not set)"),
+    }
+    if !installed {
+        println!("Install it with 'glint explorer install'");
+    }
+    Ok(())
+}
+
+fn save(config: &Config, config_path: Option<&Path>) -> anyhow::Result<()> {
+    match config_path {
+        Some(path) => config.save_to(path)?,
+        None => config.save()?,
+    }
+    Ok(())
+}
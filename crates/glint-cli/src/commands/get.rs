@@ -0,0 +1,77 @@
+//! Get command - look up one record by its volume and file ID.
+
+use crate::app::App;
+use crate::OutputFormat;
+use glint_core::{Config, FileId, SearchResult};
+use glint_frontend::format::{format_date_time, format_size};
+
+/// Run the get command.
+///
+/// `volume` is a volume's name (`D:`, or a folder volume's path) or its ID
+/// as `glint status` shows it; `id` is a file ID in decimal or, after `0x`,
+/// in hex, as USN journal dumps and audit logs write them.
+pub fn run(config: Config, volume: String, id: String, output: OutputFormat) -> anyhow::Result<()> {
+    let app = App::new(config)?;
+
+    let file_id: FileId = id.parse().map_err(|_| {
+        anyhow::anyhow!("Not a file ID: {} (expected a number, or hex after 0x)", id)
+    })?;
+    let state = app
+        .index
+        .find_volume(&volume)
+        .or_else(|| {
+            app.index
+                .volume_states()
+                .into_iter()
+                .find(|state| state.info.id.as_str().eq_ignore_ascii_case(&volume))
+        })
+        .ok_or_else(|| anyhow::anyhow!("No indexed volume named {}", volume))?;
+    let volume_id = &state.info.id;
+
+    let Some(record) = app.index.get(volume_id, file_id) else {
+        anyhow::bail!(
+            "No record {} on {} (run 'glint index' to update the index)",
+            file_id,
+            state.info.display_name()
+        );
+    };
+    let display_path = |path: &str| app.config.ui.path_format.apply(path);
+
+    match output {
+        OutputFormat::Text => {
+            let parent = match record.parent_id {
+                Some(parent) => match app.index.get(volume_id, parent) {
+                    Some(folder) => format!("{} ({})", parent, display_path(&folder.path)),
+                    None => parent.to_string(),
+                },
+                None => "-".to_string(),
+            };
+            println!("Name:      {}", record.name);
+            println!("Path:      {}", display_path(&record.path));
+            println!(
+                "Volume:    {} (ID {})",
+                state.info.display_name(),
+                volume_id
+            );
+            println!("File ID:   {} (0x{:X})", record.id, record.id.as_u64());
+            println!("Parent:    {}", parent);
+            println!(
+                "Type:      {}",
+                if record.is_dir { "folder" } else { "file" }
+            );
+            if let Some(size) = record.size {
+                println!("Size:      {}", format_size(size));
+            }
+            if let Some(modified) = record.modified {
+                println!("Modified:  {}", format_date_time(modified));
+            }
+        }
+        OutputFormat::Json => {
+            let result = SearchResult::new(record, 0);
+            let json = super::query::result_json(&result, false, false, false, &display_path);
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+//! Help command - print the query syntax reference, or help for a command.
+//!
+//! Clap's own `help` subcommand is turned off so that `glint help syntax`
+//! can print the reference; other topics are looked up as commands.
+
+use crate::Cli;
+use clap::CommandFactory;
+use glint_frontend::syntax;
+
+/// Run the help command.
+pub fn run(topic: &[String], markdown: bool) -> anyhow::Result<()> {
+    if markdown || topic.first().is_some_and(|t| t == "syntax") {
+        if markdown {
+            print!("{}", syntax::markdown());
+        } else {
+            for line in syntax::plain_text() {
+                println!("{}", line);
+            }
+        }
+        return Ok(());
+    }
+
+    let mut command = Cli::command();
+    command.build();
+    for name in topic {
+        let Some(subcommand) = command.find_subcommand(name) else {
+            anyhow::bail!(
+                "No help for '{}'; see 'glint --help' or 'glint help syntax'",
+                topic.join(" ")
+            );
+        };
+        command = subcommand.clone();
+    }
+    command.print_long_help()?;
+    Ok(())
+}
@@ -1,12 +1,20 @@
 //! Index command - build or rebuild the file index.
 
 use crate::app::App;
+use crate::progress::{Progress, ProgressMode};
 use glint_core::Config;
 use std::time::Instant;
 
 /// Run the index command.
-pub fn run(config: Config, force: bool, volumes: Vec<String>) -> anyhow::Result<()> {
+pub fn run(
+    config: Config,
+    force: bool,
+    volumes: Vec<String>,
+    folders: Vec<String>,
+    progress: ProgressMode,
+) -> anyhow::Result<()> {
     let app = App::new(config)?;
+    app.index.ensure_writable("rebuild the index")?;
 
     // Check if we need to rebuild
     let needs_rebuild = force || app.index.is_empty();
@@ -31,10 +39,12 @@ pub fn run(config: Config, force: bool, volumes: Vec<String>) -> anyhow::Result<
 
     let start = Instant::now();
 
-    app.rebuild_index(&volumes)?;
+    let progress = Progress::new(progress);
+    app.rebuild_index(&volumes, &folders, &progress)?;
 
     let elapsed = start.elapsed();
     let stats = app.index.stats();
+    progress.print_summary();
 
     println!();
     println!("Indexing complete!");
@@ -0,0 +1,181 @@
+//! Ls command - list a folder's indexed contents without touching the disk.
+
+use crate::app::App;
+use crate::OutputFormat;
+use glint_core::{Config, FileId, FileRecord, Index, VolumeId};
+use glint_frontend::format::{format_date_time, format_number, format_size};
+
+/// Deepest folder chain followed, in case of a parent loop.
+const MAX_DEPTH: usize = 256;
+
+/// A listed record and how deep below the listed folder it is (1 for its
+/// own contents).
+struct Entry {
+    record: FileRecord,
+    depth: usize,
+    /// No later entry in the listing shares its parent
+    last: bool,
+}
+
+/// Run the ls command.
+///
+/// Lists the contents of `path` as the index has them, `depth` levels
+/// down, folders first. A volume's root (`D:\`) lists the whole volume;
+/// a file lists just itself.
+pub fn run(
+    config: Config,
+    path: String,
+    depth: usize,
+    tree: bool,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    let app = App::new(config)?;
+    let path = super::absolute(&path)?;
+
+    let list = |volume_id: &VolumeId, folder_id: FileId| {
+        let mut entries = Vec::new();
+        walk(&app.index, volume_id, folder_id, 1, depth, &mut entries);
+        entries
+    };
+    let entries = match app.index.find_by_path(&path) {
+        Some(record) if record.is_dir => list(&record.volume_id, record.id),
+        Some(record) => vec![Entry {
+            record,
+            depth: 1,
+            last: true,
+        }],
+        // Volume roots have no record of their own
+        None => match app.index.find_volume(&path) {
+            Some(state) => list(&state.info.id, FileId::ROOT),
+            None => anyhow::bail!(
+                "Not in the index: {} (run 'glint index' to update it)",
+                path
+            ),
+        },
+    };
+    let display_path = |path: &str| app.config.ui.path_format.apply(path);
+
+    match output {
+        OutputFormat::Text => {
+            if tree {
+                print_tree(&display_path(&path), &entries);
+            } else {
+                print_list(&path, &entries, &display_path);
+            }
+            let (dirs, files): (Vec<_>, Vec<_>) = entries.iter().partition(|e| e.record.is_dir);
+            let size: u64 = files.iter().filter_map(|e| e.record.size).sum();
+            eprintln!();
+            eprintln!(
+                "{} folders, {} files ({})",
+                format_number(dirs.len()),
+                format_number(files.len()),
+                format_size(size)
+            );
+        }
+        OutputFormat::Json => {
+            let json: Vec<serde_json::Value> = entries
+                .iter()
+                .map(|entry| {
+                    let r = &entry.record;
+                    serde_json::json!({
+                        "name": r.name,
+                        "path": display_path(&r.path),
+                        "is_dir": r.is_dir,
+                        "size": r.size,
+                        "modified": r.modified.map(|t| t.to_rfc3339()),
+                        "depth": entry.depth,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Add the children of a folder to `entries`, each followed by its own
+/// contents down to `max_depth`.
+fn walk(
+    index: &Index,
+    volume_id: &VolumeId,
+    folder_id: FileId,
+    depth: usize,
+    max_depth: usize,
+    entries: &mut Vec<Entry>,
+) {
+    if depth > max_depth.min(MAX_DEPTH) {
+        return;
+    }
+    let mut children = index.get_children(volume_id, folder_id);
+    children.sort_by(|a, b| {
+        b.is_dir
+            .cmp(&a.is_dir)
+            .then_with(|| a.name_lower.cmp(&b.name_lower))
+    });
+    let count = children.len();
+    for (i, record) in children.into_iter().enumerate() {
+        let (is_dir, id) = (record.is_dir, record.id);
+        entries.push(Entry {
+            record,
+            depth,
+            last: i + 1 == count,
+        });
+        if is_dir {
+            walk(index, volume_id, id, depth + 1, max_depth, entries);
+        }
+    }
+}
+
+/// One line per entry: modified, size and the path below the listed folder.
+fn print_list(folder: &str, entries: &[Entry], display_path: &dyn Fn(&str) -> String) {
+    let prefix = format!("{}\\", folder.trim_end_matches('\\'));
+    for entry in entries {
+        let r = &entry.record;
+        let modified = r.modified.map(format_date_time).unwrap_or_default();
+        let size = match (r.is_dir, r.size) {
+            (true, _) => "<DIR>".to_string(),
+            (false, Some(size)) => format_size(size),
+            (false, None) => String::new(),
+        };
+        let path = display_path(&r.path);
+        let name = match r.path.get(..prefix.len()) {
+            Some(start) if start.eq_ignore_ascii_case(&prefix) => &r.path[prefix.len()..],
+            _ => path.as_str(),
+        };
+        let slash = if r.is_dir { "\\" } else { "" };
+        println!("{:<19}  {:>10}  {}{}", modified, size, name, slash);
+    }
+}
+
+/// The folder, then its entries drawn as a tree.
+fn print_tree(folder: &str, entries: &[Entry]) {
+    println!("{}", folder);
+    // Whether each level above the current entry has more entries to come
+    let mut open: Vec<bool> = Vec::new();
+    for entry in entries {
+        open.truncate(entry.depth - 1);
+        let mut line: String = open
+            .iter()
+            .map(|&more| if more { "│   " } else { "    " })
+            .collect();
+        let branch = if entry.last {
+            "└── "
+        } else {
+            "├── "
+        };
+        line.push_str(branch);
+        let r = &entry.record;
+        line.push_str(&r.name);
+        if r.is_dir {
+            line.push('\\');
+        } else if let Some(size) = r.size {
+            line.push_str(&format!("  {}", format_size(size)));
+        }
+        if let Some(modified) = r.modified {
+            line.push_str(&format!("  {}", format_date_time(modified)));
+        }
+        println!("{}", line);
+        open.push(!entry.last);
+    }
+}
@@ -1,7 +1,52 @@
 //! CLI command implementations.
 
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+pub mod attach;
+pub mod batch;
+pub mod cleanup;
 pub mod clear;
+pub mod compact;
+pub mod config;
+pub mod doctor;
+pub mod exclude;
+pub mod explorer;
+pub mod get;
+pub mod help;
 pub mod index;
+pub mod ls;
+pub mod pin;
 pub mod query;
+pub mod rename;
+pub mod report;
+pub mod schedule;
+#[cfg(feature = "soak")]
+pub mod soak;
+pub mod stats;
 pub mod status;
+pub mod top;
+pub mod volume;
 pub mod watch;
+
+/// Resolve a path against the current directory, dropping `.` components.
+pub fn absolute(path: &str) -> anyhow::Result<String> {
+    let path = Path::new(path);
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+    let normalized: PathBuf = absolute.components().collect();
+    Ok(normalized.to_string_lossy().into_owned())
+}
+
+/// Ask a yes/no question on the terminal; anything but `y` is a no.
+pub fn confirm(prompt: &str) -> anyhow::Result<bool> {
+    print!("{} [y/N] ", prompt);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().eq_ignore_ascii_case("y"))
+}
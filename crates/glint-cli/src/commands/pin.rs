@@ -0,0 +1,81 @@
+//! Pin command - pin files and folders so they appear first in searches.
+
+use crate::app::App;
+use glint_core::pins::PinStore;
+use glint_core::Config;
+
+/// Run the pin command.
+///
+/// Pins (or with `remove`, unpins) `path`, or lists pins when no path is given.
+pub fn run(config: Config, path: Option<String>, remove: bool) -> anyhow::Result<()> {
+    let app = App::new(config)?;
+    let pins_path = PinStore::path_in(&app.config.index_dir()?);
+    let mut pins = PinStore::load(&pins_path)?;
+
+    // Keep stored paths current with renames seen since the last run
+    let refreshed = pins.refresh(&app.index);
+
+    let Some(path) = path else {
+        if refreshed {
+            pins.save(&pins_path)?;
+        }
+        return list(&app, &pins);
+    };
+
+    let path = super::absolute(&path)?;
+    let changed = match app.index.find_by_path(&path) {
+        Some(record) if remove => pins.unpin(&record.volume_id, record.id),
+        Some(record) => pins.pin(&record),
+        None if remove => {
+            // Allow removing pins whose file is no longer indexed
+            let stale: Vec<_> = pins
+                .pins()
+                .iter()
+                .filter(|p| p.path.eq_ignore_ascii_case(&path))
+                .map(|p| (p.volume_id.clone(), p.file_id))
+                .collect();
+            stale
+                .iter()
+                .fold(false, |any, (vol, id)| pins.unpin(vol, *id) || any)
+        }
+        None => anyhow::bail!(
+            "Not in the index: {} (run 'glint index' to update it)",
+            path
+        ),
+    };
+
+    if changed || refreshed {
+        pins.save(&pins_path)?;
+    }
+
+    match (remove, changed) {
+        (false, true) => println!("Pinned {}", path),
+        (false, false) => println!("Already pinned: {}", path),
+        (true, true) => println!("Unpinned {}", path),
+        (true, false) => println!("Not pinned: {}", path),
+    }
+    Ok(())
+}
+
+fn list(app: &App, pins: &PinStore) -> anyhow::Result<()> {
+    if pins.is_empty() {
+        println!("Nothing pinned. Use 'glint pin <path>' to pin a file or folder.");
+        return Ok(());
+    }
+
+    let format = app.config.ui.path_format;
+    for pin in pins.pins() {
+        let type_indicator = if pin.is_dir { "📁" } else { "📄" };
+        let missing = app.index.get(&pin.volume_id, pin.file_id).is_none();
+        if missing {
+            println!(
+                "★ {} {} (not in index)",
+                type_indicator,
+                format.apply(&pin.path)
+            );
+        } else {
+            println!("★ {} {}", type_indicator, format.apply(&pin.path));
+        }
+    }
+    Ok(())
+}
@@ -1,80 +1,347 @@
 //! Query command - search for files.
 
 use crate::app::App;
+use crate::schema;
+use crate::wsl::{self, DriveMounts};
 use crate::OutputFormat;
-use glint_core::{search::parse_query, Config, SearchFilter};
-use std::time::Instant;
+use glint_core::federation;
+use glint_core::ipc::{IpcClient, QueryRequest};
+use glint_core::pins::PinStore;
+use glint_core::search::parse_query;
+use glint_core::{Config, PathFormat, ResultTotals, SearchResult};
+use glint_frontend::format::format_totals;
+use std::cmp::Ordering;
+use std::time::{Duration, Instant};
+
+/// A column of `glint query --columns` output, also used to sort results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Name,
+    Path,
+    Size,
+    Modified,
+    /// Volume ID and file ID, as `fileid:` takes them
+    FileId,
+    /// Capture group of an `r/regex/` pattern, numbered from 1
+    Capture(usize),
+}
+
+impl Column {
+    /// The capture group number, for capture columns.
+    fn capture(&self) -> Option<usize> {
+        match self {
+            Column::Capture(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    fn header(&self) -> String {
+        match self {
+            Column::Name => "Name".to_string(),
+            Column::Path => "Path".to_string(),
+            Column::Size => "Size".to_string(),
+            Column::Modified => "Modified".to_string(),
+            Column::FileId => "File ID".to_string(),
+            Column::Capture(n) => format!("cap{}", n),
+        }
+    }
+
+    fn cell(&self, result: &SearchResult, display_path: &dyn Fn(&str) -> String) -> String {
+        let record = &result.record;
+        match self {
+            Column::Name => record.name.clone(),
+            Column::Path => display_path(&record.path),
+            Column::Size => record.size.map(|s| s.to_string()).unwrap_or_default(),
+            Column::Modified => record
+                .modified
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default(),
+            Column::FileId => format!("{}:{}", record.volume_id, record.id),
+            Column::Capture(n) => result.captures.get(n - 1).cloned().unwrap_or_default(),
+        }
+    }
+
+    /// Order two results by this column; captures that are both numbers
+    /// compare as numbers, so `9` comes before `10`.
+    fn compare(&self, a: &SearchResult, b: &SearchResult) -> Ordering {
+        let (x, y) = (&a.record, &b.record);
+        match self {
+            Column::Name => x.name_lower.cmp(&y.name_lower),
+            Column::Path => x.path_lower().cmp(&y.path_lower()),
+            Column::Size => x.size.cmp(&y.size),
+            Column::Modified => x.modified.cmp(&y.modified),
+            Column::FileId => {
+                (x.volume_id.as_str(), x.id.as_u64()).cmp(&(y.volume_id.as_str(), y.id.as_u64()))
+            }
+            Column::Capture(n) => {
+                let empty = String::new();
+                let x = a.captures.get(n - 1).unwrap_or(&empty);
+                let y = b.captures.get(n - 1).unwrap_or(&empty);
+                match (x.parse::<f64>(), y.parse::<f64>()) {
+                    (Ok(x), Ok(y)) => x.total_cmp(&y),
+                    _ => x.to_lowercase().cmp(&y.to_lowercase()),
+                }
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        match s.as_str() {
+            "name" => Ok(Column::Name),
+            "path" => Ok(Column::Path),
+            "size" => Ok(Column::Size),
+            "modified" => Ok(Column::Modified),
+            "fileid" => Ok(Column::FileId),
+            _ => match s.strip_prefix("cap").map(str::parse::<usize>) {
+                Some(Ok(n)) if n > 0 => Ok(Column::Capture(n)),
+                _ => Err(format!(
+                    "Unknown column: {} (expected name, path, size, modified, fileid or cap1, cap2, ...)",
+                    s
+                )),
+            },
+        }
+    }
+}
 
 /// Run the query command.
+///
+/// Searches the local index, or a running `glint watch` service when
+/// `remote` is set or when running inside WSL. Scopes (from `scope` or
+/// `scope:` tokens) and `within` folders are resolved here, so a remote
+/// service searches the same folders. A local search also covers attached
+/// indexes and the `remotes` of the `[server]` config, which are asked at
+/// the same time; a server that doesn't answer in time is skipped with a
+/// warning.
+///
+/// Results are ranked by score, then volume, then file ID, and `offset`
+/// skips that many of them, so consecutive pages of an unchanged index
+/// neither repeat nor skip a result. Pinned items are moved to the top of
+/// the first page only.
+///
+/// Results matching the `hide_patterns` of the `[ui]` config are left out
+/// unless `show_hidden_patterns` is set.
+///
+/// With `columns`, text output is a table of those columns instead of one
+/// path per line. `sort` orders the results shown, not the whole index.
+#[allow(clippy::too_many_arguments)]
 pub fn run(
     config: Config,
     pattern: &str,
     limit: usize,
+    offset: usize,
     files_only: bool,
     dirs_only: bool,
     extensions: Vec<String>,
     search_path: bool,
+    scope: Option<String>,
+    within: Vec<String>,
+    volume: Option<String>,
+    mut exclude: Vec<String>,
+    show_hidden_patterns: bool,
+    columns: Vec<Column>,
+    sort: Option<Column>,
+    reverse: bool,
     output: OutputFormat,
+    path_format: Option<PathFormat>,
+    remote: bool,
 ) -> anyhow::Result<()> {
-    let app = App::new(config)?;
+    let in_wsl = wsl::is_wsl();
+    let mounts = in_wsl.then(DriveMounts::detect);
 
-    if app.index.is_empty() {
-        eprintln!("Index is empty. Run 'glint index' first.");
-        return Ok(());
+    let (query, mut folders) = config.scopes.split_query(pattern)?;
+    if let Some(name) = &scope {
+        folders.extend_from_slice(config.scopes.resolve(name)?);
+    }
+    if let Some(mounts) = &mounts {
+        for folder in &mut folders {
+            if let Some(windows) = mounts.to_windows(folder) {
+                *folder = windows;
+            }
+        }
+    }
+    let within = within
+        .iter()
+        .map(|dir| {
+            let dir = super::absolute(dir)?;
+            match &mounts {
+                Some(mounts) => mounts
+                    .to_windows(&dir)
+                    .ok_or_else(|| anyhow::anyhow!("{} is not on a Windows drive", dir)),
+                None => Ok(dir),
+            }
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if !show_hidden_patterns {
+        exclude.extend_from_slice(&config.ui.hide_patterns);
     }
 
-    // Parse and build query
-    let mut query = parse_query(pattern)?;
+    let request = QueryRequest {
+        query: match &mounts {
+            Some(mounts) => mounts.translate_query(&query),
+            None => query,
+        },
+        limit,
+        offset,
+        files_only,
+        dirs_only,
+        extensions,
+        search_path,
+        scope: folders,
+        within,
+        volume,
+        exclude,
+    };
 
-    if files_only {
-        query = query.with_filter(SearchFilter::FilesOnly);
-    } else if dirs_only {
-        query = query.with_filter(SearchFilter::DirsOnly);
-    }
+    // Inside WSL, show paths as they are mounted unless a format was requested
+    let display_path = |path: &str| match (&mounts, path_format) {
+        (_, Some(format)) => format.apply(path),
+        (Some(mounts), None) => mounts.to_wsl(path),
+        (None, None) => config.ui.path_format.apply(path),
+    };
 
-    if !extensions.is_empty() {
-        query = query.with_filter(SearchFilter::Extensions(extensions));
-    }
+    // Pins live next to the local index; remote and attached results are
+    // shown unmarked
+    let mut pins = PinStore::new();
+    // Set when nothing matched and the results allow one typo
+    let mut fuzzy = false;
+    // Matches beyond `limit` are only counted by the local index
+    let mut total = None;
+    let mut totals = None;
 
-    if search_path {
-        query = query.search_in_path(true);
+    // Capture groups must exist before anything is searched
+    let groups = parse_query(&request.query)?.capture_groups();
+    let needed = columns
+        .iter()
+        .chain(&sort)
+        .filter_map(Column::capture)
+        .max();
+    if let Some(n) = needed.filter(|&n| n > groups) {
+        anyhow::bail!(
+            "cap{} needs an r/regex/ pattern with at least {} capture groups",
+            n,
+            n
+        );
     }
 
-    let start = Instant::now();
-    let results = app.index.search_limited(&query, limit);
-    let elapsed = start.elapsed();
+    let (mut results, elapsed_ms) = if remote || in_wsl {
+        let mut client = connect_remote(&config, in_wsl)?;
+        client.query(request)?
+    } else {
+        let app = App::new(config.clone())?;
+        let attached = app.attached()?;
+
+        if app.index.is_empty() && attached.is_empty() {
+            eprintln!("Index is empty. Run 'glint index' first.");
+            return Ok(());
+        }
+
+        let query = request.to_search_query(&app.index)?;
+        pins = PinStore::load(&PinStore::path_in(&app.config.index_dir()?))?;
+
+        let start = Instant::now();
+        // Other machines' servers are asked while the local index is searched
+        let (mut page, remote) = federation::fan_out(
+            &app.config.server.remotes,
+            &request,
+            app.config.server.remote_timeout(),
+            || attached.search_ranked(&app.index, &query, offset, limit),
+        );
+        for (name, e) in federation::merge_remote(&mut page, remote, limit) {
+            eprintln!("⚠ {} not searched: {}", name, e);
+        }
+        total = Some(page.total);
+        totals = Some(page.totals);
+        let mut results = page.results;
+        if offset == 0 {
+            pins.promote(&app.index, &query, &mut results, limit);
+        }
+        if results.is_empty() && offset == 0 && app.config.general.typo_tolerance {
+            results = attached.search_fuzzy(&app.index, &query, limit);
+            fuzzy = !results.is_empty();
+            totals = None;
+        }
+        if app.config.general.dedup_links {
+            results = app.index.dedup_links(results);
+        }
+        (results, start.elapsed().as_secs_f64() * 1000.0)
+    };
+
+    // Only the results shown are sorted
+    if let Some(column) = sort {
+        results.sort_by(|a, b| column.compare(a, b));
+        if reverse {
+            results.reverse();
+        }
+    }
 
     match output {
         OutputFormat::Text => {
-            for result in &results {
+            if fuzzy {
+                eprintln!("No exact matches for '{}'. Did you mean:", pattern);
+            }
+            if !columns.is_empty() {
+                print_columns(&results, &columns, &display_path);
+            }
+            for result in results.iter().filter(|_| columns.is_empty()) {
                 let record = &result.record;
-                let type_indicator = if record.is_dir { "📁" } else { "📄" };
+                let type_indicator = match (record.is_dir, record.cloud) {
+                    (true, _) => "📁",
+                    (false, true) => "☁",
+                    (false, false) => "📄",
+                };
+                let pinned = result.source.is_none() && pins.is_pinned(record);
+                let pin_indicator = if pinned { "★ " } else { "" };
+                let source = match &result.source {
+                    Some(name) => format!("[{}] ", name),
+                    None => String::new(),
+                };
+                let path = match result.links {
+                    n if n > 1 => format!("{} [{} links]", display_path(&record.path), n),
+                    _ => display_path(&record.path),
+                };
 
                 if let Some(size) = record.size {
-                    println!("{} {} ({} bytes)", type_indicator, record.path, size);
+                    println!(
+                        "{}{}{} {} ({} bytes)",
+                        pin_indicator, source, type_indicator, path, size
+                    );
                 } else {
-                    println!("{} {}", type_indicator, record.path);
+                    println!("{}{}{} {}", pin_indicator, source, type_indicator, path);
                 }
             }
 
             eprintln!();
-            eprintln!(
-                "Found {} results in {:.3}ms",
-                results.len(),
-                elapsed.as_secs_f64() * 1000.0
-            );
+            match total {
+                Some(total) if offset > 0 && !results.is_empty() => eprintln!(
+                    "Showing results {}-{} of {} in {:.3}ms",
+                    offset + 1,
+                    offset + results.len(),
+                    total,
+                    elapsed_ms
+                ),
+                Some(total) if total > results.len() => eprintln!(
+                    "Showing {} of {} results in {:.3}ms (use --limit or --offset to see more)",
+                    results.len(),
+                    total,
+                    elapsed_ms
+                ),
+                _ => eprintln!("Found {} results in {:.3}ms", results.len(), elapsed_ms),
+            }
+            let totals =
+                totals.unwrap_or_else(|| ResultTotals::of(results.iter().map(|r| &r.record)));
+            eprintln!("{}", format_totals(&totals));
         }
         OutputFormat::Json => {
             let json_results: Vec<serde_json::Value> = results
                 .iter()
-                .map(|r| {
-                    serde_json::json!({
-                        "name": r.record.name,
-                        "path": r.record.path,
-                        "is_dir": r.record.is_dir,
-                        "size": r.record.size,
-                        "modified": r.record.modified.map(|t| t.to_rfc3339()),
-                    })
+                .map(|result| {
+                    let pinned = result.source.is_none() && pins.is_pinned(&result.record);
+                    result_json(result, pinned, fuzzy, groups > 0, &display_path)
                 })
                 .collect();
 
@@ -84,3 +351,175 @@ pub fn run(
 
     Ok(())
 }
+
+/// One result as `--output json` shows it; see [`crate::schema`] before
+/// changing a field.
+pub(crate) fn result_json(
+    result: &SearchResult,
+    pinned: bool,
+    fuzzy: bool,
+    captures: bool,
+    display_path: &dyn Fn(&str) -> String,
+) -> serde_json::Value {
+    let r = &result.record;
+    let mut value = serde_json::json!({
+        "name": r.name,
+        "path": display_path(&r.path),
+        "is_dir": r.is_dir,
+        "size": r.size,
+        "cloud": r.cloud,
+        "modified": r.modified.map(|t| t.to_rfc3339()),
+        "pinned": pinned,
+        "fuzzy": fuzzy,
+        "links": result.links,
+        "source": result.source.as_deref(),
+        "volume_id": r.volume_id.as_str(),
+        "file_id": r.id.as_u64(),
+    });
+    if captures {
+        value["captures"] = serde_json::json!(result.captures);
+    }
+    if let Some(deleted_at) = result.deleted_at {
+        value["deleted_at"] = serde_json::json!(deleted_at.to_rfc3339());
+    }
+    value
+}
+
+/// Print the schema of the JSON output of query, status and stats.
+pub fn print_schema() -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(&schema::schema())?);
+    Ok(())
+}
+
+/// Print results as a table of `columns`, with a header row.
+fn print_columns(
+    results: &[SearchResult],
+    columns: &[Column],
+    display_path: &dyn Fn(&str) -> String,
+) {
+    let headers: Vec<String> = columns.iter().map(Column::header).collect();
+    let rows: Vec<Vec<String>> = results
+        .iter()
+        .map(|r| columns.iter().map(|c| c.cell(r, display_path)).collect())
+        .collect();
+    let widths: Vec<usize> = (0..columns.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain([headers[i].chars().count()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    for row in std::iter::once(&headers).chain(&rows) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    }
+}
+
+/// Connect to the IPC server of a running `glint watch` service.
+///
+/// Under WSL2's default NAT networking the Windows host is not reachable on
+/// localhost, so when no explicit address is configured the host address
+/// from `/etc/resolv.conf` is tried as well. The `[server] token` is
+/// presented when one is set.
+fn connect_remote(config: &Config, in_wsl: bool) -> anyhow::Result<IpcClient> {
+    let mut client = reach_remote(config, in_wsl)?;
+    if let Some(token) = &config.server.token {
+        client.authenticate(token).map_err(|e| {
+            anyhow::anyhow!("The Glint service did not accept [server] token ({})", e)
+        })?;
+    }
+    Ok(client)
+}
+
+fn reach_remote(config: &Config, in_wsl: bool) -> anyhow::Result<IpcClient> {
+    let timeout = Duration::from_secs(2);
+    let addr = config.server.connect_addr();
+
+    let err = match config.server.connect(addr, timeout) {
+        Ok(client) => return Ok(client),
+        Err(e) => e,
+    };
+
+    if in_wsl && config.server.connect.is_none() {
+        if let (Some(host), Some((_, port))) = (wsl::windows_host_addr(), addr.rsplit_once(':')) {
+            let host_addr = format!("{}:{}", host, port);
+            if let Ok(client) = config.server.connect(&host_addr, timeout) {
+                return Ok(client);
+            }
+        }
+    }
+
+    anyhow::bail!(
+        "Cannot reach the Glint service at {} ({}). Start 'glint watch' on Windows \
+         or set [server] connect in the config.",
+        addr,
+        err
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glint_core::{FileId, FileRecord, VolumeId};
+
+    fn result(captures: &[&str]) -> SearchResult {
+        let record = FileRecord::new(
+            FileId::new(1),
+            None,
+            VolumeId::new("C"),
+            "a.txt".to_string(),
+            "C:\\a.txt".to_string(),
+            false,
+        );
+        let mut result = SearchResult::new(record, 0);
+        result.captures = captures.iter().map(|c| c.to_string()).collect();
+        result
+    }
+
+    #[test]
+    fn test_column() {
+        assert_eq!("cap2".parse::<Column>(), Ok(Column::Capture(2)));
+        assert_eq!("Size".parse::<Column>(), Ok(Column::Size));
+        assert_eq!(
+            Column::FileId.cell(&result(&[]), &|p: &str| p.to_string()),
+            "C:1"
+        );
+        assert!("cap0".parse::<Column>().is_err());
+        assert!("owner".parse::<Column>().is_err());
+
+        // Numbers compare as numbers, other text alphabetically
+        let cap1 = Column::Capture(1);
+        assert_eq!(
+            cap1.compare(&result(&["9"]), &result(&["10"])),
+            Ordering::Less
+        );
+        assert_eq!(
+            cap1.compare(&result(&["b"]), &result(&["A"])),
+            Ordering::Greater
+        );
+        assert_eq!(cap1.compare(&result(&[]), &result(&["a"])), Ordering::Less);
+    }
+
+    #[test]
+    fn test_result_json_matches_schema() {
+        let mut full = result(&["a"]);
+        full.deleted_at = Some(chrono::Utc::now());
+        let value = result_json(&full, false, false, true, &|p: &str| p.to_string());
+        let keys = schema::properties(&serde_json::json!({ "properties": value }));
+        assert_eq!(
+            keys,
+            schema::properties(&schema::schema()["$defs"]["query"]["items"])
+        );
+
+        // Optional fields are left out, not null
+        let value = result_json(&result(&[]), false, false, false, &|p: &str| p.to_string());
+        assert!(value.get("captures").is_none() && value.get("deleted_at").is_none());
+    }
+}
@@ -0,0 +1,146 @@
+//! Rename command - bulk rename search results using a template.
+
+use crate::app::App;
+use glint_core::ipc::QueryRequest;
+use glint_core::rename::{RenameJournal, RenameOptions, RenamePlan, RenameStatus, RenameTemplate};
+use glint_core::Config;
+
+/// Run the rename command.
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+    config: Config,
+    query: Option<String>,
+    template: Option<String>,
+    pattern: Option<String>,
+    start: u64,
+    limit: usize,
+    dry_run: bool,
+    skip_confirm: bool,
+    undo: bool,
+) -> anyhow::Result<()> {
+    let app = App::new(config)?;
+    app.index.ensure_writable("rename files")?;
+    let journal_path = RenameJournal::path_in(&app.config.index_dir()?);
+
+    if undo {
+        return undo_last(&app, &journal_path, skip_confirm);
+    }
+
+    let (Some(query), Some(template)) = (query, template) else {
+        anyhow::bail!("Both --query and --to are required (or use --undo)");
+    };
+
+    if app.index.is_empty() {
+        eprintln!("Index is empty. Run 'glint index' first.");
+        return Ok(());
+    }
+
+    let mut options = RenameOptions::new(RenameTemplate::parse(&template)?).with_start(start);
+    if let Some(pattern) = pattern {
+        options = options.with_pattern(&pattern)?;
+    }
+
+    let search = QueryRequest::new(query, limit).to_search_query(&app.index)?;
+    let records: Vec<_> = app
+        .index
+        .search_limited(&search, limit)
+        .into_iter()
+        .map(|r| r.record)
+        .collect();
+
+    if records.is_empty() {
+        println!("No files match the query.");
+        return Ok(());
+    }
+
+    let plan = RenamePlan::build(&records, &options)?;
+    print_plan(&plan);
+
+    let ready = plan.ready_count();
+    if dry_run || ready == 0 {
+        return Ok(());
+    }
+
+    if plan.conflict_count() > 0 {
+        println!(
+            "{} entries have conflicts and will be left unchanged.",
+            plan.conflict_count()
+        );
+    }
+
+    if !skip_confirm && !super::confirm(&format!("Rename {} files?", ready))? {
+        println!("Cancelled.");
+        return Ok(());
+    }
+
+    let journal = plan.execute();
+    apply(&app, &journal)?;
+    journal.save(&journal_path)?;
+
+    println!("Renamed {} files.", journal.entries.len());
+    println!("Run 'glint rename --undo' to revert.");
+    Ok(())
+}
+
+fn undo_last(app: &App, journal_path: &std::path::Path, skip_confirm: bool) -> anyhow::Result<()> {
+    let Some(journal) = RenameJournal::load(journal_path)? else {
+        println!("Nothing to undo.");
+        return Ok(());
+    };
+
+    if journal.entries.is_empty() {
+        println!("Nothing to undo.");
+        return Ok(());
+    }
+
+    for entry in &journal.entries {
+        println!("  {} → {}", entry.new_path, entry.old_name);
+    }
+
+    if !skip_confirm
+        && !super::confirm(&format!(
+            "Undo rename of {} files from {}?",
+            journal.entries.len(),
+            journal.timestamp.format("%Y-%m-%d %H:%M:%S")
+        ))?
+    {
+        println!("Cancelled.");
+        return Ok(());
+    }
+
+    let undone = journal.undo();
+    apply(app, &undone)?;
+
+    // The undo itself isn't undoable
+    std::fs::remove_file(journal_path)?;
+
+    println!("Restored {} files.", undone.entries.len());
+    Ok(())
+}
+
+/// Report failures and bring the index up to date with the renames.
+fn apply(app: &App, journal: &RenameJournal) -> anyhow::Result<()> {
+    for (path, error) in &journal.failures {
+        eprintln!("⚠ {}: {}", path, error);
+    }
+
+    journal.apply_to_index(&app.index);
+    app.save_index()?;
+    Ok(())
+}
+
+fn print_plan(plan: &RenamePlan) {
+    for entry in &plan.entries {
+        match &entry.status {
+            RenameStatus::Ready => {
+                println!("  {} → {}", entry.record.path, entry.new_name)
+            }
+            RenameStatus::Unchanged => println!("  {} (unchanged)", entry.record.path),
+            RenameStatus::Skipped => println!("  {} (no match, skipped)", entry.record.path),
+            RenameStatus::Conflict(reason) => {
+                println!("✗ {} → {} ({})", entry.record.path, entry.new_name, reason)
+            }
+        }
+    }
+    println!();
+}
@@ -0,0 +1,64 @@
+//! Report command - run scheduled report jobs on demand.
+
+use crate::app::App;
+use chrono::Local;
+use glint_core::Config;
+
+/// Run the report command.
+///
+/// Runs the named job (or all jobs) now, or with `list`, shows the
+/// configured jobs and when they next run.
+pub fn run(config: Config, name: Option<String>, list: bool) -> anyhow::Result<()> {
+    let jobs = &config.reports.jobs;
+    if jobs.is_empty() {
+        println!("No report jobs configured. Add [[reports.jobs]] entries to the config file.");
+        return Ok(());
+    }
+
+    if list {
+        let now = Local::now();
+        for job in jobs {
+            let next = match job.parsed_schedule() {
+                Ok(schedule) => match schedule.next_after(&now) {
+                    Some(next) => next.format("%Y-%m-%d %H:%M").to_string(),
+                    None => "never".to_string(),
+                },
+                Err(e) => format!("invalid schedule: {}", e),
+            };
+            println!("{:<24} {:<16} next: {}", job.name, job.schedule, next);
+        }
+        return Ok(());
+    }
+
+    let selected: Vec<_> = jobs
+        .iter()
+        .filter(|job| name.as_deref().map_or(true, |n| job.name == n))
+        .cloned()
+        .collect();
+    if selected.is_empty() {
+        anyhow::bail!(
+            "No report job named '{}' (see 'glint report --list')",
+            name.unwrap_or_default()
+        );
+    }
+
+    let output_dir = config.reports_dir()?;
+    let app = App::new(config)?;
+    if app.index.is_empty() {
+        eprintln!("Index is empty. Run 'glint index' first.");
+        return Ok(());
+    }
+
+    let manifest = app.store.load_manifest();
+    for job in selected {
+        let report = job.run(&app.index)?.with_manifest(manifest.clone());
+        let path = report.write_to(&output_dir)?;
+        println!(
+            "✓ {} ({} files) → {}",
+            job.name,
+            report.files.len(),
+            path.display()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,41 @@
+//! Schedule command - manage the Task Scheduler job that runs `watch --once`.
+
+use crate::ScheduleAction;
+use glint_backend_ntfs::scheduled_task::{self, TaskStatus, TaskTrigger, TASK_NAME};
+
+/// Run the schedule command.
+pub fn run(action: ScheduleAction) -> anyhow::Result<()> {
+    match action {
+        ScheduleAction::Status => {
+            let status = scheduled_task::status();
+            println!("{}: {}", TASK_NAME, status);
+            if status == TaskStatus::NotInstalled {
+                println!("Install it with 'glint schedule install'");
+            }
+        }
+        ScheduleAction::Install { every, at_logon } => {
+            let trigger = if at_logon {
+                TaskTrigger::AtLogon
+            } else {
+                TaskTrigger::Every(every)
+            };
+            let exe = std::env::current_exe()?;
+            scheduled_task::install(&exe, trigger)?;
+            println!(
+                "✓ Installed '{}', running 'glint watch --once' {}",
+                TASK_NAME, trigger
+            );
+            if !glint_backend_ntfs::NtfsBackend::has_elevated_privileges() {
+                println!(
+                    "⚠ Installed without administrator rights; run this from an elevated prompt \
+                     so the job can read the change journal"
+                );
+            }
+        }
+        ScheduleAction::Uninstall => {
+            scheduled_task::uninstall()?;
+            println!("✓ Removed '{}'", TASK_NAME);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,573 @@
+//! Soak command - keep a synthetic index under heavy change for a while and
+//! measure how the watch pipeline holds up.
+//!
+//! A mock volume is scanned into a fresh index, then a generator feeds it
+//! creates, renames, deletes and now and then a folder rename at a steady
+//! rate, through the same change channel the watchers use. The changes are
+//! applied and the index saved on a timer by the service's own tasks
+//! ([`tasks::apply_changes`] and [`tasks::every`]), while search threads
+//! query the index the whole time. Nothing touches the real index; saves
+//! go to a temporary folder that is removed after.
+//!
+//! Only built with the `soak` feature, which brings in the mock backend.
+
+use glint_core::backend::{ChangeHandler, ChannelChangeHandler};
+use glint_core::mock::{MockBackend, MockVolume};
+use glint_core::search::parse_query;
+use glint_core::tasks::{self, CancellationToken};
+use glint_core::{
+    ChangeEvent, FileId, FileRecord, FileSystemBackend, Index, IndexStore, SearchQuery, VolumeId,
+};
+use glint_frontend::format::{format_number, format_size};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the backlog of changes is measured.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How often the generator sends the changes that are due.
+const TICK: Duration = Duration::from_millis(10);
+
+/// How often a progress line is written.
+const REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Files in each synthetic folder.
+const FILES_PER_FOLDER: usize = 200;
+
+/// One change in this many renames a whole folder, moving its files.
+const FOLDER_RENAME_EVERY: u64 = 1000;
+
+/// Searches run in turn by each search thread.
+const QUERIES: &[&str] = &["file12", "ext:log", "*.tmp", "folder7", "report"];
+
+const EXTENSIONS: &[&str] = &["txt", "log", "rs", "jpg", "docx", "tmp"];
+
+/// Run the soak command.
+///
+/// Applies `events_per_sec` changes a second for `duration` (e.g. `30s`,
+/// `10m` or `1h`) to an index of `files` files, saving every `save_every`,
+/// with `searchers` threads searching alongside, then prints the latency
+/// percentiles of each.
+pub fn run(
+    events_per_sec: u64,
+    duration: String,
+    save_every: String,
+    files: usize,
+    searchers: usize,
+) -> anyhow::Result<()> {
+    let load = Load {
+        events_per_sec,
+        duration: parse_duration(&duration)?,
+        save_every: parse_duration(&save_every)?,
+        searchers,
+    };
+    let queries = QUERIES
+        .iter()
+        .map(|q| parse_query(q))
+        .collect::<Result<Vec<SearchQuery>, _>>()?;
+
+    let volume = SyntheticVolume::new(files);
+    let index = Arc::new(scan(&volume)?);
+    eprintln!(
+        "Soaking an index of {} records with {} changes a second for {}s...",
+        format_number(index.len()),
+        format_number(events_per_sec as usize),
+        load.duration.as_secs()
+    );
+
+    let dir = std::env::temp_dir().join(format!("glint-soak-{}", std::process::id()));
+    let soaked = soak(&index, &dir, volume, &queries, &load, |progress| {
+        eprintln!(
+            "{:>5}s  {} changes applied, {} behind, {} searches",
+            progress.elapsed.as_secs(),
+            format_number(progress.applied),
+            format_number(progress.behind),
+            format_number(progress.searches as usize)
+        );
+    });
+    let _ = std::fs::remove_dir_all(&dir);
+    let summary = soaked?;
+
+    println!();
+    println!(
+        "Changes:  {} generated, {} applied ({:.0} a second)",
+        format_number(summary.generated),
+        format_number(summary.applied),
+        summary.applied as f64 / summary.elapsed.as_secs_f64()
+    );
+    println!("Backlog:  at most {} changes", format_number(summary.most_behind));
+    println!(
+        "Index:    {} records, saved as {}",
+        format_number(index.len()),
+        format_size(summary.saved_size)
+    );
+    println!();
+    println!(
+        "{:<8} {:>10} {:>10} {:>10} {:>10} {:>10}",
+        "", "count", "p50", "p95", "p99", "max"
+    );
+    for (name, latencies) in [("Search", summary.search), ("Save", summary.saves)] {
+        latencies.print(name);
+    }
+
+    Ok(())
+}
+
+/// How hard to push the index.
+struct Load {
+    events_per_sec: u64,
+    duration: Duration,
+    save_every: Duration,
+    searchers: usize,
+}
+
+/// Where a soak run has got to, reported every [`REPORT_INTERVAL`].
+struct Progress {
+    elapsed: Duration,
+    applied: usize,
+    behind: usize,
+    searches: u64,
+}
+
+/// What a soak run measured.
+struct Summary {
+    generated: usize,
+    applied: usize,
+    /// Most changes waiting to be applied at once
+    most_behind: usize,
+    elapsed: Duration,
+    saved_size: u64,
+    search: Latencies,
+    saves: Latencies,
+}
+
+/// Scan the mock volume of `volume` into a new index.
+fn scan(volume: &SyntheticVolume) -> anyhow::Result<Index> {
+    let backend = MockBackend::new().with_volume(volume.mock());
+    let info = backend
+        .list_volumes()?
+        .pop()
+        .ok_or_else(|| anyhow::anyhow!("The mock volume is missing"))?;
+    let index = Index::new();
+    let records = backend.full_scan(&info, None)?;
+    index.add_volume_records(&info, records);
+    Ok(index)
+}
+
+/// Feed `index` the changes of `volume` under `load`, saving it to `dir`
+/// and searching it with `queries` the whole time.
+///
+/// The changes go through the service's change and save tasks on a tokio
+/// runtime of their own; the run ends once every generated change has been
+/// applied, with a last save like the service makes when stopping.
+fn soak(
+    index: &Arc<Index>,
+    dir: &Path,
+    volume: SyntheticVolume,
+    queries: &[SearchQuery],
+    load: &Load,
+    report: impl Fn(Progress),
+) -> anyhow::Result<Summary> {
+    if load.save_every.is_zero() {
+        anyhow::bail!("The save interval must be longer than zero");
+    }
+    let store = Arc::new(IndexStore::new(dir));
+    let stop = Arc::new(AtomicBool::new(false));
+    let searches = Arc::new(AtomicU64::new(0));
+
+    let search_threads: Vec<_> = (0..load.searchers)
+        .map(|n| {
+            let (index, stop, searches) = (index.clone(), stop.clone(), searches.clone());
+            let mut queries = queries.to_vec();
+            let shift = n % queries.len().max(1);
+            queries.rotate_left(shift);
+            thread::spawn(move || {
+                let mut latencies = Latencies::default();
+                for query in queries.iter().cycle() {
+                    if stop.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let start = Instant::now();
+                    index.search(query);
+                    latencies.record(start.elapsed());
+                    searches.fetch_add(1, Ordering::Relaxed);
+                }
+                latencies
+            })
+        })
+        .collect();
+
+    let (handler, receiver) = ChannelChangeHandler::new();
+    let (per_sec, duration) = (load.events_per_sec, load.duration);
+    let generator = thread::spawn(move || volume.generate(&handler, per_sec, duration));
+
+    let saves = Arc::new(Mutex::new(Latencies::default()));
+    let saved_generation = Arc::new(AtomicU64::new(index.generation()));
+    let save = {
+        let (index, store) = (index.clone(), store.clone());
+        let (saves, saved_generation) = (saves.clone(), saved_generation.clone());
+        move || {
+            let generation = index.generation();
+            if generation == saved_generation.load(Ordering::Relaxed) {
+                return;
+            }
+            let start = Instant::now();
+            match store.save(&index) {
+                Ok(()) => saved_generation.store(generation, Ordering::Relaxed),
+                Err(e) => eprintln!("⚠ Cannot save the index ({})", e),
+            }
+            saves.lock().unwrap().record(start.elapsed());
+        }
+    };
+
+    let started = Instant::now();
+    let start_generation = index.generation();
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .thread_name("glint-soak")
+        .build()?;
+    let (applied, most_behind) = runtime.block_on(async {
+        let cancel = CancellationToken::new();
+        let mut changes = tokio::spawn(tasks::apply_changes(
+            index.clone(),
+            receiver.clone(),
+            cancel.clone(),
+        ));
+        let saver = tokio::spawn(tasks::every(load.save_every, cancel.clone(), save.clone()));
+
+        // The change task ends once the generator is done and its changes
+        // are all applied
+        let mut most_behind = 0;
+        let mut last_report = Instant::now();
+        let applied = loop {
+            tokio::select! {
+                applied = &mut changes => break applied.unwrap_or_default(),
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+            most_behind = most_behind.max(receiver.len());
+            if last_report.elapsed() >= REPORT_INTERVAL {
+                last_report = Instant::now();
+                report(Progress {
+                    elapsed: started.elapsed(),
+                    // Each change applied moves the generation on
+                    applied: (index.generation() - start_generation) as usize,
+                    behind: receiver.len(),
+                    searches: searches.load(Ordering::Relaxed),
+                });
+            }
+        };
+        cancel.cancel();
+        let _ = saver.await;
+        (applied, most_behind)
+    });
+    let elapsed = started.elapsed();
+    save();
+
+    stop.store(true, Ordering::Relaxed);
+    let generated = generator
+        .join()
+        .map_err(|_| anyhow::anyhow!("The change generator panicked"))?;
+    let mut search = Latencies::default();
+    for thread in search_threads {
+        let latencies = thread
+            .join()
+            .map_err(|_| anyhow::anyhow!("A search thread panicked"))?;
+        search.0.extend(latencies.0);
+    }
+    let saved_size = std::fs::metadata(store.index_path())
+        .map(|m| m.len())
+        .unwrap_or(0);
+    let saves = std::mem::take(&mut *saves.lock().unwrap());
+
+    Ok(Summary {
+        generated,
+        applied,
+        most_behind,
+        elapsed,
+        saved_size,
+        search,
+        saves,
+    })
+}
+
+/// Parse a duration such as `30s`, `10m` or `1h`; a bare number is
+/// seconds.
+fn parse_duration(input: &str) -> anyhow::Result<Duration> {
+    let s = input.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let invalid = || anyhow::anyhow!("Not a duration: {} (expected e.g. 30s, 10m or 1h)", input);
+    let number: u64 = number.parse().map_err(|_| invalid())?;
+    let seconds = match unit.trim() {
+        "" | "s" => number,
+        "m" | "min" => number * 60,
+        "h" => number * 60 * 60,
+        _ => return Err(invalid()),
+    };
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Timings of one kind of work.
+#[derive(Default)]
+struct Latencies(Vec<Duration>);
+
+impl Latencies {
+    fn record(&mut self, took: Duration) {
+        self.0.push(took);
+    }
+
+    /// Print the count and percentiles as a row of the summary table.
+    fn print(mut self, name: &str) {
+        self.0.sort_unstable();
+        let at = |p: usize| match self.0.len() {
+            0 => "-".to_string(),
+            n => format_ms(self.0[(n - 1) * p / 100]),
+        };
+        println!(
+            "{:<8} {:>10} {:>10} {:>10} {:>10} {:>10}",
+            name,
+            format_number(self.0.len()),
+            at(50),
+            at(95),
+            at(99),
+            at(100)
+        );
+    }
+}
+
+fn format_ms(took: Duration) -> String {
+    format!("{:.2} ms", took.as_secs_f64() * 1000.0)
+}
+
+/// The volume being soaked: folders of files below the root, and what the
+/// generator has made of them so far.
+struct SyntheticVolume {
+    id: VolumeId,
+    /// Records of the initial scan
+    records: Vec<FileRecord>,
+    /// Folder IDs and their current names
+    folders: Vec<(FileId, String)>,
+    /// Files the generator created and hasn't deleted: ID, folder and name
+    created: Vec<(FileId, FileId, String)>,
+    next_id: u64,
+    rng: u64,
+}
+
+impl SyntheticVolume {
+    fn new(files: usize) -> Self {
+        let id = VolumeId::new("S");
+        let folder_count = files.div_ceil(FILES_PER_FOLDER).max(1);
+        let mut volume = SyntheticVolume {
+            id,
+            records: Vec::with_capacity(files + folder_count),
+            folders: Vec::with_capacity(folder_count),
+            created: Vec::new(),
+            next_id: FileId::ROOT.as_u64() + 1,
+            // A fixed seed, so runs make the same changes and compare
+            rng: 0x9E37_79B9_7F4A_7C15,
+        };
+
+        for n in 0..folder_count {
+            let (id, name) = (volume.take_id(), format!("folder{}", n));
+            let path = format!("S:\\{}", name);
+            volume.records.push(FileRecord::new(
+                id,
+                Some(FileId::ROOT),
+                volume.id.clone(),
+                name.clone(),
+                path,
+                true,
+            ));
+            volume.folders.push((id, name));
+        }
+        for n in 0..files {
+            let (folder_id, folder) = volume.folders[n / FILES_PER_FOLDER].clone();
+            let name = format!("file{}.{}", n, EXTENSIONS[n % EXTENSIONS.len()]);
+            let path = format!("S:\\{}\\{}", folder, name);
+            let id = volume.take_id();
+            let record = FileRecord::new(id, Some(folder_id), volume.id.clone(), name, path, false)
+                .with_size((n as u64 % 1000 + 1) * 1024);
+            volume.records.push(record);
+        }
+        volume
+    }
+
+    /// The mock volume to scan.
+    fn mock(&self) -> MockVolume {
+        self.records
+            .iter()
+            .cloned()
+            .fold(MockVolume::new(self.id.as_str()), MockVolume::with_record)
+    }
+
+    fn take_id(&mut self) -> FileId {
+        self.next_id += 1;
+        FileId::new(self.next_id - 1)
+    }
+
+    /// The next number of a xorshift sequence, below `n`.
+    fn random(&mut self, n: usize) -> usize {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng % n as u64) as usize
+    }
+
+    /// Send `per_sec` changes a second to `handler` for `duration`; returns
+    /// how many were sent.
+    fn generate(
+        mut self,
+        handler: &ChannelChangeHandler,
+        per_sec: u64,
+        duration: Duration,
+    ) -> usize {
+        let started = Instant::now();
+        let mut sent = 0u64;
+        while started.elapsed() < duration {
+            let due = (started.elapsed().as_secs_f64() * per_sec as f64) as u64;
+            while sent < due {
+                sent += 1;
+                let event = self.next_change(sent as i64);
+                handler.on_change(event);
+            }
+            thread::sleep(TICK);
+        }
+        sent as usize
+    }
+
+    /// A random change: mostly files created, renamed and deleted, and
+    /// once in a while a folder renamed.
+    fn next_change(&mut self, sequence: i64) -> ChangeEvent {
+        let roll = self.random(FOLDER_RENAME_EVERY as usize);
+        if roll == 0 {
+            let n = self.random(self.folders.len());
+            let (id, old_name) = self.folders[n].clone();
+            let new_name = format!("{}-{}", old_name.split('-').next().unwrap_or(""), sequence);
+            self.folders[n].1 = new_name.clone();
+            return ChangeEvent::renamed(
+                self.id.clone(),
+                id,
+                Some(FileId::ROOT),
+                old_name,
+                new_name,
+                None,
+                true,
+                sequence,
+            );
+        }
+
+        if roll < 400 || self.created.is_empty() {
+            let n = self.random(self.folders.len());
+            let folder = self.folders[n].0;
+            let id = self.take_id();
+            let name = format!(
+                "new{}.{}",
+                id,
+                EXTENSIONS[id.as_u64() as usize % EXTENSIONS.len()]
+            );
+            self.created.push((id, folder, name.clone()));
+            ChangeEvent::created(self.id.clone(), id, Some(folder), name, false, sequence)
+        } else if roll < 700 {
+            let n = self.random(self.created.len());
+            let (id, folder, old_name) = self.created[n].clone();
+            let new_name = match old_name.strip_suffix('~') {
+                Some(name) => name.to_string(),
+                None => format!("{}~", old_name),
+            };
+            self.created[n].2 = new_name.clone();
+            ChangeEvent::renamed(
+                self.id.clone(),
+                id,
+                Some(folder),
+                old_name,
+                new_name,
+                None,
+                false,
+                sequence,
+            )
+        } else {
+            let n = self.random(self.created.len());
+            let (id, folder, name) = self.created.swap_remove(n);
+            ChangeEvent::deleted(self.id.clone(), id, Some(folder), name, false, sequence)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_duration("45").unwrap(), Duration::from_secs(45));
+        assert_eq!(parse_duration(" 10m ").unwrap(), Duration::from_secs(600));
+        assert_eq!(parse_duration("2min").unwrap(), Duration::from_secs(120));
+        assert_eq!(parse_duration("1h").unwrap(), Duration::from_secs(3600));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("5d").is_err());
+        assert!(parse_duration("-5s").is_err());
+    }
+
+    #[test]
+    fn test_changes_apply_cleanly() {
+        let mut volume = SyntheticVolume::new(1000);
+        let index = scan(&volume).unwrap();
+        let scanned = index.len();
+        assert_eq!(scanned, 1000 + 5);
+
+        for sequence in 1..=5000 {
+            index.apply_change(volume.next_change(sequence));
+        }
+        // Every file created and not deleted since is in the index, under
+        // its latest name; deleted ones are left as tombstones
+        let live = index.len() - index.tombstone_count();
+        assert_eq!(live, scanned + volume.created.len());
+        let (_, _, name) = volume.created.last().unwrap();
+        let query = SearchQuery::substring(name);
+        assert!(index.search(&query).iter().any(|r| &r.record.name == name));
+    }
+
+    #[test]
+    fn test_soak_applies_every_change() {
+        let temp = TempDir::new().unwrap();
+        let volume = SyntheticVolume::new(2000);
+        let index = Arc::new(scan(&volume).unwrap());
+        let queries = vec![parse_query("file1").unwrap()];
+        let load = Load {
+            events_per_sec: 2000,
+            duration: Duration::from_secs(1),
+            save_every: Duration::from_millis(300),
+            searchers: 1,
+        };
+
+        let summary = soak(&index, temp.path(), volume, &queries, &load, |_| {}).unwrap();
+        assert!(summary.generated > 0);
+        assert_eq!(summary.applied, summary.generated);
+        assert!(!summary.search.0.is_empty());
+        // Saved on the timer and once more at the end
+        assert!(summary.saves.0.len() >= 2);
+        assert!(summary.saved_size > 0);
+        let live = index.len() - index.tombstone_count();
+        assert_eq!(IndexStore::new(temp.path()).load().unwrap().len(), live);
+    }
+
+    #[test]
+    fn test_soak_rejects_zero_save_interval() {
+        let temp = TempDir::new().unwrap();
+        let volume = SyntheticVolume::new(10);
+        let index = Arc::new(scan(&volume).unwrap());
+        let load = Load {
+            events_per_sec: 10,
+            duration: Duration::from_secs(1),
+            save_every: Duration::ZERO,
+            searchers: 0,
+        };
+        assert!(soak(&index, temp.path(), volume, &[], &load, |_| {}).is_err());
+    }
+}
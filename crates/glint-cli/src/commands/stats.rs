@@ -0,0 +1,122 @@
+//! Stats command - file counts and sizes grouped by extension, folder, volume or age.
+
+use crate::app::App;
+use crate::OutputFormat;
+use glint_core::stats::{GroupBy, Statistics};
+use glint_core::Config;
+use glint_frontend::format::format_size;
+use tracing::warn;
+
+/// Width of the bar chart column.
+const BAR_WIDTH: usize = 20;
+
+/// Run the stats command.
+pub fn run(
+    config: Config,
+    by: GroupBy,
+    limit: usize,
+    refresh: bool,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    let app = App::new(config)?;
+
+    if app.index.is_empty() {
+        eprintln!("Index is empty. Run 'glint index' first.");
+        return Ok(());
+    }
+
+    let cache_path = Statistics::cache_path_in(&app.config.index_dir()?);
+    let index_file = app.store.index_path();
+
+    let cached = if refresh {
+        None
+    } else {
+        Statistics::load_cached(&cache_path, &index_file)
+    };
+    let stats = match cached {
+        Some(stats) => stats,
+        None => {
+            let stats = Statistics::compute(&app.index);
+            // Nothing is written next to a read-only index
+            if !app.index.is_read_only() {
+                if let Err(e) = stats.save_cached(&cache_path, &index_file) {
+                    warn!(error = %e, "Failed to cache statistics");
+                }
+            }
+            stats
+        }
+    };
+
+    let rows = stats.rows(by);
+    let shown = &rows[..rows.len().min(limit)];
+
+    match output {
+        OutputFormat::Text => {
+            let key_width = shown
+                .iter()
+                .map(|r| r.key.chars().count())
+                .max()
+                .unwrap_or(0)
+                .clamp(by.label().len(), 40);
+            let max_size = shown.iter().map(|r| r.total_size).max().unwrap_or(0);
+
+            println!(
+                "{:<key_width$} {:>10} {:>10} {:>6}",
+                by.label(),
+                "Files",
+                "Size",
+                "Size%"
+            );
+            for row in shown {
+                let percent = if stats.total_size > 0 {
+                    row.total_size as f64 * 100.0 / stats.total_size as f64
+                } else {
+                    0.0
+                };
+                let bar_len = if max_size > 0 {
+                    (row.total_size as f64 / max_size as f64 * BAR_WIDTH as f64).round() as usize
+                } else {
+                    0
+                };
+                println!(
+                    "{:<key_width$} {:>10} {:>10} {:>5.1}% {}",
+                    truncate(&row.key, key_width),
+                    row.count,
+                    format_size(row.total_size),
+                    percent,
+                    "█".repeat(bar_len)
+                );
+            }
+
+            if rows.len() > shown.len() {
+                println!("… {} more", rows.len() - shown.len());
+            }
+            eprintln!();
+            eprintln!(
+                "{} files, {} total (computed {})",
+                stats.total_files,
+                format_size(stats.total_size),
+                stats
+                    .computed_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+            );
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(shown)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Shorten long keys (deep folder names) with a trailing ellipsis.
+fn truncate(s: &str, width: usize) -> String {
+    if s.chars().count() <= width {
+        s.to_string()
+    } else {
+        let mut out: String = s.chars().take(width.saturating_sub(1)).collect();
+        out.push('…');
+        out
+    }
+}
@@ -1,14 +1,246 @@
 //! Status command - show index status and statistics.
 
 use crate::app::App;
-use glint_core::Config;
+use crate::schema::SCHEMA_VERSION;
+use crate::OutputFormat;
+use chrono::{DateTime, Local, Utc};
+use glint_core::backend::{mount_overlaps, JournalState};
+use glint_core::index::{VolumeAvailability, VolumeIndexState};
+use glint_core::ipc::{IpcRequest, IpcResponse};
+use glint_core::manifest::BuildManifest;
+use glint_core::types::IndexStats;
+use glint_core::{Config, FileSystemBackend, VolumeInfo};
+use glint_frontend::format::{format_age, format_number, format_size};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How long to wait for a running watcher to answer.
+const WATCHER_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Everything `glint status` reports; printed as is by `--output json`.
+#[derive(Serialize)]
+struct StatusReport {
+    /// See [`crate::schema`]
+    schema_version: u32,
+    #[serde(flatten)]
+    stats: IndexStats,
+    data_dir: PathBuf,
+    /// Approximate memory used to look records up by path
+    path_lookup_bytes: usize,
+    index_file: Option<IndexFile>,
+    watcher: WatcherState,
+    volumes: Vec<VolumeStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    manifest: Option<BuildManifest>,
+}
+
+/// The saved index on disk.
+#[derive(Serialize)]
+struct IndexFile {
+    path: PathBuf,
+    size: u64,
+    modified: DateTime<Utc>,
+}
+
+/// Whether `glint watch` (or the service) is keeping the index current.
+#[derive(Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum WatcherState {
+    Running,
+    NotRunning,
+    /// The query server is disabled, so a watcher cannot be detected
+    Unknown,
+}
+
+/// One indexed volume.
+#[derive(Serialize)]
+struct VolumeStatus {
+    name: String,
+    id: String,
+    label: Option<String>,
+    /// How the last build read the volume ("mft" or "recursive")
+    mode: Option<String>,
+    records: u64,
+    /// Journal position the index is current to
+    indexed_journal: Option<JournalState>,
+    /// Journal position the volume is at now
+    current_journal: Option<JournalState>,
+    last_scan: Option<DateTime<Utc>>,
+    /// When the watcher last applied a change
+    last_event_applied: Option<DateTime<Utc>>,
+    /// No update for longer than `stale_after_hours`
+    stale: bool,
+    enabled: bool,
+    needs_rescan: bool,
+    watched: bool,
+    /// The volume is not mounted now
+    offline: bool,
+    free_bytes: Option<u64>,
+    total_bytes: Option<u64>,
+    low_on_space: bool,
+}
+
+impl VolumeStatus {
+    fn new(
+        state: &VolumeIndexState,
+        app: &App,
+        manifest: Option<&BuildManifest>,
+        watcher: WatcherState,
+    ) -> Self {
+        let id = state.info.id.to_string();
+        let built = manifest.and_then(|m| {
+            m.volumes
+                .iter()
+                .find(|v| v.id == id)
+                .map(|v| (v, m.built_at))
+        });
+        let current_journal = match state.journal_state {
+            Some(_) => app.backend.get_journal_state(&state.info).ok().flatten(),
+            None => None,
+        };
+
+        VolumeStatus {
+            name: state.info.display_name().to_string(),
+            id,
+            label: state.info.label.clone(),
+            mode: built.map(|(v, _)| v.scan_mode.clone()),
+            records: state.record_count,
+            indexed_journal: state.journal_state.clone(),
+            current_journal,
+            // Indexes saved before scan times were kept fall back to the manifest
+            last_scan: state.last_full_scan.or(built.map(|(_, at)| at)),
+            last_event_applied: state.last_event_applied,
+            stale: state.enabled
+                && app
+                    .config
+                    .stale_after()
+                    .is_some_and(|max_age| state.is_stale(max_age)),
+            enabled: state.enabled,
+            needs_rescan: state.needs_rescan,
+            watched: state.enabled && watcher == WatcherState::Running,
+            offline: state.availability == VolumeAvailability::Offline,
+            free_bytes: state.info.free_bytes,
+            total_bytes: state.info.total_bytes,
+            low_on_space: state.info.is_low_on_space(),
+        }
+    }
+
+    /// The current journal position, or why there is none to compare.
+    fn journal_column(&self) -> String {
+        match (&self.indexed_journal, &self.current_journal) {
+            (Some(indexed), Some(current)) if indexed.journal_id != current.journal_id => {
+                "new journal".to_string()
+            }
+            (_, Some(current)) => current.last_usn.to_string(),
+            _ => "-".to_string(),
+        }
+    }
+
+    /// How long ago the volume was last scanned or changed.
+    fn updated_column(&self) -> String {
+        match self.last_scan.max(self.last_event_applied) {
+            Some(at) => format_age((Utc::now() - at).to_std().unwrap_or_default()),
+            None => "-".to_string(),
+        }
+    }
+
+    fn state_column(&self) -> &'static str {
+        if !self.enabled {
+            "⏸ disabled"
+        } else if self.offline {
+            "⏏ offline"
+        } else if self.needs_rescan {
+            "⚠ needs rescan"
+        } else if self.stale {
+            "⚠ stale"
+        } else if self.watched {
+            "✓ watched"
+        } else {
+            "✓"
+        }
+    }
+}
 
 /// Run the status command.
-pub fn run(config: Config) -> anyhow::Result<()> {
+///
+/// With `details`, also shows the manifest saved by the last index build.
+pub fn run(config: Config, details: bool, output: OutputFormat) -> anyhow::Result<()> {
     let app = App::new(config)?;
 
-    let stats = app.index.stats();
-    let volumes = app.index.volume_states();
+    // Labels and free space may have changed since the volumes were indexed
+    if let Ok(current) = app.backend.list_volumes() {
+        app.index.refresh_volume_info(&current);
+        app.index.update_availability(&current);
+    }
+
+    let report = build_report(&app, details)?;
+    match output {
+        OutputFormat::Text => print_report(&app, &report, details),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+
+    Ok(())
+}
+
+fn build_report(app: &App, details: bool) -> anyhow::Result<StatusReport> {
+    let index_path = app.store.index_path();
+    let index_file = std::fs::metadata(&index_path)
+        .ok()
+        .map(|metadata| IndexFile {
+            path: index_path,
+            size: metadata.len(),
+            modified: metadata
+                .modified()
+                .map(DateTime::<Utc>::from)
+                .unwrap_or_else(|_| Utc::now()),
+        });
+
+    let watcher = watcher_state(&app.config);
+    let manifest = app.store.load_manifest();
+    let volumes = app
+        .index
+        .volume_states()
+        .iter()
+        .map(|state| VolumeStatus::new(state, app, manifest.as_ref(), watcher))
+        .collect();
+
+    Ok(StatusReport {
+        schema_version: SCHEMA_VERSION,
+        stats: app.index.stats(),
+        data_dir: app.config.index_dir()?,
+        path_lookup_bytes: app.index.path_lookup_bytes(),
+        index_file,
+        watcher,
+        volumes,
+        manifest: manifest.filter(|_| details),
+    })
+}
+
+/// Ask the query server whether a watcher is running.
+fn watcher_state(config: &Config) -> WatcherState {
+    if !config.server.enabled {
+        return WatcherState::Unknown;
+    }
+    let answered = config
+        .server
+        .connect(config.server.connect_addr(), WATCHER_TIMEOUT)
+        .and_then(|mut client| {
+            if let Some(token) = &config.server.token {
+                client.authenticate(token)?;
+            }
+            client.request(&IpcRequest::Status)
+        })
+        .is_ok_and(|response| matches!(response, IpcResponse::Status { .. }));
+    if answered {
+        WatcherState::Running
+    } else {
+        WatcherState::NotRunning
+    }
+}
+
+fn print_report(app: &App, report: &StatusReport, details: bool) {
+    let stats = &report.stats;
 
     println!("Glint Index Status");
     println!("==================");
@@ -16,17 +248,30 @@ pub fn run(config: Config) -> anyhow::Result<()> {
 
     if app.index.is_empty() {
         println!("Index is empty. Run 'glint index' to build the index.");
-        return Ok(());
+        return;
     }
 
     println!("Summary:");
-    println!("  Total files:       {}", stats.total_files);
-    println!("  Total directories: {}", stats.total_dirs);
-    println!("  Total entries:     {}", stats.total_entries());
     println!(
-        "  Total size:        {} bytes ({:.2} GB)",
-        stats.total_size,
-        stats.total_size as f64 / (1024.0 * 1024.0 * 1024.0)
+        "  Total files:       {}",
+        format_number(stats.total_files as usize)
+    );
+    println!(
+        "  Total directories: {}",
+        format_number(stats.total_dirs as usize)
+    );
+    println!(
+        "  Total entries:     {}",
+        format_number(stats.total_entries() as usize)
+    );
+    println!(
+        "  Total size:        {} bytes ({})",
+        format_number(stats.total_size as usize),
+        format_size(stats.total_size)
+    );
+    println!(
+        "  Path lookup:       {}",
+        format_size(report.path_lookup_bytes as u64)
     );
     println!("  Index version:     {}", stats.version);
 
@@ -37,32 +282,191 @@ pub fn run(config: Config) -> anyhow::Result<()> {
         );
     }
 
+    if let Some(file) = &report.index_file {
+        let age = (Utc::now() - file.modified).to_std().unwrap_or_default();
+        println!(
+            "  Index file:        {} (saved {})",
+            format_size(file.size),
+            format_age(age)
+        );
+    }
+
+    let watcher = match report.watcher {
+        WatcherState::Running => format!("running ({})", app.config.server.connect_addr()),
+        WatcherState::NotRunning => "not running; start it with 'glint watch'".to_string(),
+        WatcherState::Unknown => "unknown ([server] is disabled)".to_string(),
+    };
+    println!("  Watcher:           {}", watcher);
+
     println!();
     println!("Indexed Volumes:");
+    print_volume_table(&report.volumes);
 
-    for vol in &volumes {
-        let status = if vol.needs_rescan {
-            "⚠ needs rescan"
-        } else {
-            "✓"
-        };
+    for vol in report.volumes.iter().filter(|v| v.stale) {
         println!(
-            "  {} {} ({} entries) {}",
-            vol.info.mount_point,
-            vol.info.label.as_deref().unwrap_or(""),
-            vol.record_count,
-            status
+            "⚠ {} was last updated {}; run 'glint watch' or 'glint index' to catch up",
+            vol.name,
+            vol.updated_column()
         );
+    }
+
+    for vol in report.volumes.iter().filter(|v| v.low_on_space) {
+        if let (Some(free), Some(total)) = (vol.free_bytes, vol.total_bytes) {
+            println!(
+                "⚠ {} is low on space ({} free of {})",
+                vol.name,
+                format_size(free),
+                format_size(total)
+            );
+        }
+    }
+
+    // A drive also mounted in an indexed folder is indexed twice
+    let infos: Vec<VolumeInfo> = app
+        .index
+        .volume_states()
+        .iter()
+        .map(|v| v.info.clone())
+        .collect();
+    let overlaps = mount_overlaps(&infos);
+    if !overlaps.is_empty() {
+        println!();
+        for (mounted, folder) in overlaps {
+            println!(
+                "⚠ {} is inside a mount folder of {}, so its files are indexed twice",
+                folder.display_name(),
+                mounted.display_name()
+            );
+        }
+        println!("  Remove the folder volume, or set dedup_links = true under [general]");
+    }
 
-        if let Some(ref js) = vol.journal_state {
-            println!("    Journal ID: {:016X}", js.journal_id);
-            println!("    Last USN:   {}", js.last_usn);
+    if details {
+        println!();
+        match &report.manifest {
+            Some(manifest) => print_manifest(manifest),
+            None => println!("No build manifest (the index predates manifests; rebuild with 'glint index --force')"),
         }
     }
 
     // Show data directory
     println!();
-    println!("Data directory: {}", app.config.index_dir()?.display());
+    println!("Data directory: {}", report.data_dir.display());
+}
 
-    Ok(())
+/// Print one row per volume, with columns sized to fit.
+fn print_volume_table(volumes: &[VolumeStatus]) {
+    let rows: Vec<[String; 9]> = volumes
+        .iter()
+        .map(|vol| {
+            let name = match &vol.label {
+                Some(label) if !label.is_empty() => format!("{} [{}]", vol.name, label),
+                _ => vol.name.clone(),
+            };
+            let free = match vol.free_bytes {
+                Some(free) => format_size(free),
+                None => "-".to_string(),
+            };
+            [
+                name,
+                vol.mode.clone().unwrap_or_else(|| "-".to_string()),
+                vol.records.to_string(),
+                vol.indexed_journal
+                    .as_ref()
+                    .map_or("-".to_string(), |js| js.last_usn.to_string()),
+                vol.journal_column(),
+                vol.last_scan.map_or("-".to_string(), |at| {
+                    at.with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                }),
+                vol.updated_column(),
+                free,
+                vol.state_column().to_string(),
+            ]
+        })
+        .collect();
+
+    let headers = [
+        "Volume",
+        "Mode",
+        "Entries",
+        "Indexed USN",
+        "Journal USN",
+        "Last scan",
+        "Updated",
+        "Free",
+        "State",
+    ];
+    let widths: Vec<usize> = (0..headers.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain([headers[i].len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let line = |cells: &[&str]| {
+        let mut out = String::from(" ");
+        for (i, cell) in cells.iter().enumerate() {
+            let pad = widths[i].saturating_sub(cell.chars().count());
+            // Numbers line up on the right, text on the left
+            if (2..=4).contains(&i) {
+                out.push_str(&format!(" {}{}", " ".repeat(pad), cell));
+            } else {
+                out.push_str(&format!(" {}{}", cell, " ".repeat(pad)));
+            }
+        }
+        println!("{}", out.trim_end());
+    };
+
+    line(&headers);
+    for row in &rows {
+        line(&row.iter().map(String::as_str).collect::<Vec<_>>());
+    }
+}
+
+/// Print how the index was built.
+fn print_manifest(manifest: &BuildManifest) {
+    println!("Last Build:");
+    println!(
+        "  Built:             {}",
+        manifest
+            .built_at
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S")
+    );
+    println!("  Duration:          {:.1}s", manifest.duration_secs);
+    println!("  Glint version:     {}", manifest.tool_version);
+
+    for volume in &manifest.volumes {
+        println!(
+            "  {} ({} entries, {} scan)",
+            volume.name, volume.records, volume.scan_mode
+        );
+        match &volume.journal {
+            Some(js) => println!(
+                "    Journal at build: {:016X} / USN {}",
+                js.journal_id, js.last_usn
+            ),
+            None => println!("    Journal at build: none"),
+        }
+    }
+
+    let exclusions = &manifest.exclusions;
+    let list = |items: &[String]| {
+        if items.is_empty() {
+            "none".to_string()
+        } else {
+            items.join(", ")
+        }
+    };
+    println!("  Excluded paths:    {}", list(&exclusions.paths));
+    println!("  Excluded patterns: {}", list(&exclusions.patterns));
+    println!("  Excluded volumes:  {}", list(&exclusions.volumes));
+    let flag = |excluded: bool| if excluded { "excluded" } else { "included" };
+    println!("  Hidden files:      {}", flag(exclusions.hidden));
+    println!("  System files:      {}", flag(exclusions.system));
 }
@@ -0,0 +1,78 @@
+//! Top command - the largest files and folders in the index.
+
+use crate::app::App;
+use crate::OutputFormat;
+use glint_core::{Config, LargestKind, SearchFilter, SearchQuery};
+use glint_frontend::format::format_size;
+
+/// Run the top command.
+///
+/// Folders are sized by the files inside them. `within` limits the ranking
+/// to records inside those folders (relative to the current one).
+pub fn run(
+    config: Config,
+    kind: LargestKind,
+    limit: usize,
+    within: Vec<String>,
+    output: OutputFormat,
+) -> anyhow::Result<()> {
+    let app = App::new(config)?;
+
+    if app.index.is_empty() {
+        eprintln!("Index is empty. Run 'glint index' first.");
+        return Ok(());
+    }
+
+    let mut query = SearchQuery::substring("");
+    if !within.is_empty() {
+        let folders = within
+            .iter()
+            .map(|dir| super::absolute(dir))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        query = query.with_filter(SearchFilter::in_folders(&folders));
+    }
+
+    let page = app.index.largest(&query, kind, limit);
+    let display_path = |path: &str| app.config.ui.path_format.apply(path);
+
+    match output {
+        OutputFormat::Text => {
+            for result in &page.results {
+                let record = &result.record;
+                println!(
+                    "{:>10}  {} {}",
+                    format_size(record.size.unwrap_or(0)),
+                    if record.is_dir { "📁" } else { "📄" },
+                    display_path(&record.path)
+                );
+            }
+            if page.total > page.results.len() {
+                eprintln!();
+                eprintln!(
+                    "Largest {} of {} (use --limit to see more)",
+                    page.results.len(),
+                    page.total
+                );
+            }
+        }
+        OutputFormat::Json => {
+            let json: Vec<serde_json::Value> = page
+                .results
+                .iter()
+                .map(|result| {
+                    let r = &result.record;
+                    serde_json::json!({
+                        "name": r.name,
+                        "path": display_path(&r.path),
+                        "is_dir": r.is_dir,
+                        "size": r.size,
+                        "modified": r.modified.map(|t| t.to_rfc3339()),
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+    }
+
+    Ok(())
+}
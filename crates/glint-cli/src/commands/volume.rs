@@ -0,0 +1,67 @@
+//! Volume command - list indexed volumes and enable or disable them.
+
+use crate::app::App;
+use crate::VolumeAction;
+use glint_core::Config;
+use std::path::Path;
+
+/// Run the volume command.
+///
+/// Enabling or disabling a volume only changes the config file; its records
+/// stay in the index either way.
+pub fn run(config: Config, config_path: Option<&Path>, action: VolumeAction) -> anyhow::Result<()> {
+    let mut app = App::new(config)?;
+
+    let (name, disable) = match action {
+        VolumeAction::List => return list(&app),
+        VolumeAction::Enable { name } => (name, false),
+        VolumeAction::Disable { name } => (name, true),
+    };
+
+    let Some(volume) = app.index.find_volume(&name) else {
+        anyhow::bail!("Not an indexed volume: {} (see 'glint volume list')", name);
+    };
+    let display_name = volume.info.display_name();
+
+    if !app.config.set_volume_disabled(display_name, disable) {
+        let state = if disable { "disabled" } else { "enabled" };
+        println!("{} is already {}", display_name, state);
+        return Ok(());
+    }
+
+    match config_path {
+        Some(path) => app.config.save_to(path)?,
+        None => app.config.save()?,
+    }
+
+    if disable {
+        println!(
+            "Disabled {} ({} entries kept in the index)",
+            display_name, volume.record_count
+        );
+    } else {
+        println!("Enabled {}", display_name);
+    }
+    println!("Restart 'glint watch' for the change to take effect there.");
+    Ok(())
+}
+
+fn list(app: &App) -> anyhow::Result<()> {
+    let mut volumes = app.index.volume_states();
+    if volumes.is_empty() {
+        println!("No volumes indexed. Run 'glint index' first.");
+        return Ok(());
+    }
+
+    volumes.sort_by(|a, b| a.info.display_name().cmp(b.info.display_name()));
+    for vol in &volumes {
+        let state = if vol.enabled { "enabled" } else { "disabled" };
+        println!(
+            "{:<24} {:>12} entries  {}",
+            vol.info.display_name(),
+            vol.record_count,
+            state
+        );
+    }
+    Ok(())
+}
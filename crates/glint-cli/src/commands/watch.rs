@@ -1,14 +1,28 @@
 //! Watch command - monitor for file changes.
 
 use crate::app::App;
-use glint_core::backend::{ChangeHandler, ChangeHandlerMessage, ChannelChangeHandler};
-use glint_core::{Config, FileSystemBackend};
+use crate::progress::{Progress, ProgressMode};
+use glint_core::backend::{
+    ChangeHandler, ChangeHandlerMessage, ChannelChangeHandler, VolumeRefresher, VolumeResumer,
+    VOLUME_REFRESH_INTERVAL,
+};
+use glint_core::churn::ChurnReport;
+use glint_core::ipc::IpcServer;
+use glint_core::opensearch::OpenSearchServer;
+use glint_core::report::ReportScheduler;
+use glint_core::{Config, FileSystemBackend, VolumeAvailability};
+use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::{error, info, warn};
 
+/// How often the watcher looks for folders that change constantly.
+const CHURN_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
 /// Run the watch command.
 pub fn run(config: Config, _foreground: bool) -> anyhow::Result<()> {
     let app = App::new(config)?;
+    app.index.ensure_writable("watch for changes")?;
 
     if app.index.is_empty() {
         eprintln!("Index is empty. Run 'glint index' first.");
@@ -19,7 +33,10 @@ pub fn run(config: Config, _foreground: bool) -> anyhow::Result<()> {
     println!("Press Ctrl+C to stop.");
     println!();
 
-    // Get volumes to watch
+    // Get volumes to watch; those not mounted now are watched once they are back
+    if let Ok(present) = app.backend.list_volumes() {
+        app.index.update_availability(&present);
+    }
     let volumes = app.index.volume_states();
 
     if volumes.is_empty() {
@@ -35,6 +52,18 @@ pub fn run(config: Config, _foreground: bool) -> anyhow::Result<()> {
     let mut watch_handles = Vec::new();
 
     for vol_state in &volumes {
+        if !vol_state.enabled {
+            println!("⏸ Skipping {} (disabled)", vol_state.info.display_name());
+            continue;
+        }
+        if vol_state.availability == VolumeAvailability::Offline {
+            println!(
+                "⏏ {} is offline; watching it once it is back",
+                vol_state.info.display_name()
+            );
+            continue;
+        }
+
         let mut volume_info = vol_state.info.clone();
         volume_info.journal_state = vol_state.journal_state.clone();
 
@@ -43,28 +72,126 @@ pub fn run(config: Config, _foreground: bool) -> anyhow::Result<()> {
             .watch_changes(volume_info.clone(), handler.clone())
         {
             Ok(handle) => {
-                println!("✓ Watching {}", vol_state.info.mount_point);
+                println!("✓ Watching {}", vol_state.info.display_name());
                 watch_handles.push(handle);
             }
             Err(e) => {
-                eprintln!("⚠ Cannot watch {} ({})", vol_state.info.mount_point, e);
+                eprintln!("⚠ Cannot watch {} ({})", vol_state.info.display_name(), e);
             }
         }
     }
 
-    if watch_handles.is_empty() {
+    let offline = volumes
+        .iter()
+        .any(|v| v.enabled && v.availability == VolumeAvailability::Offline);
+    if watch_handles.is_empty() && !offline {
         eprintln!("No volumes could be watched. Try running as Administrator.");
         return Ok(());
     }
 
+    // Answer queries from other processes (e.g. the CLI inside WSL)
+    let _ipc_server = if app.config.server.enabled {
+        let server = &app.config.server;
+        let spawned = server.server_tls().and_then(|tls| {
+            IpcServer::spawn(
+                &server.listen,
+                app.index.clone(),
+                server.query_limits(),
+                server.token.clone(),
+                tls,
+            )
+        });
+        match spawned {
+            Ok(server) => {
+                println!("✓ Serving queries on {}", server.local_addr());
+                Some(server)
+            }
+            Err(e) => {
+                eprintln!(
+                    "⚠ Cannot start query server on {} ({})",
+                    app.config.server.listen, e
+                );
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // Answer searches typed into Explorer's search box
+    let _explorer_server = match &app.config.server.explorer_listen {
+        Some(addr) => {
+            let limits = app.config.server.query_limits();
+            match OpenSearchServer::spawn(addr, app.index.clone(), limits) {
+                Ok(server) => {
+                    println!("✓ Answering Explorer searches on {}", server.local_addr());
+                    Some(server)
+                }
+                Err(e) => {
+                    eprintln!("⚠ Cannot answer Explorer searches on {} ({})", addr, e);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    // Run scheduled reports while the index is kept current
+    let _report_scheduler = if app.config.reports.jobs.is_empty() {
+        None
+    } else {
+        let output_dir = app.config.reports_dir()?;
+        match ReportScheduler::spawn(
+            app.config.reports.jobs.clone(),
+            output_dir.clone(),
+            app.index.clone(),
+            app.store.manifest_path(),
+        ) {
+            Ok(scheduler) => {
+                println!(
+                    "✓ Scheduled {} report(s) to {}",
+                    app.config.reports.jobs.len(),
+                    output_dir.display()
+                );
+                Some(scheduler)
+            }
+            Err(e) => {
+                eprintln!("⚠ Cannot schedule reports ({})", e);
+                None
+            }
+        }
+    };
+
+    // Keep volume labels and free space current for status and queries,
+    // and watch volumes again when they come back
+    let _volume_refresher = match VolumeRefresher::spawn_resuming(
+        app.backend.clone(),
+        app.index.clone(),
+        VOLUME_REFRESH_INTERVAL,
+        VolumeResumer::new(handler.clone()),
+    ) {
+        Ok(refresher) => Some(refresher),
+        Err(e) => {
+            eprintln!("⚠ Cannot refresh volume information ({})", e);
+            None
+        }
+    };
+
     println!();
     println!("Monitoring for changes...");
 
     // Process changes
     let index = app.index.clone();
+    let mut last_churn_check = Instant::now();
+    let mut flagged = HashSet::new();
 
     loop {
-        match receiver.recv() {
+        if last_churn_check.elapsed() >= CHURN_CHECK_INTERVAL {
+            check_churn(&app, &mut flagged);
+            last_churn_check = Instant::now();
+        }
+
+        match receiver.recv_timeout(CHURN_CHECK_INTERVAL) {
             Ok(ChangeHandlerMessage::Change(event)) => {
                 info!(
                     kind = %event.kind,
@@ -89,6 +216,15 @@ pub fn run(config: Config, _foreground: bool) -> anyhow::Result<()> {
             Ok(ChangeHandlerMessage::Error { volume_id, error }) => {
                 error!(volume = %volume_id, error = %error, "Watch error");
             }
+            Ok(ChangeHandlerMessage::VolumeOffline { volume_id, reason }) => {
+                if index.set_volume_offline(&volume_id, &reason) {
+                    println!("⏏ {} went offline ({})", volume_id, reason);
+                }
+            }
+            Ok(ChangeHandlerMessage::Position { volume_id, state }) => {
+                index.update_journal_state(&volume_id, state);
+            }
+            Err(e) if e.is_timeout() => {}
             Err(_) => {
                 // Channel closed, all watchers stopped
                 break;
@@ -102,3 +238,110 @@ pub fn run(config: Config, _foreground: bool) -> anyhow::Result<()> {
     println!("Monitoring stopped.");
     Ok(())
 }
+
+/// Save the folders changing faster than `[exclude] churn_threshold` for
+/// `glint doctor`, and mention the ones not seen before.
+fn check_churn(app: &App, flagged: &mut HashSet<String>) {
+    let threshold = app.config.exclude.churn_threshold;
+    let folders = app.index.churning_folders(threshold);
+    if folders.is_empty() {
+        return;
+    }
+
+    for folder in &folders {
+        if flagged.insert(folder.path.clone()) {
+            println!(
+                "⚠ {} is changing constantly ({} changes a minute); run 'glint doctor' for suggestions",
+                folder.path, folder.per_minute
+            );
+        }
+    }
+
+    if let Err(e) = app.store.save_churn(&ChurnReport::new(threshold, folders)) {
+        warn!(error = %e, "Cannot save churn report");
+    }
+}
+
+/// Run the watch command once: catch up with the changes made since the
+/// last run, save the index and exit.
+///
+/// This suits scheduled tasks that want a fresh index without a resident
+/// watcher. Volumes whose changes can't be recovered are marked for rescan.
+pub fn run_once(config: Config, progress: ProgressMode) -> anyhow::Result<()> {
+    let app = App::new(config)?;
+    app.index.ensure_writable("catch up with changes")?;
+
+    if app.index.is_empty() {
+        eprintln!("Index is empty. Run 'glint index' first.");
+        return Ok(());
+    }
+
+    let progress = Progress::new(progress);
+    let mut total = 0usize;
+    for vol_state in app.index.volume_states() {
+        let name = vol_state.info.display_name();
+        if !vol_state.enabled {
+            println!("⏸ Skipping {} (disabled)", name);
+            continue;
+        }
+
+        let mut volume_info = vol_state.info.clone();
+        volume_info.journal_state = vol_state.journal_state.clone();
+
+        let mut volume_progress = progress.catch_up(name);
+        let (handler, receiver) = ChannelChangeHandler::new();
+        let journal_state = match app.backend.catch_up(&volume_info, &handler) {
+            Ok(Some(state)) => state,
+            Ok(None) => {
+                eprintln!(
+                    "⚠ No saved journal position for {}; run 'glint index' to rebuild it",
+                    name
+                );
+                continue;
+            }
+            Err(e) => {
+                eprintln!("⚠ Cannot catch up {} ({})", name, e);
+                continue;
+            }
+        };
+        drop(handler);
+
+        let mut changes = 0usize;
+        for message in receiver {
+            match message {
+                ChangeHandlerMessage::Change(event) => {
+                    app.index.apply_change(event);
+                    changes += 1;
+                    volume_progress.inc();
+                }
+                ChangeHandlerMessage::JournalReset { volume_id, reason } => {
+                    warn!(
+                        volume = %volume_id,
+                        reason = %reason,
+                        "Journal reset, index may be stale"
+                    );
+                    eprintln!("⚠ {} needs a rescan ({})", name, reason);
+                    app.index.mark_needs_rescan(&volume_id, &reason);
+                }
+                ChangeHandlerMessage::Error { volume_id, error } => {
+                    error!(volume = %volume_id, error = %error, "Catch-up error");
+                }
+                ChangeHandlerMessage::VolumeOffline { volume_id, reason } => {
+                    app.index.set_volume_offline(&volume_id, &reason);
+                }
+                // The position catch-up returns is recorded below
+                ChangeHandlerMessage::Position { .. } => {}
+            }
+        }
+
+        app.index
+            .update_journal_state(&vol_state.info.id, journal_state);
+        volume_progress.finish();
+        println!("✓ {}: {} change(s)", name, changes);
+        total += changes;
+    }
+
+    app.save_index()?;
+    println!("Applied {} change(s); index saved.", total);
+    Ok(())
+}
@@ -8,6 +8,12 @@
 //! - `glint query <pattern>` - Search for files matching a pattern
 //! - `glint interactive` - Start interactive TUI mode
 //! - `glint status` - Show index status and statistics
+//! - `glint stats` - Show file counts and sizes by extension, folder, volume or age
+//! - `glint rename` - Bulk rename search results using a template
+//! - `glint report` - Run scheduled report jobs now
+//! - `glint volume` - List indexed volumes, or enable or disable one
+//! - `glint schedule` - Keep the index current with a scheduled task instead of the service
+//! - `glint help syntax` - Print the query syntax reference
 //!
 //! ## Example Usage
 //!
@@ -24,9 +30,18 @@
 
 mod app;
 mod commands;
+mod progress;
+mod schema;
 mod tui;
+mod wsl;
 
 use clap::{Parser, Subcommand};
+use commands::query::Column;
+use glint_backend_ntfs::scheduled_task::DEFAULT_INTERVAL_MINUTES;
+use glint_core::stats::GroupBy;
+use glint_core::{LargestKind, PathFormat};
+use glint_frontend::format::Locale;
+use progress::ProgressMode;
 use std::path::PathBuf;
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
@@ -34,6 +49,7 @@ use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 #[derive(Parser)]
 #[command(name = "glint")]
 #[command(author, version, about, long_about = None)]
+#[command(disable_help_subcommand = true)]
 pub struct Cli {
     /// Path to configuration file
     #[arg(short, long, global = true)]
@@ -47,6 +63,16 @@ pub struct Cli {
     #[arg(short, long, global = true)]
     quiet: bool,
 
+    /// Never save the index or apply changes to it, e.g. to review a
+    /// copied snapshot (also `read_only` under [general])
+    #[arg(long, global = true)]
+    read_only: bool,
+
+    /// Set a config key for this run, e.g. --set general.max_results=500;
+    /// repeatable (GLINT_GENERAL__MAX_RESULTS=500 does the same)
+    #[arg(long = "set", value_name = "KEY=VALUE", global = true)]
+    set: Vec<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -62,17 +88,30 @@ enum Commands {
         /// Only index specific volumes (e.g., "C:" "D:")
         #[arg(short = 'V', long)]
         volumes: Vec<String>,
+
+        /// Only index specific folders (e.g., "D:\Projects"); repeatable
+        #[arg(short, long = "path", value_name = "FOLDER")]
+        paths: Vec<String>,
+
+        /// How to show scan progress on stderr (bar, quiet, json)
+        #[arg(long, default_value = "bar")]
+        progress: ProgressMode,
     },
 
     /// Search for files matching a pattern
     Query {
         /// Search pattern (supports wildcards and regex with r/pattern/)
-        pattern: String,
+        #[arg(required_unless_present = "schema")]
+        pattern: Option<String>,
 
         /// Maximum number of results to show
         #[arg(short, long, default_value = "100")]
         limit: usize,
 
+        /// Skip this many results, to show the next page
+        #[arg(long, default_value = "0")]
+        offset: usize,
+
         /// Only show files (not directories)
         #[arg(short, long)]
         files_only: bool,
@@ -89,23 +128,388 @@ enum Commands {
         #[arg(short, long)]
         path: bool,
 
+        /// Only search the folders of a scope defined in the config file
+        #[arg(long)]
+        scope: Option<String>,
+
+        /// Only search inside this folder; relative paths start from the current folder (can be used multiple times)
+        #[arg(short, long, value_name = "DIR")]
+        within: Vec<String>,
+
+        /// Only search the indexed volume of this name, e.g. D:
+        #[arg(long)]
+        volume: Option<String>,
+
+        /// Leave out names and folders matching a glob, e.g. *.tmp or node_modules (can be used multiple times)
+        #[arg(short = 'x', long, value_name = "GLOB")]
+        exclude: Vec<String>,
+
+        /// Also show results matching the hide_patterns of the [ui] config
+        #[arg(long)]
+        show_hidden_patterns: bool,
+
+        /// Show a table of these columns: name, path, size, modified, fileid, and cap1, cap2, ... for the capture groups of an r/regex/ pattern
+        #[arg(long, value_delimiter = ',', value_name = "COLUMNS")]
+        columns: Vec<Column>,
+
+        /// Sort the results shown by a column (name, path, size, modified, fileid, cap1, ...)
+        #[arg(long, value_name = "COLUMN")]
+        sort: Option<Column>,
+
+        /// Reverse the --sort order
+        #[arg(long, requires = "sort")]
+        reverse: bool,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        output: OutputFormat,
+
+        /// Path format (native, unc, forward-slash, file-uri, wsl); defaults to the config value
+        #[arg(long)]
+        path_format: Option<PathFormat>,
+
+        /// Query a running 'glint watch' service instead of the local index (default inside WSL)
+        #[arg(long)]
+        remote: bool,
+
+        /// Print the versioned JSON schema of query, status and stats output instead of searching
+        #[arg(long, exclusive = true)]
+        schema: bool,
+    },
+
+    /// Run one query per line of a file against the same view of the index
+    Batch {
+        /// File of queries, one per line; - reads standard input
+        #[arg(default_value = "-")]
+        file: String,
+
+        /// Maximum number of results to show per query
+        #[arg(short, long, default_value = "100")]
+        limit: usize,
+
         /// Output format (text, json)
         #[arg(short, long, default_value = "text")]
         output: OutputFormat,
     },
 
+    /// Show the indexed record with this file ID, as named by USN journal
+    /// entries and audit logs
+    Get {
+        /// Volume name (e.g. D:) or volume ID
+        volume: String,
+
+        /// File ID, in decimal or in hex after 0x
+        id: String,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        output: OutputFormat,
+    },
+
+    /// List a folder's indexed contents, without reading the disk
+    Ls {
+        /// Folder to list; relative paths start from the current folder
+        #[arg(default_value = ".")]
+        path: String,
+
+        /// How many levels of subfolders to list
+        #[arg(long, default_value = "1")]
+        depth: usize,
+
+        /// Draw the listing as a tree
+        #[arg(long)]
+        tree: bool,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        output: OutputFormat,
+    },
+
+    /// Bulk rename files matching a query using a template
+    Rename {
+        /// Query selecting the files to rename
+        #[arg(long, required_unless_present = "undo")]
+        query: Option<String>,
+
+        /// Name template, e.g. "photo_{n:3}.{ext}" (tokens: {name} {ext} {file} {n:W} {1}..{9})
+        #[arg(short, long, required_unless_present = "undo")]
+        to: Option<String>,
+
+        /// Regex matched against each name; its groups are available as {1}, {2}, ...
+        #[arg(short = 'm', long = "match")]
+        pattern: Option<String>,
+
+        /// First counter value
+        #[arg(long, default_value = "1")]
+        start: u64,
+
+        /// Maximum number of files to rename
+        #[arg(short, long, default_value = "1000")]
+        limit: usize,
+
+        /// Show the preview without renaming anything
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Revert the last bulk rename
+        #[arg(long, conflicts_with_all = ["query", "to", "pattern"])]
+        undo: bool,
+    },
+
+    /// Move or remove files that haven't changed in a while
+    ///
+    /// Lists the files first and asks before touching them. Removed files
+    /// go to the Recycle Bin unless --permanent is given.
+    Cleanup {
+        /// Only files last modified longer ago than this, e.g. 90d, 6mo or 2y
+        #[arg(long)]
+        older_than: String,
+
+        /// Only files inside this folder (can be repeated)
+        #[arg(long, required = true)]
+        within: Vec<String>,
+
+        /// Only files also matching this query, e.g. "ext:log,tmp"
+        #[arg(short, long)]
+        query: Option<String>,
+
+        /// Move the files into this folder, keeping their paths, instead of
+        /// removing them
+        #[arg(long)]
+        move_to: Option<String>,
+
+        /// Delete the files for good instead of using the Recycle Bin
+        #[arg(long, conflicts_with = "move_to")]
+        permanent: bool,
+
+        /// Maximum number of files to clean up
+        #[arg(short, long, default_value = "10000")]
+        limit: usize,
+
+        /// Show the preview without changing anything
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Pin a file or folder so it is listed first in matching searches
+    ///
+    /// Without a path, lists all pinned items.
+    Pin {
+        /// Path of the file or folder
+        path: Option<String>,
+
+        /// Unpin instead
+        #[arg(short, long, requires = "path")]
+        remove: bool,
+    },
+
+    /// Search another index file alongside the local one
+    ///
+    /// Without a path, lists attached indexes.
+    Attach {
+        /// Index folder or index file to attach; with --remove, the name
+        /// or path of an attached index
+        path: Option<String>,
+
+        /// Name shown next to its results (default: the folder name)
+        #[arg(short, long, conflicts_with = "remove")]
+        name: Option<String>,
+
+        /// Detach instead
+        #[arg(short, long, requires = "path")]
+        remove: bool,
+    },
+
+    /// Run report jobs now (they otherwise run on schedule while watching)
+    Report {
+        /// Name of the job to run (default: all jobs)
+        name: Option<String>,
+
+        /// List jobs and when they next run
+        #[arg(short, long, conflicts_with = "name")]
+        list: bool,
+    },
+
+    /// List indexed volumes, or enable or disable one
+    ///
+    /// A disabled volume keeps its data in the index but is left out of
+    /// searches and not watched, so enabling it again needs no re-index.
+    Volume {
+        #[command(subcommand)]
+        action: Option<VolumeAction>,
+    },
+
+    /// List, add, remove or test exclusion rules
+    ///
+    /// Rules are excluded folders, name patterns (e.g., "*.tmp") and presets.
+    /// 'glint exclude apply' takes what they leave out from the existing
+    /// index, so no re-index is needed.
+    Exclude {
+        #[command(subcommand)]
+        action: Option<ExcludeAction>,
+    },
+
+    /// Keep the index current with a Task Scheduler job instead of the service
+    ///
+    /// The job runs 'glint watch --once' and needs no administrator rights
+    /// to install.
+    Schedule {
+        #[command(subcommand)]
+        action: Option<ScheduleAction>,
+    },
+
+    /// Search the index from Explorer's search box with a search connector
+    ///
+    /// 'glint watch' or the service answers the connector's searches on
+    /// [server] explorer_listen.
+    Explorer {
+        #[command(subcommand)]
+        action: Option<ExplorerAction>,
+    },
+
     /// Start interactive TUI mode
     #[command(alias = "i")]
     Interactive,
 
+    /// Check the index and suggest exclusions for folders that change constantly
+    Doctor,
+
+    /// Print help for a command, or the query syntax with 'glint help syntax'
+    Help {
+        /// "syntax", or a command (e.g., "exclude add")
+        topic: Vec<String>,
+
+        /// Print the query syntax as a Markdown table
+        #[arg(long)]
+        markdown: bool,
+    },
+
+    /// Shrink the index by dropping deleted files and long-disconnected volumes
+    ///
+    /// Also deletes the backup of the previous index file. The service
+    /// compacts on its own when the index is quiet.
+    Compact {
+        /// Drop volumes disconnected for this many days (0 = none); defaults
+        /// to drop_absent_volumes_after_days
+        #[arg(long, value_name = "DAYS")]
+        absent_days: Option<u32>,
+
+        /// Show what would be dropped without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Show the configuration in effect
+    ///
+    /// The user config is laid over the machine config, which can lock keys
+    /// against changes.
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+    },
+
     /// Show index status and statistics
-    Status,
+    Status {
+        /// Also show how the index was built (scan modes, exclusions, version)
+        #[arg(short, long)]
+        details: bool,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        output: OutputFormat,
+    },
+
+    /// Show file counts and sizes grouped by extension, folder, volume or age
+    Stats {
+        /// Grouping (ext, folder, volume, age)
+        #[arg(short, long, default_value = "ext")]
+        by: GroupBy,
+
+        /// Maximum number of groups to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+
+        /// Recompute instead of using cached statistics
+        #[arg(long)]
+        refresh: bool,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        output: OutputFormat,
+    },
+
+    /// Show the largest files and folders in the index
+    Top {
+        /// Only rank files
+        #[arg(long, conflicts_with = "dirs")]
+        files: bool,
+
+        /// Only rank folders (sized by the files inside them)
+        #[arg(long)]
+        dirs: bool,
+
+        /// Maximum number of items to show
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+
+        /// Only rank items inside this folder (can be used multiple times)
+        #[arg(short, long, value_name = "DIR")]
+        within: Vec<String>,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        output: OutputFormat,
+    },
 
     /// Start watching for file changes (requires the index to exist)
     Watch {
         /// Run in foreground (don't daemonize)
         #[arg(short, long)]
         foreground: bool,
+
+        /// Apply the changes made since the last run, save and exit
+        #[arg(long, conflicts_with = "foreground")]
+        once: bool,
+
+        /// How to show catch-up progress on stderr with --once (bar, quiet, json)
+        #[arg(long, default_value = "bar")]
+        progress: ProgressMode,
+    },
+
+    /// Put a synthetic index under heavy change and report latencies
+    ///
+    /// For checking the change loop, saving and searching under load
+    /// before a release; the real index is left alone.
+    #[cfg(feature = "soak")]
+    #[command(hide = true)]
+    Soak {
+        /// Changes generated a second
+        #[arg(long, default_value = "5000")]
+        events_per_sec: u64,
+
+        /// How long to keep it up, e.g. 30s, 10m or 1h
+        #[arg(long, default_value = "10m")]
+        duration: String,
+
+        /// How often to save the index, as with the service
+        #[arg(long, default_value = "1m")]
+        save_every: String,
+
+        /// Files in the synthetic index
+        #[arg(long, default_value = "200000")]
+        files: usize,
+
+        /// Threads searching while the changes are applied
+        #[arg(long, default_value = "2")]
+        searchers: usize,
     },
 
     /// Clear the index and all data
@@ -116,6 +520,110 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+pub enum VolumeAction {
+    /// List indexed volumes and whether they are enabled
+    List,
+
+    /// Include a volume in searches again
+    Enable {
+        /// Mount point or indexed folder (e.g., "D:")
+        name: String,
+    },
+
+    /// Leave a volume out of searches and stop watching it
+    Disable {
+        /// Mount point or indexed folder (e.g., "D:")
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ExcludeAction {
+    /// List the exclusion rules
+    List,
+
+    /// Exclude a folder, a name pattern or a preset
+    ///
+    /// Without --folder or --pattern, preset names are presets, text with
+    /// '*' or without a path separator is a pattern, and anything else a
+    /// folder.
+    Add {
+        /// Folder, pattern or preset (e.g., "D:\Build", "*.tmp", "temp")
+        rule: String,
+
+        /// Treat the rule as a folder, relative to the current directory
+        #[arg(long, conflicts_with = "pattern")]
+        folder: bool,
+
+        /// Treat the rule as a name pattern
+        #[arg(long)]
+        pattern: bool,
+    },
+
+    /// Remove an exclusion rule
+    Remove {
+        /// The rule as listed by 'glint exclude list'
+        rule: String,
+    },
+
+    /// Show which rule, if any, excludes a path
+    Test {
+        /// Path to test
+        path: String,
+    },
+
+    /// Remove excluded files from the existing index and save it
+    Apply {
+        /// Only show how many files each rule leaves out
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ScheduleAction {
+    /// Show whether the job is installed
+    Status,
+
+    /// Install the job, or replace it with new settings
+    Install {
+        /// Minutes between runs
+        #[arg(long, value_name = "MINUTES", default_value_t = DEFAULT_INTERVAL_MINUTES)]
+        every: u32,
+
+        /// Run at logon instead of periodically
+        #[arg(long, conflicts_with = "every")]
+        at_logon: bool,
+    },
+
+    /// Remove the job
+    Uninstall,
+}
+
+#[derive(Subcommand)]
+pub enum ExplorerAction {
+    /// Show whether the connector is installed and its endpoint answering
+    Status,
+
+    /// Turn on the endpoint and add the connector to Explorer
+    Install,
+
+    /// Remove the connector and turn off the endpoint
+    Uninstall,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Print the effective configuration
+    Show {
+        /// Note after each value whether it is a default or comes from the
+        /// machine or user config
+        #[arg(long)]
+        origin: bool,
+    },
+}
+
 #[derive(Clone, Debug, Default)]
 pub enum OutputFormat {
     #[default]
@@ -155,28 +663,178 @@ fn main() -> anyhow::Result<()> {
         .init();
 
     // Load configuration
-    let config = match &cli.config {
-        Some(path) => glint_core::Config::load_from(path)?,
-        None => glint_core::Config::load()?,
+    let config_path = match &cli.config {
+        Some(path) => path.clone(),
+        None => glint_core::Config::default_config_path()?,
     };
+    let mut config = glint_core::Config::load_layered(&config_path, &cli.set)?;
+    config.general.read_only |= cli.read_only;
+    glint_core::clock::set_zone(config.general.timezone);
+    glint_frontend::format::set_locale(Locale::from_config(&config.ui));
+
+    // --quiet silences progress too
+    let quiet = cli.quiet;
+    let progress_mode = |mode: ProgressMode| if quiet { ProgressMode::Quiet } else { mode };
 
     // Execute command
     match cli.command {
-        Commands::Index { force, volumes } => commands::index::run(config, force, volumes),
+        Commands::Index {
+            force,
+            volumes,
+            paths,
+            progress,
+        } => commands::index::run(config, force, volumes, paths, progress_mode(progress)),
+        Commands::Query { schema: true, .. } => commands::query::print_schema(),
         Commands::Query {
             pattern,
             limit,
+            offset,
             files_only,
             dirs_only,
             ext,
             path,
+            scope,
+            within,
+            volume,
+            exclude,
+            show_hidden_patterns,
+            columns,
+            sort,
+            reverse,
             output,
+            path_format,
+            remote,
+            schema: false,
         } => commands::query::run(
-            config, &pattern, limit, files_only, dirs_only, ext, path, output,
+            config,
+            pattern.as_deref().unwrap_or_default(),
+            limit,
+            offset,
+            files_only,
+            dirs_only,
+            ext,
+            path,
+            scope,
+            within,
+            volume,
+            exclude,
+            show_hidden_patterns,
+            columns,
+            sort,
+            reverse,
+            output,
+            path_format,
+            remote,
+        ),
+        Commands::Batch {
+            file,
+            limit,
+            output,
+        } => commands::batch::run(config, &file, limit, output),
+        Commands::Get { volume, id, output } => commands::get::run(config, volume, id, output),
+        Commands::Ls {
+            path,
+            depth,
+            tree,
+            output,
+        } => commands::ls::run(config, path, depth, tree, output),
+        Commands::Rename {
+            query,
+            to,
+            pattern,
+            start,
+            limit,
+            dry_run,
+            yes,
+            undo,
+        } => commands::rename::run(config, query, to, pattern, start, limit, dry_run, yes, undo),
+        Commands::Cleanup {
+            older_than,
+            within,
+            query,
+            move_to,
+            permanent,
+            limit,
+            dry_run,
+            yes,
+        } => commands::cleanup::run(
+            config, older_than, within, query, move_to, permanent, limit, dry_run, yes,
+        ),
+        Commands::Pin { path, remove } => commands::pin::run(config, path, remove),
+        Commands::Attach { path, name, remove } => {
+            commands::attach::run(config, path, name, remove)
+        }
+        Commands::Report { name, list } => commands::report::run(config, name, list),
+        Commands::Volume { action } => commands::volume::run(
+            config,
+            cli.config.as_deref(),
+            action.unwrap_or(VolumeAction::List),
+        ),
+        Commands::Exclude { action } => commands::exclude::run(
+            config,
+            cli.config.as_deref(),
+            action.unwrap_or(ExcludeAction::List),
+        ),
+        Commands::Schedule { action } => {
+            commands::schedule::run(action.unwrap_or(ScheduleAction::Status))
+        }
+        Commands::Explorer { action } => commands::explorer::run(
+            config,
+            cli.config.as_deref(),
+            action.unwrap_or(ExplorerAction::Status),
         ),
         Commands::Interactive => tui::run(config),
-        Commands::Status => commands::status::run(config),
-        Commands::Watch { foreground } => commands::watch::run(config, foreground),
+        Commands::Doctor => commands::doctor::run(config),
+        Commands::Help { topic, markdown } => commands::help::run(&topic, markdown),
+        Commands::Compact {
+            absent_days,
+            dry_run,
+        } => commands::compact::run(config, absent_days, dry_run),
+        Commands::Config { action } => commands::config::run(
+            &config_path,
+            &cli.set,
+            action.unwrap_or(ConfigAction::Show { origin: false }),
+        ),
+        Commands::Status { details, output } => commands::status::run(config, details, output),
+        Commands::Stats {
+            by,
+            limit,
+            refresh,
+            output,
+        } => commands::stats::run(config, by, limit, refresh, output),
+        Commands::Top {
+            files,
+            dirs,
+            limit,
+            within,
+            output,
+        } => {
+            let kind = match (files, dirs) {
+                (true, _) => LargestKind::Files,
+                (_, true) => LargestKind::Folders,
+                _ => LargestKind::All,
+            };
+            commands::top::run(config, kind, limit, within, output)
+        }
+        Commands::Watch {
+            foreground,
+            once,
+            progress,
+        } => {
+            if once {
+                commands::watch::run_once(config, progress_mode(progress))
+            } else {
+                commands::watch::run(config, foreground)
+            }
+        }
+        #[cfg(feature = "soak")]
+        Commands::Soak {
+            events_per_sec,
+            duration,
+            save_every,
+            files,
+            searchers,
+        } => commands::soak::run(events_per_sec, duration, save_every, files, searchers),
         Commands::Clear { yes } => commands::clear::run(config, yes),
     }
 }
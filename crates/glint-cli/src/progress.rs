@@ -0,0 +1,354 @@
+//! Progress output for `glint index` and `glint watch --once`.
+//!
+//! Progress goes to stderr, either as one bar per volume, as JSON lines for
+//! scripts, or not at all. Bars are hidden when stderr isn't a terminal.
+
+use glint_core::backend::{ScanEstimate, ScanProgress};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How progress is reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProgressMode {
+    /// A progress bar per volume
+    #[default]
+    Bar,
+    /// No progress output
+    Quiet,
+    /// One JSON object per line
+    Json,
+}
+
+impl std::str::FromStr for ProgressMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "bar" => Ok(ProgressMode::Bar),
+            "quiet" => Ok(ProgressMode::Quiet),
+            "json" => Ok(ProgressMode::Json),
+            _ => Err(format!("Unknown progress mode: {}", s)),
+        }
+    }
+}
+
+/// How often spinners move while a volume is scanned.
+const TICK: Duration = Duration::from_millis(120);
+
+/// The result of scanning one volume, for the summary table.
+#[derive(Debug, Clone)]
+struct ScanSummary {
+    volume: String,
+    /// How the backend scanned the volume, if it said
+    method: Option<&'static str>,
+    files: u64,
+    dirs: u64,
+    elapsed: Duration,
+}
+
+impl ScanSummary {
+    fn rate(&self) -> f64 {
+        (self.files + self.dirs) as f64 / self.elapsed.as_secs_f64().max(0.001)
+    }
+}
+
+/// Progress of a command working through several volumes.
+pub struct Progress {
+    mode: ProgressMode,
+    bars: MultiProgress,
+    scans: Arc<Mutex<Vec<ScanSummary>>>,
+}
+
+impl Progress {
+    pub fn new(mode: ProgressMode) -> Self {
+        let target = match mode {
+            ProgressMode::Bar => ProgressDrawTarget::stderr(),
+            ProgressMode::Quiet | ProgressMode::Json => ProgressDrawTarget::hidden(),
+        };
+        Progress {
+            mode,
+            bars: MultiProgress::with_draw_target(target),
+            scans: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Start reporting the scan of a volume.
+    pub fn scan(&self, volume: &str) -> Arc<VolumeProgress> {
+        let bar = self.bars.add(ProgressBar::new_spinner());
+        bar.set_style(spinner_style());
+        bar.set_prefix(volume.to_string());
+        bar.set_message("starting");
+        bar.enable_steady_tick(TICK);
+        Arc::new(VolumeProgress {
+            mode: self.mode,
+            volume: volume.to_string(),
+            bar,
+            started: Instant::now(),
+            counts: Mutex::new((0, 0)),
+            method: Mutex::new(None),
+            scans: Arc::clone(&self.scans),
+        })
+    }
+
+    /// Start reporting the changes applied to a volume while catching up.
+    pub fn catch_up(&self, volume: &str) -> CatchUpProgress {
+        let bar = self.bars.add(ProgressBar::new_spinner());
+        bar.set_style(spinner_style());
+        bar.set_prefix(volume.to_string());
+        bar.set_message("reading change journal");
+        bar.enable_steady_tick(TICK);
+        CatchUpProgress {
+            mode: self.mode,
+            volume: volume.to_string(),
+            bar,
+            changes: 0,
+        }
+    }
+
+    /// How each scanned volume was read, by volume name.
+    pub fn scan_methods(&self) -> Vec<(String, &'static str)> {
+        let scans = self.scans.lock().unwrap_or_else(|e| e.into_inner());
+        scans
+            .iter()
+            .filter_map(|s| Some((s.volume.clone(), s.method?)))
+            .collect()
+    }
+
+    /// Print a table of the volumes scanned (or a JSON summary line).
+    pub fn print_summary(&self) {
+        let scans = self.scans.lock().unwrap_or_else(|e| e.into_inner());
+        match self.mode {
+            ProgressMode::Quiet => {}
+            ProgressMode::Json => {
+                let volumes: Vec<_> = scans
+                    .iter()
+                    .map(|s| {
+                        json!({
+                            "volume": s.volume,
+                            "method": s.method,
+                            "files": s.files,
+                            "dirs": s.dirs,
+                            "seconds": s.elapsed.as_secs_f64(),
+                        })
+                    })
+                    .collect();
+                eprintln!("{}", json!({ "event": "summary", "volumes": volumes }));
+            }
+            ProgressMode::Bar => {
+                if scans.is_empty() {
+                    return;
+                }
+                let width = scans
+                    .iter()
+                    .map(|s| s.volume.len())
+                    .max()
+                    .unwrap_or(0)
+                    .max(6);
+                println!();
+                println!(
+                    "  {:<width$}  {:<9}  {:>12}  {:>12}  {:>8}  {:>12}",
+                    "Volume", "Mode", "Files", "Folders", "Time", "Entries/sec"
+                );
+                for scan in scans.iter() {
+                    println!(
+                        "  {:<width$}  {:<9}  {:>12}  {:>12}  {:>7.1}s  {:>12.0}",
+                        scan.volume,
+                        scan.method.unwrap_or("-"),
+                        scan.files,
+                        scan.dirs,
+                        scan.elapsed.as_secs_f64(),
+                        scan.rate()
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn spinner_style() -> ProgressStyle {
+    ProgressStyle::with_template("{spinner} {prefix:<8} {msg}").expect("valid progress template")
+}
+
+fn bar_style() -> ProgressStyle {
+    ProgressStyle::with_template("  {prefix:<8} [{bar:30}] {percent:>3}% {msg}")
+        .expect("valid progress template")
+        .progress_chars("=> ")
+}
+
+/// Progress of one volume's scan.
+pub struct VolumeProgress {
+    mode: ProgressMode,
+    volume: String,
+    bar: ProgressBar,
+    started: Instant,
+    /// Files and folders found so far
+    counts: Mutex<(u64, u64)>,
+    method: Mutex<Option<&'static str>>,
+    scans: Arc<Mutex<Vec<ScanSummary>>>,
+}
+
+impl VolumeProgress {
+    fn rate(&self, entries: u64) -> f64 {
+        entries as f64 / self.started.elapsed().as_secs_f64().max(0.001)
+    }
+
+    fn show(&self, remaining: Option<Duration>) {
+        let (files, dirs) = *self.counts.lock().unwrap_or_else(|e| e.into_inner());
+        let mut message = format!(
+            "{} files, {} folders ({:.0}/s)",
+            files,
+            dirs,
+            self.rate(files + dirs)
+        );
+        if let Some(remaining) = remaining {
+            message.push_str(&format!(", about {}s left", remaining.as_secs().max(1)));
+        }
+        self.bar.set_message(message);
+    }
+}
+
+impl ScanProgress for VolumeProgress {
+    fn on_progress(&self, files_scanned: u64, dirs_scanned: u64) {
+        let rate = self.rate(files_scanned + dirs_scanned);
+        if self.mode == ProgressMode::Json {
+            let line = json!({
+                "event": "progress",
+                "volume": self.volume,
+                "files": files_scanned,
+                "dirs": dirs_scanned,
+                "rate": rate.round(),
+            });
+            eprintln!("{}", line);
+            return;
+        }
+        *self.counts.lock().unwrap_or_else(|e| e.into_inner()) = (files_scanned, dirs_scanned);
+        self.show(None);
+    }
+
+    fn on_estimate(&self, estimate: ScanEstimate) {
+        if self.mode == ProgressMode::Json {
+            let line = json!({
+                "event": "estimate",
+                "volume": self.volume,
+                "percent": (estimate.fraction() * 100.0).round(),
+                "eta_seconds": estimate.remaining().map(|d| d.as_secs()),
+            });
+            eprintln!("{}", line);
+            return;
+        }
+        if self.bar.length().is_none() {
+            self.bar.set_style(bar_style());
+        }
+        self.bar.set_length(estimate.total.max(estimate.done));
+        self.bar.set_position(estimate.done);
+        self.show(estimate.remaining());
+    }
+
+    fn on_method(&self, method: &'static str) {
+        *self.method.lock().unwrap_or_else(|e| e.into_inner()) = Some(method);
+    }
+
+    fn on_complete(&self, total_files: u64, total_dirs: u64) {
+        let summary = ScanSummary {
+            volume: self.volume.clone(),
+            method: *self.method.lock().unwrap_or_else(|e| e.into_inner()),
+            files: total_files,
+            dirs: total_dirs,
+            elapsed: self.started.elapsed(),
+        };
+        if self.mode == ProgressMode::Json {
+            let line = json!({
+                "event": "complete",
+                "volume": self.volume,
+                "method": summary.method,
+                "files": total_files,
+                "dirs": total_dirs,
+                "seconds": summary.elapsed.as_secs_f64(),
+            });
+            eprintln!("{}", line);
+        }
+        self.bar.set_style(spinner_style());
+        self.bar.finish_with_message(format!(
+            "✓ {} files, {} folders in {:.1}s",
+            total_files,
+            total_dirs,
+            summary.elapsed.as_secs_f64()
+        ));
+        self.scans
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(summary);
+    }
+}
+
+/// Progress of applying one volume's journal changes.
+pub struct CatchUpProgress {
+    mode: ProgressMode,
+    volume: String,
+    bar: ProgressBar,
+    changes: u64,
+}
+
+impl CatchUpProgress {
+    /// Count an applied change.
+    pub fn inc(&mut self) {
+        self.changes += 1;
+        if self.changes % 1000 == 0 {
+            self.bar.set_message(format!("{} changes", self.changes));
+        }
+    }
+
+    /// Report the volume as caught up.
+    pub fn finish(self) {
+        if self.mode == ProgressMode::Json {
+            let line = json!({
+                "event": "catch_up",
+                "volume": self.volume,
+                "changes": self.changes,
+            });
+            eprintln!("{}", line);
+        }
+    }
+}
+
+impl Drop for CatchUpProgress {
+    // Caught up or not, the spinner goes
+    fn drop(&mut self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_mode() {
+        assert_eq!("bar".parse::<ProgressMode>(), Ok(ProgressMode::Bar));
+        assert_eq!("JSON".parse::<ProgressMode>(), Ok(ProgressMode::Json));
+        assert_eq!("quiet".parse::<ProgressMode>(), Ok(ProgressMode::Quiet));
+        assert!("loud".parse::<ProgressMode>().is_err());
+    }
+
+    #[test]
+    fn test_scan_summary() {
+        let progress = Progress::new(ProgressMode::Quiet);
+        let volume = progress.scan("C:");
+        volume.on_method("mft");
+        volume.on_estimate(ScanEstimate {
+            done: 50,
+            total: 100,
+            elapsed: Duration::from_secs(1),
+        });
+        volume.on_complete(90, 10);
+
+        let scans = progress.scans.lock().unwrap();
+        assert_eq!(scans.len(), 1);
+        assert_eq!(scans[0].volume, "C:");
+        assert_eq!(scans[0].files + scans[0].dirs, 100);
+        drop(scans);
+        assert_eq!(progress.scan_methods(), [("C:".to_string(), "mft")]);
+    }
+}
@@ -0,0 +1,185 @@
+//! The JSON printed by `--output json`, as a versioned JSON Schema.
+//!
+//! `glint query --schema` prints [`schema`]. Within one [`SCHEMA_VERSION`]
+//! fields are only ever added: none is removed, renamed or given another
+//! type, so scripts written against a version keep working. Anything else
+//! bumps the version.
+
+use serde_json::{json, Value};
+
+/// Version of the JSON output; `glint status` reports it as `schema_version`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The schema of the JSON output of `glint query`, `glint status` and
+/// `glint stats`, under `$defs`.
+pub fn schema() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Glint JSON output",
+        "description": "Output of --output json. Fields are only added within a version.",
+        "version": SCHEMA_VERSION,
+        "$defs": {
+            "query": query(),
+            "status": status(),
+            "stats": stats(),
+            "journal": object(json!({
+                "journal_id": integer(),
+                "last_usn": integer(),
+            })),
+        },
+    })
+}
+
+/// `glint query`: an array of results.
+fn query() -> Value {
+    let result = object(json!({
+        "name": string(),
+        "path": string(),
+        "is_dir": boolean(),
+        "size": nullable("integer"),
+        "cloud": boolean(),
+        "modified": nullable_time(),
+        "pinned": boolean(),
+        "fuzzy": boolean(),
+        "links": integer(),
+        "source": nullable("string"),
+        "volume_id": string(),
+        "file_id": integer(),
+        "captures": {
+            "type": "array",
+            "items": string(),
+            "description": "Capture groups of an r/regex/ pattern; only with one",
+        },
+        "deleted_at": {
+            "type": "string",
+            "format": "date-time",
+            "description": "When the file was deleted; only for deleted: searches",
+        },
+    }));
+    json!({ "type": "array", "items": result })
+}
+
+/// `glint status`: the index, its file and each volume.
+fn status() -> Value {
+    let volume = object(json!({
+        "name": string(),
+        "id": string(),
+        "label": nullable("string"),
+        "mode": nullable("string"),
+        "records": integer(),
+        "indexed_journal": nullable_journal(),
+        "current_journal": nullable_journal(),
+        "last_scan": nullable_time(),
+        "last_event_applied": nullable_time(),
+        "stale": boolean(),
+        "enabled": boolean(),
+        "needs_rescan": boolean(),
+        "watched": boolean(),
+        "offline": boolean(),
+        "free_bytes": nullable("integer"),
+        "total_bytes": nullable("integer"),
+        "low_on_space": boolean(),
+    }));
+    let index_file = object(json!({
+        "path": string(),
+        "size": integer(),
+        "modified": time(),
+    }));
+    object(json!({
+        "schema_version": integer(),
+        "total_files": integer(),
+        "total_dirs": integer(),
+        "total_size": integer(),
+        "volume_count": integer(),
+        "last_updated": nullable_time(),
+        "version": integer(),
+        "data_dir": string(),
+        "path_lookup_bytes": integer(),
+        "index_file": { "anyOf": [index_file, { "type": "null" }] },
+        "watcher": { "enum": ["running", "not_running", "unknown"] },
+        "volumes": { "type": "array", "items": volume },
+        "manifest": {
+            "type": "object",
+            "description": "How the index was built; only with --details",
+        },
+    }))
+}
+
+/// `glint stats`: an array of groups, largest first.
+fn stats() -> Value {
+    let row = object(json!({
+        "key": string(),
+        "count": integer(),
+        "total_size": integer(),
+    }));
+    json!({ "type": "array", "items": row })
+}
+
+fn object(properties: Value) -> Value {
+    json!({ "type": "object", "properties": properties })
+}
+
+fn string() -> Value {
+    json!({ "type": "string" })
+}
+
+fn integer() -> Value {
+    json!({ "type": "integer" })
+}
+
+fn boolean() -> Value {
+    json!({ "type": "boolean" })
+}
+
+fn time() -> Value {
+    json!({ "type": "string", "format": "date-time" })
+}
+
+fn nullable(kind: &str) -> Value {
+    json!({ "type": [kind, "null"] })
+}
+
+fn nullable_time() -> Value {
+    json!({ "type": ["string", "null"], "format": "date-time" })
+}
+
+fn nullable_journal() -> Value {
+    json!({ "anyOf": [{ "$ref": "#/$defs/journal" }, { "type": "null" }] })
+}
+
+/// Names of the properties of the object schema `def`.
+#[cfg(test)]
+pub(crate) fn properties(def: &Value) -> Vec<String> {
+    let mut names: Vec<String> = def["properties"]
+        .as_object()
+        .map(|p| p.keys().cloned().collect())
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glint_core::stats::StatsRow;
+
+    #[test]
+    fn test_schema() {
+        let schema = schema();
+        assert_eq!(schema["version"], SCHEMA_VERSION);
+        for def in ["query", "status", "stats", "journal"] {
+            assert!(schema["$defs"][def].is_object(), "{} missing", def);
+        }
+
+        let row = serde_json::to_value(StatsRow {
+            key: "rs".to_string(),
+            count: 1,
+            total_size: 2,
+        })
+        .unwrap();
+        assert_eq!(
+            properties(&schema["$defs"]["stats"]["items"]),
+            properties(&object(row))
+        );
+    }
+}
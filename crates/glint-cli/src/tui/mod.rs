@@ -4,6 +4,9 @@
 //! - Real-time search as you type
 //! - Navigation through results
 //! - Quick actions (open in Explorer, copy path)
+//! - Detail pane for the selected result and horizontal scrolling of long paths
+//! - Pinning results, with pinned items listed first
+//! - The query syntax reference on F1
 
 use crate::app::App;
 use crossterm::{
@@ -13,11 +16,19 @@ use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use glint_core::{search::parse_query, Config, SearchFilter, SearchResult};
+use glint_core::federation::Federation;
+use glint_core::pins::PinStore;
+use glint_core::{Config, PathFormat, SearchFilter, SearchResult};
+use glint_frontend::search::SearchOptions;
+use glint_frontend::{actions, format::format_size};
 use ratatui::{prelude::*, widgets::*};
 use std::io;
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
+/// Characters scrolled per Left/Right key press.
+const H_SCROLL_STEP: usize = 8;
+
 /// TUI application state.
 struct TuiApp {
     /// The main application
@@ -49,10 +60,38 @@ struct TuiApp {
 
     /// Show dirs only
     dirs_only: bool,
+
+    /// Format used when copying paths
+    path_format: PathFormat,
+
+    /// Horizontal scroll offset (in characters) for long paths
+    h_scroll: usize,
+
+    /// Show the detail pane for the selected result
+    show_details: bool,
+
+    /// Pinned items
+    pins: PinStore,
+
+    /// Where pins are saved
+    pins_path: PathBuf,
+
+    /// Show only pinned items
+    show_pinned: bool,
+
+    /// Indexes searched alongside the local one
+    attached: Federation,
+
+    /// Show the query syntax reference over the results
+    show_syntax: bool,
+
+    /// Lines the syntax reference is scrolled down
+    syntax_scroll: u16,
 }
 
 impl TuiApp {
-    fn new(app: App) -> Self {
+    fn new(app: App, attached: Federation, pins: PinStore, pins_path: PathBuf) -> Self {
+        let path_format = app.config.ui.path_format;
         TuiApp {
             app,
             query_string: String::new(),
@@ -64,6 +103,15 @@ impl TuiApp {
             status_message: None,
             files_only: false,
             dirs_only: false,
+            path_format,
+            h_scroll: 0,
+            show_details: true,
+            pins,
+            pins_path,
+            show_pinned: false,
+            attached,
+            show_syntax: false,
+            syntax_scroll: 0,
         }
     }
 
@@ -71,7 +119,7 @@ impl TuiApp {
     fn search(&mut self) {
         let start = Instant::now();
 
-        let result = parse_query(&self.query_string);
+        let result = self.app.config.scopes.parse_query(&self.query_string);
         let mut query = match result {
             Ok(q) => q,
             Err(e) => {
@@ -87,13 +135,41 @@ impl TuiApp {
         } else if self.dirs_only {
             query = query.with_filter(SearchFilter::DirsOnly);
         }
+        match self.app.config.ui.hide_filters() {
+            Ok(filters) => {
+                for filter in filters {
+                    query = query.with_filter(filter);
+                }
+            }
+            Err(e) => {
+                self.status_message = Some(format!("Invalid hide pattern: {}", e));
+                self.results.clear();
+                return;
+            }
+        }
 
-        self.results = self.app.index.search_limited(&query, 1000);
+        let options = SearchOptions {
+            max_results: 1000,
+            pinned_only: self.show_pinned,
+            largest_only: false,
+            deleted_only: false,
+            typo_tolerance: self.app.config.general.typo_tolerance,
+            dedup_links: self.app.config.general.dedup_links,
+        };
+        self.results = glint_frontend::search::run(
+            &self.app.index,
+            &self.attached,
+            &self.pins,
+            &query,
+            &options,
+        )
+        .results;
         self.last_search_time = start.elapsed();
 
         // Reset selection
         self.selected = 0;
         self.scroll_offset = 0;
+        self.h_scroll = 0;
         self.status_message = None;
     }
 
@@ -149,31 +225,91 @@ impl TuiApp {
         }
     }
 
-    /// Open selected file in Explorer.
-    fn open_selected(&self) {
+    /// Show the selected file in its folder.
+    fn open_selected(&mut self) {
         if let Some(result) = self.results.get(self.selected) {
-            let path = &result.record.path;
-            // Open in Explorer and select the file
-            let _ = std::process::Command::new("explorer")
-                .arg("/select,")
-                .arg(path)
-                .spawn();
+            if let Err(e) = actions::reveal(&result.record.path) {
+                self.status_message = Some(format!("Failed to open folder: {}", e));
+            }
         }
     }
 
     /// Copy path to clipboard.
     fn copy_path(&mut self) {
         if let Some(result) = self.results.get(self.selected) {
-            // On Windows, use clip command
-            let path = &result.record.path;
-            let _ = std::process::Command::new("cmd")
-                .args(["/C", "echo", path, "|", "clip"])
-                .spawn();
-            self.status_message = Some("Path copied to clipboard".to_string());
+            self.status_message = Some(
+                match actions::copy_path(&result.record.path, self.path_format) {
+                    Ok(()) => format!("Path copied to clipboard ({})", self.path_format.label()),
+                    Err(e) => format!("Failed to copy path: {}", e),
+                },
+            );
+        }
+    }
+
+    /// Cycle the format used when copying paths.
+    fn cycle_path_format(&mut self) {
+        self.path_format = self.path_format.next();
+        self.status_message = Some(format!("Copy format: {}", self.path_format.label()));
+    }
+
+    /// Scroll long paths left.
+    fn scroll_left(&mut self) {
+        self.h_scroll = self.h_scroll.saturating_sub(H_SCROLL_STEP);
+    }
+
+    /// Scroll long paths right (clamped when drawing).
+    fn scroll_right(&mut self) {
+        self.h_scroll += H_SCROLL_STEP;
+    }
+
+    /// Toggle the detail pane.
+    fn toggle_details(&mut self) {
+        self.show_details = !self.show_details;
+    }
+
+    /// Pin or unpin the selected result.
+    fn toggle_pin(&mut self) {
+        let Some(result) = self.results.get(self.selected) else {
+            return;
+        };
+        if let Some(source) = &result.source {
+            self.status_message =
+                Some(format!("Cannot pin results of attached index '{}'", source));
+            return;
         }
+        let pinned = self.pins.toggle(&result.record);
+        self.status_message = Some(match self.pins.save(&self.pins_path) {
+            Ok(()) if pinned => "Pinned".to_string(),
+            Ok(()) => "Unpinned".to_string(),
+            Err(e) => format!("Failed to save pins: {}", e),
+        });
+    }
+
+    /// Toggle between search results and the pinned items.
+    fn toggle_pinned_view(&mut self) {
+        self.show_pinned = !self.show_pinned;
+        self.search();
     }
 
     /// Toggle files-only filter.
+    fn toggle_syntax(&mut self) {
+        self.show_syntax = !self.show_syntax;
+        self.syntax_scroll = 0;
+    }
+
+    /// Keys while the syntax reference is open: scroll it, or close it.
+    fn on_syntax_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Up => self.syntax_scroll = self.syntax_scroll.saturating_sub(1),
+            KeyCode::Down => self.syntax_scroll = self.syntax_scroll.saturating_add(1),
+            KeyCode::PageUp => self.syntax_scroll = self.syntax_scroll.saturating_sub(10),
+            KeyCode::PageDown => self.syntax_scroll = self.syntax_scroll.saturating_add(10),
+            KeyCode::Home => self.syntax_scroll = 0,
+            KeyCode::Esc | KeyCode::F(1) => self.toggle_syntax(),
+            _ => {}
+        }
+    }
+
     fn toggle_files_only(&mut self) {
         self.files_only = !self.files_only;
         self.dirs_only = false;
@@ -191,8 +327,9 @@ impl TuiApp {
 /// Run the TUI application.
 pub fn run(config: Config) -> anyhow::Result<()> {
     let app = App::new(config)?;
+    let attached = app.attached()?;
 
-    if app.index.is_empty() {
+    if app.index.is_empty() && attached.is_empty() {
         eprintln!("Index is empty. Run 'glint index' first.");
         return Ok(());
     }
@@ -204,8 +341,14 @@ pub fn run(config: Config) -> anyhow::Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
+    let pins_path = PinStore::path_in(&app.config.index_dir()?);
+    let mut pins = PinStore::load(&pins_path)?;
+    if pins.refresh(&app.index) {
+        pins.save(&pins_path)?;
+    }
+
     // Create app state
-    let mut tui_app = TuiApp::new(app);
+    let mut tui_app = TuiApp::new(app, attached, pins, pins_path);
 
     // Initial search (empty = show some results)
     tui_app.search();
@@ -233,6 +376,10 @@ fn run_loop<B: Backend>(terminal: &mut Terminal<B>, app: &mut TuiApp) -> anyhow:
         if event::poll(Duration::from_millis(100))? {
             if let Event::Key(key) = event::read()? {
                 if key.kind == KeyEventKind::Press {
+                    if app.show_syntax {
+                        app.on_syntax_key(key.code);
+                        continue;
+                    }
                     match key.code {
                         KeyCode::Esc => {
                             app.should_quit = true;
@@ -279,9 +426,30 @@ fn run_loop<B: Backend>(terminal: &mut Terminal<B>, app: &mut TuiApp) -> anyhow:
                         KeyCode::Enter => {
                             app.open_selected();
                         }
+                        KeyCode::F(1) => {
+                            app.toggle_syntax();
+                        }
                         KeyCode::F(2) => {
                             app.copy_path();
                         }
+                        KeyCode::F(3) => {
+                            app.cycle_path_format();
+                        }
+                        KeyCode::F(4) => {
+                            app.toggle_details();
+                        }
+                        KeyCode::F(5) => {
+                            app.toggle_pin();
+                        }
+                        KeyCode::F(6) => {
+                            app.toggle_pinned_view();
+                        }
+                        KeyCode::Left => {
+                            app.scroll_left();
+                        }
+                        KeyCode::Right => {
+                            app.scroll_right();
+                        }
                         _ => {}
                     }
                 }
@@ -301,19 +469,54 @@ mod ui {
 
     /// Draw the UI.
     pub fn draw(f: &mut Frame, app: &mut TuiApp) {
+        let details_height = if app.show_details { 7 } else { 0 };
+
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .margin(1)
             .constraints([
-                Constraint::Length(3), // Search box
-                Constraint::Min(10),   // Results
-                Constraint::Length(2), // Status bar
+                Constraint::Length(3),              // Search box
+                Constraint::Min(10),                // Results
+                Constraint::Length(details_height), // Detail pane
+                Constraint::Length(2),              // Status bar
             ])
             .split(f.area());
 
         draw_search_box(f, app, chunks[0]);
         draw_results(f, app, chunks[1]);
-        draw_status_bar(f, app, chunks[2]);
+        if app.show_details {
+            draw_details(f, app, chunks[2]);
+        }
+        draw_status_bar(f, app, chunks[3]);
+        if app.show_syntax {
+            draw_syntax(f, app);
+        }
+    }
+
+    /// Draw the query syntax reference over the other panes.
+    fn draw_syntax(f: &mut Frame, app: &mut TuiApp) {
+        let area = f.area().inner(Margin {
+            horizontal: 4,
+            vertical: 2,
+        });
+        let lines: Vec<Line> = glint_frontend::syntax::plain_text()
+            .into_iter()
+            .map(Line::from)
+            .collect();
+        let visible = area.height.saturating_sub(2) as usize;
+        app.syntax_scroll = app
+            .syntax_scroll
+            .min(lines.len().saturating_sub(visible) as u16);
+
+        let reference = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Query syntax (↑↓ scroll, Esc close) "),
+            )
+            .scroll((app.syntax_scroll, 0));
+        f.render_widget(Clear, area);
+        f.render_widget(reference, area);
     }
 
     /// Draw the search input box.
@@ -343,19 +546,58 @@ mod ui {
             app.scroll_offset = app.selected - visible_height + 1;
         }
 
-        let items: Vec<ListItem> = app
+        let inner_width = area.width.saturating_sub(2) as usize;
+        let visible = app
             .results
             .iter()
             .skip(app.scroll_offset)
-            .take(visible_height)
+            .take(visible_height);
+
+        // Width left for the path after the pin marker, icon and size columns
+        let path_width = |size_str: &str| inner_width.saturating_sub(5 + size_str.len());
+        // Size, plus the number of paths for results merged by `dedup_links`
+        // and the attached index a result comes from
+        let size_column = |r: &SearchResult| {
+            let mut size = r.record.size.map(format_size).unwrap_or_default();
+            if r.links > 1 {
+                size = format!("[{} links] {}", r.links, size);
+            }
+            match &r.source {
+                Some(source) => format!("[{}] {}", source, size),
+                None => size,
+            }
+        };
+
+        // Don't scroll past the end of the longest visible path
+        let max_scroll = visible
+            .clone()
+            .map(|r| {
+                let size_str = size_column(r);
+                r.record
+                    .path
+                    .chars()
+                    .count()
+                    .saturating_sub(path_width(&size_str))
+            })
+            .max()
+            .unwrap_or(0);
+        app.h_scroll = app.h_scroll.min(max_scroll);
+
+        let items: Vec<ListItem> = visible
             .enumerate()
             .map(|(i, result)| {
                 let record = &result.record;
                 let icon = if record.is_dir { "📁" } else { "📄" };
+                let pin = if result.source.is_none() && app.pins.is_pinned(record) {
+                    "★"
+                } else {
+                    " "
+                };
 
-                let size_str = record.size.map(|s| format_size(s)).unwrap_or_default();
+                let size_str = size_column(result);
+                let path = fit_path(&record.path, path_width(&size_str), app.h_scroll);
 
-                let line = format!("{} {} {}", icon, record.path, size_str);
+                let line = format!("{}{} {} {}", pin, icon, path, size_str);
 
                 let style = if i + app.scroll_offset == app.selected {
                     Style::default()
@@ -370,8 +612,16 @@ mod ui {
             })
             .collect();
 
+        let heading = if app.show_pinned {
+            "Pinned"
+        } else if app.results.first().is_some_and(|r| r.fuzzy) {
+            "Did you mean…"
+        } else {
+            "Results"
+        };
         let title = format!(
-            " Results ({} found in {:.1}ms) ",
+            " {} ({} found in {:.1}ms) ",
+            heading,
             app.results.len(),
             app.last_search_time.as_secs_f64() * 1000.0
         );
@@ -381,6 +631,59 @@ mod ui {
         f.render_widget(results, area);
     }
 
+    /// Draw the detail pane for the selected result.
+    fn draw_details(f: &mut Frame, app: &TuiApp, area: Rect) {
+        let block = Block::default().borders(Borders::ALL).title(" Details ");
+
+        let Some(result) = app.results.get(app.selected) else {
+            f.render_widget(Paragraph::new("No selection").block(block), area);
+            return;
+        };
+        let record = &result.record;
+
+        let label = Style::default().fg(Color::DarkGray);
+        let format_time = |t: Option<chrono::DateTime<chrono::Utc>>| {
+            t.map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_else(|| "-".to_string())
+        };
+
+        let size = match (record.is_dir, record.size) {
+            (true, _) => "Folder".to_string(),
+            (false, Some(size)) if record.cloud => {
+                format!("{} ({} bytes, online-only)", format_size(size), size)
+            }
+            (false, Some(size)) => format!("{} ({} bytes)", format_size(size), size),
+            (false, None) => "-".to_string(),
+        };
+
+        let lines = vec![
+            Line::from(vec![
+                Span::styled("Path      ", label),
+                Span::raw(app.path_format.apply(&record.path)),
+            ]),
+            Line::from(vec![Span::styled("Size      ", label), Span::raw(size)]),
+            Line::from(vec![
+                Span::styled("Modified  ", label),
+                Span::raw(format_time(record.modified)),
+                Span::styled("   Created  ", label),
+                Span::raw(format_time(record.created)),
+            ]),
+            Line::from(vec![
+                Span::styled("File ID   ", label),
+                Span::raw(format!("{}:{}", record.volume_id, record.id)),
+            ]),
+        ];
+
+        let details = Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false });
+        f.render_widget(details, area);
+    }
+
     /// Draw the status bar.
     fn draw_status_bar(f: &mut Frame, app: &TuiApp, area: Rect) {
         let stats = app.app.index.stats();
@@ -404,7 +707,7 @@ mod ui {
             msg.clone()
         } else {
             format!(
-                "Index: {} files, {} dirs | Filter: {} | ↑↓:Navigate Enter:Open F2:Copy Esc:Quit Ctrl+F:Files Ctrl+D:Dirs",
+                "Index: {} files, {} dirs | Filter: {} | ↑↓:Navigate ←→:Scroll Enter:Open F1:Syntax F2:Copy F3:Copy format F4:Details F5:Pin F6:Pinned Esc:Quit Ctrl+F:Files Ctrl+D:Dirs",
                 stats.total_files, stats.total_dirs, filters
             )
         };
@@ -414,20 +717,58 @@ mod ui {
         f.render_widget(status_bar, area);
     }
 
-    /// Format a file size.
-    fn format_size(size: u64) -> String {
-        const KB: u64 = 1024;
-        const MB: u64 = KB * 1024;
-        const GB: u64 = MB * 1024;
+    /// Fit a path into `width` characters.
+    ///
+    /// Unscrolled paths that are too long keep their start and end (where the
+    /// drive and file name are) with an ellipsis in the middle. When scrolled,
+    /// the path is shown from `offset` with a leading ellipsis.
+    pub(super) fn fit_path(path: &str, width: usize, offset: usize) -> String {
+        let chars: Vec<char> = path.chars().collect();
+
+        if offset == 0 {
+            if chars.len() <= width {
+                return path.to_string();
+            }
+            if width <= 1 {
+                return "…".repeat(width);
+            }
+            let keep = width - 1;
+            let head = keep / 3;
+            let tail = keep - head;
+            let mut out: String = chars[..head].iter().collect();
+            out.push('…');
+            out.extend(&chars[chars.len() - tail..]);
+            return out;
+        }
 
-        if size >= GB {
-            format!("{:.1} GB", size as f64 / GB as f64)
-        } else if size >= MB {
-            format!("{:.1} MB", size as f64 / MB as f64)
-        } else if size >= KB {
-            format!("{:.1} KB", size as f64 / KB as f64)
-        } else {
-            format!("{} B", size)
+        let mut out: String = chars.iter().skip(offset).take(width).collect();
+        if width > 0 && !out.is_empty() {
+            out.replace_range(..out.chars().next().map_or(0, char::len_utf8), "…");
         }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ui::fit_path;
+
+    #[test]
+    fn test_fit_path_middle_ellipsis() {
+        let path = "C:\\Users\\me\\Documents\\Projects\\report.docx";
+        assert_eq!(fit_path(path, 100, 0), path);
+
+        let fitted = fit_path(path, 20, 0);
+        assert_eq!(fitted.chars().count(), 20);
+        assert!(fitted.starts_with("C:\\Use"));
+        assert!(fitted.ends_with("report.docx"));
+        assert!(fitted.contains('…'));
+    }
+
+    #[test]
+    fn test_fit_path_scrolled() {
+        let path = "C:\\abcdefghij";
+        assert_eq!(fit_path(path, 5, 3), "…bcde");
+        assert_eq!(fit_path(path, 5, 100), "");
     }
 }
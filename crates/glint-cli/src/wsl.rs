@@ -0,0 +1,256 @@
+//! WSL interop.
+//!
+//! Inside WSL the NTFS index cannot be built locally, but the Windows
+//! `glint watch` service can answer queries over IPC. This module detects
+//! WSL, discovers how Windows drives are mounted (`/mnt/c`, or a custom
+//! `automount.root`), and translates paths between the two forms.
+
+use glint_core::search::{inspect_query, TokenValue};
+use std::fs;
+
+/// Returns true when running inside the Windows Subsystem for Linux.
+pub fn is_wsl() -> bool {
+    if std::env::var_os("WSL_DISTRO_NAME").is_some() {
+        return true;
+    }
+    fs::read_to_string("/proc/sys/kernel/osrelease")
+        .map(|release| release.to_lowercase().contains("microsoft"))
+        .unwrap_or(false)
+}
+
+/// Mapping between Windows drive letters and their WSL mount points.
+#[derive(Debug, Clone)]
+pub struct DriveMounts {
+    /// (uppercase drive letter, mount point without trailing slash)
+    mounts: Vec<(char, String)>,
+}
+
+impl DriveMounts {
+    /// Detect drive mounts from `/proc/mounts`, falling back to `/mnt/<drive>`.
+    pub fn detect() -> Self {
+        let contents = fs::read_to_string("/proc/mounts").unwrap_or_default();
+        Self::parse(&contents)
+    }
+
+    /// Parse drive mounts from the contents of `/proc/mounts`.
+    ///
+    /// WSL mounts drives as `9p` or `drvfs` filesystems whose source is the
+    /// drive (`C:\134`, octal-escaped) or whose options contain `path=C:\`.
+    pub fn parse(contents: &str) -> Self {
+        let mut mounts = Vec::new();
+
+        for line in contents.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 4 || !matches!(fields[2], "9p" | "drvfs") {
+                continue;
+            }
+
+            let drive = drive_letter(fields[0]).or_else(|| {
+                fields[3]
+                    .split([',', ';'])
+                    .find_map(|opt| opt.strip_prefix("path="))
+                    .and_then(drive_letter)
+            });
+
+            if let Some(drive) = drive {
+                let mount_point = unescape_mount(fields[1]);
+                mounts.push((drive, mount_point.trim_end_matches('/').to_string()));
+            }
+        }
+
+        DriveMounts { mounts }
+    }
+
+    /// Mount point for a drive letter.
+    fn mount_point(&self, drive: char) -> String {
+        let drive = drive.to_ascii_uppercase();
+        self.mounts
+            .iter()
+            .find(|(d, _)| *d == drive)
+            .map(|(_, m)| m.clone())
+            .unwrap_or_else(|| format!("/mnt/{}", drive.to_ascii_lowercase()))
+    }
+
+    /// Translate a WSL path into its Windows form, if it lives on a drive mount.
+    ///
+    /// `/mnt/c/Users/me` becomes `C:\Users\me`.
+    pub fn to_windows(&self, path: &str) -> Option<String> {
+        // Prefer the longest matching mount point
+        let detected = self
+            .mounts
+            .iter()
+            .filter(|(_, m)| is_under(path, m))
+            .max_by_key(|(_, m)| m.len())
+            .map(|(d, m)| (*d, m.len()));
+
+        let (drive, prefix_len) = detected.or_else(|| {
+            let rest = path.strip_prefix("/mnt/")?;
+            let letter = rest.chars().next()?;
+            let after = &rest[letter.len_utf8()..];
+            if letter.is_ascii_alphabetic() && (after.is_empty() || after.starts_with('/')) {
+                Some((letter.to_ascii_uppercase(), "/mnt/".len() + 1))
+            } else {
+                None
+            }
+        })?;
+
+        let rest = path[prefix_len..]
+            .trim_start_matches('/')
+            .replace('/', "\\");
+        Some(format!("{}:\\{}", drive, rest))
+    }
+
+    /// Translate a Windows path into its WSL form.
+    ///
+    /// Paths without a drive letter are returned with forward slashes.
+    pub fn to_wsl(&self, path: &str) -> String {
+        let mut chars = path.chars();
+        match (chars.next(), chars.next()) {
+            (Some(drive), Some(':')) if drive.is_ascii_alphabetic() => {
+                let mount = self.mount_point(drive);
+                let rest = path[2..].trim_start_matches('\\').replace('\\', "/");
+                if rest.is_empty() {
+                    mount
+                } else {
+                    format!("{}/{}", mount, rest)
+                }
+            }
+            _ => path.replace('\\', "/"),
+        }
+    }
+
+    /// Rewrite WSL paths in a query string into Windows paths.
+    ///
+    /// Handles folder tokens such as `in:` and bare absolute path terms,
+    /// found with the query parser's own split, and leaves everything else,
+    /// spacing included, as written.
+    pub fn translate_query(&self, query: &str) -> String {
+        let mut out = String::with_capacity(query.len());
+        let mut copied = 0;
+        for part in inspect_query(query) {
+            let text = &query[part.range.clone()];
+            let translated = match part.token {
+                Some(token) if token.value == TokenValue::Folder => {
+                    token.value_of(text).and_then(|folder| {
+                        let prefix = &text[..text.len() - folder.len()];
+                        Some(format!("{}{}", prefix, self.to_windows(folder)?))
+                    })
+                }
+                None if text.starts_with('/') => self.to_windows(text),
+                _ => None,
+            };
+            if let Some(translated) = translated {
+                out.push_str(&query[copied..part.range.start]);
+                out.push_str(&translated);
+                copied = part.range.end;
+            }
+        }
+        out.push_str(&query[copied..]);
+        out
+    }
+}
+
+/// Address of the Windows host as seen from a WSL2 (NAT mode) guest.
+///
+/// WSL2 points its resolver at the host, so the first `nameserver` entry in
+/// `/etc/resolv.conf` is the host's address on the virtual switch.
+pub fn windows_host_addr() -> Option<String> {
+    let contents = fs::read_to_string("/etc/resolv.conf").ok()?;
+    contents.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        if fields.next() == Some("nameserver") {
+            fields.next().map(|s| s.to_string())
+        } else {
+            None
+        }
+    })
+}
+
+fn is_under(path: &str, mount: &str) -> bool {
+    path.strip_prefix(mount)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Extract the drive letter from `C:`, `C:\` or `C:\134`.
+fn drive_letter(source: &str) -> Option<char> {
+    let mut chars = source.chars();
+    let letter = chars.next()?;
+    if letter.is_ascii_alphabetic() && chars.next() == Some(':') {
+        Some(letter.to_ascii_uppercase())
+    } else {
+        None
+    }
+}
+
+/// Undo the octal escaping `/proc/mounts` applies to spaces and backslashes.
+fn unescape_mount(s: &str) -> String {
+    s.replace("\\040", " ")
+        .replace("\\011", "\t")
+        .replace("\\134", "\\")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTS: &str = "\
+rootfs / rootfs rw 0 0
+C:\\134 /mnt/c 9p rw,noatime,dirsync,aname=drvfs;path=C:\\;uid=1000 0 0
+drvfs /win/data\\040drive 9p rw,relatime,aname=drvfs;path=D:\\ 0 0
+none /dev tmpfs rw 0 0
+";
+
+    #[test]
+    fn test_parse_mounts() {
+        let mounts = DriveMounts::parse(MOUNTS);
+        assert_eq!(mounts.mount_point('c'), "/mnt/c");
+        assert_eq!(mounts.mount_point('D'), "/win/data drive");
+        // Unknown drives fall back to the default automount root
+        assert_eq!(mounts.mount_point('E'), "/mnt/e");
+    }
+
+    #[test]
+    fn test_to_windows() {
+        let mounts = DriveMounts::parse(MOUNTS);
+        assert_eq!(
+            mounts.to_windows("/mnt/c/Users/me").as_deref(),
+            Some("C:\\Users\\me")
+        );
+        assert_eq!(mounts.to_windows("/mnt/c").as_deref(), Some("C:\\"));
+        assert_eq!(
+            mounts.to_windows("/win/data drive/x").as_deref(),
+            Some("D:\\x")
+        );
+        assert_eq!(mounts.to_windows("/mnt/e/a").as_deref(), Some("E:\\a"));
+        assert_eq!(mounts.to_windows("/home/me"), None);
+        assert_eq!(mounts.to_windows("/mnt/cdrom"), None);
+    }
+
+    #[test]
+    fn test_to_wsl() {
+        let mounts = DriveMounts::parse(MOUNTS);
+        assert_eq!(mounts.to_wsl("C:\\Users\\me"), "/mnt/c/Users/me");
+        assert_eq!(mounts.to_wsl("D:\\"), "/win/data drive");
+    }
+
+    #[test]
+    fn test_translate_query() {
+        let mounts = DriveMounts::parse("");
+        assert_eq!(
+            mounts.translate_query("ext:rs in:/mnt/c/src main"),
+            "ext:rs in:C:\\src main"
+        );
+        assert_eq!(mounts.translate_query("in:/home/me x"), "in:/home/me x");
+        assert_eq!(mounts.translate_query("/mnt/d/logs"), "D:\\logs");
+
+        // Spacing and quotes are left as written
+        assert_eq!(
+            mounts.translate_query("\"my  notes\"   in:/mnt/c/a  b"),
+            "\"my  notes\"   in:C:\\a  b"
+        );
+        assert_eq!(
+            mounts.translate_query("  \"/home/me/My  Docs\" "),
+            "  \"/home/me/My  Docs\" "
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! Searching the in-memory index versus the memory-mapped archive.
+//!
+//! Each index is saved once and opened as an `ArchivedView`, then searched
+//! for a common name (the limit is reached early) and a rare one (every
+//! record is scanned). The archive is scanned with the default chunk size,
+//! a larger one and as a single chunk on one thread, to guide
+//! `archive_scan::CHUNK_RECORDS`.
+//!
+//! Run with `cargo bench -p glint-core --bench archive_search`. Only 1M
+//! records are benchmarked by default; set `GLINT_BENCH_LARGE=1` to add
+//! 10M, which needs several GB of memory.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use glint_core::archive_scan::{self, CHUNK_RECORDS};
+use glint_core::archive_view::ArchivedView;
+use glint_core::{FileId, FileRecord, Index, IndexStore, SearchQuery, VolumeId, VolumeInfo};
+use tempfile::TempDir;
+
+/// Results asked for, as the GUI does.
+const LIMIT: usize = 1000;
+
+fn sizes() -> Vec<usize> {
+    let mut sizes = vec![1_000_000];
+    if std::env::var_os("GLINT_BENCH_LARGE").is_some() {
+        sizes.push(10_000_000);
+    }
+    sizes
+}
+
+fn make_index(count: usize) -> Index {
+    let extensions = ["rs", "txt", "jpg", "dll", "docx", "json"];
+    let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+    let records = (0..count)
+        .map(|i| {
+            let name = format!("document_{:08}.{}", i, extensions[i % extensions.len()]);
+            let path = format!("C:\\Users\\Files\\{:04}\\{}", i % 5000, name);
+            FileRecord::new(
+                FileId::new(i as u64 + 1),
+                None,
+                volume.id.clone(),
+                name,
+                path,
+                false,
+            )
+        })
+        .collect();
+    let index = Index::new();
+    index.add_volume_records(&volume, records);
+    index
+}
+
+fn bench_archive_search(c: &mut Criterion) {
+    let queries = [
+        ("common", SearchQuery::substring("document")),
+        ("rare", SearchQuery::substring("00999999.docx")),
+    ];
+
+    for count in sizes() {
+        let index = make_index(count);
+        let temp_dir = TempDir::new().unwrap();
+        let store = IndexStore::new(temp_dir.path());
+        store.save(&index).unwrap();
+        let view = ArchivedView::open(store.index_path()).unwrap();
+
+        let mut group = c.benchmark_group(format!("search_{}", count));
+        group.throughput(Throughput::Elements(count as u64));
+        group.sample_size(10);
+
+        for (label, query) in &queries {
+            group.bench_with_input(BenchmarkId::new("in_memory", label), query, |b, q| {
+                b.iter(|| index.search_counted(black_box(q), LIMIT).results.len())
+            });
+            for (name, chunk) in [
+                ("archive_chunked", CHUNK_RECORDS),
+                ("archive_chunked_x4", CHUNK_RECORDS * 4),
+                ("archive_single", usize::MAX),
+            ] {
+                group.bench_with_input(BenchmarkId::new(name, label), query, |b, q| {
+                    b.iter(|| archive_scan::search_chunked(&view, black_box(q), LIMIT, chunk).len())
+                });
+            }
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_archive_search);
+criterion_main!(benches);
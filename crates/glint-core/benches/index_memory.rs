@@ -0,0 +1,98 @@
+//! Heap memory held by an index of a million records.
+//!
+//! Counts the bytes allocated through the global allocator while the index
+//! is built and still held once the scanned records are consumed, so that
+//! record layouts can be compared. Allocator overhead per allocation isn't
+//! included; the number of live allocations is printed for that.
+//!
+//! Run with `cargo bench -p glint-core --bench index_memory`.
+
+use chrono::{TimeZone, Utc};
+use glint_core::{FileId, FileRecord, Index, VolumeId, VolumeInfo};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicIsize, Ordering};
+
+const RECORDS: u64 = 1_000_000;
+
+struct Counting;
+
+static BYTES: AtomicIsize = AtomicIsize::new(0);
+static ALLOCATIONS: AtomicIsize = AtomicIsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        BYTES.fetch_add(layout.size() as isize, Ordering::Relaxed);
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        BYTES.fetch_sub(layout.size() as isize, Ordering::Relaxed);
+        ALLOCATIONS.fetch_sub(1, Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        BYTES.fetch_add(
+            new_size as isize - layout.size() as isize,
+            Ordering::Relaxed,
+        );
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn make_records() -> Vec<FileRecord> {
+    let folders = [
+        "Users",
+        "Projects",
+        "node_modules",
+        "AppData",
+        "src",
+        "Documents",
+    ];
+    let modified = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    (0..RECORDS)
+        .map(|i| {
+            let name = format!(
+                "File_{:07}.{}",
+                i,
+                ["rs", "txt", "JPG", "dll"][i as usize % 4]
+            );
+            let folder = folders[i as usize % folders.len()];
+            FileRecord::new(
+                FileId::new(i + 100),
+                Some(FileId::new(5)),
+                VolumeId::new("C"),
+                name.clone(),
+                format!("C:\\{}\\{}", folder, name),
+                false,
+            )
+            .with_size(i * 37 % 1_000_000)
+            .with_modified(modified)
+        })
+        .collect()
+}
+
+fn main() {
+    let (bytes, allocations) = (
+        BYTES.load(Ordering::Relaxed),
+        ALLOCATIONS.load(Ordering::Relaxed),
+    );
+    let index = Index::new();
+    index.add_volume_records(
+        &VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS"),
+        make_records(),
+    );
+    let bytes = BYTES.load(Ordering::Relaxed) - bytes;
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    println!(
+        "{} records: {:.1} MiB in {} allocations, {} bytes a record",
+        index.len(),
+        bytes as f64 / (1024.0 * 1024.0),
+        allocations,
+        bytes / RECORDS as isize
+    );
+}
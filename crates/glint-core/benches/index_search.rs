@@ -0,0 +1,73 @@
+//! Whole-index searches over a million records.
+//!
+//! Name searches are settled by the index's name columns, the path search by
+//! its lowercase paths, for comparison.
+//!
+//! Run with `cargo bench -p glint-core --bench index_search`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use glint_core::{FileId, FileRecord, Index, SearchFilter, SearchQuery, VolumeId, VolumeInfo};
+
+const RECORDS: u64 = 1_000_000;
+
+fn make_index() -> Index {
+    let folders = [
+        "Users",
+        "Projects",
+        "node_modules",
+        "AppData",
+        "src",
+        "Documents",
+    ];
+    let records = (0..RECORDS)
+        .map(|i| {
+            let name = format!(
+                "File_{:07}.{}",
+                i,
+                ["rs", "txt", "JPG", "dll"][i as usize % 4]
+            );
+            let folder = folders[i as usize % folders.len()];
+            FileRecord::new(
+                FileId::new(i + 100),
+                None,
+                VolumeId::new("C"),
+                name.clone(),
+                format!("C:\\{}\\{}", folder, name),
+                false,
+            )
+            .with_size(i * 37 % 1_000_000)
+        })
+        .collect();
+    let index = Index::new();
+    index.add_volume_records(&VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS"), records);
+    index
+}
+
+fn bench_index_search(c: &mut Criterion) {
+    let index = make_index();
+    let mut group = c.benchmark_group("index_search");
+    group.sample_size(20);
+
+    let queries = [
+        ("name_substring", SearchQuery::substring("_00042")),
+        (
+            "name_with_filters",
+            SearchQuery::substring("file_")
+                .with_filter(SearchFilter::FilesOnly)
+                .with_filter(SearchFilter::MinSize(999_000)),
+        ),
+        (
+            "path_substring",
+            SearchQuery::substring("node_modules\\file_00042").search_in_path(true),
+        ),
+    ];
+    for (name, query) in &queries {
+        group.bench_function(*name, |b| {
+            b.iter(|| index.search_counted(black_box(query), 100).total)
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_index_search);
+criterion_main!(benches);
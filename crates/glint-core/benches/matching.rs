@@ -0,0 +1,94 @@
+//! Substring matching throughput over long paths.
+//!
+//! Compares lowercasing every candidate (what matching used to cost),
+//! `str::contains` on the cached lowercase path, and the `memmem`-based
+//! matcher behind `SearchQuery::substring`.
+//!
+//! Run with `cargo bench -p glint-core --bench matching`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use glint_core::{FileId, FileRecord, SearchQuery, VolumeId};
+
+const RECORDS: usize = 100_000;
+
+/// Deep, realistic-looking paths of 100-200 characters.
+fn make_records() -> Vec<FileRecord> {
+    let folders = [
+        "Users",
+        "Projects",
+        "node_modules",
+        "AppData",
+        "Local",
+        "Packages",
+        "src",
+        "Documents",
+        "Archive",
+        "Build",
+        "Release",
+        "Components",
+        "Assets",
+    ];
+    (0..RECORDS)
+        .map(|i| {
+            let mut path = String::from("C:");
+            for depth in 0..8 + i % 6 {
+                path.push('\\');
+                path.push_str(folders[(i / (depth + 1) + depth * 7) % folders.len()]);
+            }
+            let name = format!("File_{:06}.{}", i, ["rs", "txt", "JPG", "dll"][i % 4]);
+            path.push('\\');
+            path.push_str(&name);
+            let mut record = FileRecord::new(
+                FileId::new(i as u64),
+                None,
+                VolumeId::new("C"),
+                name,
+                path,
+                false,
+            );
+            record.init_cache();
+            record
+        })
+        .collect()
+}
+
+fn bench_path_matching(c: &mut Criterion) {
+    let records = make_records();
+    let pattern = "release\\components";
+
+    let mut group = c.benchmark_group("path_substring");
+    group.throughput(Throughput::Elements(records.len() as u64));
+
+    group.bench_function("to_lowercase_contains", |b| {
+        b.iter(|| {
+            records
+                .iter()
+                .filter(|r| r.path.to_lowercase().contains(black_box(pattern)))
+                .count()
+        })
+    });
+
+    group.bench_function("cached_str_contains", |b| {
+        b.iter(|| {
+            records
+                .iter()
+                .filter(|r| r.path_lower.contains(black_box(pattern)))
+                .count()
+        })
+    });
+
+    let query = SearchQuery::substring(pattern).search_in_path(true);
+    group.bench_function("memmem_query", |b| {
+        b.iter(|| {
+            records
+                .iter()
+                .filter(|r| query.matches(black_box(r)))
+                .count()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_path_matching);
+criterion_main!(benches);
@@ -0,0 +1,152 @@
+//! Parallel search over a memory-mapped index.
+//!
+//! An [`ArchivedView`] keeps every name in one blob. Rather than walking it
+//! front to back on one thread, the records are split into chunks that are
+//! scanned in parallel. A shared counter of matches lets every chunk stop
+//! as soon as enough results have been found anywhere, so a common query
+//! with a small limit does not pay for the whole index.
+//!
+//! Results come back in record order within each chunk and in chunk order
+//! overall, but once the limit is hit a chunk may stop before one after it,
+//! so they are *some* matches rather than strictly the first ones.
+
+use crate::archive_view::ArchivedView;
+use crate::search::{SearchQuery, SearchResult};
+use crate::types::{FileId, FileRecord, VolumeId};
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Records per chunk. Small enough to spread a million records over every
+/// core, large enough that scheduling costs stay well below matching.
+pub const CHUNK_RECORDS: usize = 64 * 1024;
+
+/// How many records a chunk scans between looks at the shared counter.
+const CHECK_EVERY: usize = 1024;
+
+/// Find up to `limit` records in `view` matching `query`.
+///
+/// Only names are stored in the archive, so the records returned have no
+/// path and `query` should not search in paths.
+pub fn search(view: &ArchivedView, query: &SearchQuery, limit: usize) -> Vec<SearchResult> {
+    search_chunked(view, query, limit, CHUNK_RECORDS)
+}
+
+/// [`search`] with chunks of `chunk_records` records; one chunk at least as
+/// large as the index scans it on a single thread.
+pub fn search_chunked(
+    view: &ArchivedView,
+    query: &SearchQuery,
+    limit: usize,
+    chunk_records: usize,
+) -> Vec<SearchResult> {
+    // Unsafe root reference lives as long as mmap
+    let root = unsafe { view.root() };
+    let count = root.is_dir.len();
+    if limit == 0 || count == 0 {
+        return Vec::new();
+    }
+
+    let found = AtomicUsize::new(0);
+    let chunk_records = chunk_records.max(1);
+    let starts: Vec<usize> = (0..count).step_by(chunk_records).collect();
+
+    let chunks: Vec<Vec<SearchResult>> = starts
+        .into_par_iter()
+        .map(|start| {
+            let mut out = Vec::new();
+            if found.load(Ordering::Relaxed) >= limit {
+                return out;
+            }
+            for i in start..(start + chunk_records).min(count) {
+                if (i - start) % CHECK_EVERY == 0 && found.load(Ordering::Relaxed) >= limit {
+                    break;
+                }
+                let offset = root.name_offsets[i] as usize;
+                let name = cstr_from_bytes(&root.names_blob[offset..]);
+                let record = FileRecord::new(
+                    FileId::new(i as u64 + 1),
+                    None,
+                    VolumeId::new("V"),
+                    name.to_string(),
+                    String::new(),
+                    root.is_dir[i] != 0,
+                );
+                if query.matches(&record) {
+                    out.push(SearchResult::new(record, 0));
+                    if found.fetch_add(1, Ordering::Relaxed) + 1 >= limit {
+                        break;
+                    }
+                }
+            }
+            out
+        })
+        .collect();
+
+    let mut results: Vec<SearchResult> = chunks.into_iter().flatten().collect();
+    results.truncate(limit);
+    results
+}
+
+/// The string up to the first NUL in `bytes`.
+fn cstr_from_bytes(bytes: &[u8]) -> &str {
+    let end = memchr::memchr(0, bytes).unwrap_or(bytes.len());
+    std::str::from_utf8(&bytes[..end]).unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::VolumeInfo;
+    use crate::index::Index;
+    use crate::persistence::IndexStore;
+    use tempfile::TempDir;
+
+    fn make_view(count: usize) -> (TempDir, ArchivedView) {
+        let temp_dir = TempDir::new().unwrap();
+        let store = IndexStore::new(temp_dir.path());
+        let index = Index::new();
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        let records = (0..count)
+            .map(|i| {
+                let name = format!("file_{:05}.{}", i, if i % 10 == 0 { "rs" } else { "txt" });
+                let path = format!("C:\\{}", name);
+                FileRecord::new(
+                    FileId::new(i as u64 + 1),
+                    None,
+                    volume.id.clone(),
+                    name,
+                    path,
+                    false,
+                )
+            })
+            .collect();
+        index.add_volume_records(&volume, records);
+        store.save(&index).unwrap();
+        let view = ArchivedView::open(store.index_path()).unwrap();
+        (temp_dir, view)
+    }
+
+    #[test]
+    fn test_search_finds_every_match() {
+        let (_dir, view) = make_view(5000);
+        let query = SearchQuery::substring(".rs");
+
+        let results = search_chunked(&view, &query, usize::MAX, 300);
+        assert_eq!(results.len(), 500);
+        assert!(results.iter().all(|r| r.record.name.ends_with(".rs")));
+
+        // One chunk scans on one thread and finds the same records
+        let single = search_chunked(&view, &query, usize::MAX, usize::MAX);
+        assert_eq!(single.len(), 500);
+    }
+
+    #[test]
+    fn test_search_stops_at_limit() {
+        let (_dir, view) = make_view(5000);
+        let query = SearchQuery::substring("file_");
+
+        assert_eq!(search_chunked(&view, &query, 25, 300).len(), 25);
+        assert_eq!(search(&view, &query, 7000).len(), 5000);
+        assert!(search(&view, &query, 0).is_empty());
+    }
+}
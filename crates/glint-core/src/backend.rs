@@ -13,9 +13,83 @@
 //! 3. Encapsulate all unsafe code within that crate
 //! 4. Register your backend with the Glint core during initialization
 
-use crate::types::{FileId, FileRecord, VolumeId};
+use crate::exclude::Exclusions;
+use crate::index::{Index, VolumeAvailability, VolumeIndexState};
+use crate::types::{to_lower, FileId, FileRecord, VolumeId};
+use std::collections::HashMap;
 use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, warn};
+
+/// Volumes with less free space than this share of their capacity are
+/// reported as low on space.
+pub const LOW_FREE_SPACE_PERCENT: f64 = 10.0;
+
+/// How often the watch service re-reads volume labels and free space.
+pub const VOLUME_REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Records in the first batch of [`FileSystemBackend::scan_batches`].
+pub const FIRST_SCAN_BATCH: usize = 4096;
+
+/// Records in the largest batches of [`FileSystemBackend::scan_batches`].
+pub const MAX_SCAN_BATCH: usize = 256 * 1024;
+
+/// How often the index is rebuilt, at most, when the backend has no change
+/// journal to follow.
+pub const NO_JOURNAL_REBUILD_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// What a backend can do and what its records hold, from
+/// [`FileSystemBackend::capabilities`].
+///
+/// Callers adapt to these rather than assume NTFS: without a change journal
+/// the index is rebuilt on a schedule instead of followed, and filters on
+/// fields scans leave empty are skipped instead of matching nothing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BackendCapabilities {
+    /// Scans read the file system's table of files in bulk (the MFT on
+    /// NTFS) instead of walking folders
+    pub supports_fast_scan: bool,
+    /// Changes can be followed, and caught up on, through a journal
+    pub supports_change_journal: bool,
+    /// Scanned records have sizes
+    pub provides_sizes: bool,
+    /// Scanned records have modification times
+    pub provides_timestamps: bool,
+    /// A file keeps its ID across scans and renames, so changes and saved
+    /// state can refer to it by ID
+    pub stable_file_ids: bool,
+}
+
+impl BackendCapabilities {
+    /// Everything a backend can offer.
+    pub fn all() -> Self {
+        BackendCapabilities {
+            supports_fast_scan: true,
+            supports_change_journal: true,
+            provides_sizes: true,
+            provides_timestamps: true,
+            stable_file_ids: true,
+        }
+    }
+
+    /// How long after a full scan the index should be rebuilt, given the
+    /// configured interval: without a change journal, rebuilds are the
+    /// only way changes reach the index, so they happen at least every
+    /// [`NO_JOURNAL_REBUILD_INTERVAL`].
+    pub fn rebuild_after(&self, configured: Option<Duration>) -> Option<Duration> {
+        if self.supports_change_journal {
+            return configured;
+        }
+        Some(
+            configured.map_or(NO_JOURNAL_REBUILD_INTERVAL, |configured| {
+                configured.min(NO_JOURNAL_REBUILD_INTERVAL)
+            }),
+        )
+    }
+}
 
 /// Information about a volume/filesystem that can be indexed.
 ///
@@ -47,6 +121,13 @@ pub struct VolumeInfo {
     /// Backend-specific state for USN journal tracking
     /// On NTFS, this stores the last processed USN and journal ID
     pub journal_state: Option<JournalState>,
+
+    /// Folder this pseudo-volume is limited to (None = the whole volume)
+    pub root: Option<String>,
+
+    /// Folders on other volumes this volume is also mounted at
+    /// (e.g., "C:\Data\" for a drive that is also "E:")
+    pub mount_folders: Vec<String>,
 }
 
 impl VolumeInfo {
@@ -65,9 +146,43 @@ impl VolumeInfo {
             free_bytes: None,
             supports_change_journal: false,
             journal_state: None,
+            root: None,
+            mount_folders: Vec::new(),
+        }
+    }
+
+    /// Create a pseudo-volume covering one folder of this volume.
+    ///
+    /// The pseudo-volume scans and watches the same device but only keeps
+    /// records under `root`. Its ID is the folder path, so several folders
+    /// of one volume can be indexed side by side.
+    pub fn subtree(&self, root: impl Into<String>) -> Self {
+        let root = root.into();
+        let root = root.trim_end_matches(['\\', '/']).to_string();
+        VolumeInfo {
+            id: VolumeId::new(&root),
+            root: Some(root),
+            mount_folders: Vec::new(),
+            ..self.clone()
         }
     }
 
+    /// Check if a path belongs to this volume's folder (always true for whole volumes).
+    pub fn contains_path(&self, path: &str) -> bool {
+        let Some(root) = &self.root else {
+            return true;
+        };
+        path.len() >= root.len()
+            && path.is_char_boundary(root.len())
+            && path[..root.len()].eq_ignore_ascii_case(root)
+            && matches!(path[root.len()..].chars().next(), None | Some('\\' | '/'))
+    }
+
+    /// Name shown to users: the folder for pseudo-volumes, else the mount point.
+    pub fn display_name(&self) -> &str {
+        self.root.as_deref().unwrap_or(&self.mount_point)
+    }
+
     /// Set the volume label
     pub fn with_label(mut self, label: impl Into<String>) -> Self {
         self.label = Some(label.into());
@@ -81,15 +196,110 @@ impl VolumeInfo {
         self
     }
 
+    /// Set the folders this volume is also mounted at
+    pub fn with_mount_folders(mut self, folders: Vec<String>) -> Self {
+        self.mount_folders = folders;
+        self
+    }
+
     /// Mark this volume as supporting change notifications
     pub fn with_change_journal_support(mut self, supported: bool) -> Self {
         self.supports_change_journal = supported;
         self
     }
+
+    /// Free space as a percentage of capacity, if both are known.
+    pub fn free_percent(&self) -> Option<f64> {
+        match (self.free_bytes, self.total_bytes) {
+            (Some(free), Some(total)) if total > 0 => Some(free as f64 * 100.0 / total as f64),
+            _ => None,
+        }
+    }
+
+    /// Check if the volume has less than [`LOW_FREE_SPACE_PERCENT`] free.
+    pub fn is_low_on_space(&self) -> bool {
+        self.free_percent()
+            .is_some_and(|percent| percent < LOW_FREE_SPACE_PERCENT)
+    }
+
+    /// Check if `current` (from a fresh `list_volumes()`) describes the
+    /// device this volume, or this folder volume, lives on.
+    pub fn is_same_device(&self, current: &VolumeInfo) -> bool {
+        if self.root.is_none() {
+            return self.id == current.id;
+        }
+        self.mount_point
+            .trim_end_matches(['\\', '/'])
+            .eq_ignore_ascii_case(current.mount_point.trim_end_matches(['\\', '/']))
+    }
+
+    /// Key shared by this volume and all folder volumes on the same device.
+    pub fn device_key(&self) -> String {
+        to_lower(self.mount_point.trim_end_matches(['\\', '/']))
+    }
+
+    /// Take the label, capacity and mount folders from a fresh listing of
+    /// the same device.
+    ///
+    /// Returns true if anything changed.
+    pub fn refresh_from(&mut self, current: &VolumeInfo) -> bool {
+        let changed = self.label != current.label
+            || self.total_bytes != current.total_bytes
+            || self.free_bytes != current.free_bytes
+            || (self.root.is_none() && self.mount_folders != current.mount_folders);
+        self.label = current.label.clone();
+        self.total_bytes = current.total_bytes;
+        self.free_bytes = current.free_bytes;
+        if self.root.is_none() {
+            self.mount_folders = current.mount_folders.clone();
+        }
+        changed
+    }
+}
+
+/// Find folder volumes that lie in another indexed volume's mount folder.
+///
+/// A drive that is both "E:" and mounted at "C:\Data" has its files
+/// indexed twice if "C:\Data" (or a folder inside it) is also indexed as a
+/// folder volume. Returns (mounted volume, folder volume) pairs.
+pub fn mount_overlaps(volumes: &[VolumeInfo]) -> Vec<(&VolumeInfo, &VolumeInfo)> {
+    let mut overlaps = Vec::new();
+    for mounted in volumes {
+        for folder in volumes.iter().filter(|v| v.root.is_some()) {
+            let inside = mounted.mount_folders.iter().any(|mount| {
+                let mount = mount.trim_end_matches(['\\', '/']);
+                mounted.subtree(mount).contains_path(folder.display_name())
+            });
+            if inside {
+                overlaps.push((mounted, folder));
+            }
+        }
+    }
+    overlaps
+}
+
+/// Find the volume holding `folder` and return a folder volume for it.
+///
+/// A volume's own root gives back the whole volume. Returns None if no
+/// volume's mount point contains the folder.
+pub fn folder_volume(volumes: &[VolumeInfo], folder: &str) -> Option<VolumeInfo> {
+    let folder = folder.trim_end_matches(['\\', '/']);
+    let volume = volumes
+        .iter()
+        .filter(|v| {
+            let mount = v.mount_point.trim_end_matches(['\\', '/']);
+            v.subtree(mount).contains_path(folder)
+        })
+        .max_by_key(|v| v.mount_point.len())?;
+    if folder.eq_ignore_ascii_case(volume.mount_point.trim_end_matches(['\\', '/'])) {
+        Some(volume.clone())
+    } else {
+        Some(volume.subtree(folder))
+    }
 }
 
 /// State for tracking journal position (used for USN journal on NTFS)
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct JournalState {
     /// Journal ID (changes if journal is deleted and recreated)
     pub journal_id: u64,
@@ -172,6 +382,15 @@ pub struct ChangeEvent {
 
     /// USN (Update Sequence Number) for NTFS, or other sequence marker
     pub sequence: i64,
+
+    /// Whether the file is an online-only cloud placeholder, for creates
+    /// and attribute changes
+    pub cloud: bool,
+
+    /// Whether the system made the change while maintaining the file, such
+    /// as when defragmenting, deduplicating or replicating it, rather than
+    /// a user or application (NTFS reports this in the USN source info)
+    pub maintenance: bool,
 }
 
 impl ChangeEvent {
@@ -194,6 +413,8 @@ impl ChangeEvent {
             new_parent_id: None,
             is_dir,
             sequence,
+            cloud: false,
+            maintenance: false,
         }
     }
 
@@ -216,6 +437,8 @@ impl ChangeEvent {
             new_parent_id: None,
             is_dir,
             sequence,
+            cloud: false,
+            maintenance: false,
         }
     }
 
@@ -240,8 +463,24 @@ impl ChangeEvent {
             new_parent_id,
             is_dir,
             sequence,
+            cloud: false,
+            maintenance: false,
         }
     }
+
+    /// Whether the event only reflects maintenance by the system and can be
+    /// skipped without the index missing anything users would see.
+    ///
+    /// Creations, deletions and renames always count, whoever made them.
+    /// So do attribute changes, which is how a cloud file going online-only
+    /// shows up.
+    pub fn is_maintenance_noise(&self) -> bool {
+        self.maintenance
+            && matches!(
+                self.kind,
+                ChangeKind::Modified | ChangeKind::SecurityChanged
+            )
+    }
 }
 
 /// Handler for filesystem change events.
@@ -257,6 +496,19 @@ pub trait ChangeHandler: Send + Sync {
 
     /// Called when an error occurs during monitoring
     fn on_error(&self, volume_id: VolumeId, error: String);
+
+    /// Called when the volume's device went away, such as an unplugged
+    /// drive. The watcher stops; the volume is watched again once it is
+    /// back (see [`VolumeResumer`]).
+    fn on_volume_offline(&self, volume_id: VolumeId, reason: String) {
+        let _ = (volume_id, reason);
+    }
+
+    /// Called once every change up to `state` has been reported, so the
+    /// position can be saved with the index and watching resumed from it.
+    fn on_position(&self, volume_id: VolumeId, state: JournalState) {
+        let _ = (volume_id, state);
+    }
 }
 
 /// A channel-based change handler implementation
@@ -272,6 +524,38 @@ pub enum ChangeHandlerMessage {
     JournalReset { volume_id: VolumeId, reason: String },
     /// An error occurred
     Error { volume_id: VolumeId, error: String },
+    /// The volume's device went away
+    VolumeOffline { volume_id: VolumeId, reason: String },
+    /// Every change up to this journal position has been sent
+    Position {
+        volume_id: VolumeId,
+        state: JournalState,
+    },
+}
+
+impl ChangeHandlerMessage {
+    /// Apply the message to `index` the way the service does: changes are
+    /// applied, journal resets mark the volume for rescan, volumes that
+    /// went away are marked offline and journal positions are kept for the
+    /// next save. Errors are only logged.
+    pub fn apply(self, index: &Index) {
+        match self {
+            ChangeHandlerMessage::Change(event) => index.apply_change(event),
+            ChangeHandlerMessage::JournalReset { volume_id, reason } => {
+                warn!(volume = %volume_id, reason = %reason, "Journal reset, index may be stale");
+                index.mark_needs_rescan(&volume_id, &reason);
+            }
+            ChangeHandlerMessage::Error { volume_id, error } => {
+                error!(volume = %volume_id, error = %error, "Watch error");
+            }
+            ChangeHandlerMessage::VolumeOffline { volume_id, reason } => {
+                index.set_volume_offline(&volume_id, &reason);
+            }
+            ChangeHandlerMessage::Position { volume_id, state } => {
+                index.update_journal_state(&volume_id, state);
+            }
+        }
+    }
 }
 
 impl ChannelChangeHandler {
@@ -304,6 +588,18 @@ impl ChangeHandler for ChannelChangeHandler {
             .sender
             .send(ChangeHandlerMessage::Error { volume_id, error });
     }
+
+    fn on_volume_offline(&self, volume_id: VolumeId, reason: String) {
+        let _ = self
+            .sender
+            .send(ChangeHandlerMessage::VolumeOffline { volume_id, reason });
+    }
+
+    fn on_position(&self, volume_id: VolumeId, state: JournalState) {
+        let _ = self
+            .sender
+            .send(ChangeHandlerMessage::Position { volume_id, state });
+    }
 }
 
 /// Abstract trait for filesystem backends.
@@ -349,6 +645,33 @@ pub trait FileSystemBackend: Send + Sync {
         progress: Option<Arc<dyn ScanProgress>>,
     ) -> anyhow::Result<Vec<FileRecord>>;
 
+    /// Scan a volume like `full_scan`, handing records over in batches.
+    ///
+    /// Callers add each batch to a live index as it arrives, so searches
+    /// find files before the scan is done. Batches start small, to show the
+    /// first results quickly, and grow so large volumes aren't slowed down
+    /// by a re-search per batch.
+    ///
+    /// The default implementation splits the result of `full_scan`; backends
+    /// that find records incrementally can stream them instead.
+    fn scan_batches(
+        &self,
+        volume: &VolumeInfo,
+        progress: Option<Arc<dyn ScanProgress>>,
+        on_batch: &mut dyn FnMut(Vec<FileRecord>),
+    ) -> anyhow::Result<()> {
+        let mut records = self.full_scan(volume, progress)?.into_iter();
+        let mut batch_size = FIRST_SCAN_BATCH;
+        loop {
+            let batch: Vec<FileRecord> = records.by_ref().take(batch_size).collect();
+            if batch.is_empty() {
+                return Ok(());
+            }
+            on_batch(batch);
+            batch_size = (batch_size * 2).min(MAX_SCAN_BATCH);
+        }
+    }
+
     /// Start monitoring a volume for changes.
     ///
     /// This method starts a background monitoring loop that:
@@ -369,11 +692,35 @@ pub trait FileSystemBackend: Send + Sync {
         handler: Arc<dyn ChangeHandler>,
     ) -> anyhow::Result<WatchHandle>;
 
+    /// Report the changes recorded since `volume.journal_state`, then return.
+    ///
+    /// This is the one-shot counterpart of `watch_changes`: it calls the
+    /// handler for every change made while nothing was watching (or
+    /// `on_journal_reset` if they can't be recovered) and returns the journal
+    /// state to resume from next time. Returns None if the volume has no
+    /// saved journal state to catch up from.
+    fn catch_up(
+        &self,
+        volume: &VolumeInfo,
+        handler: &dyn ChangeHandler,
+    ) -> anyhow::Result<Option<JournalState>> {
+        let _ = (volume, handler);
+        anyhow::bail!("The {} backend cannot catch up on changes", self.name())
+    }
+
     /// Get the current journal state for a volume.
     ///
     /// This is used to save the position for later resumption.
     fn get_journal_state(&self, volume: &VolumeInfo) -> anyhow::Result<Option<JournalState>>;
 
+    /// What this backend can do and what its records hold.
+    ///
+    /// The default claims nothing, so a new backend is treated cautiously
+    /// until it says otherwise.
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities::default()
+    }
+
     /// Get the backend name (e.g., "ntfs", "ext4")
     fn name(&self) -> &'static str;
 }
@@ -420,11 +767,288 @@ impl Drop for WatchHandle {
     }
 }
 
+/// Handle for the background thread that keeps volume metadata current.
+///
+/// Labels and free space captured at index time go stale; the refresher
+/// re-lists the backend's volumes periodically and updates the index,
+/// including which volumes are offline (see [`refresh_volumes`]).
+/// It stops when the handle is dropped.
+pub struct VolumeRefresher {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl VolumeRefresher {
+    /// Refresh the volumes of `index` from `backend` every `interval`.
+    pub fn spawn(
+        backend: Arc<dyn FileSystemBackend>,
+        index: Arc<Index>,
+        interval: Duration,
+    ) -> std::io::Result<Self> {
+        Self::spawn_with(backend, index, interval, None)
+    }
+
+    /// Like [`VolumeRefresher::spawn`], also catching up on and watching
+    /// volumes that come back with `resumer`.
+    pub fn spawn_resuming(
+        backend: Arc<dyn FileSystemBackend>,
+        index: Arc<Index>,
+        interval: Duration,
+        resumer: VolumeResumer,
+    ) -> std::io::Result<Self> {
+        Self::spawn_with(backend, index, interval, Some(resumer))
+    }
+
+    fn spawn_with(
+        backend: Arc<dyn FileSystemBackend>,
+        index: Arc<Index>,
+        interval: Duration,
+        mut resumer: Option<VolumeResumer>,
+    ) -> std::io::Result<Self> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = Arc::clone(&stop);
+
+        let thread = thread::Builder::new()
+            .name("glint-volumes".to_string())
+            .spawn(move || {
+                let tick = interval.min(Duration::from_secs(1));
+                let mut last = Instant::now();
+                while !stop_flag.load(Ordering::Acquire) {
+                    thread::sleep(tick);
+                    if last.elapsed() < interval {
+                        continue;
+                    }
+                    last = Instant::now();
+                    refresh_volumes(backend.as_ref(), &index, resumer.as_mut());
+                }
+            })?;
+
+        Ok(VolumeRefresher {
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Stop refreshing and wait for the thread to exit.
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for VolumeRefresher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Update the volumes of `index` from a fresh listing of `backend`'s
+/// volumes: labels and free space, and which volumes are offline (see
+/// [`Index::update_availability`]). Volumes that came back are resumed
+/// with `resumer`, if given, and otherwise stay stale.
+pub fn refresh_volumes(
+    backend: &dyn FileSystemBackend,
+    index: &Index,
+    resumer: Option<&mut VolumeResumer>,
+) {
+    let volumes = match backend.list_volumes() {
+        Ok(volumes) => volumes,
+        Err(e) => {
+            warn!(error = %e, "Failed to refresh volume metadata");
+            return;
+        }
+    };
+    let changed = index.refresh_volume_info(&volumes);
+    debug!(changed, "Volume metadata refreshed");
+
+    let back = index.update_availability(&volumes);
+    if let Some(resumer) = resumer {
+        resumer.forget_offline(index);
+        for state in back {
+            resumer.resume(backend, index, state);
+        }
+    }
+}
+
+/// Brings volumes that come back after going offline up to date: catches
+/// up on the changes made while they were away, then watches them again.
+///
+/// Changes go to the handler the other watchers use. Watchers started here
+/// are kept until their volume goes offline again or the resumer is
+/// dropped.
+pub struct VolumeResumer {
+    handler: Arc<dyn ChangeHandler>,
+    /// Watchers of resumed volumes, by volume ID
+    watchers: HashMap<String, WatchHandle>,
+}
+
+impl VolumeResumer {
+    pub fn new(handler: Arc<dyn ChangeHandler>) -> Self {
+        VolumeResumer {
+            handler,
+            watchers: HashMap::new(),
+        }
+    }
+
+    /// Catch up on the stale volume of `state` and watch it. If that
+    /// fails, it is marked offline again, to be retried at the next
+    /// refresh.
+    pub fn resume(
+        &mut self,
+        backend: &dyn FileSystemBackend,
+        index: &Index,
+        state: VolumeIndexState,
+    ) {
+        let id = state.info.id.clone();
+        let mut volume = state.info;
+        volume.journal_state = state.journal_state;
+        match backend.catch_up(&volume, self.handler.as_ref()) {
+            Ok(Some(journal_state)) => {
+                // Sent after the changes, so it is saved once they are applied
+                self.handler.on_position(id.clone(), journal_state.clone());
+                volume.journal_state = Some(journal_state);
+            }
+            Ok(None) => {
+                index.mark_needs_rescan(&id, "changed while offline, without a journal position");
+            }
+            Err(e) => {
+                index.set_volume_offline(&id, &format!("cannot catch up: {}", e));
+                return;
+            }
+        }
+        match backend.watch_changes(volume, Arc::clone(&self.handler)) {
+            Ok(handle) => {
+                self.watchers.insert(id.as_str().to_string(), handle);
+                index.set_volume_online(&id);
+            }
+            Err(e) => {
+                index.set_volume_offline(&id, &format!("cannot watch: {}", e));
+            }
+        }
+    }
+
+    /// Stop the watchers of volumes that went offline again.
+    fn forget_offline(&mut self, index: &Index) {
+        let offline: Vec<String> = index
+            .volume_states()
+            .into_iter()
+            .filter(|state| state.availability == VolumeAvailability::Offline)
+            .map(|state| state.info.id.as_str().to_string())
+            .collect();
+        self.watchers.retain(|id, _| !offline.contains(id));
+    }
+}
+
+/// Scan every volume of `index` again and swap the result in with
+/// [`Index::rebuild_with`], leaving out what `exclusions` exclude.
+///
+/// Searches keep using the old records until every volume is scanned.
+/// `stopped` is checked between volumes; once it returns true the rebuild
+/// is abandoned and the index left as it was, as it is if a scan fails.
+pub fn rebuild_index(
+    backend: &dyn FileSystemBackend,
+    index: &Index,
+    exclusions: Exclusions,
+    stopped: impl Fn() -> bool,
+) -> anyhow::Result<()> {
+    index.rebuild_with(|| {
+        let rebuilt = Index::new();
+        rebuilt.set_cache_lower_paths(index.caches_lower_paths());
+        rebuilt.set_exclusions(exclusions);
+        for state in index.volume_states() {
+            if stopped() {
+                anyhow::bail!("Rebuild cancelled");
+            }
+            let mut volume = state.info;
+            volume.journal_state = backend.get_journal_state(&volume).unwrap_or_else(|e| {
+                warn!(volume = %volume.display_name(), error = %e, "No journal state");
+                None
+            });
+            let records = backend.full_scan(&volume, None)?;
+            rebuilt.add_volume_records(&volume, records);
+        }
+        Ok(rebuilt)
+    })
+}
+
+/// Whether `index` should be rebuilt: a volume lost track of its changes
+/// and needs a rescan, or, with `max_age`, a volume was last scanned in
+/// full longer ago than that.
+pub fn rebuild_due(index: &Index, max_age: Option<Duration>) -> bool {
+    index.volume_states().iter().any(|state| {
+        let expired = max_age
+            .zip(state.last_full_scan)
+            .is_some_and(|(max_age, scanned)| {
+                (chrono::Utc::now() - scanned)
+                    .to_std()
+                    .is_ok_and(|age| age > max_age)
+            });
+        state.needs_rescan || expired
+    })
+}
+
+/// How far a scan has got through the entries a backend expects to visit.
+///
+/// Backends that can tell the size of a volume's file table up front (the
+/// MFT on NTFS) report this, so callers can show a percentage and the time
+/// left. `total` is an estimate: `done` can pass it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScanEstimate {
+    /// Entries gone through so far, including unused ones
+    pub done: u64,
+    /// Entries the backend expects to go through
+    pub total: u64,
+    /// Time since the scan started
+    pub elapsed: Duration,
+}
+
+impl ScanEstimate {
+    /// Share of the scan done, from 0.0 to 1.0.
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        (self.done as f64 / self.total as f64).min(1.0)
+    }
+
+    /// Time left at the rate so far (None until anything is done).
+    pub fn remaining(&self) -> Option<Duration> {
+        let fraction = self.fraction();
+        if fraction <= 0.0 {
+            return None;
+        }
+        Some(self.elapsed.mul_f64((1.0 - fraction) / fraction))
+    }
+}
+
+impl fmt::Display for ScanEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.0}%", self.fraction() * 100.0)?;
+        if let Some(remaining) = self.remaining() {
+            write!(f, ", about {}s left", remaining.as_secs().max(1))?;
+        }
+        Ok(())
+    }
+}
+
 /// Progress reporting for scan operations
 pub trait ScanProgress: Send + Sync {
     /// Called periodically during scanning with the current count
     fn on_progress(&self, files_scanned: u64, dirs_scanned: u64);
 
+    /// Called periodically by backends that know roughly how much is left.
+    fn on_estimate(&self, estimate: ScanEstimate) {
+        let _ = estimate;
+    }
+
+    /// Called by backends with more than one way to scan, naming the one used
+    /// (e.g. "mft" or "recursive").
+    fn on_method(&self, method: &'static str) {
+        let _ = method;
+    }
+
     /// Called when scanning is complete
     fn on_complete(&self, total_files: u64, total_dirs: u64);
 }
@@ -452,6 +1076,14 @@ impl ScanProgress for LoggingProgress {
         );
     }
 
+    fn on_estimate(&self, estimate: ScanEstimate) {
+        tracing::debug!(
+            volume = %self.volume,
+            progress = %estimate,
+            "Scanning progress"
+        );
+    }
+
     fn on_complete(&self, total_files: u64, total_dirs: u64) {
         tracing::info!(
             volume = %self.volume,
@@ -462,6 +1094,94 @@ impl ScanProgress for LoggingProgress {
     }
 }
 
+/// A progress report sent by [`ProgressChannel`], tagged with its volume.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProgressEvent {
+    Progress {
+        volume: String,
+        files: u64,
+        dirs: u64,
+    },
+    Estimate {
+        volume: String,
+        estimate: ScanEstimate,
+    },
+    Method {
+        volume: String,
+        method: &'static str,
+    },
+    Complete {
+        volume: String,
+        files: u64,
+        dirs: u64,
+    },
+}
+
+/// A progress reporter that sends every report over a channel.
+///
+/// Scans run on worker threads; this lets a UI loop read their progress
+/// without sharing state with them. One channel can carry the scans of
+/// several volumes, see [`ProgressChannel::for_volume`].
+#[derive(Clone)]
+pub struct ProgressChannel {
+    volume: String,
+    sender: crossbeam_channel::Sender<ProgressEvent>,
+}
+
+impl ProgressChannel {
+    /// Create a channel and a reporter for the scan of `volume`.
+    pub fn new(volume: impl Into<String>) -> (Self, crossbeam_channel::Receiver<ProgressEvent>) {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let volume = volume.into();
+        (ProgressChannel { volume, sender }, receiver)
+    }
+
+    /// A reporter for the scan of another volume on the same channel.
+    pub fn for_volume(&self, volume: impl Into<String>) -> Self {
+        ProgressChannel {
+            volume: volume.into(),
+            sender: self.sender.clone(),
+        }
+    }
+
+    fn send(&self, event: ProgressEvent) {
+        // Nobody listening is fine; the scan goes on
+        let _ = self.sender.send(event);
+    }
+}
+
+impl ScanProgress for ProgressChannel {
+    fn on_progress(&self, files_scanned: u64, dirs_scanned: u64) {
+        self.send(ProgressEvent::Progress {
+            volume: self.volume.clone(),
+            files: files_scanned,
+            dirs: dirs_scanned,
+        });
+    }
+
+    fn on_estimate(&self, estimate: ScanEstimate) {
+        self.send(ProgressEvent::Estimate {
+            volume: self.volume.clone(),
+            estimate,
+        });
+    }
+
+    fn on_method(&self, method: &'static str) {
+        self.send(ProgressEvent::Method {
+            volume: self.volume.clone(),
+            method,
+        });
+    }
+
+    fn on_complete(&self, total_files: u64, total_dirs: u64) {
+        self.send(ProgressEvent::Complete {
+            volume: self.volume.clone(),
+            files: total_files,
+            dirs: total_dirs,
+        });
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -477,6 +1197,103 @@ mod tests {
         assert_eq!(vol.mount_point, "C:");
         assert_eq!(vol.label, Some("System".to_string()));
         assert!(vol.supports_change_journal);
+        assert_eq!(vol.free_percent(), Some(20.0));
+        assert!(!vol.is_low_on_space());
+    }
+
+    #[test]
+    fn test_refresh_from() {
+        let mut vol = VolumeInfo::new(VolumeId::new("1234ABCD"), "D:\\", "NTFS")
+            .with_label("Data")
+            .with_capacity(1000, 500);
+        let current = VolumeInfo::new(VolumeId::new("1234ABCD"), "D:\\", "NTFS")
+            .with_label("Backup")
+            .with_capacity(1000, 50);
+
+        assert!(vol.is_same_device(&current));
+        assert!(vol.refresh_from(&current));
+        assert_eq!(vol.label.as_deref(), Some("Backup"));
+        assert!(vol.is_low_on_space());
+        assert!(!vol.refresh_from(&current));
+
+        // Folder volumes match their device by mount point
+        let folder = vol.subtree("D:\\Projects");
+        assert!(folder.is_same_device(&current));
+        let other = VolumeInfo::new(VolumeId::new("1234ABCD"), "E:", "NTFS");
+        assert!(!folder.is_same_device(&other));
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let hour = Duration::from_secs(3600);
+        let day = hour * 24;
+        let all = BackendCapabilities::all();
+        assert_eq!(all.rebuild_after(None), None);
+        assert_eq!(all.rebuild_after(Some(day)), Some(day));
+
+        // Without a journal, rebuilds are what keeps the index current
+        let walk = BackendCapabilities {
+            supports_change_journal: false,
+            ..all
+        };
+        assert_eq!(walk.rebuild_after(None), Some(NO_JOURNAL_REBUILD_INTERVAL));
+        assert_eq!(
+            walk.rebuild_after(Some(day)),
+            Some(NO_JOURNAL_REBUILD_INTERVAL)
+        );
+        assert_eq!(walk.rebuild_after(Some(hour / 4)), Some(hour / 4));
+    }
+
+    #[test]
+    fn test_subtree() {
+        let vol = VolumeInfo::new(VolumeId::new("D"), "D:", "NTFS");
+        let projects = vol.subtree("D:\\Projects\\");
+
+        assert_eq!(projects.id.as_str(), "D:\\Projects");
+        assert_eq!(projects.mount_point, "D:");
+        assert_eq!(projects.display_name(), "D:\\Projects");
+        assert!(projects.contains_path("D:\\Projects"));
+        assert!(projects.contains_path("d:\\projects\\glint\\Cargo.toml"));
+        assert!(!projects.contains_path("D:\\ProjectsOld\\a.txt"));
+        assert!(!projects.contains_path("D:\\Photos"));
+        assert!(vol.contains_path("D:\\Photos"));
+    }
+
+    #[test]
+    fn test_mount_overlaps() {
+        let c = VolumeInfo::new(VolumeId::new("C"), "C:\\", "NTFS");
+        let e = VolumeInfo::new(VolumeId::new("E"), "E:\\", "NTFS")
+            .with_mount_folders(vec!["C:\\Data\\".to_string()]);
+        let data = c.subtree("c:\\data\\photos");
+        let projects = c.subtree("C:\\Projects");
+        let volumes = vec![c.clone(), e.clone(), data, projects];
+
+        let overlaps = mount_overlaps(&volumes);
+        assert_eq!(overlaps.len(), 1);
+        assert_eq!(overlaps[0].0.id, e.id);
+        assert_eq!(overlaps[0].1.display_name(), "c:\\data\\photos");
+
+        // Folder volumes share their whole volume's device
+        assert_eq!(volumes[2].device_key(), c.device_key());
+        assert_ne!(e.device_key(), c.device_key());
+    }
+
+    #[test]
+    fn test_folder_volume() {
+        let volumes = vec![
+            VolumeInfo::new(VolumeId::new("C"), "C:\\", "NTFS"),
+            VolumeInfo::new(VolumeId::new("D"), "D:\\", "NTFS"),
+        ];
+
+        let folder = folder_volume(&volumes, "d:\\Projects\\").unwrap();
+        assert_eq!(folder.root.as_deref(), Some("d:\\Projects"));
+        assert_eq!(folder.mount_point, "D:\\");
+
+        let whole = folder_volume(&volumes, "C:\\").unwrap();
+        assert_eq!(whole.id.as_str(), "C");
+        assert!(whole.root.is_none());
+
+        assert!(folder_volume(&volumes, "E:\\Backup").is_none());
     }
 
     #[test]
@@ -495,6 +1312,29 @@ mod tests {
         assert!(!event.is_dir);
     }
 
+    #[test]
+    fn test_maintenance_noise() {
+        let mut event = ChangeEvent::created(
+            VolumeId::new("C"),
+            FileId::new(100),
+            None,
+            "disk.vhdx".to_string(),
+            false,
+            1,
+        );
+        event.maintenance = true;
+        assert!(!event.is_maintenance_noise());
+
+        event.kind = ChangeKind::Modified;
+        assert!(event.is_maintenance_noise());
+        event.kind = ChangeKind::AttributeChanged;
+        assert!(!event.is_maintenance_noise());
+
+        event.kind = ChangeKind::Modified;
+        event.maintenance = false;
+        assert!(!event.is_maintenance_noise());
+    }
+
     #[test]
     fn test_channel_handler() {
         let (handler, receiver) = ChannelChangeHandler::new();
@@ -510,5 +1350,167 @@ mod tests {
 
         let msg = receiver.try_recv().unwrap();
         assert!(matches!(msg, ChangeHandlerMessage::Change(_)));
+
+        handler.on_position(VolumeId::new("C"), JournalState::new(7, 42));
+        match receiver.try_recv().unwrap() {
+            ChangeHandlerMessage::Position { volume_id, state } => {
+                assert_eq!(volume_id, VolumeId::new("C"));
+                assert_eq!(state, JournalState::new(7, 42));
+            }
+            _ => panic!("expected a position"),
+        }
+    }
+
+    #[test]
+    fn test_progress_channel() {
+        let (progress, receiver) = ProgressChannel::new("C:");
+        progress.on_method("mft");
+        progress.on_progress(10, 2);
+        progress.for_volume("D:").on_complete(5, 1);
+
+        let events: Vec<_> = receiver.try_iter().collect();
+        assert_eq!(
+            events,
+            [
+                ProgressEvent::Method {
+                    volume: "C:".to_string(),
+                    method: "mft"
+                },
+                ProgressEvent::Progress {
+                    volume: "C:".to_string(),
+                    files: 10,
+                    dirs: 2
+                },
+                ProgressEvent::Complete {
+                    volume: "D:".to_string(),
+                    files: 5,
+                    dirs: 1
+                },
+            ]
+        );
+
+        // Reports after the receiver is gone are dropped
+        drop(receiver);
+        progress.on_progress(20, 4);
+    }
+
+    #[test]
+    fn test_rebuild_index() {
+        use crate::mock::{MockBackend, MockVolume};
+        use crate::search::SearchQuery;
+
+        let backend = MockBackend::new().with_volume(
+            MockVolume::new("C")
+                .with_dir(5, None, "src")
+                .with_file(10, Some(5), "main.rs", 120),
+        );
+        let volume = backend.list_volumes().unwrap().remove(0);
+        let index = Index::new();
+        index.add_volume_records(&volume, backend.full_scan(&volume, None).unwrap());
+        let count = |name: &str| index.search(&SearchQuery::substring(name)).len();
+
+        assert!(!rebuild_due(&index, None));
+        index.mark_needs_rescan(&volume.id, "journal reset");
+        assert!(rebuild_due(&index, None));
+
+        let mut records = backend.full_scan(&volume, None).unwrap();
+        records[1] = FileRecord::new(
+            FileId::new(12),
+            Some(FileId::new(5)),
+            volume.id.clone(),
+            "app.rs".to_string(),
+            "C:\\src\\app.rs".to_string(),
+            false,
+        );
+        backend.set_records("C", records);
+
+        // Stopping leaves the old records
+        assert!(rebuild_index(&backend, &index, Exclusions::default(), || true).is_err());
+        assert_eq!(count("main.rs"), 1);
+
+        rebuild_index(&backend, &index, Exclusions::default(), || false).unwrap();
+        assert_eq!(count("main.rs"), 0);
+        assert_eq!(count("app.rs"), 1);
+        assert!(!rebuild_due(&index, None));
+
+        let two_hours_ago = chrono::Utc::now() - chrono::Duration::hours(2);
+        index.restore_update_times(&volume.id, Some(two_hours_ago), None);
+        assert!(rebuild_due(&index, Some(Duration::from_secs(3600))));
+        assert!(!rebuild_due(&index, Some(Duration::from_secs(3 * 3600))));
+    }
+
+    #[test]
+    fn test_refresh_volumes_resumes() {
+        use crate::mock::{MockBackend, MockVolume};
+        use crate::search::SearchQuery;
+
+        let backend = MockBackend::new().with_volume(
+            MockVolume::new("C")
+                .with_dir(5, None, "src")
+                .then_create(11, Some(5), "lib.rs", false),
+        );
+        let mut volume = backend.list_volumes().unwrap().remove(0);
+        volume.journal_state = Some(JournalState::new(1, 0));
+        let index = Index::new();
+        index.add_volume_records(&volume, backend.full_scan(&volume, None).unwrap());
+        let (handler, receiver) = ChannelChangeHandler::new();
+        let mut resumer = VolumeResumer::new(Arc::new(handler));
+        let availability = || index.volume_states()[0].availability;
+
+        backend.set_mounted("C", false);
+        refresh_volumes(&backend, &index, Some(&mut resumer));
+        assert_eq!(availability(), VolumeAvailability::Offline);
+        assert!(receiver.try_recv().is_err());
+
+        // Back: the change made meanwhile is caught up on, then watched
+        backend.set_mounted("C", true);
+        refresh_volumes(&backend, &index, Some(&mut resumer));
+        assert_eq!(availability(), VolumeAvailability::Online);
+        assert!(resumer.watchers.contains_key("C"));
+        while let Ok(message) = receiver.try_recv() {
+            match message {
+                ChangeHandlerMessage::Change(event) => index.apply_change(event),
+                ChangeHandlerMessage::Position { volume_id, state } => {
+                    index.update_journal_state(&volume_id, state);
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(index.search(&SearchQuery::substring("lib.rs")).len(), 1);
+        assert_eq!(
+            index.volume_states()[0].journal_state,
+            Some(JournalState::new(1, 1))
+        );
+
+        backend.set_mounted("C", false);
+        refresh_volumes(&backend, &index, Some(&mut resumer));
+        assert!(resumer.watchers.is_empty());
+    }
+
+    #[test]
+    fn test_scan_estimate() {
+        let estimate = ScanEstimate {
+            done: 250,
+            total: 1000,
+            elapsed: Duration::from_secs(10),
+        };
+        assert_eq!(estimate.fraction(), 0.25);
+        assert_eq!(estimate.remaining(), Some(Duration::from_secs(30)));
+        assert_eq!(estimate.to_string(), "25%, about 30s left");
+
+        let starting = ScanEstimate {
+            done: 0,
+            ..estimate
+        };
+        assert_eq!(starting.remaining(), None);
+        assert_eq!(starting.to_string(), "0%");
+
+        // The total is only an estimate
+        let over = ScanEstimate {
+            done: 1200,
+            ..estimate
+        };
+        assert_eq!(over.fraction(), 1.0);
+        assert_eq!(over.remaining(), Some(Duration::ZERO));
     }
 }
@@ -0,0 +1,208 @@
+//! Detection of folders that change too often.
+//!
+//! Caches and temp folders can produce thousands of change journal entries
+//! a minute, which keeps the watcher busy and the index full of short-lived
+//! files. The index counts the changes in each folder per minute; folders
+//! above a threshold are written to a [`ChurnReport`] next to the index, so
+//! `glint doctor` and the GUI can suggest excluding them.
+
+use crate::error::{GlintError, Result};
+use crate::exclude::PRESETS;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::Hash;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Default changes per minute above which a folder counts as busy.
+pub const DEFAULT_CHURN_THRESHOLD: u32 = 600;
+
+/// Folders tracked at most; quiet ones are forgotten beyond this.
+const MAX_TRACKED: usize = 50_000;
+
+/// Changes per minute below which a folder is forgotten when pruning.
+const QUIET_RATE: u32 = 10;
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+/// Change counts of one folder.
+#[derive(Debug, Clone, Copy)]
+struct Activity {
+    minute_start: Instant,
+    this_minute: u32,
+    peak: u32,
+    total: u64,
+}
+
+impl Activity {
+    /// The busiest minute so far, counting the current one.
+    fn peak(&self) -> u32 {
+        self.peak.max(self.this_minute)
+    }
+}
+
+/// Counts changes per folder, keyed by whatever identifies a folder.
+#[derive(Debug)]
+pub struct ChurnDetector<K> {
+    folders: HashMap<K, Activity>,
+}
+
+impl<K> Default for ChurnDetector<K> {
+    fn default() -> Self {
+        ChurnDetector {
+            folders: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone> ChurnDetector<K> {
+    /// Count a change in a folder.
+    pub fn record(&mut self, folder: K, now: Instant) {
+        if self.folders.len() >= MAX_TRACKED && !self.folders.contains_key(&folder) {
+            self.folders.retain(|_, a| a.peak() >= QUIET_RATE);
+        }
+        let activity = self.folders.entry(folder).or_insert(Activity {
+            minute_start: now,
+            this_minute: 0,
+            peak: 0,
+            total: 0,
+        });
+        if now.duration_since(activity.minute_start) >= MINUTE {
+            activity.peak = activity.peak();
+            activity.minute_start = now;
+            activity.this_minute = 0;
+        }
+        activity.this_minute += 1;
+        activity.total += 1;
+    }
+
+    /// Folders whose busiest minute had at least `threshold` changes, with
+    /// that minute's count and their total, busiest first.
+    pub fn busy(&self, threshold: u32) -> Vec<(K, u32, u64)> {
+        if threshold == 0 {
+            return Vec::new();
+        }
+        let mut busy: Vec<_> = self
+            .folders
+            .iter()
+            .filter(|(_, a)| a.peak() >= threshold)
+            .map(|(k, a)| (k.clone(), a.peak(), a.total))
+            .collect();
+        busy.sort_by_key(|(_, per_minute, _)| Reverse(*per_minute));
+        busy
+    }
+}
+
+/// A folder that changes often.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChurnFolder {
+    /// Full path of the folder
+    pub path: String,
+
+    /// Changes in its busiest minute
+    pub per_minute: u32,
+
+    /// Changes seen since the watcher started
+    pub total: u64,
+}
+
+impl ChurnFolder {
+    /// The built-in preset that would exclude this folder, if any.
+    pub fn preset(&self) -> Option<&'static str> {
+        PRESETS
+            .iter()
+            .find(|p| p.covers(&self.path))
+            .map(|p| p.name)
+    }
+}
+
+/// Busy folders found by the watcher, saved next to the index.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChurnReport {
+    /// When the report was written
+    pub generated_at: DateTime<Utc>,
+
+    /// Changes per minute that made a folder count as busy
+    pub threshold: u32,
+
+    /// Busy folders, busiest first
+    pub folders: Vec<ChurnFolder>,
+}
+
+impl ChurnReport {
+    pub fn new(threshold: u32, folders: Vec<ChurnFolder>) -> Self {
+        ChurnReport {
+            generated_at: Utc::now(),
+            threshold,
+            folders,
+        }
+    }
+
+    /// Write the report as JSON.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| GlintError::serialization(e.to_string()))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Read a report; None if there is none or it can't be read.
+    pub fn load(path: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&contents)
+            .map_err(|e| warn!(path = %path.display(), error = %e, "Ignoring unreadable churn report"))
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_busy_folders() {
+        let start = Instant::now();
+        let mut detector = ChurnDetector::default();
+        for i in 0..700 {
+            detector.record("cache", start + Duration::from_millis(i * 50));
+        }
+        for i in 0..100 {
+            detector.record("documents", start + Duration::from_millis(i * 100));
+        }
+
+        let busy = detector.busy(600);
+        assert_eq!(busy.len(), 1);
+        let (folder, per_minute, total) = busy[0];
+        assert_eq!(folder, "cache");
+        // 700 changes over 35 seconds all fall in the first minute
+        assert_eq!((per_minute, total), (700, 700));
+        assert!(detector.busy(0).is_empty());
+
+        // The busiest minute is remembered after it passes
+        detector.record("cache", start + Duration::from_secs(120));
+        assert_eq!(detector.busy(600)[0].1, 700);
+    }
+
+    #[test]
+    fn test_report_round_trip() {
+        let folder = ChurnFolder {
+            path: r"C:\Users\ann\AppData\Local\Temp".to_string(),
+            per_minute: 900,
+            total: 5000,
+        };
+        assert_eq!(folder.preset(), Some("temp"));
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("glint.churn.json");
+        let report = ChurnReport::new(600, vec![folder]);
+        report.save(&path).unwrap();
+        assert_eq!(ChurnReport::load(&path), Some(report));
+    }
+}
@@ -0,0 +1,386 @@
+//! Moving or removing the files a query matches, such as everything in a
+//! temp folder older than two years.
+//!
+//! Like a rename, a cleanup is done in two steps so the caller can show a
+//! preview first:
+//!
+//! 1. A [`CleanupPlan`] is built for a set of records, computing where each
+//!    file goes and flagging conflicts.
+//! 2. [`CleanupPlan::execute`] moves or removes the files, dropping each one
+//!    from the index as soon as it is gone so searches stop finding it
+//!    without waiting for the change journal. A file whose size or
+//!    modification time no longer matches the index is left alone, and a
+//!    move never replaces a file that has appeared at its target since.
+//!
+//! How files are removed is up to the caller: the CLI sends them to the
+//! Recycle Bin unless asked to delete them for good.
+
+use crate::backend::ChangeEvent;
+use crate::index::Index;
+use crate::rename::rename_no_replace;
+use crate::types::FileRecord;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use tracing::{info, warn};
+
+/// What happens to the files of a plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CleanupAction {
+    /// Move files into this folder, keeping their path below the drive
+    /// root: `D:\temp\a.log` moved to `E:\archive` becomes
+    /// `E:\archive\temp\a.log`
+    MoveTo(PathBuf),
+    /// Remove files with the function passed to [`CleanupPlan::execute`]
+    Remove,
+}
+
+/// A single planned move or removal.
+#[derive(Debug, Clone)]
+pub struct CleanupEntry {
+    /// The file
+    pub record: FileRecord,
+    /// Where the file is moved to, for [`CleanupAction::MoveTo`]
+    pub target: Option<PathBuf>,
+    /// Why the file will be left alone, if it will
+    pub conflict: Option<String>,
+}
+
+impl CleanupEntry {
+    /// Whether this entry will be moved or removed.
+    pub fn is_ready(&self) -> bool {
+        self.conflict.is_none()
+    }
+}
+
+/// A previewable set of moves or removals.
+#[derive(Debug, Clone)]
+pub struct CleanupPlan {
+    pub action: CleanupAction,
+    /// Planned entries in result order
+    pub entries: Vec<CleanupEntry>,
+}
+
+impl CleanupPlan {
+    /// Plan `action` for the files among `records`.
+    ///
+    /// Folders and records without a path are ignored; a folder is cleaned
+    /// up by matching the files inside it. Moves onto an existing file, or
+    /// onto the target of another entry, are conflicts.
+    pub fn build(records: &[FileRecord], action: CleanupAction) -> Self {
+        let mut entries: Vec<CleanupEntry> = records
+            .iter()
+            .filter(|r| !r.is_dir && !r.path.is_empty())
+            .map(|record| CleanupEntry {
+                record: record.clone(),
+                target: match &action {
+                    CleanupAction::MoveTo(folder) => Some(folder.join(below_root(&record.path))),
+                    CleanupAction::Remove => None,
+                },
+                conflict: None,
+            })
+            .collect();
+
+        // NTFS is case-insensitive, so compare lowercase paths
+        let mut targets: HashMap<String, usize> = HashMap::new();
+        for target in entries.iter().filter_map(|e| e.target.as_ref()) {
+            *targets
+                .entry(target.to_string_lossy().to_lowercase())
+                .or_default() += 1;
+        }
+        for entry in &mut entries {
+            let Some(target) = &entry.target else {
+                continue;
+            };
+            if targets[&target.to_string_lossy().to_lowercase()] > 1 {
+                entry.conflict = Some("another file moves to the same place".to_string());
+            } else if target.exists() {
+                entry.conflict = Some("target already exists".to_string());
+            }
+        }
+
+        CleanupPlan { action, entries }
+    }
+
+    /// Number of entries that will be moved or removed.
+    pub fn ready_count(&self) -> usize {
+        self.entries.iter().filter(|e| e.is_ready()).count()
+    }
+
+    /// Number of entries with conflicts.
+    pub fn conflict_count(&self) -> usize {
+        self.entries.len() - self.ready_count()
+    }
+
+    /// Total size of the entries that will be moved or removed.
+    pub fn ready_size(&self) -> u64 {
+        self.entries
+            .iter()
+            .filter(|e| e.is_ready())
+            .filter_map(|e| e.record.size)
+            .sum()
+    }
+
+    /// Move or remove every ready entry, removing with `remove`.
+    ///
+    /// Each file is dropped from `index` once it is gone. A file moved to
+    /// another indexed place shows up there when the change journal
+    /// reports it. Files changed since the index was read are skipped, and
+    /// listed with the failures. Failures on individual files are logged
+    /// and collected; they don't abort the rest.
+    pub fn execute<F>(&self, index: &Index, mut remove: F) -> CleanupOutcome
+    where
+        F: FnMut(&Path) -> io::Result<()>,
+    {
+        let mut outcome = CleanupOutcome::default();
+
+        for entry in self.entries.iter().filter(|e| e.is_ready()) {
+            let path = Path::new(&entry.record.path);
+            let done = unchanged(&entry.record).and_then(|()| match &entry.target {
+                Some(target) => move_file(path, target),
+                None => remove(path),
+            });
+            match done {
+                Ok(()) => {
+                    let record = &entry.record;
+                    index.apply_change(ChangeEvent::deleted(
+                        record.volume_id.clone(),
+                        record.id,
+                        record.parent_id,
+                        record.name.clone(),
+                        false,
+                        0,
+                    ));
+                    outcome.done += 1;
+                    outcome.bytes += record.size.unwrap_or(0);
+                }
+                Err(e) => {
+                    warn!(path = %entry.record.path, error = %e, "Cleanup failed");
+                    outcome
+                        .failures
+                        .push((entry.record.path.clone(), e.to_string()));
+                }
+            }
+        }
+
+        info!(
+            done = outcome.done,
+            failed = outcome.failures.len(),
+            "Cleanup finished"
+        );
+        outcome
+    }
+}
+
+/// What a cleanup did.
+#[derive(Debug, Clone, Default)]
+pub struct CleanupOutcome {
+    /// Files moved or removed
+    pub done: usize,
+    /// Their total size
+    pub bytes: u64,
+    /// Paths that could not be moved or removed, with the error message
+    pub failures: Vec<(String, String)>,
+}
+
+/// The part of `path` below its drive or root.
+fn below_root(path: &str) -> PathBuf {
+    Path::new(path)
+        .components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .collect()
+}
+
+/// Fail if the file of `record` is no longer the one the index knows: its
+/// size or modification time differ. The plan may be built from an index
+/// that is out of date, and a removal can't be undone.
+fn unchanged(record: &FileRecord) -> io::Result<()> {
+    let meta = fs::symlink_metadata(&record.path)?;
+    let size_changed = record.size.is_some_and(|size| size != meta.len());
+    let modified_changed = match (record.modified, meta.modified()) {
+        (Some(modified), Ok(now)) => DateTime::<Utc>::from(now) != modified,
+        _ => false,
+    };
+    if size_changed || modified_changed {
+        return Err(io::Error::other("changed since the index was read"));
+    }
+    Ok(())
+}
+
+/// Move a file, copying it when `to` is on another volume. Never replaces
+/// a file already at `to`.
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    match rename_no_replace(from, to) {
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Err(e),
+        Err(_) => copy_then_remove(from, to),
+        Ok(()) => Ok(()),
+    }
+}
+
+/// Copy `from` into a new file at `to`, then remove `from`. A partial copy
+/// is removed again.
+fn copy_then_remove(from: &Path, to: &Path) -> io::Result<()> {
+    let mut source = File::open(from)?;
+    let mut target = OpenOptions::new().write(true).create_new(true).open(to)?;
+    let copied = io::copy(&mut source, &mut target)
+        .and_then(|_| target.set_permissions(source.metadata()?.permissions()))
+        .and_then(|()| target.sync_all());
+    drop(target);
+    if let Err(e) = copied {
+        let _ = fs::remove_file(to);
+        return Err(e);
+    }
+    fs::remove_file(from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::VolumeInfo;
+    use crate::types::{FileId, VolumeId};
+    use tempfile::TempDir;
+
+    fn record(id: u64, path: &Path) -> FileRecord {
+        FileRecord::new(
+            FileId::new(id),
+            None,
+            VolumeId::new("C"),
+            path.file_name().unwrap().to_string_lossy().to_string(),
+            path.to_string_lossy().to_string(),
+            false,
+        )
+        .with_size(10)
+    }
+
+    #[test]
+    fn test_move_targets_and_conflicts() {
+        let plan = CleanupPlan::build(
+            &[
+                record(1, Path::new("/temp/a.log")),
+                record(2, Path::new("/temp/sub/b.log")),
+                record(3, Path::new("/other/sub/b.log")),
+                record(4, Path::new("/TEMP/A.LOG")),
+            ],
+            CleanupAction::MoveTo(PathBuf::from("/archive")),
+        );
+        assert_eq!(
+            plan.entries[1].target.as_deref(),
+            Some(Path::new("/archive/temp/sub/b.log"))
+        );
+        assert!(plan.entries[1].is_ready());
+        assert!(plan.entries[2].is_ready());
+        // Two files landing on the same (case-insensitive) path
+        assert!(!plan.entries[0].is_ready());
+        assert!(!plan.entries[3].is_ready());
+        assert_eq!(plan.ready_count(), 2);
+        assert_eq!(plan.ready_size(), 20);
+
+        let mut folder = record(5, Path::new("/temp/sub"));
+        folder.is_dir = true;
+        let plan = CleanupPlan::build(&[folder], CleanupAction::Remove);
+        assert!(plan.entries.is_empty());
+    }
+
+    #[test]
+    fn test_execute_updates_index() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("src");
+        fs::create_dir_all(&source).unwrap();
+        let records: Vec<FileRecord> = ["old.log", "older.log", "taken.log"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let path = source.join(name);
+                fs::write(&path, "0123456789").unwrap();
+                record(i as u64 + 1, &path)
+            })
+            .collect();
+
+        let index = Index::new();
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        index.add_volume_records(&volume, records.clone());
+
+        // Moving keeps the path below the root and skips existing targets
+        let archive = temp.path().join("archive");
+        let taken = archive.join(below_root(&records[2].path));
+        fs::create_dir_all(taken.parent().unwrap()).unwrap();
+        fs::write(&taken, "other").unwrap();
+        let plan = CleanupPlan::build(&records[1..], CleanupAction::MoveTo(archive.clone()));
+        assert_eq!(plan.conflict_count(), 1);
+        let outcome = plan.execute(&index, |_| unreachable!());
+        assert_eq!(outcome.done, 1);
+        assert!(archive.join(below_root(&records[1].path)).exists());
+        assert!(!Path::new(&records[1].path).exists());
+        assert!(Path::new(&records[2].path).exists());
+
+        // Removing goes through the given function
+        let plan = CleanupPlan::build(&records[..1], CleanupAction::Remove);
+        let outcome = plan.execute(&index, |path| fs::remove_file(path));
+        assert_eq!((outcome.done, outcome.bytes), (1, 10));
+        assert!(!Path::new(&records[0].path).exists());
+
+        let plan = CleanupPlan::build(&records[..1], CleanupAction::Remove);
+        let outcome = plan.execute(&index, |path| fs::remove_file(path));
+        assert_eq!(outcome.failures.len(), 1);
+
+        let mut remaining = Vec::new();
+        index.for_each_record(|r| remaining.push(r.id));
+        assert_eq!(remaining, vec![FileId::new(3)]);
+    }
+
+    #[test]
+    fn test_execute_skips_changed_and_taken() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("report.txt");
+        fs::write(&path, "0123456789").unwrap();
+        let mut report = record(1, &path);
+        let index = Index::new();
+
+        // A file that grew, or was touched, since it was indexed stays
+        fs::write(&path, "01234567890123").unwrap();
+        let plan = CleanupPlan::build(&[report.clone()], CleanupAction::Remove);
+        let outcome = plan.execute(&index, |path| fs::remove_file(path));
+        assert_eq!(outcome.done, 0);
+        assert!(outcome.failures[0].1.contains("changed"));
+
+        report.size = Some(14);
+        report.modified = Some(Utc::now() - chrono::Duration::days(1));
+        let plan = CleanupPlan::build(&[report.clone()], CleanupAction::Remove);
+        assert_eq!(plan.execute(&index, |_| unreachable!()).done, 0);
+        assert!(path.exists());
+
+        // A file that appears at the target after planning is not replaced
+        report.modified = None;
+        let archive = temp.path().join("archive");
+        let plan = CleanupPlan::build(&[report], CleanupAction::MoveTo(archive.clone()));
+        let target = plan.entries[0].target.clone().unwrap();
+        fs::create_dir_all(target.parent().unwrap()).unwrap();
+        fs::write(&target, "other").unwrap();
+        let outcome = plan.execute(&index, |_| unreachable!());
+        assert_eq!(outcome.done, 0);
+        assert_eq!(fs::read_to_string(&target).unwrap(), "other");
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn test_copy_then_remove() {
+        let temp = TempDir::new().unwrap();
+        let from = temp.path().join("a.log");
+        let to = temp.path().join("b.log");
+        fs::write(&from, "data").unwrap();
+        fs::write(&to, "other").unwrap();
+        let err = copy_then_remove(&from, &to).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&to).unwrap(), "other");
+
+        fs::remove_file(&to).unwrap();
+        copy_then_remove(&from, &to).unwrap();
+        assert_eq!(fs::read_to_string(&to).unwrap(), "data");
+        assert!(!from.exists());
+    }
+}
@@ -0,0 +1,224 @@
+//! The current time and where days start.
+//!
+//! Age filters (`older-than:7d`), the age buckets of [`stats`](crate::stats)
+//! and scheduled reports read the time through a [`Clock`] instead of the
+//! system, so tests can fix both the time and the time zone.
+//!
+//! Days are counted on the calendar of a time zone: `7d` before noon is noon
+//! a week earlier even when clocks changed in between, and "Today" starts at
+//! midnight. The zone is local time unless `timezone = "utc"` is set under
+//! `[general]`; [`set_zone`] applies that to the whole process.
+
+use chrono::{DateTime, Duration, Local, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether days follow UTC instead of local time, for the whole process.
+static USE_UTC: AtomicBool = AtomicBool::new(false);
+
+/// Time zone that days are counted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Zone {
+    /// The system's local time
+    #[default]
+    Local,
+
+    /// UTC, the same on every machine
+    Utc,
+}
+
+/// Count days in `zone` from now on, in every [`SystemClock`].
+pub fn set_zone(zone: Zone) {
+    USE_UTC.store(zone == Zone::Utc, Ordering::Relaxed);
+}
+
+/// The zone set with [`set_zone`].
+pub fn zone() -> Zone {
+    match USE_UTC.load(Ordering::Relaxed) {
+        true => Zone::Utc,
+        false => Zone::Local,
+    }
+}
+
+/// A source of the current time, with a calendar to count days on.
+pub trait Clock {
+    /// The current time.
+    fn now(&self) -> DateTime<Utc>;
+
+    /// The time `age` before now. Whole days are calendar days, so a day
+    /// across a daylight saving change is 23 or 25 hours.
+    fn before(&self, age: Duration) -> DateTime<Utc>;
+
+    /// Midnight starting the day `days` before today (0 for today).
+    fn day_start(&self, days: u32) -> DateTime<Utc>;
+}
+
+/// The system clock, counting days in the zone set with [`set_zone`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn before(&self, age: Duration) -> DateTime<Utc> {
+        match zone() {
+            Zone::Local => before(&Local, self.now(), age),
+            Zone::Utc => before(&Utc, self.now(), age),
+        }
+    }
+
+    fn day_start(&self, days: u32) -> DateTime<Utc> {
+        match zone() {
+            Zone::Local => day_start(&Local, self.now(), days),
+            Zone::Utc => day_start(&Utc, self.now(), days),
+        }
+    }
+}
+
+/// A clock stopped at one time, counting days in any time zone.
+#[derive(Debug, Clone)]
+pub struct FixedClock<Tz: TimeZone> {
+    now: DateTime<Utc>,
+    tz: Tz,
+}
+
+impl<Tz: TimeZone> FixedClock<Tz> {
+    /// A clock that always reads `now`, with days starting at midnight in
+    /// `tz`.
+    pub fn new(now: DateTime<Utc>, tz: Tz) -> Self {
+        FixedClock { now, tz }
+    }
+}
+
+impl<Tz: TimeZone> Clock for FixedClock<Tz> {
+    fn now(&self) -> DateTime<Utc> {
+        self.now
+    }
+
+    fn before(&self, age: Duration) -> DateTime<Utc> {
+        before(&self.tz, self.now, age)
+    }
+
+    fn day_start(&self, days: u32) -> DateTime<Utc> {
+        day_start(&self.tz, self.now, days)
+    }
+}
+
+fn before<Tz: TimeZone>(tz: &Tz, now: DateTime<Utc>, age: Duration) -> DateTime<Utc> {
+    let days = age.num_days();
+    if days == 0 || age != Duration::days(days) {
+        return now - age;
+    }
+    let local = now.with_timezone(tz).naive_local() - Duration::days(days);
+    resolve(tz, local).unwrap_or(now - age)
+}
+
+fn day_start<Tz: TimeZone>(tz: &Tz, now: DateTime<Utc>, days: u32) -> DateTime<Utc> {
+    let date = now.with_timezone(tz).date_naive() - Duration::days(days.into());
+    let midnight = date.and_time(NaiveTime::MIN);
+    resolve(tz, midnight).unwrap_or_else(|| Utc.from_utc_datetime(&midnight))
+}
+
+/// The time a local time in `tz` names. A local time that clocks skipped
+/// when moving forward is moved past the gap, so 02:30 becomes 03:30.
+fn resolve<Tz: TimeZone>(tz: &Tz, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    (0..=2)
+        .find_map(|hours| {
+            tz.from_local_datetime(&(local + Duration::hours(hours)))
+                .earliest()
+        })
+        .map(|time| time.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use chrono::{FixedOffset, LocalResult, NaiveDate};
+
+    /// Central European time in 2024: UTC+1, then UTC+2 from 31 March
+    /// 01:00 UTC, when local clocks jumped from 02:00 to 03:00.
+    #[derive(Debug, Clone, Copy)]
+    pub(crate) struct Cet;
+
+    impl Cet {
+        fn switch() -> NaiveDateTime {
+            NaiveDate::from_ymd_opt(2024, 3, 31)
+                .unwrap()
+                .and_hms_opt(1, 0, 0)
+                .unwrap()
+        }
+
+        fn offset(hours: i32) -> FixedOffset {
+            FixedOffset::east_opt(hours * 3600).unwrap()
+        }
+    }
+
+    impl TimeZone for Cet {
+        type Offset = FixedOffset;
+
+        fn from_offset(_offset: &FixedOffset) -> Self {
+            Cet
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_time(NaiveTime::MIN))
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            let winter = *local - Duration::hours(1) < Self::switch();
+            let summer = *local - Duration::hours(2) >= Self::switch();
+            match (winter, summer) {
+                (true, _) => LocalResult::Single(Self::offset(1)),
+                (false, true) => LocalResult::Single(Self::offset(2)),
+                (false, false) => LocalResult::None,
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_time(NaiveTime::MIN))
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            match *utc < Self::switch() {
+                true => Self::offset(1),
+                false => Self::offset(2),
+            }
+        }
+    }
+
+    pub(crate) fn utc(month: u32, day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, month, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_days_across_dst() {
+        // Noon on 1 April in Central Europe, after clocks moved forward
+        let clock = FixedClock::new(utc(4, 1, 10), Cet);
+
+        // Two days back is noon on 30 March, 47 hours earlier; ages that
+        // aren't whole days are exact
+        assert_eq!(clock.before(Duration::days(2)), utc(3, 30, 11));
+        assert_eq!(clock.before(Duration::hours(36)), utc(3, 30, 22));
+
+        assert_eq!(clock.day_start(0), utc(3, 31, 22));
+        assert_eq!(clock.day_start(1), utc(3, 30, 23));
+        assert_eq!(clock.day_start(2), utc(3, 29, 23));
+
+        // The same times in UTC are plain arithmetic
+        let clock = FixedClock::new(utc(4, 1, 10), Utc);
+        assert_eq!(clock.before(Duration::days(2)), utc(3, 30, 10));
+        assert_eq!(clock.day_start(1), utc(3, 31, 0));
+    }
+
+    #[test]
+    fn test_skipped_local_time() {
+        // 02:30 on 31 March never happened, so a day before 02:30 on
+        // 1 April is 03:30 on 31 March
+        let half_past = Duration::minutes(30);
+        let clock = FixedClock::new(utc(4, 1, 0) + half_past, Cet);
+        assert_eq!(clock.before(Duration::days(1)), utc(3, 31, 1) + half_past);
+    }
+}
@@ -3,11 +3,22 @@
 //! This module provides configuration loading, saving, and defaults.
 //! Configuration is stored in TOML format in a platform-appropriate location.
 
+use crate::churn::DEFAULT_CHURN_THRESHOLD;
+use crate::clock::Zone;
 use crate::error::{GlintError, Result};
+use crate::exclude::default_presets;
+use crate::ipc::{IpcClient, QueryLimits, DEFAULT_LISTEN_ADDR};
+use crate::layers::{self, machine_config_path, ConfigLayers};
+use crate::path_format::PathFormat;
+use crate::report::ReportJob;
+use crate::scope::Scopes;
+use crate::search::SearchFilter;
+use crate::tls::{ClientTls, ServerTls};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tracing::{debug, info};
 
 /// Main configuration structure for Glint.
@@ -18,18 +29,70 @@ use tracing::{debug, info};
 /// [general]
 /// auto_start_usn = true
 /// max_results = 1000
+/// typo_tolerance = true
+/// dedup_links = false
+/// stale_after_hours = 24
+/// rebuild_after_hours = 168
+/// drop_absent_volumes_after_days = 90
+/// read_only = false
+/// timezone = "local"
+/// ignore_maintenance_changes = true
+/// hide_offline_volumes = false
 ///
 /// [exclude]
-/// paths = ["C:\\Windows\\Temp", "C:\\$Recycle.Bin"]
+/// paths = ["D:\\Build"]
 /// patterns = ["*.tmp", "~$*"]
+/// presets = ["browser-cache", "temp", "recycle-bin"]
+/// churn_threshold = 600
 ///
 /// [performance]
 /// max_memory_mb = 512
 /// parallel_search = true
+/// cache_lower_paths = true
 ///
 /// [ui]
 /// show_hidden = false
 /// show_system = false
+/// path_format = "native"
+/// number_format = "en"
+/// si_units = false
+/// date_format = "iso"
+/// hide_patterns = ["*.pyc", "Thumbs.db"]
+///
+/// [volumes]
+/// folders = ["D:\\Projects"]
+/// disabled = ["E:"]
+///
+/// [server]
+/// enabled = true
+/// listen = "127.0.0.1:7379"
+/// token = "a-long-random-secret"
+/// remote_timeout_ms = 2000
+/// max_results = 10000
+/// query_timeout_ms = 10000
+/// max_concurrent_queries = 4
+/// max_connections = 64
+/// tls_cert = "C:\\Glint\\server.pem"
+/// tls_key = "C:\\Glint\\server.key"
+/// explorer_listen = "127.0.0.1:7380"
+///
+/// [[server.remotes]]
+/// name = "lab-3"
+/// addr = "lab-3:7379"
+/// token = "lab-3's-secret"
+/// tls_ca = "C:\\Glint\\lab-3.pem"
+///
+/// [reports]
+/// output_dir = "D:\\Reports"
+///
+/// [[reports.jobs]]
+/// name = "large-new-files"
+/// schedule = "0 8 * * 1"
+/// min_size = "1GB"
+/// created_within = "7d"
+///
+/// [scopes]
+/// Work = ["C:\\repos", "D:\\docs"]
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -48,6 +111,15 @@ pub struct Config {
 
     /// Volumes to index (empty = all NTFS volumes)
     pub volumes: VolumesConfig,
+
+    /// IPC server settings
+    pub server: ServerConfig,
+
+    /// Scheduled reports
+    pub reports: ReportsConfig,
+
+    /// Named search scopes (sets of folders or volumes)
+    pub scopes: Scopes,
 }
 
 impl Default for Config {
@@ -58,6 +130,9 @@ impl Default for Config {
             performance: PerformanceConfig::default(),
             ui: UiConfig::default(),
             volumes: VolumesConfig::default(),
+            server: ServerConfig::default(),
+            reports: ReportsConfig::default(),
+            scopes: Scopes::default(),
         }
     }
 }
@@ -77,6 +152,40 @@ pub struct GeneralConfig {
 
     /// Log level (trace, debug, info, warn, error)
     pub log_level: String,
+
+    /// When a search finds nothing, retry allowing one typo
+    pub typo_tolerance: bool,
+
+    /// Show a file found under several indexed paths (e.g., through a
+    /// folder volume and its whole volume) as one result
+    pub dedup_links: bool,
+
+    /// Hours without an update after which a volume is reported as stale
+    /// (0 = never)
+    pub stale_after_hours: u32,
+
+    /// Hours after a volume's last full scan at which the service rebuilds
+    /// the index in the background (0 = only when a volume needs a rescan)
+    pub rebuild_after_hours: u32,
+
+    /// Days a volume may stay disconnected before compacting the index
+    /// drops its records (0 = never)
+    pub drop_absent_volumes_after_days: u32,
+
+    /// Never write the index or apply changes to it, e.g. to review an
+    /// imported snapshot without altering it
+    pub read_only: bool,
+
+    /// Time zone that ages and "today" are counted in: `local` or `utc`
+    pub timezone: Zone,
+
+    /// Skip changes Windows makes while maintaining files, such as
+    /// defragmentation, deduplication and backup or replication runs
+    pub ignore_maintenance_changes: bool,
+
+    /// Leave the files of disconnected volumes out of search results
+    /// instead of showing them greyed out
+    pub hide_offline_volumes: bool,
 }
 
 impl Default for GeneralConfig {
@@ -86,12 +195,21 @@ impl Default for GeneralConfig {
             max_results: 10000,
             index_path: None,
             log_level: "info".to_string(),
+            typo_tolerance: true,
+            dedup_links: false,
+            stale_after_hours: 24,
+            rebuild_after_hours: 0,
+            drop_absent_volumes_after_days: 90,
+            read_only: false,
+            timezone: Zone::Local,
+            ignore_maintenance_changes: true,
+            hide_offline_volumes: false,
         }
     }
 }
 
 /// Exclusion configuration
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ExcludeConfig {
     /// Paths to exclude from indexing
@@ -105,6 +223,26 @@ pub struct ExcludeConfig {
 
     /// Exclude system files and directories
     pub system: bool,
+
+    /// Built-in exclusion presets to apply (see [`crate::exclude::PRESETS`])
+    pub presets: Vec<String>,
+
+    /// Changes per minute above which the watcher reports a folder as busy
+    /// and suggests excluding it (0 = never)
+    pub churn_threshold: u32,
+}
+
+impl Default for ExcludeConfig {
+    fn default() -> Self {
+        ExcludeConfig {
+            paths: Vec::new(),
+            patterns: Vec::new(),
+            hidden: false,
+            system: false,
+            presets: default_presets(),
+            churn_threshold: DEFAULT_CHURN_THRESHOLD,
+        }
+    }
 }
 
 /// Performance configuration
@@ -122,6 +260,10 @@ pub struct PerformanceConfig {
 
     /// Use compression for index storage
     pub compress_index: bool,
+
+    /// Keep lowercase copies of full paths so path searches don't allocate.
+    /// Turning this off saves about as much memory as the paths take.
+    pub cache_lower_paths: bool,
 }
 
 impl Default for PerformanceConfig {
@@ -131,6 +273,7 @@ impl Default for PerformanceConfig {
             parallel_search: true,
             parallel_threshold: 10000,
             compress_index: true,
+            cache_lower_paths: true,
         }
     }
 }
@@ -156,6 +299,23 @@ pub struct UiConfig {
 
     /// Show modification times
     pub show_modified: bool,
+
+    /// Format used when displaying and copying paths
+    pub path_format: PathFormat,
+
+    /// How counts and sizes are written: digit grouping and decimal mark
+    pub number_format: NumberFormat,
+
+    /// Count sizes in powers of 1000 (kB, MB) rather than 1024
+    pub si_units: bool,
+
+    /// How dates are written
+    pub date_format: DateFormat,
+
+    /// Globs of names or folders left out of search results, such as
+    /// `*.pyc` or `Thumbs.db`. Unlike `[exclude] patterns` the files are
+    /// still indexed, so showing them again needs no re-index.
+    pub hide_patterns: Vec<String>,
 }
 
 impl Default for UiConfig {
@@ -167,6 +327,126 @@ impl Default for UiConfig {
             highlight_matches: true,
             show_size: true,
             show_modified: true,
+            path_format: PathFormat::Native,
+            number_format: NumberFormat::En,
+            si_units: false,
+            date_format: DateFormat::Iso,
+            hide_patterns: Vec::new(),
+        }
+    }
+}
+
+impl UiConfig {
+    /// Filters leaving out the results matching `hide_patterns`.
+    pub fn hide_filters(&self) -> Result<Vec<SearchFilter>> {
+        self.hide_patterns
+            .iter()
+            .map(|pattern| SearchFilter::exclude_glob(pattern))
+            .collect()
+    }
+}
+
+/// Digit grouping and decimal mark for displayed numbers, named after a
+/// language that writes them so.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NumberFormat {
+    /// `1,234,567.8`
+    #[default]
+    En,
+    /// `1.234.567,8`
+    De,
+    /// `1 234 567,8`
+    Fr,
+    /// `1'234'567.8`
+    Ch,
+    /// `1234567.8`
+    Plain,
+}
+
+impl NumberFormat {
+    /// All formats, in the order they are offered in settings.
+    pub const ALL: [NumberFormat; 5] = [
+        NumberFormat::En,
+        NumberFormat::De,
+        NumberFormat::Fr,
+        NumberFormat::Ch,
+        NumberFormat::Plain,
+    ];
+
+    /// An example number, as a label for menus and settings.
+    pub fn label(&self) -> &'static str {
+        match self {
+            NumberFormat::En => "1,234.5",
+            NumberFormat::De => "1.234,5",
+            NumberFormat::Fr => "1 234,5",
+            NumberFormat::Ch => "1'234.5",
+            NumberFormat::Plain => "1234.5",
+        }
+    }
+
+    /// The character between groups of three digits, if any. French
+    /// uses a no-break space so numbers don't wrap.
+    pub fn group_separator(&self) -> Option<char> {
+        match self {
+            NumberFormat::En => Some(','),
+            NumberFormat::De => Some('.'),
+            NumberFormat::Fr => Some('\u{a0}'),
+            NumberFormat::Ch => Some('\''),
+            NumberFormat::Plain => None,
+        }
+    }
+
+    /// The character before the fraction.
+    pub fn decimal_mark(&self) -> char {
+        match self {
+            NumberFormat::De | NumberFormat::Fr => ',',
+            NumberFormat::En | NumberFormat::Ch | NumberFormat::Plain => '.',
+        }
+    }
+}
+
+/// Order and separators of displayed dates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateFormat {
+    /// `2024-01-31`
+    #[default]
+    Iso,
+    /// `01/31/2024`
+    Us,
+    /// `31/01/2024`
+    Uk,
+    /// `31.01.2024`
+    De,
+}
+
+impl DateFormat {
+    /// All formats, in the order they are offered in settings.
+    pub const ALL: [DateFormat; 4] = [
+        DateFormat::Iso,
+        DateFormat::Us,
+        DateFormat::Uk,
+        DateFormat::De,
+    ];
+
+    /// An example date, as a label for menus and settings.
+    pub fn label(&self) -> &'static str {
+        match self {
+            DateFormat::Iso => "2024-01-31",
+            DateFormat::Us => "01/31/2024",
+            DateFormat::Uk => "31/01/2024",
+            DateFormat::De => "31.01.2024",
+        }
+    }
+
+    /// The `chrono` format string of a date.
+    pub fn pattern(&self) -> &'static str {
+        match self {
+            DateFormat::Iso => "%Y-%m-%d",
+            DateFormat::Us => "%m/%d/%Y",
+            DateFormat::Uk => "%d/%m/%Y",
+            DateFormat::De => "%d.%m.%Y",
         }
     }
 }
@@ -180,15 +460,202 @@ pub struct VolumesConfig {
 
     /// Volumes to exclude
     pub exclude: Vec<String>,
+
+    /// Folders to index on their own (e.g. "D:\Projects"). When set and
+    /// `include` is empty, only these folders are indexed.
+    pub folders: Vec<String>,
+
+    /// Indexed volumes or folders left out of searches and not watched.
+    /// Their records stay in the index.
+    pub disabled: Vec<String>,
+}
+
+/// IPC server configuration
+///
+/// The watch service answers queries from other processes (such as the CLI
+/// running inside WSL) on this address.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// Run the IPC server while watching. Off by default: the server
+    /// answers with every indexed name, other users' files included, to
+    /// whoever can connect
+    pub enabled: bool,
+
+    /// Address the watch service listens on
+    pub listen: String,
+
+    /// Address clients connect to (None = same as `listen`)
+    pub connect: Option<String>,
+
+    /// Token clients must present before the server answers them, and
+    /// that the CLI presents to it (None = no token needed). Set one
+    /// before listening anywhere but localhost.
+    pub token: Option<String>,
+
+    /// Other Glint servers searched alongside the local index
+    pub remotes: Vec<RemoteServer>,
+
+    /// How long to wait for each remote server, in milliseconds
+    pub remote_timeout_ms: u64,
+
+    /// Most results the server returns for one query
+    pub max_results: usize,
+
+    /// How long the server lets a client wait for a query, in milliseconds
+    pub query_timeout_ms: u64,
+
+    /// Most queries the server runs at once (at least 1); more are refused
+    /// as busy
+    pub max_concurrent_queries: usize,
+
+    /// Most connections the server keeps open at once (at least 1); more
+    /// are turned away as busy
+    pub max_connections: usize,
+
+    /// PEM certificate chain the server encrypts its connections with,
+    /// with `tls_key` (None = plain TCP); needs the `tls` feature
+    pub tls_cert: Option<PathBuf>,
+
+    /// PEM private key of `tls_cert`
+    pub tls_key: Option<PathBuf>,
+
+    /// PEM certificates the CLI checks the service's certificate with,
+    /// when it serves over TLS (None = connect over plain TCP)
+    pub tls_ca: Option<PathBuf>,
+
+    /// Address on this machine answering searches from Explorer's search
+    /// box (None = off); see [`crate::opensearch`]
+    pub explorer_listen: Option<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            enabled: false,
+            listen: DEFAULT_LISTEN_ADDR.to_string(),
+            connect: None,
+            token: None,
+            remotes: Vec::new(),
+            remote_timeout_ms: 2000,
+            max_results: 10_000,
+            query_timeout_ms: 10_000,
+            max_concurrent_queries: 4,
+            max_connections: 64,
+            tls_cert: None,
+            tls_key: None,
+            tls_ca: None,
+            explorer_listen: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// The address clients should connect to.
+    pub fn connect_addr(&self) -> &str {
+        self.connect.as_deref().unwrap_or(&self.listen)
+    }
+
+    /// How long to wait for each remote server.
+    pub fn remote_timeout(&self) -> Duration {
+        Duration::from_millis(self.remote_timeout_ms)
+    }
+
+    /// The limits the server keeps queries within.
+    pub fn query_limits(&self) -> QueryLimits {
+        QueryLimits {
+            max_results: self.max_results,
+            timeout: Duration::from_millis(self.query_timeout_ms),
+            max_concurrent: self.max_concurrent_queries.max(1),
+            max_connections: self.max_connections.max(1),
+        }
+    }
+
+    /// The certificate the server encrypts its connections with, when
+    /// `tls_cert` and `tls_key` are set.
+    pub fn server_tls(&self) -> Result<Option<ServerTls>> {
+        match (&self.tls_cert, &self.tls_key) {
+            (Some(cert), Some(key)) => ServerTls::load(cert, key).map(Some),
+            (None, None) => Ok(None),
+            _ => Err(GlintError::ConfigError {
+                reason: "[server] tls_cert and tls_key must be set together".to_string(),
+            }),
+        }
+    }
+
+    /// Connect to the server at `addr`, over TLS when `tls_ca` is set.
+    pub fn connect(&self, addr: &str, timeout: Duration) -> Result<IpcClient> {
+        connect(addr, self.tls_ca.as_deref(), timeout)
+    }
+}
+
+/// Another machine's Glint server, searched alongside the local index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteServer {
+    /// Name shown next to its results
+    pub name: String,
+
+    /// Address of its query server, such as `lab-3:7878`
+    pub addr: String,
+
+    /// The server's `[server] token`, when it has one
+    #[serde(default)]
+    pub token: Option<String>,
+
+    /// PEM certificates to check the server's certificate with, when it
+    /// serves over TLS (None = plain TCP)
+    #[serde(default)]
+    pub tls_ca: Option<PathBuf>,
+}
+
+impl RemoteServer {
+    /// Connect to the server, over TLS when `tls_ca` is set.
+    pub fn connect(&self, timeout: Duration) -> Result<IpcClient> {
+        connect(&self.addr, self.tls_ca.as_deref(), timeout)
+    }
+}
+
+fn connect(addr: &str, tls_ca: Option<&Path>, timeout: Duration) -> Result<IpcClient> {
+    match tls_ca {
+        Some(ca) => IpcClient::connect_tls(addr, timeout, &ClientTls::load(ca)?),
+        None => IpcClient::connect(addr, timeout),
+    }
+}
+
+/// Scheduled report configuration
+///
+/// Report jobs are run by the watch service; see [`crate::report`].
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ReportsConfig {
+    /// Folder reports are written to (None = `reports` in the data directory)
+    pub output_dir: Option<PathBuf>,
+
+    /// Report jobs
+    pub jobs: Vec<ReportJob>,
 }
 
 impl Config {
-    /// Load configuration from the default location.
+    /// Load configuration from the default location, laid over the
+    /// machine config and under `GLINT_*` environment variables (see
+    /// [`crate::layers`]).
     ///
-    /// Returns default config if no config file exists.
+    /// Returns default config if neither file exists.
     pub fn load() -> Result<Self> {
         let config_path = Self::default_config_path()?;
-        Self::load_from(&config_path)
+        Self::load_layered(&config_path, &[])
+    }
+
+    /// Load the user configuration at `path` laid over the machine config,
+    /// with `GLINT_*` environment variables and then the `set` arguments,
+    /// `key=value`, over it.
+    pub fn load_layered(path: &Path, set: &[String]) -> Result<Self> {
+        if path.exists() {
+            info!(path = %path.display(), "Loading configuration");
+        } else {
+            debug!(path = %path.display(), "Config file not found, using defaults");
+        }
+        Ok(ConfigLayers::load(&machine_config_path(), path, set)?.config)
     }
 
     /// Load configuration from a specific path.
@@ -214,14 +681,34 @@ impl Config {
     }
 
     /// Save configuration to a specific path.
+    ///
+    /// Values the same as in the machine config are left out, so they keep
+    /// following it when an administrator changes them.
     pub fn save_to(&self, path: &Path) -> Result<()> {
+        let machine_path = machine_config_path();
+        let machine = if path == machine_path {
+            None
+        } else {
+            layers::read_table(&machine_path).unwrap_or_default()
+        };
+        self.save_over(path, machine.as_ref())
+    }
+
+    fn save_over(&self, path: &Path, machine: Option<&toml::Table>) -> Result<()> {
         // Ensure parent directory exists
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
         }
 
         info!(path = %path.display(), "Saving configuration");
-        let contents = toml::to_string_pretty(self).map_err(|e| GlintError::ConfigError {
+        let contents = match machine {
+            Some(machine) => toml::Table::try_from(self).and_then(|mut table| {
+                layers::strip_machine_values(&mut table, machine);
+                toml::to_string_pretty(&table)
+            }),
+            None => toml::to_string_pretty(self),
+        }
+        .map_err(|e| GlintError::ConfigError {
             reason: format!("Failed to serialize config: {}", e),
         })?;
 
@@ -256,6 +743,15 @@ impl Config {
         }
     }
 
+    /// Get the folder reports are written to (from config or default).
+    pub fn reports_dir(&self) -> Result<PathBuf> {
+        if let Some(ref path) = self.reports.output_dir {
+            Ok(path.clone())
+        } else {
+            Ok(Self::default_data_dir()?.join("reports"))
+        }
+    }
+
     /// Check if a path should be excluded.
     pub fn should_exclude_path(&self, path: &str) -> bool {
         let path_lower = path.to_lowercase();
@@ -282,27 +778,85 @@ impl Config {
 
     /// Check if a volume should be indexed.
     pub fn should_index_volume(&self, mount_point: &str) -> bool {
+        // Configured folders replace whole volumes unless some are included
+        if !self.volumes.folders.is_empty() && self.volumes.include.is_empty() {
+            return false;
+        }
+
+        // "C:" matches "C:\", and mount folders match with or without a
+        // trailing separator
+        let mount_point = mount_point.trim_end_matches(['\\', '/']);
+        let matches =
+            |v: &String| mount_point.eq_ignore_ascii_case(v.trim_end_matches(['\\', '/']));
+
         // If explicit includes are specified, check them
         if !self.volumes.include.is_empty() {
-            return self
-                .volumes
-                .include
-                .iter()
-                .any(|v| mount_point.eq_ignore_ascii_case(v));
+            return self.volumes.include.iter().any(matches);
         }
 
         // Check excludes
-        if self
-            .volumes
-            .exclude
-            .iter()
-            .any(|v| mount_point.eq_ignore_ascii_case(v))
-        {
+        if self.volumes.exclude.iter().any(matches) {
             return false;
         }
 
         true
     }
+
+    /// How long a volume may go without an update before it is reported as
+    /// stale, if ever.
+    pub fn stale_after(&self) -> Option<std::time::Duration> {
+        match self.general.stale_after_hours {
+            0 => None,
+            hours => Some(std::time::Duration::from_secs(u64::from(hours) * 3600)),
+        }
+    }
+
+    /// How long after a full scan the service rebuilds the index, if ever
+    /// before a volume needs a rescan.
+    pub fn rebuild_after(&self) -> Option<std::time::Duration> {
+        match self.general.rebuild_after_hours {
+            0 => None,
+            hours => Some(std::time::Duration::from_secs(u64::from(hours) * 3600)),
+        }
+    }
+
+    /// How long a volume may stay disconnected before compacting drops it,
+    /// if ever.
+    pub fn drop_absent_after(&self) -> Option<std::time::Duration> {
+        match self.general.drop_absent_volumes_after_days {
+            0 => None,
+            days => Some(std::time::Duration::from_secs(u64::from(days) * 86_400)),
+        }
+    }
+
+    /// Check if an indexed volume (a mount point or folder) is disabled.
+    pub fn is_volume_disabled(&self, name: &str) -> bool {
+        self.volumes
+            .disabled
+            .iter()
+            .any(|v| same_volume_name(v, name))
+    }
+
+    /// Enable or disable an indexed volume. Returns false if it already was.
+    pub fn set_volume_disabled(&mut self, name: &str, disabled: bool) -> bool {
+        if self.is_volume_disabled(name) == disabled {
+            return false;
+        }
+        if disabled {
+            self.volumes
+                .disabled
+                .push(name.trim_end_matches(['\\', '/']).to_string());
+        } else {
+            self.volumes.disabled.retain(|v| !same_volume_name(v, name));
+        }
+        true
+    }
+}
+
+/// Compare volume names ignoring case and trailing separators ("D:" and "D:\").
+fn same_volume_name(a: &str, b: &str) -> bool {
+    a.trim_end_matches(['\\', '/'])
+        .eq_ignore_ascii_case(b.trim_end_matches(['\\', '/']))
 }
 
 /// Simple pattern matching for exclusion patterns.
@@ -310,7 +864,7 @@ impl Config {
 /// Supports:
 /// - `*` at start or end (e.g., `*.tmp`, `~*`)
 /// - Exact match otherwise
-fn matches_simple_pattern(name: &str, pattern: &str) -> bool {
+pub(crate) fn matches_simple_pattern(name: &str, pattern: &str) -> bool {
     let name_lower = name.to_lowercase();
     let pattern_lower = pattern.to_lowercase();
 
@@ -333,13 +887,33 @@ fn matches_simple_pattern(name: &str, pattern: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::{FileId, FileRecord, VolumeId};
     use tempfile::TempDir;
 
+    #[test]
+    fn test_save_over_machine_config() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("glint.toml");
+        let machine: toml::Table = "[general]\nmax_results = 500\n".parse().unwrap();
+
+        let mut config = Config::default();
+        config.general.max_results = 500;
+        config.ui.page_size = 20;
+        config.save_over(&path, Some(&machine)).unwrap();
+
+        let saved: toml::Table = fs::read_to_string(&path).unwrap().parse().unwrap();
+        assert!(saved["general"].get("max_results").is_none());
+        assert_eq!(saved["ui"]["page_size"].as_integer(), Some(20));
+    }
+
     #[test]
     fn test_default_config() {
         let config = Config::default();
         assert!(config.general.auto_start_usn);
         assert_eq!(config.general.max_results, 10000);
+        // Nothing listens for queries until asked to
+        assert!(!config.server.enabled);
+        assert!(config.server.token.is_none());
     }
 
     #[test]
@@ -367,6 +941,47 @@ mod tests {
         assert_eq!(config.general.max_results, 10000); // Default value
     }
 
+    #[test]
+    fn test_report_jobs() {
+        let config: Config = toml::from_str(
+            r#"
+            [[reports.jobs]]
+            name = "weekly"
+            schedule = "@weekly"
+            min_size = "1GB"
+            format = "json"
+            "#,
+        )
+        .unwrap();
+
+        let job = &config.reports.jobs[0];
+        assert_eq!(job.name, "weekly");
+        assert_eq!(job.format, crate::report::ReportFormat::Json);
+        assert_eq!(job.limit, 1000);
+        assert!(job.parsed_schedule().is_ok());
+    }
+
+    #[test]
+    fn test_scopes() {
+        let config: Config = toml::from_str(
+            r#"
+            [scopes]
+            Work = ['C:\repos', 'D:\docs']
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.scopes.names().collect::<Vec<_>>(), vec!["Work"]);
+        assert_eq!(
+            config.scopes.resolve("work").unwrap(),
+            &["C:\\repos".to_string(), "D:\\docs".to_string()]
+        );
+
+        let saved = toml::to_string(&config).unwrap();
+        let loaded: Config = toml::from_str(&saved).unwrap();
+        assert_eq!(loaded.scopes, config.scopes);
+    }
+
     #[test]
     fn test_should_exclude_path() {
         let mut config = Config::default();
@@ -414,12 +1029,62 @@ mod tests {
         // With explicit includes
         config.volumes.include = vec!["C:".to_string()];
         assert!(config.should_index_volume("C:"));
+        assert!(config.should_index_volume("C:\\"));
         assert!(!config.should_index_volume("D:"));
 
+        // Volumes mounted in folders are named by their mount folder
+        config.volumes.include = vec!["C:\\Mount\\Data".to_string()];
+        assert!(config.should_index_volume("c:\\mount\\data\\"));
+        assert!(!config.should_index_volume("C:\\"));
+
         // With excludes only
         config.volumes.include.clear();
         config.volumes.exclude = vec!["D:".to_string()];
         assert!(config.should_index_volume("C:"));
         assert!(!config.should_index_volume("D:"));
+
+        // Folders replace whole volumes unless some are included
+        config.volumes.folders = vec!["D:\\Projects".to_string()];
+        assert!(!config.should_index_volume("C:"));
+        config.volumes.include = vec!["C:".to_string()];
+        assert!(config.should_index_volume("C:"));
+    }
+
+    #[test]
+    fn test_disabled_volumes() {
+        let mut config = Config::default();
+        assert!(!config.is_volume_disabled("E:\\"));
+
+        assert!(config.set_volume_disabled("E:\\", true));
+        assert!(!config.set_volume_disabled("e:", true));
+        assert!(config.is_volume_disabled("E:"));
+        assert_eq!(config.volumes.disabled, vec!["E:".to_string()]);
+
+        assert!(config.set_volume_disabled("e:\\", false));
+        assert!(config.volumes.disabled.is_empty());
+    }
+
+    #[test]
+    fn test_hide_filters() {
+        let mut config = Config::default();
+        assert!(config.ui.hide_filters().unwrap().is_empty());
+
+        config.ui.hide_patterns = vec!["*.pyc".to_string(), "__pycache__".to_string()];
+        let filters = config.ui.hide_filters().unwrap();
+        let shown = |path: &str| {
+            let name = path.rsplit('\\').next().unwrap();
+            let record = FileRecord::new(
+                FileId::new(1),
+                None,
+                VolumeId::new("C"),
+                name.to_string(),
+                path.to_string(),
+                false,
+            );
+            filters.iter().all(|f| f.matches(&record))
+        };
+        assert!(shown("C:\\src\\main.py"));
+        assert!(!shown("C:\\src\\main.PYC"));
+        assert!(!shown("C:\\src\\__pycache__\\main.py"));
     }
 }
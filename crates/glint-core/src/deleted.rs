@@ -0,0 +1,140 @@
+//! Files recently removed from the index.
+//!
+//! A deletion leaves only a blanked tombstone in the index, so the last
+//! record of each removed file goes into [`RecentlyDeleted`] instead, with
+//! when it went. The `deleted:` query token searches this list, newest
+//! first, to show what just disappeared and where it was. The list keeps
+//! the latest [`MAX_RECENTLY_DELETED`] files and is saved next to the index.
+
+use crate::error::{GlintError, Result};
+use crate::types::{FileId, FileRecord, VolumeId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+use tracing::warn;
+
+/// Deleted files remembered at most; older ones are forgotten.
+pub const MAX_RECENTLY_DELETED: usize = 10_000;
+
+/// A file as it was when it was deleted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedRecord {
+    pub record: FileRecord,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// The files deleted most recently, oldest first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecentlyDeleted {
+    entries: VecDeque<DeletedRecord>,
+}
+
+impl RecentlyDeleted {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember that `record` was deleted at `deleted_at`.
+    pub fn push(&mut self, record: FileRecord, deleted_at: DateTime<Utc>) {
+        if self.entries.len() == MAX_RECENTLY_DELETED {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(DeletedRecord { record, deleted_at });
+    }
+
+    /// Forget a file that is back in the index, such as one restored from
+    /// the Recycle Bin.
+    pub fn forget(&mut self, volume_id: &VolumeId, file_id: FileId) {
+        self.entries
+            .retain(|d| d.record.id != file_id || d.record.volume_id != *volume_id);
+    }
+
+    /// The deleted files, newest first.
+    pub fn newest_first(&self) -> impl Iterator<Item = &DeletedRecord> {
+        self.entries.iter().rev()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub(crate) fn entries_mut(&mut self) -> impl Iterator<Item = &mut DeletedRecord> {
+        self.entries.iter_mut()
+    }
+
+    /// Write the list as JSON.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents =
+            serde_json::to_string(self).map_err(|e| GlintError::serialization(e.to_string()))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Read a list; None if there is none or it can't be read.
+    pub fn load(path: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&contents)
+            .map_err(
+                |e| warn!(path = %path.display(), error = %e, "Ignoring unreadable deleted files"),
+            )
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: u64, name: &str) -> FileRecord {
+        FileRecord::new(
+            FileId::new(id),
+            Some(FileId::new(5)),
+            VolumeId::new("C"),
+            name.to_string(),
+            format!(r"C:\Users\{}", name),
+            false,
+        )
+    }
+
+    #[test]
+    fn test_recently_deleted() {
+        let mut deleted = RecentlyDeleted::new();
+        let now = Utc::now();
+        for i in 0..MAX_RECENTLY_DELETED as u64 + 2 {
+            deleted.push(record(i, &format!("{}.txt", i)), now);
+        }
+        assert_eq!(deleted.len(), MAX_RECENTLY_DELETED);
+        let newest: Vec<u64> = deleted
+            .newest_first()
+            .map(|d| d.record.id.as_u64())
+            .collect();
+        assert_eq!(newest[0], MAX_RECENTLY_DELETED as u64 + 1);
+        // The oldest two were dropped
+        assert_eq!(*newest.last().unwrap(), 2);
+
+        deleted.forget(&VolumeId::new("C"), FileId::new(3));
+        deleted.forget(&VolumeId::new("D"), FileId::new(4));
+        assert_eq!(deleted.len(), MAX_RECENTLY_DELETED - 1);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("glint.deleted.json");
+        deleted.save(&path).unwrap();
+        let loaded = RecentlyDeleted::load(&path).unwrap();
+        let first = loaded.newest_first().next().unwrap();
+        assert_eq!(
+            first.record.path,
+            format!(r"C:\Users\{}.txt", MAX_RECENTLY_DELETED + 1)
+        );
+        assert_eq!(first.deleted_at, now);
+        assert!(RecentlyDeleted::load(&dir.path().join("missing.json")).is_none());
+    }
+}
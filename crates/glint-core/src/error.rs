@@ -33,6 +33,10 @@ pub enum GlintError {
     #[error("index is stale for volume {volume}: {reason}")]
     IndexStale { volume: String, reason: String },
 
+    /// The index was opened read-only and something tried to change it
+    #[error("index is read-only: cannot {operation}")]
+    ReadOnly { operation: String },
+
     // === Filesystem Backend Errors ===
     /// Volume not found or inaccessible
     #[error("volume not found: {volume}")]
@@ -115,8 +119,16 @@ impl GlintError {
     pub fn serialization(reason: impl Into<String>) -> Self {
         GlintError::Serialization(reason.into())
     }
+
+    /// Create a read-only error for a refused `operation`
+    pub fn read_only(operation: impl Into<String>) -> Self {
+        GlintError::ReadOnly {
+            operation: operation.into(),
+        }
+    }
 }
 
+#[cfg(feature = "persistence")]
 impl From<bincode::Error> for GlintError {
     fn from(err: bincode::Error) -> Self {
         GlintError::Serialization(err.to_string())
@@ -0,0 +1,389 @@
+//! Exclusions applied while indexing.
+//!
+//! Besides the paths and name patterns listed under `[exclude]`, Glint has
+//! built-in presets for folders that change constantly but are rarely
+//! searched, such as browser caches and temp folders. Their churn would
+//! otherwise fill the index and keep the change journal busy. Presets are
+//! turned on by name:
+//!
+//! ```toml
+//! [exclude]
+//! presets = ["browser-cache", "temp", "recycle-bin"]
+//! ```
+//!
+//! Excluded paths and presets leave out a whole folder with everything in
+//! it; name patterns leave out matching files and folders only. Each of
+//! them is an [`ExcludeRule`], which the GUI's exclusions manager and
+//! `glint exclude` add, remove and test one at a time.
+
+use crate::config::{matches_simple_pattern, ExcludeConfig};
+use crate::index::Index;
+use crate::types::{to_lower, FileRecord};
+use std::fmt;
+
+/// A built-in set of folders to leave out of the index.
+#[derive(Debug)]
+pub struct ExcludePreset {
+    /// Name used in the config file
+    pub name: &'static str,
+
+    /// What the preset leaves out
+    pub description: &'static str,
+
+    /// Folders, matched anywhere in a path; `*` stands for any one folder
+    folders: &'static [&'static str],
+}
+
+/// The built-in presets. All of them are on by default.
+pub const PRESETS: &[ExcludePreset] = &[
+    ExcludePreset {
+        name: "browser-cache",
+        description: "Chrome, Edge and Firefox caches",
+        folders: &[
+            r"Google\Chrome\User Data\*\Cache",
+            r"Google\Chrome\User Data\*\Code Cache",
+            r"Google\Chrome\User Data\*\GPUCache",
+            r"Microsoft\Edge\User Data\*\Cache",
+            r"Microsoft\Edge\User Data\*\Code Cache",
+            r"Microsoft\Edge\User Data\*\GPUCache",
+            r"Mozilla\Firefox\Profiles\*\cache2",
+        ],
+    },
+    ExcludePreset {
+        name: "temp",
+        description: "Windows and per-user temp folders",
+        folders: &[r"AppData\Local\Temp", r"Windows\Temp"],
+    },
+    ExcludePreset {
+        name: "recycle-bin",
+        description: "Deleted files in the Recycle Bin",
+        folders: &[r"$Recycle.Bin"],
+    },
+];
+
+/// Names of all presets, the default for `[exclude] presets`.
+pub fn default_presets() -> Vec<String> {
+    PRESETS.iter().map(|p| p.name.to_string()).collect()
+}
+
+/// Find a built-in preset by name.
+pub fn preset(name: &str) -> Option<&'static ExcludePreset> {
+    PRESETS.iter().find(|p| p.name.eq_ignore_ascii_case(name))
+}
+
+impl ExcludePreset {
+    /// Check if the preset covers a folder (or anything inside it).
+    pub fn covers(&self, path: &str) -> bool {
+        let path_lower = to_lower(path);
+        let parts = split_path(&path_lower);
+        self.folders
+            .iter()
+            .any(|folder| contains_folder(&parts, &split_pattern(folder)))
+    }
+}
+
+/// Exclusions compiled from the config for matching against records.
+#[derive(Debug, Clone, Default)]
+pub struct Exclusions {
+    /// Excluded path prefixes, lowercase without trailing separators
+    paths: Vec<String>,
+
+    /// Name patterns, as in [`Config::should_exclude_name`](crate::Config::should_exclude_name)
+    patterns: Vec<String>,
+
+    /// Folder patterns from enabled presets, split into lowercase parts
+    folders: Vec<Vec<String>>,
+}
+
+impl Exclusions {
+    /// Compile the `[exclude]` section. Unknown preset names are ignored.
+    pub fn new(config: &ExcludeConfig) -> Self {
+        let folders = config
+            .presets
+            .iter()
+            .filter_map(|name| preset(name))
+            .flat_map(|preset| preset.folders.iter().map(|f| split_pattern(f)))
+            .collect();
+        Exclusions {
+            paths: config
+                .paths
+                .iter()
+                .map(|p| to_lower(p.trim_end_matches(['\\', '/'])))
+                .filter(|p| !p.is_empty())
+                .collect(),
+            patterns: config.patterns.clone(),
+            folders,
+        }
+    }
+
+    /// Check if nothing is excluded.
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty() && self.patterns.is_empty() && self.folders.is_empty()
+    }
+
+    /// Check if a record is excluded, by its folder or its name.
+    pub fn excludes(&self, record: &FileRecord) -> bool {
+        self.excludes_folder(&record.path_lower()) || self.excludes_name(&record.name)
+    }
+
+    /// Check if a path (lowercase) is, or is inside, an excluded folder.
+    pub fn excludes_folder(&self, path_lower: &str) -> bool {
+        let in_path = self.paths.iter().any(|prefix| {
+            path_lower.strip_prefix(prefix.as_str()).is_some_and(|rest| {
+                rest.is_empty() || rest.starts_with(['\\', '/'])
+            })
+        });
+        if in_path || self.folders.is_empty() {
+            return in_path;
+        }
+        let parts = split_path(path_lower);
+        self.folders
+            .iter()
+            .any(|folder| contains_folder(&parts, folder))
+    }
+
+    /// Check if a file or folder name matches an excluded pattern.
+    pub fn excludes_name(&self, name: &str) -> bool {
+        self.patterns
+            .iter()
+            .any(|pattern| matches_simple_pattern(name, pattern))
+    }
+
+    /// Check if a full path, in any case, is excluded by its folder or its
+    /// name, as a record with that path would be.
+    pub fn excludes_path(&self, path: &str) -> bool {
+        let path = path.trim_end_matches(['\\', '/']);
+        let name = path.rsplit(['\\', '/']).next().unwrap_or(path);
+        self.excludes_folder(&to_lower(path)) || self.excludes_name(name)
+    }
+}
+
+/// One entry of the `[exclude]` section.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ExcludeRule {
+    /// A folder left out with everything in it
+    Path(String),
+    /// A name pattern such as `*.tmp`, matched against files and folders
+    Pattern(String),
+    /// A built-in preset, by name
+    Preset(String),
+}
+
+impl ExcludeRule {
+    /// Read a rule as typed: a preset name, a name pattern if it has a
+    /// wildcard or no folder separator, or else a folder path. Returns
+    /// `None` for blank input.
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        if input.is_empty() {
+            return None;
+        }
+        if let Some(preset) = preset(input) {
+            return Some(ExcludeRule::Preset(preset.name.to_string()));
+        }
+        let rule = if input.contains('*') || !input.contains(['\\', '/']) {
+            ExcludeRule::Pattern(input.to_string())
+        } else {
+            ExcludeRule::Path(input.to_string())
+        };
+        Some(rule)
+    }
+
+    /// The folder, pattern or preset name.
+    pub fn text(&self) -> &str {
+        match self {
+            ExcludeRule::Path(text) | ExcludeRule::Pattern(text) | ExcludeRule::Preset(text) => {
+                text
+            }
+        }
+    }
+
+    /// What kind of rule this is, for listings.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ExcludeRule::Path(_) => "folder",
+            ExcludeRule::Pattern(_) => "pattern",
+            ExcludeRule::Preset(_) => "preset",
+        }
+    }
+
+    /// The rule on its own, compiled for matching.
+    pub fn exclusions(&self) -> Exclusions {
+        let mut config = ExcludeConfig {
+            presets: Vec::new(),
+            ..Default::default()
+        };
+        config.add_rule(self.clone());
+        Exclusions::new(&config)
+    }
+}
+
+impl fmt::Display for ExcludeRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.kind(), self.text())
+    }
+}
+
+impl ExcludeConfig {
+    /// The excluded folders, name patterns and enabled presets, in that
+    /// order.
+    pub fn rules(&self) -> Vec<ExcludeRule> {
+        let paths = self.paths.iter().cloned().map(ExcludeRule::Path);
+        let patterns = self.patterns.iter().cloned().map(ExcludeRule::Pattern);
+        let presets = self.presets.iter().cloned().map(ExcludeRule::Preset);
+        paths.chain(patterns).chain(presets).collect()
+    }
+
+    /// Add a rule, returning false if it was already there.
+    pub fn add_rule(&mut self, rule: ExcludeRule) -> bool {
+        let (list, text) = match rule {
+            ExcludeRule::Path(path) => (&mut self.paths, path),
+            ExcludeRule::Pattern(pattern) => (&mut self.patterns, pattern),
+            ExcludeRule::Preset(name) => (&mut self.presets, name),
+        };
+        if list.iter().any(|t| t.eq_ignore_ascii_case(&text)) {
+            return false;
+        }
+        list.push(text);
+        true
+    }
+
+    /// Remove a rule, returning false if it wasn't there.
+    pub fn remove_rule(&mut self, rule: &ExcludeRule) -> bool {
+        let list = match rule {
+            ExcludeRule::Path(_) => &mut self.paths,
+            ExcludeRule::Pattern(_) => &mut self.patterns,
+            ExcludeRule::Preset(_) => &mut self.presets,
+        };
+        let before = list.len();
+        list.retain(|t| !t.eq_ignore_ascii_case(rule.text()));
+        list.len() != before
+    }
+
+    /// The first rule excluding `path`, if any.
+    pub fn matching_rule(&self, path: &str) -> Option<ExcludeRule> {
+        self.rules()
+            .into_iter()
+            .find(|rule| rule.exclusions().excludes_path(path))
+    }
+}
+
+/// How many records of `index` each of `rules` leaves out. A record
+/// left out by several rules counts for each of them.
+pub fn count_by_rule(index: &Index, rules: &[ExcludeRule]) -> Vec<usize> {
+    let compiled: Vec<Exclusions> = rules.iter().map(ExcludeRule::exclusions).collect();
+    let mut counts = vec![0; rules.len()];
+    index.for_each_record(|record| {
+        for (count, exclusions) in counts.iter_mut().zip(&compiled) {
+            if exclusions.excludes(record) {
+                *count += 1;
+            }
+        }
+    });
+    counts
+}
+
+fn split_path(path: &str) -> Vec<&str> {
+    path.split(['\\', '/']).filter(|p| !p.is_empty()).collect()
+}
+
+fn split_pattern(pattern: &str) -> Vec<String> {
+    pattern.split('\\').map(to_lower).collect()
+}
+
+/// Check if `parts` contains the folder `pattern` as consecutive parts.
+fn contains_folder(parts: &[&str], pattern: &[String]) -> bool {
+    pattern.len() <= parts.len()
+        && parts.windows(pattern.len()).any(|window| {
+            window
+                .iter()
+                .zip(pattern)
+                .all(|(part, want)| want == "*" || part == want)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exclusions(paths: &[&str], patterns: &[&str], presets: &[&str]) -> Exclusions {
+        Exclusions::new(&ExcludeConfig {
+            paths: paths.iter().map(|s| s.to_string()).collect(),
+            patterns: patterns.iter().map(|s| s.to_string()).collect(),
+            presets: presets.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_presets() {
+        let ex = exclusions(&[], &[], &["browser-cache", "temp"]);
+        assert!(ex.excludes_folder(
+            r"c:\users\ann\appdata\local\google\chrome\user data\default\cache\cache_data\f_0001"
+        ));
+        assert!(ex.excludes_folder(r"c:\users\ann\appdata\local\temp"));
+        assert!(ex.excludes_folder(r"c:\windows\temp\x.log"));
+        assert!(!ex.excludes_folder(r"c:\users\ann\appdata\local\google\chrome\user data\default\bookmarks"));
+        assert!(!ex.excludes_folder(r"c:\users\ann\documents\temp notes.txt"));
+        assert!(!ex.excludes_folder(r"c:\$recycle.bin\s-1-5-21\$r1.txt"));
+
+        let recycle = preset("Recycle-Bin").unwrap();
+        assert!(recycle.covers(r"C:\$Recycle.Bin\S-1-5-21"));
+        assert!(Exclusions::new(&ExcludeConfig::default()).excludes_folder(r"d:\$recycle.bin"));
+    }
+
+    #[test]
+    fn test_paths_and_patterns() {
+        let ex = exclusions(&[r"D:\Build\"], &["*.tmp"], &[]);
+        assert!(ex.excludes_folder(r"d:\build"));
+        assert!(ex.excludes_folder(r"d:\build\out.o"));
+        assert!(!ex.excludes_folder(r"d:\builds\out.o"));
+        assert!(ex.excludes_name("Report.TMP"));
+        assert!(!ex.excludes_name("report.txt"));
+        assert!(exclusions(&[], &[], &["no-such-preset"]).is_empty());
+    }
+
+    #[test]
+    fn test_rules() {
+        assert_eq!(
+            ExcludeRule::parse(r" D:\Build "),
+            Some(ExcludeRule::Path(r"D:\Build".to_string()))
+        );
+        assert_eq!(
+            ExcludeRule::parse("*.tmp"),
+            Some(ExcludeRule::Pattern("*.tmp".to_string()))
+        );
+        assert_eq!(
+            ExcludeRule::parse("node_modules"),
+            Some(ExcludeRule::Pattern("node_modules".to_string()))
+        );
+        assert_eq!(
+            ExcludeRule::parse("Temp"),
+            Some(ExcludeRule::Preset("temp".to_string()))
+        );
+        assert_eq!(ExcludeRule::parse("  "), None);
+
+        let mut config = ExcludeConfig {
+            presets: Vec::new(),
+            ..Default::default()
+        };
+        assert!(config.add_rule(ExcludeRule::parse(r"D:\Build").unwrap()));
+        assert!(!config.add_rule(ExcludeRule::parse(r"d:\build").unwrap()));
+        assert!(config.add_rule(ExcludeRule::parse("*.tmp").unwrap()));
+        assert_eq!(config.rules().len(), 2);
+
+        assert_eq!(
+            config.matching_rule(r"D:\Build\obj\a.o"),
+            Some(ExcludeRule::Path(r"D:\Build".to_string()))
+        );
+        assert_eq!(
+            config.matching_rule(r"C:\Users\ann\notes.TMP"),
+            Some(ExcludeRule::Pattern("*.tmp".to_string()))
+        );
+        assert_eq!(config.matching_rule(r"C:\Users\ann\notes.txt"), None);
+
+        assert!(config.remove_rule(&ExcludeRule::Path(r"D:\BUILD".to_string())));
+        assert!(!config.remove_rule(&ExcludeRule::Path(r"D:\Build".to_string())));
+        assert_eq!(config.matching_rule(r"D:\Build\obj\a.o"), None);
+    }
+}
@@ -0,0 +1,268 @@
+//! Interned file extensions.
+//!
+//! Every distinct lowercase extension gets a small numeric ID, stored on
+//! each [`FileRecord`] when its name is set. Extension filters compare
+//! these IDs instead of strings, and the index counts files per ID for
+//! extension facets.
+//!
+//! The table is shared by the whole process and only grows. Extensions
+//! that are very long (usually not real extensions, such as timestamps
+//! after a dot) or that arrive after the table is full get
+//! [`OTHER_EXTENSION`] and are compared by name instead.
+//!
+//! What counts as a name's extension is decided by [`of_name`] alone; the
+//! records, filters, stats and renames all go through it.
+
+use crate::types::{to_lower, FileRecord};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// ID of an interned lowercase extension.
+pub type ExtId = u16;
+
+/// ID of names without an extension.
+pub const NO_EXTENSION: ExtId = 0;
+
+/// ID shared by all extensions that are not interned.
+pub const OTHER_EXTENSION: ExtId = ExtId::MAX;
+
+/// Longest extension that is interned, in bytes.
+const MAX_INTERNED_LEN: usize = 16;
+
+/// Extensions by ID and IDs by extension.
+struct Table {
+    ids: HashMap<String, ExtId>,
+    names: Vec<String>,
+}
+
+fn table() -> &'static RwLock<Table> {
+    static TABLE: OnceLock<RwLock<Table>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        RwLock::new(Table {
+            ids: HashMap::new(),
+            // ID 0 is "no extension"
+            names: vec![String::new()],
+        })
+    })
+}
+
+/// Intern an extension, given in any case.
+pub fn intern(ext: &str) -> ExtId {
+    intern_lower(&to_lower(ext))
+}
+
+/// Intern an extension that is already lowercase.
+fn intern_lower(ext: &str) -> ExtId {
+    if ext.is_empty() {
+        return NO_EXTENSION;
+    }
+    if ext.len() > MAX_INTERNED_LEN {
+        return OTHER_EXTENSION;
+    }
+    if let Some(&id) = table().read().ids.get(ext) {
+        return id;
+    }
+
+    let mut table = table().write();
+    if let Some(&id) = table.ids.get(ext) {
+        return id;
+    }
+    let Ok(id) = ExtId::try_from(table.names.len()) else {
+        return OTHER_EXTENSION;
+    };
+    if id == OTHER_EXTENSION {
+        return OTHER_EXTENSION;
+    }
+    table.names.push(ext.to_string());
+    table.ids.insert(ext.to_string(), id);
+    id
+}
+
+/// The extension of a file name: the text after its last dot.
+///
+/// - Leading dots don't start an extension: `.gitignore` has none, while
+///   `.eslintrc.json` has `json`.
+/// - Neither does a trailing dot: `notes.` has none.
+/// - Only the last part counts: `archive.tar.gz` has `gz`. An
+///   [`ExtensionSet`] given `tar.gz` matches the whole suffix instead.
+pub fn of_name(name: &str) -> Option<&str> {
+    let (stem, ext) = name.rsplit_once('.')?;
+    if ext.is_empty() || stem.trim_start_matches('.').is_empty() {
+        return None;
+    }
+    Some(ext)
+}
+
+/// The ID of the extension of a lowercase file name.
+pub(crate) fn of_lower_name(name_lower: &str) -> ExtId {
+    of_name(name_lower).map_or(NO_EXTENSION, intern_lower)
+}
+
+/// The extension an ID stands for (None for the reserved IDs).
+pub fn name(id: ExtId) -> Option<String> {
+    if id == NO_EXTENSION || id == OTHER_EXTENSION {
+        return None;
+    }
+    table().read().names.get(usize::from(id)).cloned()
+}
+
+/// A set of extensions to filter by, with their interned IDs.
+///
+/// An extension of several parts such as `tar.gz` matches names ending in
+/// `.tar.gz`, following the same rules as [`of_name`] for leading dots.
+#[derive(Debug, Clone, Default)]
+pub struct ExtensionSet {
+    /// Lowercase extensions, as given
+    names: Vec<String>,
+    /// Interned IDs of the single-part `names`, without [`OTHER_EXTENSION`]
+    ids: Vec<ExtId>,
+    /// The multi-part `names`, with their leading dot
+    suffixes: Vec<String>,
+}
+
+impl ExtensionSet {
+    /// Create a set from extensions in any case, with or without the dot.
+    pub fn new<I, S>(extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let names: Vec<String> = extensions
+            .into_iter()
+            .map(|ext| to_lower(ext.as_ref().strip_prefix('.').unwrap_or(ext.as_ref())))
+            .collect();
+        let (multi, single): (Vec<&String>, Vec<&String>) =
+            names.iter().partition(|ext| ext.contains('.'));
+        let ids = single
+            .into_iter()
+            .map(|ext| intern_lower(ext))
+            .filter(|&id| id != NO_EXTENSION && id != OTHER_EXTENSION)
+            .collect();
+        let suffixes = multi.into_iter().map(|ext| format!(".{}", ext)).collect();
+        ExtensionSet {
+            names,
+            ids,
+            suffixes,
+        }
+    }
+
+    /// The extensions in the set, lowercase.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Check if a record's extension is in the set.
+    pub fn contains(&self, record: &FileRecord) -> bool {
+        let by_id = match record.ext_id {
+            NO_EXTENSION => false,
+            OTHER_EXTENSION => record
+                .extension()
+                .is_some_and(|ext| self.names.iter().any(|n| to_lower(ext) == *n)),
+            id => self.ids.contains(&id),
+        };
+        by_id || self.has_suffix(&record.name_lower())
+    }
+
+    /// Check a lowercase name and its extension ID against the set, or
+    /// `None` if the extension is too long to be interned and only the
+    /// record can tell.
+    pub(crate) fn contains_row(&self, name_lower: &str, ext_id: ExtId) -> Option<bool> {
+        let by_id = match ext_id {
+            NO_EXTENSION => false,
+            OTHER_EXTENSION => return None,
+            id => self.ids.contains(&id),
+        };
+        Some(by_id || self.has_suffix(name_lower))
+    }
+
+    fn has_suffix(&self, name_lower: &str) -> bool {
+        self.suffixes.iter().any(|suffix| {
+            name_lower
+                .strip_suffix(suffix.as_str())
+                .is_some_and(|stem| !stem.trim_start_matches('.').is_empty())
+        })
+    }
+}
+
+impl From<Vec<String>> for ExtensionSet {
+    fn from(extensions: Vec<String>) -> Self {
+        ExtensionSet::new(extensions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{FileId, VolumeId};
+
+    fn record(name: &str) -> FileRecord {
+        FileRecord::new(
+            FileId::new(1),
+            None,
+            VolumeId::new("C"),
+            name.to_string(),
+            format!("C:\\{}", name),
+            false,
+        )
+    }
+
+    #[test]
+    fn test_intern() {
+        let rs = intern("rs");
+        assert_ne!(rs, NO_EXTENSION);
+        assert_eq!(intern("RS"), rs);
+        assert_eq!(name(rs).as_deref(), Some("rs"));
+        assert_eq!(intern(""), NO_EXTENSION);
+        assert_eq!(intern("20240101T120000Z"), intern("20240101t120000z"));
+        assert_eq!(intern("20240101T120000Z-backup"), OTHER_EXTENSION);
+
+        assert_eq!(record("main.RS").ext_id, rs);
+        assert_eq!(record("Makefile").ext_id, NO_EXTENSION);
+        assert_eq!(record(".gitignore").ext_id, NO_EXTENSION);
+    }
+
+    #[test]
+    fn test_of_name() {
+        assert_eq!(of_name("main.rs"), Some("rs"));
+        assert_eq!(of_name("archive.tar.gz"), Some("gz"));
+        assert_eq!(of_name("Makefile"), None);
+        assert_eq!(of_name(".gitignore"), None);
+        assert_eq!(of_name("..hidden"), None);
+        assert_eq!(of_name(".eslintrc.json"), Some("json"));
+        assert_eq!(of_name("notes."), None);
+        assert_eq!(of_name("."), None);
+        assert_eq!(of_name(""), None);
+    }
+
+    #[test]
+    fn test_extension_set() {
+        let set = ExtensionSet::new(["RS", "toml"]);
+        assert_eq!(set.names(), ["rs", "toml"]);
+        assert!(set.contains(&record("main.rs")));
+        assert!(set.contains(&record("Cargo.TOML")));
+        assert!(!set.contains(&record("notes.txt")));
+        assert!(!set.contains(&record("Makefile")));
+
+        // Long extensions are compared by name
+        let long = ExtensionSet::new(["backup-2024-01-01"]);
+        assert!(long.contains(&record("db.Backup-2024-01-01")));
+        assert!(!long.contains(&record("db.backup-2023-12-31")));
+
+        // A leading dot is optional
+        assert!(ExtensionSet::new([".rs"]).contains(&record("main.rs")));
+
+        // `gz` matches the last part, `tar.gz` the whole suffix
+        let gz = ExtensionSet::new(["gz"]);
+        let tar_gz = ExtensionSet::new(["TAR.GZ"]);
+        assert!(gz.contains(&record("backup.tar.gz")));
+        assert!(gz.contains(&record("log.gz")));
+        assert!(tar_gz.contains(&record("backup.tar.gz")));
+        assert!(!tar_gz.contains(&record("log.gz")));
+        assert!(!tar_gz.contains(&record(".tar.gz")));
+
+        // Dotfiles and trailing dots have no extension
+        assert!(!ExtensionSet::new(["gitignore"]).contains(&record(".gitignore")));
+        assert!(!ExtensionSet::new([""]).contains(&record("notes.")));
+    }
+}
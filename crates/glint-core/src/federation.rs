@@ -0,0 +1,560 @@
+//! Searching other indexes alongside the local one.
+//!
+//! An index exported from a server, or an older snapshot of this machine's
+//! index, can be attached under a name. Attached indexes are loaded
+//! read-only and searched together with the local index by a
+//! [`Federation`]; each of their results carries the name of the index it
+//! came from in [`SearchResult::source`], so frontends can badge it.
+//!
+//! The list of attached indexes is kept in the local index directory, like
+//! pins; see [`AttachedList`].
+//!
+//! Glint servers on other machines (see [`RemoteServer`]) can be searched
+//! too: [`fan_out`] sends a query to all of them at once while the local
+//! search runs, and [`merge_remote`] mixes their results in, tagged with
+//! the server's name like those of an attached index.
+
+use crate::config::RemoteServer;
+use crate::error::{GlintError, Result};
+use crate::index::Index;
+use crate::ipc::QueryRequest;
+use crate::persistence::IndexStore;
+use crate::search::{ResultTotals, SearchPage, SearchQuery, SearchResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tracing::warn;
+
+/// File name of the attached index list inside the index directory.
+pub const ATTACHED_FILE: &str = "attached.json";
+
+/// An index attached to the local one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachedIndex {
+    /// Name shown next to its results
+    pub name: String,
+
+    /// Directory holding the index
+    pub dir: PathBuf,
+
+    /// When the index was attached
+    pub attached_at: DateTime<Utc>,
+}
+
+/// The list of attached indexes, in the order they were attached.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttachedList {
+    indexes: Vec<AttachedIndex>,
+}
+
+impl AttachedList {
+    /// Create an empty list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Path of the list inside an index directory.
+    pub fn path_in(dir: &Path) -> PathBuf {
+        dir.join(ATTACHED_FILE)
+    }
+
+    /// Load the list, returning an empty one if the file does not exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+        let contents = fs::read_to_string(path)?;
+        serde_json::from_str(&contents).map_err(|e| GlintError::serialization(e.to_string()))
+    }
+
+    /// Save the list, replacing the previous file.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| GlintError::serialization(e.to_string()))?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// All attached indexes, oldest first.
+    pub fn indexes(&self) -> &[AttachedIndex] {
+        &self.indexes
+    }
+
+    /// Number of attached indexes.
+    pub fn len(&self) -> usize {
+        self.indexes.len()
+    }
+
+    /// Check if nothing is attached.
+    pub fn is_empty(&self) -> bool {
+        self.indexes.is_empty()
+    }
+
+    /// Attach the index at `path` as `name`.
+    ///
+    /// `path` is an index directory or the index file inside one. Fails if
+    /// there is no index there or `name` is empty or already taken.
+    pub fn attach(&mut self, name: &str, path: &Path) -> Result<&AttachedIndex> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(GlintError::ConfigError {
+                reason: "an attached index needs a name".to_string(),
+            });
+        }
+        if self
+            .indexes
+            .iter()
+            .any(|a| a.name.eq_ignore_ascii_case(name))
+        {
+            return Err(GlintError::ConfigError {
+                reason: format!("an index is already attached as '{}'", name),
+            });
+        }
+        let dir = index_dir(path)?;
+        self.indexes.push(AttachedIndex {
+            name: name.to_string(),
+            dir,
+            attached_at: Utc::now(),
+        });
+        Ok(&self.indexes[self.indexes.len() - 1])
+    }
+
+    /// Detach the index attached as `name`. Returns false if there was none.
+    pub fn detach(&mut self, name: &str) -> bool {
+        let before = self.indexes.len();
+        self.indexes
+            .retain(|a| !a.name.eq_ignore_ascii_case(name.trim()));
+        self.indexes.len() != before
+    }
+}
+
+/// The directory of the index at `path`, which is either that directory or
+/// the index file inside it.
+pub fn index_dir(path: &Path) -> Result<PathBuf> {
+    let dir = match path.is_file() {
+        true => path.parent().unwrap_or(Path::new(".")).to_path_buf(),
+        false => path.to_path_buf(),
+    };
+    let store = IndexStore::new(&dir);
+    if !store.exists() {
+        return Err(GlintError::IndexNotFound {
+            path: store.index_path(),
+        });
+    }
+    Ok(dir)
+}
+
+/// An attached index loaded for searching.
+pub struct Source {
+    /// Name shown next to its results
+    pub name: Arc<str>,
+
+    /// The index, read-only
+    pub index: Arc<Index>,
+}
+
+/// Attached indexes searched together with the local one.
+///
+/// The local index is passed to each search rather than held, so frontends
+/// keep owning it; its results have no source.
+#[derive(Default)]
+pub struct Federation {
+    sources: Vec<Source>,
+}
+
+impl Federation {
+    /// A federation with nothing attached.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load every index in `list`.
+    ///
+    /// Indexes that fail to load are left out and returned with their
+    /// error, so one missing snapshot doesn't stop the others.
+    pub fn open(list: &AttachedList) -> (Self, Vec<(String, GlintError)>) {
+        let mut federation = Self::new();
+        let mut failures = Vec::new();
+        for attached in list.indexes() {
+            let store = IndexStore::new(&attached.dir).with_read_only(true);
+            match store.load() {
+                Ok(index) => federation.add(&attached.name, Arc::new(index)),
+                Err(e) => {
+                    warn!(name = %attached.name, error = %e, "Could not load attached index");
+                    failures.push((attached.name.clone(), e));
+                }
+            }
+        }
+        (federation, failures)
+    }
+
+    /// Add a loaded index as `name`, making it read-only.
+    pub fn add(&mut self, name: &str, index: Arc<Index>) {
+        index.set_read_only(true);
+        self.sources.push(Source {
+            name: name.into(),
+            index,
+        });
+    }
+
+    /// The attached indexes, in the order they were added.
+    pub fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    /// Check if nothing is attached.
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /// Search `local` and every attached index, returning the first `limit`
+    /// results and the total match count.
+    ///
+    /// Local results come first, then those of each attached index in
+    /// turn, each in index order as from [`Index::search_counted`].
+    pub fn search_counted(&self, local: &Index, query: &SearchQuery, limit: usize) -> SearchPage {
+        let mut page = local.search_counted(query, limit);
+        for source in &self.sources {
+            let remaining = limit.saturating_sub(page.results.len());
+            let other = source.index.search_counted(query, remaining);
+            page.results.extend(tagged(other.results, &source.name));
+            page.total += other.total;
+            page.totals = page.totals.merge(other.totals);
+        }
+        page
+    }
+
+    /// Search `local` and every attached index, returning the results from
+    /// `offset` to `offset + limit` in rank order and the total match count.
+    ///
+    /// Results of all indexes are ranked together by
+    /// [`SearchResult::rank_cmp`], as in [`Index::search_ranked`].
+    pub fn search_ranked(
+        &self,
+        local: &Index,
+        query: &SearchQuery,
+        offset: usize,
+        limit: usize,
+    ) -> SearchPage {
+        if self.sources.is_empty() {
+            return local.search_ranked(query, offset, limit);
+        }
+        // Any result on the page is among the best `offset + limit` of its index
+        let best = offset.saturating_add(limit);
+        let mut page = local.search_ranked(query, 0, best);
+        for source in &self.sources {
+            let other = source.index.search_ranked(query, 0, best);
+            page.results.extend(tagged(other.results, &source.name));
+            page.total += other.total;
+            page.totals = page.totals.merge(other.totals);
+        }
+        page.results.sort_by(|a, b| a.rank_cmp(b));
+        page.results = page.results.into_iter().skip(offset).take(limit).collect();
+        page
+    }
+
+    /// Search `local` and every attached index allowing one typo, like
+    /// [`Index::search_fuzzy`].
+    pub fn search_fuzzy(
+        &self,
+        local: &Index,
+        query: &SearchQuery,
+        limit: usize,
+    ) -> Vec<SearchResult> {
+        let mut results = local.search_fuzzy(query, limit);
+        for source in &self.sources {
+            let remaining = limit.saturating_sub(results.len());
+            results.extend(tagged(
+                source.index.search_fuzzy(query, remaining),
+                &source.name,
+            ));
+        }
+        results
+    }
+}
+
+/// Mark `results` as coming from the index or server called `name`.
+fn tagged(results: Vec<SearchResult>, name: &Arc<str>) -> impl Iterator<Item = SearchResult> + '_ {
+    results.into_iter().map(move |mut result| {
+        result.source = Some(Arc::clone(name));
+        result
+    })
+}
+
+/// What a remote server returned for a search.
+#[derive(Debug)]
+pub struct RemoteOutcome {
+    /// Name of the server
+    pub name: Arc<str>,
+
+    /// Its results, tagged with its name, or why it could not be searched
+    pub results: Result<Vec<SearchResult>>,
+}
+
+/// Run `local` while `request` is sent to every server in `remotes`.
+///
+/// Each server is queried on its own thread and gets `timeout` to accept
+/// the connection and again for each read, so one that is down or slow
+/// fails on its own instead of holding up the others. Outcomes are in the
+/// order of `remotes`.
+pub fn fan_out<T>(
+    remotes: &[RemoteServer],
+    request: &QueryRequest,
+    timeout: Duration,
+    local: impl FnOnce() -> T,
+) -> (T, Vec<RemoteOutcome>) {
+    thread::scope(|scope| {
+        let queries: Vec<_> = remotes
+            .iter()
+            .map(|remote| scope.spawn(move || query_remote(remote, request.clone(), timeout)))
+            .collect();
+        let local = local();
+        let outcomes = remotes
+            .iter()
+            .zip(queries)
+            .map(|(remote, query)| {
+                let name: Arc<str> = remote.name.as_str().into();
+                let results = query
+                    .join()
+                    .unwrap_or_else(|_| Err(GlintError::Internal("remote search panicked".into())))
+                    .map(|results| tagged(results, &name).collect());
+                if let Err(e) = &results {
+                    warn!(server = %remote.name, error = %e, "Remote search failed");
+                }
+                RemoteOutcome { name, results }
+            })
+            .collect();
+        (local, outcomes)
+    })
+}
+
+fn query_remote(
+    remote: &RemoteServer,
+    request: QueryRequest,
+    timeout: Duration,
+) -> Result<Vec<SearchResult>> {
+    let mut client = remote.connect(timeout)?;
+    client.set_timeout(timeout)?;
+    if let Some(token) = &remote.token {
+        client.authenticate(token)?;
+    }
+    Ok(client.query(request)?.0)
+}
+
+/// Mix the results of `remotes` into `page`, keeping at most `limit`.
+///
+/// Remote results come back unscored, so the sources take turns: the
+/// first local result, then the first of each server, then the second of
+/// each, and so on. A server only reports the results it sends, so those
+/// are all that is added to the page's counts. Servers that failed are
+/// returned with their error.
+pub fn merge_remote(
+    page: &mut SearchPage,
+    remotes: Vec<RemoteOutcome>,
+    limit: usize,
+) -> Vec<(Arc<str>, GlintError)> {
+    let mut failures = Vec::new();
+    let mut sources = vec![std::mem::take(&mut page.results).into_iter()];
+    for remote in remotes {
+        match remote.results {
+            Ok(found) => {
+                page.total += found.len();
+                page.totals = page
+                    .totals
+                    .merge(ResultTotals::of(found.iter().map(|r| &r.record)));
+                sources.push(found.into_iter());
+            }
+            Err(e) => failures.push((remote.name, e)),
+        }
+    }
+
+    let results = &mut page.results;
+    while results.len() < limit {
+        let before = results.len();
+        for source in &mut sources {
+            results.extend(source.next());
+        }
+        if results.len() == before {
+            break;
+        }
+    }
+    results.truncate(limit);
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::VolumeInfo;
+    use crate::ipc::{IpcServer, QueryLimits};
+    use crate::types::{FileId, FileRecord, VolumeId};
+    use std::collections::HashSet;
+    use tempfile::TempDir;
+
+    fn index(names: &[&str]) -> Index {
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        let records = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                FileRecord::new(
+                    FileId::new(i as u64 + 1),
+                    None,
+                    volume.id.clone(),
+                    name.to_string(),
+                    format!("C:\\{}", name),
+                    false,
+                )
+                .with_size(100)
+            })
+            .collect();
+        let index = Index::new();
+        index.add_volume_records(&volume, records);
+        index
+    }
+
+    fn sources(results: &[SearchResult]) -> Vec<Option<&str>> {
+        results.iter().map(|r| r.source.as_deref()).collect()
+    }
+
+    #[test]
+    fn test_attached_list() {
+        let temp = TempDir::new().unwrap();
+        let server = temp.path().join("server");
+        IndexStore::new(&server).save(&index(&["a.txt"])).unwrap();
+
+        let mut list = AttachedList::new();
+        let store = IndexStore::new(&server);
+        assert_eq!(
+            list.attach("server", &store.index_path()).unwrap().dir,
+            server
+        );
+        assert!(list.attach("Server", &server).is_err());
+        assert!(list.attach(" ", &server).is_err());
+        assert!(matches!(
+            list.attach("empty", temp.path()),
+            Err(GlintError::IndexNotFound { .. })
+        ));
+
+        let path = AttachedList::path_in(temp.path());
+        list.save(&path).unwrap();
+        let mut loaded = AttachedList::load(&path).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert!(loaded.detach("SERVER"));
+        assert!(!loaded.detach("server"));
+        assert!(AttachedList::load(&temp.path().join("none.json"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_federated_search() {
+        let local = index(&["report.txt", "notes.txt"]);
+        let mut federation = Federation::new();
+        let server = Arc::new(index(&["report.txt", "server.log"]));
+        federation.add("server", Arc::clone(&server));
+        assert!(server.is_read_only());
+
+        let query = SearchQuery::substring("report");
+        let page = federation.search_counted(&local, &query, 10);
+        assert_eq!(sources(&page.results), [None, Some("server")]);
+        assert_eq!((page.total, page.totals.size), (2, 200));
+
+        let query = SearchQuery::substring("");
+        let page = federation.search_counted(&local, &query, 3);
+        assert_eq!(page.results.len(), 3);
+        assert_eq!(page.total, 4);
+
+        // Ranked pages cover every index without repeating a result
+        let first = federation.search_ranked(&local, &query, 0, 2);
+        let second = federation.search_ranked(&local, &query, 2, 2);
+        let seen: HashSet<_> = first
+            .results
+            .iter()
+            .chain(&second.results)
+            .map(|r| (r.source.clone(), r.record.id))
+            .collect();
+        assert_eq!(seen.len(), 4);
+        assert_eq!(second.total, 4);
+
+        let fuzzy = federation.search_fuzzy(&local, &SearchQuery::substring("servr"), 10);
+        assert_eq!(sources(&fuzzy), [Some("server")]);
+    }
+
+    #[test]
+    fn test_fan_out_and_merge() {
+        let served = Arc::new(index(&["a.txt", "b.txt"]));
+        let token = Some("secret".to_string());
+        let mut server = IpcServer::spawn(
+            "127.0.0.1:0",
+            served,
+            QueryLimits::default(),
+            token.clone(),
+            None,
+        )
+        .unwrap();
+        let addr = server.local_addr().to_string();
+        let remotes = [
+            RemoteServer {
+                name: "lab".to_string(),
+                addr: addr.clone(),
+                token,
+                tls_ca: None,
+            },
+            // Nothing listens on the discard port
+            RemoteServer {
+                name: "down".to_string(),
+                addr: "127.0.0.1:9".to_string(),
+                token: None,
+                tls_ca: None,
+            },
+            RemoteServer {
+                name: "no-token".to_string(),
+                addr,
+                token: None,
+                tls_ca: None,
+            },
+        ];
+        let local = index(&["c.txt", "d.txt", "e.txt"]);
+        let request = QueryRequest::new(".txt", 10);
+        let query = SearchQuery::substring(".txt");
+
+        let (mut page, outcomes) = fan_out(&remotes, &request, Duration::from_secs(2), || {
+            local.search_counted(&query, 10)
+        });
+        assert_eq!(outcomes.len(), 3);
+        let failures = merge_remote(&mut page, outcomes, 4);
+        let failed: Vec<&str> = failures.iter().map(|(name, _)| &**name).collect();
+        assert_eq!(failed, ["down", "no-token"]);
+        assert_eq!(
+            sources(&page.results),
+            [None, Some("lab"), None, Some("lab")]
+        );
+        assert_eq!((page.total, page.totals.files), (5, 5));
+        server.stop();
+    }
+
+    #[test]
+    fn test_open_skips_missing() {
+        let temp = TempDir::new().unwrap();
+        let server = temp.path().join("server");
+        IndexStore::new(&server).save(&index(&["a.txt"])).unwrap();
+        let mut list = AttachedList::new();
+        list.attach("server", &server).unwrap();
+        list.attach("gone", &server).unwrap();
+        list.indexes[1].dir = temp.path().join("gone");
+
+        let (federation, failures) = Federation::open(&list);
+        assert_eq!(federation.sources().len(), 1);
+        assert!(federation.sources()[0].index.is_read_only());
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, "gone");
+    }
+}
@@ -11,22 +11,35 @@
 //! ## Architecture
 //!
 //! The index uses a simple but effective design:
-//! - A `Vec<FileRecord>` stores all records for cache-friendly iteration
-//! - A `HashMap<(VolumeId, FileId), usize>` maps IDs to indices for O(1) lookups
-//! - A `HashMap<(VolumeId, FileId), Vec<usize>>` tracks parent-child relationships
+//! - Records are stored as columns, one per field (see `records.rs`), that
+//!   search loops scan without building a `FileRecord`; they are shared
+//!   with [`IndexSnapshot`]s and only copied when they change while one is
+//!   held
+//! - A `HashMap<(volume, FileId), u32>` maps IDs to positions for O(1) lookups
+//! - Children are found from a view of the records sorted by parent
 //!
 //! This design prioritizes simplicity and search performance over update efficiency,
 //! which is appropriate since searches vastly outnumber updates.
 
-use crate::backend::{ChangeEvent, ChangeKind, JournalState, VolumeInfo};
-use crate::search::{SearchQuery, SearchResult};
-use crate::types::{FileId, FileRecord, IndexStats, VolumeId};
+use crate::backend::{BackendCapabilities, ChangeEvent, ChangeKind, JournalState, VolumeInfo};
+use crate::churn::{ChurnDetector, ChurnFolder};
+use crate::deleted::RecentlyDeleted;
+use crate::error::{GlintError, Result};
+use crate::exclude::Exclusions;
+use crate::extensions::{self, ExtId, NO_EXTENSION};
+use crate::par::*;
+use crate::records::{normalize_path, Records, Row};
+use crate::search::{ResultTotals, SearchPage, SearchQuery, SearchResult};
+use crate::types::{to_lower, FileId, FileRecord, IndexStats, VolumeId};
+use chrono::{DateTime, Utc};
+use crossbeam_channel::{unbounded, Receiver, Sender};
 use dashmap::DashMap;
-use parking_lot::RwLock;
-use rayon::prelude::*;
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicU64, Ordering};
+use parking_lot::{MappedRwLockWriteGuard, Mutex, RwLock, RwLockWriteGuard};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::{debug, info, instrument, warn};
 
 /// The main in-memory index containing all file records.
@@ -40,23 +53,24 @@ use tracing::{debug, info, instrument, warn};
 /// ```rust
 /// use glint_core::{Index, SearchQuery};
 ///
-/// let mut index = Index::new();
+/// let index = Index::new();
 ///
 /// // Search the index
 /// let query = SearchQuery::substring("readme");
-/// for result in index.search(&query).take(100) {
+/// for result in index.search_limited(&query, 100) {
 ///     println!("{}: {}", result.record.name, result.record.path);
 /// }
 /// ```
 pub struct Index {
-    /// All file records in the index
-    records: RwLock<Vec<FileRecord>>,
-
-    /// Map from (volume_id, file_id) to record index
-    id_to_index: DashMap<(String, u64), usize>,
+    /// All file records in the index.
+    ///
+    /// Records are found by file ID ([`Records::position`]) and folders'
+    /// children ([`Records::children`]) through the records too.
+    /// Update through [`Index::records_mut`], which leaves snapshots alone.
+    records: RwLock<Arc<Records>>,
 
-    /// Map from (volume_id, parent_id) to child record indices
-    children: DashMap<(String, u64), Vec<usize>>,
+    /// Number of live files per extension ID
+    ext_counts: DashMap<ExtId, u64>,
 
     /// Statistics about the index
     stats: RwLock<IndexStats>,
@@ -66,6 +80,172 @@ pub struct Index {
 
     /// Generation counter for detecting concurrent modifications
     generation: AtomicU64,
+
+    /// Whether records keep a lowercase copy of their path
+    cache_lower_paths: AtomicBool,
+
+    /// Whether changes to the records and volumes are refused
+    read_only: AtomicBool,
+
+    /// Whether searches leave out offline volumes rather than show them
+    hide_offline: AtomicBool,
+
+    /// Channels notified on every generation change
+    subscribers: Mutex<Vec<Sender<IndexChange>>>,
+
+    /// Folders and names left out of the index
+    exclusions: RwLock<Exclusions>,
+
+    /// (volume_id, file_id) of excluded folders, so changes inside them
+    /// can be skipped without a path
+    excluded_dirs: DashMap<(String, u64), ()>,
+
+    /// Changes per folder, keyed by (volume_id, folder file_id)
+    churn: Mutex<ChurnDetector<(String, u64)>>,
+
+    /// Changes applied while a rebuild runs, replayed onto the rebuilt
+    /// index before it is swapped in
+    replay: Mutex<Option<Replay>>,
+
+    /// Files deleted recently, shared with snapshots like the records
+    deleted: Mutex<Arc<RecentlyDeleted>>,
+
+    /// What the backend filling the index can do, which decides the
+    /// filters searches can apply
+    capabilities: RwLock<BackendCapabilities>,
+}
+
+/// What happened to an index while it was being rebuilt.
+#[derive(Default)]
+struct Replay {
+    changes: Vec<ChangeEvent>,
+    /// Latest journal position of each volume
+    positions: HashMap<String, JournalState>,
+}
+
+/// Notification that the index changed, see [`Index::subscribe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexChange {
+    /// Generation after the change
+    pub generation: u64,
+
+    /// Volume whose records changed, or None if the whole index did
+    pub volume: Option<VolumeId>,
+
+    /// The file, if the change only added it to the index
+    pub created: Option<FileId>,
+}
+
+/// Which records [`Index::largest`] ranks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LargestKind {
+    /// Files and folders
+    #[default]
+    All,
+    Files,
+    Folders,
+}
+
+impl LargestKind {
+    fn files(self) -> bool {
+        self != LargestKind::Folders
+    }
+
+    fn folders(self) -> bool {
+        self != LargestKind::Files
+    }
+}
+
+/// The index's records frozen at one point, see [`Index::snapshot`].
+#[derive(Clone)]
+pub struct IndexSnapshot {
+    records: Arc<Records>,
+    deleted: Arc<RecentlyDeleted>,
+    /// Volumes disabled when the snapshot was taken
+    disabled: Vec<String>,
+    capabilities: BackendCapabilities,
+    generation: u64,
+}
+
+impl IndexSnapshot {
+    /// The index generation the snapshot was taken at.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Number of records in the snapshot, like [`Index::len`].
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Like [`Index::search`].
+    pub fn search(&self, query: &SearchQuery) -> Vec<SearchResult> {
+        let query = &*query.supported_by(&self.capabilities);
+        if query.searches_deleted() {
+            return search_deleted(&self.deleted, query, &self.disabled, 0, usize::MAX).results;
+        }
+        search_records(&self.records, query, &self.disabled)
+    }
+
+    /// Like [`Index::search_limited`].
+    pub fn search_limited(&self, query: &SearchQuery, limit: usize) -> Vec<SearchResult> {
+        let query = &*query.supported_by(&self.capabilities);
+        if query.searches_deleted() {
+            return search_deleted(&self.deleted, query, &self.disabled, 0, limit).results;
+        }
+        search_records_limited(&self.records, query, &self.disabled, limit)
+    }
+
+    /// Like [`Index::search_counted`].
+    pub fn search_counted(&self, query: &SearchQuery, limit: usize) -> SearchPage {
+        let query = &*query.supported_by(&self.capabilities);
+        if query.searches_deleted() {
+            return search_deleted(&self.deleted, query, &self.disabled, 0, limit);
+        }
+        search_records_counted(&self.records, query, &self.disabled, limit)
+    }
+
+    /// Like [`Index::search_ranked`]; pages of one snapshot never overlap
+    /// or leave a gap, even while the index changes.
+    pub fn search_ranked(&self, query: &SearchQuery, offset: usize, limit: usize) -> SearchPage {
+        let query = &*query.supported_by(&self.capabilities);
+        if query.searches_deleted() {
+            return search_deleted(&self.deleted, query, &self.disabled, offset, limit);
+        }
+        search_records_ranked(&self.records, query, &self.disabled, offset, limit, None)
+    }
+
+    /// Like [`IndexSnapshot::search_ranked`], but gives up once `cancel` is
+    /// set, such as when whoever asked stopped waiting. None if it gave up.
+    pub fn search_ranked_cancellable(
+        &self,
+        query: &SearchQuery,
+        offset: usize,
+        limit: usize,
+        cancel: &AtomicBool,
+    ) -> Option<SearchPage> {
+        let query = &*query.supported_by(&self.capabilities);
+        let page = if query.searches_deleted() {
+            search_deleted(&self.deleted, query, &self.disabled, offset, limit)
+        } else {
+            let records = &self.records;
+            search_records_ranked(records, query, &self.disabled, offset, limit, Some(cancel))
+        };
+        (!cancel.load(Ordering::Acquire)).then_some(page)
+    }
+}
+
+impl std::fmt::Debug for IndexSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IndexSnapshot")
+            .field("records", &self.records.len())
+            .field("generation", &self.generation)
+            .finish()
+    }
 }
 
 /// State tracking for an indexed volume
@@ -82,8 +262,105 @@ pub struct VolumeIndexState {
 
     /// Whether this volume needs a rescan
     pub needs_rescan: bool,
+
+    /// Whether this volume's records are included in searches
+    pub enabled: bool,
+
+    /// When the volume was last scanned in full (None if unknown)
+    pub last_full_scan: Option<DateTime<Utc>>,
+
+    /// When a change from the volume's journal was last applied
+    pub last_event_applied: Option<DateTime<Utc>>,
+
+    /// Whether the volume's device is there and its records current
+    pub availability: VolumeAvailability,
+}
+
+/// Whether an indexed volume's device can be read, as reported by its
+/// watcher and by listing the backend's volumes.
+///
+/// A volume that goes away keeps its records; searches show them as
+/// offline, or leave them out (see [`Index::set_hide_offline`]). When it
+/// comes back it is stale until the changes made meanwhile are caught up
+/// on, and then online again.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VolumeAvailability {
+    /// Mounted, with its changes followed
+    #[default]
+    Online,
+    /// Not mounted; its records are what it held when it went away
+    Offline,
+    /// Mounted again, but changes made while it was away aren't applied yet
+    Stale,
+}
+
+impl std::fmt::Display for VolumeAvailability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            VolumeAvailability::Online => "online",
+            VolumeAvailability::Offline => "offline",
+            VolumeAvailability::Stale => "stale",
+        })
+    }
+}
+
+impl VolumeIndexState {
+    /// Whether searches skip the volume's records.
+    fn is_left_out(&self, hide_offline: bool) -> bool {
+        !self.enabled || (hide_offline && self.availability == VolumeAvailability::Offline)
+    }
+
+    /// When the volume's records were last brought up to date, by a scan or
+    /// a change.
+    pub fn last_updated(&self) -> Option<DateTime<Utc>> {
+        self.last_full_scan.max(self.last_event_applied)
+    }
+
+    /// Whether the volume has gone longer than `max_age` without an update.
+    ///
+    /// Volumes with no recorded update time are not reported as stale.
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        let Some(updated) = self.last_updated() else {
+            return false;
+        };
+        (Utc::now() - updated)
+            .to_std()
+            .is_ok_and(|age| age > max_age)
+    }
+}
+
+/// What [`Index::compact`] dropped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompactReport {
+    /// Records of deleted files
+    pub tombstones: usize,
+
+    /// Records of the dropped volumes
+    pub volume_records: usize,
+
+    /// Display names of the dropped volumes
+    pub volumes: Vec<String>,
+}
+
+impl CompactReport {
+    /// Whether nothing was dropped.
+    pub fn is_empty(&self) -> bool {
+        self.tombstones == 0 && self.volume_records == 0 && self.volumes.is_empty()
+    }
+}
+
+/// Whether `record` is what a deletion left behind. Volume roots have no
+/// name either, but keep their path.
+fn is_tombstone(record: &FileRecord) -> bool {
+    record.name.is_empty() && record.path.is_empty()
 }
 
+/// Records per chunk when counting matches in parallel.
+const COUNT_CHUNK: usize = 64 * 1024;
+
+/// Records per chunk when collecting every match in parallel.
+const SEARCH_CHUNK: usize = 8 * 1024;
+
 impl Default for Index {
     fn default() -> Self {
         Self::new()
@@ -94,27 +371,64 @@ impl Index {
     /// Create a new empty index.
     pub fn new() -> Self {
         Index {
-            records: RwLock::new(Vec::new()),
-            id_to_index: DashMap::new(),
-            children: DashMap::new(),
+            records: RwLock::new(Arc::new(Records::default())),
+            ext_counts: DashMap::new(),
             stats: RwLock::new(IndexStats::new()),
             volumes: RwLock::new(HashMap::new()),
             generation: AtomicU64::new(0),
+            cache_lower_paths: AtomicBool::new(true),
+            read_only: AtomicBool::new(false),
+            hide_offline: AtomicBool::new(false),
+            subscribers: Mutex::new(Vec::new()),
+            exclusions: RwLock::new(Exclusions::default()),
+            excluded_dirs: DashMap::new(),
+            churn: Mutex::new(ChurnDetector::default()),
+            replay: Mutex::new(None),
+            deleted: Mutex::new(Arc::new(RecentlyDeleted::new())),
+            capabilities: RwLock::new(BackendCapabilities::all()),
         }
     }
 
     /// Create an index with pre-allocated capacity.
     pub fn with_capacity(capacity: usize) -> Self {
         Index {
-            records: RwLock::new(Vec::with_capacity(capacity)),
-            id_to_index: DashMap::with_capacity(capacity),
-            children: DashMap::with_capacity(capacity / 10), // Fewer parents than files
+            records: RwLock::new(Arc::new(Records::with_capacity(capacity))),
+            ext_counts: DashMap::new(),
             stats: RwLock::new(IndexStats::new()),
             volumes: RwLock::new(HashMap::new()),
             generation: AtomicU64::new(0),
+            cache_lower_paths: AtomicBool::new(true),
+            read_only: AtomicBool::new(false),
+            hide_offline: AtomicBool::new(false),
+            subscribers: Mutex::new(Vec::new()),
+            exclusions: RwLock::new(Exclusions::default()),
+            excluded_dirs: DashMap::new(),
+            churn: Mutex::new(ChurnDetector::default()),
+            replay: Mutex::new(None),
+            deleted: Mutex::new(Arc::new(RecentlyDeleted::new())),
+            capabilities: RwLock::new(BackendCapabilities::all()),
         }
     }
 
+    /// Leave files matching `exclusions` out of scans and changes from now
+    /// on. Records already in the index are kept until the next scan.
+    pub fn set_exclusions(&self, exclusions: Exclusions) {
+        *self.exclusions.write() = exclusions;
+    }
+
+    /// Tell the index what the backend filling it can do. Searches then
+    /// skip filters on fields its records leave empty (see
+    /// [`SearchQuery::supported_by`]). An index assumes everything until
+    /// told otherwise.
+    pub fn set_capabilities(&self, capabilities: BackendCapabilities) {
+        *self.capabilities.write() = capabilities;
+    }
+
+    /// What the backend filling the index can do.
+    pub fn capabilities(&self) -> BackendCapabilities {
+        *self.capabilities.read()
+    }
+
     /// Get the number of records in the index.
     pub fn len(&self) -> usize {
         self.records.read().len()
@@ -135,6 +449,53 @@ impl Index {
         self.generation.load(Ordering::Acquire)
     }
 
+    /// Receive an [`IndexChange`] each time the generation changes.
+    ///
+    /// Events are sent after the change is visible to searches, one per
+    /// change, so drain the receiver regularly. Dropping it unsubscribes.
+    pub fn subscribe(&self) -> Receiver<IndexChange> {
+        let (tx, rx) = unbounded();
+        self.subscribers.lock().push(tx);
+        rx
+    }
+
+    /// Freeze the records as they are now.
+    ///
+    /// The snapshot shares the records with the index, so taking one is
+    /// cheap; the first change to the index while it is held copies them.
+    /// Searches of the snapshot keep giving the same results however the
+    /// index changes, so a batch of queries, or the pages of one query, see
+    /// one consistent state. Volumes disabled now stay left out.
+    pub fn snapshot(&self) -> IndexSnapshot {
+        let records = self.records.read();
+        IndexSnapshot {
+            records: Arc::clone(&records),
+            deleted: Arc::clone(&self.deleted.lock()),
+            disabled: self.disabled_volume_ids(),
+            capabilities: self.capabilities(),
+            generation: self.generation(),
+        }
+    }
+
+    /// Lock the records for writing, first copying them if a snapshot
+    /// still shares them.
+    fn records_mut(&self) -> MappedRwLockWriteGuard<'_, Records> {
+        RwLockWriteGuard::map(self.records.write(), Arc::make_mut)
+    }
+
+    /// Advance the generation and notify subscribers.
+    fn bump_generation(&self, volume: Option<&VolumeId>, created: Option<FileId>) {
+        let generation = self.generation.fetch_add(1, Ordering::Release) + 1;
+        let change = IndexChange {
+            generation,
+            volume: volume.cloned(),
+            created,
+        };
+        self.subscribers
+            .lock()
+            .retain(|tx| tx.send(change.clone()).is_ok());
+    }
+
     /// Add records from a volume scan.
     ///
     /// This method is used during initial indexing or rescans. It:
@@ -144,6 +505,9 @@ impl Index {
     /// - Updates statistics
     #[instrument(skip(self, records, volume))]
     pub fn add_volume_records(&self, volume: &VolumeInfo, records: Vec<FileRecord>) {
+        if self.refuses("add volume records") {
+            return;
+        }
         let volume_id = volume.id.as_str().to_string();
         let record_count = records.len();
 
@@ -153,51 +517,20 @@ impl Index {
             "Adding records from volume scan"
         );
 
-        // Remove existing records for this volume
-        self.remove_volume(&volume.id);
-
-        // Add new records
-        let mut all_records = self.records.write();
-        let base_index = all_records.len();
-
-        // Track stats
-        let mut files = 0u64;
-        let mut dirs = 0u64;
-        let mut total_size = 0u64;
-
-        for (i, mut record) in records.into_iter().enumerate() {
-            let idx = base_index + i;
-
-            // Ensure cache is initialized
-            record.init_cache();
-
-            // Update ID mapping
-            let key = (record.volume_id.as_str().to_string(), record.id.as_u64());
-            self.id_to_index.insert(key, idx);
-
-            // Update parent-child mapping
-            if let Some(parent_id) = record.parent_id {
-                let parent_key = (record.volume_id.as_str().to_string(), parent_id.as_u64());
-                self.children
-                    .entry(parent_key)
-                    .or_insert_with(Vec::new)
-                    .push(idx);
-            }
-
-            // Update stats
-            if record.is_dir {
-                dirs += 1;
-            } else {
-                files += 1;
-                if let Some(size) = record.size {
-                    total_size += size;
-                }
-            }
-
-            all_records.push(record);
-        }
-
+        // Keep the volume disabled if it was
+        let enabled = self
+            .volumes
+            .read()
+            .get(&volume_id)
+            .map_or(true, |v| v.enabled);
+
+        // Replace existing records for this volume under one lock, so
+        // searches and change events never see the volume half-replaced
+        let mut all_records = self.records_mut();
+        self.remove_volume_records(&mut all_records, &volume_id);
+        let (files, dirs, total_size) = self.insert_records(&mut all_records, records);
         drop(all_records);
+        let record_count = files + dirs;
 
         // Update volume state
         {
@@ -207,8 +540,12 @@ impl Index {
                 VolumeIndexState {
                     info: volume.clone(),
                     journal_state: volume.journal_state.clone(),
-                    record_count: record_count as u64,
+                    record_count,
                     needs_rescan: false,
+                    enabled,
+                    last_full_scan: Some(Utc::now()),
+                    last_event_applied: None,
+                    availability: VolumeAvailability::Online,
                 },
             );
         }
@@ -223,8 +560,7 @@ impl Index {
             stats.last_updated = Some(chrono::Utc::now());
         }
 
-        // Increment generation
-        self.generation.fetch_add(1, Ordering::Release);
+        self.bump_generation(Some(&volume.id), None);
 
         info!(
             volume = %volume_id,
@@ -234,68 +570,110 @@ impl Index {
         );
     }
 
-    /// Remove all records for a volume.
-    #[instrument(skip(self))]
-    pub fn remove_volume(&self, volume_id: &VolumeId) {
-        let vid = volume_id.as_str().to_string();
+    /// Add another batch of records from a volume scan in progress.
+    ///
+    /// Unlike [`Index::add_volume_records`], the volume's existing records
+    /// are kept, so a scan can be added as it goes: start it with
+    /// `add_volume_records` and append the batches from
+    /// [`FileSystemBackend::scan_batches`](crate::backend::FileSystemBackend::scan_batches).
+    /// Searches see each batch as soon as it is added.
+    pub fn append_volume_records(&self, volume: &VolumeInfo, records: Vec<FileRecord>) {
+        if self.refuses("add volume records") {
+            return;
+        }
+        let volume_id = volume.id.as_str().to_string();
+
+        let mut all_records = self.records_mut();
+        let (files, dirs, total_size) = self.insert_records(&mut all_records, records);
+        drop(all_records);
+        let record_count = files + dirs;
 
-        // Find indices to remove
-        let mut to_remove = Vec::new();
         {
-            let records = self.records.read();
-            for (i, record) in records.iter().enumerate() {
-                if record.volume_id.as_str() == vid {
-                    to_remove.push(i);
-                }
-            }
+            let mut volumes = self.volumes.write();
+            let state = volumes
+                .entry(volume_id)
+                .or_insert_with(|| VolumeIndexState {
+                    info: volume.clone(),
+                    journal_state: volume.journal_state.clone(),
+                    record_count: 0,
+                    needs_rescan: false,
+                    enabled: true,
+                    last_full_scan: Some(Utc::now()),
+                    last_event_applied: None,
+                    availability: VolumeAvailability::Online,
+                });
+            state.record_count += record_count;
         }
 
-        if to_remove.is_empty() {
-            return;
+        {
+            let mut stats = self.stats.write();
+            stats.total_files += files;
+            stats.total_dirs += dirs;
+            stats.total_size += total_size;
+            stats.volume_count = self.volumes.read().len() as u32;
+            stats.last_updated = Some(chrono::Utc::now());
         }
 
-        debug!(volume = %vid, count = to_remove.len(), "Removing volume records");
+        self.bump_generation(Some(&volume.id), None);
+    }
 
-        // This is expensive but correct - we rebuild the index
-        // A more sophisticated approach would mark records as deleted
-        // and compact periodically
-        let mut all_records = self.records.write();
+    /// Append scanned records, leaving out excluded ones and updating the
+    /// lookup maps and extension counts. Returns the number of files and
+    /// folders added and their total size.
+    fn insert_records(
+        &self,
+        all_records: &mut Records,
+        records: Vec<FileRecord>,
+    ) -> (u64, u64, u64) {
+        let exclusions = self.exclusions.read();
+
+        // Track stats
+        let mut files = 0u64;
+        let mut dirs = 0u64;
+        let mut total_size = 0u64;
+        let mut ext_counts: HashMap<ExtId, u64> = HashMap::new();
+
+        for mut record in records {
+            // Ensure cache is initialized
+            self.init_record_cache(&mut record);
 
-        // Remove from auxiliary indices first
-        for &idx in &to_remove {
-            let record = &all_records[idx];
-            let key = (record.volume_id.as_str().to_string(), record.id.as_u64());
-            self.id_to_index.remove(&key);
+            if !exclusions.is_empty() && self.exclude_record(&exclusions, &record) {
+                continue;
+            }
 
-            if let Some(parent_id) = record.parent_id {
-                let parent_key = (record.volume_id.as_str().to_string(), parent_id.as_u64());
-                if let Some(mut children) = self.children.get_mut(&parent_key) {
-                    children.retain(|&i| i != idx);
+            // Update stats
+            if record.is_dir {
+                dirs += 1;
+            } else {
+                files += 1;
+                *ext_counts.entry(record.ext_id).or_insert(0) += 1;
+                if let Some(size) = record.size {
+                    total_size += size;
                 }
             }
-        }
 
-        // Remove records (in reverse order to preserve indices)
-        for &idx in to_remove.iter().rev() {
-            all_records.swap_remove(idx);
+            all_records.push(record);
         }
+        all_records.link_parents();
 
-        // Rebuild ID-to-index mapping (indices changed)
-        self.id_to_index.clear();
-        self.children.clear();
-        for (i, record) in all_records.iter().enumerate() {
-            let key = (record.volume_id.as_str().to_string(), record.id.as_u64());
-            self.id_to_index.insert(key, i);
+        for (id, count) in ext_counts {
+            *self.ext_counts.entry(id).or_insert(0) += count;
+        }
+        (files, dirs, total_size)
+    }
 
-            if let Some(parent_id) = record.parent_id {
-                let parent_key = (record.volume_id.as_str().to_string(), parent_id.as_u64());
-                self.children
-                    .entry(parent_key)
-                    .or_insert_with(Vec::new)
-                    .push(i);
-            }
+    /// Remove all records for a volume.
+    #[instrument(skip(self))]
+    pub fn remove_volume(&self, volume_id: &VolumeId) {
+        if self.refuses("remove a volume") {
+            return;
         }
+        let vid = volume_id.as_str().to_string();
 
+        let mut all_records = self.records_mut();
+        if !self.remove_volume_records(&mut all_records, &vid) {
+            return;
+        }
         drop(all_records);
 
         // Remove volume state
@@ -310,7 +688,137 @@ impl Index {
             // A production implementation would track these per-volume
         }
 
-        self.generation.fetch_add(1, Ordering::Release);
+        self.bump_generation(Some(volume_id), None);
+    }
+
+    /// Drop the records of deleted files, which stay behind blanked so
+    /// record positions don't move, and all records of `volumes`, such as
+    /// drives that were never reconnected.
+    ///
+    /// Positions change, so this counts as a change to the whole index.
+    #[instrument(skip(self))]
+    pub fn compact(&self, volumes: &[VolumeId]) -> CompactReport {
+        let mut report = CompactReport::default();
+        if self.refuses("compact the index") {
+            return report;
+        }
+        let dropped: HashSet<&str> = volumes.iter().map(|v| v.as_str()).collect();
+
+        let mut all_records = self.records_mut();
+        report.tombstones = (0..all_records.len())
+            .filter(|&idx| all_records.is_blank(idx))
+            .count();
+        let removed = self.retain_records(&mut all_records, |record| {
+            !is_tombstone(record) && !dropped.contains(record.volume_id.as_str())
+        });
+        report.volume_records = removed - report.tombstones;
+        self.excluded_dirs
+            .retain(|(volume, _), _| !dropped.contains(volume.as_str()));
+        drop(all_records);
+
+        {
+            let mut states = self.volumes.write();
+            for volume in volumes {
+                if let Some(state) = states.remove(volume.as_str()) {
+                    report.volumes.push(state.info.display_name().to_string());
+                }
+            }
+            let mut stats = self.stats.write();
+            stats.volume_count = states.len() as u32;
+            stats.last_updated = Some(Utc::now());
+        }
+
+        if !report.is_empty() {
+            info!(
+                tombstones = report.tombstones,
+                volumes = ?report.volumes,
+                "Compacted the index"
+            );
+            self.bump_generation(None, None);
+        }
+        report
+    }
+
+    /// Drop the records `exclusions` leave out, as if the index had been
+    /// built with them, and return how many went. Unlike deletions they
+    /// don't show among the recently deleted files.
+    ///
+    /// Positions change, so this counts as a change to the whole index.
+    #[instrument(skip(self, exclusions))]
+    pub fn remove_excluded(&self, exclusions: &Exclusions) -> usize {
+        if exclusions.is_empty() || self.refuses("remove excluded records") {
+            return 0;
+        }
+        let mut all_records = self.records_mut();
+        let removed = self.retain_records(&mut all_records, |record| {
+            is_tombstone(record) || !self.exclude_record(exclusions, record)
+        });
+        drop(all_records);
+
+        if removed > 0 {
+            info!(removed, "Removed excluded records");
+            self.stats.write().last_updated = Some(Utc::now());
+            self.bump_generation(None, None);
+        }
+        removed
+    }
+
+    /// Number of records left behind by deleted files, which
+    /// [`compact`](Self::compact) drops.
+    pub fn tombstone_count(&self) -> usize {
+        let records = self.records.read();
+        (0..records.len())
+            .filter(|&idx| records.is_blank(idx))
+            .count()
+    }
+
+    /// Volumes missing from `present`, a fresh `list_volumes()`, that have
+    /// gone longer than `absent_for` without an update.
+    pub fn absent_volumes(
+        &self,
+        present: &[VolumeInfo],
+        absent_for: Duration,
+    ) -> Vec<VolumeIndexState> {
+        self.volumes
+            .read()
+            .values()
+            .filter(|state| {
+                !present.iter().any(|v| state.info.is_same_device(v)) && state.is_stale(absent_for)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Drop a volume's records and rebuild the auxiliary indices.
+    ///
+    /// Takes the locked records so callers can do more under the same lock.
+    /// Returns false if the volume had no records.
+    fn remove_volume_records(&self, all_records: &mut Records, vid: &str) -> bool {
+        let removed = self.retain_records(all_records, |record| record.volume_id.as_str() != vid);
+        if removed == 0 {
+            return false;
+        }
+        debug!(volume = %vid, count = removed, "Removing volume records");
+        self.excluded_dirs.retain(|(volume, _), _| volume != vid);
+        true
+    }
+
+    /// Keep only the records `keep` accepts and rebuild the auxiliary
+    /// indices, returning how many were dropped.
+    fn retain_records(
+        &self,
+        all_records: &mut Records,
+        keep: impl Fn(&FileRecord) -> bool,
+    ) -> usize {
+        let removed = all_records.retain(keep);
+        if removed == 0 {
+            return 0;
+        }
+
+        // This is expensive but correct - we rebuild the counts
+        self.ext_counts.clear();
+        all_records.visit(|_, record| self.count_extension(record, true));
+        removed
     }
 
     /// Apply a change event to the index.
@@ -319,6 +827,15 @@ impl Index {
     /// are detected. It updates the index incrementally.
     #[instrument(skip(self))]
     pub fn apply_change(&self, event: ChangeEvent) {
+        if self.refuses("apply a change") {
+            return;
+        }
+        // Held until the change is in, so a rebuild being swapped in can't
+        // miss it
+        let mut replay = self.replay.lock();
+        if let Some(replay) = replay.as_mut() {
+            replay.changes.push(event.clone());
+        }
         debug!(
             kind = %event.kind,
             file_id = %event.file_id,
@@ -326,112 +843,327 @@ impl Index {
             "Applying change event"
         );
 
+        let Some(event) = self.scope_change(event) else {
+            return;
+        };
+        let volume_id = event.volume_id.clone();
+        let created = (event.kind == ChangeKind::Created).then_some(event.file_id);
+        if let Some(parent_id) = event.new_parent_id.or(event.parent_id) {
+            let folder = (volume_id.as_str().to_string(), parent_id.as_u64());
+            self.churn.lock().record(folder, Instant::now());
+        }
+
         match event.kind {
             ChangeKind::Created => self.handle_create(event),
             ChangeKind::Deleted => self.handle_delete(event),
             ChangeKind::Renamed => self.handle_rename(event),
-            ChangeKind::Modified | ChangeKind::AttributeChanged | ChangeKind::SecurityChanged => {
+            ChangeKind::AttributeChanged => self.handle_attributes(event),
+            ChangeKind::Modified | ChangeKind::SecurityChanged => {
                 // For now, we don't track modification times in real-time
                 // A future enhancement could update the modified timestamp
             }
         }
 
-        self.generation.fetch_add(1, Ordering::Release);
+        if let Some(state) = self.volumes.write().get_mut(volume_id.as_str()) {
+            state.last_event_applied = Some(Utc::now());
+        }
+        self.bump_generation(Some(&volume_id), created);
+    }
+
+    /// Limit events on folder volumes to the folder.
+    ///
+    /// The watcher reports changes for the whole device. An event belongs to
+    /// the folder if its file or its (new) parent is already indexed there;
+    /// moves across the folder boundary become creates or deletes.
+    fn scope_change(&self, mut event: ChangeEvent) -> Option<ChangeEvent> {
+        let scoped = self
+            .volumes
+            .read()
+            .get(event.volume_id.as_str())
+            .is_some_and(|v| v.info.root.is_some());
+        if !scoped {
+            return Some(event);
+        }
+
+        let records = self.records.read();
+        let known = |id: FileId| records.position(&event.volume_id, id).is_some();
+        let tracked = known(event.file_id);
+        let parent = event.new_parent_id.or(event.parent_id);
+        let inside = parent.is_some_and(known);
+        drop(records);
+
+        match (event.kind, tracked, inside) {
+            (ChangeKind::Created, _, true) => Some(event),
+            (ChangeKind::Created, _, false) => None,
+            // Moved out of the folder
+            (ChangeKind::Renamed, true, false) => {
+                event.kind = ChangeKind::Deleted;
+                Some(event)
+            }
+            // Moved into the folder
+            (ChangeKind::Renamed, false, true) => {
+                event.kind = ChangeKind::Created;
+                event.parent_id = parent;
+                if let Some(new_name) = event.new_name.take() {
+                    event.name = new_name;
+                }
+                Some(event)
+            }
+            (_, tracked, _) => tracked.then_some(event),
+        }
     }
 
     fn handle_create(&self, event: ChangeEvent) {
         let volume_id = event.volume_id.clone();
 
-        // Build the path
+        // Inside an excluded folder; its subfolders are excluded too
+        if self.in_excluded_dir(&volume_id, event.parent_id) {
+            if event.is_dir {
+                let key = (volume_id.as_str().to_string(), event.file_id.as_u64());
+                self.excluded_dirs.insert(key, ());
+            }
+            return;
+        }
+
+        // Build the path
         let path = self.build_path(&volume_id, event.parent_id, &event.name);
 
-        let record = FileRecord::new(
+        let mut record = FileRecord::new(
             event.file_id,
             event.parent_id,
             volume_id,
             event.name,
             path,
             event.is_dir,
-        );
-
-        let mut records = self.records.write();
-        let idx = records.len();
-
-        let key = (record.volume_id.as_str().to_string(), record.id.as_u64());
-        self.id_to_index.insert(key, idx);
-
-        if let Some(parent_id) = record.parent_id {
-            let parent_key = (record.volume_id.as_str().to_string(), parent_id.as_u64());
-            self.children
-                .entry(parent_key)
-                .or_insert_with(Vec::new)
-                .push(idx);
+        )
+        .with_cloud(event.cloud);
+        self.init_record_cache(&mut record);
+        if self.exclude_record(&self.exclusions.read(), &record) {
+            return;
         }
 
+        let mut records = self.records_mut();
+        self.count_extension(&record, true);
+        Arc::make_mut(&mut self.deleted.lock()).forget(&record.volume_id, record.id);
         records.push(record);
     }
 
     fn handle_delete(&self, event: ChangeEvent) {
         let key = (event.volume_id.as_str().to_string(), event.file_id.as_u64());
+        self.excluded_dirs.remove(&key);
+
+        let mut records = self.records_mut();
+        if let Some(idx) = records.position(&event.volume_id, event.file_id) {
+            // Mark record as deleted by clearing the name, which also drops
+            // it from the ID lookup (we don't actually remove to avoid
+            // reindexing)
+            let record = records.cloned(idx);
+            self.count_extension(&record, false);
+            Arc::make_mut(&mut self.deleted.lock()).push(record, Utc::now());
+            records.update(idx, |record| {
+                record.name.clear();
+                record.ext_id = NO_EXTENSION;
+                record.path.clear();
+                record.clear_path_cache();
+            });
+        }
+    }
 
-        if let Some((_, idx)) = self.id_to_index.remove(&key) {
-            // Mark record as deleted by clearing the name
-            // (We don't actually remove to avoid reindexing)
-            let mut records = self.records.write();
-            if idx < records.len() {
-                records[idx].name.clear();
-                records[idx].name_lower.clear();
-                records[idx].path.clear();
-            }
+    /// Track files becoming online-only or being downloaded.
+    fn handle_attributes(&self, event: ChangeEvent) {
+        let mut records = self.records_mut();
+        if let Some(idx) = records.position(&event.volume_id, event.file_id) {
+            records.update(idx, |record| record.cloud = event.cloud);
         }
     }
 
     fn handle_rename(&self, event: ChangeEvent) {
         let key = (event.volume_id.as_str().to_string(), event.file_id.as_u64());
 
-        if let Some(idx_ref) = self.id_to_index.get(&key) {
-            let idx = *idx_ref;
-            drop(idx_ref);
-
+        let known = self
+            .records
+            .read()
+            .position(&event.volume_id, event.file_id)
+            .is_some();
+        if known {
             let new_name = event.new_name.unwrap_or(event.name);
             let new_parent = event.new_parent_id.or(event.parent_id);
             let new_path = self.build_path(&event.volume_id, new_parent, &new_name);
 
-            let mut records = self.records.write();
-            if idx < records.len() {
-                records[idx].name = new_name.clone();
-                records[idx].name_lower = new_name.to_lowercase();
-                records[idx].path = new_path;
-                records[idx].parent_id = new_parent;
+            // Moved into an excluded folder, or renamed to an excluded name
+            let excluded = self.in_excluded_dir(&event.volume_id, new_parent) || {
+                let exclusions = self.exclusions.read();
+                exclusions.excludes_folder(&to_lower(&new_path))
+                    || exclusions.excludes_name(&new_name)
+            };
+            if excluded {
+                self.handle_delete(ChangeEvent::deleted(
+                    event.volume_id,
+                    event.file_id,
+                    new_parent,
+                    new_name,
+                    event.is_dir,
+                    event.sequence,
+                ));
+                if event.is_dir {
+                    self.excluded_dirs.insert(key, ());
+                }
+                return;
+            }
+
+            // Look the record up again under the lock; a concurrent volume
+            // rescan may have moved or removed it while the path was built
+            let mut records = self.records_mut();
+            let Some(idx) = records.position(&event.volume_id, event.file_id) else {
+                return;
+            };
+            let old_path = records.path(idx).to_string();
+            records.update(idx, |record| {
+                self.count_extension(record, false);
+                record.name = new_name;
+                record.name_lower.clear();
+                record.path = new_path.clone();
+                record.clear_path_cache();
+                record.parent_id = new_parent;
+                self.init_record_cache(record);
+                self.count_extension(record, true);
+            });
+
+            if event.is_dir && old_path != new_path {
+                self.move_descendants(
+                    &mut records,
+                    &event.volume_id,
+                    event.file_id,
+                    &old_path,
+                    &new_path,
+                );
+            }
+        }
+    }
+
+    /// Rewrite the paths of everything inside a renamed or moved folder,
+    /// which would otherwise keep the folder's old path, and be found by it.
+    fn move_descendants(
+        &self,
+        records: &mut Records,
+        volume_id: &VolumeId,
+        folder_id: FileId,
+        old_path: &str,
+        new_path: &str,
+    ) {
+        let mut pending = vec![folder_id];
+        // Every record is visited at most once, unless parents loop
+        let mut budget = records.len();
+        while let Some(parent) = pending.pop() {
+            for idx in records.children(volume_id, parent) {
+                let rest = records
+                    .path(idx)
+                    .strip_prefix(old_path)
+                    .filter(|rest| rest.starts_with('\\'));
+                let Some(rest) = rest else {
+                    continue;
+                };
+                let path = format!("{}{}", new_path, rest);
+                if records.row(idx).is_dir && budget > 0 {
+                    budget -= 1;
+                    pending.push(records.id(idx));
+                }
+                records.update(idx, |record| {
+                    record.path = path;
+                    record.clear_path_cache();
+                    self.init_record_cache(record);
+                });
+            }
+        }
+    }
+
+    /// Check if a record is excluded, remembering excluded folders so that
+    /// changes inside them can be skipped.
+    fn exclude_record(&self, exclusions: &Exclusions, record: &FileRecord) -> bool {
+        if exclusions.excludes_folder(&record.path_lower()) {
+            if record.is_dir {
+                let key = (record.volume_id.as_str().to_string(), record.id.as_u64());
+                self.excluded_dirs.insert(key, ());
             }
+            return true;
+        }
+        exclusions.excludes_name(&record.name)
+    }
+
+    /// Check if a parent folder is excluded.
+    fn in_excluded_dir(&self, volume_id: &VolumeId, parent_id: Option<FileId>) -> bool {
+        parent_id.is_some_and(|parent| {
+            self.excluded_dirs
+                .contains_key(&(volume_id.as_str().to_string(), parent.as_u64()))
+        })
+    }
+
+    /// Folders that changed at least `threshold` times in a minute since
+    /// the index was created, busiest first.
+    ///
+    /// Only folders in the index are reported; excluded ones are quiet as
+    /// far as the index is concerned.
+    pub fn churning_folders(&self, threshold: u32) -> Vec<ChurnFolder> {
+        let busy = self.churn.lock().busy(threshold);
+        let records = self.records.read();
+        busy.into_iter()
+            .filter_map(|((volume, id), per_minute, total)| {
+                let idx = records.position(&VolumeId::new(volume), FileId::new(id))?;
+                records.row(idx).live.then_some(())?;
+                Some(ChurnFolder {
+                    path: records.path(idx).to_string(),
+                    per_minute,
+                    total,
+                })
+            })
+            .collect()
+    }
+
+    /// Count a live file in (or out of) its extension's total.
+    ///
+    /// Called with the records lock held, as records are added or removed.
+    fn count_extension(&self, record: &FileRecord, added: bool) {
+        if record.is_dir || record.name.is_empty() {
+            return;
+        }
+        let mut count = self.ext_counts.entry(record.ext_id).or_insert(0);
+        if added {
+            *count += 1;
+        } else {
+            *count = count.saturating_sub(1);
         }
     }
 
+    /// Number of files per extension, most common first.
+    ///
+    /// The counts are kept as records change, so this is cheap enough to
+    /// call for every search. Files without an extension, or with one too
+    /// long to intern (see [`crate::extensions`]), are left out.
+    pub fn extension_counts(&self) -> Vec<(String, u64)> {
+        let mut counts: Vec<(String, u64)> = self
+            .ext_counts
+            .iter()
+            .filter(|entry| *entry.value() > 0)
+            .filter_map(|entry| Some((extensions::name(*entry.key())?, *entry.value())))
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts
+    }
+
     /// Build a full path from parent ID and filename.
     fn build_path(&self, volume_id: &VolumeId, parent_id: Option<FileId>, name: &str) -> String {
         let mut path_parts = Vec::new();
         path_parts.push(name.to_string());
 
-        let mut current_parent = parent_id;
         let records = self.records.read();
 
         // Walk up the tree
-        while let Some(pid) = current_parent {
-            let key = (volume_id.as_str().to_string(), pid.as_u64());
-            if let Some(idx_ref) = self.id_to_index.get(&key) {
-                let idx = *idx_ref;
-                if idx < records.len() {
-                    let parent_record = &records[idx];
-                    if !parent_record.name.is_empty() {
-                        path_parts.push(parent_record.name.clone());
-                    }
-                    current_parent = parent_record.parent_id;
-                } else {
-                    break;
-                }
-            } else {
-                break;
+        let mut current = parent_id.and_then(|pid| records.position(volume_id, pid));
+        while let Some(idx) = current {
+            if records.row(idx).live {
+                path_parts.push(records.name(idx).to_string());
             }
+            current = records.parent(idx);
         }
 
         drop(records);
@@ -439,8 +1171,16 @@ impl Index {
         // Reverse and join
         path_parts.reverse();
 
-        // Add volume prefix (e.g., "C:\")
-        let volume_prefix = format!("{}:\\", volume_id.as_str());
+        // Add the volume's mount point (e.g., "C:\" or a mount folder such
+        // as "C:\Mount\Data\"). Folder volumes share their device's mount
+        // point; their root's own record supplies the folder names.
+        let volume_prefix = match self.volumes.read().get(volume_id.as_str()) {
+            Some(state) => format!("{}\\", state.info.mount_point.trim_end_matches(['\\', '/'])),
+            None => {
+                let drive = volume_id.as_str().split(':').next().unwrap_or_default();
+                format!("{}:\\", drive)
+            }
+        };
         format!("{}{}", volume_prefix, path_parts.join("\\"))
     }
 
@@ -454,117 +1194,408 @@ impl Index {
     /// Uses parallel iteration via Rayon for multi-core scaling.
     /// For large indices, this can provide significant speedup.
     pub fn search(&self, query: &SearchQuery) -> Vec<SearchResult> {
+        let query = &*query.supported_by(&self.capabilities());
+        if query.searches_deleted() {
+            return self.search_deleted(query, 0, usize::MAX).results;
+        }
+        let disabled = self.disabled_volume_ids();
         let records = self.records.read();
+        search_records(&records, query, &disabled)
+    }
 
-        // Use parallel filtering for large indices
-        if records.len() > 10000 {
-            self.search_parallel(&records, query)
-        } else {
-            self.search_sequential(&records, query)
+    /// Search with a limit on results.
+    ///
+    /// More efficient than `search().take(n)` for large indices.
+    pub fn search_limited(&self, query: &SearchQuery, limit: usize) -> Vec<SearchResult> {
+        let query = &*query.supported_by(&self.capabilities());
+        if query.searches_deleted() {
+            return self.search_deleted(query, 0, limit).results;
         }
+        let disabled = self.disabled_volume_ids();
+        let records = self.records.read();
+        search_records_limited(&records, query, &disabled, limit)
     }
 
-    fn search_sequential(&self, records: &[FileRecord], query: &SearchQuery) -> Vec<SearchResult> {
-        records
-            .iter()
-            .filter(|r| !r.name.is_empty() && query.matches(r))
-            .map(|r| {
-                let score = self.compute_score(r, query);
-                SearchResult::new(r.clone(), score)
-            })
-            .collect()
+    /// Search returning the first `limit` results and the total match count.
+    ///
+    /// Matching runs in parallel over chunks of records and only counts and
+    /// sums sizes; records are cloned just for the first `limit` matches, in
+    /// the same order as [`Index::search_limited`].
+    pub fn search_counted(&self, query: &SearchQuery, limit: usize) -> SearchPage {
+        let query = &*query.supported_by(&self.capabilities());
+        if query.searches_deleted() {
+            return self.search_deleted(query, 0, limit);
+        }
+        let disabled = self.disabled_volume_ids();
+        let records = self.records.read();
+        search_records_counted(&records, query, &disabled, limit)
     }
 
-    fn search_parallel(&self, records: &[FileRecord], query: &SearchQuery) -> Vec<SearchResult> {
-        records
+    /// Search returning the results from `offset` to `offset + limit` in
+    /// rank order, and the total match count.
+    ///
+    /// Results are ordered by [`SearchResult::rank_cmp`]: score, then
+    /// volume, then file ID. Unlike index order this does not change when
+    /// records are added or moved elsewhere in the index, so consecutive
+    /// pages of an unchanged index neither repeat nor skip a result. Each
+    /// chunk of records keeps only its best `offset + limit` matches, so
+    /// memory grows with the page, not with the number of matches.
+    pub fn search_ranked(&self, query: &SearchQuery, offset: usize, limit: usize) -> SearchPage {
+        let query = &*query.supported_by(&self.capabilities());
+        if query.searches_deleted() {
+            return self.search_deleted(query, offset, limit);
+        }
+        let disabled = self.disabled_volume_ids();
+        let records = self.records.read();
+        search_records_ranked(&records, query, &disabled, offset, limit, None)
+    }
+
+    /// Count the matches of `query` on each volume.
+    ///
+    /// Volumes without matches are left out. Like [`Index::search_counted`],
+    /// this runs in parallel and clones nothing.
+    pub fn count_by_volume(&self, query: &SearchQuery) -> HashMap<VolumeId, usize> {
+        let query = &*query.supported_by(&self.capabilities());
+        if query.searches_deleted() {
+            let mut counts = HashMap::new();
+            for result in self.search_deleted(query, 0, usize::MAX).results {
+                *counts.entry(result.record.volume_id).or_default() += 1;
+            }
+            return counts;
+        }
+        let disabled = self.disabled_volume_ids();
+        let records = self.records.read();
+
+        let scan = Scan::new(&records, query, &disabled);
+
+        let chunks: Vec<HashMap<&VolumeId, usize>> = records
+            .chunks(COUNT_CHUNK)
             .par_iter()
-            .filter(|r| !r.name.is_empty() && query.matches(r))
-            .map(|r| {
-                let score = self.compute_score(r, query);
-                SearchResult::new(r.clone(), score)
+            .map(|chunk| {
+                let mut counts: HashMap<&VolumeId, usize> = HashMap::new();
+                for i in chunk.clone() {
+                    if scan.matches(i) {
+                        *counts.entry(records.volume_id(i)).or_default() += 1;
+                    }
+                }
+                counts
             })
+            .collect();
+
+        let mut total: HashMap<&VolumeId, usize> = HashMap::new();
+        for (volume, count) in chunks.into_iter().flatten() {
+            *total.entry(volume).or_default() += count;
+        }
+        total
+            .into_iter()
+            .map(|(volume, count)| (volume.clone(), count))
             .collect()
     }
 
-    /// Search with a limit on results.
+    /// The page of recently deleted files matching `query` from `offset`
+    /// to `offset + limit`, newest first.
+    fn search_deleted(&self, query: &SearchQuery, offset: usize, limit: usize) -> SearchPage {
+        let disabled = self.disabled_volume_ids();
+        let deleted = Arc::clone(&self.deleted.lock());
+        search_deleted(&deleted, query, &disabled, offset, limit)
+    }
+
+    /// The files deleted recently, newest first.
+    pub fn recently_deleted(&self) -> Arc<RecentlyDeleted> {
+        Arc::clone(&self.deleted.lock())
+    }
+
+    /// Replace the recently deleted files, as when loading them with the
+    /// index.
+    pub fn restore_recently_deleted(&self, mut deleted: RecentlyDeleted) {
+        for entry in deleted.entries_mut() {
+            self.init_record_cache(&mut entry.record);
+        }
+        *self.deleted.lock() = Arc::new(deleted);
+    }
+
+    /// Search allowing one typo, for when `query` itself found nothing.
     ///
-    /// More efficient than `search().take(n)` for large indices.
-    pub fn search_limited(&self, query: &SearchQuery, limit: usize) -> Vec<SearchResult> {
-        let records = self.records.read();
-        let mut results = Vec::with_capacity(limit);
+    /// Results are marked as fuzzy. Returns nothing if the query has no
+    /// typo-tolerant version (see [`SearchQuery::fuzzy`]).
+    pub fn search_fuzzy(&self, query: &SearchQuery, limit: usize) -> Vec<SearchResult> {
+        let Some(fuzzy) = query.fuzzy() else {
+            return Vec::new();
+        };
+        let mut results = self.search_limited(&fuzzy, limit);
+        for result in &mut results {
+            result.fuzzy = true;
+        }
+        results
+    }
 
-        for record in records.iter() {
-            if record.name.is_empty() {
-                continue;
-            }
-            if query.matches(record) {
-                let score = self.compute_score(record, query);
-                results.push(SearchResult::new(record.clone(), score));
-                if results.len() >= limit {
-                    break;
+    /// Merge results that are the same file found under several paths.
+    ///
+    /// This happens when a device is indexed more than once, such as a
+    /// folder volume next to its whole volume. Results are the same file if
+    /// they are on the same device (see [`VolumeInfo::device_key`]) and
+    /// agree on file ID, name, size and modification time; the recursive
+    /// fallback scan numbers files itself, so its IDs alone prove nothing.
+    /// Results from different attached indexes are never merged.
+    /// The first result of each file is kept, with `links` counting the
+    /// paths it was found under.
+    pub fn dedup_links(&self, results: Vec<SearchResult>) -> Vec<SearchResult> {
+        let devices: HashMap<String, String> = self
+            .volumes
+            .read()
+            .iter()
+            .map(|(id, state)| (id.clone(), state.info.device_key()))
+            .collect();
+        // Without two volumes on one device there is nothing to merge
+        let distinct: HashSet<&String> = devices.values().collect();
+        if distinct.len() == devices.len() {
+            return results;
+        }
+
+        let mut first: HashMap<_, usize> = HashMap::with_capacity(results.len());
+        let mut merged: Vec<SearchResult> = Vec::with_capacity(results.len());
+        for result in results {
+            let record = &result.record;
+            let device = devices
+                .get(record.volume_id.as_str())
+                .map_or(record.volume_id.as_str(), String::as_str);
+            let key = (
+                result.source.clone(),
+                device.to_string(),
+                record.id.as_u64(),
+                record.name_lower.clone(),
+                record.size,
+                record.modified,
+            );
+            match first.get(&key) {
+                Some(&i) => merged[i].links += 1,
+                None => {
+                    first.insert(key, merged.len());
+                    merged.push(result);
                 }
             }
         }
+        merged
+    }
 
-        results
+    /// Get a record by its ID.
+    pub fn get(&self, volume_id: &VolumeId, file_id: FileId) -> Option<FileRecord> {
+        let records = self.records.read();
+        let idx = records.position(volume_id, file_id)?;
+        Some(records.cloned(idx))
     }
 
-    /// Compute a relevance score for a record.
+    /// Find a record by its full path (case-insensitive, either slash).
     ///
-    /// Higher scores indicate better matches. Factors:
-    /// - Exact name match: highest score
-    /// - Name starts with query: high score
-    /// - Shorter names: higher score (more specific)
-    fn compute_score(&self, record: &FileRecord, _query: &SearchQuery) -> u32 {
-        // Simple scoring based on name length
-        // Shorter names are generally more relevant (more specific)
-        let length_score = 1000u32.saturating_sub(record.name.len() as u32);
+    /// Looks the path up by its hash rather than scanning the records.
+    /// Where volumes overlap, the record of the volume indexed first is
+    /// returned.
+    pub fn find_by_path(&self, path: &str) -> Option<FileRecord> {
+        self.find_path(None, path)
+    }
 
-        // Boost directories slightly (often what users are looking for)
-        let type_boost = if record.is_dir { 10 } else { 0 };
+    /// Find a record by its full path on one volume, like
+    /// [`Index::find_by_path`].
+    pub fn find_by_volume_path(&self, volume_id: &VolumeId, path: &str) -> Option<FileRecord> {
+        self.find_path(Some(volume_id), path)
+    }
 
-        length_score + type_boost
+    fn find_path(&self, volume_id: Option<&VolumeId>, path: &str) -> Option<FileRecord> {
+        let records = self.records.read();
+        let idx = records.find_path(volume_id, &normalize_path(path))?;
+        Some(records.cloned(idx))
     }
 
-    /// Get a record by its ID.
-    pub fn get(&self, volume_id: &VolumeId, file_id: FileId) -> Option<FileRecord> {
-        let key = (volume_id.as_str().to_string(), file_id.as_u64());
-        self.id_to_index.get(&key).and_then(|idx_ref| {
-            let idx = *idx_ref;
-            let records = self.records.read();
-            records.get(idx).cloned()
-        })
+    /// Approximate memory used to look records up by path, in bytes.
+    pub fn path_lookup_bytes(&self) -> usize {
+        self.records.read().path_lookup_bytes()
     }
 
     /// Get all children of a directory.
     pub fn get_children(&self, volume_id: &VolumeId, parent_id: FileId) -> Vec<FileRecord> {
-        let key = (volume_id.as_str().to_string(), parent_id.as_u64());
+        let records = self.records.read();
+        records
+            .children(volume_id, parent_id)
+            .into_iter()
+            .map(|idx| records.cloned(idx))
+            .collect()
+    }
 
-        if let Some(children_ref) = self.children.get(&key) {
-            let children_indices = children_ref.clone();
-            drop(children_ref);
+    /// Count the files and folders inside a directory, at any depth, and
+    /// sum the sizes of the files.
+    ///
+    /// Walks the directory's subtree, so the cost grows with the number of
+    /// entries inside it rather than with the whole index.
+    pub fn folder_totals(&self, volume_id: &VolumeId, folder_id: FileId) -> ResultTotals {
+        let records = self.records.read();
+        let mut totals = ResultTotals::default();
+        let mut pending = vec![folder_id];
+        while let Some(parent) = pending.pop() {
+            for idx in records.children(volume_id, parent) {
+                let row = records.row(idx);
+                totals.add_row(&row);
+                if row.is_dir {
+                    pending.push(records.id(idx));
+                }
+            }
+        }
+        totals
+    }
 
-            let records = self.records.read();
-            children_indices
-                .iter()
-                .filter_map(|&idx| records.get(idx).cloned())
-                .filter(|r| !r.name.is_empty())
-                .collect()
+    /// The largest records matching `query`, biggest first, and the totals
+    /// of all the files or folders that were ranked.
+    ///
+    /// Folders are sized by the files inside them, as by
+    /// [`Index::folder_totals`], and returned with that size on their
+    /// record. Records of equal size are ordered by path.
+    pub fn largest(&self, query: &SearchQuery, kind: LargestKind, limit: usize) -> SearchPage {
+        let query = &*query.supported_by(&self.capabilities());
+        let disabled = self.disabled_volume_ids();
+        let records = self.records.read();
+        let folder_sizes = if kind.folders() {
+            self.all_folder_sizes(&records)
         } else {
-            Vec::new()
+            HashMap::new()
+        };
+        let scan = Scan::new(&records, query, &disabled);
+        let size_of = |i: usize, row: &Row| match row.is_dir {
+            true if kind.folders() => Some(folder_sizes.get(&i).copied().unwrap_or(0)),
+            false if kind.files() => Some(row.size.unwrap_or(0)),
+            _ => None,
+        };
+
+        let chunks: Vec<(ResultTotals, BinaryHeap<(SizeKey, usize)>)> = records
+            .chunks(COUNT_CHUNK)
+            .par_iter()
+            .map(|chunk| {
+                let mut totals = ResultTotals::default();
+                // Max-heap, so the smallest record kept is on top
+                let mut best = BinaryHeap::new();
+                for i in chunk.clone() {
+                    if !scan.matches(i) {
+                        continue;
+                    }
+                    let row = records.row(i);
+                    let Some(size) = size_of(i, &row) else {
+                        continue;
+                    };
+                    totals.add_row(&row);
+                    let entry = ((Reverse(size), records.path(i)), i);
+                    if best.len() < limit {
+                        best.push(entry);
+                    } else if let Some(mut worst) = best.peek_mut() {
+                        if entry < *worst {
+                            *worst = entry;
+                        }
+                    }
+                }
+                (totals, best)
+            })
+            .collect();
+
+        let totals = chunks
+            .iter()
+            .fold(ResultTotals::default(), |sum, (totals, _)| {
+                sum.merge(*totals)
+            });
+        let mut best: Vec<(SizeKey, usize)> = chunks
+            .into_iter()
+            .flat_map(|(_, best)| best.into_vec())
+            .collect();
+        best.sort_unstable();
+        let results = best
+            .into_iter()
+            .take(limit)
+            .map(|((Reverse(size), _), i)| {
+                let mut record = records.cloned(i);
+                record.size = Some(size);
+                SearchResult::new(record, 0)
+            })
+            .collect();
+
+        SearchPage {
+            results,
+            total: totals.files + totals.dirs,
+            totals,
+        }
+    }
+
+    /// The size of every folder, by the position of its record, from one
+    /// pass over the folders' children.
+    fn all_folder_sizes(&self, records: &Records) -> HashMap<usize, u64> {
+        let children = |i: usize| records.children(records.volume_id(i), records.id(i));
+        let mut sizes: HashMap<usize, u64> = HashMap::new();
+        // Folders already expanded, so a parent loop ends
+        let mut seen: HashSet<usize> = HashSet::new();
+        for start in 0..records.len() {
+            if !records.row(start).is_dir || seen.contains(&start) {
+                continue;
+            }
+            // Children are summed once all of their own children are
+            let mut stack = vec![(start, false)];
+            while let Some((i, expanded)) = stack.pop() {
+                let inside = children(i);
+                if expanded {
+                    let size = inside
+                        .iter()
+                        .map(|&c| (c, records.row(c)))
+                        .filter(|(_, row)| row.live)
+                        .map(|(c, row)| match row.is_dir {
+                            true => sizes.get(&c).copied().unwrap_or(0),
+                            false => row.size.unwrap_or(0),
+                        })
+                        .sum();
+                    sizes.insert(i, size);
+                } else if seen.insert(i) {
+                    stack.push((i, true));
+                    for &c in &inside {
+                        if records.row(c).is_dir && !seen.contains(&c) {
+                            stack.push((c, false));
+                        }
+                    }
+                }
+            }
         }
+        sizes
     }
 
     /// Update journal state for a volume.
     pub fn update_journal_state(&self, volume_id: &VolumeId, state: JournalState) {
+        if self.refuses("update a journal position") {
+            return;
+        }
+        if let Some(replay) = self.replay.lock().as_mut() {
+            replay
+                .positions
+                .insert(volume_id.as_str().to_string(), state.clone());
+        }
         let mut volumes = self.volumes.write();
         if let Some(vol_state) = volumes.get_mut(volume_id.as_str()) {
             vol_state.journal_state = Some(state);
         }
     }
 
+    /// Set when a volume was last scanned and last changed, as saved with
+    /// the index; adding the records on load would otherwise make them look
+    /// freshly scanned.
+    pub fn restore_update_times(
+        &self,
+        volume_id: &VolumeId,
+        last_full_scan: Option<DateTime<Utc>>,
+        last_event_applied: Option<DateTime<Utc>>,
+    ) {
+        let mut volumes = self.volumes.write();
+        if let Some(vol_state) = volumes.get_mut(volume_id.as_str()) {
+            vol_state.last_full_scan = last_full_scan;
+            vol_state.last_event_applied = last_event_applied;
+        }
+    }
+
     /// Mark a volume as needing rescan.
     pub fn mark_needs_rescan(&self, volume_id: &VolumeId, reason: &str) {
+        if self.refuses("mark a volume for rescan") {
+            return;
+        }
         warn!(volume = %volume_id, reason = %reason, "Volume marked for rescan");
         let mut volumes = self.volumes.write();
         if let Some(vol_state) = volumes.get_mut(volume_id.as_str()) {
@@ -572,6 +1603,164 @@ impl Index {
         }
     }
 
+    /// Update volume labels and free space from a fresh `list_volumes()`.
+    ///
+    /// Doesn't change the generation, since no records change. Returns the
+    /// number of volumes whose metadata changed.
+    pub fn refresh_volume_info(&self, current: &[VolumeInfo]) -> usize {
+        let mut volumes = self.volumes.write();
+        volumes
+            .values_mut()
+            .filter_map(|state| {
+                let fresh = current.iter().find(|v| state.info.is_same_device(v))?;
+                state.info.refresh_from(fresh).then_some(())
+            })
+            .count()
+    }
+
+    /// Include or leave out a volume's records in searches.
+    ///
+    /// A disabled volume keeps its records, so enabling it again needs no
+    /// rescan. Returns false if the volume is not in the index.
+    pub fn set_volume_enabled(&self, volume_id: &VolumeId, enabled: bool) -> bool {
+        let mut volumes = self.volumes.write();
+        match volumes.get_mut(volume_id.as_str()) {
+            Some(vol_state) => {
+                vol_state.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Check if any volume is left out of searches, disabled or hidden
+    /// while offline.
+    pub fn has_disabled_volumes(&self) -> bool {
+        let hide_offline = self.hides_offline();
+        self.volumes
+            .read()
+            .values()
+            .any(|v| v.is_left_out(hide_offline))
+    }
+
+    /// Volumes left out of searches: the disabled ones, and the offline
+    /// ones if they are hidden.
+    fn disabled_volume_ids(&self) -> Vec<String> {
+        let hide_offline = self.hides_offline();
+        self.volumes
+            .read()
+            .iter()
+            .filter(|(_, v)| v.is_left_out(hide_offline))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Mark a volume offline, as when its device went away.
+    ///
+    /// Returns false if it already was, or is not in the index.
+    pub fn set_volume_offline(&self, volume_id: &VolumeId, reason: &str) -> bool {
+        self.set_availability(volume_id, VolumeAvailability::Offline, reason)
+    }
+
+    /// Mark a volume online, once the changes made while it was away are
+    /// caught up on.
+    ///
+    /// Returns false if it already was, or is not in the index.
+    pub fn set_volume_online(&self, volume_id: &VolumeId) -> bool {
+        self.set_availability(volume_id, VolumeAvailability::Online, "caught up")
+    }
+
+    /// Update which volumes are mounted from a fresh `list_volumes()`.
+    ///
+    /// Volumes missing from `present` go offline, and offline volumes found
+    /// in it again become stale. Returns those that came back, to be caught
+    /// up on.
+    pub fn update_availability(&self, present: &[VolumeInfo]) -> Vec<VolumeIndexState> {
+        let mut back = Vec::new();
+        for mut state in self.volume_states() {
+            let mounted = present.iter().any(|v| state.info.is_same_device(v));
+            if state.availability == VolumeAvailability::Offline {
+                if mounted
+                    && self.set_availability(&state.info.id, VolumeAvailability::Stale, "mounted")
+                {
+                    state.availability = VolumeAvailability::Stale;
+                    back.push(state);
+                }
+            } else if !mounted {
+                self.set_volume_offline(&state.info.id, "no longer listed");
+            }
+        }
+        back
+    }
+
+    /// Move a volume to `availability`, telling subscribers so results can
+    /// be shown as offline (or hidden) right away. Records don't change.
+    fn set_availability(
+        &self,
+        volume_id: &VolumeId,
+        availability: VolumeAvailability,
+        reason: &str,
+    ) -> bool {
+        {
+            let mut volumes = self.volumes.write();
+            let Some(state) = volumes.get_mut(volume_id.as_str()) else {
+                return false;
+            };
+            if state.availability == availability {
+                return false;
+            }
+            info!(
+                volume = %volume_id,
+                from = %state.availability,
+                to = %availability,
+                reason = %reason,
+                "Volume availability changed"
+            );
+            state.availability = availability;
+        }
+        self.bump_generation(Some(volume_id), None);
+        true
+    }
+
+    /// Leave offline volumes out of searches, rather than show their
+    /// records as offline.
+    pub fn set_hide_offline(&self, hide: bool) {
+        if self.hide_offline.swap(hide, Ordering::AcqRel) != hide {
+            self.bump_generation(None, None);
+        }
+    }
+
+    /// Check if searches leave out offline volumes.
+    pub fn hides_offline(&self) -> bool {
+        self.hide_offline.load(Ordering::Acquire)
+    }
+
+    /// Keep or drop the lowercase copy of every record's path.
+    ///
+    /// Cached paths make path searches allocation-free at the cost of
+    /// roughly doubling the memory used by paths. Uncached records are
+    /// lowercased per query instead.
+    pub fn set_cache_lower_paths(&self, enabled: bool) {
+        if self.cache_lower_paths.swap(enabled, Ordering::AcqRel) == enabled {
+            return;
+        }
+        self.records_mut().set_path_cache(enabled);
+    }
+
+    /// Check if records keep a lowercase copy of their path.
+    pub fn caches_lower_paths(&self) -> bool {
+        self.cache_lower_paths.load(Ordering::Acquire)
+    }
+
+    fn init_record_cache(&self, record: &mut FileRecord) {
+        if self.caches_lower_paths() {
+            record.init_cache();
+        } else {
+            record.init_name_cache();
+            record.clear_path_cache();
+        }
+    }
+
     /// Get volumes that need rescanning.
     pub fn volumes_needing_rescan(&self) -> Vec<VolumeInfo> {
         self.volumes
@@ -587,39 +1776,530 @@ impl Index {
         self.volumes.read().values().cloned().collect()
     }
 
+    /// Find an indexed volume by its display name, ignoring case and
+    /// trailing separators; a drive letter alone (`D`) names `D:`.
+    pub fn find_volume(&self, name: &str) -> Option<VolumeIndexState> {
+        let requested = name.trim_end_matches(['\\', '/']);
+        self.volumes
+            .read()
+            .values()
+            .find(|state| {
+                let name = state.info.display_name().trim_end_matches(['\\', '/']);
+                name.eq_ignore_ascii_case(requested)
+                    || name
+                        .strip_suffix(':')
+                        .is_some_and(|letter| letter.eq_ignore_ascii_case(requested))
+            })
+            .cloned()
+    }
+
+    /// Visit every live record under a single read lock.
+    ///
+    /// Cheaper than `all_records()` for aggregations that don't need copies:
+    /// each record is copied into the same `FileRecord` in turn, which `f`
+    /// only borrows.
+    pub fn for_each_record(&self, mut f: impl FnMut(&FileRecord)) {
+        let records = self.records.read();
+        records.visit(|_, record| f(record));
+    }
+
     /// Get a copy of all records (for persistence).
     pub fn all_records(&self) -> Vec<FileRecord> {
-        self.records.read().clone()
+        let records = self.records.read();
+        (0..records.len()).map(|i| records.cloned(i)).collect()
+    }
+
+    /// Refuse or allow changes to the records and volumes.
+    ///
+    /// While read-only, scans, change events, volume removal and
+    /// [`Index::clear`] are ignored with a warning, so the index stays as it
+    /// was loaded. Searches and per-session settings such as disabled
+    /// volumes and exclusions still work.
+    pub fn set_read_only(&self, read_only: bool) {
+        self.read_only.store(read_only, Ordering::Release);
+    }
+
+    /// Check if changes to the index are refused.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::Acquire)
+    }
+
+    /// Fail with [`GlintError::ReadOnly`] if the index is read-only, so a
+    /// caller can refuse `operation` up front rather than having its
+    /// changes ignored.
+    pub fn ensure_writable(&self, operation: &str) -> Result<()> {
+        if self.is_read_only() {
+            return Err(GlintError::read_only(operation));
+        }
+        Ok(())
+    }
+
+    /// Whether `operation` must be skipped because the index is read-only.
+    fn refuses(&self, operation: &str) -> bool {
+        let read_only = self.is_read_only();
+        if read_only {
+            warn!(operation, "Index is read-only, ignoring change");
+        }
+        read_only
     }
 
     /// Clear the entire index.
+    ///
+    /// Ignored while the index is read-only.
     pub fn clear(&self) {
-        let mut records = self.records.write();
+        if self.refuses("clear the index") {
+            return;
+        }
+        let mut records = self.records_mut();
         records.clear();
-        self.id_to_index.clear();
-        self.children.clear();
+        self.ext_counts.clear();
+        self.excluded_dirs.clear();
+        *self.deleted.lock() = Arc::new(RecentlyDeleted::new());
         *self.stats.write() = IndexStats::new();
         self.volumes.write().clear();
-        self.generation.fetch_add(1, Ordering::Release);
-    }
-}
-
-impl std::fmt::Debug for Index {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Index")
-            .field("record_count", &self.len())
-            .field("generation", &self.generation())
-            .finish()
+        self.bump_generation(None, None);
     }
-}
 
-// Thread-safe sharing
-unsafe impl Send for Index {}
-unsafe impl Sync for Index {}
+    /// Rebuild the index from scratch while it keeps serving searches.
+    ///
+    /// `build` makes the new index on the calling thread, typically by
+    /// scanning every volume again. Changes applied to this index meanwhile
+    /// are replayed onto it, and it then takes the place of the current
+    /// records in one step: searches see the old records until the new ones
+    /// are complete, never an empty or partial index. If `build` fails,
+    /// nothing changes.
+    ///
+    /// The new index's exclusions and path cache setting take over too.
+    /// Volumes disabled here stay disabled, and journal positions reached
+    /// during the rebuild are kept along with the changes up to them.
+    /// Ignored while the index is read-only.
+    pub fn rebuild_with<E>(
+        &self,
+        build: impl FnOnce() -> std::result::Result<Index, E>,
+    ) -> std::result::Result<(), E> {
+        if self.refuses("rebuild the index") {
+            return Ok(());
+        }
+        *self.replay.lock() = Some(Replay::default());
+        let rebuilt = match build() {
+            Ok(rebuilt) => rebuilt,
+            Err(e) => {
+                *self.replay.lock() = None;
+                return Err(e);
+            }
+        };
 
-#[cfg(test)]
+        // No change can be applied from here until the swap is done
+        let mut replay = self.replay.lock();
+        let Replay { changes, positions } = replay.take().unwrap_or_default();
+        debug!(
+            changes = changes.len(),
+            "Replaying changes onto the rebuilt index"
+        );
+        for event in changes {
+            rebuilt.apply_change(event);
+        }
+        for (volume_id, state) in positions {
+            rebuilt.update_journal_state(&VolumeId::new(volume_id), state);
+        }
+        self.take_over(rebuilt);
+        drop(replay);
+
+        self.bump_generation(None, None);
+        info!(records = self.len(), "Rebuilt index swapped in");
+        Ok(())
+    }
+
+    /// Replace the records, lookup maps and volumes with those of `rebuilt`.
+    fn take_over(&self, rebuilt: Index) {
+        let Index {
+            records,
+            ext_counts,
+            stats,
+            volumes,
+            cache_lower_paths,
+            exclusions,
+            excluded_dirs,
+            ..
+        } = rebuilt;
+
+        let mut new_volumes = volumes.into_inner();
+        {
+            let old_volumes = self.volumes.read();
+            for (id, state) in new_volumes.iter_mut() {
+                if let Some(old) = old_volumes.get(id) {
+                    state.enabled = old.enabled;
+                    state.availability = old.availability;
+                }
+            }
+        }
+
+        let mut all_records = self.records.write();
+        *all_records = records.into_inner();
+        self.ext_counts.clear();
+        for (key, value) in ext_counts {
+            self.ext_counts.insert(key, value);
+        }
+        self.excluded_dirs.clear();
+        for (key, value) in excluded_dirs {
+            self.excluded_dirs.insert(key, value);
+        }
+        *self.exclusions.write() = exclusions.into_inner();
+        self.cache_lower_paths
+            .store(cache_lower_paths.into_inner(), Ordering::Release);
+        drop(all_records);
+
+        *self.volumes.write() = new_volumes;
+        *self.stats.write() = stats.into_inner();
+    }
+}
+
+/// All records matching `query`, in parallel for large indices.
+fn search_records(
+    records: &Records,
+    query: &SearchQuery,
+    disabled: &[String],
+) -> Vec<SearchResult> {
+    let scan = Scan::new(records, query, disabled);
+    let chunks: Vec<Vec<SearchResult>> = records
+        .chunks(SEARCH_CHUNK)
+        .par_iter()
+        .map(|chunk| {
+            chunk
+                .clone()
+                .filter(|&i| scan.matches(i))
+                .map(|i| to_result(records.cloned(i), query))
+                .collect()
+        })
+        .collect();
+    chunks.into_iter().flatten().collect()
+}
+
+/// The recently deleted files matching `query` from `offset` to
+/// `offset + limit`, newest first, with when each was deleted.
+fn search_deleted(
+    deleted: &RecentlyDeleted,
+    query: &SearchQuery,
+    disabled: &[String],
+    offset: usize,
+    limit: usize,
+) -> SearchPage {
+    let mut results = Vec::new();
+    let mut totals = ResultTotals::default();
+    for entry in deleted.newest_first() {
+        if !is_searchable(&entry.record, disabled) || !query.matches(&entry.record) {
+            continue;
+        }
+        let position = totals.files + totals.dirs;
+        if position >= offset && results.len() < limit {
+            let mut result = to_result(entry.record.clone(), query);
+            result.deleted_at = Some(entry.deleted_at);
+            results.push(result);
+        }
+        totals.add(&entry.record);
+    }
+    SearchPage {
+        results,
+        total: totals.files + totals.dirs,
+        totals,
+    }
+}
+
+/// The first `limit` records matching `query`.
+fn search_records_limited(
+    records: &Records,
+    query: &SearchQuery,
+    disabled: &[String],
+    limit: usize,
+) -> Vec<SearchResult> {
+    let scan = Scan::new(records, query, disabled);
+    let mut results = Vec::with_capacity(limit);
+
+    for i in 0..records.len() {
+        if scan.matches(i) {
+            results.push(to_result(records.cloned(i), query));
+            if results.len() >= limit {
+                break;
+            }
+        }
+    }
+
+    results
+}
+
+/// The first `limit` records matching `query` and the totals of all of them.
+fn search_records_counted(
+    records: &Records,
+    query: &SearchQuery,
+    disabled: &[String],
+    limit: usize,
+) -> SearchPage {
+    let scan = Scan::new(records, query, disabled);
+    let chunks: Vec<(ResultTotals, Vec<usize>)> = records
+        .chunks(COUNT_CHUNK)
+        .par_iter()
+        .map(|chunk| {
+            let mut totals = ResultTotals::default();
+            let mut first = Vec::new();
+            for i in chunk.clone() {
+                if scan.matches(i) {
+                    if first.len() < limit {
+                        first.push(i);
+                    }
+                    totals.add_row(&records.row(i));
+                }
+            }
+            (totals, first)
+        })
+        .collect();
+
+    let totals = chunks
+        .iter()
+        .fold(ResultTotals::default(), |sum, (totals, _)| {
+            sum.merge(*totals)
+        });
+    let results = chunks
+        .into_iter()
+        .flat_map(|(_, first)| first)
+        .take(limit)
+        .map(|i| to_result(records.cloned(i), query))
+        .collect();
+
+    SearchPage {
+        results,
+        total: totals.files + totals.dirs,
+        totals,
+    }
+}
+
+/// Order of [`Index::largest`], smallest first: biggest size, then path.
+type SizeKey<'a> = (Reverse<u64>, &'a str);
+
+/// Rank of a record matching a query, smallest first; sorts like
+/// [`SearchResult::rank_cmp`].
+type RankKey<'a> = (Reverse<u32>, &'a str, u64);
+
+fn rank_key<'a>(records: &'a Records, i: usize, query: &SearchQuery) -> RankKey<'a> {
+    let row = records.row(i);
+    let score = compute_score(
+        records.path(i),
+        records.name(i),
+        row.is_dir,
+        row.name_lower(),
+        query,
+    );
+    (
+        Reverse(score),
+        records.volume_id(i).as_str(),
+        records.id(i).as_u64(),
+    )
+}
+
+/// The matches of `query` from `offset` to `offset + limit` in rank order,
+/// and the totals of all of them. Chunks not yet searched when `cancel` is
+/// set are skipped, leaving the page incomplete.
+fn search_records_ranked(
+    records: &Records,
+    query: &SearchQuery,
+    disabled: &[String],
+    offset: usize,
+    limit: usize,
+    cancel: Option<&AtomicBool>,
+) -> SearchPage {
+    let scan = Scan::new(records, query, disabled);
+    let keep = offset.saturating_add(limit);
+    let chunks: Vec<(ResultTotals, BinaryHeap<(RankKey, usize)>)> = records
+        .chunks(COUNT_CHUNK)
+        .par_iter()
+        .map(|chunk| {
+            let mut totals = ResultTotals::default();
+            // Max-heap, so the worst match kept is on top
+            let mut best = BinaryHeap::new();
+            if cancel.is_some_and(|cancel| cancel.load(Ordering::Acquire)) {
+                return (totals, best);
+            }
+            for i in chunk.clone() {
+                if !scan.matches(i) {
+                    continue;
+                }
+                totals.add_row(&records.row(i));
+                let entry = (rank_key(records, i, query), i);
+                if best.len() < keep {
+                    best.push(entry);
+                } else if let Some(mut worst) = best.peek_mut() {
+                    if entry < *worst {
+                        *worst = entry;
+                    }
+                }
+            }
+            (totals, best)
+        })
+        .collect();
+
+    let totals = chunks
+        .iter()
+        .fold(ResultTotals::default(), |sum, (totals, _)| {
+            sum.merge(*totals)
+        });
+    let mut best: Vec<(RankKey, usize)> = chunks
+        .into_iter()
+        .flat_map(|(_, best)| best.into_vec())
+        .collect();
+    best.sort_unstable();
+    let results = best
+        .into_iter()
+        .skip(offset)
+        .take(limit)
+        .map(|(_, i)| to_result(records.cloned(i), query))
+        .collect();
+
+    SearchPage {
+        results,
+        total: totals.files + totals.dirs,
+        totals,
+    }
+}
+
+/// A result for a record matching `query`, with its lowercase name filled
+/// in ([`Records::cloned`]).
+fn to_result(record: FileRecord, query: &SearchQuery) -> SearchResult {
+    let score = compute_score(
+        &record.path,
+        &record.name,
+        record.is_dir,
+        &record.name_lower,
+        query,
+    );
+    let captures = query.captures(&record);
+    let mut result = SearchResult::new(record, score);
+    result.captures = captures;
+    result
+}
+
+/// Compute a relevance score for a record.
+///
+/// Higher scores indicate better matches. Factors, each outweighing all
+/// the ones after it:
+/// - How the name matches plain text: exact match, then name starts with
+///   the query, then a word in the name starts with it, then the name
+///   contains it, then only the path does
+/// - Shallower paths: higher score
+/// - Shorter names: higher score (more specific)
+/// - Directories: slightly higher
+fn compute_score(
+    path: &str,
+    name: &str,
+    is_dir: bool,
+    name_lower: &str,
+    query: &SearchQuery,
+) -> u32 {
+    let match_score = query
+        .literal()
+        .map_or(0, |text| name_match_tier(name_lower, text) * 1_000_000);
+
+    // Fewer separators means the file is closer to a root
+    let depth = path.matches(['\\', '/']).count() as u32;
+    let depth_score = 100u32.saturating_sub(depth) * 2000;
+
+    // Shorter names are generally more relevant (more specific)
+    let length_score = 1000u32.saturating_sub(name.len() as u32);
+
+    // Boost directories slightly (often what users are looking for)
+    let type_boost = if is_dir { 10 } else { 0 };
+
+    match_score + depth_score + length_score + type_boost
+}
+
+/// How closely a lowercase name matches `text`, from 4 for the exact name
+/// down to 0 when the name doesn't contain it at all.
+fn name_match_tier(name_lower: &str, text: &str) -> u32 {
+    if name_lower == text {
+        4
+    } else if name_lower.starts_with(text) {
+        3
+    } else if name_lower.match_indices(text).any(|(i, _)| {
+        !name_lower[..i]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric)
+    }) {
+        2
+    } else if name_lower.contains(text) {
+        1
+    } else {
+        0
+    }
+}
+
+/// Checks records against a query by position, reading their columns
+/// first so that most records are ruled out without loading them.
+struct Scan<'a> {
+    records: &'a Records,
+    query: &'a SearchQuery,
+    /// Whether each of the records' volumes is disabled, by column slot
+    disabled: Vec<bool>,
+    /// Records whose name contains the query's text, if it has plain text
+    candidates: Option<Vec<u64>>,
+    /// Whether the query needs records' lowercase paths
+    paths: bool,
+}
+
+impl<'a> Scan<'a> {
+    fn new(records: &'a Records, query: &'a SearchQuery, disabled: &[String]) -> Self {
+        Scan {
+            records,
+            query,
+            disabled: records.volume_mask(disabled),
+            candidates: query
+                .name_literal()
+                .map(|text| records.names_containing(text)),
+            paths: query.reads_paths(),
+        }
+    }
+
+    /// Whether the record at `i` is live, on an enabled volume and
+    /// matches the query; agrees with `is_searchable` and
+    /// [`SearchQuery::matches`].
+    #[inline]
+    fn matches(&self, i: usize) -> bool {
+        if let Some(candidates) = &self.candidates {
+            if candidates[i / 64] & (1 << (i % 64)) == 0 {
+                return false;
+            }
+        }
+        let row = self.records.row(i);
+        if !row.live || self.disabled[row.volume] {
+            return false;
+        }
+        let path_lower = self.paths.then(|| self.records.path_lower(i));
+        let settled = self.query.matches_row(&row, path_lower.as_deref());
+        settled.unwrap_or_else(|| {
+            self.records.with_record(i, |record| {
+                self.query.matches_named(record, row.name_lower())
+            })
+        })
+    }
+}
+
+/// Check if a record is live and belongs to an enabled volume.
+fn is_searchable(record: &FileRecord, disabled: &[String]) -> bool {
+    !record.name.is_empty()
+        && (disabled.is_empty() || !disabled.iter().any(|id| id == record.volume_id.as_str()))
+}
+
+impl std::fmt::Debug for Index {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Index")
+            .field("record_count", &self.len())
+            .field("generation", &self.generation())
+            .finish()
+    }
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
+    use crate::search::{parse_query, SearchFilter};
 
     fn make_test_records() -> Vec<FileRecord> {
         vec![
@@ -701,8 +2381,9 @@ mod tests {
         let index = Index::new();
         index.add_volume_records(&make_volume_info(), make_test_records());
 
-        let query = SearchQuery::substring("")
-            .with_filter(crate::search::SearchFilter::Extensions(vec!["rs".to_string()]));
+        let query = SearchQuery::substring("").with_filter(
+            crate::search::SearchFilter::Extensions(vec!["rs".to_string()].into()),
+        );
         let results = index.search(&query);
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].record.name, "main.rs");
@@ -718,6 +2399,88 @@ mod tests {
         assert_eq!(results.len(), 2);
     }
 
+    #[test]
+    fn test_search_counted() {
+        let index = Index::new();
+        index.add_volume_records(&make_volume_info(), make_test_records());
+
+        let query = SearchQuery::substring("");
+        let page = index.search_counted(&query, 2);
+        assert_eq!(page.total, 4);
+        assert_eq!(page.results.len(), 2);
+        assert_eq!(page.totals.files + page.totals.dirs, 4);
+        assert_eq!(page.totals.size, 1024 + 256 + 2048);
+        let limited = index.search_limited(&query, 2);
+        for (counted, limited) in page.results.iter().zip(&limited) {
+            assert_eq!(counted.record.name, limited.record.name);
+        }
+
+        let page = index.search_counted(&SearchQuery::substring("nothing"), 2);
+        assert_eq!(page.total, 0);
+        assert_eq!(page.totals, ResultTotals::default());
+        assert!(page.results.is_empty());
+    }
+
+    #[test]
+    fn test_capabilities_skip_filters() {
+        let index = Index::new();
+        index.add_volume_records(&make_volume_info(), make_test_records());
+        let query = SearchQuery::substring("")
+            .with_filter(SearchFilter::FilesOnly)
+            .with_filter(SearchFilter::MinSize(1000));
+        assert_eq!(index.search_counted(&query, 10).total, 2);
+
+        // A backend without sizes would leave nothing to find
+        index.set_capabilities(BackendCapabilities {
+            provides_sizes: false,
+            ..BackendCapabilities::all()
+        });
+        assert_eq!(index.search_counted(&query, 10).total, 3);
+        assert_eq!(index.snapshot().search(&query).len(), 3);
+    }
+
+    #[test]
+    fn test_count_by_volume() {
+        let index = Index::new();
+        index.add_volume_records(&make_volume_info(), make_test_records());
+        let d = VolumeInfo::new(VolumeId::new("D"), "D:", "NTFS");
+        index.add_volume_records(
+            &d,
+            vec![FileRecord::new(
+                FileId::new(7),
+                None,
+                d.id.clone(),
+                "main.c".to_string(),
+                "D:\\main.c".to_string(),
+                false,
+            )],
+        );
+
+        let counts = index.count_by_volume(&SearchQuery::substring("m"));
+        assert_eq!(counts.get(&VolumeId::new("C")), Some(&3));
+        assert_eq!(counts.get(&d.id), Some(&1));
+        let none = index.count_by_volume(&SearchQuery::substring("nothing"));
+        assert!(none.is_empty());
+
+        index.set_volume_enabled(&d.id, false);
+        let counts = index.count_by_volume(&SearchQuery::substring("m"));
+        assert_eq!(counts.get(&d.id), None);
+    }
+
+    #[test]
+    fn test_search_fuzzy() {
+        let index = Index::new();
+        index.add_volume_records(&make_volume_info(), make_test_records());
+
+        let query = SearchQuery::substring("confg");
+        assert!(index.search_limited(&query, 10).is_empty());
+
+        let results = index.search_fuzzy(&query, 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].record.name, "config.toml");
+        assert!(results[0].fuzzy);
+    }
+
     #[test]
     fn test_apply_create_change() {
         let index = Index::new();
@@ -743,6 +2506,144 @@ mod tests {
         assert_eq!(results.len(), 1);
     }
 
+    #[test]
+    fn test_remove_excluded() {
+        let index = Index::new();
+        index.add_volume_records(&make_volume_info(), make_test_records());
+        assert_eq!(index.len(), 5);
+        let generation = index.generation();
+
+        let config = crate::config::ExcludeConfig {
+            patterns: vec!["*.toml".to_string()],
+            presets: Vec::new(),
+            ..Default::default()
+        };
+        assert_eq!(index.remove_excluded(&Exclusions::new(&config)), 1);
+        assert_eq!(index.len(), 4);
+        assert!(index.generation() > generation);
+        assert!(index.search(&parse_query("config").unwrap()).is_empty());
+        // Not a deletion, so not among the recently deleted files
+        assert!(index.search(&parse_query("deleted:").unwrap()).is_empty());
+        assert_eq!(index.remove_excluded(&Exclusions::new(&config)), 0);
+    }
+
+    #[test]
+    fn test_apply_cloud_attribute_change() {
+        let index = Index::new();
+        index.add_volume_records(&make_volume_info(), make_test_records());
+
+        let mut event = ChangeEvent::created(
+            VolumeId::new("C"),
+            FileId::new(200),
+            Some(FileId::new(100)),
+            "photos.zip".to_string(),
+            false,
+            1000,
+        );
+        event.cloud = true;
+        index.apply_change(event.clone());
+
+        let cloud = parse_query("cloud:").unwrap();
+        assert_eq!(index.search(&cloud).len(), 1);
+
+        // Downloaded: no longer a placeholder
+        event.kind = ChangeKind::AttributeChanged;
+        event.cloud = false;
+        index.apply_change(event);
+        assert!(index.search(&cloud).is_empty());
+    }
+
+    #[test]
+    fn test_exclusions() {
+        let index = Index::new();
+        let config = crate::config::ExcludeConfig {
+            paths: vec!["C:\\Users\\cache".to_string()],
+            patterns: vec!["*.tmp".to_string()],
+            ..Default::default()
+        };
+        index.set_exclusions(Exclusions::new(&config));
+
+        let mut records = make_test_records();
+        records.push(FileRecord::new(
+            FileId::new(110),
+            Some(FileId::new(100)),
+            VolumeId::new("C"),
+            "cache".to_string(),
+            "C:\\Users\\cache".to_string(),
+            true,
+        ));
+        records.push(FileRecord::new(
+            FileId::new(111),
+            Some(FileId::new(110)),
+            VolumeId::new("C"),
+            "blob".to_string(),
+            "C:\\Users\\cache\\blob".to_string(),
+            false,
+        ));
+        index.add_volume_records(&make_volume_info(), records);
+        assert_eq!(index.len(), 5);
+        assert_eq!(index.volume_states()[0].record_count, 5);
+
+        // Changes inside the excluded folder, at any depth, are skipped
+        let create = |id: u64, parent: u64, name: &str, is_dir: bool| {
+            ChangeEvent::created(
+                VolumeId::new("C"),
+                FileId::new(id),
+                Some(FileId::new(parent)),
+                name.to_string(),
+                is_dir,
+                0,
+            )
+        };
+        index.apply_change(create(112, 110, "blob2", false));
+        index.apply_change(create(113, 110, "sub", true));
+        index.apply_change(create(114, 113, "blob3", false));
+        index.apply_change(create(115, 100, "scratch.tmp", false));
+        assert_eq!(index.len(), 5);
+
+        // Moving a file into the excluded folder drops it
+        index.apply_change(ChangeEvent::renamed(
+            VolumeId::new("C"),
+            FileId::new(103),
+            Some(FileId::new(100)),
+            "main.rs".to_string(),
+            "main.rs".to_string(),
+            Some(FileId::new(110)),
+            false,
+            0,
+        ));
+        assert!(index.search(&SearchQuery::substring("main")).is_empty());
+    }
+
+    #[test]
+    fn test_churning_folders() {
+        let index = Index::new();
+        index.add_volume_records(&make_volume_info(), make_test_records());
+        for i in 0..700 {
+            index.apply_change(ChangeEvent::created(
+                VolumeId::new("C"),
+                FileId::new(1000 + i),
+                Some(FileId::new(100)),
+                format!("f{}.tmp", i),
+                false,
+                0,
+            ));
+        }
+        index.apply_change(ChangeEvent::created(
+            VolumeId::new("C"),
+            FileId::new(2000),
+            Some(FileId::new(5)),
+            "quiet.txt".to_string(),
+            false,
+            0,
+        ));
+
+        let busy = index.churning_folders(600);
+        assert_eq!(busy.len(), 1);
+        assert_eq!(busy[0].path, "C:\\Users");
+        assert_eq!(busy[0].per_minute, 700);
+    }
+
     #[test]
     fn test_apply_delete_change() {
         let index = Index::new();
@@ -764,6 +2665,86 @@ mod tests {
         assert!(results.is_empty());
     }
 
+    #[test]
+    fn test_search_deleted() {
+        let index = Index::new();
+        index.add_volume_records(&make_volume_info(), make_test_records());
+        for (id, name) in [(101, "README.md"), (102, "config.toml")] {
+            index.apply_change(ChangeEvent::deleted(
+                VolumeId::new("C"),
+                FileId::new(id),
+                Some(FileId::new(100)),
+                name.to_string(),
+                false,
+                1001,
+            ));
+        }
+
+        // Newest first, with the path the file had
+        let query = parse_query("deleted:").unwrap();
+        let page = index.search_counted(&query, 10);
+        assert_eq!(page.total, 2);
+        let names: Vec<&str> = page
+            .results
+            .iter()
+            .map(|r| r.record.name.as_str())
+            .collect();
+        assert_eq!(names, ["config.toml", "README.md"]);
+        assert_eq!(page.results[1].record.path, "C:\\Users\\README.md");
+        assert!(page.results.iter().all(|r| r.deleted_at.is_some()));
+        let page = index.search_ranked(&query, 1, 10);
+        assert_eq!(page.results[0].record.name, "README.md");
+
+        let query = parse_query("readme deleted:").unwrap();
+        assert_eq!(index.search(&query).len(), 1);
+        assert_eq!(index.snapshot().search(&query).len(), 1);
+        assert_eq!(index.count_by_volume(&query)[&VolumeId::new("C")], 1);
+        assert!(index.search(&parse_query("readme").unwrap()).is_empty());
+
+        // A file that comes back is no longer deleted
+        index.apply_change(ChangeEvent::created(
+            VolumeId::new("C"),
+            FileId::new(101),
+            Some(FileId::new(100)),
+            "README.md".to_string(),
+            false,
+            1002,
+        ));
+        assert!(index.search(&query).is_empty());
+        assert_eq!(index.recently_deleted().len(), 1);
+    }
+
+    #[test]
+    fn test_read_only_ignores_changes() {
+        let index = Index::new();
+        index.add_volume_records(&make_volume_info(), make_test_records());
+        let (len, generation) = (index.len(), index.generation());
+        index.set_read_only(true);
+
+        index.apply_change(ChangeEvent::deleted(
+            VolumeId::new("C"),
+            FileId::new(101),
+            Some(FileId::new(100)),
+            "README.md".to_string(),
+            false,
+            1001,
+        ));
+        index.add_volume_records(&make_volume_info(), Vec::new());
+        index.remove_volume(&VolumeId::new("C"));
+        index.clear();
+        assert_eq!((index.len(), index.generation()), (len, generation));
+        assert_eq!(index.search(&SearchQuery::substring("README")).len(), 1);
+        assert!(matches!(
+            index.ensure_writable("rebuild the index"),
+            Err(GlintError::ReadOnly { .. })
+        ));
+
+        index.set_read_only(false);
+        assert!(index.ensure_writable("rebuild the index").is_ok());
+        index.clear();
+        assert!(index.is_empty());
+    }
+
     #[test]
     fn test_apply_rename_change() {
         let index = Index::new();
@@ -791,6 +2772,489 @@ mod tests {
         assert!(results.is_empty());
     }
 
+    #[test]
+    fn test_rename_folder_moves_paths() {
+        let index = Index::new();
+        let mut records = make_test_records();
+        records.push(FileRecord::new(
+            FileId::new(104),
+            Some(FileId::new(5)),
+            VolumeId::new("C"),
+            "Archive".to_string(),
+            "C:\\Archive".to_string(),
+            true,
+        ));
+        index.add_volume_records(&make_volume_info(), records);
+        assert_eq!(
+            index.find_by_path("c:/users/main.rs/").map(|r| r.id),
+            Some(FileId::new(103))
+        );
+
+        // Users is renamed, and main.rs moved out of it
+        index.apply_change(ChangeEvent::renamed(
+            VolumeId::new("C"),
+            FileId::new(100),
+            Some(FileId::new(5)),
+            "Users".to_string(),
+            "People".to_string(),
+            None,
+            true,
+            1,
+        ));
+        index.apply_change(ChangeEvent::renamed(
+            VolumeId::new("C"),
+            FileId::new(103),
+            Some(FileId::new(100)),
+            "main.rs".to_string(),
+            "main.rs".to_string(),
+            Some(FileId::new(104)),
+            false,
+            2,
+        ));
+
+        let readme = index.find_by_path("C:\\People\\README.md").unwrap();
+        assert_eq!(readme.id, FileId::new(101));
+        assert!(index.find_by_path("C:\\Users\\README.md").is_none());
+        let moved = index
+            .find_by_volume_path(&VolumeId::new("C"), "C:\\Archive\\main.rs")
+            .unwrap();
+        assert_eq!(moved.id, FileId::new(103));
+        assert!(index
+            .find_by_volume_path(&VolumeId::new("D"), "C:\\Archive\\main.rs")
+            .is_none());
+        let names = |folder: u64| -> Vec<String> {
+            let children = index.get_children(&VolumeId::new("C"), FileId::new(folder));
+            children.into_iter().map(|r| r.name).collect()
+        };
+        assert_eq!(names(104), ["main.rs"]);
+        assert_eq!(names(100), ["README.md", "config.toml"]);
+        assert!(index.path_lookup_bytes() > 0);
+        assert_consistent(&index);
+    }
+
+    #[test]
+    fn test_extension_counts() {
+        let index = Index::new();
+        index.add_volume_records(&make_volume_info(), make_test_records());
+        let count = |ext: &str| {
+            index
+                .extension_counts()
+                .into_iter()
+                .find(|(e, _)| e == ext)
+                .map_or(0, |(_, n)| n)
+        };
+        assert_eq!(count("md"), 1);
+        assert_eq!(count("rs"), 1);
+
+        index.apply_change(ChangeEvent::renamed(
+            VolumeId::new("C"),
+            FileId::new(102),
+            Some(FileId::new(100)),
+            "config.toml".to_string(),
+            "config.rs".to_string(),
+            None,
+            false,
+            1,
+        ));
+        assert_eq!(count("toml"), 0);
+        assert_eq!(count("rs"), 2);
+        assert_eq!(index.extension_counts()[0], ("rs".to_string(), 2));
+
+        index.apply_change(ChangeEvent::deleted(
+            VolumeId::new("C"),
+            FileId::new(103),
+            Some(FileId::new(100)),
+            "main.rs".to_string(),
+            false,
+            2,
+        ));
+        index.apply_change(ChangeEvent::created(
+            VolumeId::new("C"),
+            FileId::new(104),
+            Some(FileId::new(100)),
+            "notes.MD".to_string(),
+            false,
+            3,
+        ));
+        assert_eq!(count("rs"), 1);
+        assert_eq!(count("md"), 2);
+
+        // Rescanning the volume replaces its counts
+        index.add_volume_records(&make_volume_info(), make_test_records());
+        assert_eq!(count("md"), 1);
+        assert_eq!(count("toml"), 1);
+    }
+
+    #[test]
+    fn test_append_volume_records() {
+        let whole = Index::new();
+        whole.add_volume_records(&make_volume_info(), make_test_records());
+
+        let index = Index::new();
+        let mut records = make_test_records();
+        let rest = records.split_off(2);
+        index.add_volume_records(&make_volume_info(), records);
+        let generation = index.generation();
+        assert!(index.search(&SearchQuery::substring("README")).is_empty());
+
+        for record in rest {
+            index.append_volume_records(&make_volume_info(), vec![record]);
+        }
+        assert_eq!(index.generation(), generation + 3);
+        assert_eq!(index.search(&SearchQuery::substring("README")).len(), 1);
+        let children = index.get_children(&VolumeId::new("C"), FileId::new(100));
+        assert_eq!(children.len(), 3);
+        assert_eq!(index.volume_states()[0].record_count, 5);
+        assert_eq!(index.stats().total_files, whole.stats().total_files);
+        assert_eq!(index.stats().total_size, whole.stats().total_size);
+        assert_eq!(index.extension_counts(), whole.extension_counts());
+    }
+
+    #[test]
+    fn test_folder_volume_changes() {
+        let volume = make_volume_info().subtree("C:\\Users");
+        let records: Vec<FileRecord> = make_test_records()
+            .into_iter()
+            .filter(|r| volume.contains_path(&r.path))
+            .map(|mut r| {
+                r.volume_id = volume.id.clone();
+                r
+            })
+            .collect();
+        let index = Index::new();
+        index.add_volume_records(&volume, records);
+        assert_eq!(index.len(), 4);
+
+        // Inside the folder
+        index.apply_change(ChangeEvent::created(
+            volume.id.clone(),
+            FileId::new(200),
+            Some(FileId::new(100)),
+            "notes.txt".to_string(),
+            false,
+            1,
+        ));
+        let results = index.search(&SearchQuery::substring("notes"));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].record.path, "C:\\Users\\notes.txt");
+
+        // Elsewhere on the volume
+        index.apply_change(ChangeEvent::created(
+            volume.id.clone(),
+            FileId::new(201),
+            Some(FileId::new(5)),
+            "pagefile.sys".to_string(),
+            false,
+            2,
+        ));
+        assert!(index.search(&SearchQuery::substring("pagefile")).is_empty());
+
+        // Moved out of the folder
+        index.apply_change(ChangeEvent::renamed(
+            volume.id.clone(),
+            FileId::new(101),
+            Some(FileId::new(100)),
+            "README.md".to_string(),
+            "README.md".to_string(),
+            Some(FileId::new(5)),
+            false,
+            3,
+        ));
+        assert!(index.search(&SearchQuery::substring("README")).is_empty());
+    }
+
+    #[test]
+    fn test_mount_folder_paths() {
+        // A volume without a drive letter, mounted in a folder
+        let volume = VolumeInfo::new(VolumeId::new("5A5A5A5A"), "C:\\Mount\\Data\\", "NTFS");
+        let index = Index::new();
+        index.add_volume_records(
+            &volume,
+            vec![FileRecord::new(
+                FileId::new(100),
+                None,
+                volume.id.clone(),
+                "photos".to_string(),
+                "C:\\Mount\\Data\\photos".to_string(),
+                true,
+            )],
+        );
+
+        index.apply_change(ChangeEvent::created(
+            volume.id.clone(),
+            FileId::new(101),
+            Some(FileId::new(100)),
+            "beach.jpg".to_string(),
+            false,
+            1,
+        ));
+        let results = index.search(&SearchQuery::substring("beach"));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].record.path, "C:\\Mount\\Data\\photos\\beach.jpg");
+    }
+
+    #[test]
+    fn test_dedup_links() {
+        let whole = make_volume_info();
+        let folder = whole.subtree("C:\\Users");
+        let folder_records: Vec<FileRecord> = make_test_records()
+            .into_iter()
+            .filter(|r| folder.contains_path(&r.path))
+            .map(|mut r| {
+                r.volume_id = folder.id.clone();
+                r
+            })
+            .collect();
+        let index = Index::new();
+        index.add_volume_records(&whole, make_test_records());
+        index.add_volume_records(&folder, folder_records);
+
+        let results = index.search(&SearchQuery::substring("main"));
+        assert_eq!(results.len(), 2);
+        let merged = index.dedup_links(results);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].links, 2);
+
+        // Same ID on another device is another file
+        let other = VolumeInfo::new(VolumeId::new("D"), "D:", "NTFS");
+        let mut copy = make_test_records().remove(4);
+        copy.volume_id = other.id.clone();
+        copy.path = "D:\\main.rs".to_string();
+        index.add_volume_records(&other, vec![copy]);
+        let merged = index.dedup_links(index.search(&SearchQuery::substring("main")));
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged.iter().map(|r| r.links).sum::<usize>(), 3);
+    }
+
+    #[test]
+    fn test_find_volume() {
+        let index = Index::new();
+        index.add_volume_records(&make_volume_info(), make_test_records());
+
+        for name in ["C:", "c:\\", "c"] {
+            let found = index.find_volume(name).map(|v| v.info.id);
+            assert_eq!(found, Some(VolumeId::new("C")), "{}", name);
+        }
+        assert!(index.find_volume("D:").is_none());
+        assert!(index.find_volume("").is_none());
+    }
+
+    #[test]
+    fn test_disabled_volume() {
+        let index = Index::new();
+        let volume = make_volume_info();
+        index.add_volume_records(&volume, make_test_records());
+        let query = SearchQuery::substring("config");
+
+        assert!(index.set_volume_enabled(&volume.id, false));
+        assert!(index.has_disabled_volumes());
+        assert!(index.search(&query).is_empty());
+        assert!(index.search_limited(&query, 10).is_empty());
+        assert_eq!(index.len(), 5);
+
+        // A rescan keeps the volume disabled
+        index.add_volume_records(&volume, make_test_records());
+        assert!(index.search(&query).is_empty());
+
+        assert!(index.set_volume_enabled(&volume.id, true));
+        assert_eq!(index.search(&query).len(), 1);
+        assert!(!index.set_volume_enabled(&VolumeId::new("Z"), false));
+    }
+
+    #[test]
+    fn test_cache_lower_paths() {
+        let index = Index::new();
+        index.set_cache_lower_paths(false);
+        index.add_volume_records(&make_volume_info(), make_test_records());
+        let query = SearchQuery::substring("users\\config").search_in_path(true);
+
+        index.for_each_record(|r| assert!(r.path_lower.is_empty()));
+        assert_eq!(index.search(&query).len(), 1);
+        assert!(index.find_by_path("c:\\USERS").is_some());
+
+        index.set_cache_lower_paths(true);
+        index.for_each_record(|r| assert_eq!(r.path_lower, r.path.to_lowercase()));
+        assert_eq!(index.search(&query).len(), 1);
+    }
+
+    #[test]
+    fn test_refresh_volume_info() {
+        let index = Index::new();
+        let volume = make_volume_info().with_capacity(1000, 500);
+        index.add_volume_records(&volume, make_test_records());
+        index.add_volume_records(&volume.subtree("C:\\Users"), Vec::new());
+
+        let current = vec![make_volume_info()
+            .with_label("System")
+            .with_capacity(1000, 20)];
+        assert_eq!(index.refresh_volume_info(&current), 2);
+        assert_eq!(index.refresh_volume_info(&current), 0);
+        for state in index.volume_states() {
+            assert_eq!(state.info.label.as_deref(), Some("System"));
+            assert!(state.info.is_low_on_space());
+        }
+    }
+
+    #[test]
+    fn test_volume_availability() {
+        let index = Index::new();
+        let volume = make_volume_info();
+        index.add_volume_records(&volume, make_test_records());
+        let query = SearchQuery::substring("config");
+        let availability = || index.volume_states()[0].availability;
+
+        // Unplugged: still searchable unless offline volumes are hidden
+        assert!(index.update_availability(&[]).is_empty());
+        assert_eq!(availability(), VolumeAvailability::Offline);
+        assert!(!index.set_volume_offline(&volume.id, "gone"));
+        assert_eq!(index.search(&query).len(), 1);
+        index.set_hide_offline(true);
+        assert!(index.has_disabled_volumes());
+        assert!(index.search(&query).is_empty());
+
+        // Plugged back in: stale until caught up, and searched again
+        let back = index.update_availability(&[make_volume_info()]);
+        assert_eq!(back.len(), 1);
+        assert_eq!(back[0].availability, VolumeAvailability::Stale);
+        assert!(index.update_availability(&[make_volume_info()]).is_empty());
+        assert_eq!(index.search(&query).len(), 1);
+        assert!(index.set_volume_online(&volume.id));
+        assert_eq!(availability(), VolumeAvailability::Online);
+        assert!(!index.has_disabled_volumes());
+
+        // Having been scanned, a volume is online again
+        assert!(index.set_volume_offline(&volume.id, "gone"));
+        index.add_volume_records(&volume, make_test_records());
+        assert_eq!(availability(), VolumeAvailability::Online);
+        assert!(!index.set_volume_online(&VolumeId::new("Z")));
+    }
+
+    #[test]
+    fn test_index_is_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Index>();
+    }
+
+    /// Check that the auxiliary indices point at the records they describe.
+    fn assert_consistent(index: &Index) {
+        let records = index.records.read();
+        let live = (0..records.len()).filter(|&idx| records.row(idx).live);
+        for idx in live.clone() {
+            let (volume_id, id) = (records.volume_id(idx), records.id(idx));
+            let found = records.position(volume_id, id);
+            let found = found.map(|idx| (records.volume_id(idx), records.id(idx)));
+            assert_eq!(found, Some((volume_id, id)));
+            if let Some(parent) = records.parent(idx) {
+                assert_eq!(Some(records.id(parent)), records.parent_id(idx));
+                assert_eq!(records.volume_id(parent), volume_id);
+            }
+            if let Some(parent) = records.parent_id(idx) {
+                assert!(records.children(volume_id, parent).contains(&idx));
+            }
+        }
+        for idx in live {
+            let found = records.find_path(Some(records.volume_id(idx)), &records.path_lower(idx));
+            assert_eq!(found.map(|idx| records.path(idx)), Some(records.path(idx)));
+        }
+    }
+
+    #[test]
+    fn test_concurrent_search_and_updates() {
+        let index = Index::new();
+        let volume_c = make_volume_info();
+        let volume_d = VolumeInfo::new(VolumeId::new("D"), "D:", "NTFS");
+        let records_d = || -> Vec<FileRecord> {
+            make_test_records()
+                .into_iter()
+                .map(|mut r| {
+                    r.volume_id = VolumeId::new("D");
+                    r.path = r.path.replacen("C:", "D:", 1);
+                    r
+                })
+                .collect()
+        };
+        index.add_volume_records(&volume_c, make_test_records());
+        index.add_volume_records(&volume_d, records_d());
+
+        let done = AtomicBool::new(false);
+        std::thread::scope(|s| {
+            // Rescans move every record of the other volume around
+            let rescans = s.spawn(|| {
+                for _ in 0..200 {
+                    index.add_volume_records(&volume_d, records_d());
+                }
+            });
+
+            let changes = s.spawn(|| {
+                let c = VolumeId::new("C");
+                let users = Some(FileId::new(100));
+                for i in 0..300u64 {
+                    let id = FileId::new(1000 + i);
+                    let name = format!("new_{}.txt", i);
+                    index.apply_change(ChangeEvent::created(
+                        c.clone(),
+                        id,
+                        users,
+                        name.clone(),
+                        false,
+                        0,
+                    ));
+                    index.apply_change(ChangeEvent::renamed(
+                        c.clone(),
+                        id,
+                        users,
+                        name,
+                        format!("renamed_{}.txt", i),
+                        users,
+                        false,
+                        0,
+                    ));
+                    if i % 2 == 0 {
+                        index.apply_change(ChangeEvent::deleted(
+                            c.clone(),
+                            id,
+                            users,
+                            format!("renamed_{}.txt", i),
+                            false,
+                            0,
+                        ));
+                    }
+                }
+            });
+
+            for _ in 0..2 {
+                s.spawn(|| {
+                    let query = SearchQuery::substring("main");
+                    while !done.load(Ordering::Acquire) {
+                        let results = index.search(&query);
+                        assert!(results.iter().all(|r| r.record.name == "main.rs"));
+                        assert!((1..=2).contains(&results.len()));
+
+                        let page = index.search_counted(&query, 1);
+                        assert!(page.total >= page.results.len());
+
+                        let main = index.get(&VolumeId::new("C"), FileId::new(103));
+                        assert_eq!(main.map(|r| r.name), Some("main.rs".to_string()));
+                        let children = index.get_children(&VolumeId::new("C"), FileId::new(100));
+                        assert!(children.len() >= 3);
+                    }
+                });
+            }
+
+            rescans.join().unwrap();
+            changes.join().unwrap();
+            done.store(true, Ordering::Release);
+        });
+
+        assert_consistent(&index);
+        assert_eq!(index.search(&SearchQuery::substring("main")).len(), 2);
+        let renamed = index.search(&SearchQuery::substring("renamed_"));
+        assert_eq!(renamed.len(), 150);
+        assert!(renamed
+            .iter()
+            .all(|r| r.record.path == format!("C:\\Users\\{}", r.record.name)));
+    }
+
     #[test]
     fn test_get_children() {
         let index = Index::new();
@@ -800,6 +3264,68 @@ mod tests {
         assert_eq!(children.len(), 3); // README.md, config.toml, main.rs
     }
 
+    #[test]
+    fn test_largest() {
+        let index = Index::new();
+        index.add_volume_records(&make_volume_info(), make_test_records());
+        let all = SearchQuery::substring("");
+        let names = |page: &SearchPage| {
+            page.results
+                .iter()
+                .map(|r| (r.record.name.clone(), r.record.size.unwrap()))
+                .collect::<Vec<_>>()
+        };
+
+        let files = index.largest(&all, LargestKind::Files, 2);
+        assert_eq!(
+            names(&files),
+            [
+                ("main.rs".to_string(), 2048),
+                ("README.md".to_string(), 1024)
+            ]
+        );
+        assert_eq!(files.total, 3);
+
+        // Folders are sized by their contents
+        let folders = index.largest(&all, LargestKind::Folders, 10);
+        assert_eq!(names(&folders), [("Users".to_string(), 3328)]);
+
+        let both = index.largest(&all, LargestKind::All, 10);
+        assert_eq!(both.total, 4);
+        assert_eq!(both.results[0].record.name, "Users");
+        assert_eq!(both.results[3].record.name, "config.toml");
+
+        let toml = SearchQuery::substring("toml");
+        assert_eq!(index.largest(&toml, LargestKind::All, 10).total, 1);
+    }
+
+    #[test]
+    fn test_folder_totals() {
+        let index = Index::new();
+        index.add_volume_records(&make_volume_info(), make_test_records());
+        let c = VolumeId::new("C");
+
+        let users = index.folder_totals(&c, FileId::new(100));
+        assert_eq!((users.files, users.dirs, users.size), (3, 0, 3328));
+        let root = index.folder_totals(&c, FileId::new(5));
+        assert_eq!((root.files, root.dirs, root.size), (3, 1, 3328));
+
+        // Deleted files no longer count
+        index.apply_change(ChangeEvent::deleted(
+            c.clone(),
+            FileId::new(101),
+            Some(FileId::new(100)),
+            "README.md".into(),
+            false,
+            1,
+        ));
+        assert_eq!(index.folder_totals(&c, FileId::new(5)).size, 2304);
+        assert_eq!(
+            index.folder_totals(&c, FileId::new(101)),
+            ResultTotals::default()
+        );
+    }
+
     #[test]
     fn test_stats() {
         let index = Index::new();
@@ -823,6 +3349,119 @@ mod tests {
         assert!(index.is_empty());
     }
 
+    #[test]
+    fn test_compact() {
+        let index = Index::new();
+        index.add_volume_records(&make_volume_info(), make_test_records());
+        let backup = VolumeInfo::new(VolumeId::new("E"), "E:", "NTFS");
+        index.add_volume_records(
+            &backup,
+            vec![FileRecord::new(
+                FileId::new(7),
+                None,
+                backup.id.clone(),
+                "old.zip".to_string(),
+                "E:\\old.zip".to_string(),
+                false,
+            )],
+        );
+        index.apply_change(ChangeEvent::deleted(
+            VolumeId::new("C"),
+            FileId::new(101),
+            Some(FileId::new(100)),
+            "README.md".to_string(),
+            false,
+            1001,
+        ));
+        assert_eq!(index.tombstone_count(), 1);
+
+        // Only volumes missing from the listing for long enough are absent
+        let present = [make_volume_info()];
+        assert!(index
+            .absent_volumes(&present, Duration::from_secs(3600))
+            .is_empty());
+        std::thread::sleep(Duration::from_millis(5));
+        let absent = index.absent_volumes(&present, Duration::from_millis(1));
+        assert_eq!(absent.len(), 1);
+        assert_eq!(absent[0].info.id, backup.id);
+
+        let report = index.compact(&[backup.id]);
+        assert_eq!(
+            report,
+            CompactReport {
+                tombstones: 1,
+                volume_records: 1,
+                volumes: vec!["E:".to_string()],
+            }
+        );
+        assert_eq!(index.len(), 4);
+        assert_eq!(index.tombstone_count(), 0);
+        assert_eq!(index.volume_states().len(), 1);
+        assert!(index.compact(&[]).is_empty());
+
+        // Changes still find their records at the new positions
+        index.apply_change(ChangeEvent::renamed(
+            VolumeId::new("C"),
+            FileId::new(103),
+            Some(FileId::new(100)),
+            "main.rs".to_string(),
+            "lib.rs".to_string(),
+            Some(FileId::new(100)),
+            false,
+            1002,
+        ));
+        let results = index.search(&SearchQuery::substring("lib.rs"));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].record.path, "C:\\Users\\lib.rs");
+        assert_eq!(index.search(&SearchQuery::substring("config")).len(), 1);
+    }
+
+    #[test]
+    fn test_rebuild_with() {
+        let index = Index::new();
+        index.add_volume_records(&make_volume_info(), make_test_records());
+        let count = |name: &str| index.search(&SearchQuery::substring(name)).len();
+
+        // A failed build leaves the index alone
+        let failed = index.rebuild_with(|| Err("scan failed"));
+        assert_eq!(failed, Err("scan failed"));
+        assert_eq!(count("main.rs"), 1);
+        assert!(index.replay.lock().is_none());
+
+        let before = index.generation();
+        index
+            .rebuild_with(|| {
+                // The scan no longer finds main.rs, and a file is created
+                // while it runs
+                let rebuilt = Index::new();
+                let mut records = make_test_records();
+                records.pop();
+                rebuilt.add_volume_records(&make_volume_info(), records);
+                index.apply_change(ChangeEvent::created(
+                    VolumeId::new("C"),
+                    FileId::new(104),
+                    Some(FileId::new(100)),
+                    "notes.txt".to_string(),
+                    false,
+                    1,
+                ));
+                index.update_journal_state(&VolumeId::new("C"), JournalState::new(7, 42));
+                // Searches still see the old records
+                assert_eq!(count("main.rs"), 1);
+                Ok::<_, ()>(rebuilt)
+            })
+            .unwrap();
+
+        assert!(index.generation() > before);
+        assert_eq!(count("main.rs"), 0);
+        assert_eq!(count("README"), 1);
+        let notes = index.search(&SearchQuery::substring("notes.txt"));
+        assert_eq!(notes[0].record.path, "C:\\Users\\notes.txt");
+        assert_eq!(index.stats().total_files, 2);
+        let journal = index.volume_states().remove(0).journal_state;
+        assert_eq!(journal, Some(JournalState::new(7, 42)));
+    }
+
     #[test]
     fn test_generation() {
         let index = Index::new();
@@ -833,4 +3472,171 @@ mod tests {
 
         assert!(gen2 > gen1);
     }
+
+    #[test]
+    fn test_snapshot() {
+        let index = Index::new();
+        index.add_volume_records(&make_volume_info(), make_test_records());
+        let query = SearchQuery::substring("");
+        let snapshot = index.snapshot();
+        assert_eq!(snapshot.generation(), index.generation());
+        let before = snapshot.search_counted(&query, 2);
+
+        // Changes and a cleared index don't show up in the snapshot
+        index.apply_change(ChangeEvent::deleted(
+            VolumeId::new("C"),
+            FileId::new(101),
+            Some(FileId::new(100)),
+            "README.md".into(),
+            false,
+            1,
+        ));
+        index.clear();
+        assert!(index.search(&query).is_empty());
+        let after = snapshot.search_counted(&query, 2);
+        assert_eq!(after.total, before.total);
+        let ids = |page: &SearchPage| page.results.iter().map(|r| r.record.id).collect::<Vec<_>>();
+        assert_eq!(ids(&after), ids(&before));
+        assert_eq!(snapshot.search(&query).len(), before.total);
+    }
+
+    #[test]
+    fn test_rank_name_matches() {
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        let paths = [
+            "C:\\cargo.toml.d\\readme.txt",
+            "C:\\oldcargo.toml",
+            "C:\\old\\my_cargo.toml.bak",
+            "C:\\cargo.toml.bak",
+            "C:\\src\\app\\Cargo.toml",
+            "C:\\src\\Cargo.toml",
+        ];
+        let records = paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| {
+                FileRecord::new(
+                    FileId::new(i as u64 + 1),
+                    None,
+                    volume.id.clone(),
+                    path.rsplit('\\').next().unwrap().to_string(),
+                    path.to_string(),
+                    false,
+                )
+            })
+            .collect();
+        let index = Index::new();
+        index.add_volume_records(&volume, records);
+
+        // Exact names first, the shallower one ahead, then prefix, word and
+        // substring matches, and paths that only contain it in a folder
+        let query = SearchQuery::substring("cargo.toml").search_in_path(true);
+        let mut results = index.search(&query);
+        results.sort_by(|a, b| a.rank_cmp(b));
+        let ranked: Vec<&str> = results.iter().map(|r| r.record.path.as_str()).collect();
+        let mut expected = paths.to_vec();
+        expected.reverse();
+        assert_eq!(ranked, expected);
+    }
+
+    #[test]
+    fn test_search_ranked_pages() {
+        let volumes = [
+            VolumeInfo::new(VolumeId::new("D"), "D:", "NTFS"),
+            VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS"),
+        ];
+        // Many names of the same length, so most scores tie
+        let records = |volume: &VolumeInfo, reversed: bool| {
+            let mut records: Vec<FileRecord> = (1..=40u64)
+                .map(|i| {
+                    let name = format!("file_{:02}{}", i, if i % 7 == 0 { "" } else { ".txt" });
+                    FileRecord::new(
+                        FileId::new(i),
+                        None,
+                        volume.id.clone(),
+                        name.clone(),
+                        format!("{}\\{}", volume.mount_point, name),
+                        false,
+                    )
+                })
+                .collect();
+            if reversed {
+                records.reverse();
+            }
+            records
+        };
+        let index = Index::new();
+        let shuffled = Index::new();
+        for volume in &volumes {
+            index.add_volume_records(volume, records(volume, false));
+        }
+        for volume in volumes.iter().rev() {
+            shuffled.add_volume_records(volume, records(volume, true));
+        }
+
+        let query = SearchQuery::substring("file_");
+        let key = |r: &SearchResult| (r.record.volume_id.as_str().to_string(), r.record.id);
+        let mut all = index.search(&query);
+        all.sort_by(|a, b| a.rank_cmp(b));
+        let all: Vec<_> = all.iter().map(key).collect();
+        assert_eq!(all.len(), 80);
+        assert_eq!(all[0], ("C".to_string(), FileId::new(7)));
+
+        // Pages join up to the full ranking whatever the index order
+        for index in [&index, &shuffled] {
+            let mut paged = Vec::new();
+            for offset in (0..90).step_by(7) {
+                let page = index.search_ranked(&query, offset, 7);
+                assert_eq!(page.total, 80);
+                paged.extend(page.results.iter().map(key));
+            }
+            assert_eq!(paged, all);
+        }
+        assert!(index.search_ranked(&query, 80, 10).results.is_empty());
+        assert!(index.search_ranked(&query, 0, 0).results.is_empty());
+        let snapshot = index.snapshot();
+        let page = snapshot.search_ranked(&query, 3, 5);
+        assert_eq!(page.results.iter().map(key).collect::<Vec<_>>(), all[3..8]);
+
+        // A search told to stop gives up; one that isn't finishes the same
+        let cancel = AtomicBool::new(false);
+        let page = snapshot
+            .search_ranked_cancellable(&query, 3, 5, &cancel)
+            .unwrap();
+        assert_eq!(page.results.iter().map(key).collect::<Vec<_>>(), all[3..8]);
+        cancel.store(true, Ordering::Release);
+        assert!(snapshot
+            .search_ranked_cancellable(&query, 3, 5, &cancel)
+            .is_none());
+    }
+
+    #[test]
+    fn test_subscribe() {
+        let index = Index::new();
+        let rx = index.subscribe();
+
+        index.add_volume_records(&make_volume_info(), make_test_records());
+        let change = rx.try_recv().unwrap();
+        assert_eq!(change.generation, index.generation());
+        assert_eq!(change.volume, Some(VolumeId::new("C")));
+        assert_eq!(change.created, None);
+        assert!(rx.try_recv().is_err());
+
+        // Creates name the new file so live results can add it
+        index.apply_change(ChangeEvent::created(
+            VolumeId::new("C"),
+            FileId::new(200),
+            Some(FileId::new(100)),
+            "new.txt".to_string(),
+            false,
+            0,
+        ));
+        let change = rx.try_recv().unwrap();
+        assert_eq!(change.created, Some(FileId::new(200)));
+
+        // A dropped receiver is pruned on the next change
+        drop(rx);
+        index.clear();
+        assert!(index.subscribers.lock().is_empty());
+    }
 }
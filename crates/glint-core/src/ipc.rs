@@ -0,0 +1,1296 @@
+//! Local IPC for querying a running Glint instance.
+//!
+//! The watch service owns the live index. Other processes (most notably the
+//! Linux CLI running inside WSL) can query it over a TCP connection instead
+//! of loading the index themselves.
+//!
+//! ## Protocol
+//!
+//! The protocol is deliberately simple: one JSON-encoded [`IpcRequest`] per
+//! line, answered by one JSON-encoded [`IpcResponse`] per line. A connection
+//! may carry any number of requests.
+//!
+//! ```text
+//! -> {"Query":{"query":"*.rs","limit":100,...}}
+//! <- {"Results":{"results":[...],"elapsed_ms":1.2}}
+//! ```
+//!
+//! The server shares the index with everything else the service does, so
+//! queries are kept within [`QueryLimits`]: results are capped, and a query
+//! is refused while too many others run or answered with a timeout, and
+//! stopped, when it runs too long (see [`Refusal`]). Connections past
+//! [`QueryLimits::max_connections`] are turned away, request lines longer
+//! than [`MAX_REQUEST_BYTES`] end the connection, and so does
+//! [`IDLE_TIMEOUT`] without a request.
+//!
+//! ## Paging
+//!
+//! A query is searched in an [`IndexSnapshot`] of the index as it is now.
+//! When it finds more results than the page it was asked for, the server
+//! keeps that snapshot for [`PAGE_SNAPSHOT_TTL`], and later pages of the
+//! same query, from any connection, are searched in it, so they neither
+//! repeat nor skip results while the index changes. Queries paged while the
+//! index is unchanged share one snapshot. A kept snapshot holds a copy of
+//! the records once the index changes, so only the [`MAX_PAGE_SNAPSHOTS`]
+//! most recent are kept, and queries whose results fit one page keep none.
+//!
+//! ## Authentication
+//!
+//! Every filename on the machine can be read through the server, so when a
+//! token is set in `[server] token` a connection must first present it:
+//!
+//! ```text
+//! -> {"Auth":{"token":"..."}}
+//! <- "Authenticated"
+//! ```
+//!
+//! Until it has, every other request is refused with
+//! [`Refusal::Unauthorized`].
+//!
+//! ## Encryption
+//!
+//! Connections are plain TCP unless the server is given a certificate
+//! ([`ServerTls`], `tls` feature), when they are TLS and clients connect
+//! with [`IpcClient::connect_tls`]. Without it, beyond a trusted network
+//! reach the server through an SSH tunnel or VPN: the token and every
+//! result cross the network in the clear.
+
+use crate::error::{GlintError, Result};
+use crate::index::{Index, IndexSnapshot};
+use crate::search::{parse_query, SearchFilter, SearchQuery, SearchResult};
+use crate::tls::{ClientTls, IpcStream, ServerTls};
+use crate::types::{FileRecord, IndexStats};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// Default address the IPC server listens on.
+pub const DEFAULT_LISTEN_ADDR: &str = "127.0.0.1:7379";
+
+/// How long later pages of a query are searched in the snapshot its first
+/// page was.
+pub const PAGE_SNAPSHOT_TTL: Duration = Duration::from_secs(60);
+
+/// Most snapshots, each of a different state of the index, a server keeps
+/// for later pages of queries.
+pub const MAX_PAGE_SNAPSHOTS: usize = 2;
+
+/// Longest request line a server reads, newline included; a longer one is
+/// answered with an error and ends the connection.
+pub const MAX_REQUEST_BYTES: usize = 64 * 1024;
+
+/// How long a server waits for the next request of a connection before
+/// closing it.
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Limits on the queries a server answers, from the `[server]` config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryLimits {
+    /// Most results one query returns; larger limits are lowered to this
+    pub max_results: usize,
+
+    /// How long a client waits for a query before it is told the query
+    /// timed out
+    pub timeout: Duration,
+
+    /// Most queries run at once; more are refused until one finishes
+    pub max_concurrent: usize,
+
+    /// Most connections open at once; more are turned away until one
+    /// closes
+    pub max_connections: usize,
+}
+
+impl Default for QueryLimits {
+    fn default() -> Self {
+        QueryLimits {
+            max_results: 10_000,
+            timeout: Duration::from_secs(10),
+            max_concurrent: 4,
+            max_connections: 64,
+        }
+    }
+}
+
+/// A search request as sent over IPC.
+///
+/// This mirrors the options of `glint query` so that local and remote
+/// searches build exactly the same [`SearchQuery`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueryRequest {
+    /// Query string in the normal query syntax
+    pub query: String,
+
+    /// Maximum number of results to return
+    pub limit: usize,
+
+    /// Number of results to skip, for asking for the next page; results
+    /// are in [`SearchResult::rank_cmp`] order
+    pub offset: usize,
+
+    /// Only return files
+    pub files_only: bool,
+
+    /// Only return directories
+    pub dirs_only: bool,
+
+    /// Only return files with these extensions
+    pub extensions: Vec<String>,
+
+    /// Match against full paths instead of filenames
+    pub search_path: bool,
+
+    /// Only return results inside these folders (from the selected scopes)
+    pub scope: Vec<String>,
+
+    /// Only return results inside any of these folders (absolute paths)
+    pub within: Vec<String>,
+
+    /// Only return results on the indexed volume of this name, e.g. `D:`
+    pub volume: Option<String>,
+
+    /// Leave out results with a name, or in a folder, matching any of
+    /// these globs
+    pub exclude: Vec<String>,
+}
+
+impl QueryRequest {
+    /// Create a request for the given query string and result limit.
+    pub fn new(query: impl Into<String>, limit: usize) -> Self {
+        QueryRequest {
+            query: query.into(),
+            limit,
+            ..Default::default()
+        }
+    }
+
+    /// Compile this request into a search query for `index`, which the
+    /// volume name is looked up in.
+    pub fn to_search_query(&self, index: &Index) -> Result<SearchQuery> {
+        let mut query = parse_query(&self.query)?;
+
+        if self.files_only {
+            query = query.with_filter(SearchFilter::FilesOnly);
+        } else if self.dirs_only {
+            query = query.with_filter(SearchFilter::DirsOnly);
+        }
+
+        if !self.extensions.is_empty() {
+            query = query.with_filter(SearchFilter::Extensions(self.extensions.clone().into()));
+        }
+
+        if !self.scope.is_empty() {
+            query = query.with_filter(SearchFilter::in_folders(&self.scope));
+        }
+
+        if !self.within.is_empty() {
+            query = query.with_filter(SearchFilter::in_folders(&self.within));
+        }
+
+        if let Some(name) = &self.volume {
+            let volume = index
+                .find_volume(name)
+                .ok_or_else(|| GlintError::VolumeNotFound {
+                    volume: name.clone(),
+                })?;
+            query = query.with_filter(SearchFilter::Volume(volume.info.id));
+        }
+
+        for pattern in &self.exclude {
+            query = query.with_filter(SearchFilter::exclude_glob(pattern)?);
+        }
+
+        if self.search_path {
+            query = query.search_in_path(true);
+        }
+
+        Ok(query)
+    }
+
+    /// This request with the page left out, which all pages of a query
+    /// share.
+    fn without_page(&self) -> QueryRequest {
+        QueryRequest {
+            limit: 0,
+            offset: 0,
+            ..self.clone()
+        }
+    }
+}
+
+/// Requests understood by the IPC server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum IpcRequest {
+    /// Run a search
+    Query(QueryRequest),
+    /// Return index statistics
+    Status,
+    /// Present the server's token; needed once per connection before
+    /// anything else when the server has one
+    Auth { token: String },
+}
+
+/// Responses sent by the IPC server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum IpcResponse {
+    /// Search results
+    Results {
+        results: Vec<FileRecord>,
+        /// Regex capture groups of each result; empty when the query has none
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        captures: Vec<Vec<String>>,
+        elapsed_ms: f64,
+    },
+    /// Index statistics
+    Status { stats: IndexStats },
+    /// The token was accepted, or the server needs none
+    Authenticated,
+    /// The request failed
+    Error {
+        message: String,
+        /// Set when the server's [`QueryLimits`] turned the query away
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        refused: Option<Refusal>,
+    },
+}
+
+impl IpcResponse {
+    /// A failed request.
+    pub fn error(message: impl Into<String>) -> Self {
+        IpcResponse::Error {
+            message: message.into(),
+            refused: None,
+        }
+    }
+}
+
+/// Why a server turned a query away, like an HTTP status code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Refusal {
+    /// Too many queries were running, or connections open (HTTP 429); try
+    /// again shortly
+    Busy,
+    /// The query ran longer than the server allows (HTTP 504)
+    TimedOut,
+    /// The connection has not presented the server's token (HTTP 401)
+    Unauthorized,
+}
+
+/// Runs queries within [`QueryLimits`], shared by all connections of a
+/// server.
+///
+/// Each query runs on a thread of its own, so one that overruns the
+/// timeout can be answered while it is told to stop. It keeps its place
+/// among the running queries until it has, so clients that give up and
+/// retry can't pile up searches.
+#[derive(Debug, Clone)]
+pub struct QueryGate {
+    limits: QueryLimits,
+    running: Arc<AtomicUsize>,
+    connections: Arc<AtomicUsize>,
+    token: Option<Arc<str>>,
+    pages: PageSnapshots,
+}
+
+impl QueryGate {
+    pub fn new(limits: QueryLimits) -> Self {
+        QueryGate {
+            limits,
+            running: Arc::new(AtomicUsize::new(0)),
+            connections: Arc::new(AtomicUsize::new(0)),
+            token: None,
+            pages: PageSnapshots::default(),
+        }
+    }
+
+    /// Require connections to present `token` before anything else.
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token.filter(|t| !t.is_empty()).map(Arc::from);
+        self
+    }
+
+    /// Whether a connection starts out allowed to make requests.
+    pub fn is_open(&self) -> bool {
+        self.token.is_none()
+    }
+
+    /// Answer a request that a connection may not make yet, or an
+    /// [`IpcRequest::Auth`], updating whether the connection is
+    /// `authenticated`. None when the request should go on to
+    /// [`handle`](Self::handle).
+    pub fn admit(&self, request: &IpcRequest, authenticated: &mut bool) -> Option<IpcResponse> {
+        match request {
+            IpcRequest::Auth { token } => {
+                *authenticated = match &self.token {
+                    Some(expected) => tokens_match(expected.as_bytes(), token.as_bytes()),
+                    None => true,
+                };
+                Some(if *authenticated {
+                    IpcResponse::Authenticated
+                } else {
+                    warn!("IPC client presented a wrong token");
+                    IpcResponse::Error {
+                        message: "Wrong token".to_string(),
+                        refused: Some(Refusal::Unauthorized),
+                    }
+                })
+            }
+            _ if !*authenticated => Some(IpcResponse::Error {
+                message: "This server needs a token; set it in the client's config".to_string(),
+                refused: Some(Refusal::Unauthorized),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Warn when the server listens beyond this machine without a token.
+    pub fn warn_if_exposed(&self, addr: SocketAddr) {
+        if self.token.is_none() && !addr.ip().is_loopback() {
+            warn!(
+                addr = %addr,
+                "IPC server is reachable from other machines without a token; \
+                 set [server] token"
+            );
+        }
+    }
+
+    /// Execute a request against the index within the limits, searching
+    /// later pages of a query in the snapshot of its first (see
+    /// [Paging](self#paging)).
+    pub fn handle(&self, request: IpcRequest, index: &Arc<Index>) -> IpcResponse {
+        let mut req = match request {
+            IpcRequest::Query(req) => req,
+            other => return handle_request(other, index),
+        };
+        req.limit = req.limit.min(self.limits.max_results);
+        let kept = if req.offset > 0 {
+            self.pages.kept(&req)
+        } else {
+            None
+        };
+        let fresh = kept.is_none();
+        let snapshot = kept.unwrap_or_else(|| self.pages.current(index));
+        let pages = self.pages.clone();
+        let index = Arc::clone(index);
+        self.run(move |cancel| {
+            let (response, more) = run_query(&req, &index, &snapshot, cancel);
+            if fresh && more {
+                pages.keep(&req, &snapshot);
+            } else if req.offset == 0 {
+                pages.forget(&req);
+            }
+            response
+        })
+    }
+
+    /// Take a place among the open connections, if one is free; the
+    /// connection is turned away with the given response otherwise.
+    pub fn connect(&self) -> std::result::Result<Slot, IpcResponse> {
+        Slot::take(&self.connections, self.limits.max_connections).ok_or_else(|| {
+            IpcResponse::Error {
+                message: format!(
+                    "Server busy: {} connections are already open, try again shortly",
+                    self.limits.max_connections
+                ),
+                refused: Some(Refusal::Busy),
+            }
+        })
+    }
+
+    /// Run `query` on a thread of its own, answering with its response or
+    /// with why it was turned away. The query is passed a flag set when it
+    /// times out, which it should stop at.
+    fn run(&self, query: impl FnOnce(&AtomicBool) -> IpcResponse + Send + 'static) -> IpcResponse {
+        let Some(slot) = Slot::take(&self.running, self.limits.max_concurrent) else {
+            return IpcResponse::Error {
+                message: format!(
+                    "Server busy: {} queries are already running, try again shortly",
+                    self.limits.max_concurrent
+                ),
+                refused: Some(Refusal::Busy),
+            };
+        };
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        let cancel = Arc::new(AtomicBool::new(false));
+        let cancelled = Arc::clone(&cancel);
+        let spawned = thread::Builder::new()
+            .name("glint-ipc-query".to_string())
+            .spawn(move || {
+                let response = query(&cancelled);
+                drop(slot);
+                let _ = sender.send(response);
+            });
+        if let Err(e) = spawned {
+            return IpcResponse::error(format!("Cannot run the query: {}", e));
+        }
+        receiver
+            .recv_timeout(self.limits.timeout)
+            .unwrap_or_else(|_| {
+                warn!(timeout = ?self.limits.timeout, "IPC query timed out");
+                cancel.store(true, Ordering::Release);
+                IpcResponse::Error {
+                    message: format!(
+                        "Query timed out after {:.1}s",
+                        self.limits.timeout.as_secs_f64()
+                    ),
+                    refused: Some(Refusal::TimedOut),
+                }
+            })
+    }
+}
+
+/// Compare two tokens in time that depends only on their length.
+fn tokens_match(expected: &[u8], given: &[u8]) -> bool {
+    expected.len() == given.len()
+        && expected
+            .iter()
+            .zip(given)
+            .fold(0, |differ, (a, b)| differ | (a ^ b))
+            == 0
+}
+
+/// Snapshots later pages of recent queries are searched in, shared by all
+/// connections of a server.
+#[derive(Debug, Clone, Default)]
+struct PageSnapshots(Arc<Mutex<Vec<PageSnapshot>>>);
+
+#[derive(Debug)]
+struct PageSnapshot {
+    snapshot: IndexSnapshot,
+    /// The queries searched in it, without their page
+    requests: Vec<QueryRequest>,
+    /// When a query was last kept in it
+    taken: Instant,
+}
+
+impl PageSnapshots {
+    /// The snapshot a first page of `request` was searched in, if it was
+    /// kept and hasn't expired.
+    fn kept(&self, request: &QueryRequest) -> Option<IndexSnapshot> {
+        let key = request.without_page();
+        let mut pages = self.0.lock();
+        pages.retain(|page| page.taken.elapsed() < PAGE_SNAPSHOT_TTL);
+        pages
+            .iter()
+            .find(|page| page.requests.contains(&key))
+            .map(|page| page.snapshot.clone())
+    }
+
+    /// A snapshot of `index` as it is now, the kept one if the index hasn't
+    /// changed since it was taken.
+    fn current(&self, index: &Index) -> IndexSnapshot {
+        let generation = index.generation();
+        let pages = self.0.lock();
+        match pages
+            .iter()
+            .find(|page| page.snapshot.generation() == generation)
+        {
+            Some(page) => page.snapshot.clone(),
+            None => index.snapshot(),
+        }
+    }
+
+    /// Stop searching later pages of `request` in the snapshot its first
+    /// page was, dropping snapshots no query is kept in.
+    fn forget(&self, request: &QueryRequest) {
+        let key = request.without_page();
+        let mut pages = self.0.lock();
+        for page in pages.iter_mut() {
+            page.requests.retain(|kept| *kept != key);
+        }
+        pages.retain(|page| !page.requests.is_empty() && page.taken.elapsed() < PAGE_SNAPSHOT_TTL);
+    }
+
+    /// Keep `snapshot` for the later pages of `request`.
+    fn keep(&self, request: &QueryRequest, snapshot: &IndexSnapshot) {
+        self.forget(request);
+        let key = request.without_page();
+        let mut pages = self.0.lock();
+        let generation = snapshot.generation();
+        if let Some(page) = pages
+            .iter_mut()
+            .find(|page| page.snapshot.generation() == generation)
+        {
+            page.requests.push(key);
+            page.taken = Instant::now();
+            return;
+        }
+        if pages.len() >= MAX_PAGE_SNAPSHOTS {
+            pages.remove(0);
+        }
+        pages.push(PageSnapshot {
+            snapshot: snapshot.clone(),
+            requests: vec![key],
+            taken: Instant::now(),
+        });
+    }
+
+    /// How many snapshots are kept.
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.0.lock().len()
+    }
+}
+
+/// A running query's, or an open connection's, place among those a
+/// [`QueryGate`] allows; given back when dropped.
+#[derive(Debug)]
+pub struct Slot(Arc<AtomicUsize>);
+
+impl Slot {
+    /// Take one of `max` places counted by `taken`, if one is free.
+    fn take(taken: &Arc<AtomicUsize>, max: usize) -> Option<Slot> {
+        taken
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < max).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| Slot(Arc::clone(taken)))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Handle for a running IPC server.
+///
+/// The server stops accepting connections when the handle is dropped.
+pub struct IpcServer {
+    local_addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl IpcServer {
+    /// Start serving `index` on the given address, keeping queries within
+    /// `limits`, answering only connections that present `token` when it is
+    /// set, and encrypting connections with `tls` when it is.
+    pub fn spawn(
+        addr: &str,
+        index: Arc<Index>,
+        limits: QueryLimits,
+        token: Option<String>,
+        tls: Option<ServerTls>,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+        let gate = QueryGate::new(limits).with_token(token);
+        gate.warn_if_exposed(local_addr);
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = Arc::clone(&stop);
+
+        let thread = thread::Builder::new()
+            .name("glint-ipc".to_string())
+            .spawn(move || accept_loop(listener, index, gate, tls, stop_flag))?;
+
+        info!(addr = %local_addr, "IPC server listening");
+
+        Ok(IpcServer {
+            local_addr,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// The address the server is bound to.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Stop the server and wait for the accept loop to exit.
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn accept_loop(
+    listener: TcpListener,
+    index: Arc<Index>,
+    gate: QueryGate,
+    tls: Option<ServerTls>,
+    stop: Arc<AtomicBool>,
+) {
+    while !stop.load(Ordering::Acquire) {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                debug!(peer = %peer, "IPC client connected");
+                let slot = match gate.connect() {
+                    Ok(slot) => slot,
+                    Err(refusal) => {
+                        warn!(peer = %peer, "Turning away an IPC client: too many connections");
+                        if tls.is_none() {
+                            let _ = refuse(stream, &refusal);
+                        }
+                        continue;
+                    }
+                };
+                let index = Arc::clone(&index);
+                let gate = gate.clone();
+                let tls = tls.clone();
+                let _ = thread::Builder::new()
+                    .name("glint-ipc-conn".to_string())
+                    .spawn(move || {
+                        let served = open_connection(stream, tls.as_ref())
+                            .and_then(|stream| serve_connection(stream, &index, &gate));
+                        if let Err(e) = served {
+                            debug!(peer = %peer, error = %e, "IPC connection closed");
+                        }
+                        drop(slot);
+                    });
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(50));
+            }
+            Err(e) => {
+                warn!(error = %e, "IPC accept failed");
+                thread::sleep(Duration::from_millis(200));
+            }
+        }
+    }
+}
+
+/// Answer a connection turned away before it is served with `refusal`.
+fn refuse(stream: TcpStream, refusal: &IpcResponse) -> Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_write_timeout(Some(Duration::from_secs(1)))?;
+    write_message(&mut IpcStream::Plain(stream), refusal)
+}
+
+/// Ready an accepted connection to be served, encrypting it with `tls`
+/// when set.
+fn open_connection(stream: TcpStream, tls: Option<&ServerTls>) -> Result<IpcStream> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(IDLE_TIMEOUT))?;
+    match tls {
+        Some(tls) => tls.accept(stream),
+        None => Ok(IpcStream::Plain(stream)),
+    }
+}
+
+fn serve_connection(stream: IpcStream, index: &Arc<Index>, gate: &QueryGate) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut authenticated = gate.is_open();
+    let mut line = String::new();
+
+    loop {
+        match read_request_line(&mut reader, &mut line) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                write_message(reader.get_mut(), &IpcResponse::error(e.to_string()))?;
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<IpcRequest>(&line) {
+            Ok(request) => gate
+                .admit(&request, &mut authenticated)
+                .unwrap_or_else(|| gate.handle(request, index)),
+            Err(e) => IpcResponse::error(format!("Malformed request: {}", e)),
+        };
+        write_message(reader.get_mut(), &response)?;
+    }
+
+    Ok(())
+}
+
+/// Read the next line of a connection into `line`, keeping the server from
+/// buffering more than [`MAX_REQUEST_BYTES`] of it. False at the end of
+/// the connection; an error of kind `InvalidData` when the line is longer.
+pub(crate) fn read_request_line(reader: &mut impl BufRead, line: &mut String) -> io::Result<bool> {
+    line.clear();
+    let read = reader.take(MAX_REQUEST_BYTES as u64).read_line(line)?;
+    if read == MAX_REQUEST_BYTES && !line.ends_with('\n') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Request longer than {} bytes", MAX_REQUEST_BYTES),
+        ));
+    }
+    Ok(read > 0)
+}
+
+/// Send one JSON-encoded message, and its newline.
+fn write_message(writer: &mut impl Write, message: &impl Serialize) -> Result<()> {
+    let mut out =
+        serde_json::to_vec(message).map_err(|e| GlintError::serialization(e.to_string()))?;
+    out.push(b'\n');
+    writer.write_all(&out)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Execute a single request against the index, without limits, searching
+/// a snapshot of its own.
+pub fn handle_request(request: IpcRequest, index: &Index) -> IpcResponse {
+    match request {
+        IpcRequest::Query(req) => {
+            run_query(&req, index, &index.snapshot(), &AtomicBool::new(false)).0
+        }
+        IpcRequest::Status => IpcResponse::Status {
+            stats: index.stats(),
+        },
+        IpcRequest::Auth { .. } => IpcResponse::Authenticated,
+    }
+}
+
+/// Search `snapshot` for the page of results `req` asks for, unless
+/// `cancel` is set first; volume names are looked up in `index`. Also
+/// tells whether more results follow the page.
+fn run_query(
+    req: &QueryRequest,
+    index: &Index,
+    snapshot: &IndexSnapshot,
+    cancel: &AtomicBool,
+) -> (IpcResponse, bool) {
+    let query = match req.to_search_query(index) {
+        Ok(query) => query,
+        Err(e) => return (IpcResponse::error(e.to_string()), false),
+    };
+    let start = Instant::now();
+    let Some(page) = snapshot.search_ranked_cancellable(&query, req.offset, req.limit, cancel)
+    else {
+        return (IpcResponse::error("Query cancelled"), false);
+    };
+    let more = page.total > req.offset + page.results.len();
+    let found = page.results;
+    let captures = if query.capture_groups() > 0 {
+        found.iter().map(|r| r.captures.clone()).collect()
+    } else {
+        Vec::new()
+    };
+    let response = IpcResponse::Results {
+        results: found.into_iter().map(|r| r.record).collect(),
+        captures,
+        elapsed_ms: start.elapsed().as_secs_f64() * 1000.0,
+    };
+    (response, more)
+}
+
+/// Client connection to an IPC server.
+pub struct IpcClient {
+    stream: BufReader<IpcStream>,
+}
+
+impl IpcClient {
+    /// Connect to a server, trying each resolved address in turn.
+    pub fn connect(addr: &str, timeout: Duration) -> Result<Self> {
+        let stream = connect_socket(addr, timeout)?;
+        Ok(IpcClient {
+            stream: BufReader::new(IpcStream::Plain(stream)),
+        })
+    }
+
+    /// Connect to a server that encrypts its connections, checking its
+    /// certificate with `tls`.
+    pub fn connect_tls(addr: &str, timeout: Duration, tls: &ClientTls) -> Result<Self> {
+        let stream = connect_socket(addr, timeout)?;
+        Ok(IpcClient {
+            stream: BufReader::new(tls.connect(addr, stream)?),
+        })
+    }
+
+    /// Wait at most `timeout` for each read and write, instead of the 30
+    /// seconds set on connecting.
+    pub fn set_timeout(&self, timeout: Duration) -> Result<()> {
+        let socket = self.stream.get_ref().socket();
+        socket.set_read_timeout(Some(timeout))?;
+        socket.set_write_timeout(Some(timeout))?;
+        Ok(())
+    }
+
+    /// Present the server's token, which it needs before any other request
+    /// when it has one.
+    pub fn authenticate(&mut self, token: &str) -> Result<()> {
+        let request = IpcRequest::Auth {
+            token: token.to_string(),
+        };
+        match self.request(&request)? {
+            IpcResponse::Authenticated => Ok(()),
+            IpcResponse::Error { message, .. } => Err(GlintError::Internal(message)),
+            other => Err(GlintError::Internal(format!(
+                "Unexpected IPC response: {:?}",
+                other
+            ))),
+        }
+    }
+
+    /// Send a request and wait for its response.
+    pub fn request(&mut self, request: &IpcRequest) -> Result<IpcResponse> {
+        write_message(self.stream.get_mut(), request)?;
+
+        let mut line = String::new();
+        if self.stream.read_line(&mut line)? == 0 {
+            return Err(GlintError::Internal(
+                "IPC server closed the connection".to_string(),
+            ));
+        }
+        serde_json::from_str(&line).map_err(|e| GlintError::serialization(e.to_string()))
+    }
+
+    /// Run a search on the server.
+    ///
+    /// Returned records have their lowercase caches initialized. Scores are
+    /// not sent, so results come back unscored.
+    pub fn query(&mut self, request: QueryRequest) -> Result<(Vec<SearchResult>, f64)> {
+        match self.request(&IpcRequest::Query(request))? {
+            IpcResponse::Results {
+                results,
+                mut captures,
+                elapsed_ms,
+            } => {
+                captures.resize(results.len(), Vec::new());
+                let results = results
+                    .into_iter()
+                    .zip(captures)
+                    .map(|(mut record, captures)| {
+                        record.init_cache();
+                        let mut result = SearchResult::new(record, 0);
+                        result.captures = captures;
+                        result
+                    })
+                    .collect();
+                Ok((results, elapsed_ms))
+            }
+            IpcResponse::Error { message, .. } => Err(GlintError::Internal(message)),
+            other => Err(GlintError::Internal(format!(
+                "Unexpected IPC response: {:?}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Open a TCP connection to `addr`, trying each resolved address in turn.
+fn connect_socket(addr: &str, timeout: Duration) -> Result<TcpStream> {
+    let mut last_err = None;
+    for sock_addr in addr.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&sock_addr, timeout) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+                return Ok(stream);
+            }
+            Err(e) => last_err = Some(e),
+        }
+    }
+    Err(last_err
+        .map(GlintError::from)
+        .unwrap_or_else(|| GlintError::Internal(format!("No address resolved for {}", addr))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{ChangeEvent, VolumeInfo};
+    use crate::types::{FileId, VolumeId};
+
+    fn make_index() -> Arc<Index> {
+        let index = Index::new();
+        let records = vec![
+            FileRecord::new(
+                FileId::new(1),
+                None,
+                VolumeId::new("C"),
+                "main.rs".to_string(),
+                "C:\\src\\main.rs".to_string(),
+                false,
+            ),
+            FileRecord::new(
+                FileId::new(2),
+                None,
+                VolumeId::new("C"),
+                "notes.txt".to_string(),
+                "C:\\notes.txt".to_string(),
+                false,
+            ),
+        ];
+        index.add_volume_records(&VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS"), records);
+        Arc::new(index)
+    }
+
+    #[test]
+    fn test_query_request_filters() {
+        let mut req = QueryRequest::new("main", 10);
+        req.extensions = vec!["txt".to_string()];
+        let response = handle_request(IpcRequest::Query(req), &make_index());
+        match response {
+            IpcResponse::Results { results, .. } => assert!(results.is_empty()),
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_query_request_within_volume_exclude() {
+        let index = make_index();
+        let search = |req: QueryRequest| match handle_request(IpcRequest::Query(req), &index) {
+            IpcResponse::Results { results, .. } => Ok(results.len()),
+            IpcResponse::Error { message, .. } => Err(message),
+            other => panic!("unexpected response: {:?}", other),
+        };
+
+        let mut req = QueryRequest::new("", 10);
+        req.volume = Some("c".to_string());
+        let all = search(req.clone()).unwrap();
+        assert!(all > 0);
+
+        req.exclude = vec!["*.txt".to_string()];
+        assert!(search(req.clone()).unwrap() < all);
+
+        req.within = vec!["C:\\nowhere".to_string()];
+        assert_eq!(search(req).unwrap(), 0);
+
+        let mut req = QueryRequest::new("", 1);
+        req.volume = Some("c".to_string());
+        req.offset = all - 1;
+        assert_eq!(search(req.clone()).unwrap(), 1);
+        req.offset = all;
+        assert_eq!(search(req).unwrap(), 0);
+
+        let mut req = QueryRequest::new("", 10);
+        req.volume = Some("Z:".to_string());
+        assert!(search(req).is_err());
+    }
+
+    #[test]
+    fn test_query_gate() {
+        let index = make_index();
+        let gate = QueryGate::new(QueryLimits {
+            max_results: 1,
+            timeout: Duration::from_millis(50),
+            max_concurrent: 1,
+            max_connections: 1,
+        });
+        match gate.handle(IpcRequest::Query(QueryRequest::new("", 10)), &index) {
+            IpcResponse::Results { results, .. } => assert_eq!(results.len(), 1),
+            other => panic!("unexpected response: {:?}", other),
+        }
+
+        // A query that overruns is answered, and holds its place until done
+        let (release, blocked) = crossbeam_channel::bounded::<()>(0);
+        let slow = gate.run(move |_| {
+            let _ = blocked.recv();
+            IpcResponse::error("finished")
+        });
+        let refused = |response: IpcResponse| match response {
+            IpcResponse::Error { refused, .. } => refused,
+            other => panic!("unexpected response: {:?}", other),
+        };
+        assert_eq!(refused(slow), Some(Refusal::TimedOut));
+        let busy = gate.handle(IpcRequest::Query(QueryRequest::new("", 10)), &index);
+        assert_eq!(refused(busy), Some(Refusal::Busy));
+        assert!(matches!(
+            gate.handle(IpcRequest::Status, &index),
+            IpcResponse::Status { .. }
+        ));
+
+        release.send(()).unwrap();
+        let start = Instant::now();
+        while gate.running.load(Ordering::Acquire) > 0 {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(5));
+        }
+        let response = gate.handle(IpcRequest::Query(QueryRequest::new("", 10)), &index);
+        assert!(matches!(response, IpcResponse::Results { .. }));
+    }
+
+    #[test]
+    fn test_timed_out_query_is_cancelled() {
+        let gate = QueryGate::new(QueryLimits {
+            timeout: Duration::from_millis(20),
+            ..QueryLimits::default()
+        });
+        let (stopped, was_stopped) = crossbeam_channel::bounded(1);
+        let response = gate.run(move |cancel| {
+            while !cancel.load(Ordering::Acquire) {
+                thread::sleep(Duration::from_millis(1));
+            }
+            let _ = stopped.send(());
+            IpcResponse::error("stopped")
+        });
+        assert!(matches!(
+            response,
+            IpcResponse::Error {
+                refused: Some(Refusal::TimedOut),
+                ..
+            }
+        ));
+        assert!(was_stopped.recv_timeout(Duration::from_secs(5)).is_ok());
+    }
+
+    #[test]
+    fn test_read_request_line() {
+        let mut line = String::new();
+        let fits = format!("{}\n", "a".repeat(MAX_REQUEST_BYTES - 1));
+        let mut reader = fits.as_bytes();
+        assert!(read_request_line(&mut reader, &mut line).unwrap());
+        assert_eq!(line, fits);
+        assert!(!read_request_line(&mut reader, &mut line).unwrap());
+
+        let long = "a".repeat(MAX_REQUEST_BYTES + 1);
+        let err = read_request_line(&mut long.as_bytes(), &mut line).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // The last line needs no newline
+        assert!(read_request_line(&mut "{}".as_bytes(), &mut line).unwrap());
+        assert_eq!(line, "{}");
+    }
+
+    #[test]
+    fn test_pages_share_a_snapshot() {
+        let index = make_index();
+        let gate = QueryGate::new(QueryLimits::default());
+        let page = |query: &str, offset: usize, limit: usize| {
+            let mut req = QueryRequest::new(query, limit);
+            req.offset = offset;
+            match gate.handle(IpcRequest::Query(req), &index) {
+                IpcResponse::Results { results, .. } => {
+                    results.into_iter().map(|r| r.path).collect::<Vec<_>>()
+                }
+                other => panic!("unexpected response: {:?}", other),
+            }
+        };
+        let all = page("", 0, 10);
+        assert_eq!(all.len(), 2);
+        assert_eq!(page("", 0, 1), all[..1]);
+
+        // The second page comes from the first page's snapshot
+        let second = index.find_by_path(&all[1]).unwrap();
+        index.apply_change(ChangeEvent::deleted(
+            VolumeId::new("C"),
+            second.id,
+            None,
+            second.name,
+            false,
+            1,
+        ));
+        index.apply_change(ChangeEvent::created(
+            VolumeId::new("C"),
+            FileId::new(3),
+            None,
+            "a".to_string(),
+            false,
+            2,
+        ));
+        assert_eq!(page("", 1, 1), all[1..]);
+        assert_eq!(page("", 1, 1), all[1..]);
+
+        // A first page, or another query, sees the index as it is now
+        assert_eq!(page("*", 1, 10).len(), 1);
+        let now = page("", 0, 10);
+        assert_eq!(now.len(), 2);
+        assert!(!now.contains(&all[1]));
+        assert_eq!(page("", 1, 10), now[1..]);
+    }
+
+    #[test]
+    fn test_pages_kept_only_when_paged() {
+        let index = make_index();
+        let gate = QueryGate::new(QueryLimits::default());
+        let query = |text: &str, offset: usize, limit: usize| {
+            let mut req = QueryRequest::new(text, limit);
+            req.offset = offset;
+            gate.handle(IpcRequest::Query(req), &index);
+        };
+
+        // Results that fit one page keep no snapshot
+        query("", 0, 10);
+        query("*.rs", 0, 10);
+        assert_eq!(gate.pages.len(), 0);
+
+        // Queries paged while the index is unchanged share one
+        query("", 0, 1);
+        query("*", 0, 1);
+        assert_eq!(gate.pages.len(), 1);
+
+        // A change starts another, up to the limit
+        for id in 0..MAX_PAGE_SNAPSHOTS as u64 + 1 {
+            index.apply_change(ChangeEvent::created(
+                VolumeId::new("C"),
+                FileId::new(10 + id),
+                None,
+                format!("new{}", id),
+                false,
+                10 + id as i64,
+            ));
+            query("", 0, 1);
+        }
+        assert_eq!(gate.pages.len(), MAX_PAGE_SNAPSHOTS);
+    }
+
+    #[test]
+    fn test_server_roundtrip() {
+        let mut server = IpcServer::spawn(
+            "127.0.0.1:0",
+            make_index(),
+            QueryLimits::default(),
+            None,
+            None,
+        )
+        .unwrap();
+        let addr = server.local_addr().to_string();
+
+        let mut client = IpcClient::connect(&addr, Duration::from_secs(2)).unwrap();
+        let (results, _) = client.query(QueryRequest::new("*.rs", 10)).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].record.name_lower, "main.rs");
+
+        let (results, _) = client
+            .query(QueryRequest::new(r"r/^(\w+)\.rs$/", 10))
+            .unwrap();
+        assert_eq!(results[0].captures, vec!["main"]);
+
+        match client.request(&IpcRequest::Status).unwrap() {
+            IpcResponse::Status { stats } => assert_eq!(stats.total_files, 2),
+            other => panic!("unexpected response: {:?}", other),
+        }
+
+        let err = client.query(QueryRequest::new("r/[/", 10));
+        assert!(err.is_err());
+
+        // A server without a token accepts any
+        client.authenticate("anything").unwrap();
+
+        server.stop();
+    }
+
+    #[test]
+    fn test_server_token() {
+        let token = Some("secret".to_string());
+        let mut server = IpcServer::spawn(
+            "127.0.0.1:0",
+            make_index(),
+            QueryLimits::default(),
+            token,
+            None,
+        )
+        .unwrap();
+        let addr = server.local_addr().to_string();
+
+        let mut client = IpcClient::connect(&addr, Duration::from_secs(2)).unwrap();
+        match client.request(&IpcRequest::Status).unwrap() {
+            IpcResponse::Error { refused, .. } => assert_eq!(refused, Some(Refusal::Unauthorized)),
+            other => panic!("unexpected response: {:?}", other),
+        }
+        assert!(client.authenticate("secreT").is_err());
+        assert!(client.query(QueryRequest::new("*.rs", 10)).is_err());
+
+        client.authenticate("secret").unwrap();
+        let (results, _) = client.query(QueryRequest::new("*.rs", 10)).unwrap();
+        assert_eq!(results.len(), 1);
+
+        // Each connection presents the token anew
+        let mut other = IpcClient::connect(&addr, Duration::from_secs(2)).unwrap();
+        assert!(other.query(QueryRequest::new("*.rs", 10)).is_err());
+
+        assert!(tokens_match(b"secret", b"secret"));
+        assert!(!tokens_match(b"secret", b"secrets"));
+        server.stop();
+    }
+
+    #[test]
+    fn test_server_limits_connections() {
+        let limits = QueryLimits {
+            max_connections: 1,
+            ..QueryLimits::default()
+        };
+        let mut server = IpcServer::spawn("127.0.0.1:0", make_index(), limits, None, None).unwrap();
+        let addr = server.local_addr().to_string();
+        let response = |stream: &TcpStream| {
+            let mut line = String::new();
+            BufReader::new(stream).read_line(&mut line).ok()?;
+            serde_json::from_str::<IpcResponse>(&line).ok()
+        };
+
+        let mut first = IpcClient::connect(&addr, Duration::from_secs(2)).unwrap();
+        first.query(QueryRequest::new("*.rs", 10)).unwrap();
+        let second = TcpStream::connect(&addr).unwrap();
+        match response(&second) {
+            Some(IpcResponse::Error { refused, .. }) => assert_eq!(refused, Some(Refusal::Busy)),
+            other => panic!("unexpected response: {:?}", other),
+        }
+
+        // Closing a connection frees its place
+        drop(first);
+        let start = Instant::now();
+        let mut stream = loop {
+            let mut stream = TcpStream::connect(&addr).unwrap();
+            let _ = stream.write_all(b"\"Status\"\n");
+            if let Some(IpcResponse::Status { .. }) = response(&stream) {
+                break stream;
+            }
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        };
+
+        // A request line too long is answered with an error and ends it
+        stream.write_all(&[b'a'; MAX_REQUEST_BYTES]).unwrap();
+        match response(&stream) {
+            Some(IpcResponse::Error { refused, .. }) => assert_eq!(refused, None),
+            other => panic!("unexpected response: {:?}", other),
+        }
+        assert_eq!(stream.read(&mut [0; 1]).unwrap(), 0);
+        server.stop();
+    }
+
+    #[cfg(feature = "tls")]
+    #[test]
+    fn test_server_tls() {
+        let dir = tempfile::tempdir().unwrap();
+        let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let (cert, key) = (dir.path().join("server.pem"), dir.path().join("server.key"));
+        std::fs::write(&cert, certified.cert.pem()).unwrap();
+        std::fs::write(&key, certified.key_pair.serialize_pem()).unwrap();
+
+        let tls = ServerTls::load(&cert, &key).unwrap();
+        let mut server = IpcServer::spawn(
+            "127.0.0.1:0",
+            make_index(),
+            QueryLimits::default(),
+            None,
+            Some(tls),
+        )
+        .unwrap();
+        let addr = format!("localhost:{}", server.local_addr().port());
+
+        let trusted = ClientTls::load(&cert).unwrap();
+        let mut client = IpcClient::connect_tls(&addr, Duration::from_secs(2), &trusted).unwrap();
+        let (results, _) = client.query(QueryRequest::new("*.rs", 10)).unwrap();
+        assert_eq!(results.len(), 1);
+
+        // Plain clients, and ones trusting another certificate, get nothing
+        let mut plain = IpcClient::connect(&addr, Duration::from_secs(2)).unwrap();
+        plain.set_timeout(Duration::from_secs(2)).unwrap();
+        assert!(plain.query(QueryRequest::new("*.rs", 10)).is_err());
+        let other = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let other_cert = dir.path().join("other.pem");
+        std::fs::write(&other_cert, other.cert.pem()).unwrap();
+        let untrusted = ClientTls::load(&other_cert).unwrap();
+        let mut client = IpcClient::connect_tls(&addr, Duration::from_secs(2), &untrusted).unwrap();
+        assert!(client.query(QueryRequest::new("*.rs", 10)).is_err());
+        server.stop();
+    }
+}
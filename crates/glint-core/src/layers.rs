@@ -0,0 +1,572 @@
+//! Machine-wide and per-user config files, layered.
+//!
+//! Administrators can roll settings out to every user of a machine in the
+//! machine config at [`machine_config_path`]: `%ProgramData%\glint\glint.toml`
+//! on Windows, `/etc/glint/glint.toml` elsewhere. Each user's own config is
+//! laid over it, so the precedence, lowest first, is:
+//!
+//! 1. built-in defaults
+//! 2. the machine config
+//! 3. the user config
+//!
+//! 4. `GLINT_*` environment variables
+//! 5. `--set key=value` on the command line
+//!
+//! Tables are merged key by key; any other value, lists included, is
+//! replaced whole. Keys the machine config names in its top-level `locked`
+//! list, such as `"exclude"` or `"server.listen"`, can't be changed by the
+//! layers above it: their values for them are ignored with a warning.
+//!
+//! An environment variable names a key with `__` between the table and the
+//! key, so `GLINT_PERFORMANCE__PARALLEL_SEARCH=false` sets
+//! `parallel_search` under `[performance]`. Values from the environment and
+//! `--set` are read as TOML (`false`, `500`, `["*.tmp"]`), or as a string if
+//! they aren't valid TOML, so paths and addresses need no quotes.
+//!
+//! ```toml
+//! # %ProgramData%\glint\glint.toml
+//! locked = ["exclude.paths", "server.enabled"]
+//!
+//! [exclude]
+//! paths = ["C:\\Windows\\WinSxS"]
+//!
+//! [server]
+//! enabled = false
+//! ```
+
+use crate::config::Config;
+use crate::error::{GlintError, Result};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+use tracing::{debug, warn};
+
+/// Top-level key of the machine config listing the keys users can't change.
+pub const LOCKED_KEY: &str = "locked";
+
+/// Prefix of the environment variables that set config keys.
+pub const ENV_PREFIX: &str = "GLINT_";
+
+/// Where the machine config is read from.
+pub fn machine_config_path() -> PathBuf {
+    if cfg!(windows) {
+        let program_data =
+            std::env::var_os("ProgramData").unwrap_or_else(|| r"C:\ProgramData".into());
+        PathBuf::from(program_data).join("glint").join("glint.toml")
+    } else {
+        PathBuf::from("/etc/glint/glint.toml")
+    }
+}
+
+/// Where a config value came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    /// Built in; neither file sets it
+    Default,
+    /// The machine config
+    Machine,
+    /// The machine config, which locks it
+    Locked,
+    /// The user config
+    User,
+    /// A `GLINT_*` environment variable
+    Environment,
+    /// `--set` on the command line
+    CommandLine,
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::Default => write!(f, "default"),
+            Origin::Machine => write!(f, "machine"),
+            Origin::Locked => write!(f, "machine (locked)"),
+            Origin::User => write!(f, "user"),
+            Origin::Environment => write!(f, "environment"),
+            Origin::CommandLine => write!(f, "--set"),
+        }
+    }
+}
+
+/// A config value set outside the config files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Override {
+    /// Dotted key, such as `performance.parallel_search`
+    pub key: String,
+
+    /// The value
+    pub value: Value,
+
+    /// [`Origin::Environment`] or [`Origin::CommandLine`]
+    pub origin: Origin,
+}
+
+impl Override {
+    /// Parse a `--set` argument, `key=value`.
+    pub fn parse(arg: &str) -> Result<Self> {
+        match arg.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => Ok(Override {
+                key: key.trim().to_string(),
+                value: parse_value(value.trim()),
+                origin: Origin::CommandLine,
+            }),
+            _ => Err(GlintError::ConfigError {
+                reason: format!("Expected key=value to set, got '{}'", arg),
+            }),
+        }
+    }
+
+    /// The overrides among `vars`, sorted by key.
+    ///
+    /// Every config key lies inside a table, so only variables with a `__`
+    /// are read and other `GLINT_` variables aren't taken for keys.
+    pub fn from_env(vars: impl IntoIterator<Item = (String, String)>) -> Vec<Self> {
+        let mut overrides: Vec<Override> = vars
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let key = name.strip_prefix(ENV_PREFIX)?;
+                key.contains("__").then(|| Override {
+                    key: key.to_ascii_lowercase().replace("__", "."),
+                    value: parse_value(&value),
+                    origin: Origin::Environment,
+                })
+            })
+            .collect();
+        overrides.sort_by(|a, b| a.key.cmp(&b.key));
+        overrides
+    }
+}
+
+/// The machine and user configs and the config they make together.
+#[derive(Debug, Clone)]
+pub struct ConfigLayers {
+    /// The effective config
+    pub config: Config,
+
+    /// The machine config read, if there was one
+    pub machine_path: Option<PathBuf>,
+
+    /// The user config read, if there was one
+    pub user_path: Option<PathBuf>,
+
+    /// Keys set above the machine config but ignored because it locks
+    /// them, and where they were set
+    pub ignored: Vec<(Origin, String)>,
+
+    machine: Table,
+    user: Table,
+    env: Table,
+    set: Table,
+    locked: Vec<String>,
+}
+
+impl ConfigLayers {
+    /// Lay the user config at `user_path` over the machine config at
+    /// `machine_path`, and `GLINT_*` environment variables and the `set`
+    /// arguments, `key=value`, over both. Either file may be missing.
+    pub fn load(machine_path: &Path, user_path: &Path, set: &[String]) -> Result<Self> {
+        let mut overrides = Override::from_env(std::env::vars());
+        for arg in set {
+            overrides.push(Override::parse(arg)?);
+        }
+        Self::load_with(machine_path, user_path, &overrides)
+    }
+
+    /// Lay the user config at `user_path` over the machine config at
+    /// `machine_path`, and `overrides` over both.
+    pub fn load_with(
+        machine_path: &Path,
+        user_path: &Path,
+        overrides: &[Override],
+    ) -> Result<Self> {
+        let mut machine = read_table(machine_path)?;
+        let mut user = read_table(user_path)?;
+        let machine_found = machine.is_some();
+        let user_found = user.is_some();
+
+        let locked = match machine.as_mut().and_then(|m| m.remove(LOCKED_KEY)) {
+            Some(Value::Array(keys)) => keys
+                .into_iter()
+                .filter_map(|key| key.as_str().map(str::to_string))
+                .collect(),
+            Some(_) => {
+                return Err(config_error(
+                    machine_path,
+                    "`locked` must be a list of keys",
+                ))
+            }
+            None => Vec::new(),
+        };
+        let (machine, mut user) = (machine.unwrap_or_default(), user.take().unwrap_or_default());
+        if user.remove(LOCKED_KEY).is_some() {
+            warn!(path = %user_path.display(), "Ignoring `locked` outside the machine config");
+        }
+
+        let sections = Table::try_from(Config::default())
+            .map_err(|e| GlintError::serialization(e.to_string()))?;
+        let (mut env, mut set) = (Table::new(), Table::new());
+        for item in overrides {
+            let section = item.key.split('.').next().unwrap_or_default();
+            if !sections.contains_key(section) {
+                let reason = format!("Unknown config key '{}'", item.key);
+                if item.origin == Origin::CommandLine {
+                    return Err(GlintError::ConfigError { reason });
+                }
+                warn!(key = %item.key, "Ignoring an environment variable for an unknown config key");
+                continue;
+            }
+            let layer = match item.origin {
+                Origin::CommandLine => &mut set,
+                _ => &mut env,
+            };
+            insert(layer, &item.key, item.value.clone());
+        }
+
+        let mut ignored = Vec::new();
+        for (origin, layer) in [
+            (Origin::User, &mut user),
+            (Origin::Environment, &mut env),
+            (Origin::CommandLine, &mut set),
+        ] {
+            for key in remove_locked(layer, &locked) {
+                warn!(key = %key, from = %origin, "Ignoring a setting the machine config locks");
+                ignored.push((origin, key));
+            }
+        }
+
+        let mut merged = machine.clone();
+        for layer in [&user, &env, &set] {
+            merge(&mut merged, layer.clone());
+        }
+        let config = Value::Table(merged)
+            .try_into()
+            .map_err(|e: toml::de::Error| GlintError::ConfigError {
+                reason: format!("Invalid configuration: {}", e),
+            })?;
+        debug!(
+            machine = machine_found,
+            user = user_found,
+            overrides = overrides.len(),
+            locked = locked.len(),
+            "Loaded layered configuration"
+        );
+
+        Ok(ConfigLayers {
+            config,
+            machine_path: machine_found.then(|| machine_path.to_path_buf()),
+            user_path: user_found.then(|| user_path.to_path_buf()),
+            ignored,
+            machine,
+            user,
+            env,
+            set,
+            locked,
+        })
+    }
+
+    /// Where the value at the dotted `key`, such as `server.listen`, came
+    /// from.
+    pub fn origin(&self, key: &str) -> Origin {
+        if lookup(&self.set, key).is_some() {
+            Origin::CommandLine
+        } else if lookup(&self.env, key).is_some() {
+            Origin::Environment
+        } else if lookup(&self.user, key).is_some() {
+            Origin::User
+        } else if lookup(&self.machine, key).is_some() {
+            if is_locked(&self.locked, key) {
+                Origin::Locked
+            } else {
+                Origin::Machine
+            }
+        } else {
+            Origin::Default
+        }
+    }
+
+    /// Every value of the effective config under its dotted key, sorted by
+    /// key. Lists are single values.
+    pub fn values(&self) -> Result<Vec<(String, Value)>> {
+        let table =
+            Table::try_from(&self.config).map_err(|e| GlintError::serialization(e.to_string()))?;
+        let mut values = Vec::new();
+        flatten("", &table, &mut values);
+        Ok(values)
+    }
+}
+
+/// Read a config file as a table; None if there is none.
+pub(crate) fn read_table(path: &Path) -> Result<Option<Table>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(path)?;
+    contents
+        .parse::<Table>()
+        .map(Some)
+        .map_err(|e| config_error(path, &e.to_string()))
+}
+
+/// Leave out of `user` the values the same as in `machine`, so a saved
+/// user config keeps following the machine config where they agree.
+pub(crate) fn strip_machine_values(user: &mut Table, machine: &Table) {
+    user.retain(|key, value| match (value, machine.get(key)) {
+        (Value::Table(user), Some(Value::Table(machine))) => {
+            strip_machine_values(user, machine);
+            !user.is_empty()
+        }
+        (value, Some(machine)) => value != machine,
+        (_, None) => true,
+    });
+}
+
+fn config_error(path: &Path, reason: &str) -> GlintError {
+    GlintError::ConfigError {
+        reason: format!("Failed to parse config {}: {}", path.display(), reason),
+    }
+}
+
+/// Lay `over` onto `base`, merging tables and replacing everything else.
+fn merge(base: &mut Table, over: Table) {
+    for (key, value) in over {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(over)) => merge(base, over),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Read an override's value as TOML, or as a string if it isn't valid TOML.
+fn parse_value(value: &str) -> Value {
+    format!("value = {}", value)
+        .parse::<Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(value.to_string()))
+}
+
+/// Set the value at the dotted `key`, adding the tables on the way.
+fn insert(table: &mut Table, key: &str, value: Value) {
+    match key.split_once('.') {
+        None => {
+            table.insert(key.to_string(), value);
+        }
+        Some((first, rest)) => {
+            let inner = table
+                .entry(first)
+                .or_insert_with(|| Value::Table(Table::new()));
+            if !inner.is_table() {
+                *inner = Value::Table(Table::new());
+            }
+            if let Value::Table(inner) = inner {
+                insert(inner, rest, value);
+            }
+        }
+    }
+}
+
+/// Remove the values of `table` at or inside locked keys, returning their
+/// keys. A lock on a whole table covers every key set inside it.
+fn remove_locked(table: &mut Table, locked: &[String]) -> Vec<String> {
+    let mut keys = Vec::new();
+    flatten("", table, &mut keys);
+    keys.into_iter()
+        .map(|(key, _)| key)
+        .filter(|key| is_locked(locked, key) && remove(table, key).is_some())
+        .collect()
+}
+
+/// Collect the non-table values of `table` under their dotted keys.
+fn flatten(prefix: &str, table: &Table, out: &mut Vec<(String, Value)>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Value::Table(inner) => flatten(&key, inner, out),
+            value => out.push((key, value.clone())),
+        }
+    }
+}
+
+fn lookup<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    let (first, rest) = key.split_once('.').unwrap_or((key, ""));
+    let value = table.get(first)?;
+    match (value, rest) {
+        (value, "") => Some(value),
+        (Value::Table(inner), rest) => lookup(inner, rest),
+        _ => None,
+    }
+}
+
+fn remove(table: &mut Table, key: &str) -> Option<Value> {
+    match key.split_once('.') {
+        None => table.remove(key),
+        Some((first, rest)) => match table.get_mut(first)? {
+            Value::Table(inner) => remove(inner, rest),
+            _ => None,
+        },
+    }
+}
+
+/// Whether `key` is, or lies inside, a locked key.
+fn is_locked(locked: &[String], key: &str) -> bool {
+    locked.iter().any(|lock| {
+        key == lock || (key.starts_with(lock.as_str()) && key[lock.len()..].starts_with('.'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_layers() {
+        let dir = TempDir::new().unwrap();
+        let machine = dir.path().join("machine.toml");
+        let user = dir.path().join("user.toml");
+        fs::write(
+            &machine,
+            r#"
+locked = ["exclude", "server.enabled"]
+
+[general]
+max_results = 500
+stale_after_hours = 48
+
+[exclude]
+paths = ['C:\Windows']
+
+[server]
+enabled = false
+listen = "0.0.0.0:7379"
+"#,
+        )
+        .unwrap();
+        fs::write(
+            &user,
+            r#"
+[general]
+max_results = 50
+
+[exclude]
+paths = []
+hidden = false
+
+[server]
+enabled = true
+"#,
+        )
+        .unwrap();
+
+        let layers = ConfigLayers::load_with(&machine, &user, &[]).unwrap();
+        let config = &layers.config;
+        assert_eq!(config.general.max_results, 50);
+        assert_eq!(config.general.stale_after_hours, 48);
+        assert_eq!(config.exclude.paths, vec![r"C:\Windows".to_string()]);
+        assert!(!config.server.enabled);
+        assert_eq!(config.server.listen, "0.0.0.0:7379");
+        let ignored: Vec<_> = layers.ignored.iter().map(|(_, key)| key.as_str()).collect();
+        assert_eq!(
+            ignored,
+            ["exclude.hidden", "exclude.paths", "server.enabled"]
+        );
+
+        assert_eq!(layers.origin("general.max_results"), Origin::User);
+        assert_eq!(layers.origin("general.stale_after_hours"), Origin::Machine);
+        assert_eq!(layers.origin("exclude.paths"), Origin::Locked);
+        assert_eq!(layers.origin("server.enabled"), Origin::Locked);
+        assert_eq!(layers.origin("ui.page_size"), Origin::Default);
+        let values = layers.values().unwrap();
+        assert!(values
+            .iter()
+            .any(|(key, value)| key == "server.listen" && value.as_str() == Some("0.0.0.0:7379")));
+
+        // Missing files leave the defaults
+        let none =
+            ConfigLayers::load_with(&dir.path().join("a"), &dir.path().join("b"), &[]).unwrap();
+        assert_eq!(none.config.general.max_results, 10000);
+        assert!(none.machine_path.is_none() && none.user_path.is_none());
+    }
+
+    #[test]
+    fn test_overrides() {
+        let env = Override::from_env([
+            (
+                "GLINT_PERFORMANCE__PARALLEL_SEARCH".to_string(),
+                "false".to_string(),
+            ),
+            ("GLINT_GENERAL__MAX_RESULTS".to_string(), "200".to_string()),
+            (
+                "GLINT_SERVER__LISTEN".to_string(),
+                "0.0.0.0:7000".to_string(),
+            ),
+            ("GLINT_BENCH_LARGE".to_string(), "1".to_string()),
+            ("PATH".to_string(), "/bin".to_string()),
+        ]);
+        let keys: Vec<_> = env.iter().map(|o| o.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                "general.max_results",
+                "performance.parallel_search",
+                "server.listen"
+            ]
+        );
+        assert_eq!(env[0].value, Value::Integer(200));
+        assert_eq!(env[2].value, Value::String("0.0.0.0:7000".into()));
+
+        let set = Override::parse("exclude.patterns = [\"*.tmp\"]").unwrap();
+        assert_eq!(set.key, "exclude.patterns");
+        assert!(set.value.is_array());
+        assert!(Override::parse("general.max_results").is_err());
+
+        let dir = TempDir::new().unwrap();
+        let machine = dir.path().join("machine.toml");
+        let user = dir.path().join("user.toml");
+        fs::write(&machine, "locked = [\"server.listen\"]\n").unwrap();
+        fs::write(&user, "[general]\nmax_results = 50\n").unwrap();
+
+        let mut overrides = env.clone();
+        overrides.push(Override::parse("general.max_results=300").unwrap());
+        overrides.push(set);
+        let layers = ConfigLayers::load_with(&machine, &user, &overrides).unwrap();
+        assert_eq!(layers.config.general.max_results, 300);
+        assert!(!layers.config.performance.parallel_search);
+        assert_eq!(layers.config.exclude.patterns, ["*.tmp"]);
+        assert_eq!(layers.config.server.listen, Config::default().server.listen);
+        assert_eq!(
+            layers.ignored,
+            [(Origin::Environment, "server.listen".to_string())]
+        );
+        assert_eq!(layers.origin("general.max_results"), Origin::CommandLine);
+        assert_eq!(
+            layers.origin("performance.parallel_search"),
+            Origin::Environment
+        );
+
+        let unknown = Override::parse("genral.max_results=1").unwrap();
+        assert!(ConfigLayers::load_with(&machine, &user, &[unknown]).is_err());
+        let wrong_type = Override::parse("general.max_results=many").unwrap();
+        assert!(ConfigLayers::load_with(&machine, &user, &[wrong_type]).is_err());
+    }
+
+    #[test]
+    fn test_strip_machine_values() {
+        let machine: Table = "[general]\nmax_results = 500\n[ui]\npage_size = 50\n"
+            .parse()
+            .unwrap();
+        let mut user: Table =
+            "[general]\nmax_results = 500\nlog_level = \"debug\"\n[ui]\npage_size = 50\n"
+                .parse()
+                .unwrap();
+        strip_machine_values(&mut user, &machine);
+        assert_eq!(user.to_string(), "[general]\nlog_level = \"debug\"\n");
+    }
+}
@@ -10,42 +10,110 @@
 //! - **Types** (`types`): Core data types for file records and volume info
 //! - **Index** (`index`): In-memory index with fast search capabilities
 //! - **Search** (`search`): Query parsing and matching logic
-//! - **Persistence** (`persistence`): On-disk storage of the index
+//! - **Clock** (`clock`): The current time and where days start, fixable in tests
+//! - **Extensions** (`extensions`): Interned file extensions for fast extension filters
+//! - **Statistics** (`stats`): File counts and sizes by extension, folder, volume and age
+//! - **Persistence** (`persistence`, `persistence` feature): On-disk storage of the index
+//! - **Manifests** (`manifest`): How an index was built, saved next to it
 //! - **Config** (`config`): Configuration management
+//! - **Config layers** (`layers`): Machine-wide config under the user's, with locked keys
+//! - **Exclusions** (`exclude`, `churn`): Excluded folders, built-in presets and busy-folder detection
+//! - **Reports** (`report`, `schedule`): Saved queries written to CSV/JSON on a cron schedule
+//! - **Mock backend** (`mock`, `test-util` feature): Scriptable backend for tests
+//! - **Recently deleted** (`deleted`): Files removed from the index, for the `deleted:` token
+//! - **Pins** (`pins`): Pinned files and folders, kept across renames
+//! - **Rename** (`rename`): Template-based bulk renaming with undo
+//! - **Cleanup** (`cleanup`): Moving or removing the files a query matches
+//! - **Federation** (`federation`, `persistence` feature): Searching attached indexes alongside the local one
+//! - **Scopes** (`scope`): Named sets of folders to search in
+//! - **IPC** (`ipc`): Line-delimited JSON protocol for querying a running instance
+//! - **TLS** (`tls`): Certificates for encrypted IPC connections (`tls` feature)
+//! - **Explorer search** (`opensearch`): OpenSearch endpoint for Explorer's search box
+//! - **Tasks** (`tasks`, `tokio` feature): The watch pipeline as cancellable tokio tasks
+//! - **Path formats** (`path_format`): Native/UNC/URI/WSL path conversion
+//! - **Prelude** (`prelude`): The stable core of the API, for one glob import
+//!
+//! ## Features
+//!
+//! - `persistence` (default): index files, their memory-mapped views
+//!   (`archive_view`, `archive_scan`) and federation. Without it the index
+//!   lives in memory only and rkyv, bincode, LZ4 and memmap2 aren't built.
+//! - `parallel` (default): searches and aggregates run on all cores with
+//!   Rayon; without it they run on the calling thread.
+//! - `tokio`: the `tasks` module.
+//! - `tls`: IPC connections encrypted with rustls. Without it the `tls`
+//!   module is still there, but loading a certificate fails.
+//! - `test-util`: the `mock` module.
 //!
 //! ## Example
 //!
-//! ```rust,ignore
-//! use glint_core::{Index, SearchQuery, FileSystemBackend};
+//! ```rust
+//! use glint_core::prelude::*;
 //!
 //! // Create or load an index
-//! let mut index = Index::new();
+//! let index = Index::new();
 //!
 //! // Perform a search
 //! let query = SearchQuery::substring("myfile");
-//! for result in index.search(&query).take(100) {
-//!     println!("{}", result.path);
+//! for result in index.search_limited(&query, 100) {
+//!     println!("{}", result.record.path);
 //! }
 //! ```
 
 pub mod backend;
+pub mod churn;
+pub mod cleanup;
+pub mod clock;
 pub mod config;
-pub mod error;
+pub(crate) mod deleted;
+pub(crate) mod error;
+pub mod exclude;
+pub mod extensions;
+#[cfg(feature = "persistence")]
+pub mod federation;
 pub mod index;
-pub mod persistence;
+pub mod ipc;
+pub mod layers;
+pub mod manifest;
+pub mod opensearch;
+#[cfg(any(test, feature = "test-util"))]
+pub mod mock;
+mod par;
+pub(crate) mod path_format;
+#[cfg(feature = "persistence")]
+pub(crate) mod persistence;
+pub mod prelude;
+pub mod pins;
+mod records;
+pub mod rename;
+pub mod report;
+pub mod schedule;
+pub(crate) mod scope;
 pub mod search;
+pub mod stats;
+#[cfg(feature = "tokio")]
+pub mod tasks;
+pub mod tls;
 pub mod types;
+#[cfg(feature = "persistence")]
 pub mod archive_view;
+#[cfg(feature = "persistence")]
+pub mod archive_scan;
 
 // Re-export commonly used types
 pub use backend::{ChangeEvent, ChangeHandler, ChangeKind, FileSystemBackend, VolumeInfo};
 pub use config::Config;
+pub use deleted::{DeletedRecord, RecentlyDeleted, MAX_RECENTLY_DELETED};
 pub use error::{GlintError, Result};
-pub use index::Index;
-pub use persistence::IndexStore;
-pub use search::{SearchFilter, SearchQuery, SearchResult};
+pub use index::{Index, IndexChange, IndexSnapshot, LargestKind, VolumeAvailability};
+pub use path_format::PathFormat;
+#[cfg(feature = "persistence")]
+pub use persistence::{IndexStamp, IndexStore, LoadProgress, SaveProgress, VolumeLoad};
+pub use scope::Scopes;
+pub use search::{ResultTotals, SearchFilter, SearchPage, SearchQuery, SearchResult};
 pub use types::{FileId, FileRecord, VolumeId};
 
 // Expose archive module internally
+#[cfg(feature = "persistence")]
 #[allow(dead_code)]
 mod archive;
@@ -0,0 +1,181 @@
+//! Index build manifests.
+//!
+//! When an index is built, a manifest describing the build is saved next to
+//! it: which volumes were scanned and how, how many records each produced,
+//! the exclusions in effect, how long it took, the Glint version and the
+//! journal position each volume was caught up to. It is shown by
+//! `glint status --details` and included in JSON reports, so an index can be
+//! reasoned about without access to the machine that built it.
+
+use crate::backend::JournalState;
+use crate::config::Config;
+use crate::error::{GlintError, Result};
+use crate::index::Index;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tracing::warn;
+
+/// Scan mode recorded when the backend didn't report one.
+pub const UNKNOWN_SCAN_MODE: &str = "unknown";
+
+/// How an index was built.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuildManifest {
+    /// Version of Glint that built the index
+    pub tool_version: String,
+
+    /// When the build finished
+    pub built_at: DateTime<Utc>,
+
+    /// How long the build took, in seconds
+    pub duration_secs: f64,
+
+    /// The volumes (and folder volumes) indexed
+    pub volumes: Vec<ManifestVolume>,
+
+    /// Exclusions from the config at build time
+    pub exclusions: ManifestExclusions,
+}
+
+/// One indexed volume in a [`BuildManifest`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestVolume {
+    /// Mount point, or folder for folder volumes
+    pub name: String,
+
+    /// Volume ID in the index
+    pub id: String,
+
+    /// How the backend read the volume (e.g. "mft" or "recursive")
+    pub scan_mode: String,
+
+    /// Records indexed from the volume
+    pub records: u64,
+
+    /// Journal position the index was current to
+    #[serde(default)]
+    pub journal: Option<JournalState>,
+}
+
+/// The `[exclude]` and `[volumes]` settings that left things out of a build.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ManifestExclusions {
+    #[serde(default)]
+    pub paths: Vec<String>,
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    #[serde(default)]
+    pub volumes: Vec<String>,
+    #[serde(default)]
+    pub presets: Vec<String>,
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(default)]
+    pub system: bool,
+}
+
+impl BuildManifest {
+    /// Describe a just-built index.
+    ///
+    /// Scan modes start out unknown; set them with [`BuildManifest::set_scan_mode`].
+    pub fn new(index: &Index, config: &Config, duration: Duration) -> Self {
+        let mut volumes: Vec<ManifestVolume> = index
+            .volume_states()
+            .into_iter()
+            .map(|state| ManifestVolume {
+                name: state.info.display_name().to_string(),
+                id: state.info.id.as_str().to_string(),
+                scan_mode: UNKNOWN_SCAN_MODE.to_string(),
+                records: state.record_count,
+                journal: state.journal_state,
+            })
+            .collect();
+        volumes.sort_by(|a, b| a.name.cmp(&b.name));
+        BuildManifest {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            built_at: Utc::now(),
+            duration_secs: duration.as_secs_f64(),
+            volumes,
+            exclusions: ManifestExclusions {
+                paths: config.exclude.paths.clone(),
+                patterns: config.exclude.patterns.clone(),
+                volumes: config.volumes.exclude.clone(),
+                presets: config.exclude.presets.clone(),
+                hidden: config.exclude.hidden,
+                system: config.exclude.system,
+            },
+        }
+    }
+
+    /// Record how the volume named `name` was scanned.
+    pub fn set_scan_mode(&mut self, name: &str, mode: &str) {
+        for volume in self.volumes.iter_mut().filter(|v| v.name == name) {
+            volume.scan_mode = mode.to_string();
+        }
+    }
+
+    /// Write the manifest as JSON.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| GlintError::serialization(e.to_string()))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Read a manifest; None if there is none or it can't be read.
+    pub fn load(path: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&contents)
+            .map_err(|e| warn!(path = %path.display(), error = %e, "Ignoring unreadable manifest"))
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::VolumeInfo;
+    use crate::types::{FileId, FileRecord, VolumeId};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let index = Index::new();
+        let mut volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        volume.journal_state = Some(JournalState::new(7, 42));
+        let record = FileRecord::new(
+            FileId::new(10),
+            None,
+            volume.id.clone(),
+            "a.txt".to_string(),
+            "C:\\a.txt".to_string(),
+            false,
+        );
+        index.add_volume_records(&volume, vec![record]);
+
+        let mut config = Config::default();
+        config.exclude.patterns = vec!["*.tmp".to_string()];
+        let mut manifest = BuildManifest::new(&index, &config, Duration::from_secs(3));
+        assert_eq!(manifest.volumes[0].scan_mode, UNKNOWN_SCAN_MODE);
+        manifest.set_scan_mode("C:", "mft");
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("glint.manifest.json");
+        manifest.save(&path).unwrap();
+        let loaded = BuildManifest::load(&path).unwrap();
+        assert_eq!(loaded, manifest);
+        assert_eq!(loaded.volumes[0].records, 1);
+        assert_eq!(loaded.volumes[0].scan_mode, "mft");
+        assert_eq!(loaded.volumes[0].journal.as_ref().unwrap().last_usn, 42);
+        assert_eq!(loaded.exclusions.patterns, ["*.tmp"]);
+
+        fs::write(&path, "not json").unwrap();
+        assert!(BuildManifest::load(&path).is_none());
+    }
+}
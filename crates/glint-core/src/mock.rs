@@ -0,0 +1,661 @@
+//! Scriptable in-memory backend for tests.
+//!
+//! `MockBackend` implements [`FileSystemBackend`] without touching any
+//! filesystem. Each [`MockVolume`] has a set of records returned by
+//! `full_scan` and a script of change events replayed by `watch_changes`
+//! (or all at once, without delays, by `catch_up`), which makes watch orchestration, rescan handling and IPC testable on any
+//! platform.
+//!
+//! Enable the `test-util` feature to use it from other crates.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use glint_core::mock::{MockBackend, MockVolume};
+//! use std::time::Duration;
+//!
+//! let backend = MockBackend::new().with_volume(
+//!     MockVolume::new("C")
+//!         .with_dir(5, None, "src")
+//!         .with_file(10, Some(5), "main.rs", 120)
+//!         .then_create(11, Some(5), "lib.rs", false)
+//!         .then_delay(Duration::from_millis(10))
+//!         .then_journal_reset("journal truncated"),
+//! );
+//! ```
+//!
+//! Change events carry increasing sequence numbers. When `watch_changes` is
+//! given a volume whose `journal_state` has a `last_usn`, events up to and
+//! including that sequence are skipped, just like resuming a USN journal.
+
+use crate::backend::{
+    BackendCapabilities, ChangeEvent, ChangeHandler, FileSystemBackend, JournalState, ScanProgress,
+    VolumeInfo, WatchHandle,
+};
+use crate::types::{FileId, FileRecord, VolumeId};
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// One step of a volume's change script.
+#[derive(Debug, Clone)]
+pub enum ScriptStep {
+    /// Deliver a change event
+    Change(ChangeEvent),
+    /// Wait before the next step (interrupted by stopping the watcher)
+    Delay(Duration),
+    /// Report that the journal was reset and a rescan is required
+    JournalReset(String),
+    /// Report a monitoring error
+    Error(String),
+}
+
+/// A scripted volume.
+#[derive(Debug, Clone)]
+pub struct MockVolume {
+    info: VolumeInfo,
+    records: Vec<FileRecord>,
+    script: Vec<ScriptStep>,
+    journal_id: u64,
+    next_usn: i64,
+    scan_error: Option<String>,
+    watch_error: Option<String>,
+}
+
+impl MockVolume {
+    /// Create an empty volume with the given ID (e.g. "C"), mounted at `<id>:`.
+    pub fn new(id: &str) -> Self {
+        let info = VolumeInfo::new(VolumeId::new(id), format!("{}:", id), "MOCK")
+            .with_change_journal_support(true);
+        MockVolume {
+            info,
+            records: Vec::new(),
+            script: Vec::new(),
+            journal_id: 1,
+            next_usn: 1,
+            scan_error: None,
+            watch_error: None,
+        }
+    }
+
+    /// Volume information as returned by `list_volumes`.
+    pub fn info(&self) -> &VolumeInfo {
+        &self.info
+    }
+
+    /// Set the label reported for this volume.
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.info = self.info.with_label(label);
+        self
+    }
+
+    /// Set the journal ID; a watcher resuming with a different ID gets a reset.
+    pub fn with_journal_id(mut self, journal_id: u64) -> Self {
+        self.journal_id = journal_id;
+        self
+    }
+
+    /// Make `full_scan` fail with the given message.
+    pub fn with_scan_error(mut self, error: &str) -> Self {
+        self.scan_error = Some(error.to_string());
+        self
+    }
+
+    /// Make `watch_changes` fail with the given message.
+    pub fn with_watch_error(mut self, error: &str) -> Self {
+        self.watch_error = Some(error.to_string());
+        self
+    }
+
+    /// Add a pre-built record.
+    pub fn with_record(mut self, record: FileRecord) -> Self {
+        self.records.push(record);
+        self
+    }
+
+    /// Add a directory; its path is built from already-added parents.
+    pub fn with_dir(self, id: u64, parent: Option<u64>, name: &str) -> Self {
+        let record = self.make_record(id, parent, name, true);
+        self.with_record(record)
+    }
+
+    /// Add a file with a size.
+    pub fn with_file(self, id: u64, parent: Option<u64>, name: &str, size: u64) -> Self {
+        let record = self.make_record(id, parent, name, false).with_size(size);
+        self.with_record(record)
+    }
+
+    fn make_record(&self, id: u64, parent: Option<u64>, name: &str, is_dir: bool) -> FileRecord {
+        let parent_path = parent
+            .and_then(|pid| self.records.iter().find(|r| r.id.as_u64() == pid))
+            .map(|r| r.path.clone())
+            .unwrap_or_else(|| format!("{}:", self.info.id.as_str()));
+
+        FileRecord::new(
+            FileId::new(id),
+            parent.map(FileId::new),
+            self.info.id.clone(),
+            name.to_string(),
+            format!("{}\\{}", parent_path, name),
+            is_dir,
+        )
+    }
+
+    fn next_sequence(&mut self) -> i64 {
+        let usn = self.next_usn;
+        self.next_usn += 1;
+        usn
+    }
+
+    /// Append a step to the script.
+    pub fn then(mut self, step: ScriptStep) -> Self {
+        self.script.push(step);
+        self
+    }
+
+    /// Append a change event, assigning it the next sequence number.
+    pub fn then_change(mut self, mut event: ChangeEvent) -> Self {
+        event.sequence = self.next_sequence();
+        self.then(ScriptStep::Change(event))
+    }
+
+    /// Append a create event.
+    pub fn then_create(self, id: u64, parent: Option<u64>, name: &str, is_dir: bool) -> Self {
+        let event = ChangeEvent::created(
+            self.info.id.clone(),
+            FileId::new(id),
+            parent.map(FileId::new),
+            name.to_string(),
+            is_dir,
+            0,
+        );
+        self.then_change(event)
+    }
+
+    /// Append a delete event.
+    pub fn then_delete(self, id: u64, parent: Option<u64>, name: &str, is_dir: bool) -> Self {
+        let event = ChangeEvent::deleted(
+            self.info.id.clone(),
+            FileId::new(id),
+            parent.map(FileId::new),
+            name.to_string(),
+            is_dir,
+            0,
+        );
+        self.then_change(event)
+    }
+
+    /// Append a rename event within the same directory.
+    pub fn then_rename(self, id: u64, parent: Option<u64>, old_name: &str, new_name: &str) -> Self {
+        let event = ChangeEvent::renamed(
+            self.info.id.clone(),
+            FileId::new(id),
+            parent.map(FileId::new),
+            old_name.to_string(),
+            new_name.to_string(),
+            None,
+            false,
+            0,
+        );
+        self.then_change(event)
+    }
+
+    /// Append a delay.
+    pub fn then_delay(self, delay: Duration) -> Self {
+        self.then(ScriptStep::Delay(delay))
+    }
+
+    /// Append a journal reset.
+    pub fn then_journal_reset(self, reason: &str) -> Self {
+        self.then(ScriptStep::JournalReset(reason.to_string()))
+    }
+
+    /// Append a monitoring error.
+    pub fn then_error(self, error: &str) -> Self {
+        self.then(ScriptStep::Error(error.to_string()))
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    /// Records returned by the next `full_scan`, by volume ID
+    records: HashMap<String, Vec<FileRecord>>,
+    /// Current journal position, by volume ID
+    journal: HashMap<String, JournalState>,
+    /// Number of full scans performed, by volume ID
+    scans: HashMap<String, usize>,
+    /// Volumes left out of `list_volumes`, as if unplugged
+    unmounted: HashSet<String>,
+}
+
+/// A [`FileSystemBackend`] that replays scripted volumes.
+#[derive(Clone, Default)]
+pub struct MockBackend {
+    volumes: Vec<MockVolume>,
+    state: Arc<Mutex<MockState>>,
+    /// What the backend claims to do (None = everything)
+    capabilities: Option<BackendCapabilities>,
+}
+
+impl MockBackend {
+    /// Create a backend with no volumes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a volume.
+    pub fn with_volume(mut self, volume: MockVolume) -> Self {
+        {
+            let mut state = self.state.lock();
+            let key = volume.info.id.as_str().to_string();
+            state.records.insert(key.clone(), volume.records.clone());
+            state
+                .journal
+                .insert(key, JournalState::new(volume.journal_id, 0));
+        }
+        self.volumes.push(volume);
+        self
+    }
+
+    /// Claim `capabilities` instead of everything.
+    pub fn with_capabilities(mut self, capabilities: BackendCapabilities) -> Self {
+        self.capabilities = Some(capabilities);
+        self
+    }
+
+    /// Replace the records the next `full_scan` of a volume returns.
+    ///
+    /// Use this to simulate the on-disk state after scripted changes, so a
+    /// rescan triggered by a journal reset sees the new files.
+    pub fn set_records(&self, volume_id: &str, records: Vec<FileRecord>) {
+        self.state
+            .lock()
+            .records
+            .insert(volume_id.to_string(), records);
+    }
+
+    /// Unplug a volume, leaving it out of `list_volumes`, or plug it back
+    /// in.
+    pub fn set_mounted(&self, volume_id: &str, mounted: bool) {
+        let mut state = self.state.lock();
+        if mounted {
+            state.unmounted.remove(volume_id);
+        } else {
+            state.unmounted.insert(volume_id.to_string());
+        }
+    }
+
+    /// Number of full scans performed on a volume.
+    pub fn scan_count(&self, volume_id: &str) -> usize {
+        self.state.lock().scans.get(volume_id).copied().unwrap_or(0)
+    }
+
+    fn volume(&self, id: &VolumeId) -> anyhow::Result<&MockVolume> {
+        self.volumes
+            .iter()
+            .find(|v| &v.info.id == id)
+            .ok_or_else(|| anyhow::anyhow!("volume not found: {}", id))
+    }
+}
+
+impl FileSystemBackend for MockBackend {
+    fn list_volumes(&self) -> anyhow::Result<Vec<VolumeInfo>> {
+        let state = self.state.lock();
+        Ok(self
+            .volumes
+            .iter()
+            .filter(|v| !state.unmounted.contains(v.info.id.as_str()))
+            .map(|v| v.info.clone())
+            .collect())
+    }
+
+    fn full_scan(
+        &self,
+        volume: &VolumeInfo,
+        progress: Option<Arc<dyn ScanProgress>>,
+    ) -> anyhow::Result<Vec<FileRecord>> {
+        let mock = self.volume(&volume.id)?;
+        if let Some(error) = &mock.scan_error {
+            anyhow::bail!("{}", error);
+        }
+
+        let records = {
+            let mut state = self.state.lock();
+            *state
+                .scans
+                .entry(volume.id.as_str().to_string())
+                .or_default() += 1;
+            state
+                .records
+                .get(volume.id.as_str())
+                .cloned()
+                .unwrap_or_default()
+        };
+
+        if let Some(progress) = progress {
+            let dirs = records.iter().filter(|r| r.is_dir).count() as u64;
+            let files = records.len() as u64 - dirs;
+            progress.on_progress(files, dirs);
+            progress.on_complete(files, dirs);
+        }
+
+        Ok(records)
+    }
+
+    fn watch_changes(
+        &self,
+        volume: VolumeInfo,
+        handler: Arc<dyn ChangeHandler>,
+    ) -> anyhow::Result<WatchHandle> {
+        let mock = self.volume(&volume.id)?;
+        if let Some(error) = &mock.watch_error {
+            anyhow::bail!("{}", error);
+        }
+
+        let script = mock.script.clone();
+        let journal_id = mock.journal_id;
+        let state = Arc::clone(&self.state);
+        let volume_id = volume.id.clone();
+
+        // Resume like a USN reader: skip events already seen, reset on ID change
+        let mut resume_after = 0;
+        let mut id_changed = false;
+        if let Some(js) = &volume.journal_state {
+            if js.journal_id == journal_id {
+                resume_after = js.last_usn;
+            } else {
+                id_changed = true;
+            }
+        }
+
+        let (shutdown_tx, shutdown_rx) = crossbeam_channel::bounded::<()>(1);
+
+        let thread = thread::Builder::new()
+            .name(format!("mock-watch-{}", volume_id))
+            .spawn(move || {
+                if id_changed {
+                    handler.on_journal_reset(volume_id.clone(), "journal ID changed".to_string());
+                }
+
+                for step in script {
+                    if shutdown_rx.try_recv().is_ok() {
+                        return;
+                    }
+
+                    match step {
+                        ScriptStep::Change(event) => {
+                            if event.sequence <= resume_after {
+                                continue;
+                            }
+                            let usn = event.sequence;
+                            handler.on_change(event);
+                            state.lock().journal.insert(
+                                volume_id.as_str().to_string(),
+                                JournalState::new(journal_id, usn),
+                            );
+                        }
+                        ScriptStep::Delay(delay) => {
+                            if shutdown_rx.recv_timeout(delay).is_ok() {
+                                return;
+                            }
+                        }
+                        ScriptStep::JournalReset(reason) => {
+                            handler.on_journal_reset(volume_id.clone(), reason);
+                        }
+                        ScriptStep::Error(error) => {
+                            handler.on_error(volume_id.clone(), error);
+                        }
+                    }
+                }
+            })?;
+
+        Ok(WatchHandle::new(thread, shutdown_tx))
+    }
+
+    fn catch_up(
+        &self,
+        volume: &VolumeInfo,
+        handler: &dyn ChangeHandler,
+    ) -> anyhow::Result<Option<JournalState>> {
+        let mock = self.volume(&volume.id)?;
+        if let Some(error) = &mock.watch_error {
+            anyhow::bail!("{}", error);
+        }
+        let Some(js) = &volume.journal_state else {
+            return Ok(None);
+        };
+
+        // The whole script counts as already recorded; delays are skipped
+        let last_usn = mock
+            .script
+            .iter()
+            .filter_map(|step| match step {
+                ScriptStep::Change(event) => Some(event.sequence),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        if js.journal_id != mock.journal_id {
+            handler.on_journal_reset(volume.id.clone(), "journal ID changed".to_string());
+            return Ok(Some(JournalState::new(mock.journal_id, last_usn)));
+        }
+
+        for step in &mock.script {
+            match step {
+                ScriptStep::Change(event) if event.sequence > js.last_usn => {
+                    handler.on_change(event.clone());
+                }
+                ScriptStep::Change(_) | ScriptStep::Delay(_) => {}
+                ScriptStep::JournalReset(reason) => {
+                    handler.on_journal_reset(volume.id.clone(), reason.clone());
+                }
+                ScriptStep::Error(error) => handler.on_error(volume.id.clone(), error.clone()),
+            }
+        }
+        let state = JournalState::new(mock.journal_id, last_usn.max(js.last_usn));
+        self.state
+            .lock()
+            .journal
+            .insert(volume.id.as_str().to_string(), state.clone());
+        Ok(Some(state))
+    }
+
+    fn get_journal_state(&self, volume: &VolumeInfo) -> anyhow::Result<Option<JournalState>> {
+        Ok(self.state.lock().journal.get(volume.id.as_str()).cloned())
+    }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        self.capabilities.unwrap_or_else(BackendCapabilities::all)
+    }
+
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{ChangeHandlerMessage, ChannelChangeHandler};
+    use crate::index::Index;
+    use crate::search::SearchQuery;
+
+    fn volume() -> MockVolume {
+        MockVolume::new("C")
+            .with_dir(5, None, "src")
+            .with_file(10, Some(5), "main.rs", 120)
+            .then_create(11, Some(5), "lib.rs", false)
+            .then_rename(10, Some(5), "main.rs", "app.rs")
+            .then_delete(11, Some(5), "lib.rs", false)
+    }
+
+    fn drain(backend: &MockBackend, vol: VolumeInfo) -> Vec<ChangeHandlerMessage> {
+        let (handler, rx) = ChannelChangeHandler::new();
+        let _handle = backend.watch_changes(vol, Arc::new(handler)).unwrap();
+        // The script thread drops the handler when done, closing the channel
+        rx.iter().collect()
+    }
+
+    #[test]
+    fn test_scan_builds_paths() {
+        let backend = MockBackend::new().with_volume(volume());
+        let vol = backend.list_volumes().unwrap().remove(0);
+        let records = backend.full_scan(&vol, None).unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].path, "C:\\src\\main.rs");
+        assert_eq!(records[1].size, Some(120));
+        assert_eq!(backend.scan_count("C"), 1);
+    }
+
+    #[test]
+    fn test_scan_batches() {
+        use crate::backend::{FIRST_SCAN_BATCH, MAX_SCAN_BATCH};
+
+        let backend = MockBackend::new().with_volume(volume());
+        let vol = backend.list_volumes().unwrap().remove(0);
+        let total = FIRST_SCAN_BATCH * 200;
+        let records = (0..total)
+            .map(|i| {
+                FileRecord::new(
+                    FileId::new(i as u64 + 100),
+                    Some(FileId::new(5)),
+                    vol.id.clone(),
+                    format!("{}.txt", i),
+                    format!("C:\\{}.txt", i),
+                    false,
+                )
+            })
+            .collect();
+        backend.set_records("C", records);
+
+        let index = Index::new();
+        index.add_volume_records(&vol, Vec::new());
+        let mut sizes = Vec::new();
+        backend
+            .scan_batches(&vol, None, &mut |batch| {
+                sizes.push(batch.len());
+                index.append_volume_records(&vol, batch);
+            })
+            .unwrap();
+
+        // Batches double from the first size up to the largest
+        assert_eq!(sizes[0], FIRST_SCAN_BATCH);
+        assert_eq!(sizes[1], FIRST_SCAN_BATCH * 2);
+        assert!(sizes.iter().all(|&n| n <= MAX_SCAN_BATCH));
+        assert!(sizes.contains(&MAX_SCAN_BATCH));
+        assert_eq!(sizes.iter().sum::<usize>(), total);
+        assert_eq!(index.len(), total);
+    }
+
+    #[test]
+    fn test_replay_into_index() {
+        let backend = MockBackend::new().with_volume(volume());
+        let vol = backend.list_volumes().unwrap().remove(0);
+
+        let index = Index::new();
+        index.add_volume_records(&vol, backend.full_scan(&vol, None).unwrap());
+
+        for message in drain(&backend, vol.clone()) {
+            if let ChangeHandlerMessage::Change(event) = message {
+                index.apply_change(event);
+            }
+        }
+
+        let results = index.search(&SearchQuery::substring("app.rs"));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].record.path, "C:\\src\\app.rs");
+        assert!(index.search(&SearchQuery::substring("lib.rs")).is_empty());
+
+        let state = backend.get_journal_state(&vol).unwrap().unwrap();
+        assert_eq!(state.last_usn, 3);
+    }
+
+    #[test]
+    fn test_catch_up() {
+        let backend = MockBackend::new().with_volume(volume());
+        let mut vol = backend.list_volumes().unwrap().remove(0);
+        let index = Index::new();
+        index.add_volume_records(&vol, backend.full_scan(&vol, None).unwrap());
+
+        // Nothing to resume from
+        let (handler, rx) = ChannelChangeHandler::new();
+        assert!(backend.catch_up(&vol, &handler).unwrap().is_none());
+
+        vol.journal_state = Some(JournalState::new(1, 1));
+        let state = backend.catch_up(&vol, &handler).unwrap().unwrap();
+        assert_eq!(state.last_usn, 3);
+        drop(handler);
+        for message in rx.iter() {
+            if let ChangeHandlerMessage::Change(event) = message {
+                index.apply_change(event);
+            }
+        }
+        assert_eq!(index.search(&SearchQuery::substring("app.rs")).len(), 1);
+        assert!(index.search(&SearchQuery::substring("lib.rs")).is_empty());
+
+        // A different journal can't be caught up from
+        vol.journal_state = Some(JournalState::new(99, 1));
+        let (handler, rx) = ChannelChangeHandler::new();
+        backend.catch_up(&vol, &handler).unwrap();
+        drop(handler);
+        let messages: Vec<_> = rx.iter().collect();
+        assert_eq!(messages.len(), 1);
+        assert!(matches!(
+            messages[0],
+            ChangeHandlerMessage::JournalReset { .. }
+        ));
+    }
+
+    #[test]
+    fn test_resume_and_journal_id_change() {
+        let backend = MockBackend::new().with_volume(volume());
+        let mut vol = backend.list_volumes().unwrap().remove(0);
+
+        vol.journal_state = Some(JournalState::new(1, 2));
+        let messages = drain(&backend, vol.clone());
+        assert_eq!(messages.len(), 1);
+
+        vol.journal_state = Some(JournalState::new(99, 2));
+        let messages = drain(&backend, vol);
+        assert!(matches!(
+            messages[0],
+            ChangeHandlerMessage::JournalReset { .. }
+        ));
+        assert_eq!(messages.len(), 4);
+    }
+
+    #[test]
+    fn test_stop_interrupts_delay() {
+        let backend = MockBackend::new().with_volume(
+            MockVolume::new("D")
+                .then_delay(Duration::from_secs(60))
+                .then_error("unreachable"),
+        );
+        let vol = backend.list_volumes().unwrap().remove(0);
+
+        let (handler, rx) = ChannelChangeHandler::new();
+        let mut handle = backend.watch_changes(vol, Arc::new(handler)).unwrap();
+        handle.stop();
+
+        let start = std::time::Instant::now();
+        assert_eq!(rx.iter().count(), 0);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_errors() {
+        let backend = MockBackend::new().with_volume(
+            MockVolume::new("E")
+                .with_scan_error("access denied")
+                .with_watch_error("no journal"),
+        );
+        let vol = backend.list_volumes().unwrap().remove(0);
+        assert!(backend.full_scan(&vol, None).is_err());
+        assert!(backend
+            .watch_changes(vol, Arc::new(ChannelChangeHandler::default()))
+            .is_err());
+    }
+}
@@ -0,0 +1,472 @@
+//! Searching the index from Explorer's search box.
+//!
+//! Windows federated search sends the text typed into Explorer's search box
+//! to an OpenSearch endpoint and lists the RSS items it answers with like
+//! files in a folder. [`OpenSearchServer`] is that endpoint for the local
+//! index: it answers `GET /search?q=...` with an RSS feed whose links are
+//! the paths of the results. Opening the [`description`] file it is
+//! registered with adds a "Glint" search connector under the user's
+//! Searches folder, so queries typed there run at index speed.
+//!
+//! ```text
+//! GET /search?q=report%20ext:pdf&start=1&count=50 HTTP/1.1
+//! ```
+//!
+//! The query is in the normal query syntax. Explorer can't present a
+//! token, so only connections from this machine are answered; queries are
+//! kept within the same [`QueryLimits`] as the IPC server. A web page in a
+//! browser on this machine connects from it too, through a domain of its
+//! own rebound to 127.0.0.1, so requests must also name the endpoint by its
+//! loopback address or `localhost` in their `Host` header.
+
+use crate::error::Result;
+use crate::index::Index;
+use crate::ipc::{
+    read_request_line, IpcRequest, IpcResponse, QueryGate, QueryLimits, QueryRequest, Refusal,
+};
+use crate::types::FileRecord;
+use std::io::{BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Default address of the search connector's endpoint.
+pub const DEFAULT_EXPLORER_ADDR: &str = "127.0.0.1:7380";
+
+/// Results per page when Explorer doesn't ask for a count.
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Name of the search connector in Explorer.
+pub const CONNECTOR_NAME: &str = "Glint";
+
+/// The OpenSearch description registering the endpoint at `addr` with
+/// Explorer. Saved as a `.osdx` file and opened, it adds the connector.
+pub fn description(addr: &str) -> String {
+    let template = format!(
+        "http://{}/search?q={{searchTerms}}&start={{startIndex?}}&count={{count?}}",
+        addr
+    );
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/" xmlns:ms-ose="http://schemas.microsoft.com/opensearchext/2009/">
+  <ShortName>{name}</ShortName>
+  <Description>Search file names with the Glint index</Description>
+  <Url type="application/rss+xml" template="{template}"/>
+  <ms-ose:ResultsProcessing format="application/rss+xml">
+    <ms-ose:LinkIsFilePath>-1</ms-ose:LinkIsFilePath>
+  </ms-ose:ResultsProcessing>
+</OpenSearchDescription>
+"#,
+        name = CONNECTOR_NAME,
+        template = xml_escape(&template),
+    )
+}
+
+/// Where Windows keeps the connector once the description is opened: the
+/// user's Searches folder.
+pub fn connector_path() -> Option<PathBuf> {
+    let dirs = directories::UserDirs::new()?;
+    Some(
+        dirs.home_dir()
+            .join("Searches")
+            .join(format!("{}.searchConnector-ms", CONNECTOR_NAME)),
+    )
+}
+
+/// The RSS feed answering `query`, one item per result with its path as
+/// the link.
+pub fn feed(query: &str, results: &[FileRecord]) -> String {
+    let mut out = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+        "\n",
+        r#"<rss version="2.0"><channel>"#,
+        "\n"
+    ));
+    out.push_str(&format!(
+        "<title>{}: {}</title>\n<description>Glint results</description>\n",
+        CONNECTOR_NAME,
+        xml_escape(query)
+    ));
+    for record in results {
+        out.push_str(&format!(
+            "<item><title>{}</title><link>{}</link><description>{}</description>",
+            xml_escape(&record.name),
+            xml_escape(&record.path),
+            xml_escape(folder_of(&record.path)),
+        ));
+        if let Some(modified) = record.modified {
+            out.push_str(&format!("<pubDate>{}</pubDate>", modified.to_rfc2822()));
+        }
+        out.push_str("</item>\n");
+    }
+    out.push_str("</channel></rss>\n");
+    out
+}
+
+/// The folder part of `path`.
+fn folder_of(path: &str) -> &str {
+    path.rfind(['\\', '/']).map_or("", |i| &path[..i])
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// The search asked for by an HTTP request line such as
+/// `GET /search?q=notes&start=1 HTTP/1.1`, or None for any other request.
+///
+/// `start` is 1-based, as in OpenSearch.
+pub fn parse_request(line: &str) -> Option<QueryRequest> {
+    let mut parts = line.split_whitespace();
+    if parts.next()? != "GET" {
+        return None;
+    }
+    let target = parts.next()?;
+    let (path, params) = target.split_once('?').unwrap_or((target, ""));
+    if path != "/search" {
+        return None;
+    }
+
+    let mut request = QueryRequest::new("", DEFAULT_PAGE_SIZE);
+    for param in params.split('&') {
+        let (key, value) = param.split_once('=').unwrap_or((param, ""));
+        let value = percent_decode(value);
+        match key {
+            "q" => request.query = value,
+            "start" => request.offset = value.parse::<usize>().map_or(0, |s| s.saturating_sub(1)),
+            "count" => {
+                if let Ok(count) = value.parse() {
+                    request.limit = count;
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(request)
+}
+
+/// Decode a query string value: `%XX` escapes and `+` for a space.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' => {
+                let hex = s
+                    .get(i + 1..i + 3)
+                    .and_then(|h| u8::from_str_radix(h, 16).ok());
+                if let Some(byte) = hex {
+                    out.push(byte);
+                    i += 2;
+                } else {
+                    out.push(b'%');
+                }
+            }
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Handle for a running search connector endpoint.
+///
+/// The server stops accepting connections when the handle is dropped.
+pub struct OpenSearchServer {
+    local_addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl OpenSearchServer {
+    /// Start answering Explorer's searches of `index` on the given address,
+    /// keeping queries within `limits`.
+    pub fn spawn(addr: &str, index: Arc<Index>, limits: QueryLimits) -> Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = Arc::clone(&stop);
+        let gate = QueryGate::new(limits);
+
+        let thread = thread::Builder::new()
+            .name("glint-opensearch".to_string())
+            .spawn(move || accept_loop(listener, index, gate, stop_flag))?;
+
+        info!(addr = %local_addr, "Explorer search endpoint listening");
+
+        Ok(OpenSearchServer {
+            local_addr,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// The address the server is bound to.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Stop the server and wait for the accept loop to exit.
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for OpenSearchServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn accept_loop(listener: TcpListener, index: Arc<Index>, gate: QueryGate, stop: Arc<AtomicBool>) {
+    while !stop.load(Ordering::Acquire) {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                if !peer.ip().is_loopback() {
+                    debug!(peer = %peer, "Refusing a search from another machine");
+                    let _ = respond(&stream, "403 Forbidden", "text/plain", "Forbidden\n");
+                    continue;
+                }
+                let slot = match gate.connect() {
+                    Ok(slot) => slot,
+                    Err(_) => {
+                        warn!("Turning away an Explorer search: too many connections");
+                        let _ = respond(&stream, "503 Service Unavailable", "text/plain", "Busy\n");
+                        continue;
+                    }
+                };
+                let index = Arc::clone(&index);
+                let gate = gate.clone();
+                let _ = thread::Builder::new()
+                    .name("glint-opensearch-conn".to_string())
+                    .spawn(move || {
+                        if let Err(e) = serve_connection(stream, &index, &gate) {
+                            debug!(peer = %peer, error = %e, "Explorer search failed");
+                        }
+                        drop(slot);
+                    });
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(50));
+            }
+            Err(e) => {
+                warn!(error = %e, "Explorer search accept failed");
+                thread::sleep(Duration::from_millis(200));
+            }
+        }
+    }
+}
+
+/// Answer the one request of a connection.
+fn serve_connection(stream: TcpStream, index: &Arc<Index>, gate: &QueryGate) -> Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    match read_request_line(&mut reader, &mut request_line) {
+        Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+            return respond(
+                &stream,
+                "414 URI Too Long",
+                "text/plain",
+                &format!("{}\n", e),
+            );
+        }
+        read => read?,
+    };
+    let mut host = None;
+    let mut header = String::new();
+    while read_request_line(&mut reader, &mut header)? && !header.trim().is_empty() {
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("host") {
+                host = Some(value.trim().to_string());
+            }
+        }
+    }
+    let port = stream.local_addr()?.port();
+    if !host.is_some_and(|host| is_local_host(&host, port)) {
+        debug!("Refusing a search for another host");
+        return respond(&stream, "403 Forbidden", "text/plain", "Forbidden\n");
+    }
+
+    let Some(request) = parse_request(&request_line) else {
+        return respond(&stream, "404 Not Found", "text/plain", "Not found\n");
+    };
+    let query = request.query.clone();
+    match gate.handle(IpcRequest::Query(request), index) {
+        IpcResponse::Results { results, .. } => respond(
+            &stream,
+            "200 OK",
+            "application/rss+xml; charset=utf-8",
+            &feed(&query, &results),
+        ),
+        IpcResponse::Error { message, refused } => {
+            let status = match refused {
+                Some(Refusal::Busy) => "503 Service Unavailable",
+                Some(Refusal::TimedOut) => "504 Gateway Timeout",
+                Some(Refusal::Unauthorized) => "401 Unauthorized",
+                None => "400 Bad Request",
+            };
+            respond(&stream, status, "text/plain", &format!("{}\n", message))
+        }
+        other => respond(
+            &stream,
+            "500 Internal Server Error",
+            "text/plain",
+            &format!("Unexpected response: {:?}\n", other),
+        ),
+    }
+}
+
+/// Whether a `Host` header names this endpoint, listening on `port`, by a
+/// loopback address or `localhost`, as no rebound domain can.
+fn is_local_host(host: &str, port: u16) -> bool {
+    let Some((name, host_port)) = host.rsplit_once(':') else {
+        return false;
+    };
+    host_port.parse() == Ok(port)
+        && (name == "127.0.0.1" || name == "[::1]" || name.eq_ignore_ascii_case("localhost"))
+}
+
+fn respond(mut stream: &TcpStream, status: &str, content_type: &str, body: &str) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::VolumeInfo;
+    use crate::types::{FileId, VolumeId};
+    use std::io::Read;
+
+    #[test]
+    fn test_parse_request() {
+        let request =
+            parse_request("GET /search?q=tax+report%20ext%3Apdf&start=11&count=10 HTTP/1.1")
+                .unwrap();
+        assert_eq!(request.query, "tax report ext:pdf");
+        assert_eq!((request.offset, request.limit), (10, 10));
+
+        // Optional parameters Explorer left empty keep their defaults
+        let request = parse_request("GET /search?q=notes&start=&count= HTTP/1.1").unwrap();
+        assert_eq!((request.offset, request.limit), (0, DEFAULT_PAGE_SIZE));
+
+        assert!(parse_request("GET /favicon.ico HTTP/1.1").is_none());
+        assert!(parse_request("POST /search?q=a HTTP/1.1").is_none());
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%C3%A9t%C3%A9"), "été");
+    }
+
+    #[test]
+    fn test_description_and_feed() {
+        let description = description("127.0.0.1:7380");
+        assert!(description.contains(
+            "http://127.0.0.1:7380/search?q={searchTerms}&amp;start={startIndex?}&amp;count={count?}"
+        ));
+
+        let mut record = FileRecord::new(
+            FileId::new(1),
+            None,
+            VolumeId::new("C"),
+            "R&D <draft>.txt".to_string(),
+            r"C:\Work\R&D <draft>.txt".to_string(),
+            false,
+        );
+        record.modified = Some(chrono::DateTime::from_timestamp(0, 0).unwrap());
+        let feed = feed("r&d", &[record]);
+        assert!(feed.contains("<title>Glint: r&amp;d</title>"));
+        assert!(feed.contains(r"<link>C:\Work\R&amp;D &lt;draft&gt;.txt</link>"));
+        assert!(feed.contains(r"<description>C:\Work</description>"));
+        assert!(feed.contains("<pubDate>Thu, 1 Jan 1970 00:00:00 +0000</pubDate>"));
+    }
+
+    #[test]
+    fn test_server() {
+        let index = Index::new();
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        let records = ["main.rs", "notes.txt"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                FileRecord::new(
+                    FileId::new(i as u64 + 1),
+                    None,
+                    volume.id.clone(),
+                    name.to_string(),
+                    format!(r"C:\src\{}", name),
+                    false,
+                )
+            })
+            .collect();
+        index.add_volume_records(&volume, records);
+        let mut server =
+            OpenSearchServer::spawn("127.0.0.1:0", Arc::new(index), QueryLimits::default())
+                .unwrap();
+
+        let port = server.local_addr().port();
+        let get_from = |host: &str, target: &str| {
+            let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+            write!(stream, "GET {} HTTP/1.1\r\n{}\r\n", target, host).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let get = |target: &str| get_from(&format!("Host: localhost:{}\r\n", port), target);
+        let found = get("/search?q=*.rs");
+        assert!(found.starts_with("HTTP/1.1 200 OK"));
+        assert!(found.contains(r"<link>C:\src\main.rs</link>"));
+        assert!(!found.contains("notes.txt"));
+        assert!(get("/search?q=r/[/").starts_with("HTTP/1.1 400"));
+        assert!(get("/").starts_with("HTTP/1.1 404"));
+
+        // A page whose domain was rebound to 127.0.0.1 names that domain
+        let rebound = format!("Host: attacker.example:{}\r\n", port);
+        assert!(get_from(&rebound, "/search?q=*.rs").starts_with("HTTP/1.1 403"));
+        assert!(get_from("", "/search?q=*.rs").starts_with("HTTP/1.1 403"));
+        let by_address = format!("host: 127.0.0.1:{}\r\n", port);
+        assert!(get_from(&by_address, "/search?q=*.rs").starts_with("HTTP/1.1 200"));
+        server.stop();
+    }
+
+    #[test]
+    fn test_is_local_host() {
+        assert!(is_local_host("127.0.0.1:7380", 7380));
+        assert!(is_local_host("LocalHost:7380", 7380));
+        assert!(is_local_host("[::1]:7380", 7380));
+        assert!(!is_local_host("127.0.0.1:80", 7380));
+        assert!(!is_local_host("127.0.0.1", 7380));
+        assert!(!is_local_host("localhost.attacker.example:7380", 7380));
+        assert!(!is_local_host("attacker.example:7380", 7380));
+    }
+}
@@ -0,0 +1,22 @@
+//! Parallel iteration over records, sequential without the `parallel`
+//! feature.
+//!
+//! With the feature this is Rayon's prelude. Without it the same method
+//! names give plain slice iterators, so call sites read the same either way
+//! as long as they stick to adapters both kinds of iterator have (`map`,
+//! `filter`, `enumerate`, `for_each`, `collect`).
+
+#[cfg(feature = "parallel")]
+pub(crate) use rayon::prelude::*;
+
+#[cfg(not(feature = "parallel"))]
+pub(crate) trait ParallelSlice<T> {
+    fn par_iter(&self) -> std::slice::Iter<'_, T>;
+}
+
+#[cfg(not(feature = "parallel"))]
+impl<T> ParallelSlice<T> for [T] {
+    fn par_iter(&self) -> std::slice::Iter<'_, T> {
+        self.iter()
+    }
+}
@@ -0,0 +1,231 @@
+//! Path display and copy formats.
+//!
+//! Paths are stored in the index in native Windows form (`C:\Users\...`).
+//! Many users paste them into cross-platform tools, so this module converts
+//! a native path into the other common representations:
+//!
+//! - **Native**: `C:\Users\doc.txt`
+//! - **UNC**: `\\HOST\c$\Users\doc.txt` (administrative share)
+//! - **Forward slash**: `C:/Users/doc.txt`
+//! - **File URI**: `file:///C:/Users/doc.txt`
+//! - **WSL**: `/mnt/c/Users/doc.txt`
+
+use crate::error::{GlintError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// The format used when displaying or copying a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PathFormat {
+    /// Native Windows path (`C:\dir\file`)
+    #[default]
+    Native,
+
+    /// UNC path through the drive's administrative share (`\\host\c$\dir\file`)
+    Unc,
+
+    /// Native path with forward slashes (`C:/dir/file`)
+    ForwardSlash,
+
+    /// `file://` URI with percent-encoding (`file:///C:/dir/file`)
+    FileUri,
+
+    /// WSL mount path (`/mnt/c/dir/file`)
+    Wsl,
+}
+
+impl PathFormat {
+    /// All formats, in the order they are offered in menus.
+    pub const ALL: [PathFormat; 5] = [
+        PathFormat::Native,
+        PathFormat::Unc,
+        PathFormat::ForwardSlash,
+        PathFormat::FileUri,
+        PathFormat::Wsl,
+    ];
+
+    /// Human-readable label for menus and settings.
+    pub fn label(&self) -> &'static str {
+        match self {
+            PathFormat::Native => "Native",
+            PathFormat::Unc => "UNC path",
+            PathFormat::ForwardSlash => "Forward slashes",
+            PathFormat::FileUri => "file:// URI",
+            PathFormat::Wsl => "WSL path",
+        }
+    }
+
+    /// The next format in `ALL`, wrapping around (used for cycling in the TUI).
+    pub fn next(&self) -> PathFormat {
+        let pos = Self::ALL.iter().position(|f| f == self).unwrap_or(0);
+        Self::ALL[(pos + 1) % Self::ALL.len()]
+    }
+
+    /// Convert a native path into this format, using the local host name for UNC paths.
+    pub fn apply(&self, path: &str) -> String {
+        match self {
+            PathFormat::Unc => self.apply_with_host(path, &local_hostname()),
+            _ => self.apply_with_host(path, ""),
+        }
+    }
+
+    /// Convert a native path into this format with an explicit UNC host name.
+    pub fn apply_with_host(&self, path: &str, host: &str) -> String {
+        match self {
+            PathFormat::Native => path.to_string(),
+            PathFormat::ForwardSlash => path.replace('\\', "/"),
+            PathFormat::Unc => match split_drive(path) {
+                Some((drive, rest)) => format!(
+                    "\\\\{}\\{}$\\{}",
+                    host,
+                    drive.to_ascii_lowercase(),
+                    rest.trim_start_matches('\\')
+                ),
+                None => path.to_string(),
+            },
+            PathFormat::FileUri => {
+                if let Some(share) = path.strip_prefix("\\\\") {
+                    // \\server\share\x -> file://server/share/x
+                    format!("file://{}", percent_encode(&share.replace('\\', "/")))
+                } else {
+                    let forward = path.replace('\\', "/");
+                    let forward = forward.trim_start_matches('/');
+                    format!("file:///{}", percent_encode(forward))
+                }
+            }
+            PathFormat::Wsl => match split_drive(path) {
+                Some((drive, rest)) => {
+                    let rest = rest.trim_start_matches('\\').replace('\\', "/");
+                    if rest.is_empty() {
+                        format!("/mnt/{}", drive.to_ascii_lowercase())
+                    } else {
+                        format!("/mnt/{}/{}", drive.to_ascii_lowercase(), rest)
+                    }
+                }
+                None => path.replace('\\', "/"),
+            },
+        }
+    }
+}
+
+impl fmt::Display for PathFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathFormat::Native => write!(f, "native"),
+            PathFormat::Unc => write!(f, "unc"),
+            PathFormat::ForwardSlash => write!(f, "forward-slash"),
+            PathFormat::FileUri => write!(f, "file-uri"),
+            PathFormat::Wsl => write!(f, "wsl"),
+        }
+    }
+}
+
+impl FromStr for PathFormat {
+    type Err = GlintError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "native" | "windows" => Ok(PathFormat::Native),
+            "unc" => Ok(PathFormat::Unc),
+            "forward-slash" | "forward" | "slash" => Ok(PathFormat::ForwardSlash),
+            "file-uri" | "uri" | "file" => Ok(PathFormat::FileUri),
+            "wsl" => Ok(PathFormat::Wsl),
+            _ => Err(GlintError::ConfigError {
+                reason: format!(
+                    "Unknown path format: {} (expected native, unc, forward-slash, file-uri or wsl)",
+                    s
+                ),
+            }),
+        }
+    }
+}
+
+/// Split `C:\rest` into the drive letter and the remainder.
+fn split_drive(path: &str) -> Option<(char, &str)> {
+    let mut chars = path.chars();
+    let letter = chars.next()?;
+    if letter.is_ascii_alphabetic() && chars.next() == Some(':') {
+        Some((letter, &path[2..]))
+    } else {
+        None
+    }
+}
+
+/// Percent-encode everything except unreserved characters, `/` and `:`.
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// Best-effort local host name for UNC paths.
+fn local_hostname() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "localhost".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATH: &str = "C:\\Users\\me\\My Doc.txt";
+
+    #[test]
+    fn test_native_and_forward_slash() {
+        assert_eq!(PathFormat::Native.apply(PATH), PATH);
+        assert_eq!(
+            PathFormat::ForwardSlash.apply(PATH),
+            "C:/Users/me/My Doc.txt"
+        );
+    }
+
+    #[test]
+    fn test_unc() {
+        assert_eq!(
+            PathFormat::Unc.apply_with_host(PATH, "box"),
+            "\\\\box\\c$\\Users\\me\\My Doc.txt"
+        );
+        // Paths without a drive letter are left alone
+        assert_eq!(
+            PathFormat::Unc.apply_with_host("\\\\srv\\share\\x", "box"),
+            "\\\\srv\\share\\x"
+        );
+    }
+
+    #[test]
+    fn test_file_uri() {
+        assert_eq!(
+            PathFormat::FileUri.apply(PATH),
+            "file:///C:/Users/me/My%20Doc.txt"
+        );
+        assert_eq!(
+            PathFormat::FileUri.apply("\\\\srv\\share\\a b"),
+            "file://srv/share/a%20b"
+        );
+    }
+
+    #[test]
+    fn test_wsl() {
+        assert_eq!(PathFormat::Wsl.apply(PATH), "/mnt/c/Users/me/My Doc.txt");
+        assert_eq!(PathFormat::Wsl.apply("D:\\"), "/mnt/d");
+    }
+
+    #[test]
+    fn test_parse_and_display_roundtrip() {
+        for format in PathFormat::ALL {
+            let parsed: PathFormat = format.to_string().parse().unwrap();
+            assert_eq!(parsed, format);
+        }
+        assert!("bogus".parse::<PathFormat>().is_err());
+    }
+}
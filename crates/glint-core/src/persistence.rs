@@ -35,14 +35,19 @@
 //! ```
 
 use crate::backend::{JournalState, VolumeInfo};
+use crate::churn::ChurnReport;
+use crate::deleted::RecentlyDeleted;
 use crate::error::{GlintError, Result};
-use crate::index::{Index, VolumeIndexState};
-use crate::types::{FileRecord, IndexStats, VolumeId};
+use crate::index::{Index, VolumeAvailability, VolumeIndexState};
+use crate::manifest::BuildManifest;
+use crate::types::{FileId, FileRecord, IndexStats, VolumeId};
 use crate::archive;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs::{self, File};
 use std::io::{BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use tracing::{debug, info, warn};
 use rayon::prelude::*;
 
@@ -66,9 +71,9 @@ impl IndexFlags {
     pub const CHUNKED: Self = IndexFlags(2);
 
     fn is_compressed(&self) -> bool {
-        self.0 & 1 != 0
+        self.0 & Self::COMPRESSED_LZ4.0 != 0
     }
-    fn is_chunked(&self) -> bool { self.0 & 2 != 0 }
+    fn is_chunked(&self) -> bool { self.0 & Self::CHUNKED.0 != 0 }
 }
 
 /// Header structure for the index file
@@ -142,6 +147,10 @@ impl StoredVolumeState {
             journal_state: self.journal_state.clone(),
             record_count: self.record_count,
             needs_rescan: false,
+            enabled: true,
+            last_full_scan: None,
+            last_event_applied: None,
+            availability: VolumeAvailability::Online,
         }
     }
 }
@@ -153,6 +162,59 @@ struct StoredMeta {
     volumes: Vec<StoredVolumeState>,
 }
 
+/// Volume entry in the JSON volume table
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredVolume {
+    id: String,
+    mount_point: String,
+    filesystem_type: String,
+    #[serde(default)]
+    label: Option<String>,
+    /// Folder of a folder (pseudo) volume
+    #[serde(default)]
+    root: Option<String>,
+    /// Folders the volume is also mounted at
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    mount_folders: Vec<String>,
+    #[serde(default)]
+    journal_state: Option<JournalState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_full_scan: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_event_applied: Option<DateTime<Utc>>,
+}
+
+impl From<&VolumeIndexState> for StoredVolume {
+    fn from(state: &VolumeIndexState) -> Self {
+        StoredVolume {
+            id: state.info.id.as_str().to_string(),
+            mount_point: state.info.mount_point.clone(),
+            filesystem_type: state.info.filesystem_type.clone(),
+            label: state.info.label.clone(),
+            root: state.info.root.clone(),
+            mount_folders: state.info.mount_folders.clone(),
+            journal_state: state.journal_state.clone(),
+            last_full_scan: state.last_full_scan,
+            last_event_applied: state.last_event_applied,
+        }
+    }
+}
+
+impl StoredVolume {
+    fn volume_info(&self) -> VolumeInfo {
+        let mut info = VolumeInfo::new(
+            VolumeId::new(&self.id),
+            &self.mount_point,
+            &self.filesystem_type,
+        );
+        info.label = self.label.clone();
+        info.root = self.root.clone();
+        info.mount_folders = self.mount_folders.clone();
+        info.journal_state = self.journal_state.clone();
+        info
+    }
+}
+
 /// Manages persistence of the index to disk.
 ///
 /// ## Example
@@ -174,8 +236,8 @@ pub struct IndexStore {
     /// Base directory for storing index files
     base_dir: PathBuf,
 
-    /// Whether to use compression
-    use_compression: bool,
+    /// Whether writing to the stored files is refused
+    read_only: bool,
 }
 
 impl IndexStore {
@@ -185,45 +247,170 @@ impl IndexStore {
     pub fn new(base_dir: impl AsRef<Path>) -> Self {
         IndexStore {
             base_dir: base_dir.as_ref().to_path_buf(),
-            use_compression: true,
+            read_only: false,
         }
     }
 
     /// Set whether to use compression when saving.
-    pub fn with_compression(mut self, compress: bool) -> Self {
-        self.use_compression = compress;
+    ///
+    /// Version 3 indexes are archives read in place, so they are always
+    /// written uncompressed and this has no effect.
+    #[deprecated(note = "indexes are always saved uncompressed; this has no effect")]
+    pub fn with_compression(self, _compress: bool) -> Self {
         self
     }
 
+    /// Set whether the stored files may be changed.
+    ///
+    /// A read-only store still loads, but saving, clearing or restoring
+    /// from backup fails with [`GlintError::ReadOnly`], and so does saving
+    /// a manifest or busy-folder report next to the index.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Whether the stored files may not be changed.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Fail with [`GlintError::ReadOnly`] if the store is read-only;
+    /// `operation` says what was refused.
+    pub fn ensure_writable(&self, operation: &str) -> Result<()> {
+        if self.read_only {
+            return Err(GlintError::read_only(operation));
+        }
+        Ok(())
+    }
+
     /// Get the path to the main index file.
     pub fn index_path(&self) -> PathBuf {
         self.base_dir.join("glint.idx")
     }
 
     /// Get the path to a backup index file.
-    fn backup_path(&self) -> PathBuf {
+    pub fn backup_path(&self) -> PathBuf {
         self.base_dir.join("glint.idx.bak")
     }
 
+    /// Get the path to the volume table saved next to the index.
+    ///
+    /// The v3 archive only holds records, so volume details (folder roots,
+    /// journal positions) are kept here.
+    pub fn volumes_path(&self) -> PathBuf {
+        self.base_dir.join("glint.volumes.json")
+    }
+
+    /// Get the path to the build manifest saved next to the index.
+    pub fn manifest_path(&self) -> PathBuf {
+        self.base_dir.join("glint.manifest.json")
+    }
+
+    /// Save the manifest of the index's last build.
+    pub fn save_manifest(&self, manifest: &BuildManifest) -> Result<()> {
+        self.ensure_writable("save the build manifest")?;
+        manifest.save(&self.manifest_path())
+    }
+
+    /// Load the manifest of the index's last build, if there is one.
+    pub fn load_manifest(&self) -> Option<BuildManifest> {
+        BuildManifest::load(&self.manifest_path())
+    }
+
+    /// Get the path to the busy-folder report written by the watcher.
+    pub fn churn_path(&self) -> PathBuf {
+        self.base_dir.join("glint.churn.json")
+    }
+
+    /// Save the watcher's busy-folder report.
+    pub fn save_churn(&self, report: &ChurnReport) -> Result<()> {
+        self.ensure_writable("save the busy-folder report")?;
+        report.save(&self.churn_path())
+    }
+
+    /// Load the watcher's last busy-folder report, if there is one.
+    pub fn load_churn(&self) -> Option<ChurnReport> {
+        ChurnReport::load(&self.churn_path())
+    }
+
+    /// Get the path to the recently deleted files saved next to the index.
+    pub fn deleted_path(&self) -> PathBuf {
+        self.base_dir.join("glint.deleted.json")
+    }
+
     /// Get the path to a temporary file during save.
     fn temp_path(&self) -> PathBuf {
         self.base_dir.join("glint.idx.tmp")
     }
 
+    /// Bytes taken by the index file, its backup and any temporary file a
+    /// failed save left behind.
+    pub fn disk_usage(&self) -> u64 {
+        [self.index_path(), self.backup_path(), self.temp_path()]
+            .iter()
+            .filter_map(|path| fs::metadata(path).ok())
+            .map(|meta| meta.len())
+            .sum()
+    }
+
+    /// Delete the backup of the previous index file and any temporary file
+    /// a failed save left behind, returning the bytes freed.
+    pub fn remove_spare_files(&self) -> Result<u64> {
+        self.ensure_writable("remove the index backup")?;
+        let mut freed = 0;
+        for path in [self.backup_path(), self.temp_path()] {
+            if let Ok(meta) = fs::metadata(&path) {
+                fs::remove_file(&path)?;
+                freed += meta.len();
+            }
+        }
+        Ok(freed)
+    }
+
     /// Check if an index file exists.
     pub fn exists(&self) -> bool {
         self.index_path().exists()
     }
 
+    /// Modification time and size of the index file, or `None` if there
+    /// is none.
+    ///
+    /// Saves replace the file by renaming, so a changed stamp means a
+    /// complete new index, written by this process or another one.
+    pub fn stamp(&self) -> Option<IndexStamp> {
+        let meta = fs::metadata(self.index_path()).ok()?;
+        Some(IndexStamp {
+            modified: meta.modified().ok()?,
+            len: meta.len(),
+        })
+    }
+
     /// Save the index to disk.
     ///
     /// Uses atomic write (write to temp, then rename) to prevent corruption.
     pub fn save(&self, index: &Index) -> Result<()> {
+        self.save_with_progress(index, |_| {})
+    }
+
+    /// Save the index to disk, reporting how much of the file is written.
+    ///
+    /// The archive is built whole in memory first, as its offsets are only
+    /// known once every record is laid out, so saving takes about the size
+    /// of the index file on top of the index itself. It is then checksummed
+    /// in parallel and written in blocks of [`SAVE_BLOCK`] bytes, with
+    /// `progress` called after each one; the build itself isn't reported.
+    pub fn save_with_progress(
+        &self,
+        index: &Index,
+        mut progress: impl FnMut(SaveProgress),
+    ) -> Result<()> {
+        self.ensure_writable("save the index")?;
+
         // Ensure directory exists
         fs::create_dir_all(&self.base_dir)?;
 
-        let records = index.all_records();
-        let record_count = records.len() as u64;
+        let record_count = index.len() as u64;
 
         info!(
             path = %self.index_path().display(),
@@ -236,30 +423,26 @@ impl IndexStore {
 
         // (v3 does not use meta_bytes)
 
-        // Prepare chunks of records
-        let chunk_size: usize = 200_000.max(1);
-        let total = records.len();
-        let chunks: Vec<&[FileRecord]> = (0..total)
-            .step_by(chunk_size)
-            .map(|start| {
-                let end = (start + chunk_size).min(total);
-                &records[start..end]
+        // Build rkyv archive in memory, then checksum its blocks in parallel
+        let data_buf = archive::build_archived_bytes(index);
+        let checksum = data_buf
+            .par_chunks(SAVE_BLOCK)
+            .map(|block| {
+                let mut hasher = crc32fast::Hasher::new();
+                hasher.update(block);
+                hasher
             })
-            .collect();
-
-        // Serialize (and compress) each chunk
-        let mut chunk_blobs: Vec<Vec<u8>> = Vec::with_capacity(chunks.len());
-        for ch in &chunks {
-            let bytes = bincode::serialize(ch)?;
-            let blob = if self.use_compression {
-                lz4_flex::compress_prepend_size(&bytes)
-            } else {
-                bytes
-            };
-            chunk_blobs.push(blob);
-        }
+            .reduce(crc32fast::Hasher::new, |mut a, b| {
+                a.combine(&b);
+                a
+            })
+            .finalize();
 
-        // Checksum computed after assembling data buffer below
+        let header = IndexHeader::new(record_count, flags);
+        let header_bytes = bincode::serialize(&header)?;
+        let total = (header_bytes.len() + data_buf.len() + 8) as u64;
+        let mut written = 0;
+        progress(SaveProgress { written, total });
 
         // Write to temp file
         let temp_path = self.temp_path();
@@ -268,21 +451,26 @@ impl IndexStore {
             let mut writer = BufWriter::new(file);
 
             // Write header
-            let header = IndexHeader::new(record_count, flags);
-            let header_bytes = bincode::serialize(&header)?;
             writer.write_all(&header_bytes)?;
+            written += header_bytes.len() as u64;
 
-            // Build rkyv archive in memory and write directly
-            let data_buf = archive::build_archived_bytes(index);
-            writer.write_all(&data_buf)?;
+            // Stream the archive, so progress moves with the disk
+            for block in data_buf.chunks(SAVE_BLOCK) {
+                writer.write_all(block)?;
+                written += block.len() as u64;
+                progress(SaveProgress { written, total });
+            }
 
             // Write footer
-            let checksum = crc32fast::hash(&data_buf);
             writer.write_all(&checksum.to_le_bytes())?;
             writer.write_all(MAGIC_FOOTER)?;
 
             writer.flush()?;
         }
+        progress(SaveProgress {
+            written: total,
+            total,
+        });
 
         // Backup existing index
         let index_path = self.index_path();
@@ -295,6 +483,9 @@ impl IndexStore {
         // Rename temp to final
         fs::rename(&temp_path, &index_path)?;
 
+        self.save_volume_table(index)?;
+        index.recently_deleted().save(&self.deleted_path())?;
+
         debug!(compressed = false, "Index saved successfully (v3 rkyv)");
 
         Ok(())
@@ -304,6 +495,21 @@ impl IndexStore {
     ///
     /// Returns a new Index populated with the stored data.
     pub fn load(&self) -> Result<Index> {
+        let index = Index::new();
+        self.load_progressive(&index, |_| {})?;
+        Ok(index)
+    }
+
+    /// Load the index from disk into `index` one volume at a time.
+    ///
+    /// Each volume is searchable as soon as `progress` reports it ready, so
+    /// callers can share `index` with a search thread while the rest load.
+    /// The system volume goes first.
+    pub fn load_progressive(
+        &self,
+        index: &Index,
+        mut progress: impl FnMut(LoadProgress),
+    ) -> Result<()> {
         let index_path = self.index_path();
 
         if !index_path.exists() {
@@ -353,36 +559,94 @@ impl IndexStore {
             });
         }
 
+        if let Some(deleted) = RecentlyDeleted::load(&self.deleted_path()) {
+            index.restore_recently_deleted(deleted);
+        }
+
         // v3 path: rkyv archive (uncompressed)
         if header.version == 3 {
-            // Map into memory for zero-copy view
-            // (We still build an Index today for compatibility. Next step: expose a zero-copy view.)
-            // No decompression step; data is an rkyv archive
-            unsafe {
-                let root = archive::archived_root(&data);
-                let mut recs: Vec<FileRecord> = Vec::with_capacity(root.is_dir.len());
-                for i in 0..root.is_dir.len() {
-                    let noff = root.name_offsets[i] as usize;
-                    let poff = root.path_offsets[i] as usize;
-                    let name = read_cstr(&root.names_blob[noff..]);
-                    let path = read_cstr(&root.paths_blob[poff..]);
-                    use crate::types::{FileId, VolumeId as VID};
-                    let rec = FileRecord::new(
-                        FileId::new(i as u64 + 1),
-                        None,
-                        VID::new("V"),
-                        name.to_string(),
-                        path.to_string(),
-                        root.is_dir[i] != 0,
-                    );
-                    recs.push(rec);
+            // No decompression step; data is an rkyv archive. The archive
+            // has no volume table, so records go to the folder volume that
+            // contains them, or else to their drive.
+            let root = unsafe { archive::archived_root(&data) };
+            let stored = self.load_volume_table();
+            let table: Vec<VolumeInfo> = stored.iter().map(StoredVolume::volume_info).collect();
+            let mut folders: Vec<&VolumeInfo> = table.iter().filter(|v| v.root.is_some()).collect();
+            folders.sort_by_key(|v| std::cmp::Reverse(v.display_name().len()));
+
+            let mut positions: Vec<(VolumeId, Vec<usize>)> = Vec::new();
+            for i in 0..root.is_dir.len() {
+                let poff = root.path_offsets[i] as usize;
+                let path = read_cstr(&root.paths_blob[poff..]);
+                let drive = volume_of_path(path);
+                let volume = match folders.iter().find(|v| v.contains_path(path)) {
+                    Some(folder) => folder.id.clone(),
+                    None => table
+                        .iter()
+                        .find(|v| {
+                            v.root.is_none()
+                                && v.mount_point
+                                    .get(..1)
+                                    .is_some_and(|d| d.eq_ignore_ascii_case(drive))
+                        })
+                        .map(|v| v.id.clone())
+                        .unwrap_or_else(|| VolumeId::new(drive)),
+                };
+                match positions.iter_mut().find(|(id, _)| *id == volume) {
+                    Some((_, list)) => list.push(i),
+                    None => positions.push((volume, vec![i])),
                 }
-                let idx = Index::with_capacity(recs.len());
-                let vol = VolumeInfo::new(VolumeId::new("V"), "V:", "NTFS");
-                idx.add_volume_records(&vol, recs);
-                info!(records = idx.len(), "Index loaded successfully (v3 rkyv)");
-                return Ok(idx);
             }
+            let volumes = positions
+                .into_iter()
+                .map(|(id, list)| {
+                    let info = table
+                        .iter()
+                        .find(|v| v.id == id)
+                        .cloned()
+                        .unwrap_or_else(|| {
+                            VolumeInfo::new(id.clone(), format!("{}:", id.as_str()), "NTFS")
+                        });
+                    let saved = stored.iter().find(|v| v.id == id.as_str());
+                    PendingVolume {
+                        journal_state: info.journal_state.clone(),
+                        info,
+                        last_full_scan: saved.and_then(|v| v.last_full_scan),
+                        last_event_applied: saved.and_then(|v| v.last_event_applied),
+                        records: list.len(),
+                        source: (id, list),
+                    }
+                })
+                .collect();
+            publish_volumes(
+                index,
+                volumes,
+                |(id, list)| {
+                    list.into_iter()
+                        .map(|i| {
+                            let noff = root.name_offsets[i] as usize;
+                            let poff = root.path_offsets[i] as usize;
+                            let name = read_cstr(&root.names_blob[noff..]);
+                            let path = read_cstr(&root.paths_blob[poff..]);
+                            FileRecord::new(
+                                FileId::new(i as u64 + 1),
+                                None,
+                                id.clone(),
+                                name.to_string(),
+                                path.to_string(),
+                                root.is_dir[i] != 0,
+                            )
+                        })
+                        .collect()
+                },
+                &mut progress,
+            );
+            info!(
+                records = index.len(),
+                volumes = index.volume_states().len(),
+                "Index loaded successfully (v3 rkyv)"
+            );
+            return Ok(());
         }
 
         // v1 path (legacy): single blob (maybe compressed) containing StoredIndex
@@ -396,24 +660,14 @@ impl IndexStore {
                 .map_err(|e| GlintError::IndexCorrupted { reason: format!("Deserialization failed: {}", e) })?;
 
             let mut records: Vec<FileRecord> = stored.records;
-            records.par_iter_mut().for_each(|r| r.init_cache());
-            let index = Index::with_capacity(records.len());
-            let mut records_by_volume: std::collections::HashMap<String, Vec<FileRecord>> = std::collections::HashMap::new();
-            for record in records { records_by_volume.entry(record.volume_id.as_str().to_string()).or_default().push(record); }
-            for vol_state in stored.volumes {
-                let vid = vol_state.id.clone();
-                if let Some(records) = records_by_volume.remove(&vid) {
-                    let volume_info = VolumeInfo::new(VolumeId::new(&vol_state.id), &vol_state.mount_point, &vol_state.filesystem_type);
-                    index.add_volume_records(&volume_info, records);
-                    if let Some(js) = vol_state.journal_state { index.update_journal_state(&VolumeId::new(&vid), js); }
-                }
-            }
+            records.par_iter_mut().for_each(|r| r.init_name_cache());
+            publish_volumes(index, group_by_volume(stored.volumes, records), |r| r, &mut progress);
             info!(records = index.len(), volumes = index.volume_states().len(), "Index loaded successfully (v1)");
             // Opportunistically rewrite to v2 chunked format for faster future loads
-            if let Err(e) = self.save(&index) {
+            if let Err(e) = self.save(index) {
                 warn!(error = %e, "Failed to rewrite index to v2 format");
             }
-            return Ok(index);
+            return Ok(());
         }
 
         // v2 path: chunked
@@ -444,7 +698,7 @@ impl IndexStore {
         }
 
         // Decompress + deserialize chunks in parallel
-        let mut all_records: Vec<FileRecord> = chunk_slices
+        let all_records: Vec<FileRecord> = chunk_slices
             .par_iter()
             .map(|blob| {
                 let bytes = if flags.is_compressed() {
@@ -453,34 +707,17 @@ impl IndexStore {
                 } else { (*blob).to_vec() };
                 let mut recs: Vec<FileRecord> = bincode::deserialize(&bytes)
                     .map_err(|e| GlintError::IndexCorrupted { reason: format!("Deserialization failed: {}", e) })?;
-                recs.par_iter_mut().for_each(|r| r.init_cache());
+                recs.par_iter_mut().for_each(|r| r.init_name_cache());
                 Ok::<Vec<FileRecord>, GlintError>(recs)
             })
             .try_reduce(|| Vec::new(), |mut acc, mut v| { acc.append(&mut v); Ok::<Vec<FileRecord>, GlintError>(acc) })?;
 
-        // Build the index
-        let index = Index::with_capacity(all_records.len());
-        // Group by volume
-        let mut records_by_volume: std::collections::HashMap<String, Vec<FileRecord>> = std::collections::HashMap::new();
-        for record in all_records.drain(..) {
-            records_by_volume.entry(record.volume_id.as_str().to_string()).or_default().push(record);
-        }
-        for vol_state in meta.volumes {
-            let vid = vol_state.id.clone();
-            if let Some(records) = records_by_volume.remove(&vid) {
-                let volume_info = VolumeInfo::new(
-                    VolumeId::new(&vol_state.id),
-                    &vol_state.mount_point,
-                    &vol_state.filesystem_type,
-                );
-                index.add_volume_records(&volume_info, records);
-
-                // Restore journal state
-                if let Some(js) = vol_state.journal_state {
-                    index.update_journal_state(&VolumeId::new(&vid), js);
-                }
-            }
-        }
+        publish_volumes(
+            index,
+            group_by_volume(meta.volumes, all_records),
+            |r| r,
+            &mut progress,
+        );
 
         info!(
             records = index.len(),
@@ -488,7 +725,7 @@ impl IndexStore {
             "Index loaded successfully"
         );
 
-        Ok(index)
+        Ok(())
     }
 
     /// Load the index, or return a new empty one if loading fails.
@@ -504,8 +741,37 @@ impl IndexStore {
         }
     }
 
+    fn save_volume_table(&self, index: &Index) -> Result<()> {
+        let table: Vec<StoredVolume> = index
+            .volume_states()
+            .iter()
+            .map(StoredVolume::from)
+            .collect();
+        let contents = serde_json::to_string_pretty(&table)
+            .map_err(|e| GlintError::serialization(e.to_string()))?;
+        fs::write(self.volumes_path(), contents)?;
+        Ok(())
+    }
+
+    /// Read the volume table; a missing or unreadable table is treated as empty.
+    fn load_volume_table(&self) -> Vec<StoredVolume> {
+        let path = self.volumes_path();
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(_) => return Vec::new(),
+        };
+        match serde_json::from_str::<Vec<StoredVolume>>(&contents) {
+            Ok(table) => table,
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "Ignoring unreadable volume table");
+                Vec::new()
+            }
+        }
+    }
+
     /// Delete all stored index data.
     pub fn clear(&self) -> Result<()> {
+        self.ensure_writable("clear the index")?;
         let index_path = self.index_path();
         let backup_path = self.backup_path();
 
@@ -515,12 +781,29 @@ impl IndexStore {
         if backup_path.exists() {
             fs::remove_file(&backup_path)?;
         }
+        let volumes_path = self.volumes_path();
+        if volumes_path.exists() {
+            fs::remove_file(&volumes_path)?;
+        }
+        let manifest_path = self.manifest_path();
+        if manifest_path.exists() {
+            fs::remove_file(&manifest_path)?;
+        }
+        let churn_path = self.churn_path();
+        if churn_path.exists() {
+            fs::remove_file(&churn_path)?;
+        }
+        let deleted_path = self.deleted_path();
+        if deleted_path.exists() {
+            fs::remove_file(&deleted_path)?;
+        }
 
         Ok(())
     }
 
     /// Restore from backup if main index is corrupted.
     pub fn restore_from_backup(&self) -> Result<Index> {
+        self.ensure_writable("restore the index from backup")?;
         let backup_path = self.backup_path();
         let index_path = self.index_path();
 
@@ -536,6 +819,134 @@ impl IndexStore {
     }
 }
 
+/// Bytes of the archive written between [`SaveProgress`] reports.
+pub const SAVE_BLOCK: usize = 4 << 20;
+
+/// Progress reported by [`IndexStore::save_with_progress`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SaveProgress {
+    /// Bytes of the index file written so far
+    pub written: u64,
+    /// Size of the whole index file
+    pub total: u64,
+}
+
+impl SaveProgress {
+    /// Share of the file written, from 0.0 to 1.0.
+    pub fn fraction(&self) -> f64 {
+        self.written as f64 / self.total.max(1) as f64
+    }
+}
+
+/// What the index file looked like at some point, from
+/// [`IndexStore::stamp`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexStamp {
+    pub modified: SystemTime,
+    pub len: u64,
+}
+
+/// Progress reported by [`IndexStore::load_progressive`].
+#[derive(Debug, Clone)]
+pub enum LoadProgress {
+    /// The file has been read; these volumes will be added in this order.
+    Started(Vec<VolumeLoad>),
+    /// A volume's records are in the index and can be searched.
+    VolumeReady(VolumeId),
+}
+
+/// A volume waiting to be added during a progressive load.
+#[derive(Debug, Clone)]
+pub struct VolumeLoad {
+    pub id: VolumeId,
+    pub mount_point: String,
+    pub records: usize,
+}
+
+/// A volume whose records have not been decoded yet.
+struct PendingVolume<T> {
+    info: VolumeInfo,
+    journal_state: Option<JournalState>,
+    /// Update times saved with the index, if any
+    last_full_scan: Option<DateTime<Utc>>,
+    last_event_applied: Option<DateTime<Utc>>,
+    records: usize,
+    source: T,
+}
+
+/// Pair stored volume states with their records, dropping volumes without any.
+fn group_by_volume(
+    volumes: Vec<StoredVolumeState>,
+    records: Vec<FileRecord>,
+) -> Vec<PendingVolume<Vec<FileRecord>>> {
+    let mut records_by_volume: std::collections::HashMap<String, Vec<FileRecord>> =
+        std::collections::HashMap::new();
+    for record in records {
+        records_by_volume
+            .entry(record.volume_id.as_str().to_string())
+            .or_default()
+            .push(record);
+    }
+    volumes
+        .into_iter()
+        .filter_map(|state| {
+            let records = records_by_volume.remove(&state.id)?;
+            Some(PendingVolume {
+                info: VolumeInfo::new(
+                    VolumeId::new(&state.id),
+                    &state.mount_point,
+                    &state.filesystem_type,
+                ),
+                journal_state: state.journal_state,
+                last_full_scan: None,
+                last_event_applied: None,
+                records: records.len(),
+                source: records,
+            })
+        })
+        .collect()
+}
+
+/// Decode and add volumes one at a time, system volume first.
+fn publish_volumes<T>(
+    index: &Index,
+    mut volumes: Vec<PendingVolume<T>>,
+    mut decode: impl FnMut(T) -> Vec<FileRecord>,
+    progress: &mut impl FnMut(LoadProgress),
+) {
+    if let Ok(system) = std::env::var("SystemDrive") {
+        // Stable sort keeps the stored order for everything else
+        volumes.sort_by_key(|v| !v.info.mount_point.eq_ignore_ascii_case(&system));
+    }
+    progress(LoadProgress::Started(
+        volumes
+            .iter()
+            .map(|v| VolumeLoad {
+                id: v.info.id.clone(),
+                mount_point: v.info.mount_point.clone(),
+                records: v.records,
+            })
+            .collect(),
+    ));
+    for volume in volumes {
+        let id = volume.info.id.clone();
+        index.add_volume_records(&volume.info, decode(volume.source));
+        if let Some(js) = volume.journal_state {
+            index.update_journal_state(&id, js);
+        }
+        index.restore_update_times(&id, volume.last_full_scan, volume.last_event_applied);
+        progress(LoadProgress::VolumeReady(id));
+    }
+}
+
+/// Drive letter of a Windows path, or "V" for anything else.
+fn volume_of_path(path: &str) -> &str {
+    match path.as_bytes() {
+        [letter, b':', ..] if letter.is_ascii_alphabetic() => &path[..1],
+        _ => "V",
+    }
+}
+
 fn read_cstr(bytes: &[u8]) -> &str {
     let mut end = 0;
     while end < bytes.len() && bytes[end] != 0 { end += 1; }
@@ -555,6 +966,8 @@ struct StoredIndexV1 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::backend::ChangeEvent;
+    use crate::search::parse_query;
     use crate::types::FileId;
     use tempfile::TempDir;
 
@@ -599,6 +1012,77 @@ mod tests {
     }
 
     #[test]
+    fn test_save_and_load_deleted() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = IndexStore::new(temp_dir.path());
+
+        let index = Index::new();
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        index.add_volume_records(&volume, make_test_records());
+        index.apply_change(ChangeEvent::deleted(
+            VolumeId::new("C"),
+            FileId::new(2),
+            None,
+            "file2.rs".to_string(),
+            false,
+            1,
+        ));
+        store.save(&index).unwrap();
+
+        let loaded = store.load().unwrap();
+        let results = loaded.search(&parse_query("file2 deleted:").unwrap());
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].record.path, "C:\\file2.rs");
+    }
+
+    #[test]
+    fn test_remove_spare_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = IndexStore::new(temp_dir.path());
+
+        let index = Index::new();
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        index.add_volume_records(&volume, make_test_records());
+        store.save(&index).unwrap();
+        store.save(&index).unwrap();
+        assert!(store.backup_path().exists());
+        let usage = store.disk_usage();
+
+        let freed = store.remove_spare_files().unwrap();
+        assert!(freed > 0);
+        assert_eq!(store.disk_usage(), usage - freed);
+        assert!(!store.backup_path().exists());
+        assert_eq!(store.load().unwrap().len(), index.len());
+        assert_eq!(store.remove_spare_files().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_save_progress() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = IndexStore::new(temp_dir.path());
+
+        let index = Index::new();
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        index.add_volume_records(&volume, make_test_records());
+
+        let mut reports = Vec::new();
+        store
+            .save_with_progress(&index, |progress| reports.push(progress))
+            .unwrap();
+
+        // Reports only move forward and end with the whole file
+        let size = fs::metadata(store.index_path()).unwrap().len();
+        assert!(reports.len() >= 2);
+        assert_eq!(reports[0].written, 0);
+        assert!(reports.windows(2).all(|w| w[0].written <= w[1].written));
+        let last = reports.last().unwrap();
+        assert_eq!((last.written, last.total), (size, size));
+        assert_eq!(last.fraction(), 1.0);
+        assert_eq!(store.load().unwrap().len(), index.len());
+    }
+
+    #[test]
+    #[allow(deprecated)]
     fn test_save_and_load_uncompressed() {
         let temp_dir = TempDir::new().unwrap();
         let store = IndexStore::new(temp_dir.path()).with_compression(false);
@@ -612,6 +1096,113 @@ mod tests {
         assert_eq!(loaded.len(), index.len());
     }
 
+    #[test]
+    fn test_load_progressive() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = IndexStore::new(temp_dir.path());
+
+        let index = Index::new();
+        index.add_volume_records(
+            &VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS"),
+            make_test_records(),
+        );
+        let data = FileRecord::new(
+            FileId::new(1),
+            None,
+            VolumeId::new("D"),
+            "photo.jpg".to_string(),
+            "D:\\photo.jpg".to_string(),
+            false,
+        );
+        index.add_volume_records(
+            &VolumeInfo::new(VolumeId::new("D"), "D:", "NTFS"),
+            vec![data],
+        );
+        store.save(&index).unwrap();
+
+        let loaded = Index::new();
+        let mut started = Vec::new();
+        let mut ready = Vec::new();
+        store
+            .load_progressive(&loaded, |event| match event {
+                LoadProgress::Started(volumes) => started = volumes,
+                LoadProgress::VolumeReady(id) => {
+                    // Each volume is searchable as soon as it is reported
+                    let expected: usize = started
+                        .iter()
+                        .take(ready.len() + 1)
+                        .map(|v| v.records)
+                        .sum();
+                    assert_eq!(loaded.len(), expected);
+                    ready.push(id);
+                }
+            })
+            .unwrap();
+
+        assert_eq!(started.len(), 2);
+        assert_eq!(ready.len(), 2);
+        assert_eq!(loaded.len(), 3);
+    }
+
+    #[test]
+    fn test_folder_volumes_survive_reload() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = IndexStore::new(temp_dir.path());
+
+        let drive = VolumeInfo::new(VolumeId::new("D"), "D:", "NTFS");
+        let projects = drive.subtree("D:\\Projects");
+        let record = |volume: &VolumeInfo, id: u64, name: &str, path: &str| {
+            FileRecord::new(
+                FileId::new(id),
+                None,
+                volume.id.clone(),
+                name.to_string(),
+                path.to_string(),
+                false,
+            )
+        };
+        let index = Index::new();
+        index.add_volume_records(
+            &projects,
+            vec![record(&projects, 1, "main.rs", "D:\\Projects\\main.rs")],
+        );
+        index.add_volume_records(
+            &drive,
+            vec![record(&drive, 2, "a.jpg", "D:\\Photos\\a.jpg")],
+        );
+        store.save(&index).unwrap();
+
+        let loaded = store.load().unwrap();
+        let mut states = loaded.volume_states();
+        states.sort_by(|a, b| a.info.id.as_str().cmp(b.info.id.as_str()));
+        assert_eq!(states.len(), 2);
+        assert_eq!(states[0].info.id.as_str(), "D");
+        assert_eq!(states[1].info.root.as_deref(), Some("D:\\Projects"));
+        assert_eq!(states[1].record_count, 1);
+    }
+
+    #[test]
+    fn test_update_times_survive_reload() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = IndexStore::new(temp_dir.path());
+
+        let index = Index::new();
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        index.add_volume_records(&volume, make_test_records());
+        let scanned = Utc::now() - chrono::Duration::days(3);
+        let changed = Utc::now() - chrono::Duration::hours(2);
+        index.restore_update_times(&volume.id, Some(scanned), Some(changed));
+        store.save(&index).unwrap();
+
+        let loaded = store.load().unwrap();
+        let state = &loaded.volume_states()[0];
+        assert_eq!(state.last_full_scan, Some(scanned));
+        assert_eq!(state.last_event_applied, Some(changed));
+        assert_eq!(state.last_updated(), Some(changed));
+        assert!(state.is_stale(std::time::Duration::from_secs(3600)));
+        assert!(!state.is_stale(std::time::Duration::from_secs(24 * 3600)));
+    }
+
     #[test]
     fn test_load_nonexistent() {
         let temp_dir = TempDir::new().unwrap();
@@ -643,6 +1234,52 @@ mod tests {
         assert!(!store.exists());
     }
 
+    #[test]
+    fn test_stamp() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = IndexStore::new(temp_dir.path());
+        assert_eq!(store.stamp(), None);
+
+        let index = Index::new();
+        store.save(&index).unwrap();
+        let empty = store.stamp().unwrap();
+        assert_eq!(store.stamp(), Some(empty));
+
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        index.add_volume_records(&volume, make_test_records());
+        store.save(&index).unwrap();
+        let saved = store.stamp().unwrap();
+        assert_ne!(saved, empty);
+        assert!(saved.len > empty.len);
+
+        store.clear().unwrap();
+        assert_eq!(store.stamp(), None);
+    }
+
+    #[test]
+    fn test_read_only_store() {
+        let temp_dir = TempDir::new().unwrap();
+        let index = Index::new();
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        index.add_volume_records(&volume, make_test_records());
+        IndexStore::new(temp_dir.path()).save(&index).unwrap();
+        let index_path = temp_dir.path().join("glint.idx");
+        let modified = || fs::metadata(&index_path).unwrap().modified().unwrap();
+        let saved = modified();
+
+        let store = IndexStore::new(temp_dir.path()).with_read_only(true);
+        assert_eq!(store.load().unwrap().len(), 2);
+        for refused in [
+            store.save(&index),
+            store.clear(),
+            store.restore_from_backup().map(|_| ()),
+        ] {
+            assert!(matches!(refused, Err(GlintError::ReadOnly { .. })));
+        }
+        assert!(store.exists());
+        assert_eq!(modified(), saved);
+    }
+
     // CRC is validated indirectly via save/load paths.
 
     #[test]
@@ -0,0 +1,320 @@
+//! Pinned files and folders.
+//!
+//! Pins are keyed by volume and file ID rather than by path, so a pinned
+//! file stays pinned when it is renamed or moved within its volume. The
+//! last known path is stored alongside for display when the file is not
+//! (or no longer) in the index.
+//!
+//! Pinned items that match a query are listed before all other results;
+//! see [`PinStore::promote`].
+
+use crate::error::{GlintError, Result};
+use crate::index::Index;
+use crate::search::{SearchQuery, SearchResult};
+use crate::types::{FileId, FileRecord, VolumeId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name of the pin list inside the index directory.
+pub const PINS_FILE: &str = "pins.json";
+
+/// A pinned file or folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pin {
+    /// Volume the item lives on
+    pub volume_id: VolumeId,
+
+    /// File ID within the volume
+    pub file_id: FileId,
+
+    /// Path when the item was last seen
+    pub path: String,
+
+    /// True if the item is a directory
+    pub is_dir: bool,
+
+    /// When the item was pinned
+    pub pinned_at: DateTime<Utc>,
+}
+
+impl Pin {
+    fn matches(&self, volume_id: &VolumeId, file_id: FileId) -> bool {
+        &self.volume_id == volume_id && self.file_id == file_id
+    }
+}
+
+/// The list of pinned items, in the order they were pinned.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PinStore {
+    pins: Vec<Pin>,
+}
+
+impl PinStore {
+    /// Create an empty pin list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Path of the pin file inside an index directory.
+    pub fn path_in(dir: &Path) -> PathBuf {
+        dir.join(PINS_FILE)
+    }
+
+    /// Load pins, returning an empty list if the file does not exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+        let contents = fs::read_to_string(path)?;
+        serde_json::from_str(&contents).map_err(|e| GlintError::serialization(e.to_string()))
+    }
+
+    /// Save pins, replacing the previous file.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| GlintError::serialization(e.to_string()))?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// All pins, oldest first.
+    pub fn pins(&self) -> &[Pin] {
+        &self.pins
+    }
+
+    /// Number of pins.
+    pub fn len(&self) -> usize {
+        self.pins.len()
+    }
+
+    /// Check if nothing is pinned.
+    pub fn is_empty(&self) -> bool {
+        self.pins.is_empty()
+    }
+
+    /// Check if a record is pinned.
+    pub fn is_pinned(&self, record: &FileRecord) -> bool {
+        self.pins
+            .iter()
+            .any(|p| p.matches(&record.volume_id, record.id))
+    }
+
+    /// Pin a record. Returns false if it was already pinned.
+    pub fn pin(&mut self, record: &FileRecord) -> bool {
+        if self.is_pinned(record) {
+            return false;
+        }
+        self.pins.push(Pin {
+            volume_id: record.volume_id.clone(),
+            file_id: record.id,
+            path: record.path.clone(),
+            is_dir: record.is_dir,
+            pinned_at: Utc::now(),
+        });
+        true
+    }
+
+    /// Unpin an item. Returns false if it was not pinned.
+    pub fn unpin(&mut self, volume_id: &VolumeId, file_id: FileId) -> bool {
+        let before = self.pins.len();
+        self.pins.retain(|p| !p.matches(volume_id, file_id));
+        self.pins.len() != before
+    }
+
+    /// Pin or unpin a record. Returns true if the record is now pinned.
+    pub fn toggle(&mut self, record: &FileRecord) -> bool {
+        if self.unpin(&record.volume_id, record.id) {
+            false
+        } else {
+            self.pin(record)
+        }
+    }
+
+    /// Update stored paths from the index, picking up renames and moves.
+    ///
+    /// Pins whose file is not in the index are kept unchanged, since the
+    /// volume may simply not be indexed at the moment. Returns true if any
+    /// path changed.
+    pub fn refresh(&mut self, index: &Index) -> bool {
+        let mut changed = false;
+        for pin in &mut self.pins {
+            if let Some(record) = index.get(&pin.volume_id, pin.file_id) {
+                if record.path != pin.path {
+                    pin.path = record.path;
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Current index records of all pinned items that are in the index.
+    pub fn resolve(&self, index: &Index) -> Vec<FileRecord> {
+        self.pins
+            .iter()
+            .filter_map(|p| index.get(&p.volume_id, p.file_id))
+            .filter(|r| !r.name.is_empty())
+            .collect()
+    }
+
+    /// Move pinned items matching `query` to the top of `results`.
+    ///
+    /// Pinned matches are looked up in the index, so they are included even
+    /// if the search stopped before reaching them. The results are then
+    /// truncated to `limit`.
+    pub fn promote(
+        &self,
+        index: &Index,
+        query: &SearchQuery,
+        results: &mut Vec<SearchResult>,
+        limit: usize,
+    ) {
+        if self.pins.is_empty() {
+            return;
+        }
+
+        let pinned: Vec<SearchResult> = self
+            .resolve(index)
+            .into_iter()
+            .filter(|r| query.matches(r))
+            .map(|r| {
+                let score = results
+                    .iter()
+                    .find(|existing| {
+                        existing.record.volume_id == r.volume_id && existing.record.id == r.id
+                    })
+                    .map(|existing| existing.score)
+                    .unwrap_or(0);
+                let mut result = SearchResult::new(r, score);
+                result.captures = query.captures(&result.record);
+                result
+            })
+            .collect();
+
+        if pinned.is_empty() {
+            return;
+        }
+
+        let keys: HashSet<(VolumeId, FileId)> = pinned
+            .iter()
+            .map(|r| (r.record.volume_id.clone(), r.record.id))
+            .collect();
+        // Results of other indexes may share a pinned item's IDs
+        results.retain(|r| {
+            r.source.is_some() || !keys.contains(&(r.record.volume_id.clone(), r.record.id))
+        });
+        results.splice(0..0, pinned);
+        results.truncate(limit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{ChangeEvent, VolumeInfo};
+    use tempfile::TempDir;
+
+    fn index() -> Index {
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        let vol = VolumeId::new("C");
+        let records = vec![
+            FileRecord::new(
+                FileId::new(5),
+                None,
+                vol.clone(),
+                "docs".into(),
+                "C:\\docs".into(),
+                true,
+            ),
+            FileRecord::new(
+                FileId::new(10),
+                Some(FileId::new(5)),
+                vol.clone(),
+                "report.txt".into(),
+                "C:\\docs\\report.txt".into(),
+                false,
+            ),
+            FileRecord::new(
+                FileId::new(11),
+                Some(FileId::new(5)),
+                vol,
+                "notes.txt".into(),
+                "C:\\docs\\notes.txt".into(),
+                false,
+            ),
+        ];
+        let index = Index::new();
+        index.add_volume_records(&volume, records);
+        index
+    }
+
+    #[test]
+    fn test_pin_follows_rename() {
+        let index = index();
+        let mut pins = PinStore::new();
+        let notes = index.get(&VolumeId::new("C"), FileId::new(11)).unwrap();
+        assert!(pins.pin(&notes));
+        assert!(!pins.pin(&notes));
+
+        index.apply_change(ChangeEvent::renamed(
+            VolumeId::new("C"),
+            FileId::new(11),
+            Some(FileId::new(5)),
+            "notes.txt".into(),
+            "todo.txt".into(),
+            None,
+            false,
+            0,
+        ));
+
+        assert!(pins.refresh(&index));
+        assert_eq!(pins.pins()[0].path, "C:\\docs\\todo.txt");
+        assert_eq!(pins.resolve(&index)[0].name, "todo.txt");
+    }
+
+    #[test]
+    fn test_promote() {
+        let index = index();
+        let mut pins = PinStore::new();
+        pins.pin(&index.get(&VolumeId::new("C"), FileId::new(11)).unwrap());
+
+        // The search stops before reaching the pinned file
+        let query = SearchQuery::substring(".txt");
+        let mut results = index.search_limited(&query, 1);
+        assert_eq!(results[0].record.name, "report.txt");
+
+        pins.promote(&index, &query, &mut results, 2);
+        let names: Vec<_> = results.iter().map(|r| r.record.name.as_str()).collect();
+        assert_eq!(names, ["notes.txt", "report.txt"]);
+
+        // Pins that don't match the query are not added
+        let query = SearchQuery::substring("docs");
+        let mut results = index.search_limited(&query, 10);
+        pins.promote(&index, &query, &mut results, 10);
+        assert_eq!(results.len(), 1);
+    }
+
+    #[test]
+    fn test_save_load_toggle() {
+        let dir = TempDir::new().unwrap();
+        let path = PinStore::path_in(dir.path());
+        assert!(PinStore::load(&path).unwrap().is_empty());
+
+        let index = index();
+        let report = index.get(&VolumeId::new("C"), FileId::new(10)).unwrap();
+        let mut pins = PinStore::new();
+        assert!(pins.toggle(&report));
+        pins.save(&path).unwrap();
+
+        let mut loaded = PinStore::load(&path).unwrap();
+        assert!(loaded.is_pinned(&report));
+        assert!(!loaded.toggle(&report));
+        assert!(loaded.is_empty());
+    }
+}
@@ -0,0 +1,29 @@
+//! The types most programs using the index need, for one glob import.
+//!
+//! ```rust,no_run
+//! use glint_core::prelude::*;
+//!
+//! # fn main() -> Result<()> {
+//! let config = Config::load()?;
+//! let index = IndexStore::new(config.index_dir()?).load()?;
+//! let query = parse_query("report ext:pdf")?;
+//! for result in index.search_limited(&query, 20) {
+//!     println!("{}", result.record.path);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Everything here stays source compatible within a minor version; the rest
+//! of the public modules may change as the frontends need. Modules only the
+//! crate itself uses are private, with the types the API hands out
+//! re-exported at the crate root.
+
+pub use crate::backend::{ChangeEvent, ChangeHandler, ChangeKind, FileSystemBackend, VolumeInfo};
+pub use crate::config::Config;
+pub use crate::error::{GlintError, Result};
+pub use crate::index::{Index, IndexSnapshot};
+#[cfg(feature = "persistence")]
+pub use crate::persistence::IndexStore;
+pub use crate::search::{parse_query, SearchFilter, SearchPage, SearchQuery, SearchResult};
+pub use crate::types::{FileId, FileRecord, VolumeId};
@@ -0,0 +1,1171 @@
+//! The index's records, kept as columns rather than as `FileRecord`s.
+//!
+//! A `Vec<FileRecord>` keeps every field of a record together, spread over
+//! several heap allocations, so testing one name against a query means
+//! loading a few cache lines per record, and each record costs its
+//! allocations' overhead on top of its text. [`Records`] keeps each field
+//! in one array instead, indexed by `u32` record position:
+//!
+//! - paths, back to back in one buffer; a record's name is the end of its
+//!   path, so only its length is kept, and the rare name that isn't is kept
+//!   on the side
+//! - lowercase names, back to back in another buffer, with each record's
+//!   span; lowercase paths in a third while they are cached
+//!   ([`Records::set_path_cache`])
+//! - flags: folder, live (not a deleted record's blank), online-only
+//! - file and parent IDs, sizes, times, interned extensions and volumes
+//! - the position of each record's parent folder
+//!
+//! A changed name or path is written again at the end of its buffer; the
+//! paths are compacted once more than half of them is stale, the names
+//! when records are dropped. On the `index_memory` benchmark a million
+//! records take about 266 bytes each in a few dozen allocations, down from
+//! 386 bytes in 6 million allocations as `FileRecord`s.
+//!
+//! `FileRecord`s are only built for records leaving the index
+//! ([`Records::cloned`]), or lent one at a time where a whole record is
+//! needed ([`Records::visit`], [`Records::with_record`]).
+//!
+//! Records are found by volume and file ID ([`Records::position`]) through
+//! one map of `u32` positions, about 30 bytes a record. A parent that isn't
+//! indexed yet when its child is added, as scans often go, is looked up
+//! again by [`Records::link_parents`].
+//!
+//! It also keeps each record's position by the hash of its volume and
+//! lowercase path, so [`Records::find_path`] turns a path back into a
+//! record without a scan. That map costs about 20 bytes a record
+//! ([`Records::path_lookup_bytes`]).
+//!
+//! A folder's children are found from each record's own parent ID rather
+//! than from lists kept per folder: [`Records::children`] reads a sorted
+//! view of (volume, parent, position), built on first use and again once
+//! enough records were added or moved since. Records added or moved in the
+//! meantime are checked one by one, and every entry is checked against the
+//! record before it is returned, so a stale view can be slow but never
+//! wrong. The view costs 12 bytes a record.
+//!
+//! Search loops read a record's [`Row`] first and only build the whole
+//! record when the row can't settle the query, such as for path patterns.
+//! Plain text in names is looked for in one pass over the whole names
+//! buffer ([`Records::names_containing`]) rather than name by name.
+
+use crate::extensions::{self, ExtId};
+use crate::types::{to_lower, FileId, FileRecord, VolumeId};
+use chrono::{DateTime, Utc};
+use memchr::memmem::Finder;
+use parking_lot::Mutex;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::sync::Arc;
+
+const DIR: u8 = 1;
+const LIVE: u8 = 1 << 1;
+const CLOUD: u8 = 1 << 2;
+/// The record has a parent ID
+const HAS_PARENT: u8 = 1 << 3;
+/// The record's name isn't the end of its path, see `Records::odd_names`
+const ODD_NAME: u8 = 1 << 4;
+
+/// Stored in place of a missing size.
+const NO_SIZE: u64 = u64::MAX;
+
+/// Stored in place of a parent that isn't indexed.
+const NO_PARENT: u32 = u32::MAX;
+
+/// Records added or moved since the child view was built that are checked
+/// one by one before it is built again, at least; a 64th of the records
+/// for larger indexes.
+const VIEW_SLACK: usize = 4096;
+
+thread_local! {
+    /// The record [`Records::with_record`] lends out, kept to reuse its
+    /// strings' allocations.
+    static LENT: RefCell<Option<FileRecord>> = const { RefCell::new(None) };
+}
+
+/// A record's column values, see [`Records::row`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Row<'a> {
+    names: &'a str,
+    name_span: (u32, u32),
+    pub is_dir: bool,
+    /// False for the blanked records deleted files leave behind
+    pub live: bool,
+    pub cloud: bool,
+    pub size: Option<u64>,
+    pub ext_id: ExtId,
+    /// Position of the record's volume in [`Records::volume_mask`]
+    pub volume: usize,
+}
+
+/// Strings kept back to back in one buffer, each found by where it starts
+/// and its length. A changed string is written again at the end; the
+/// buffer is compacted once more than half of it is stale.
+#[derive(Debug, Clone, Default)]
+struct Texts {
+    text: String,
+    starts: Vec<usize>,
+    lens: Vec<u32>,
+    /// Bytes of `text` no string uses any more
+    stale: usize,
+}
+
+/// The records' fields, one column each.
+#[derive(Debug, Clone)]
+pub(crate) struct Records {
+    file_ids: Vec<u64>,
+    /// Parent ID of the records with the `HAS_PARENT` flag
+    parent_ids: Vec<u64>,
+    /// Full paths as written
+    paths: Texts,
+    /// Length of each record's name, which ends its path unless the record
+    /// has the `ODD_NAME` flag
+    name_lens: Vec<u16>,
+    /// Names of the records with the `ODD_NAME` flag: ones that don't end
+    /// their path, or too long for `name_lens`
+    odd_names: HashMap<u32, Box<str>>,
+    /// Lowercase paths, while they are cached
+    lower_paths: Option<Texts>,
+
+    /// Lowercase names, back to back; a renamed record's old name stays
+    /// behind until records are dropped
+    names: String,
+    /// Start and end of each record's name in `names`
+    name_spans: Vec<(u32, u32)>,
+    /// Start of every name in `names` and the record it was written for, in
+    /// buffer order; renamed records leave their old entries behind
+    name_owners: Vec<(u32, u32)>,
+    flags: Vec<u8>,
+    sizes: Vec<u64>,
+    exts: Vec<ExtId>,
+    modified: Vec<Option<DateTime<Utc>>>,
+    created: Vec<Option<DateTime<Utc>>>,
+    /// Each record's volume, as a position in `volume_ids`
+    volumes: Vec<u16>,
+    volume_ids: Vec<VolumeId>,
+    /// Position of each record's parent, or `NO_PARENT`; may be stale,
+    /// see [`Records::parent`]
+    parents: Vec<u32>,
+    /// Records with a parent ID whose parent wasn't found when they were
+    /// added or moved
+    unlinked: Vec<u32>,
+    /// Position of the record with each volume slot and file ID; a deleted
+    /// file's blank is dropped from it
+    by_id: HashMap<(u16, u64), u32>,
+    /// Position of the record with each hash of volume and lowercase path
+    /// ([`path_hash`]); deleted records and nameless roots are left out
+    by_path: HashMap<u64, u32>,
+    /// Records whose path hash was already taken when they were added, by
+    /// a hash collision or a stale record of the same path
+    path_overflow: Vec<u32>,
+    /// Records whose parent or volume changed since the child view was
+    /// built, so that it may list them under another folder
+    moved: Vec<u32>,
+    child_view: ChildViewCell,
+}
+
+/// The records with a parent, sorted by volume slot, parent ID and position.
+#[derive(Debug, Default)]
+struct ChildView {
+    order: Vec<u32>,
+    /// Parent ID of each entry of `order`
+    parents: Vec<u64>,
+    /// Where each volume slot's entries start in `order`, and where the
+    /// last one ends
+    volume_starts: Vec<usize>,
+    /// Number of records when built; the ones after it aren't in the view
+    built_len: usize,
+    /// Length of `Records::moved` when built; the moves before are in it
+    moved_seen: usize,
+}
+
+/// The child view, if built; shared by clones of the records, which are
+/// the same until changed.
+#[derive(Debug, Default)]
+struct ChildViewCell(Mutex<Option<Arc<ChildView>>>);
+
+impl Clone for ChildViewCell {
+    fn clone(&self) -> Self {
+        ChildViewCell(Mutex::new(self.0.lock().clone()))
+    }
+}
+
+impl<'a> Row<'a> {
+    /// The record's lowercase name. Sliced on demand, since most rows are
+    /// settled without it.
+    #[inline]
+    pub fn name_lower(&self) -> &'a str {
+        let (start, end) = self.name_span;
+        &self.names[start as usize..end as usize]
+    }
+}
+
+impl Texts {
+    fn with_capacity(capacity: usize) -> Self {
+        Texts {
+            starts: Vec::with_capacity(capacity),
+            lens: Vec::with_capacity(capacity),
+            ..Texts::default()
+        }
+    }
+
+    fn get(&self, idx: usize) -> &str {
+        let start = self.starts[idx];
+        &self.text[start..start + self.lens[idx] as usize]
+    }
+
+    fn push(&mut self, text: &str) {
+        self.starts.push(self.text.len());
+        self.lens
+            .push(u32::try_from(text.len()).expect("string over 4 GiB"));
+        self.text.push_str(text);
+    }
+
+    fn set(&mut self, idx: usize, text: &str) {
+        if self.get(idx) == text {
+            return;
+        }
+        self.stale += self.lens[idx] as usize;
+        self.starts[idx] = self.text.len();
+        self.lens[idx] = u32::try_from(text.len()).expect("string over 4 GiB");
+        self.text.push_str(text);
+        if self.stale > self.text.len() / 2 {
+            self.compact();
+        }
+    }
+
+    /// Write the strings again without the stale bytes between them.
+    fn compact(&mut self) {
+        let mut text = String::with_capacity(self.text.len() - self.stale);
+        for idx in 0..self.starts.len() {
+            let start = text.len();
+            text.push_str(self.get(idx));
+            self.starts[idx] = start;
+        }
+        self.text = text;
+        self.stale = 0;
+    }
+}
+
+impl Default for Records {
+    fn default() -> Self {
+        Records::with_capacity(0)
+    }
+}
+
+impl Records {
+    /// Empty records with room for `capacity`, caching lowercase paths as
+    /// the index does by default.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Records {
+            file_ids: Vec::with_capacity(capacity),
+            parent_ids: Vec::with_capacity(capacity),
+            paths: Texts::with_capacity(capacity),
+            name_lens: Vec::with_capacity(capacity),
+            odd_names: HashMap::new(),
+            lower_paths: Some(Texts::with_capacity(capacity)),
+            names: String::new(),
+            name_spans: Vec::with_capacity(capacity),
+            name_owners: Vec::with_capacity(capacity),
+            flags: Vec::with_capacity(capacity),
+            sizes: Vec::with_capacity(capacity),
+            exts: Vec::with_capacity(capacity),
+            modified: Vec::with_capacity(capacity),
+            created: Vec::with_capacity(capacity),
+            volumes: Vec::with_capacity(capacity),
+            volume_ids: Vec::new(),
+            parents: Vec::with_capacity(capacity),
+            unlinked: Vec::new(),
+            by_id: HashMap::with_capacity(capacity),
+            by_path: HashMap::with_capacity(capacity),
+            path_overflow: Vec::new(),
+            moved: Vec::new(),
+            child_view: ChildViewCell::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.file_ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.file_ids.is_empty()
+    }
+
+    /// Add a record at the end.
+    pub fn push(&mut self, mut record: FileRecord) {
+        let idx = self.len();
+        let name_lower = take_lower_name(&mut record);
+        let span = self.push_name(idx, &name_lower);
+        self.name_spans.push(span);
+        let volume = self.volume_slot(&record.volume_id);
+        self.volumes.push(volume);
+        self.file_ids.push(record.id.as_u64());
+        self.parent_ids
+            .push(record.parent_id.map_or(0, |parent| parent.as_u64()));
+        self.paths.push(&record.path);
+        if let Some(lower_paths) = &mut self.lower_paths {
+            lower_paths.push(&take_lower_path(&mut record));
+        }
+        self.name_lens.push(0);
+        let odd = self.set_name(idx, &record.name);
+        self.flags.push(flags_of(&record) | odd);
+        self.sizes.push(record.size.unwrap_or(NO_SIZE));
+        self.exts.push(record.ext_id);
+        self.modified.push(record.modified);
+        self.created.push(record.created);
+        if let Some(hash) = self.path_key(idx) {
+            self.link_path(idx, hash);
+        }
+        if let Some(key) = self.id_key(idx) {
+            self.by_id.insert(key, idx as u32);
+        }
+        self.parents.push(NO_PARENT);
+        self.link_parent(idx);
+    }
+
+    /// Change the record at `idx` with `f`, if there is one.
+    ///
+    /// `f` gets a copy of the record with empty lowercase caches; the
+    /// lowercase name and path are worked out again from what it leaves.
+    pub fn update(&mut self, idx: usize, f: impl FnOnce(&mut FileRecord)) {
+        if idx >= self.len() {
+            return;
+        }
+        let path_before = self.path_key(idx);
+        let key_before = self.id_key(idx);
+        let (volume_before, parent_before) = (self.volumes[idx], self.parent_id(idx));
+        let mut record = self.cloned(idx);
+        record.name_lower = String::new();
+        record.clear_path_cache();
+        f(&mut record);
+
+        if record.name != self.name(idx) {
+            let name = take_lower_name(&mut record);
+            self.name_spans[idx] = match name.is_empty() {
+                true => (0, 0),
+                false => self.push_name(idx, &name),
+            };
+            record.ext_id = extensions::of_lower_name(&name);
+        }
+        if record.path != self.paths.get(idx) {
+            self.paths.set(idx, &record.path);
+            if let Some(lower_paths) = &mut self.lower_paths {
+                lower_paths.set(idx, &to_lower(&record.path));
+            }
+        }
+        let odd = self.set_name(idx, &record.name);
+        self.flags[idx] = flags_of(&record) | odd;
+        self.sizes[idx] = record.size.unwrap_or(NO_SIZE);
+        self.exts[idx] = record.ext_id;
+        self.modified[idx] = record.modified;
+        self.created[idx] = record.created;
+        self.file_ids[idx] = record.id.as_u64();
+        self.parent_ids[idx] = record.parent_id.map_or(0, |parent| parent.as_u64());
+        if record.volume_id != self.volume_ids[usize::from(volume_before)] {
+            self.volumes[idx] = self.volume_slot(&record.volume_id);
+        }
+
+        let key_after = self.id_key(idx);
+        if key_after != key_before {
+            if let Some(key) = key_before {
+                if self.by_id.get(&key) == Some(&(idx as u32)) {
+                    self.by_id.remove(&key);
+                }
+            }
+            if let Some(key) = key_after {
+                self.by_id.insert(key, idx as u32);
+            }
+        }
+        if record.parent_id != parent_before || self.volumes[idx] != volume_before {
+            self.note_move(idx);
+            self.link_parent(idx);
+        }
+        let path_after = self.path_key(idx);
+        if path_after != path_before {
+            if let Some(hash) = path_before {
+                self.unlink_path(idx, hash);
+            }
+            if let Some(hash) = path_after {
+                self.link_path(idx, hash);
+            }
+        }
+    }
+
+    /// Keep only the records `keep` accepts, returning how many were
+    /// dropped. The columns are written anew, which also drops old names
+    /// and paths.
+    pub fn retain(&mut self, keep: impl Fn(&FileRecord) -> bool) -> usize {
+        let mut record = None;
+        let kept: Vec<bool> = (0..self.len())
+            .map(|idx| keep(self.lend(idx, &mut record)))
+            .collect();
+        let removed = kept.iter().filter(|&&kept| !kept).count();
+        if removed > 0 {
+            let old = std::mem::replace(self, Records::with_capacity(kept.len() - removed));
+            if old.lower_paths.is_none() {
+                self.lower_paths = None;
+            }
+            for idx in (0..kept.len()).filter(|&idx| kept[idx]) {
+                self.push(old.cloned(idx));
+            }
+            self.link_parents();
+        }
+        removed
+    }
+
+    /// Drop every record, keeping whether lowercase paths are cached.
+    pub fn clear(&mut self) {
+        let cache_paths = self.lower_paths.is_some();
+        *self = Records::default();
+        self.set_path_cache(cache_paths);
+    }
+
+    /// Start or stop caching every record's lowercase path.
+    pub fn set_path_cache(&mut self, enabled: bool) {
+        if !enabled {
+            self.lower_paths = None;
+        } else if self.lower_paths.is_none() {
+            let mut lower_paths = Texts::with_capacity(self.len());
+            for idx in 0..self.len() {
+                lower_paths.push(&to_lower(self.paths.get(idx)));
+            }
+            self.lower_paths = Some(lower_paths);
+        }
+    }
+
+    /// The column values of the record at `idx`.
+    #[inline]
+    pub fn row(&self, idx: usize) -> Row<'_> {
+        let flags = self.flags[idx];
+        let size = self.sizes[idx];
+        Row {
+            names: &self.names,
+            name_span: self.name_spans[idx],
+            is_dir: flags & DIR != 0,
+            live: flags & LIVE != 0,
+            cloud: flags & CLOUD != 0,
+            size: (size != NO_SIZE).then_some(size),
+            ext_id: self.exts[idx],
+            volume: usize::from(self.volumes[idx]),
+        }
+    }
+
+    pub fn id(&self, idx: usize) -> FileId {
+        FileId::new(self.file_ids[idx])
+    }
+
+    pub fn parent_id(&self, idx: usize) -> Option<FileId> {
+        (self.flags[idx] & HAS_PARENT != 0).then(|| FileId::new(self.parent_ids[idx]))
+    }
+
+    pub fn volume_id(&self, idx: usize) -> &VolumeId {
+        &self.volume_ids[usize::from(self.volumes[idx])]
+    }
+
+    /// The record's name as written.
+    pub fn name(&self, idx: usize) -> &str {
+        if self.flags[idx] & ODD_NAME != 0 {
+            return &self.odd_names[&(idx as u32)];
+        }
+        let path = self.paths.get(idx);
+        &path[path.len() - usize::from(self.name_lens[idx])..]
+    }
+
+    pub fn path(&self, idx: usize) -> &str {
+        self.paths.get(idx)
+    }
+
+    /// The lowercase path, from the cache if paths are cached.
+    pub fn path_lower(&self, idx: usize) -> Cow<'_, str> {
+        match &self.lower_paths {
+            Some(lower_paths) => Cow::Borrowed(lower_paths.get(idx)),
+            None => Cow::Owned(to_lower(self.paths.get(idx))),
+        }
+    }
+
+    /// Whether the record at `idx` is the blank a deleted file leaves
+    /// behind: no name, and unlike a volume root, no path either.
+    pub fn is_blank(&self, idx: usize) -> bool {
+        self.flags[idx] & LIVE == 0 && self.paths.lens[idx] == 0
+    }
+
+    /// The record at `idx` as a `FileRecord`, with its lowercase name
+    /// filled in, and its lowercase path if paths are cached.
+    pub fn cloned(&self, idx: usize) -> FileRecord {
+        let row = self.row(idx);
+        FileRecord {
+            id: self.id(idx),
+            parent_id: self.parent_id(idx),
+            volume_id: self.volume_id(idx).clone(),
+            name: self.name(idx).to_string(),
+            name_lower: row.name_lower().to_string(),
+            ext_id: row.ext_id,
+            path_lower: match &self.lower_paths {
+                Some(lower_paths) => lower_paths.get(idx).to_string(),
+                None => String::new(),
+            },
+            path: self.paths.get(idx).to_string(),
+            is_dir: row.is_dir,
+            size: row.size,
+            modified: self.modified[idx],
+            created: self.created[idx],
+            cloud: row.cloud,
+        }
+    }
+
+    /// Call `f` with the record at `idx`, copied into a record kept per
+    /// thread rather than cloned; `f` must not call this again.
+    pub fn with_record<T>(&self, idx: usize, f: impl FnOnce(&FileRecord) -> T) -> T {
+        LENT.with(|lent| f(self.lend(idx, &mut lent.borrow_mut())))
+    }
+
+    /// Call `f` with every live record and its position, in order, copied
+    /// into one record in turn.
+    pub fn visit(&self, mut f: impl FnMut(usize, &FileRecord)) {
+        let mut record = None;
+        for idx in 0..self.len() {
+            if self.flags[idx] & LIVE != 0 {
+                f(idx, self.lend(idx, &mut record));
+            }
+        }
+    }
+
+    /// Copy the record at `idx` into `lent`, reusing its strings.
+    fn lend<'r>(&self, idx: usize, lent: &'r mut Option<FileRecord>) -> &'r FileRecord {
+        let record = lent.get_or_insert_with(|| self.cloned(idx));
+        let row = self.row(idx);
+        let copy = |to: &mut String, from: &str| {
+            to.clear();
+            to.push_str(from);
+        };
+        record.id = self.id(idx);
+        record.parent_id = self.parent_id(idx);
+        if record.volume_id != *self.volume_id(idx) {
+            record.volume_id = self.volume_id(idx).clone();
+        }
+        copy(&mut record.name, self.name(idx));
+        copy(&mut record.name_lower, row.name_lower());
+        record.ext_id = row.ext_id;
+        copy(&mut record.path, self.paths.get(idx));
+        match &self.lower_paths {
+            Some(lower_paths) => copy(&mut record.path_lower, lower_paths.get(idx)),
+            None => record.path_lower.clear(),
+        }
+        record.is_dir = row.is_dir;
+        record.size = row.size;
+        record.modified = self.modified[idx];
+        record.created = self.created[idx];
+        record.cloud = row.cloud;
+        record
+    }
+
+    /// Consecutive ranges of at most `size` positions covering all records,
+    /// to search in parallel.
+    pub fn chunks(&self, size: usize) -> Vec<Range<usize>> {
+        (0..self.len())
+            .step_by(size)
+            .map(|start| start..(start + size).min(self.len()))
+            .collect()
+    }
+
+    /// The position of the record with this file ID on `volume`, unless it
+    /// was deleted.
+    pub fn position(&self, volume: &VolumeId, id: FileId) -> Option<usize> {
+        let slot = self.volume_ids.iter().position(|v| v == volume)?;
+        let idx = self.by_id.get(&(slot as u16, id.as_u64()))?;
+        Some(*idx as usize)
+    }
+
+    /// The position of the folder holding the record at `idx`, if it is in
+    /// the records.
+    ///
+    /// Read from the parent column, unless the record there was deleted or
+    /// replaced since, or the parent was added after the record; then the
+    /// parent is looked up by ID.
+    pub fn parent(&self, idx: usize) -> Option<usize> {
+        let parent = self.parent_id(idx)?;
+        let linked = self.parents[idx] as usize;
+        let holds = |p: usize| {
+            self.volumes[p] == self.volumes[idx]
+                && self.file_ids[p] == parent.as_u64()
+                && !self.is_blank(p)
+        };
+        if self.parents[idx] != NO_PARENT && holds(linked) {
+            return Some(linked);
+        }
+        self.find_parent(idx)
+    }
+
+    /// Fill in the parents that weren't in the records yet when their
+    /// children were added, such as folders scanned after their contents.
+    pub fn link_parents(&mut self) {
+        let mut unlinked = std::mem::take(&mut self.unlinked);
+        unlinked.sort_unstable();
+        unlinked.dedup();
+        unlinked.retain(|&idx| match self.find_parent(idx as usize) {
+            Some(parent) => {
+                self.parents[idx as usize] = parent as u32;
+                false
+            }
+            None => self.parent_id(idx as usize).is_some(),
+        });
+        self.unlinked = unlinked;
+    }
+
+    /// Set the parent column of the record at `idx`, or note it for
+    /// [`Records::link_parents`] if its parent isn't found.
+    fn link_parent(&mut self, idx: usize) {
+        match self.find_parent(idx) {
+            Some(parent) => self.parents[idx] = parent as u32,
+            None => {
+                self.parents[idx] = NO_PARENT;
+                if self.parent_id(idx).is_some() {
+                    self.unlinked.push(idx as u32);
+                }
+            }
+        }
+    }
+
+    fn find_parent(&self, idx: usize) -> Option<usize> {
+        let parent = self.parent_id(idx)?;
+        let found = self.by_id.get(&(self.volumes[idx], parent.as_u64()))?;
+        Some(*found as usize)
+    }
+
+    /// Whether each volume of [`Row::volume`] is left out of searches,
+    /// given the IDs of the disabled volumes.
+    pub fn volume_mask(&self, disabled: &[String]) -> Vec<bool> {
+        self.volume_ids
+            .iter()
+            .map(|id| disabled.iter().any(|d| d == id.as_str()))
+            .collect()
+    }
+
+    /// The records whose lowercase name contains `needle`, as a bitset of
+    /// positions (bit `i % 64` of word `i / 64`).
+    ///
+    /// Searches the names buffer in one pass; a hit that runs across the
+    /// end of a name, or lies in a renamed record's old name, is skipped.
+    pub fn names_containing(&self, needle: &str) -> Vec<u64> {
+        let mut hits = vec![0u64; self.len().div_ceil(64)];
+        let haystack = self.names.as_bytes();
+        let finder = Finder::new(needle);
+        let (mut pos, mut owner) = (0, 0);
+        while let Some(found) = finder.find(&haystack[pos..]) {
+            let at = pos + found;
+            // Hits come in buffer order, so gallop on from the last owner
+            let rest = &self.name_owners[owner..];
+            let mut step = 1;
+            while step < rest.len() && rest[step].0 as usize <= at {
+                step *= 2;
+            }
+            let within = &rest[..step.min(rest.len())];
+            owner += within.partition_point(|&(start, _)| start as usize <= at) - 1;
+            let (start, idx) = self.name_owners[owner];
+            let (span_start, span_end) = self.name_spans[idx as usize];
+            if span_start == start && at + needle.len() <= span_end as usize {
+                hits[idx as usize / 64] |= 1 << (idx % 64);
+                // One hit per name is enough
+                pos = span_end as usize;
+            } else {
+                pos = at + 1;
+            }
+        }
+        hits
+    }
+
+    /// The position of the live record with this path on `volume`, or on
+    /// any volume, the first one indexed first; `path_lower` is lowercase,
+    /// as [`normalize_path`] leaves it.
+    pub fn find_path(&self, volume: Option<&VolumeId>, path_lower: &str) -> Option<usize> {
+        let slots = match volume {
+            Some(volume) => {
+                let slot = self.volume_ids.iter().position(|id| id == volume)?;
+                slot..slot + 1
+            }
+            None => 0..self.volume_ids.len(),
+        };
+        let is_at = |slot: usize, idx: u32| {
+            let idx = idx as usize;
+            let found = usize::from(self.volumes[idx]) == slot
+                && self.flags[idx] & LIVE != 0
+                && self.path_lower(idx) == path_lower;
+            found.then_some(idx)
+        };
+        slots.into_iter().find_map(|slot| {
+            let hash = path_hash(slot as u16, path_lower);
+            self.by_path
+                .get(&hash)
+                .and_then(|&idx| is_at(slot, idx))
+                .or_else(|| self.path_overflow.iter().find_map(|&idx| is_at(slot, idx)))
+        })
+    }
+
+    /// Positions of the live records inside the folder `parent` on `volume`,
+    /// in order.
+    pub fn children(&self, volume: &VolumeId, parent: FileId) -> Vec<usize> {
+        let Some(slot) = self.volume_ids.iter().position(|id| id == volume) else {
+            return Vec::new();
+        };
+        let is_child = |idx: usize| {
+            usize::from(self.volumes[idx]) == slot
+                && self.parent_id(idx) == Some(parent)
+                && self.flags[idx] & LIVE != 0
+        };
+
+        let view = self.child_view();
+        let mut found = Vec::new();
+        if let Some(bounds) = view.volume_starts.get(slot..slot + 2) {
+            let parents = &view.parents[bounds[0]..bounds[1]];
+            let start = bounds[0] + parents.partition_point(|&p| p < parent.as_u64());
+            let end = bounds[0] + parents.partition_point(|&p| p <= parent.as_u64());
+            found.extend(view.order[start..end].iter().map(|&idx| idx as usize));
+            found.retain(|&idx| is_child(idx));
+        }
+        let listed = found.len();
+        let moved = self.moved[view.moved_seen..]
+            .iter()
+            .map(|&idx| idx as usize);
+        found.extend(
+            moved
+                .chain(view.built_len..self.len())
+                .filter(|&idx| is_child(idx)),
+        );
+        if found.len() > listed {
+            found.sort_unstable();
+            found.dedup();
+        }
+        found
+    }
+
+    /// The child view, built again if too many records were added or moved
+    /// since it was.
+    fn child_view(&self) -> Arc<ChildView> {
+        let mut cell = self.child_view.0.lock();
+        if let Some(view) = cell.as_ref() {
+            let pending = self.len() - view.built_len + self.moved.len() - view.moved_seen;
+            if pending <= VIEW_SLACK.max(view.built_len / 64) {
+                return Arc::clone(view);
+            }
+        }
+        let view = Arc::new(self.build_child_view());
+        *cell = Some(Arc::clone(&view));
+        view
+    }
+
+    fn build_child_view(&self) -> ChildView {
+        let mut entries: Vec<(u16, u64, u32)> = (0..self.len())
+            .filter_map(|idx| {
+                let parent = self.parent_id(idx)?;
+                (self.flags[idx] & LIVE != 0).then_some((
+                    self.volumes[idx],
+                    parent.as_u64(),
+                    idx as u32,
+                ))
+            })
+            .collect();
+        entries.sort_unstable();
+        let volume_starts = (0..=self.volume_ids.len())
+            .map(|slot| entries.partition_point(|&(volume, _, _)| usize::from(volume) < slot))
+            .collect();
+        ChildView {
+            order: entries.iter().map(|&(_, _, idx)| idx).collect(),
+            parents: entries.iter().map(|&(_, parent, _)| parent).collect(),
+            volume_starts,
+            built_len: self.len(),
+            moved_seen: self.moved.len(),
+        }
+    }
+
+    /// Remember that the record at `idx` changed folder, for the child view
+    /// to check; once that is too many, drop the view to build it anew.
+    fn note_move(&mut self, idx: usize) {
+        let view = self.child_view.0.get_mut();
+        let Some(built) = view.as_ref() else {
+            return;
+        };
+        if self.moved.len() - built.moved_seen < VIEW_SLACK.max(built.built_len / 64) {
+            self.moved.push(idx as u32);
+        } else {
+            *view = None;
+            self.moved.clear();
+        }
+    }
+
+    /// Approximate heap memory used to look records up by path.
+    pub fn path_lookup_bytes(&self) -> usize {
+        // hashbrown keeps one control byte per bucket next to each entry
+        let entry = std::mem::size_of::<(u64, u32)>() + 1;
+        self.by_path.capacity() * entry + self.path_overflow.capacity() * 4
+    }
+
+    /// The record's key in the ID lookup, unless it is a deleted file's
+    /// blank.
+    fn id_key(&self, idx: usize) -> Option<(u16, u64)> {
+        (!self.is_blank(idx)).then(|| (self.volumes[idx], self.file_ids[idx]))
+    }
+
+    /// The record's key in the path lookup, if it is looked up: deleted
+    /// records have neither name nor path, and volume roots have no name.
+    fn path_key(&self, idx: usize) -> Option<u64> {
+        if self.flags[idx] & LIVE == 0 || self.paths.lens[idx] == 0 {
+            return None;
+        }
+        Some(path_hash(self.volumes[idx], &self.path_lower(idx)))
+    }
+
+    fn link_path(&mut self, idx: usize, hash: u64) {
+        let idx = u32::try_from(idx).expect("more than 4 billion records");
+        match self.by_path.entry(hash) {
+            Entry::Vacant(slot) => {
+                slot.insert(idx);
+            }
+            Entry::Occupied(_) => self.path_overflow.push(idx),
+        }
+    }
+
+    /// Forget that the record at `idx` had this path hash, letting a record
+    /// with the same hash waiting in the overflow take its place.
+    fn unlink_path(&mut self, idx: usize, hash: u64) {
+        if self.by_path.get(&hash) != Some(&(idx as u32)) {
+            self.path_overflow.retain(|&other| other as usize != idx);
+            return;
+        }
+        self.by_path.remove(&hash);
+        let waiting = self
+            .path_overflow
+            .iter()
+            .position(|&other| self.path_key(other as usize) == Some(hash));
+        if let Some(pos) = waiting {
+            let other = self.path_overflow.swap_remove(pos);
+            self.by_path.insert(hash, other);
+        }
+    }
+
+    fn push_name(&mut self, idx: usize, name_lower: &str) -> (u32, u32) {
+        // 4 GiB of names is hundreds of millions of files, far past what
+        // one index holds in memory anyway
+        let offset = |n: usize| u32::try_from(n).expect("names column exceeds 4 GiB");
+        let start = offset(self.names.len());
+        self.names.push_str(name_lower);
+        if !name_lower.is_empty() {
+            let idx = u32::try_from(idx).expect("more than 4 billion records");
+            self.name_owners.push((start, idx));
+        }
+        (start, offset(self.names.len()))
+    }
+
+    /// Keep the record's name as the end of its path if it is that, or on
+    /// the side; returns the `ODD_NAME` flag for the latter.
+    fn set_name(&mut self, idx: usize, name: &str) -> u8 {
+        let key = idx as u32;
+        match u16::try_from(name.len()) {
+            Ok(len) if self.paths.get(idx).ends_with(name) => {
+                self.name_lens[idx] = len;
+                if !self.odd_names.is_empty() {
+                    self.odd_names.remove(&key);
+                }
+                0
+            }
+            _ => {
+                self.name_lens[idx] = 0;
+                self.odd_names.insert(key, name.into());
+                ODD_NAME
+            }
+        }
+    }
+
+    fn volume_slot(&mut self, volume_id: &VolumeId) -> u16 {
+        // Records mostly arrive a volume at a time
+        if let Some(&last) = self.volumes.last() {
+            if self.volume_ids[usize::from(last)] == *volume_id {
+                return last;
+            }
+        }
+        let slot = match self.volume_ids.iter().position(|id| id == volume_id) {
+            Some(slot) => slot,
+            None => {
+                self.volume_ids.push(volume_id.clone());
+                self.volume_ids.len() - 1
+            }
+        };
+        u16::try_from(slot).expect("more than 65535 volumes")
+    }
+}
+
+/// Lowercase `path` and write it the way record paths are: backslashes,
+/// and no separator at the end.
+pub(crate) fn normalize_path(path: &str) -> String {
+    to_lower(path.trim_end_matches(['\\', '/'])).replace('/', "\\")
+}
+
+/// Hash of a record's volume slot and its lowercase path.
+fn path_hash(volume: u16, path_lower: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    volume.hash(&mut hasher);
+    path_lower.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Take the record's lowercase name out of its cache, or compute it if the
+/// cache is empty.
+fn take_lower_name(record: &mut FileRecord) -> String {
+    match std::mem::take(&mut record.name_lower) {
+        name_lower if name_lower.is_empty() => to_lower(&record.name),
+        name_lower => name_lower,
+    }
+}
+
+/// Take the record's lowercase path out of its cache, or compute it if the
+/// cache is empty.
+fn take_lower_path(record: &mut FileRecord) -> String {
+    match std::mem::take(&mut record.path_lower) {
+        path_lower if path_lower.is_empty() => to_lower(&record.path),
+        path_lower => path_lower,
+    }
+}
+
+fn flags_of(record: &FileRecord) -> u8 {
+    let mut flags = 0;
+    if record.is_dir {
+        flags |= DIR;
+    }
+    if !record.name.is_empty() {
+        flags |= LIVE;
+    }
+    if record.cloud {
+        flags |= CLOUD;
+    }
+    if record.parent_id.is_some() {
+        flags |= HAS_PARENT;
+    }
+    flags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FileId;
+
+    fn record(id: u64, volume: &str, name: &str) -> FileRecord {
+        let mut record = FileRecord::new(
+            FileId::new(id),
+            None,
+            VolumeId::new(volume),
+            name.to_string(),
+            format!("{}:\\{}", volume, name),
+            false,
+        );
+        record.init_name_cache();
+        record
+    }
+
+    #[test]
+    fn test_columns_follow_records() {
+        let mut records = Records::default();
+        records.push(record(1, "C", "Report.PDF").with_size(10));
+        records.push(record(2, "D", "notes.txt"));
+        records.push(record(3, "C", "Photo.jpg"));
+
+        let row = records.row(0);
+        assert_eq!(row.name_lower(), "report.pdf");
+        assert_eq!(records.name(0), "Report.PDF");
+        assert_eq!(records.path(0), "C:\\Report.PDF");
+        assert_eq!(records.cloned(0).name_lower, "report.pdf");
+        assert!(row.live && !row.is_dir && !row.cloud);
+        assert_eq!(row.size, Some(10));
+        assert_eq!(row.ext_id, records.cloned(0).ext_id);
+        assert_eq!(records.row(1).size, None);
+        assert_eq!(records.volume_mask(&["D".to_string()]), [false, true]);
+        assert_eq!(records.row(2).volume, records.row(0).volume);
+
+        // Renamed, then deleted
+        records.update(1, |r| {
+            r.name = "Todo.md".to_string();
+            r.name_lower.clear();
+            r.init_name_cache();
+            r.cloud = true;
+        });
+        assert_eq!(records.row(1).name_lower(), "todo.md");
+        assert!(records.row(1).cloud);
+        // "notes.txt" is still in the names buffer but no longer D's name;
+        // "pdfn" only runs across two names
+        assert_eq!(records.names_containing("o"), [0b111]);
+        assert_eq!(records.names_containing("notes"), [0]);
+        assert_eq!(records.names_containing("pdfn"), [0]);
+        assert_eq!(records.names_containing("todo"), [0b010]);
+        records.update(0, |r| {
+            r.name.clear();
+            r.name_lower.clear();
+        });
+        assert!(!records.row(0).live);
+        assert_eq!(records.row(0).name_lower(), "");
+
+        assert_eq!(records.retain(|r| !r.name.is_empty()), 1);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records.row(0).name_lower(), "todo.md");
+        assert_eq!(records.row(1).name_lower(), "photo.jpg");
+        assert_eq!(records.names, "todo.mdphoto.jpg");
+        assert_eq!(records.names_containing(".jpg"), [0b10]);
+    }
+
+    #[test]
+    fn test_fields_from_columns() {
+        let mut records = Records::default();
+        let modified = Utc::now();
+        records.push(record(1, "C", "Report.PDF").with_modified(modified));
+        // A name that isn't the end of its path is kept on the side
+        let mut odd = record(2, "C", "notes.txt");
+        odd.path = "C:\\Notes.txt".to_string();
+        records.push(odd);
+        assert_eq!(records.name(1), "notes.txt");
+        assert_eq!(records.cloned(0).modified, Some(modified));
+
+        // Renamed so the name ends the path again, then moved twice; the
+        // stale paths are dropped once they outweigh the live ones
+        records.update(1, |r| r.name = "Notes.txt".to_string());
+        assert!(records.odd_names.is_empty());
+        records.update(0, |r| r.path = "C:\\Archive\\Report.PDF".to_string());
+        records.update(0, |r| r.path = "C:\\Old\\Report.PDF".to_string());
+        assert_eq!(records.name(0), "Report.PDF");
+        assert_eq!(records.path_lower(0), "c:\\old\\report.pdf");
+        assert_eq!(records.paths.text, "C:\\Old\\Report.PDFC:\\Notes.txt");
+
+        records.set_path_cache(false);
+        assert_eq!(records.cloned(0).path_lower, "");
+        assert_eq!(records.path_lower(0), "c:\\old\\report.pdf");
+        records.set_path_cache(true);
+        assert_eq!(records.cloned(0).path_lower, "c:\\old\\report.pdf");
+
+        // Lent records are the same as cloned ones
+        let mut seen = Vec::new();
+        records.visit(|idx, r| seen.push((idx, r.name.clone(), r.path_lower.clone())));
+        assert_eq!(seen[1], (1, "Notes.txt".into(), "c:\\notes.txt".into()));
+        let lent = records.with_record(0, |r| (r.id, r.name_lower.clone(), r.modified));
+        assert_eq!(lent, (FileId::new(1), "report.pdf".into(), Some(modified)));
+    }
+
+    #[test]
+    fn test_find_path() {
+        let mut records = Records::default();
+        records.push(record(1, "C", "Report.PDF"));
+        records.push(record(2, "D", "notes.txt"));
+        // The same path again, as a folder volume inside C indexes it
+        records.push(record(3, "C", "report.pdf"));
+        let c = VolumeId::new("C");
+        assert_eq!(records.find_path(None, "c:\\report.pdf"), Some(0));
+        assert_eq!(records.find_path(Some(&c), "c:\\report.pdf"), Some(0));
+        assert_eq!(records.find_path(None, "d:\\notes.txt"), Some(1));
+        assert_eq!(records.find_path(Some(&c), "d:\\notes.txt"), None);
+        assert_eq!(records.find_path(None, "c:\\notes.txt"), None);
+
+        // Moved away, the other record with its path takes over
+        records.update(0, |r| {
+            r.path = "C:\\Archive\\Report.PDF".to_string();
+            r.clear_path_cache();
+        });
+        assert_eq!(records.find_path(None, "c:\\report.pdf"), Some(2));
+        assert_eq!(records.find_path(None, "c:\\archive\\report.pdf"), Some(0));
+
+        // Deleted records have neither name nor path
+        records.update(1, |r| {
+            r.name.clear();
+            r.path.clear();
+        });
+        assert_eq!(records.find_path(None, "d:\\notes.txt"), None);
+        assert_eq!(records.find_path(None, ""), None);
+        assert!(records.path_lookup_bytes() > 0);
+
+        assert_eq!(records.retain(|r| r.id.as_u64() != 2), 1);
+        assert_eq!(records.find_path(None, "c:\\archive\\report.pdf"), Some(0));
+        assert_eq!(records.find_path(None, "c:\\report.pdf"), Some(1));
+        assert_eq!(
+            normalize_path("C:/Archive/Report.PDF/"),
+            "c:\\archive\\report.pdf"
+        );
+    }
+
+    #[test]
+    fn test_children() {
+        let child = |id: u64, volume: &str, parent: u64| {
+            let mut record = record(id, volume, &format!("f{}", id));
+            record.parent_id = Some(FileId::new(parent));
+            record
+        };
+        let (c, d) = (VolumeId::new("C"), VolumeId::new("D"));
+        let mut records = Records::default();
+        records.push(child(10, "C", 5));
+        records.push(child(11, "C", 10));
+        records.push(child(12, "C", 10));
+        records.push(child(10, "D", 5));
+        records.push(child(13, "D", 10));
+        assert_eq!(records.children(&c, FileId::new(10)), [1, 2]);
+        assert_eq!(records.children(&d, FileId::new(10)), [4]);
+        assert_eq!(records.children(&c, FileId::new(99)), Vec::<usize>::new());
+
+        // Added and moved after the view was built
+        records.push(child(14, "C", 10));
+        records.update(1, |r| r.parent_id = Some(FileId::new(5)));
+        records.update(2, |r| r.name.clear());
+        assert_eq!(records.children(&c, FileId::new(10)), [5]);
+        assert_eq!(records.children(&c, FileId::new(5)), [0, 1]);
+        records.update(1, |r| r.parent_id = Some(FileId::new(10)));
+        assert_eq!(records.children(&c, FileId::new(10)), [1, 5]);
+
+        // Built again from scratch
+        assert_eq!(records.retain(|r| !r.name.is_empty()), 1);
+        assert_eq!(records.children(&c, FileId::new(10)), [1, 4]);
+        assert_eq!(records.children(&d, FileId::new(10)), [3]);
+    }
+
+    #[test]
+    fn test_positions_and_parents() {
+        let child = |id: u64, volume: &str, parent: u64| {
+            let mut record = record(id, volume, &format!("f{}", id));
+            record.parent_id = Some(FileId::new(parent));
+            record
+        };
+        let (c, d) = (VolumeId::new("C"), VolumeId::new("D"));
+        let mut records = Records::default();
+        // Scanned before its folder
+        records.push(child(11, "C", 10));
+        records.push(child(10, "C", 5));
+        records.push(child(10, "D", 5));
+        records.push(child(12, "D", 10));
+        assert_eq!(records.position(&c, FileId::new(10)), Some(1));
+        assert_eq!(records.position(&d, FileId::new(10)), Some(2));
+        assert_eq!(records.position(&c, FileId::new(99)), None);
+        assert_eq!(records.parents[0], NO_PARENT);
+        assert_eq!(records.parent(0), Some(1));
+        records.link_parents();
+        assert_eq!(records.parents[0], 1);
+        assert_eq!(records.parent(3), Some(2));
+        assert_eq!(records.parent(1), None);
+
+        // Moved to the other folder 10, then its folder deleted
+        records.update(0, |r| r.volume_id = d.clone());
+        assert_eq!(records.parent(0), Some(2));
+        assert_eq!(records.position(&c, FileId::new(11)), None);
+        assert_eq!(records.position(&d, FileId::new(11)), Some(0));
+        records.update(2, |r| {
+            r.name.clear();
+            r.path.clear();
+        });
+        assert_eq!(records.position(&d, FileId::new(10)), None);
+        assert_eq!(records.parent(0), None);
+
+        // Created again further on
+        records.push(child(10, "D", 5));
+        assert_eq!(records.parent(0), Some(4));
+        assert_eq!(records.retain(|r| !r.name.is_empty()), 1);
+        assert_eq!(records.parents, [3, NO_PARENT, 3, NO_PARENT]);
+        assert_eq!(records.parent_id(3), Some(FileId::new(5)));
+        assert_eq!(records.cloned(3).name_lower, "f10");
+    }
+}
@@ -0,0 +1,742 @@
+//! Template-based bulk renaming of search results.
+//!
+//! A rename is done in three steps:
+//!
+//! 1. A [`RenameTemplate`] is parsed from a string such as `photo_{n:3}.{ext}`.
+//! 2. A [`RenamePlan`] is built for a set of records, computing every new name
+//!    up front and flagging conflicts, so the caller can show a preview.
+//! 3. [`RenamePlan::execute`] performs the renames and returns a
+//!    [`RenameJournal`], which can be saved to disk and later undone.
+//!
+//! ## Template Syntax
+//!
+//! | Token | Meaning |
+//! |-------|---------|
+//! | `{name}` | Original name without extension |
+//! | `{ext}` | Original extension without the dot |
+//! | `{file}` | Original full file name |
+//! | `{n}` | Counter (starts at 1 by default) |
+//! | `{n:3}` | Counter zero-padded to 3 digits |
+//! | `{0}`, `{1}`, ... | Capture groups from the match regex |
+//! | `{{`, `}}` | Literal braces |
+
+use crate::backend::ChangeEvent;
+use crate::error::{GlintError, Result};
+use crate::index::Index;
+use crate::types::{FileId, FileRecord, VolumeId};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// File name of the undo journal inside the index directory.
+pub const JOURNAL_FILE: &str = "rename-journal.json";
+
+/// Characters that are not allowed in Windows file names.
+const INVALID_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Stem,
+    Ext,
+    File,
+    Counter { width: usize },
+    Capture(usize),
+}
+
+/// A parsed rename template.
+#[derive(Debug, Clone)]
+pub struct RenameTemplate {
+    source: String,
+    parts: Vec<Part>,
+}
+
+impl RenameTemplate {
+    /// Parse a template string.
+    pub fn parse(template: &str) -> Result<Self> {
+        let invalid = |reason: String| GlintError::InvalidPattern {
+            pattern: template.to_string(),
+            reason,
+        };
+
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut token = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(ch) => token.push(ch),
+                            None => return Err(invalid("unclosed '{'".to_string())),
+                        }
+                    }
+
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(
+                        parse_token(&token)
+                            .ok_or_else(|| invalid(format!("unknown token {{{}}}", token)))?,
+                    );
+                }
+                '}' => return Err(invalid("unmatched '}'".to_string())),
+                _ => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+
+        if parts.is_empty() {
+            return Err(invalid("template is empty".to_string()));
+        }
+
+        Ok(RenameTemplate {
+            source: template.to_string(),
+            parts,
+        })
+    }
+
+    /// The template string this was parsed from.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Highest capture group referenced by the template, if any.
+    fn max_capture(&self) -> Option<usize> {
+        self.parts
+            .iter()
+            .filter_map(|p| match p {
+                Part::Capture(i) => Some(*i),
+                _ => None,
+            })
+            .max()
+    }
+
+    /// Render a new name for `record`, from its extension as
+    /// [`FileRecord::extension`] finds it.
+    fn render(
+        &self,
+        record: &FileRecord,
+        counter: u64,
+        captures: Option<&regex::Captures>,
+    ) -> String {
+        let file_name = record.name.as_str();
+        let ext = record.extension().unwrap_or_default();
+        let stem = match ext {
+            "" => file_name,
+            ext => &file_name[..file_name.len() - ext.len() - 1],
+        };
+        let mut out = String::new();
+
+        for part in &self.parts {
+            match part {
+                Part::Literal(s) => out.push_str(s),
+                Part::Stem => out.push_str(stem),
+                Part::Ext => out.push_str(ext),
+                Part::File => out.push_str(file_name),
+                Part::Counter { width } => {
+                    out.push_str(&format!("{:0width$}", counter, width = *width))
+                }
+                Part::Capture(i) => {
+                    if let Some(m) = captures.and_then(|c| c.get(*i)) {
+                        out.push_str(m.as_str());
+                    }
+                }
+            }
+        }
+
+        // `{name}.{ext}` on a file without an extension would leave a trailing dot
+        out.trim_end_matches('.').to_string()
+    }
+}
+
+fn parse_token(token: &str) -> Option<Part> {
+    match token {
+        "name" => Some(Part::Stem),
+        "ext" => Some(Part::Ext),
+        "file" => Some(Part::File),
+        "n" => Some(Part::Counter { width: 1 }),
+        _ => {
+            if let Some(width) = token.strip_prefix("n:") {
+                width.parse().ok().map(|width| Part::Counter { width })
+            } else {
+                token.parse().ok().map(Part::Capture)
+            }
+        }
+    }
+}
+
+/// Options controlling how a plan is built.
+#[derive(Debug, Clone)]
+pub struct RenameOptions {
+    /// Template for the new names
+    pub template: RenameTemplate,
+
+    /// Optional regex applied to each file name; provides `{0}`..`{9}` captures.
+    /// Records that don't match are skipped.
+    pub pattern: Option<Regex>,
+
+    /// First counter value
+    pub start: u64,
+
+    /// Check the filesystem for existing files at the target paths
+    pub check_filesystem: bool,
+}
+
+impl RenameOptions {
+    /// Create options for a template with default settings.
+    pub fn new(template: RenameTemplate) -> Self {
+        RenameOptions {
+            template,
+            pattern: None,
+            start: 1,
+            check_filesystem: true,
+        }
+    }
+
+    /// Set the regex that provides capture groups.
+    pub fn with_pattern(mut self, pattern: &str) -> Result<Self> {
+        let regex = Regex::new(pattern).map_err(|e| GlintError::InvalidPattern {
+            pattern: pattern.to_string(),
+            reason: e.to_string(),
+        })?;
+        self.pattern = Some(regex);
+        Ok(self)
+    }
+
+    /// Set the first counter value.
+    pub fn with_start(mut self, start: u64) -> Self {
+        self.start = start;
+        self
+    }
+}
+
+/// What will happen to a single entry of a plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameStatus {
+    /// The entry will be renamed
+    Ready,
+    /// The new name equals the old name
+    Unchanged,
+    /// The match regex did not match this name
+    Skipped,
+    /// The entry cannot be renamed
+    Conflict(String),
+}
+
+impl RenameStatus {
+    /// Whether this entry will be renamed.
+    pub fn is_ready(&self) -> bool {
+        matches!(self, RenameStatus::Ready)
+    }
+}
+
+/// A single planned rename.
+#[derive(Debug, Clone)]
+pub struct RenameEntry {
+    /// The record being renamed
+    pub record: FileRecord,
+    /// The new file name
+    pub new_name: String,
+    /// The new full path
+    pub new_path: String,
+    /// What will happen to this entry
+    pub status: RenameStatus,
+}
+
+/// A previewable set of renames.
+#[derive(Debug, Clone, Default)]
+pub struct RenamePlan {
+    /// Planned entries in result order
+    pub entries: Vec<RenameEntry>,
+}
+
+impl RenamePlan {
+    /// Compute new names for `records` and detect conflicts.
+    ///
+    /// Records without a name or path (deleted entries) are ignored. The
+    /// counter only advances for records that are not skipped.
+    pub fn build(records: &[FileRecord], options: &RenameOptions) -> Result<Self> {
+        if let (Some(max), Some(regex)) = (options.template.max_capture(), &options.pattern) {
+            if max >= regex.captures_len() {
+                return Err(GlintError::InvalidPattern {
+                    pattern: options.template.as_str().to_string(),
+                    reason: format!("capture group {} does not exist in the match regex", max),
+                });
+            }
+        } else if options.template.max_capture().is_some() {
+            return Err(GlintError::InvalidPattern {
+                pattern: options.template.as_str().to_string(),
+                reason: "capture groups require a match regex".to_string(),
+            });
+        }
+
+        let mut entries = Vec::with_capacity(records.len());
+        let mut counter = options.start;
+
+        for record in records
+            .iter()
+            .filter(|r| !r.name.is_empty() && !r.path.is_empty())
+        {
+            let captures = match &options.pattern {
+                Some(regex) => match regex.captures(&record.name) {
+                    Some(caps) => Some(caps),
+                    None => {
+                        entries.push(RenameEntry {
+                            record: record.clone(),
+                            new_name: record.name.clone(),
+                            new_path: record.path.clone(),
+                            status: RenameStatus::Skipped,
+                        });
+                        continue;
+                    }
+                },
+                None => None,
+            };
+
+            let new_name = options.template.render(record, counter, captures.as_ref());
+            counter += 1;
+
+            let new_path = sibling_path(&record.path, &new_name);
+            let status = if new_name == record.name {
+                RenameStatus::Unchanged
+            } else {
+                validate_name(&new_name).map_or(RenameStatus::Ready, RenameStatus::Conflict)
+            };
+
+            entries.push(RenameEntry {
+                record: record.clone(),
+                new_name,
+                new_path,
+                status,
+            });
+        }
+
+        let mut plan = RenamePlan { entries };
+        plan.detect_conflicts(options.check_filesystem);
+        Ok(plan)
+    }
+
+    fn detect_conflicts(&mut self, check_filesystem: bool) {
+        // NTFS is case-insensitive, so compare lowercase paths
+        let sources: HashSet<String> = self
+            .entries
+            .iter()
+            .map(|e| e.record.path.to_lowercase())
+            .collect();
+
+        let mut targets: HashMap<String, usize> = HashMap::new();
+        for entry in &self.entries {
+            if entry.status.is_ready() {
+                *targets.entry(entry.new_path.to_lowercase()).or_default() += 1;
+            }
+        }
+
+        for entry in &mut self.entries {
+            if !entry.status.is_ready() {
+                continue;
+            }
+
+            let target = entry.new_path.to_lowercase();
+            let case_only = target == entry.record.path.to_lowercase();
+
+            if targets.get(&target).copied().unwrap_or(0) > 1 {
+                entry.status = RenameStatus::Conflict("duplicate target name".to_string());
+            } else if !case_only && sources.contains(&target) {
+                entry.status =
+                    RenameStatus::Conflict("target is another file being renamed".to_string());
+            } else if check_filesystem && !case_only && Path::new(&entry.new_path).exists() {
+                entry.status = RenameStatus::Conflict("target already exists".to_string());
+            }
+        }
+    }
+
+    /// Number of entries that will be renamed.
+    pub fn ready_count(&self) -> usize {
+        self.entries.iter().filter(|e| e.status.is_ready()).count()
+    }
+
+    /// Number of entries with conflicts.
+    pub fn conflict_count(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|e| matches!(e.status, RenameStatus::Conflict(_)))
+            .count()
+    }
+
+    /// Perform all ready renames.
+    ///
+    /// Failures on individual files are logged and collected in the journal;
+    /// they don't abort the remaining renames.
+    pub fn execute(&self) -> RenameJournal {
+        let mut journal = RenameJournal::new();
+
+        for entry in self.entries.iter().filter(|e| e.status.is_ready()) {
+            match rename_no_replace(Path::new(&entry.record.path), Path::new(&entry.new_path)) {
+                Ok(()) => journal.entries.push(JournalEntry::from_entry(entry)),
+                Err(e) => {
+                    warn!(from = %entry.record.path, to = %entry.new_path, error = %e, "Rename failed");
+                    journal
+                        .failures
+                        .push((entry.record.path.clone(), e.to_string()));
+                }
+            }
+        }
+
+        info!(
+            renamed = journal.entries.len(),
+            failed = journal.failures.len(),
+            "Bulk rename finished"
+        );
+        journal
+    }
+}
+
+/// Rename `from` to `to`, failing instead of replacing a file that is
+/// already at `to`; the plan's check may be long out of date by now.
+///
+/// Windows moves without `MOVEFILE_REPLACE_EXISTING`, so checking and
+/// renaming are one step.
+#[cfg(windows)]
+pub(crate) fn rename_no_replace(from: &Path, to: &Path) -> io::Result<()> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::{MoveFileExW, MOVE_FILE_FLAGS};
+
+    let wide = |path: &Path| -> Vec<u16> {
+        path.as_os_str()
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect()
+    };
+    let (from, to) = (wide(from), wide(to));
+    unsafe {
+        MoveFileExW(
+            PCWSTR(from.as_ptr()),
+            PCWSTR(to.as_ptr()),
+            MOVE_FILE_FLAGS(0),
+        )
+    }
+    .map_err(io::Error::from)
+}
+
+/// Rename `from` to `to`, failing instead of replacing a file that is
+/// already at `to`; the plan's check may be long out of date by now.
+///
+/// There is no portable rename that won't replace, so `to` is checked right
+/// before renaming. Case-only renames go ahead, as on NTFS.
+#[cfg(not(windows))]
+pub(crate) fn rename_no_replace(from: &Path, to: &Path) -> io::Result<()> {
+    let case_only = from.to_string_lossy().to_lowercase() == to.to_string_lossy().to_lowercase();
+    if !case_only && fs::symlink_metadata(to).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "target already exists",
+        ));
+    }
+    fs::rename(from, to)
+}
+
+/// Replace the last component of a Windows path.
+fn sibling_path(path: &str, new_name: &str) -> String {
+    match path.rfind('\\') {
+        Some(pos) => format!("{}{}", &path[..=pos], new_name),
+        None => new_name.to_string(),
+    }
+}
+
+/// Check a file name against Windows naming rules.
+fn validate_name(name: &str) -> Option<String> {
+    if name.is_empty() {
+        return Some("new name is empty".to_string());
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| INVALID_CHARS.contains(c) || c.is_control())
+    {
+        return Some(format!("invalid character {:?}", c));
+    }
+    if name.ends_with(' ') || name.ends_with('.') {
+        return Some("name cannot end with a space or dot".to_string());
+    }
+    None
+}
+
+/// A completed rename, as recorded in the journal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub volume_id: VolumeId,
+    pub file_id: FileId,
+    pub parent_id: Option<FileId>,
+    pub is_dir: bool,
+    pub old_name: String,
+    pub new_name: String,
+    pub old_path: String,
+    pub new_path: String,
+}
+
+impl JournalEntry {
+    fn from_entry(entry: &RenameEntry) -> Self {
+        JournalEntry {
+            volume_id: entry.record.volume_id.clone(),
+            file_id: entry.record.id,
+            parent_id: entry.record.parent_id,
+            is_dir: entry.record.is_dir,
+            old_name: entry.record.name.clone(),
+            new_name: entry.new_name.clone(),
+            old_path: entry.record.path.clone(),
+            new_path: entry.new_path.clone(),
+        }
+    }
+
+    fn change_event(&self, from: &str, to: &str) -> ChangeEvent {
+        ChangeEvent::renamed(
+            self.volume_id.clone(),
+            self.file_id,
+            self.parent_id,
+            from.to_string(),
+            to.to_string(),
+            None,
+            self.is_dir,
+            0,
+        )
+    }
+}
+
+/// Record of a bulk rename, used for undo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameJournal {
+    /// When the renames were performed
+    pub timestamp: DateTime<Utc>,
+    /// Successful renames, in the order they were performed
+    pub entries: Vec<JournalEntry>,
+    /// Paths that failed to rename, with the error message
+    pub failures: Vec<(String, String)>,
+}
+
+impl RenameJournal {
+    fn new() -> Self {
+        RenameJournal {
+            timestamp: Utc::now(),
+            entries: Vec::new(),
+            failures: Vec::new(),
+        }
+    }
+
+    /// Path of the journal file inside an index directory.
+    pub fn path_in(dir: &Path) -> PathBuf {
+        dir.join(JOURNAL_FILE)
+    }
+
+    /// Load a journal, returning None if none exists.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(path)?;
+        let journal = serde_json::from_str(&contents)
+            .map_err(|e| GlintError::serialization(e.to_string()))?;
+        Ok(Some(journal))
+    }
+
+    /// Save the journal, replacing any previous one.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| GlintError::serialization(e.to_string()))?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Apply the renames to an index without waiting for the change journal.
+    pub fn apply_to_index(&self, index: &Index) {
+        for entry in &self.entries {
+            index.apply_change(entry.change_event(&entry.old_name, &entry.new_name));
+        }
+    }
+
+    /// Reverse the renames, newest first.
+    ///
+    /// Returns a journal of the renames that were undone; failed entries are
+    /// listed in its `failures`.
+    pub fn undo(&self) -> RenameJournal {
+        let mut undone = RenameJournal::new();
+
+        for entry in self.entries.iter().rev() {
+            match rename_no_replace(Path::new(&entry.new_path), Path::new(&entry.old_path)) {
+                Ok(()) => undone.entries.push(JournalEntry {
+                    old_name: entry.new_name.clone(),
+                    new_name: entry.old_name.clone(),
+                    old_path: entry.new_path.clone(),
+                    new_path: entry.old_path.clone(),
+                    ..entry.clone()
+                }),
+                Err(e) => undone
+                    .failures
+                    .push((entry.new_path.clone(), e.to_string())),
+            }
+        }
+
+        undone
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record(id: u64, dir: &str, name: &str) -> FileRecord {
+        FileRecord::new(
+            FileId::new(id),
+            Some(FileId::new(5)),
+            VolumeId::new("C"),
+            name.to_string(),
+            format!("{}\\{}", dir, name),
+            false,
+        )
+    }
+
+    fn options(template: &str) -> RenameOptions {
+        let mut options = RenameOptions::new(RenameTemplate::parse(template).unwrap());
+        options.check_filesystem = false;
+        options
+    }
+
+    #[test]
+    fn test_template_parse() {
+        assert!(RenameTemplate::parse("{name}_{n:3}.{ext}").is_ok());
+        assert!(RenameTemplate::parse("{{literal}}").is_ok());
+        assert!(RenameTemplate::parse("{bogus}").is_err());
+        assert!(RenameTemplate::parse("{name").is_err());
+        assert!(RenameTemplate::parse("").is_err());
+    }
+
+    #[test]
+    fn test_counter_and_tokens() {
+        let records = vec![
+            record(1, "C:\\pics", "IMG_1.JPG"),
+            record(2, "C:\\pics", "IMG_2.JPG"),
+            record(3, "C:\\pics", "README"),
+        ];
+        let plan = RenamePlan::build(&records, &options("photo_{n:3}.{ext}")).unwrap();
+        let names: Vec<_> = plan.entries.iter().map(|e| e.new_name.as_str()).collect();
+        assert_eq!(names, vec!["photo_001.JPG", "photo_002.JPG", "photo_003"]);
+        assert_eq!(plan.entries[0].new_path, "C:\\pics\\photo_001.JPG");
+        assert_eq!(plan.ready_count(), 3);
+    }
+
+    #[test]
+    fn test_captures_and_skips() {
+        let records = vec![
+            record(1, "C:\\m", "2023-05-01 report.pdf"),
+            record(2, "C:\\m", "notes.txt"),
+        ];
+        let opts = options("{3}{2}{1}_{name}")
+            .with_pattern(r"^(\d{4})-(\d{2})-(\d{2})")
+            .unwrap();
+        let plan = RenamePlan::build(&records, &opts).unwrap();
+        assert_eq!(plan.entries[0].new_name, "01052023_2023-05-01 report");
+        assert_eq!(plan.entries[1].status, RenameStatus::Skipped);
+
+        // Captures without a regex are rejected
+        assert!(RenamePlan::build(&records, &options("{1}")).is_err());
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let records = vec![
+            record(1, "C:\\d", "a.txt"),
+            record(2, "C:\\d", "b.txt"),
+            record(3, "C:\\d", "c.txt"),
+        ];
+
+        let plan = RenamePlan::build(&records, &options("same.txt")).unwrap();
+        assert_eq!(plan.conflict_count(), 3);
+
+        let plan = RenamePlan::build(&records, &options("{name}?")).unwrap();
+        assert!(matches!(plan.entries[0].status, RenameStatus::Conflict(_)));
+
+        // Case-only renames are allowed; renaming onto another source is not
+        let records = vec![record(1, "C:\\d", "a.txt"), record(2, "C:\\d", "A.TXT")];
+        let plan = RenamePlan::build(&records[..1], &options("A.txt")).unwrap();
+        assert_eq!(plan.entries[0].status, RenameStatus::Ready);
+        let plan = RenamePlan::build(&records, &options("{n}.txt").with_start(0)).unwrap();
+        assert_eq!(plan.ready_count(), 2);
+    }
+
+    #[test]
+    fn test_execute_undo_and_index_update() {
+        let temp = TempDir::new().unwrap();
+        // Point the record at a real file; paths use the native separator here
+        let native = |name: &str| temp.path().join(name).to_string_lossy().to_string();
+        fs::write(native("one.txt"), "1").unwrap();
+
+        let mut rec = record(1, "C:\\d", "one.txt");
+        rec.path = native("one.txt");
+        let mut plan = RenamePlan::build(&[rec.clone()], &options("two.txt")).unwrap();
+        plan.entries[0].new_path = native("two.txt");
+
+        let journal = plan.execute();
+        assert_eq!(journal.entries.len(), 1);
+        assert!(Path::new(&native("two.txt")).exists());
+
+        let index = Index::new();
+        index.add_volume_records(
+            &crate::backend::VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS"),
+            vec![rec],
+        );
+        journal.apply_to_index(&index);
+        let updated = index.get(&VolumeId::new("C"), FileId::new(1)).unwrap();
+        assert_eq!(updated.name, "two.txt");
+
+        let journal_path = RenameJournal::path_in(temp.path());
+        journal.save(&journal_path).unwrap();
+        let loaded = RenameJournal::load(&journal_path).unwrap().unwrap();
+        let undone = loaded.undo();
+        assert!(undone.failures.is_empty());
+        assert!(Path::new(&native("one.txt")).exists());
+    }
+
+    #[test]
+    fn test_execute_never_replaces() {
+        let temp = TempDir::new().unwrap();
+        let native = |name: &str| temp.path().join(name).to_string_lossy().to_string();
+        fs::write(native("one.txt"), "1").unwrap();
+
+        let mut rec = record(1, "C:\\d", "one.txt");
+        rec.path = native("one.txt");
+        let mut plan = RenamePlan::build(&[rec], &options("two.txt")).unwrap();
+        plan.entries[0].new_path = native("two.txt");
+        assert_eq!(plan.ready_count(), 1);
+
+        // A file that appears after the plan was checked is left alone
+        fs::write(native("two.txt"), "2").unwrap();
+        let journal = plan.execute();
+        assert!(journal.entries.is_empty());
+        assert_eq!(journal.failures.len(), 1);
+        assert_eq!(fs::read_to_string(native("one.txt")).unwrap(), "1");
+        assert_eq!(fs::read_to_string(native("two.txt")).unwrap(), "2");
+    }
+}
@@ -0,0 +1,573 @@
+//! Scheduled reports.
+//!
+//! A report job runs a saved query against the index, optionally narrowed to
+//! large or recent files, and writes the matching files to a CSV or JSON
+//! file. Jobs are configured under `[[reports.jobs]]` in the config file and
+//! run by the watch service on a cron-style [`Schedule`]:
+//!
+//! ```toml
+//! [[reports.jobs]]
+//! name = "large-new-files"
+//! schedule = "0 8 * * 1"      # Mondays at 08:00
+//! query = "ext:iso,zip,mp4"
+//! min_size = "1GB"
+//! created_within = "7d"
+//! sort = "size"
+//! limit = 100
+//! format = "csv"
+//! ```
+//!
+//! JSON reports carry the [`BuildManifest`] of the index they ran against.
+//!
+//! Schedules are read in local time, or in UTC with `timezone = "utc"` under
+//! `[general]` (see [`clock`]).
+
+use crate::clock::{self, Clock, SystemClock, Zone};
+use crate::error::{GlintError, Result};
+use crate::index::Index;
+use crate::manifest::BuildManifest;
+use crate::schedule::Schedule;
+use crate::search::{parse_query_at, SearchFilter};
+use crate::types::FileRecord;
+use chrono::{DateTime, Duration, Local, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use tracing::{debug, info, warn};
+
+/// How often the scheduler checks for due jobs.
+const TICK: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Output format of a report file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+impl ReportFormat {
+    /// File extension for report files.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Csv => "csv",
+            ReportFormat::Json => "json",
+        }
+    }
+}
+
+/// Order of the files in a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportSort {
+    /// Largest first
+    #[default]
+    Size,
+
+    /// Most recently modified first
+    Modified,
+
+    /// Most recently created first
+    Created,
+
+    /// By path, alphabetically
+    Name,
+}
+
+/// A saved query that is run on a schedule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportJob {
+    /// Name of the job, used in report file names
+    pub name: String,
+
+    /// Cron expression (see [`Schedule`])
+    pub schedule: String,
+
+    /// Search query, in the same syntax as `glint search` (empty = all files)
+    #[serde(default)]
+    pub query: String,
+
+    /// Only include files at least this large (e.g. "1GB", "500MB")
+    #[serde(default)]
+    pub min_size: Option<String>,
+
+    /// Only include files modified within this long (e.g. "7d", "12h")
+    #[serde(default)]
+    pub modified_within: Option<String>,
+
+    /// Only include files created within this long (e.g. "7d", "2w")
+    #[serde(default)]
+    pub created_within: Option<String>,
+
+    /// Order of the files
+    #[serde(default)]
+    pub sort: ReportSort,
+
+    /// Maximum number of files in a report
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+
+    /// Output format
+    #[serde(default)]
+    pub format: ReportFormat,
+}
+
+fn default_limit() -> usize {
+    1000
+}
+
+impl ReportJob {
+    /// Parse the job's schedule.
+    pub fn parsed_schedule(&self) -> Result<Schedule> {
+        Schedule::parse(&self.schedule)
+    }
+
+    /// Run the job against the index.
+    pub fn run(&self, index: &Index) -> Result<Report> {
+        self.run_at(index, &SystemClock)
+    }
+
+    fn run_at(&self, index: &Index, clock: &dyn Clock) -> Result<Report> {
+        let now = clock.now();
+        let mut query = parse_query_at(&self.query, clock)?.with_filter(SearchFilter::FilesOnly);
+        if let Some(size) = &self.min_size {
+            query = query.with_filter(SearchFilter::MinSize(parse_size(size)?));
+        }
+        let modified_after = self
+            .modified_within
+            .as_deref()
+            .map(|s| parse_age(s).map(|age| clock.before(age)))
+            .transpose()?;
+        let created_after = self
+            .created_within
+            .as_deref()
+            .map(|s| parse_age(s).map(|age| clock.before(age)))
+            .transpose()?;
+
+        let mut records: Vec<FileRecord> = Vec::new();
+        index.for_each_record(|record| {
+            if !query.matches(record) {
+                return;
+            }
+            if let Some(after) = modified_after {
+                if record.modified.map_or(true, |m| m < after) {
+                    return;
+                }
+            }
+            if let Some(after) = created_after {
+                if record.created.map_or(true, |c| c < after) {
+                    return;
+                }
+            }
+            records.push(record.clone());
+        });
+
+        match self.sort {
+            ReportSort::Size => records.sort_by_key(|r| Reverse(r.size)),
+            ReportSort::Modified => records.sort_by_key(|r| Reverse(r.modified)),
+            ReportSort::Created => records.sort_by_key(|r| Reverse(r.created)),
+            ReportSort::Name => records.sort_by_cached_key(|r| r.path_lower().into_owned()),
+        }
+        records.truncate(self.limit);
+
+        Ok(Report {
+            job: self.name.clone(),
+            generated_at: now,
+            format: self.format,
+            files: records.into_iter().map(ReportRow::from).collect(),
+            index: None,
+        })
+    }
+}
+
+/// One file in a report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportRow {
+    pub path: String,
+    pub size: Option<u64>,
+    pub modified: Option<DateTime<Utc>>,
+    pub created: Option<DateTime<Utc>>,
+}
+
+impl From<FileRecord> for ReportRow {
+    fn from(record: FileRecord) -> Self {
+        ReportRow {
+            path: record.path,
+            size: record.size,
+            modified: record.modified,
+            created: record.created,
+        }
+    }
+}
+
+/// The result of running a report job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    /// Name of the job that produced the report
+    pub job: String,
+
+    /// When the report was generated
+    pub generated_at: DateTime<Utc>,
+
+    /// Format the report is written in
+    #[serde(skip)]
+    pub format: ReportFormat,
+
+    /// Matching files, in report order
+    pub files: Vec<ReportRow>,
+
+    /// How the index was built (JSON reports only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<BuildManifest>,
+}
+
+impl Report {
+    /// Attach the manifest of the index the report ran against.
+    pub fn with_manifest(mut self, manifest: Option<BuildManifest>) -> Self {
+        self.index = manifest;
+        self
+    }
+
+    /// File name for the report, e.g. `large-new-files-20240108-080000.csv`.
+    pub fn file_name(&self) -> String {
+        let name: String = self
+            .job
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!(
+            "{}-{}.{}",
+            name,
+            self.generated_at
+                .with_timezone(&Local)
+                .format("%Y%m%d-%H%M%S"),
+            self.format.extension()
+        )
+    }
+
+    /// Render the report in its format.
+    pub fn render(&self) -> Result<String> {
+        match self.format {
+            ReportFormat::Json => serde_json::to_string_pretty(self)
+                .map_err(|e| GlintError::serialization(e.to_string())),
+            ReportFormat::Csv => {
+                let mut out = String::from("path,size,modified,created\r\n");
+                for row in &self.files {
+                    out.push_str(&csv_field(&row.path));
+                    out.push(',');
+                    if let Some(size) = row.size {
+                        out.push_str(&size.to_string());
+                    }
+                    out.push(',');
+                    if let Some(modified) = row.modified {
+                        out.push_str(&modified.to_rfc3339());
+                    }
+                    out.push(',');
+                    if let Some(created) = row.created {
+                        out.push_str(&created.to_rfc3339());
+                    }
+                    out.push_str("\r\n");
+                }
+                Ok(out)
+            }
+        }
+    }
+
+    /// Write the report into `dir`, returning the path of the new file.
+    pub fn write_to(&self, dir: &Path) -> Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(self.file_name());
+        fs::write(&path, self.render()?)?;
+        Ok(path)
+    }
+}
+
+/// Quote a CSV field if it contains separators, quotes or line breaks.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Parse a size such as `1GB`, `500 MB` or `4096` (bytes, binary units).
+pub fn parse_size(input: &str) -> Result<u64> {
+    let s = input.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let multiplier: u64 = match unit.trim().to_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1024,
+        "m" | "mb" => 1024 * 1024,
+        "g" | "gb" => 1024 * 1024 * 1024,
+        "t" | "tb" => 1024 * 1024 * 1024 * 1024,
+        _ => return Err(invalid_value("size", input)),
+    };
+    let number: f64 = number.parse().map_err(|_| invalid_value("size", input))?;
+    Ok((number * multiplier as f64) as u64)
+}
+
+/// Parse an age such as `30m`, `12h`, `7d`, `2w`, `6mo` or `2y`.
+///
+/// Months count as 30 days and years as 365.
+pub fn parse_age(input: &str) -> Result<Duration> {
+    let s = input.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: i64 = number.parse().map_err(|_| invalid_value("age", input))?;
+    match unit.trim().to_lowercase().as_str() {
+        "m" | "min" => Ok(Duration::minutes(number)),
+        "h" => Ok(Duration::hours(number)),
+        "d" | "" => Ok(Duration::days(number)),
+        "w" => Ok(Duration::weeks(number)),
+        "mo" => Ok(Duration::days(number * 30)),
+        "y" => Ok(Duration::days(number * 365)),
+        _ => Err(invalid_value("age", input)),
+    }
+}
+
+fn invalid_value(kind: &str, input: &str) -> GlintError {
+    GlintError::ConfigError {
+        reason: format!("Invalid {}: {}", kind, input),
+    }
+}
+
+/// Handle for the background thread that runs report jobs on schedule.
+///
+/// The scheduler stops when the handle is dropped.
+pub struct ReportScheduler {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ReportScheduler {
+    /// Start running `jobs` against `index`, writing reports to `output_dir`.
+    ///
+    /// Each report carries the manifest at `manifest_path` as it is when the
+    /// report runs. Fails if any job has an invalid schedule.
+    pub fn spawn(
+        jobs: Vec<ReportJob>,
+        output_dir: PathBuf,
+        index: Arc<Index>,
+        manifest_path: PathBuf,
+    ) -> Result<Self> {
+        let jobs = jobs
+            .into_iter()
+            .map(|job| Ok((job.parsed_schedule()?, job)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = Arc::clone(&stop);
+
+        let thread = thread::Builder::new()
+            .name("glint-reports".to_string())
+            .spawn(move || match clock::zone() {
+                Zone::Local => {
+                    schedule_loop(Local, jobs, output_dir, index, manifest_path, stop_flag)
+                }
+                Zone::Utc => schedule_loop(Utc, jobs, output_dir, index, manifest_path, stop_flag),
+            })?;
+
+        Ok(ReportScheduler {
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Stop the scheduler and wait for its thread to exit.
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for ReportScheduler {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Run jobs when due, reading their schedules in time zone `tz`.
+fn schedule_loop<Tz: TimeZone>(
+    tz: Tz,
+    jobs: Vec<(Schedule, ReportJob)>,
+    output_dir: PathBuf,
+    index: Arc<Index>,
+    manifest_path: PathBuf,
+    stop: Arc<AtomicBool>,
+) where
+    Tz::Offset: std::fmt::Display,
+{
+    let start = Utc::now().with_timezone(&tz);
+    let mut next_runs: Vec<Option<DateTime<Tz>>> = jobs
+        .iter()
+        .map(|(schedule, _)| schedule.next_after(&start))
+        .collect();
+
+    for ((_, job), next) in jobs.iter().zip(&next_runs) {
+        match next {
+            Some(next) => info!(job = %job.name, next = %next, "Report scheduled"),
+            None => warn!(job = %job.name, "Report schedule never fires"),
+        }
+    }
+
+    while !stop.load(Ordering::Acquire) {
+        let now = Utc::now().with_timezone(&tz);
+        for ((schedule, job), next) in jobs.iter().zip(next_runs.iter_mut()) {
+            if !next.as_ref().is_some_and(|next| *next <= now) {
+                continue;
+            }
+            match job.run(&index).and_then(|report| {
+                report
+                    .with_manifest(BuildManifest::load(&manifest_path))
+                    .write_to(&output_dir)
+            }) {
+                Ok(path) => info!(job = %job.name, path = %path.display(), "Report written"),
+                Err(e) => warn!(job = %job.name, error = %e, "Report failed"),
+            }
+            *next = schedule.next_after(&now);
+            debug!(job = %job.name, next = ?next, "Next report");
+        }
+        thread::sleep(TICK);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::VolumeInfo;
+    use crate::clock::FixedClock;
+    use crate::types::{FileId, VolumeId};
+    use tempfile::TempDir;
+
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    fn index(now: DateTime<Utc>) -> Index {
+        let vol = VolumeId::new("C");
+        let file = |id: u64, name: &str, size: u64, days: i64| {
+            FileRecord::new(
+                FileId::new(id),
+                None,
+                vol.clone(),
+                name.into(),
+                format!("C:\\{}", name),
+                false,
+            )
+            .with_size(size)
+            .with_modified(now - Duration::days(days))
+            .with_created(now - Duration::days(days))
+        };
+        let records = vec![
+            file(10, "old.iso", 4 * GB, 30),
+            file(11, "new.iso", 2 * GB, 1),
+            file(12, "newer, \"big\".iso", 3 * GB, 0),
+            file(13, "small.iso", 1024, 0),
+            file(14, "notes.txt", 2 * GB, 0),
+        ];
+        let index = Index::new();
+        index.add_volume_records(&VolumeInfo::new(vol, "C:", "NTFS"), records);
+        index
+    }
+
+    fn job() -> ReportJob {
+        ReportJob {
+            name: "big files".into(),
+            schedule: "@weekly".into(),
+            query: "*.iso".into(),
+            min_size: Some("1GB".into()),
+            modified_within: None,
+            created_within: Some("7d".into()),
+            sort: ReportSort::Size,
+            limit: 10,
+            format: ReportFormat::Csv,
+        }
+    }
+
+    #[test]
+    fn test_run_filters_and_sorts() {
+        let now = Utc::now();
+        let clock = FixedClock::new(now, Utc);
+        let report = job().run_at(&index(now), &clock).unwrap();
+        let paths: Vec<_> = report.files.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(paths, ["C:\\newer, \"big\".iso", "C:\\new.iso"]);
+
+        let mut job = job();
+        job.created_within = None;
+        job.limit = 1;
+        let report = job.run_at(&index(now), &clock).unwrap();
+        assert_eq!(report.files[0].path, "C:\\old.iso");
+    }
+
+    #[test]
+    fn test_write_csv_and_json() {
+        let now = Utc::now();
+        let clock = FixedClock::new(now, Utc);
+        let dir = TempDir::new().unwrap();
+        let report = job().run_at(&index(now), &clock).unwrap();
+
+        let path = report.write_to(dir.path()).unwrap();
+        assert!(path
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("big_files-"));
+        let csv = fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines[0], "path,size,modified,created");
+        assert!(lines[1].starts_with("\"C:\\newer, \"\"big\"\".iso\",3221225472,"));
+
+        let mut job = job();
+        job.format = ReportFormat::Json;
+        let path = job
+            .run_at(&index(now), &clock)
+            .unwrap()
+            .write_to(dir.path())
+            .unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(json["files"].as_array().unwrap().len(), 2);
+        assert!(json.get("index").is_none());
+
+        let index = index(now);
+        let manifest = BuildManifest::new(&index, &crate::Config::default(), Default::default());
+        let path = job
+            .run_at(&index, &clock)
+            .unwrap()
+            .with_manifest(Some(manifest))
+            .write_to(dir.path())
+            .unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(json["index"]["volumes"][0]["records"], 5);
+    }
+
+    #[test]
+    fn test_parse_size_and_age() {
+        assert_eq!(parse_size("1GB").unwrap(), GB);
+        assert_eq!(parse_size("1.5 kb").unwrap(), 1536);
+        assert_eq!(parse_size("42").unwrap(), 42);
+        assert!(parse_size("big").is_err());
+
+        assert_eq!(parse_age("7d").unwrap(), Duration::days(7));
+        assert_eq!(parse_age("2w").unwrap(), Duration::weeks(2));
+        assert_eq!(parse_age("6mo").unwrap(), Duration::days(180));
+        assert_eq!(parse_age("2y").unwrap(), Duration::days(730));
+        assert!(parse_age("soon").is_err());
+    }
+}
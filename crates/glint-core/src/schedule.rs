@@ -0,0 +1,259 @@
+//! Cron-style schedules.
+//!
+//! A schedule has the five classic cron fields, evaluated in local time:
+//!
+//! ```text
+//! ┌ minute (0-59)
+//! │ ┌ hour (0-23)
+//! │ │ ┌ day of month (1-31)
+//! │ │ │ ┌ month (1-12)
+//! │ │ │ │ ┌ day of week (0-6, Sunday = 0 or 7)
+//! 0 8 * * 1        every Monday at 08:00
+//! ```
+//!
+//! Each field accepts `*`, numbers, ranges (`1-5`), lists (`1,15`) and steps
+//! (`*/15`, `0-30/10`). The shortcuts `@hourly`, `@daily`, `@weekly`,
+//! `@monthly` and `@yearly` are also understood. As in cron, when both the
+//! day of month and day of week are restricted, either may match.
+
+use crate::error::{GlintError, Result};
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike};
+use std::fmt;
+use std::str::FromStr;
+
+/// How far ahead `next_after` looks before giving up (covers Feb 29).
+const MAX_LOOKAHEAD_DAYS: i64 = 366 * 5;
+
+/// A parsed cron schedule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    source: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Day of month field was `*`
+    any_day: bool,
+    /// Day of week field was `*`
+    any_weekday: bool,
+}
+
+impl Schedule {
+    /// Parse a cron expression.
+    pub fn parse(expr: &str) -> Result<Self> {
+        let source = expr.trim().to_string();
+        let expanded = match source.as_str() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(invalid(
+                &source,
+                "expected 5 fields: minute hour day month weekday",
+            ));
+        }
+
+        let mut weekdays = parse_field(&source, fields[4], 0, 7)?;
+        // Both 0 and 7 mean Sunday
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+
+        Ok(Schedule {
+            minutes: parse_field(&source, fields[0], 0, 59)?,
+            hours: parse_field(&source, fields[1], 0, 23)?,
+            days: parse_field(&source, fields[2], 1, 31)?,
+            months: parse_field(&source, fields[3], 1, 12)?,
+            weekdays,
+            any_day: fields[2] == "*",
+            any_weekday: fields[4] == "*",
+            source,
+        })
+    }
+
+    /// Check if the schedule fires at the given minute.
+    pub fn matches<Tz: TimeZone>(&self, time: &DateTime<Tz>) -> bool {
+        self.matches_day(time) && has(self.hours, time.hour()) && has(self.minutes, time.minute())
+    }
+
+    fn matches_day<Tz: TimeZone>(&self, time: &DateTime<Tz>) -> bool {
+        if !has(self.months, time.month()) {
+            return false;
+        }
+        let day = has(self.days, time.day());
+        let weekday = has(self.weekdays, time.weekday().num_days_from_sunday());
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+
+    /// The first time strictly after `after` at which the schedule fires.
+    ///
+    /// Returns None for schedules that never fire (e.g. February 31st).
+    pub fn next_after<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let start = after.clone() + Duration::minutes(1);
+        let mut time = start
+            .with_second(0)
+            .and_then(|t| t.with_nanosecond(0))
+            .unwrap_or(start);
+        let limit = after.clone() + Duration::days(MAX_LOOKAHEAD_DAYS);
+
+        while time < limit {
+            let (hour, minute) = (time.hour() as i64, time.minute() as i64);
+            if !self.matches_day(&time) {
+                // Jump to the start of the next day
+                time += Duration::minutes((24 - hour) * 60 - minute);
+            } else if !has(self.hours, hour as u32) {
+                time += Duration::minutes(60 - minute);
+            } else if !has(self.minutes, minute as u32) {
+                time += Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = GlintError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Schedule::parse(s)
+    }
+}
+
+fn has(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+fn invalid(expr: &str, reason: &str) -> GlintError {
+    GlintError::InvalidPattern {
+        pattern: expr.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+/// Parse one field into a bit set of allowed values.
+fn parse_field(expr: &str, field: &str, min: u32, max: u32) -> Result<u64> {
+    let number = |s: &str| -> Result<u32> {
+        let value: u32 = s
+            .parse()
+            .map_err(|_| invalid(expr, &format!("'{}' is not a number", s)))?;
+        if value < min || value > max {
+            return Err(invalid(
+                expr,
+                &format!("{} is out of range {}-{}", value, min, max),
+            ));
+        }
+        Ok(value)
+    };
+
+    let mut set = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, number_step(expr, step)?),
+            None => (item, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (number(a)?, number(b)?)
+        } else {
+            let value = number(range)?;
+            // "5/15" means from 5 to the end in steps of 15
+            (value, if step > 1 { max } else { value })
+        };
+
+        if start > end {
+            return Err(invalid(expr, &format!("range {} is backwards", range)));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
+
+fn number_step(expr: &str, step: &str) -> Result<u32> {
+    match step.parse::<u32>() {
+        Ok(step) if step > 0 => Ok(step),
+        _ => Err(invalid(expr, &format!("invalid step '{}'", step))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Schedule::parse("* * * *").is_err());
+        assert!(Schedule::parse("60 * * * *").is_err());
+        assert!(Schedule::parse("*/0 * * * *").is_err());
+        assert!(Schedule::parse("5-1 * * * *").is_err());
+        assert!(Schedule::parse("@daily").is_ok());
+    }
+
+    #[test]
+    fn test_next_after() {
+        // Every Monday at 08:00; 2024-01-03 is a Wednesday
+        let weekly = Schedule::parse("0 8 * * 1").unwrap();
+        assert_eq!(
+            weekly.next_after(&at("2024-01-03T12:00:00Z")),
+            Some(at("2024-01-08T08:00:00Z"))
+        );
+
+        let quarter = Schedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(
+            quarter.next_after(&at("2024-01-01T10:14:30Z")),
+            Some(at("2024-01-01T10:15:00Z"))
+        );
+        // Strictly after
+        assert_eq!(
+            quarter.next_after(&at("2024-01-01T10:15:00Z")),
+            Some(at("2024-01-01T10:30:00Z"))
+        );
+
+        let leap = Schedule::parse("0 0 29 2 *").unwrap();
+        assert_eq!(
+            leap.next_after(&at("2024-03-01T00:00:00Z")),
+            Some(at("2028-02-29T00:00:00Z"))
+        );
+        assert_eq!(
+            Schedule::parse("0 0 31 2 *")
+                .unwrap()
+                .next_after(&at("2024-01-01T00:00:00Z")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_day_or_weekday() {
+        // The 1st of the month or any Sunday (7 = Sunday)
+        let schedule = Schedule::parse("0 0 1 * 7").unwrap();
+        assert!(schedule.matches(&at("2024-02-01T00:00:00Z"))); // Thursday the 1st
+        assert!(schedule.matches(&at("2024-02-04T00:00:00Z"))); // Sunday
+        assert!(!schedule.matches(&at("2024-02-05T00:00:00Z")));
+    }
+}
@@ -0,0 +1,157 @@
+//! Named search scopes.
+//!
+//! A scope is a named set of folders or volumes, such as "Work" for
+//! `C:\repos` and `D:\docs`. Scopes are defined in the `[scopes]` section of
+//! the config file and selected with a `scope:NAME` query token, the
+//! `--scope` CLI flag or the GUI scope dropdown. A selected scope becomes a
+//! single [`SearchFilter::InFolders`] prefix filter.
+
+use crate::error::{GlintError, Result};
+use crate::search::{parse_query, SearchFilter, SearchQuery};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Scopes by name, each a list of folders or volumes (e.g. "D:").
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Scopes(BTreeMap<String, Vec<String>>);
+
+impl Scopes {
+    /// Create an empty set of scopes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Define (or replace) a scope.
+    pub fn insert(&mut self, name: impl Into<String>, paths: Vec<String>) {
+        self.0.insert(name.into(), paths);
+    }
+
+    /// Check if no scopes are defined.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Scope names in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    /// The folders of a scope, looked up ignoring case.
+    pub fn resolve(&self, name: &str) -> Result<&[String]> {
+        self.0
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, paths)| paths.as_slice())
+            .ok_or_else(|| GlintError::InvalidPattern {
+                pattern: format!("scope:{}", name),
+                reason: if self.is_empty() {
+                    "no scopes are defined in the config file".to_string()
+                } else {
+                    format!(
+                        "unknown scope (defined: {})",
+                        self.names().collect::<Vec<_>>().join(", ")
+                    )
+                },
+            })
+    }
+
+    /// Take the `scope:NAME` tokens out of a query string.
+    ///
+    /// Returns the rest of the query and the folders of all named scopes,
+    /// to be applied with [`SearchFilter::in_folders`].
+    pub fn split_query(&self, query: &str) -> Result<(String, Vec<String>)> {
+        if !query.contains("scope:") {
+            return Ok((query.to_string(), Vec::new()));
+        }
+        let mut rest = Vec::new();
+        let mut folders = Vec::new();
+        for part in query.split_whitespace() {
+            match part.strip_prefix("scope:") {
+                Some(name) => folders.extend_from_slice(self.resolve(name)?),
+                None => rest.push(part),
+            }
+        }
+        Ok((rest.join(" "), folders))
+    }
+
+    /// Parse a query string that may contain `scope:NAME` tokens.
+    pub fn parse_query(&self, input: &str) -> Result<SearchQuery> {
+        let (rest, folders) = self.split_query(input)?;
+        let query = parse_query(&rest)?;
+        Ok(if folders.is_empty() {
+            query
+        } else {
+            query.with_filter(SearchFilter::in_folders(&folders))
+        })
+    }
+}
+
+impl FromIterator<(String, Vec<String>)> for Scopes {
+    fn from_iter<I: IntoIterator<Item = (String, Vec<String>)>>(iter: I) -> Self {
+        Scopes(iter.into_iter().collect())
+    }
+}
+
+impl SearchFilter {
+    /// Filter matching anything inside one of `folders`.
+    pub fn in_folders(folders: &[String]) -> Self {
+        SearchFilter::InFolders(
+            folders
+                .iter()
+                .map(|f| f.trim_end_matches(['\\', '/']).to_lowercase())
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{FileId, FileRecord, VolumeId};
+
+    fn make_scopes() -> Scopes {
+        [
+            (
+                "Work".to_string(),
+                vec!["C:\\repos".to_string(), "D:\\".to_string()],
+            ),
+            ("Photos".to_string(), vec!["E:\\Photos".to_string()]),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    fn make_record(path: &str) -> FileRecord {
+        let name = path.rsplit('\\').next().unwrap_or(path);
+        FileRecord::new(
+            FileId::new(1),
+            None,
+            VolumeId::new("C"),
+            name.to_string(),
+            path.to_string(),
+            false,
+        )
+    }
+
+    #[test]
+    fn test_split_query() {
+        let scopes = make_scopes();
+        let (rest, folders) = scopes.split_query("report scope:work ext:pdf").unwrap();
+        assert_eq!(rest, "report ext:pdf");
+        assert_eq!(folders, vec!["C:\\repos", "D:\\"]);
+
+        assert!(scopes.split_query("scope:Music").is_err());
+        assert!(Scopes::new().split_query("scope:Work").is_err());
+    }
+
+    #[test]
+    fn test_in_folders() {
+        let query = make_scopes().parse_query("scope:Work").unwrap();
+
+        assert!(query.matches(&make_record("C:\\Repos\\glint\\main.rs")));
+        assert!(query.matches(&make_record("D:\\notes.txt")));
+        assert!(!query.matches(&make_record("C:\\repository\\main.rs")));
+        assert!(!query.matches(&make_record("E:\\Photos\\cat.jpg")));
+    }
+}
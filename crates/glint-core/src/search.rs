@@ -5,6 +5,7 @@
 //! - Wildcard/glob patterns (*, ?)
 //! - Regular expression matching
 //! - Filtering by type (file/directory) and extension
+//! - Typo-tolerant fallback for substring queries ([`SearchQuery::fuzzy`])
 //!
 //! ## Performance
 //!
@@ -13,9 +14,19 @@
 //! - Returns results as an iterator for incremental display
 //! - Pre-computes lowercase names for fast case-insensitive matching
 
+use crate::backend::{BackendCapabilities, VolumeInfo};
+use crate::clock::{Clock, SystemClock};
 use crate::error::{GlintError, Result};
-use crate::types::FileRecord;
+use crate::extensions::ExtensionSet;
+use crate::records::Row;
+use crate::report::parse_age;
+use crate::types::{to_lower, FileId, FileRecord, VolumeId};
+use chrono::{DateTime, Utc};
+use memchr::memmem::Finder;
 use regex::Regex;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::ops::Range;
 use std::sync::Arc;
 
 /// A compiled search query ready for matching.
@@ -32,6 +43,9 @@ pub struct SearchQuery {
 
     /// Whether to search in paths (true) or just filenames (false)
     search_path: bool,
+
+    /// Whether to search recently deleted files instead of the index
+    deleted: bool,
 }
 
 impl std::fmt::Debug for SearchQuery {
@@ -39,6 +53,7 @@ impl std::fmt::Debug for SearchQuery {
         f.debug_struct("SearchQuery")
             .field("filters", &self.filters)
             .field("search_path", &self.search_path)
+            .field("deleted", &self.deleted)
             .finish()
     }
 }
@@ -59,6 +74,7 @@ impl SearchQuery {
             matcher: Arc::new(SubstringMatcher::new(pattern)),
             filters: Vec::new(),
             search_path: false,
+            deleted: false,
         }
     }
 
@@ -77,6 +93,7 @@ impl SearchQuery {
             matcher: Arc::new(matcher),
             filters: Vec::new(),
             search_path: false,
+            deleted: false,
         })
     }
 
@@ -99,6 +116,7 @@ impl SearchQuery {
             matcher: Arc::new(RegexMatcher { regex: re }),
             filters: Vec::new(),
             search_path: false,
+            deleted: false,
         })
     }
 
@@ -108,6 +126,7 @@ impl SearchQuery {
             matcher: Arc::new(ExactMatcher::new(name)),
             filters: Vec::new(),
             search_path: false,
+            deleted: false,
         }
     }
 
@@ -125,30 +144,179 @@ impl SearchQuery {
         self
     }
 
+    /// Set whether to search the files deleted recently (see
+    /// [`RecentlyDeleted`](crate::RecentlyDeleted)) instead of the index,
+    /// newest first.
+    pub fn search_deleted(mut self, deleted: bool) -> Self {
+        self.deleted = deleted;
+        self
+    }
+
+    /// Whether the query searches recently deleted files.
+    pub fn searches_deleted(&self) -> bool {
+        self.deleted
+    }
+
     /// Check if a record matches this query.
     ///
     /// First applies the pattern matcher, then all filters.
     pub fn matches(&self, record: &FileRecord) -> bool {
+        self.matches_named(record, &record.name_lower())
+    }
+
+    /// Check if a record matches this query, given its lowercase name, as
+    /// the index keeps it apart from the record.
+    pub(crate) fn matches_named(&self, record: &FileRecord, name_lower: &str) -> bool {
         // Get the text to search in
         let text = if self.search_path {
-            &record.path_lower
+            record.path_lower()
         } else {
-            &record.name_lower
+            Cow::Borrowed(name_lower)
         };
 
         // Apply pattern matcher
-        if !self.matcher.matches(text, record) {
+        if !self.matcher.matches(&text) {
             return false;
         }
 
         // Apply all filters
-        self.filters.iter().all(|f| f.matches(record))
+        self.filters
+            .iter()
+            .all(|f| f.matches_named(record, name_lower))
+    }
+
+    /// Check a record's column values (see [`crate::records`]) against this
+    /// query: `Some` if they settle whether it matches, `None` if only the
+    /// record itself can tell, as for times and volumes. Path patterns and
+    /// path filters are only settled given the lowercase path.
+    ///
+    /// Agrees with [`SearchQuery::matches`] whenever it answers.
+    pub(crate) fn matches_row(&self, row: &Row, path_lower: Option<&str>) -> Option<bool> {
+        let text = match self.search_path {
+            true => path_lower,
+            false => Some(row.name_lower()),
+        };
+        let mut settled = text.is_some();
+        if text.is_some_and(|text| !self.matcher.matches(text)) {
+            return Some(false);
+        }
+        for filter in &self.filters {
+            match filter.matches_row(row, path_lower) {
+                Some(false) => return Some(false),
+                Some(true) => {}
+                None => settled = false,
+            }
+        }
+        settled.then_some(true)
+    }
+
+    /// Whether matching this query reads records' paths.
+    pub(crate) fn reads_paths(&self) -> bool {
+        self.search_path || self.filters.iter().any(SearchFilter::reads_path)
+    }
+
+    /// Text of the pattern's capture groups in `record`, in order.
+    ///
+    /// Only regex patterns have capture groups; other queries, and groups
+    /// that took no part in the match, give empty strings. Groups are read
+    /// from the name (or path) as written, not lowercased.
+    pub fn captures(&self, record: &FileRecord) -> Vec<String> {
+        if self.matcher.capture_groups() == 0 {
+            return Vec::new();
+        }
+        let text = if self.search_path {
+            &record.path
+        } else {
+            &record.name
+        };
+        self.matcher.captures(text)
+    }
+
+    /// Number of capture groups in the pattern.
+    pub fn capture_groups(&self) -> usize {
+        self.matcher.capture_groups()
+    }
+
+    /// The lowercase text a plain substring or exact query looks for, used
+    /// to rank how closely names match it.
+    pub fn literal(&self) -> Option<&str> {
+        self.matcher.literal()
+    }
+
+    /// Text every name this query matches contains, if it matches names
+    /// (not paths) by plain text.
+    pub(crate) fn name_literal(&self) -> Option<&str> {
+        self.literal().filter(|_| !self.search_path)
     }
 
     /// Check if this query would match everything (empty pattern)
     pub fn matches_all(&self) -> bool {
-        self.matcher.matches_all() && self.filters.is_empty()
+        self.matcher.matches_all() && self.filters.is_empty() && !self.deleted
     }
+
+    /// A typo-tolerant version of this query.
+    ///
+    /// Matches names containing the pattern with at most one inserted,
+    /// deleted or substituted character, keeping all filters. Only plain
+    /// substring queries of 3 to 32 characters have a fuzzy version;
+    /// shorter patterns would match almost everything.
+    pub fn fuzzy(&self) -> Option<SearchQuery> {
+        let matcher = self.matcher.fuzzy()?;
+        Some(SearchQuery {
+            matcher,
+            filters: self.filters.clone(),
+            search_path: self.search_path,
+            deleted: self.deleted,
+        })
+    }
+
+    /// This query without the filters on fields `capabilities` says scans
+    /// leave empty, which would otherwise match nothing (or everything).
+    pub fn supported_by(&self, capabilities: &BackendCapabilities) -> Cow<'_, SearchQuery> {
+        if self.filters.iter().all(|f| f.is_supported_by(capabilities)) {
+            return Cow::Borrowed(self);
+        }
+        Cow::Owned(SearchQuery {
+            filters: self
+                .filters
+                .iter()
+                .filter(|f| f.is_supported_by(capabilities))
+                .cloned()
+                .collect(),
+            ..self.clone()
+        })
+    }
+
+    /// Check if records on this volume could match.
+    ///
+    /// Only folder and path filters are considered, so this is true unless
+    /// the query is limited to other volumes.
+    pub fn may_match_volume(&self, volume: &VolumeInfo) -> bool {
+        let root = to_lower(volume.display_name().trim_end_matches(['\\', '/']));
+        self.filters.iter().all(|filter| match filter {
+            SearchFilter::PathPrefix(prefix) => {
+                prefix.starts_with(root.as_str()) || root.starts_with(prefix.as_str())
+            }
+            SearchFilter::InFolders(folders) => folders
+                .iter()
+                .any(|folder| in_folder(folder, &root) || in_folder(&root, folder)),
+            SearchFilter::Volume(id) => *id == volume.id,
+            SearchFilter::FileId(id, _) => same_volume(&volume.id, id),
+            _ => true,
+        })
+    }
+}
+
+/// Check if two volume IDs are the same, ignoring case as Windows does for
+/// the serial numbers and paths they are made of.
+fn same_volume(a: &VolumeId, b: &VolumeId) -> bool {
+    a.as_str().eq_ignore_ascii_case(b.as_str())
+}
+
+/// Check if `path` is `folder` or inside it (both lowercase, no trailing separator).
+fn in_folder(path: &str, folder: &str) -> bool {
+    path.starts_with(folder)
+        && matches!(path[folder.len()..].chars().next(), None | Some('\\' | '/'))
 }
 
 /// Filters to narrow search results.
@@ -161,10 +329,10 @@ pub enum SearchFilter {
     DirsOnly,
 
     /// Only match files with specific extensions
-    Extensions(Vec<String>),
+    Extensions(ExtensionSet),
 
     /// Exclude files with specific extensions
-    ExcludeExtensions(Vec<String>),
+    ExcludeExtensions(ExtensionSet),
 
     /// Only match files larger than this size
     MinSize(u64),
@@ -172,37 +340,139 @@ pub enum SearchFilter {
     /// Only match files smaller than this size
     MaxSize(u64),
 
-    /// Only match files in this path prefix
+    /// Only match files last modified before this time
+    ModifiedBefore(DateTime<Utc>),
+
+    /// Only match files last modified at or after this time
+    ModifiedAfter(DateTime<Utc>),
+
+    /// Only match files in this path prefix (lowercase)
     PathPrefix(String),
 
-    /// Exclude files in this path prefix
+    /// Exclude files in this path prefix (lowercase)
     ExcludePath(String),
+
+    /// Only match files inside any of these folders (lowercase, without
+    /// trailing separators; see [`SearchFilter::in_folders`])
+    InFolders(Vec<String>),
+
+    /// Only match online-only cloud files (true) or local ones (false)
+    Cloud(bool),
+
+    /// Only match files on this volume; unlike a path filter, this still
+    /// holds when the volume's drive letter changes
+    Volume(VolumeId),
+
+    /// Only match the record with this file ID on this volume, as USN
+    /// journal entries and audit logs name files
+    FileId(VolumeId, FileId),
+
+    /// Exclude files whose name, or the name of a folder they are in,
+    /// matches this glob (see [`SearchFilter::exclude_glob`])
+    ExcludeGlob(Regex),
 }
 
 impl SearchFilter {
+    /// Whether records of a backend with `capabilities` have the field this
+    /// filter tests.
+    pub fn is_supported_by(&self, capabilities: &BackendCapabilities) -> bool {
+        match self {
+            SearchFilter::MinSize(_) | SearchFilter::MaxSize(_) => capabilities.provides_sizes,
+            SearchFilter::ModifiedBefore(_) | SearchFilter::ModifiedAfter(_) => {
+                capabilities.provides_timestamps
+            }
+            _ => true,
+        }
+    }
+
     /// Check if a record matches this filter.
     pub fn matches(&self, record: &FileRecord) -> bool {
         match self {
             SearchFilter::FilesOnly => !record.is_dir,
             SearchFilter::DirsOnly => record.is_dir,
-            SearchFilter::Extensions(exts) => record.extension().map_or(false, |e| {
-                exts.iter().any(|ext| e.eq_ignore_ascii_case(ext))
-            }),
-            SearchFilter::ExcludeExtensions(exts) => record.extension().map_or(true, |e| {
-                !exts.iter().any(|ext| e.eq_ignore_ascii_case(ext))
-            }),
+            SearchFilter::Extensions(exts) => exts.contains(record),
+            SearchFilter::ExcludeExtensions(exts) => !exts.contains(record),
             SearchFilter::MinSize(size) => record.size.map_or(false, |s| s >= *size),
             SearchFilter::MaxSize(size) => record.size.map_or(true, |s| s <= *size),
-            SearchFilter::PathPrefix(prefix) => record
-                .path
-                .to_lowercase()
-                .starts_with(&prefix.to_lowercase()),
-            SearchFilter::ExcludePath(prefix) => !record
-                .path
-                .to_lowercase()
-                .starts_with(&prefix.to_lowercase()),
+            SearchFilter::ModifiedBefore(time) => record.modified.is_some_and(|m| m < *time),
+            SearchFilter::ModifiedAfter(time) => record.modified.is_some_and(|m| m >= *time),
+            SearchFilter::PathPrefix(prefix) => record.path_lower().starts_with(prefix.as_str()),
+            SearchFilter::ExcludePath(prefix) => !record.path_lower().starts_with(prefix.as_str()),
+            SearchFilter::InFolders(folders) => {
+                let path_lower = record.path_lower();
+                folders.iter().any(|folder| in_folder(&path_lower, folder))
+            }
+            SearchFilter::Cloud(cloud) => record.cloud == *cloud,
+            SearchFilter::Volume(id) => record.volume_id == *id,
+            SearchFilter::FileId(volume, id) => {
+                record.id == *id && same_volume(&record.volume_id, volume)
+            }
+            SearchFilter::ExcludeGlob(glob) => !record
+                .path_lower()
+                .split(['\\', '/'])
+                .any(|part| glob.is_match(part)),
+        }
+    }
+
+    /// Check if a record matches this filter, given its lowercase name.
+    fn matches_named(&self, record: &FileRecord, name_lower: &str) -> bool {
+        let contains = |exts: &ExtensionSet| {
+            exts.contains_row(name_lower, record.ext_id)
+                .unwrap_or_else(|| exts.contains(record))
+        };
+        match self {
+            SearchFilter::Extensions(exts) => contains(exts),
+            SearchFilter::ExcludeExtensions(exts) => !contains(exts),
+            _ => self.matches(record),
         }
     }
+
+    /// Whether this filter tests the record's path.
+    fn reads_path(&self) -> bool {
+        matches!(
+            self,
+            SearchFilter::PathPrefix(_)
+                | SearchFilter::ExcludePath(_)
+                | SearchFilter::InFolders(_)
+                | SearchFilter::ExcludeGlob(_)
+        )
+    }
+
+    /// Check a record's column values, and its lowercase path if given,
+    /// against this filter, or `None` if the filter needs more of the
+    /// record.
+    fn matches_row(&self, row: &Row, path_lower: Option<&str>) -> Option<bool> {
+        match self {
+            SearchFilter::FilesOnly => Some(!row.is_dir),
+            SearchFilter::DirsOnly => Some(row.is_dir),
+            SearchFilter::Extensions(exts) => exts.contains_row(row.name_lower(), row.ext_id),
+            SearchFilter::ExcludeExtensions(exts) => exts
+                .contains_row(row.name_lower(), row.ext_id)
+                .map(|contains| !contains),
+            SearchFilter::MinSize(size) => Some(row.size.is_some_and(|s| s >= *size)),
+            SearchFilter::MaxSize(size) => Some(row.size.map_or(true, |s| s <= *size)),
+            SearchFilter::Cloud(cloud) => Some(row.cloud == *cloud),
+            SearchFilter::PathPrefix(prefix) => path_lower.map(|p| p.starts_with(prefix.as_str())),
+            SearchFilter::ExcludePath(prefix) => {
+                path_lower.map(|p| !p.starts_with(prefix.as_str()))
+            }
+            SearchFilter::InFolders(folders) => {
+                path_lower.map(|p| folders.iter().any(|folder| in_folder(p, folder)))
+            }
+            SearchFilter::ExcludeGlob(glob) => {
+                path_lower.map(|p| !p.split(['\\', '/']).any(|part| glob.is_match(part)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Exclude files matching a glob such as `*.tmp` or `node_modules`.
+    ///
+    /// The glob is matched against each part of the path, so excluding a
+    /// folder name also excludes everything inside it.
+    pub fn exclude_glob(pattern: &str) -> Result<Self> {
+        Ok(SearchFilter::ExcludeGlob(glob_regex(pattern)?))
+    }
 }
 
 /// A search result with relevance scoring.
@@ -213,12 +483,124 @@ pub struct SearchResult {
 
     /// Relevance score (higher is more relevant)
     pub score: u32,
+
+    /// Matched by the typo-tolerant fallback rather than the query itself
+    pub fuzzy: bool,
+
+    /// Number of indexed paths of this file (1 unless merged by
+    /// [`Index::dedup_links`](crate::Index::dedup_links))
+    pub links: usize,
+
+    /// Text of the query's regex capture groups (see
+    /// [`SearchQuery::captures`]); empty when the query has none
+    pub captures: Vec<String>,
+
+    /// Name of the attached index the record comes from, or `None` for
+    /// the local index (see [`crate::federation`])
+    pub source: Option<Arc<str>>,
+
+    /// When the file was deleted, for results of a `deleted:` search (see
+    /// [`SearchQuery::search_deleted`])
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 impl SearchResult {
     /// Create a new search result
     pub fn new(record: FileRecord, score: u32) -> Self {
-        SearchResult { record, score }
+        SearchResult {
+            record,
+            score,
+            fuzzy: false,
+            links: 1,
+            captures: Vec::new(),
+            source: None,
+            deleted_at: None,
+        }
+    }
+
+    /// Compare two results by rank: higher score first, then by volume ID,
+    /// then by file ID, then by source.
+    ///
+    /// A file has one record per volume of each index, so no two results
+    /// of one search are equal and the order does not depend on where records sit in the
+    /// index. [`Index::search_ranked`](crate::Index::search_ranked) pages
+    /// through results in this order.
+    pub fn rank_cmp(&self, other: &SearchResult) -> Ordering {
+        other
+            .score
+            .cmp(&self.score)
+            .then_with(|| {
+                self.record
+                    .volume_id
+                    .as_str()
+                    .cmp(other.record.volume_id.as_str())
+            })
+            .then_with(|| self.record.id.as_u64().cmp(&other.record.id.as_u64()))
+            .then_with(|| self.source.cmp(&other.source))
+    }
+}
+
+/// A page of the results of a search and how many records matched in total.
+#[derive(Debug, Clone, Default)]
+pub struct SearchPage {
+    /// The matches on this page: the first ones in index order from
+    /// [`Index::search_counted`](crate::Index::search_counted), or in rank
+    /// order from [`Index::search_ranked`](crate::Index::search_ranked)
+    pub results: Vec<SearchResult>,
+
+    /// Number of matching records, including those not in `results`
+    pub total: usize,
+
+    /// Files, folders and size of all matching records
+    pub totals: ResultTotals,
+}
+
+/// Combined count and size of a set of results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResultTotals {
+    pub files: usize,
+    pub dirs: usize,
+    /// Sum of the known file sizes, in bytes
+    pub size: u64,
+}
+
+impl ResultTotals {
+    /// Totals of `records`.
+    pub fn of<'a>(records: impl IntoIterator<Item = &'a FileRecord>) -> Self {
+        let mut totals = ResultTotals::default();
+        for record in records {
+            totals.add(record);
+        }
+        totals
+    }
+
+    /// Count `record` in.
+    pub fn add(&mut self, record: &FileRecord) {
+        if record.is_dir {
+            self.dirs += 1;
+        } else {
+            self.files += 1;
+            self.size += record.size.unwrap_or(0);
+        }
+    }
+
+    /// Count a record by its column values, as [`ResultTotals::add`] does.
+    pub(crate) fn add_row(&mut self, row: &Row) {
+        if row.is_dir {
+            self.dirs += 1;
+        } else {
+            self.files += 1;
+            self.size += row.size.unwrap_or(0);
+        }
+    }
+
+    /// Combine the totals of two sets of results.
+    pub fn merge(self, other: ResultTotals) -> Self {
+        ResultTotals {
+            files: self.files + other.files,
+            dirs: self.dirs + other.dirs,
+            size: self.size + other.size,
+        }
     }
 }
 
@@ -227,42 +609,117 @@ impl SearchResult {
 /// Trait for pattern matching implementations.
 trait Matcher: Send + Sync {
     /// Check if the given text matches this pattern.
-    ///
-    /// The `record` parameter is provided for matchers that need additional
-    /// context (though most just use `text`).
-    fn matches(&self, text: &str, record: &FileRecord) -> bool;
+    fn matches(&self, text: &str) -> bool;
 
     /// Returns true if this matcher matches everything
     fn matches_all(&self) -> bool {
         false
     }
+
+    /// A matcher that also accepts one typo, if this matcher supports it.
+    fn fuzzy(&self) -> Option<Arc<dyn Matcher>> {
+        None
+    }
+
+    /// Number of capture groups the pattern has.
+    fn capture_groups(&self) -> usize {
+        0
+    }
+
+    /// Text of each capture group in `text`.
+    fn captures(&self, _text: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// The lowercase text the pattern looks for, if it is plain text.
+    fn literal(&self) -> Option<&str> {
+        None
+    }
 }
 
 /// Case-insensitive substring matcher.
+///
+/// The needle is compiled once into a SIMD-accelerated `memmem` searcher
+/// and run over the cached lowercase text, so matching never allocates.
 struct SubstringMatcher {
     pattern_lower: String,
+    finder: Finder<'static>,
 }
 
 impl SubstringMatcher {
     fn new(pattern: &str) -> Self {
+        let pattern_lower = to_lower(pattern);
+        let finder = Finder::new(pattern_lower.as_bytes()).into_owned();
         SubstringMatcher {
-            pattern_lower: pattern.to_lowercase(),
+            pattern_lower,
+            finder,
         }
     }
 }
 
 impl Matcher for SubstringMatcher {
-    fn matches(&self, text: &str, _record: &FileRecord) -> bool {
+    fn matches(&self, text: &str) -> bool {
         if self.pattern_lower.is_empty() {
             return true;
         }
         // `text` is already lowercase (name_lower or path_lower)
-        text.contains(&self.pattern_lower)
+        self.finder.find(text.as_bytes()).is_some()
     }
 
     fn matches_all(&self) -> bool {
         self.pattern_lower.is_empty()
     }
+
+    fn literal(&self) -> Option<&str> {
+        Some(self.pattern_lower.as_str()).filter(|p| !p.is_empty())
+    }
+
+    fn fuzzy(&self) -> Option<Arc<dyn Matcher>> {
+        let pattern: Vec<char> = self.pattern_lower.chars().collect();
+        if (MIN_FUZZY_LEN..=MAX_FUZZY_LEN).contains(&pattern.len()) {
+            Some(Arc::new(FuzzyMatcher { pattern }))
+        } else {
+            None
+        }
+    }
+}
+
+/// Shortest pattern that gets a typo-tolerant fallback.
+const MIN_FUZZY_LEN: usize = 3;
+
+/// Longest pattern that gets a typo-tolerant fallback (bounds the work per name).
+const MAX_FUZZY_LEN: usize = 32;
+
+/// Substring matcher allowing one edit (Levenshtein distance 1).
+///
+/// Uses Sellers' dynamic programming over the text, where a match may start
+/// at any position. Distances are capped at 2 since only 0 and 1 matter.
+struct FuzzyMatcher {
+    pattern: Vec<char>,
+}
+
+impl Matcher for FuzzyMatcher {
+    fn matches(&self, text: &str) -> bool {
+        let m = self.pattern.len();
+        // column[j] = fewest edits turning pattern[..j] into a suffix of the text so far
+        let mut column = [0u8; MAX_FUZZY_LEN + 1];
+        for (j, cell) in column.iter_mut().enumerate().take(m + 1) {
+            *cell = j.min(2) as u8;
+        }
+
+        for c in text.chars() {
+            let mut diagonal = column[0];
+            for j in 1..=m {
+                let substitution = diagonal + u8::from(self.pattern[j - 1] != c);
+                diagonal = column[j];
+                column[j] = substitution.min(column[j] + 1).min(column[j - 1] + 1).min(2);
+            }
+            if column[m] <= 1 {
+                return true;
+            }
+        }
+        false
+    }
 }
 
 /// Exact name matcher (case-insensitive).
@@ -273,16 +730,20 @@ struct ExactMatcher {
 impl ExactMatcher {
     fn new(pattern: &str) -> Self {
         ExactMatcher {
-            pattern_lower: pattern.to_lowercase(),
+            pattern_lower: to_lower(pattern),
         }
     }
 }
 
 impl Matcher for ExactMatcher {
-    fn matches(&self, text: &str, _record: &FileRecord) -> bool {
+    fn matches(&self, text: &str) -> bool {
         // `text` is already lowercase (name_lower or path_lower)
         text == self.pattern_lower
     }
+
+    fn literal(&self) -> Option<&str> {
+        Some(&self.pattern_lower)
+    }
 }
 
 /// Wildcard pattern matcher.
@@ -294,53 +755,409 @@ struct WildcardMatcher {
 
 impl WildcardMatcher {
     fn new(pattern: &str) -> Result<Self> {
-        // Convert glob pattern to regex
-        let mut regex_pattern = String::with_capacity(pattern.len() * 2 + 4);
-        regex_pattern.push_str("(?i)^");
-
-        for c in pattern.chars() {
-            match c {
-                '*' => regex_pattern.push_str(".*"),
-                '?' => regex_pattern.push('.'),
-                // Escape regex special characters
-                '.' | '+' | '(' | ')' | '[' | ']' | '{' | '}' | '^' | '$' | '|' | '\\' => {
-                    regex_pattern.push('\\');
-                    regex_pattern.push(c);
-                }
-                _ => regex_pattern.push(c),
-            }
-        }
-
-        regex_pattern.push('$');
-
-        let regex = Regex::new(&regex_pattern).map_err(|e| GlintError::InvalidPattern {
-            pattern: pattern.to_string(),
-            reason: e.to_string(),
-        })?;
-
-        Ok(WildcardMatcher { regex })
+        Ok(WildcardMatcher {
+            regex: glob_regex(pattern)?,
+        })
     }
 }
 
 impl Matcher for WildcardMatcher {
-    fn matches(&self, text: &str, _record: &FileRecord) -> bool {
+    fn matches(&self, text: &str) -> bool {
         self.regex.is_match(text)
     }
 }
 
+/// Compile a glob pattern (`*` and `?`) into a case-insensitive regex
+/// matching whole names.
+fn glob_regex(pattern: &str) -> Result<Regex> {
+    // Convert glob pattern to regex
+    let mut regex_pattern = String::with_capacity(pattern.len() * 2 + 4);
+    regex_pattern.push_str("(?i)^");
+
+    for c in pattern.chars() {
+        match c {
+            '*' => regex_pattern.push_str(".*"),
+            '?' => regex_pattern.push('.'),
+            // Escape regex special characters
+            '.' | '+' | '(' | ')' | '[' | ']' | '{' | '}' | '^' | '$' | '|' | '\\' => {
+                regex_pattern.push('\\');
+                regex_pattern.push(c);
+            }
+            _ => regex_pattern.push(c),
+        }
+    }
+
+    regex_pattern.push('$');
+
+    Regex::new(&regex_pattern).map_err(|e| GlintError::InvalidPattern {
+        pattern: pattern.to_string(),
+        reason: e.to_string(),
+    })
+}
+
 /// Regular expression matcher.
 struct RegexMatcher {
     regex: Regex,
 }
 
 impl Matcher for RegexMatcher {
-    fn matches(&self, text: &str, _record: &FileRecord) -> bool {
+    fn matches(&self, text: &str) -> bool {
         self.regex.is_match(text)
     }
+
+    fn capture_groups(&self) -> usize {
+        self.regex.captures_len() - 1
+    }
+
+    fn captures(&self, text: &str) -> Vec<String> {
+        let groups = self.capture_groups();
+        match self.regex.captures(text) {
+            Some(caps) => (1..=groups)
+                .map(|i| caps.get(i).map_or("", |m| m.as_str()).to_string())
+                .collect(),
+            None => vec![String::new(); groups],
+        }
+    }
 }
 
 // === Query Parsing ===
 
+/// What follows the colon of a query token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenValue {
+    /// Nothing, as in `file:`
+    None,
+    /// Comma-separated extensions, as in `ext:rs,txt`
+    Extensions,
+    /// The start of a folder path, as in `in:C:\Users`
+    Folder,
+    /// An age, as in `older-than:2y`
+    Age,
+    /// A scope name from the config, as in `scope:Work`
+    Scope,
+    /// One of these words, or nothing for the first
+    OneOf(&'static [&'static str]),
+    /// A volume ID and file ID, as in `fileid:1A2B3C4D:1234`
+    FileId,
+}
+
+impl TokenValue {
+    /// How the value is written in the syntax reference, such as `<age>`.
+    pub fn placeholder(self) -> String {
+        match self {
+            TokenValue::None => String::new(),
+            TokenValue::Extensions => "<ext>[,<ext>...]".to_string(),
+            TokenValue::Folder => "<folder>".to_string(),
+            TokenValue::Age => "<age>".to_string(),
+            TokenValue::Scope => "<name>".to_string(),
+            TokenValue::OneOf(words) => format!("[{}]", words.join("|")),
+            TokenValue::FileId => "<volume ID>:<file ID>".to_string(),
+        }
+    }
+
+    /// How values of this kind are read, for the syntax reference.
+    pub fn note(self) -> Option<&'static str> {
+        match self {
+            TokenValue::Extensions => Some(
+                "Extensions of several parts such as tar.gz match as a whole; \
+                 gz matches their last part too.",
+            ),
+            TokenValue::Age => Some(
+                "Ages are a number and a unit: m (minutes), h, d, w, mo (30 days) \
+                 or y (365 days), counted back from now.",
+            ),
+            TokenValue::Scope => {
+                Some("Scopes are lists of folders named under [scopes] in the config file.")
+            }
+            TokenValue::FileId => Some("Volume and file IDs are decimal, or hex after 0x."),
+            TokenValue::None | TokenValue::Folder | TokenValue::OneOf(_) => None,
+        }
+    }
+}
+
+/// A filter token of the query syntax.
+///
+/// [`QUERY_TOKENS`] is what [`parse_query`] reads tokens with, and what
+/// frontends hint at and document them from.
+#[derive(Debug, Clone, Copy)]
+pub struct QueryToken {
+    /// The token up to and including its colon, such as `ext:`
+    pub prefix: &'static str,
+
+    /// Other spellings of the same token
+    pub aliases: &'static [&'static str],
+
+    /// What may follow the colon
+    pub value: TokenValue,
+
+    /// What the token does
+    pub description: &'static str,
+
+    /// A query using the token
+    pub example: &'static str,
+
+    /// Read the part of a query that is this token, given what follows
+    /// the colon
+    parse: fn(part: &str, value: &str, clock: &dyn Clock) -> Result<Part>,
+}
+
+impl QueryToken {
+    /// Whether `part` of a query is this token.
+    pub fn starts(&self, part: &str) -> bool {
+        self.value_of(part).is_some()
+    }
+
+    /// What follows the colon if `part` is this token. Tokens without a
+    /// value only match on their own.
+    pub fn value_of<'a>(&self, part: &'a str) -> Option<&'a str> {
+        std::iter::once(&self.prefix)
+            .chain(self.aliases)
+            .find_map(|prefix| match self.value {
+                TokenValue::None => (part == *prefix).then_some(""),
+                _ => part.strip_prefix(prefix),
+            })
+    }
+
+    /// The token as written in the syntax reference, such as
+    /// `older-than:<age>`.
+    pub fn usage(&self) -> String {
+        format!("{}{}", self.prefix, self.value.placeholder())
+    }
+}
+
+/// The filter tokens [`parse_query`] understands, plus `scope:`, which
+/// frontends take out first.
+pub const QUERY_TOKENS: &[QueryToken] = &[
+    QueryToken {
+        prefix: "ext:",
+        aliases: &[],
+        value: TokenValue::Extensions,
+        description: "Only these extensions",
+        example: "doc ext:md,txt",
+        parse: |_, value, _| {
+            let extensions: Vec<String> = value
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+            Ok(if extensions.is_empty() {
+                Part::Nothing
+            } else {
+                Part::Filter(SearchFilter::Extensions(extensions.into()))
+            })
+        },
+    },
+    QueryToken {
+        prefix: "file:",
+        aliases: &["files:"],
+        value: TokenValue::None,
+        description: "Only files",
+        example: "file: *.log",
+        parse: |_, _, _| Ok(Part::Filter(SearchFilter::FilesOnly)),
+    },
+    QueryToken {
+        prefix: "dir:",
+        aliases: &["dirs:", "folder:"],
+        value: TokenValue::None,
+        description: "Only folders",
+        example: "dir: src",
+        parse: |_, _, _| Ok(Part::Filter(SearchFilter::DirsOnly)),
+    },
+    QueryToken {
+        prefix: "path:",
+        aliases: &[],
+        value: TokenValue::None,
+        description: "Match the full path, not just the name",
+        example: "path: users",
+        parse: |_, _, _| Ok(Part::SearchPath),
+    },
+    QueryToken {
+        prefix: "deleted:",
+        aliases: &[],
+        value: TokenValue::None,
+        description: "Only files deleted recently, newest first",
+        example: "deleted: ext:docx",
+        parse: |_, _, _| Ok(Part::Deleted),
+    },
+    QueryToken {
+        prefix: "in:",
+        aliases: &[],
+        value: TokenValue::Folder,
+        description: "Only inside this folder",
+        example: "in:C:\\Projects *.rs",
+        parse: |_, value, _| Ok(Part::Filter(SearchFilter::PathPrefix(to_lower(value)))),
+    },
+    QueryToken {
+        prefix: "cloud:",
+        aliases: &[],
+        value: TokenValue::OneOf(&["yes", "no"]),
+        description: "Only online-only cloud files, or with cloud:no local ones",
+        example: "cloud: ext:mp4",
+        parse: |part, value, _| {
+            let cloud = match value.to_lowercase().as_str() {
+                "" | "yes" | "true" => true,
+                "no" | "false" => false,
+                _ => {
+                    return Err(GlintError::InvalidPattern {
+                        pattern: part.to_string(),
+                        reason: "expected cloud:, cloud:yes or cloud:no".to_string(),
+                    })
+                }
+            };
+            Ok(Part::Filter(SearchFilter::Cloud(cloud)))
+        },
+    },
+    QueryToken {
+        prefix: "older-than:",
+        aliases: &[],
+        value: TokenValue::Age,
+        description: "Last modified longer ago than this",
+        example: "ext:log older-than:90d",
+        parse: |part, value, clock| {
+            let cutoff = age_cutoff(part, value, clock)?;
+            Ok(Part::Filter(SearchFilter::ModifiedBefore(cutoff)))
+        },
+    },
+    QueryToken {
+        prefix: "newer-than:",
+        aliases: &[],
+        value: TokenValue::Age,
+        description: "Modified within this long",
+        example: "newer-than:12h ext:docx",
+        parse: |part, value, clock| {
+            let cutoff = age_cutoff(part, value, clock)?;
+            Ok(Part::Filter(SearchFilter::ModifiedAfter(cutoff)))
+        },
+    },
+    QueryToken {
+        prefix: "scope:",
+        aliases: &[],
+        value: TokenValue::Scope,
+        description: "Only inside the folders of a scope",
+        example: "scope:Work *.rs",
+        parse: |part, _, _| {
+            Err(GlintError::InvalidPattern {
+                pattern: part.to_string(),
+                reason: "scopes are not supported here".to_string(),
+            })
+        },
+    },
+    QueryToken {
+        prefix: "fileid:",
+        aliases: &[],
+        value: TokenValue::FileId,
+        description: "Only the file with this volume ID and file ID",
+        example: "fileid:1A2B3C4D:0x4D2",
+        parse: |part, value, _| {
+            let invalid = || GlintError::InvalidPattern {
+                pattern: part.to_string(),
+                reason: "expected fileid:<volume ID>:<file ID>".to_string(),
+            };
+            let (volume, id) = value.rsplit_once(':').ok_or_else(invalid)?;
+            let id = id.parse::<FileId>().map_err(|_| invalid())?;
+            if volume.is_empty() {
+                return Err(invalid());
+            }
+            let volume = VolumeId::new(volume);
+            Ok(Part::Filter(SearchFilter::FileId(volume, id)))
+        },
+    },
+];
+
+/// A form of search text, for the syntax reference.
+#[derive(Debug, Clone, Copy)]
+pub struct QueryPattern {
+    /// The pattern as written, such as `r/<regex>/`
+    pub syntax: &'static str,
+
+    /// What it matches
+    pub description: &'static str,
+
+    /// A query using it
+    pub example: &'static str,
+}
+
+/// The forms of search text [`parse_query`] tells apart, in the order it
+/// tries them.
+pub const QUERY_PATTERNS: &[QueryPattern] = &[
+    QueryPattern {
+        syntax: "r/<regex>/",
+        description: "Regular expression",
+        example: "r/test_\\d+/",
+    },
+    QueryPattern {
+        syntax: "*",
+        description: "Any characters (a wildcard pattern)",
+        example: "*.rs",
+    },
+    QueryPattern {
+        syntax: "?",
+        description: "Any single character (a wildcard pattern)",
+        example: "test?.txt",
+    },
+    QueryPattern {
+        syntax: "<text>",
+        description: "Names containing the text, ignoring case",
+        example: "readme",
+    },
+];
+
+/// One whitespace-separated part of a query, as [`parse_query`] reads it.
+#[derive(Debug, Clone)]
+pub struct QueryPart {
+    /// Byte range of the part in the input
+    pub range: Range<usize>,
+
+    /// The filter token the part is, or `None` for search text
+    pub token: Option<&'static QueryToken>,
+
+    /// Why [`parse_query`] rejects the part, if it does
+    pub error: Option<String>,
+}
+
+/// Split `input` into parts the way [`parse_query`] does and check each.
+///
+/// Frontends use this to mark mistakes as the user types. Search text that
+/// doesn't make a valid pattern, such as a broken regex, marks every text
+/// part. `scope:` parts are marked too; callers that resolve scopes check
+/// those themselves.
+pub fn inspect_query(input: &str) -> Vec<QueryPart> {
+    let mut parts: Vec<QueryPart> = input
+        .split_whitespace()
+        .map(|part| {
+            let start = part.as_ptr() as usize - input.as_ptr() as usize;
+            QueryPart {
+                range: start..start + part.len(),
+                token: QUERY_TOKENS.iter().find(|t| t.starts(part)),
+                error: parse_part(part, &SystemClock)
+                    .err()
+                    .map(|e| error_reason(&e)),
+            }
+        })
+        .collect();
+
+    let text: Vec<&str> = parts
+        .iter()
+        .filter(|p| p.token.is_none())
+        .map(|p| &input[p.range.clone()])
+        .collect();
+    if let Err(e) = pattern_query(&text.join(" ")) {
+        let reason = error_reason(&e);
+        for part in parts.iter_mut().filter(|p| p.token.is_none()) {
+            part.error = Some(reason.clone());
+        }
+    }
+    parts
+}
+
+/// The reason of a query error, without the part it is about.
+fn error_reason(error: &GlintError) -> String {
+    match error {
+        GlintError::InvalidPattern { reason, .. } => reason.clone(),
+        other => other.to_string(),
+    }
+}
+
 /// Parse a query string into a SearchQuery.
 ///
 /// Supports various query formats:
@@ -351,15 +1168,24 @@ impl Matcher for RegexMatcher {
 ///
 /// # Query Syntax
 ///
-/// - `pattern` - Search for files containing "pattern" (case-insensitive)
-/// - `*.txt` - Wildcard pattern (matches files ending in .txt)
-/// - `r/regex/` - Regular expression pattern
-/// - `ext:rs` - Filter by extension
-/// - `ext:rs,txt,md` - Filter by multiple extensions
-/// - `file:` - Only show files (not directories)
-/// - `dir:` - Only show directories
-/// - `path:` - Search in full path, not just filename
+/// Each whitespace-separated part is read by the first of the
+/// [`QUERY_TOKENS`] it starts with, and the rest is search text, one of the
+/// [`QUERY_PATTERNS`]. `glint help syntax` prints both.
+///
+/// Ages are a number and a unit: `m`, `h`, `d`, `w`, `mo` or `y`, counted
+/// back from now on the calendar of the [system clock](SystemClock). Files
+/// without a modification time match neither.
+///
+/// `scope:NAME` tokens name folders from the config file and must be taken
+/// out first with [`Scopes::split_query`](crate::scope::Scopes::split_query).
+/// [`inspect_query`] checks each part of a query on its own.
 pub fn parse_query(input: &str) -> Result<SearchQuery> {
+    parse_query_at(input, &SystemClock)
+}
+
+/// Parse a query like [`parse_query`], with ages counted back from the time
+/// of `clock`.
+pub fn parse_query_at(input: &str, clock: &dyn Clock) -> Result<SearchQuery> {
     let input = input.trim();
 
     if input.is_empty() {
@@ -367,62 +1193,82 @@ pub fn parse_query(input: &str) -> Result<SearchQuery> {
     }
 
     let mut search_path = false;
+    let mut deleted = false;
     let mut filters = Vec::new();
     let mut pattern_parts = Vec::new();
 
     // Parse the query into parts
     for part in input.split_whitespace() {
-        if let Some(exts) = part.strip_prefix("ext:") {
-            let extensions: Vec<String> = exts
-                .split(',')
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect();
-            if !extensions.is_empty() {
-                filters.push(SearchFilter::Extensions(extensions));
-            }
-        } else if part == "file:" || part == "files:" {
-            filters.push(SearchFilter::FilesOnly);
-        } else if part == "dir:" || part == "dirs:" || part == "folder:" {
-            filters.push(SearchFilter::DirsOnly);
-        } else if part == "path:" {
-            search_path = true;
-        } else if let Some(prefix) = part.strip_prefix("in:") {
-            filters.push(SearchFilter::PathPrefix(prefix.to_string()));
-        } else {
-            pattern_parts.push(part);
+        match parse_part(part, clock)? {
+            Part::Filter(filter) => filters.push(filter),
+            Part::SearchPath => search_path = true,
+            Part::Deleted => deleted = true,
+            Part::Pattern => pattern_parts.push(part),
+            Part::Nothing => {}
         }
     }
 
-    let pattern = pattern_parts.join(" ");
+    let mut query = pattern_query(&pattern_parts.join(" "))?;
+
+    // Apply filters
+    for filter in filters {
+        query = query.with_filter(filter);
+    }
+
+    query = query.search_in_path(search_path).search_deleted(deleted);
+
+    Ok(query)
+}
+
+/// What a part of a query stands for.
+enum Part {
+    Filter(SearchFilter),
+    SearchPath,
+    Deleted,
+    /// Search text
+    Pattern,
+    /// A token without effect, such as `ext:` with no extensions
+    Nothing,
+}
+
+fn parse_part(part: &str, clock: &dyn Clock) -> Result<Part> {
+    for token in QUERY_TOKENS {
+        if let Some(value) = token.value_of(part) {
+            return (token.parse)(part, value, clock);
+        }
+    }
+    Ok(Part::Pattern)
+}
 
+/// The query for the search text of a query, without its tokens.
+fn pattern_query(pattern: &str) -> Result<SearchQuery> {
     // Determine query type from pattern
-    let mut query = if pattern.starts_with("r/") && pattern.ends_with('/') && pattern.len() > 3 {
+    let query = if pattern.starts_with("r/") && pattern.ends_with('/') && pattern.len() > 3 {
         // Regex pattern
         let regex_pattern = &pattern[2..pattern.len() - 1];
         SearchQuery::regex(regex_pattern)?
     } else if pattern.contains('*') || pattern.contains('?') {
         // Wildcard pattern
-        SearchQuery::wildcard(&pattern)?
+        SearchQuery::wildcard(pattern)?
     } else {
         // Default: substring search
-        SearchQuery::substring(&pattern)
+        SearchQuery::substring(pattern)
     };
-
-    // Apply filters
-    for filter in filters {
-        query = query.with_filter(filter);
-    }
-
-    query = query.search_in_path(search_path);
-
     Ok(query)
 }
 
+/// The time `age` ago, for the `older-than:` and `newer-than:` token `part`.
+fn age_cutoff(part: &str, age: &str, clock: &dyn Clock) -> Result<DateTime<Utc>> {
+    let age = parse_age(age).map_err(|_| GlintError::InvalidPattern {
+        pattern: part.to_string(),
+        reason: "expected an age such as 30d, 6mo or 2y".to_string(),
+    })?;
+    Ok(clock.before(age))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{FileId, VolumeId};
 
     fn make_record(name: &str, is_dir: bool) -> FileRecord {
         FileRecord::new(
@@ -445,6 +1291,188 @@ mod tests {
         assert!(!query.matches(&make_record("other.txt", false)));
     }
 
+    #[test]
+    fn test_matches_row_agrees() {
+        let mut records = crate::records::Records::default();
+        for (name, is_dir) in [
+            ("Report.pdf", false),
+            ("report.tar.gz", false),
+            ("Reports", true),
+            ("photo.averyveryverylongextension", false),
+        ] {
+            records.push(make_record(name, is_dir).with_size(2048));
+        }
+        let queries = [
+            SearchQuery::substring("report"),
+            SearchQuery::exact("reports"),
+            SearchQuery::substring("rep").with_filter(SearchFilter::FilesOnly),
+            SearchQuery::substring("")
+                .with_filter(SearchFilter::Extensions(ExtensionSet::new(["pdf", "tar.gz"]))),
+            SearchQuery::substring("").with_filter(SearchFilter::ExcludeExtensions(
+                ExtensionSet::new(["averyveryverylongextension"]),
+            )),
+            SearchQuery::substring("").with_filter(SearchFilter::MinSize(4096)),
+            SearchQuery::substring("c:\\rep").search_in_path(true),
+            SearchQuery::substring("report").with_filter(SearchFilter::PathPrefix("d:".into())),
+            SearchQuery::substring("").with_filter(SearchFilter::ModifiedAfter(Utc::now())),
+        ];
+        for query in &queries {
+            for i in 0..records.len() {
+                let record = records.cloned(i);
+                let path_lower = record.path_lower();
+                for path in [None, Some(&*path_lower)] {
+                    if let Some(matches) = query.matches_row(&records.row(i), path) {
+                        let expected = query.matches(&record);
+                        assert_eq!(matches, expected, "{:?} {}", query, record.name);
+                    }
+                }
+            }
+        }
+        // Path patterns and filters need the path; times and long
+        // extensions need the record
+        let path = Some("c:\\report.pdf");
+        assert!(queries[6].matches_row(&records.row(0), None).is_none());
+        assert_eq!(queries[6].matches_row(&records.row(0), path), Some(true));
+        assert!(queries[7].matches_row(&records.row(0), None).is_none());
+        assert_eq!(queries[7].matches_row(&records.row(0), path), Some(false));
+        assert!(queries[8].matches_row(&records.row(0), path).is_none());
+        assert!(queries[4].matches_row(&records.row(3), None).is_none());
+        assert_eq!(queries[0].matches_row(&records.row(0), None), Some(true));
+    }
+
+    #[test]
+    fn test_inspect_query() {
+        let input = "report  ext:pdf cloud:maybe older-than:2y";
+        let parts = inspect_query(input);
+        let spans: Vec<&str> = parts.iter().map(|p| &input[p.range.clone()]).collect();
+        assert_eq!(spans, ["report", "ext:pdf", "cloud:maybe", "older-than:2y"]);
+        assert!(parts[0].token.is_none());
+        assert_eq!(parts[1].token.unwrap().value, TokenValue::Extensions);
+        assert_eq!(
+            parts[2].error.as_deref(),
+            Some("expected cloud:, cloud:yes or cloud:no")
+        );
+        assert_eq!(parts.iter().filter(|p| p.error.is_some()).count(), 1);
+
+        // Aliases are the same token; a value makes a flag search text
+        let parts = inspect_query("folder: file:x");
+        assert_eq!(parts[0].token.unwrap().prefix, "dir:");
+        assert!(parts[1].token.is_none());
+
+        // A broken regex marks the search text
+        let parts = inspect_query("r/(unclosed/ file:");
+        assert!(parts[0].error.is_some());
+        assert!(parts[1].error.is_none());
+    }
+
+    #[test]
+    fn test_syntax_examples() {
+        for token in QUERY_TOKENS {
+            let mut parts = token.example.split_whitespace();
+            assert!(
+                parts.any(|part| token.starts(part)),
+                "{} is not in its example",
+                token.prefix
+            );
+            let parsed = parse_query(token.example);
+            if token.value == TokenValue::Scope {
+                assert!(parsed.is_err());
+            } else {
+                assert!(parsed.is_ok(), "{}: {:?}", token.example, parsed);
+            }
+        }
+        for pattern in QUERY_PATTERNS {
+            assert!(parse_query(pattern.example).is_ok(), "{}", pattern.example);
+        }
+
+        let token = |prefix| QUERY_TOKENS.iter().find(|t| t.prefix == prefix).unwrap();
+        let dir = token("dir:");
+        assert_eq!(dir.value_of("folder:"), Some(""));
+        assert_eq!(dir.value_of("folder:x"), None);
+        let age = token("older-than:");
+        assert_eq!(age.value_of("older-than:2y"), Some("2y"));
+        assert_eq!(age.usage(), "older-than:<age>");
+    }
+
+    #[test]
+    fn test_exclude_glob() {
+        let filter = SearchFilter::exclude_glob("*.TMP").unwrap();
+        assert!(!filter.matches(&make_record("build.tmp", false)));
+        assert!(filter.matches(&make_record("build.rs", false)));
+
+        // A folder name excludes everything inside it
+        let filter = SearchFilter::exclude_glob("node_modules").unwrap();
+        let record = |path: &str| {
+            FileRecord::new(
+                FileId::new(1),
+                None,
+                VolumeId::new("C"),
+                "index.js".to_string(),
+                path.to_string(),
+                false,
+            )
+        };
+        assert!(!filter.matches(&record("C:\\app\\node_modules\\lib\\index.js")));
+        assert!(filter.matches(&record("C:\\app\\src\\index.js")));
+    }
+
+    #[test]
+    fn test_may_match_volume() {
+        let c = VolumeInfo::new(VolumeId::new("C"), "C:\\", "NTFS");
+        let mut projects = VolumeInfo::new(VolumeId::new("D"), "D:\\", "NTFS");
+        projects.root = Some("D:\\Projects".to_string());
+
+        let query = SearchQuery::substring("main");
+        assert!(query.may_match_volume(&c));
+        assert!(query.may_match_volume(&projects));
+
+        let query = query.with_filter(SearchFilter::in_folders(&["C:\\Users".to_string()]));
+        assert!(query.may_match_volume(&c));
+        assert!(!query.may_match_volume(&projects));
+
+        let query = SearchQuery::substring("main")
+            .with_filter(SearchFilter::in_folders(&["D:\\".to_string()]));
+        assert!(!query.may_match_volume(&c));
+        assert!(query.may_match_volume(&projects));
+
+        let query = SearchQuery::substring("main")
+            .with_filter(SearchFilter::in_folders(&["D:\\Projects2".to_string()]));
+        assert!(!query.may_match_volume(&projects));
+
+        let query = SearchQuery::substring("main")
+            .with_filter(SearchFilter::PathPrefix("d:\\proj".to_string()));
+        assert!(!query.may_match_volume(&c));
+        assert!(query.may_match_volume(&projects));
+
+        let query =
+            SearchQuery::substring("main").with_filter(SearchFilter::Volume(VolumeId::new("D")));
+        assert!(!query.may_match_volume(&c));
+        assert!(query.may_match_volume(&projects));
+        assert!(!query.matches(&make_record("main.rs", false)));
+
+        let query =
+            SearchQuery::substring("main").with_filter(SearchFilter::Volume(VolumeId::new("C")));
+        assert!(query.matches(&make_record("main.rs", false)));
+    }
+
+    #[test]
+    fn test_fuzzy_fallback() {
+        let query = SearchQuery::substring("reprot").with_filter(SearchFilter::FilesOnly);
+        assert!(!query.matches(&make_record("report.txt", false)));
+
+        let fuzzy = query.fuzzy().unwrap();
+        assert!(fuzzy.matches(&make_record("annual_repot.txt", false))); // insertion
+        assert!(fuzzy.matches(&make_record("Reprt.txt", false))); // deletion
+        assert!(fuzzy.matches(&make_record("REPROT.txt", false))); // exact
+        assert!(fuzzy.matches(&make_record("xreprat", false))); // substitution
+        assert!(!fuzzy.matches(&make_record("report.txt", false))); // transposition = 2 edits
+        assert!(!fuzzy.matches(&make_record("repxot", true))); // filters still apply
+
+        // Too short or not a substring query
+        assert!(SearchQuery::substring("ab").fuzzy().is_none());
+        assert!(SearchQuery::wildcard("*.rs").unwrap().fuzzy().is_none());
+    }
+
     #[test]
     fn test_empty_substring() {
         let query = SearchQuery::substring("");
@@ -481,6 +1509,26 @@ mod tests {
         assert!(!query.matches(&make_record("test_abc.rs", false)));
     }
 
+    #[test]
+    fn test_regex_captures() {
+        let query = parse_query(r"r/(\d{4})-(\d{2})(x)?/").unwrap();
+        assert_eq!(query.capture_groups(), 3);
+        assert_eq!(
+            query.captures(&make_record("Scan 2023-07.PDF", false)),
+            vec!["2023", "07", ""]
+        );
+        // Groups come from the name as written
+        let query = SearchQuery::regex(r"^(\w+)\.").unwrap();
+        assert_eq!(
+            query.captures(&make_record("README.md", false)),
+            vec!["README"]
+        );
+
+        let query = SearchQuery::substring("scan");
+        assert_eq!(query.capture_groups(), 0);
+        assert!(query.captures(&make_record("scan.pdf", false)).is_empty());
+    }
+
     #[test]
     fn test_filter_files_only() {
         let query = SearchQuery::substring("").with_filter(SearchFilter::FilesOnly);
@@ -499,10 +1547,8 @@ mod tests {
 
     #[test]
     fn test_filter_extensions() {
-        let query = SearchQuery::substring("").with_filter(SearchFilter::Extensions(vec![
-            "rs".to_string(),
-            "toml".to_string(),
-        ]));
+        let query = SearchQuery::substring("")
+            .with_filter(SearchFilter::Extensions(ExtensionSet::new(["rs", "toml"])));
 
         assert!(query.matches(&make_record("main.rs", false)));
         assert!(query.matches(&make_record("Cargo.toml", false)));
@@ -522,6 +1568,25 @@ mod tests {
         assert!(!query.matches(&record));
     }
 
+    #[test]
+    fn test_supported_by() {
+        let query = parse_query("report ext:pdf")
+            .unwrap()
+            .with_filter(SearchFilter::MinSize(1 << 20));
+        let all = BackendCapabilities::all();
+        assert!(matches!(query.supported_by(&all), Cow::Borrowed(_)));
+        assert!(!query.matches(&make_record("report.pdf", false)));
+
+        // Records without sizes would never match a size filter
+        let no_sizes = BackendCapabilities {
+            provides_sizes: false,
+            ..all
+        };
+        let supported = query.supported_by(&no_sizes);
+        assert!(supported.matches(&make_record("report.pdf", false)));
+        assert!(!supported.matches(&make_record("report.txt", false)));
+    }
+
     #[test]
     fn test_parse_query_simple() {
         let query = parse_query("readme").unwrap();
@@ -544,6 +1609,97 @@ mod tests {
         assert!(!query.matches(&make_record("folder", true)));
     }
 
+    #[test]
+    fn test_parse_query_deleted() {
+        let query = parse_query("report deleted:").unwrap();
+        assert!(query.searches_deleted());
+        assert!(query.matches(&make_record("report.docx", false)));
+        assert!(query.fuzzy().unwrap().searches_deleted());
+
+        let query = parse_query("deleted:").unwrap();
+        assert!(query.searches_deleted());
+        assert!(!query.matches_all());
+        assert!(!parse_query("report").unwrap().searches_deleted());
+    }
+
+    #[test]
+    fn test_parse_query_cloud() {
+        let placeholder = make_record("video.mp4", false).with_cloud(true);
+        let local = make_record("video.mp4", false);
+
+        let query = parse_query("video cloud:").unwrap();
+        assert!(query.matches(&placeholder));
+        assert!(!query.matches(&local));
+
+        let query = parse_query("cloud:no").unwrap();
+        assert!(!query.matches(&placeholder));
+        assert!(query.matches(&local));
+
+        assert!(parse_query("cloud:maybe").is_err());
+    }
+
+    #[test]
+    fn test_parse_query_file_id() {
+        let record = make_record("video.mp4", false);
+
+        assert!(parse_query("fileid:C:1").unwrap().matches(&record));
+        assert!(parse_query("fileid:c:0x1").unwrap().matches(&record));
+        assert!(!parse_query("fileid:C:2").unwrap().matches(&record));
+        assert!(!parse_query("fileid:D:1").unwrap().matches(&record));
+        assert!(!parse_query("fileid:C:1 audio").unwrap().matches(&record));
+
+        // Folder volumes are named by their path, colon and all
+        let query = parse_query("fileid:D:\\Projects:42").unwrap();
+        assert!(query.filters.iter().any(|f| matches!(
+            f,
+            SearchFilter::FileId(volume, id) if volume.as_str() == "D:\\Projects" && id.as_u64() == 42
+        )));
+
+        for bad in ["fileid:", "fileid:C", "fileid::1", "fileid:C:x1"] {
+            assert!(parse_query(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_parse_query_age() {
+        let now = Utc::now();
+        let old = make_record("old.log", false).with_modified(now - chrono::Duration::days(800));
+        let recent = make_record("new.log", false).with_modified(now - chrono::Duration::days(2));
+        let unknown = make_record("unknown.log", false);
+
+        let query = parse_query("log older-than:2y").unwrap();
+        assert!(query.matches(&old));
+        assert!(!query.matches(&recent));
+        assert!(!query.matches(&unknown));
+
+        let query = parse_query("newer-than:7d").unwrap();
+        assert!(!query.matches(&old));
+        assert!(query.matches(&recent));
+        assert!(!query.matches(&unknown));
+
+        let query = parse_query("older-than:1w newer-than:6mo").unwrap();
+        assert!(!query.matches(&old));
+        assert!(!query.matches(&recent));
+
+        assert!(parse_query("older-than:ages").is_err());
+        assert!(parse_query("newer-than:").is_err());
+    }
+
+    #[test]
+    fn test_parse_query_age_across_dst() {
+        use crate::clock::tests::{utc, Cet};
+        use crate::clock::FixedClock;
+
+        // A day before 01:00 on 1 April in Central Europe is 01:00 on
+        // 31 March, 23 hours earlier since clocks moved forward
+        let clock = FixedClock::new(utc(3, 31, 23), Cet);
+        let query = parse_query_at("newer-than:1d", &clock).unwrap();
+        let at = |time| make_record("a.log", false).with_modified(time);
+        let half_hour = chrono::Duration::minutes(30);
+        assert!(query.matches(&at(utc(3, 31, 0) + half_hour)));
+        assert!(!query.matches(&at(utc(3, 31, 0) - half_hour)));
+    }
+
     #[test]
     fn test_parse_query_wildcard() {
         let query = parse_query("*.rs").unwrap();
@@ -585,4 +1741,20 @@ mod tests {
 
         assert!(query.matches(&record));
     }
+
+    #[test]
+    fn test_parse_query_in_path() {
+        let query = parse_query("in:C:\\Users file").unwrap();
+
+        let mut record = make_record("file.txt", false);
+        record.path = "C:\\users\\test\\file.txt".to_string();
+        assert!(query.matches(&record));
+
+        record.init_cache();
+        assert!(query.matches(&record));
+
+        record.path = "D:\\Users\\file.txt".to_string();
+        record.clear_path_cache();
+        assert!(!query.matches(&record));
+    }
 }
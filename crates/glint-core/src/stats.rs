@@ -0,0 +1,477 @@
+//! File-type statistics computed from the index.
+//!
+//! [`Statistics::compute`] walks the index once and aggregates file counts
+//! and total sizes by extension, top-level folder, volume and age (by
+//! modification time, in calendar days of the [`clock`](crate::clock)'s
+//! time zone). Directories are not counted.
+//!
+//! Aggregating a large index takes a moment, so results can be cached on
+//! disk next to the index. A cached result is only used while the index
+//! file it was computed from is unchanged.
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::{GlintError, Result};
+use crate::index::Index;
+use crate::types::FileRecord;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// File name of the statistics cache inside the index directory.
+pub const STATS_CACHE_FILE: &str = "stats-cache.json";
+
+/// Key used for files without an extension.
+pub const NO_EXTENSION: &str = "(none)";
+
+/// Age buckets, newest first, as (label, calendar days covered including
+/// today). The last bucket takes everything older.
+const AGE_BUCKETS: [(&str, u32); 5] = [
+    ("Today", 1),
+    ("This week", 7),
+    ("This month", 30),
+    ("This year", 365),
+    ("Older", u32::MAX),
+];
+
+/// Label for files without a modification time.
+const AGE_UNKNOWN: &str = "Unknown";
+
+/// How to group files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GroupBy {
+    /// By lowercase extension
+    #[default]
+    Extension,
+
+    /// By top-level folder (`C:\Users`)
+    Folder,
+
+    /// By volume (`C:`)
+    Volume,
+
+    /// By modification time bucket
+    Age,
+}
+
+impl GroupBy {
+    /// All groupings, in the order they are offered in menus.
+    pub const ALL: [GroupBy; 4] = [
+        GroupBy::Extension,
+        GroupBy::Folder,
+        GroupBy::Volume,
+        GroupBy::Age,
+    ];
+
+    /// Human-readable label for menus and headings.
+    pub fn label(&self) -> &'static str {
+        match self {
+            GroupBy::Extension => "Extension",
+            GroupBy::Folder => "Folder",
+            GroupBy::Volume => "Volume",
+            GroupBy::Age => "Age",
+        }
+    }
+}
+
+impl fmt::Display for GroupBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl FromStr for GroupBy {
+    type Err = GlintError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "ext" | "extension" | "type" => Ok(GroupBy::Extension),
+            "folder" | "dir" => Ok(GroupBy::Folder),
+            "volume" | "drive" => Ok(GroupBy::Volume),
+            "age" | "modified" => Ok(GroupBy::Age),
+            _ => Err(GlintError::ConfigError {
+                reason: format!(
+                    "Unknown grouping: {} (expected ext, folder, volume or age)",
+                    s
+                ),
+            }),
+        }
+    }
+}
+
+/// Count and total size of the files in one group.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatsRow {
+    pub key: String,
+    pub count: u64,
+    pub total_size: u64,
+}
+
+/// Aggregated statistics for the whole index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Statistics {
+    /// When the statistics were computed
+    pub computed_at: DateTime<Utc>,
+
+    /// Modification time of the index file they were computed from (set when cached)
+    #[serde(default)]
+    pub index_modified: Option<DateTime<Utc>>,
+
+    /// Number of files counted
+    pub total_files: u64,
+
+    /// Total size of all files
+    pub total_size: u64,
+
+    /// Groups by extension, largest first
+    pub by_extension: Vec<StatsRow>,
+
+    /// Groups by top-level folder, largest first
+    pub by_folder: Vec<StatsRow>,
+
+    /// Groups by volume, largest first
+    pub by_volume: Vec<StatsRow>,
+
+    /// Groups by age, newest first
+    pub by_age: Vec<StatsRow>,
+}
+
+#[derive(Default)]
+struct Accumulator(HashMap<String, (u64, u64)>);
+
+impl Accumulator {
+    fn add(&mut self, key: &str, size: u64) {
+        // Avoid allocating a key for the common case of an existing group
+        if let Some(entry) = self.0.get_mut(key) {
+            entry.0 += 1;
+            entry.1 += size;
+        } else {
+            self.0.insert(key.to_string(), (1, size));
+        }
+    }
+
+    /// Rows sorted by total size, then count, then key.
+    fn into_rows(self) -> Vec<StatsRow> {
+        let mut rows: Vec<StatsRow> = self
+            .0
+            .into_iter()
+            .map(|(key, (count, total_size))| StatsRow {
+                key,
+                count,
+                total_size,
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            b.total_size
+                .cmp(&a.total_size)
+                .then(b.count.cmp(&a.count))
+                .then_with(|| a.key.cmp(&b.key))
+        });
+        rows
+    }
+}
+
+impl Statistics {
+    /// Aggregate statistics over every file in the index.
+    pub fn compute(index: &Index) -> Self {
+        Self::compute_at(index, &SystemClock)
+    }
+
+    /// Aggregate statistics, with file ages counted in calendar days before
+    /// the time of `clock`.
+    pub fn compute_at(index: &Index, clock: &dyn Clock) -> Self {
+        let now = clock.now();
+        // Start of the oldest day in each bucket but the last
+        let bucket_starts: Vec<DateTime<Utc>> = AGE_BUCKETS[..AGE_BUCKETS.len() - 1]
+            .iter()
+            .map(|(_, days)| clock.day_start(days - 1))
+            .collect();
+        let mut by_extension = Accumulator::default();
+        let mut by_folder = Accumulator::default();
+        let mut by_volume = Accumulator::default();
+        let mut by_age = vec![(0u64, 0u64); AGE_BUCKETS.len() + 1];
+        let mut total_files = 0;
+        let mut total_size = 0;
+        let mut ext_lower = String::new();
+
+        index.for_each_record(|record| {
+            if record.is_dir {
+                return;
+            }
+            let size = record.size.unwrap_or(0);
+            total_files += 1;
+            total_size += size;
+
+            match record.extension() {
+                Some(ext) => {
+                    ext_lower.clear();
+                    ext_lower.extend(ext.chars().flat_map(char::to_lowercase));
+                    by_extension.add(&ext_lower, size);
+                }
+                None => by_extension.add(NO_EXTENSION, size),
+            }
+            by_folder.add(top_level_folder(record), size);
+            by_volume.add(record.volume_id.as_str(), size);
+
+            let bucket = age_bucket(record, &bucket_starts);
+            by_age[bucket].0 += 1;
+            by_age[bucket].1 += size;
+        });
+
+        let by_age = AGE_BUCKETS
+            .iter()
+            .map(|(label, _)| *label)
+            .chain(std::iter::once(AGE_UNKNOWN))
+            .zip(by_age)
+            .filter(|(_, (count, _))| *count > 0)
+            .map(|(label, (count, total_size))| StatsRow {
+                key: label.to_string(),
+                count,
+                total_size,
+            })
+            .collect();
+
+        let mut by_volume = by_volume.into_rows();
+        for row in &mut by_volume {
+            row.key.push(':');
+        }
+
+        Statistics {
+            computed_at: now,
+            index_modified: None,
+            total_files,
+            total_size,
+            by_extension: by_extension.into_rows(),
+            by_folder: by_folder.into_rows(),
+            by_volume,
+            by_age,
+        }
+    }
+
+    /// Rows for a grouping.
+    pub fn rows(&self, by: GroupBy) -> &[StatsRow] {
+        match by {
+            GroupBy::Extension => &self.by_extension,
+            GroupBy::Folder => &self.by_folder,
+            GroupBy::Volume => &self.by_volume,
+            GroupBy::Age => &self.by_age,
+        }
+    }
+
+    /// Path of the cache file inside an index directory.
+    pub fn cache_path_in(dir: &Path) -> PathBuf {
+        dir.join(STATS_CACHE_FILE)
+    }
+
+    /// Load cached statistics if they were computed from the current `index_file`.
+    ///
+    /// Returns None if there is no cache, it cannot be read, or it is stale.
+    pub fn load_cached(cache_path: &Path, index_file: &Path) -> Option<Self> {
+        let modified = file_modified(index_file)?;
+        let contents = fs::read_to_string(cache_path).ok()?;
+        let stats: Statistics = serde_json::from_str(&contents).ok()?;
+        (stats.index_modified == Some(modified)).then_some(stats)
+    }
+
+    /// Cache the statistics, tagged with the modification time of `index_file`.
+    pub fn save_cached(&self, cache_path: &Path, index_file: &Path) -> Result<()> {
+        let mut stats = self.clone();
+        stats.index_modified = file_modified(index_file);
+
+        if let Some(parent) = cache_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents =
+            serde_json::to_string(&stats).map_err(|e| GlintError::serialization(e.to_string()))?;
+        fs::write(cache_path, contents)?;
+        Ok(())
+    }
+}
+
+/// The first folder below the volume root, e.g. `C:\Users` for
+/// `C:\Users\me\doc.txt`. Files in the root map to `C:\`.
+fn top_level_folder(record: &FileRecord) -> &str {
+    let path = record.path.as_str();
+    let Some(root_end) = path.find('\\').map(|i| i + 1) else {
+        return path;
+    };
+    let rest = &path[root_end..];
+    match rest.find('\\') {
+        Some(i) => &path[..root_end + i],
+        None => &path[..root_end],
+    }
+}
+
+/// Index into the age buckets (the extra last slot is "unknown"), given
+/// where each bucket but the last starts.
+fn age_bucket(record: &FileRecord, starts: &[DateTime<Utc>]) -> usize {
+    let Some(modified) = record.modified else {
+        return AGE_BUCKETS.len();
+    };
+    starts
+        .iter()
+        .position(|start| modified >= *start)
+        .unwrap_or(AGE_BUCKETS.len() - 1)
+}
+
+fn file_modified(path: &Path) -> Option<DateTime<Utc>> {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(DateTime::<Utc>::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::VolumeInfo;
+    use crate::clock::FixedClock;
+    use crate::types::{FileId, VolumeId};
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    fn index(now: DateTime<Utc>) -> Index {
+        let vol = VolumeId::new("C");
+        let file = |id: u64, parent: u64, name: &str, path: &str, size: u64, days: i64| {
+            FileRecord::new(
+                FileId::new(id),
+                Some(FileId::new(parent)),
+                vol.clone(),
+                name.into(),
+                path.into(),
+                false,
+            )
+            .with_size(size)
+            .with_modified(now - Duration::days(days))
+        };
+        let records = vec![
+            FileRecord::new(
+                FileId::new(5),
+                None,
+                vol.clone(),
+                "src".into(),
+                "C:\\src".into(),
+                true,
+            ),
+            file(10, 5, "main.rs", "C:\\src\\main.rs", 100, 0),
+            file(11, 5, "LIB.RS", "C:\\src\\LIB.RS", 50, 3),
+            file(12, 5, "Makefile", "C:\\src\\Makefile", 10, 400),
+            file(13, 0, "boot.ini", "C:\\boot.ini", 500, 20),
+        ];
+        let index = Index::new();
+        index.add_volume_records(&VolumeInfo::new(vol, "C:", "NTFS"), records);
+        index
+    }
+
+    #[test]
+    fn test_compute() {
+        let now = Utc::now();
+        let stats = Statistics::compute_at(&index(now), &FixedClock::new(now, Utc));
+
+        assert_eq!(stats.total_files, 4);
+        assert_eq!(stats.total_size, 660);
+
+        let ext = stats.rows(GroupBy::Extension);
+        assert_eq!(ext[0].key, "ini");
+        assert_eq!(
+            ext[1],
+            StatsRow {
+                key: "rs".into(),
+                count: 2,
+                total_size: 150
+            }
+        );
+        assert_eq!(ext[2].key, NO_EXTENSION);
+
+        let folders: Vec<_> = stats
+            .rows(GroupBy::Folder)
+            .iter()
+            .map(|r| r.key.as_str())
+            .collect();
+        assert_eq!(folders, ["C:\\", "C:\\src"]);
+        assert_eq!(stats.rows(GroupBy::Volume)[0].key, "C:");
+
+        let ages: Vec<_> = stats
+            .rows(GroupBy::Age)
+            .iter()
+            .map(|r| r.key.as_str())
+            .collect();
+        assert_eq!(ages, ["Today", "This week", "This month", "Older"]);
+    }
+
+    #[test]
+    fn test_age_follows_calendar() {
+        use crate::clock::tests::{utc, Cet};
+
+        // At 10:00 on 1 April in Central Europe, a file from 00:30 is from
+        // today and one from 23:30 the night before is not
+        let half_hour = Duration::minutes(30);
+        let vol = VolumeId::new("C");
+        let records = [utc(3, 31, 22), utc(3, 31, 21)]
+            .into_iter()
+            .enumerate()
+            .map(|(i, modified)| {
+                let name = format!("{}.txt", i);
+                FileRecord::new(
+                    FileId::new(i as u64 + 1),
+                    None,
+                    vol.clone(),
+                    name.clone(),
+                    format!("C:\\{}", name),
+                    false,
+                )
+                .with_modified(modified + half_hour)
+            })
+            .collect();
+        let index = Index::new();
+        index.add_volume_records(&VolumeInfo::new(vol, "C:", "NTFS"), records);
+
+        let clock = FixedClock::new(utc(4, 1, 8), Cet);
+        let stats = Statistics::compute_at(&index, &clock);
+        let ages: Vec<_> = stats
+            .rows(GroupBy::Age)
+            .iter()
+            .map(|r| (r.key.as_str(), r.count))
+            .collect();
+        assert_eq!(ages, [("Today", 1), ("This week", 1)]);
+    }
+
+    #[test]
+    fn test_cache_invalidation() {
+        let dir = TempDir::new().unwrap();
+        let index_file = dir.path().join("glint.idx");
+        let cache = Statistics::cache_path_in(dir.path());
+        fs::write(&index_file, b"v1").unwrap();
+
+        let stats = Statistics::compute(&index(Utc::now()));
+        assert!(Statistics::load_cached(&cache, &index_file).is_none());
+        stats.save_cached(&cache, &index_file).unwrap();
+        assert_eq!(
+            Statistics::load_cached(&cache, &index_file)
+                .unwrap()
+                .total_files,
+            4
+        );
+
+        // Rewriting the index invalidates the cache
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(&index_file)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(Statistics::load_cached(&cache, &index_file).is_none());
+    }
+
+    #[test]
+    fn test_group_by_from_str() {
+        assert_eq!("ext".parse::<GroupBy>().unwrap(), GroupBy::Extension);
+        assert_eq!("Folder".parse::<GroupBy>().unwrap(), GroupBy::Folder);
+        assert!("size".parse::<GroupBy>().is_err());
+    }
+}
@@ -0,0 +1,531 @@
+//! The watch pipeline as tokio tasks.
+//!
+//! The blocking pieces ([`IpcServer`](crate::ipc::IpcServer),
+//! [`VolumeRefresher`](crate::backend::VolumeRefresher) and a loop over the
+//! change channel) each own a thread and a stop flag. A program that also
+//! runs its own servers and timers can instead spawn the tasks here on one
+//! runtime and stop them all with a single [`CancellationToken`]:
+//!
+//! ```ignore
+//! let cancel = CancellationToken::new();
+//! let changes = tokio::spawn(tasks::apply_changes(index.clone(), receiver, cancel.clone()));
+//! tokio::spawn(tasks::serve_ipc(listener, index.clone(), limits, None, None, cancel.clone()));
+//! tokio::spawn(tasks::every(SAVE_INTERVAL, cancel.clone(), move || save(&index)));
+//! // ... later
+//! cancel.cancel();
+//! ```
+//!
+//! Work that blocks (applying changes, searching, saving) runs on tokio's
+//! blocking pool, so the tasks never stall the runtime. Only built with the
+//! `tokio` feature; the thread-based API stays the default.
+
+use crate::backend::{self, ChangeHandlerMessage, FileSystemBackend, VolumeResumer};
+use crate::error::{GlintError, Result};
+use crate::index::Index;
+use crate::ipc::{
+    IpcRequest, IpcResponse, QueryGate, QueryLimits, IDLE_TIMEOUT, MAX_REQUEST_BYTES,
+};
+use crate::tls::ServerTls;
+use crossbeam_channel::Receiver;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader,
+};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, error, warn};
+
+pub use tokio_util::sync::CancellationToken;
+
+/// How long the change loop waits for a change before checking for
+/// cancellation.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Apply the changes from `receiver` to `index` until cancelled or every
+/// watcher has stopped.
+///
+/// Each message is applied with [`ChangeHandlerMessage::apply`], as the
+/// service's thread-based loop does. Returns the number of changes applied.
+pub async fn apply_changes(
+    index: Arc<Index>,
+    receiver: Receiver<ChangeHandlerMessage>,
+    cancel: CancellationToken,
+) -> usize {
+    let task = tokio::task::spawn_blocking(move || {
+        let mut applied = 0;
+        while !cancel.is_cancelled() {
+            match receiver.recv_timeout(POLL_INTERVAL) {
+                Ok(message) => {
+                    if matches!(message, ChangeHandlerMessage::Change(_)) {
+                        applied += 1;
+                    }
+                    message.apply(&index);
+                }
+                Err(e) if e.is_timeout() => {}
+                Err(_) => {
+                    warn!("All watchers stopped");
+                    break;
+                }
+            }
+        }
+        applied
+    });
+    task.await.unwrap_or_default()
+}
+
+/// Run `f` on the blocking pool every `interval` until cancelled.
+///
+/// The first run is one interval after the start; a run that overruns
+/// delays the next one rather than piling up.
+pub async fn every<F>(interval: Duration, cancel: CancellationToken, f: F)
+where
+    F: FnMut() + Send + 'static,
+{
+    let f = Arc::new(parking_lot::Mutex::new(f));
+    let mut ticks = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+    ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = ticks.tick() => {
+                let f = Arc::clone(&f);
+                if let Err(e) = tokio::task::spawn_blocking(move || (*f.lock())()).await {
+                    error!(error = %e, "Periodic task failed");
+                }
+            }
+        }
+    }
+}
+
+/// Keep volume labels, free space and availability current, resuming
+/// volumes that come back with `resumer` if given, like
+/// [`VolumeRefresher`](crate::backend::VolumeRefresher).
+pub async fn refresh_volumes(
+    backend: Arc<dyn FileSystemBackend>,
+    index: Arc<Index>,
+    interval: Duration,
+    mut resumer: Option<VolumeResumer>,
+    cancel: CancellationToken,
+) {
+    every(interval, cancel, move || {
+        backend::refresh_volumes(backend.as_ref(), &index, resumer.as_mut())
+    })
+    .await
+}
+
+/// Answer IPC requests on `listener` until cancelled.
+///
+/// Speaks the same protocol as [`IpcServer`](crate::ipc::IpcServer) and
+/// keeps queries within `limits`, behind `token` and encrypted with `tls`
+/// the same way; each connection is a task of its own and ends with the
+/// server.
+pub async fn serve_ipc(
+    listener: TcpListener,
+    index: Arc<Index>,
+    limits: QueryLimits,
+    token: Option<String>,
+    tls: Option<ServerTls>,
+    cancel: CancellationToken,
+) {
+    let gate = QueryGate::new(limits).with_token(token);
+    if let Ok(addr) = listener.local_addr() {
+        gate.warn_if_exposed(addr);
+    }
+    loop {
+        let (stream, peer) = tokio::select! {
+            _ = cancel.cancelled() => break,
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!(error = %e, "IPC accept failed");
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    continue;
+                }
+            },
+        };
+        debug!(peer = %peer, "IPC client connected");
+        let slot = match gate.connect() {
+            Ok(slot) => slot,
+            Err(refusal) => {
+                warn!(peer = %peer, "Turning away an IPC client: too many connections");
+                if tls.is_none() {
+                    let mut stream = stream;
+                    let _ = write_message(&mut stream, &refusal).await;
+                }
+                continue;
+            }
+        };
+        let index = Arc::clone(&index);
+        let gate = gate.clone();
+        let tls = tls.clone();
+        let cancel = cancel.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = cancel.cancelled() => {}
+                served = open_connection(stream, tls, index, gate) => {
+                    if let Err(e) = served {
+                        debug!(peer = %peer, error = %e, "IPC connection closed");
+                    }
+                }
+            }
+            drop(slot);
+        });
+    }
+}
+
+/// Serve an accepted connection, encrypting it with `tls` when set.
+async fn open_connection(
+    stream: TcpStream,
+    tls: Option<ServerTls>,
+    index: Arc<Index>,
+    gate: QueryGate,
+) -> Result<()> {
+    #[cfg(feature = "tls")]
+    if let Some(tls) = tls {
+        let stream = tls.acceptor().accept(stream).await?;
+        return serve_connection(stream, index, gate).await;
+    }
+    // Without the feature no ServerTls can be made
+    #[cfg(not(feature = "tls"))]
+    let _ = tls;
+    serve_connection(stream, index, gate).await
+}
+
+async fn serve_connection(
+    stream: impl AsyncRead + AsyncWrite + Unpin,
+    index: Arc<Index>,
+    gate: QueryGate,
+) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut authenticated = gate.is_open();
+    let mut line = String::new();
+
+    loop {
+        let read = tokio::time::timeout(IDLE_TIMEOUT, read_request_line(&mut reader, &mut line))
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "Connection idle"))?;
+        match read {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                write_message(reader.get_mut(), &IpcResponse::error(e.to_string())).await?;
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<IpcRequest>(&line) {
+            Ok(request) => {
+                if let Some(response) = gate.admit(&request, &mut authenticated) {
+                    response
+                } else {
+                    let (index, gate) = (Arc::clone(&index), gate.clone());
+                    tokio::task::spawn_blocking(move || gate.handle(request, &index))
+                        .await
+                        .map_err(|e| GlintError::Internal(e.to_string()))?
+                }
+            }
+            Err(e) => IpcResponse::error(format!("Malformed request: {}", e)),
+        };
+        write_message(reader.get_mut(), &response).await?;
+    }
+
+    Ok(())
+}
+
+/// Read the next line of a connection into `line`, at most
+/// [`MAX_REQUEST_BYTES`] of it, as the thread-based server does.
+async fn read_request_line(
+    reader: &mut (impl AsyncBufRead + Unpin),
+    line: &mut String,
+) -> io::Result<bool> {
+    line.clear();
+    let read = reader
+        .take(MAX_REQUEST_BYTES as u64)
+        .read_line(line)
+        .await?;
+    if read == MAX_REQUEST_BYTES && !line.ends_with('\n') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Request longer than {} bytes", MAX_REQUEST_BYTES),
+        ));
+    }
+    Ok(read > 0)
+}
+
+/// Send one JSON-encoded response, and its newline.
+async fn write_message(
+    writer: &mut (impl AsyncWrite + Unpin),
+    response: &IpcResponse,
+) -> Result<()> {
+    let mut out =
+        serde_json::to_vec(response).map_err(|e| GlintError::serialization(e.to_string()))?;
+    out.push(b'\n');
+    writer.write_all(&out).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{ChangeEvent, ChannelChangeHandler, VolumeInfo};
+    use crate::index::VolumeAvailability;
+    use crate::ipc::{IpcClient, QueryRequest};
+    use crate::mock::{MockBackend, MockVolume};
+    use crate::search::SearchQuery;
+    use crate::types::{FileId, FileRecord, VolumeId};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn make_index() -> Arc<Index> {
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        let index = Arc::new(Index::new());
+        index.add_volume_records(
+            &volume,
+            vec![FileRecord::new(
+                FileId::new(5),
+                None,
+                volume.id.clone(),
+                "docs".into(),
+                "C:\\docs".into(),
+                true,
+            )],
+        );
+        index
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_tasks_stop_on_cancel() {
+        let index = make_index();
+        let cancel = CancellationToken::new();
+
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let changes = tokio::spawn(apply_changes(Arc::clone(&index), receiver, cancel.clone()));
+        sender
+            .send(ChangeHandlerMessage::Change(ChangeEvent::created(
+                VolumeId::new("C"),
+                FileId::new(6),
+                Some(FileId::new(5)),
+                "notes.txt".into(),
+                false,
+                1,
+            )))
+            .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = tokio::spawn(serve_ipc(
+            listener,
+            Arc::clone(&index),
+            QueryLimits::default(),
+            None,
+            None,
+            cancel.clone(),
+        ));
+
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&runs);
+        let timer = tokio::spawn(every(
+            Duration::from_millis(10),
+            cancel.clone(),
+            move || {
+                counter.fetch_add(1, Ordering::Relaxed);
+            },
+        ));
+
+        // The change reaches the index and the server answers from it
+        let found = tokio::task::spawn_blocking(move || {
+            for _ in 0..100 {
+                let mut client = IpcClient::connect(&addr, Duration::from_secs(1)).unwrap();
+                let (results, _) = client.query(QueryRequest::new("notes", 10)).unwrap();
+                if !results.is_empty() {
+                    return true;
+                }
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            false
+        })
+        .await
+        .unwrap();
+        assert!(found);
+        let ticked = tokio::time::timeout(Duration::from_secs(5), async {
+            while runs.load(Ordering::Relaxed) == 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        });
+        assert!(ticked.await.is_ok());
+
+        // Cancelling stops every task, even with the watchers still running
+        cancel.cancel();
+        assert_eq!(changes.await.unwrap(), 1);
+        server.await.unwrap();
+        timer.await.unwrap();
+        drop(sender);
+    }
+
+    #[tokio::test]
+    async fn test_serve_ipc_bounds_request_lines() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let cancel = CancellationToken::new();
+        let limits = QueryLimits::default();
+        let server = tokio::spawn(serve_ipc(
+            listener,
+            make_index(),
+            limits,
+            None,
+            None,
+            cancel.clone(),
+        ));
+
+        let mut stream = BufReader::new(TcpStream::connect(addr).await.unwrap());
+        stream
+            .get_mut()
+            .write_all(&vec![b'a'; MAX_REQUEST_BYTES])
+            .await
+            .unwrap();
+        let mut line = String::new();
+        stream.read_line(&mut line).await.unwrap();
+        assert!(matches!(
+            serde_json::from_str(&line).unwrap(),
+            IpcResponse::Error { refused: None, .. }
+        ));
+        line.clear();
+        assert_eq!(stream.read_line(&mut line).await.unwrap(), 0);
+
+        cancel.cancel();
+        server.await.unwrap();
+    }
+
+    /// An index of every volume of `backend`, freshly scanned.
+    fn scan(backend: &MockBackend) -> Arc<Index> {
+        let index = Arc::new(Index::new());
+        for volume in backend.list_volumes().unwrap() {
+            let records = backend.full_scan(&volume, None).unwrap();
+            index.add_volume_records(&volume, records);
+        }
+        index
+    }
+
+    /// Wait up to five seconds for `done`.
+    async fn eventually(done: impl Fn() -> bool) -> bool {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !done() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .is_ok()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_apply_changes_from_watcher() {
+        let backend = MockBackend::new().with_volume(
+            MockVolume::new("C")
+                .with_dir(5, None, "src")
+                .then_create(11, Some(5), "lib.rs", false)
+                .then_delay(Duration::from_secs(60))
+                .then_create(12, Some(5), "late.rs", false),
+        );
+        let index = scan(&backend);
+        let (handler, receiver) = ChannelChangeHandler::new();
+        let volume = backend.list_volumes().unwrap().remove(0);
+        let mut watch = backend.watch_changes(volume, Arc::new(handler)).unwrap();
+
+        let cancel = CancellationToken::new();
+        let changes = tokio::spawn(apply_changes(Arc::clone(&index), receiver, cancel.clone()));
+        let count = |name: &str| index.search(&SearchQuery::substring(name)).len();
+        assert!(eventually(|| count("lib.rs") == 1).await);
+
+        // Cancelling ends the task while the watcher waits mid-script
+        cancel.cancel();
+        let applied = tokio::time::timeout(Duration::from_secs(5), changes).await;
+        assert_eq!(applied.unwrap().unwrap(), 1);
+        assert_eq!(count("late.rs"), 0);
+        watch.stop();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_apply_changes_ends_with_watchers() {
+        let backend = MockBackend::new().with_volume(
+            MockVolume::new("C")
+                .with_dir(5, None, "src")
+                .then_create(11, Some(5), "lib.rs", false)
+                .then_delay(Duration::from_secs(60)),
+        );
+        let index = scan(&backend);
+        let (handler, receiver) = ChannelChangeHandler::new();
+        let volume = backend.list_volumes().unwrap().remove(0);
+        let mut watch = backend.watch_changes(volume, Arc::new(handler)).unwrap();
+
+        let cancel = CancellationToken::new();
+        let changes = tokio::spawn(apply_changes(Arc::clone(&index), receiver, cancel.clone()));
+        assert!(eventually(|| index.search(&SearchQuery::substring("lib.rs")).len() == 1).await);
+
+        // Stopping the last watcher closes the channel and ends the task
+        // without cancelling it
+        watch.stop();
+        let applied = tokio::time::timeout(Duration::from_secs(5), changes).await;
+        assert_eq!(applied.unwrap().unwrap(), 1);
+        assert!(!cancel.is_cancelled());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_refresh_volumes_task() {
+        let backend = MockBackend::new().with_volume(
+            MockVolume::new("C")
+                .with_dir(5, None, "src")
+                .then_create(11, Some(5), "lib.rs", false),
+        );
+        let index = scan(&backend);
+        let (handler, receiver) = ChannelChangeHandler::new();
+        let resumer = VolumeResumer::new(Arc::new(handler));
+
+        let cancel = CancellationToken::new();
+        let refresher = tokio::spawn(refresh_volumes(
+            Arc::new(backend.clone()),
+            Arc::clone(&index),
+            Duration::from_millis(10),
+            Some(resumer),
+            cancel.clone(),
+        ));
+        let changes = tokio::spawn(apply_changes(Arc::clone(&index), receiver, cancel.clone()));
+        let availability = || index.volume_states()[0].availability;
+
+        backend.set_mounted("C", false);
+        assert!(eventually(|| availability() == VolumeAvailability::Offline).await);
+
+        // Back: the volume is resumed and its change caught up on
+        backend.set_mounted("C", true);
+        assert!(eventually(|| availability() == VolumeAvailability::Online).await);
+        assert!(eventually(|| index.search(&SearchQuery::substring("lib.rs")).len() == 1).await);
+
+        cancel.cancel();
+        let stopped = tokio::time::timeout(Duration::from_secs(5), async {
+            refresher.await.unwrap();
+            changes.await.unwrap()
+        });
+        assert_eq!(stopped.await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_every_cancelled_before_first_run() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&runs);
+        let cancel = CancellationToken::new();
+        let timer = tokio::spawn(every(Duration::from_secs(60), cancel.clone(), move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }));
+
+        cancel.cancel();
+        assert!(tokio::time::timeout(Duration::from_secs(5), timer)
+            .await
+            .unwrap()
+            .is_ok());
+        assert_eq!(runs.load(Ordering::Relaxed), 0);
+    }
+}
@@ -0,0 +1,223 @@
+//! TLS for query server connections (`tls` feature).
+//!
+//! A server encrypts its connections with the certificate and key named in
+//! `[server] tls_cert` and `tls_key` ([`ServerTls`]); a client checks the
+//! server's certificate against the ones in `tls_ca` ([`ClientTls`]), which
+//! for a self-signed certificate is that certificate itself.
+//!
+//! Without the `tls` feature loading either fails, so a config that asks
+//! for TLS is never served, or queried, in the clear.
+
+use crate::error::{GlintError, Result};
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+#[cfg(feature = "tls")]
+use std::sync::Arc;
+
+#[cfg(feature = "tls")]
+use rustls::pki_types::pem::PemObject;
+#[cfg(feature = "tls")]
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+#[cfg(feature = "tls")]
+use rustls::{ClientConfig, ClientConnection, RootCertStore, ServerConfig, ServerConnection};
+
+/// The certificate a server proves itself with.
+#[derive(Clone)]
+pub struct ServerTls {
+    #[cfg(feature = "tls")]
+    config: Arc<ServerConfig>,
+    #[cfg(not(feature = "tls"))]
+    never: std::convert::Infallible,
+}
+
+impl ServerTls {
+    /// Read the certificate chain in `cert` and its private key in `key`,
+    /// both PEM.
+    pub fn load(cert: &Path, key: &Path) -> Result<Self> {
+        #[cfg(feature = "tls")]
+        {
+            let certs = CertificateDer::pem_file_iter(cert)
+                .and_then(|certs| certs.collect::<std::result::Result<Vec<_>, _>>())
+                .map_err(|e| pem_error(cert, e))?;
+            let key = PrivateKeyDer::from_pem_file(key).map_err(|e| pem_error(key, e))?;
+            let config = ServerConfig::builder()
+                .with_no_client_auth()
+                .with_single_cert(certs, key)
+                .map_err(tls_error)?;
+            Ok(ServerTls {
+                config: Arc::new(config),
+            })
+        }
+        #[cfg(not(feature = "tls"))]
+        {
+            let _ = (cert, key);
+            Err(not_built())
+        }
+    }
+
+    /// Encrypt an accepted connection; the handshake happens on its first
+    /// read.
+    pub(crate) fn accept(&self, socket: TcpStream) -> Result<IpcStream> {
+        #[cfg(feature = "tls")]
+        {
+            let session = ServerConnection::new(Arc::clone(&self.config)).map_err(tls_error)?;
+            Ok(IpcStream::Server(Box::new(rustls::StreamOwned::new(
+                session, socket,
+            ))))
+        }
+        #[cfg(not(feature = "tls"))]
+        {
+            let _ = socket;
+            match self.never {}
+        }
+    }
+
+    /// The acceptor encrypting connections of the tokio server.
+    #[cfg(all(feature = "tls", feature = "tokio"))]
+    pub(crate) fn acceptor(&self) -> tokio_rustls::TlsAcceptor {
+        tokio_rustls::TlsAcceptor::from(Arc::clone(&self.config))
+    }
+}
+
+/// The certificates a client trusts a server's certificate by.
+#[derive(Clone)]
+pub struct ClientTls {
+    #[cfg(feature = "tls")]
+    config: Arc<ClientConfig>,
+    #[cfg(not(feature = "tls"))]
+    never: std::convert::Infallible,
+}
+
+impl ClientTls {
+    /// Trust the PEM certificates in `ca`, and the certificates they
+    /// issued.
+    pub fn load(ca: &Path) -> Result<Self> {
+        #[cfg(feature = "tls")]
+        {
+            let mut roots = RootCertStore::empty();
+            for cert in CertificateDer::pem_file_iter(ca).map_err(|e| pem_error(ca, e))? {
+                roots
+                    .add(cert.map_err(|e| pem_error(ca, e))?)
+                    .map_err(tls_error)?;
+            }
+            if roots.is_empty() {
+                return Err(GlintError::ConfigError {
+                    reason: format!("No certificates in {}", ca.display()),
+                });
+            }
+            let config = ClientConfig::builder()
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+            Ok(ClientTls {
+                config: Arc::new(config),
+            })
+        }
+        #[cfg(not(feature = "tls"))]
+        {
+            let _ = ca;
+            Err(not_built())
+        }
+    }
+
+    /// Encrypt a connection to the server at `addr` (`host:port`), whose
+    /// certificate must be for `host`.
+    pub(crate) fn connect(&self, addr: &str, socket: TcpStream) -> Result<IpcStream> {
+        #[cfg(feature = "tls")]
+        {
+            let host = addr.rsplit_once(':').map_or(addr, |(host, _)| host);
+            let host = host.trim_start_matches('[').trim_end_matches(']');
+            let name =
+                ServerName::try_from(host.to_string()).map_err(|e| GlintError::ConfigError {
+                    reason: format!("Invalid server name {}: {}", host, e),
+                })?;
+            let session =
+                ClientConnection::new(Arc::clone(&self.config), name).map_err(tls_error)?;
+            Ok(IpcStream::Client(Box::new(rustls::StreamOwned::new(
+                session, socket,
+            ))))
+        }
+        #[cfg(not(feature = "tls"))]
+        {
+            let _ = (addr, socket);
+            match self.never {}
+        }
+    }
+}
+
+/// A query server connection, from either end, encrypted or not.
+pub(crate) enum IpcStream {
+    Plain(TcpStream),
+    #[cfg(feature = "tls")]
+    Server(Box<rustls::StreamOwned<ServerConnection, TcpStream>>),
+    #[cfg(feature = "tls")]
+    Client(Box<rustls::StreamOwned<ClientConnection, TcpStream>>),
+}
+
+impl IpcStream {
+    /// The socket underneath, for setting timeouts.
+    pub(crate) fn socket(&self) -> &TcpStream {
+        match self {
+            IpcStream::Plain(socket) => socket,
+            #[cfg(feature = "tls")]
+            IpcStream::Server(stream) => &stream.sock,
+            #[cfg(feature = "tls")]
+            IpcStream::Client(stream) => &stream.sock,
+        }
+    }
+}
+
+impl Read for IpcStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            IpcStream::Plain(socket) => socket.read(buf),
+            #[cfg(feature = "tls")]
+            IpcStream::Server(stream) => stream.read(buf),
+            #[cfg(feature = "tls")]
+            IpcStream::Client(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for IpcStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            IpcStream::Plain(socket) => socket.write(buf),
+            #[cfg(feature = "tls")]
+            IpcStream::Server(stream) => stream.write(buf),
+            #[cfg(feature = "tls")]
+            IpcStream::Client(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            IpcStream::Plain(socket) => socket.flush(),
+            #[cfg(feature = "tls")]
+            IpcStream::Server(stream) => stream.flush(),
+            #[cfg(feature = "tls")]
+            IpcStream::Client(stream) => stream.flush(),
+        }
+    }
+}
+
+#[cfg(feature = "tls")]
+fn pem_error(path: &Path, e: rustls::pki_types::pem::Error) -> GlintError {
+    GlintError::ConfigError {
+        reason: format!("Cannot read {}: {}", path.display(), e),
+    }
+}
+
+#[cfg(feature = "tls")]
+fn tls_error(e: rustls::Error) -> GlintError {
+    GlintError::ConfigError {
+        reason: format!("TLS: {}", e),
+    }
+}
+
+#[cfg(not(feature = "tls"))]
+fn not_built() -> GlintError {
+    GlintError::ConfigError {
+        reason: "TLS is configured but Glint was built without the `tls` feature".to_string(),
+    }
+}
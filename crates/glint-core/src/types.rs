@@ -7,10 +7,14 @@
 //! - **Platform-agnostic**: No OS-specific details leak into these types
 //! - **Efficient**: Optimized for both memory usage and search performance
 
+use crate::extensions::{self, ExtId};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::fmt;
 use std::hash::Hash;
+use std::num::ParseIntError;
+use std::str::FromStr;
 
 /// Unique identifier for a file within a volume.
 ///
@@ -43,6 +47,19 @@ impl fmt::Display for FileId {
     }
 }
 
+impl FromStr for FileId {
+    type Err = ParseIntError;
+
+    /// Parse a file ID in decimal, or in hex with `0x` in front as USN
+    /// journal dumps write them.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex) => u64::from_str_radix(hex, 16).map(FileId),
+            None => s.parse().map(FileId),
+        }
+    }
+}
+
 /// Unique identifier for a volume.
 ///
 /// On Windows, this is typically derived from the volume serial number.
@@ -88,7 +105,8 @@ impl From<&str> for VolumeId {
 /// ## Design Notes
 ///
 /// - `name` is stored separately from `path` for efficient filename-only searches
-/// - `name_lower` is pre-computed for fast case-insensitive matching
+/// - `name_lower` is pre-computed for fast case-insensitive matching, except
+///   on records borrowed from the index, which keeps lowercase names apart
 /// - `path` is the full path including the filename
 /// - Parent-child relationships are tracked via `parent_id` for path reconstruction
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -105,11 +123,18 @@ pub struct FileRecord {
     /// Filename without path (e.g., "document.txt")
     pub name: String,
 
-    /// Pre-computed lowercase filename for fast case-insensitive search
+    /// Pre-computed lowercase filename for fast case-insensitive search.
+    /// May be empty on records built elsewhere; read it through
+    /// [`FileRecord::name_lower`].
     #[serde(skip)]
     pub name_lower: String,
 
-    /// Pre-computed lowercase full path for fast case-insensitive path search
+    /// Interned extension, set along with `name_lower`
+    #[serde(skip)]
+    pub ext_id: ExtId,
+
+    /// Lowercase full path, filled by `init_cache` unless the index is
+    /// configured not to cache paths. Read it through [`FileRecord::path_lower`].
     #[serde(skip)]
     pub path_lower: String,
     /// Full path including filename (e.g., "C:\Users\doc\document.txt")
@@ -126,12 +151,19 @@ pub struct FileRecord {
 
     /// Creation time (if available)
     pub created: Option<DateTime<Utc>>,
+
+    /// Online-only cloud file (e.g. a OneDrive placeholder) whose contents
+    /// are downloaded when it is opened. Set by scans and the change
+    /// journal; like sizes, it is not kept in the saved index.
+    #[serde(skip)]
+    pub cloud: bool,
 }
 
 impl FileRecord {
     /// Create a new file record with the given parameters.
     ///
-    /// The `name_lower` field is automatically computed from `name`.
+    /// The `name_lower` field is automatically computed from `name`; the
+    /// lowercase path is computed on demand until `init_cache` is called.
     pub fn new(
         id: FileId,
         parent_id: Option<FileId>,
@@ -140,20 +172,21 @@ impl FileRecord {
         path: String,
         is_dir: bool,
     ) -> Self {
-        let name_lower = name.to_lowercase();
-        let path_lower = path.to_lowercase();
+        let name_lower = to_lower(&name);
         FileRecord {
             id,
             parent_id,
             volume_id,
             name,
+            ext_id: extensions::of_lower_name(&name_lower),
             name_lower,
             path,
-            path_lower,
+            path_lower: String::new(),
             is_dir,
             size: None,
             modified: None,
             created: None,
+            cloud: false,
         }
     }
 
@@ -175,21 +208,60 @@ impl FileRecord {
         self
     }
 
-    /// Get the file extension (lowercase), if any
+    /// Mark the file as an online-only cloud placeholder (or not)
+    pub fn with_cloud(mut self, cloud: bool) -> Self {
+        self.cloud = cloud;
+        self
+    }
+
+    /// Get the file extension as written in the name, if any (see
+    /// [`extensions::of_name`])
     pub fn extension(&self) -> Option<&str> {
-        self.name.rsplit('.').next().filter(|ext| {
-            // Make sure we actually found an extension, not the whole filename
-            ext.len() < self.name.len()
-        })
+        extensions::of_name(&self.name)
     }
 
-    /// Initialize the lowercase name cache after deserialization
+    /// Initialize the lowercase name and path caches after deserialization
     pub fn init_cache(&mut self) {
+        self.init_name_cache();
+        self.init_path_cache();
+    }
+
+    /// Initialize only the lowercase path cache.
+    pub fn init_path_cache(&mut self) {
+        if self.path_lower.is_empty() {
+            self.path_lower = to_lower(&self.path);
+        }
+    }
+
+    /// Initialize only the lowercase name cache (and the extension ID),
+    /// leaving paths uncached.
+    pub fn init_name_cache(&mut self) {
         if self.name_lower.is_empty() {
-            self.name_lower = self.name.to_lowercase();
+            self.name_lower = to_lower(&self.name);
+            self.ext_id = extensions::of_lower_name(&self.name_lower);
         }
+    }
+
+    /// Drop the cached lowercase path, e.g. after changing `path`.
+    pub fn clear_path_cache(&mut self) {
+        self.path_lower = String::new();
+    }
+
+    /// The lowercase filename, from the cache if it is filled.
+    pub fn name_lower(&self) -> Cow<'_, str> {
+        if self.name_lower.is_empty() {
+            Cow::Owned(to_lower(&self.name))
+        } else {
+            Cow::Borrowed(&self.name_lower)
+        }
+    }
+
+    /// The lowercase full path, from the cache if it is filled.
+    pub fn path_lower(&self) -> Cow<'_, str> {
         if self.path_lower.is_empty() {
-            self.path_lower = self.path.to_lowercase();
+            Cow::Owned(to_lower(&self.path))
+        } else {
+            Cow::Borrowed(&self.path_lower)
         }
     }
 
@@ -256,6 +328,18 @@ impl IndexStats {
     }
 }
 
+/// Lowercase `s`, taking a cheaper path for the common all-ASCII name.
+///
+/// Names and patterns are lowered the same way, so matching can compare
+/// bytes directly.
+pub(crate) fn to_lower(s: &str) -> String {
+    if s.is_ascii() {
+        s.to_ascii_lowercase()
+    } else {
+        s.to_lowercase()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
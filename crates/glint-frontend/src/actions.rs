@@ -0,0 +1,42 @@
+//! Actions on results: opening, revealing and copying.
+
+use glint_core::PathFormat;
+use std::ffi::OsStr;
+use std::io;
+
+/// Open a file or folder with its default program.
+pub fn open(path: impl AsRef<OsStr>) -> io::Result<()> {
+    open::that(path)
+}
+
+/// Show a file in its folder: selected in Explorer on Windows, elsewhere by
+/// opening the folder that contains it.
+pub fn reveal(path: &str) -> io::Result<()> {
+    #[cfg(windows)]
+    {
+        std::process::Command::new("explorer")
+            .arg("/select,")
+            .arg(path)
+            .spawn()
+            .map(|_| ())
+    }
+    #[cfg(not(windows))]
+    {
+        let parent = std::path::Path::new(path)
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No containing folder"))?;
+        open::that(parent)
+    }
+}
+
+/// Copy text to the clipboard.
+pub fn copy_text(text: &str) -> Result<(), String> {
+    let mut clipboard = arboard::Clipboard::new().map_err(|e| e.to_string())?;
+    clipboard.set_text(text).map_err(|e| e.to_string())
+}
+
+/// Copy a path to the clipboard, converted to `format`.
+pub fn copy_path(path: &str, format: PathFormat) -> Result<(), String> {
+    copy_text(&format.apply(path))
+}
@@ -0,0 +1,349 @@
+//! Reusing the results of a search that was just run.
+//!
+//! Frontends often run the same search again with nothing changed: the GUI
+//! re-runs its query when the window regains focus or a setting is toggled
+//! back. [`QueryCache`] keeps the last few outcomes of [`search::run`], each
+//! stamped with the index generation it was computed at, and follows the
+//! index's [`IndexChange`]s to hand one back only while none of the volumes
+//! it searched has changed since. A change on one drive leaves cached
+//! searches limited to another drive in place.
+//!
+//! [`search::run`]: crate::search::run
+
+use crate::search::{SearchOptions, SearchOutcome};
+use crossbeam_channel::Receiver;
+use glint_core::{Index, IndexChange, SearchQuery, VolumeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Everything besides the index that decides a search's outcome.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    /// The query text, trimmed
+    query: String,
+    /// The toggles, volume and folder the query was built with, in any form
+    /// that differs when they do
+    filters: String,
+    /// The selected scope
+    scope: Option<String>,
+    max_results: usize,
+    pinned_only: bool,
+    largest_only: bool,
+    deleted_only: bool,
+    typo_tolerance: bool,
+    dedup_links: bool,
+    /// Bumped by the frontend whenever pins change
+    pins: u64,
+}
+
+impl CacheKey {
+    pub fn new(
+        query: &str,
+        filters: impl Into<String>,
+        scope: Option<&str>,
+        options: &SearchOptions,
+        pins: u64,
+    ) -> Self {
+        CacheKey {
+            query: query.trim().to_string(),
+            filters: filters.into(),
+            scope: scope.map(str::to_string),
+            max_results: options.max_results,
+            pinned_only: options.pinned_only,
+            largest_only: options.largest_only,
+            deleted_only: options.deleted_only,
+            typo_tolerance: options.typo_tolerance,
+            dedup_links: options.dedup_links,
+            pins,
+        }
+    }
+}
+
+/// A cached outcome and the index state it was computed from.
+struct Entry {
+    key: CacheKey,
+    /// The index generation before the search ran
+    generation: u64,
+    /// The enabled volumes searched, which change without a new generation
+    volumes: Vec<VolumeId>,
+    outcome: Arc<SearchOutcome>,
+}
+
+/// A small least-recently-used cache of search outcomes.
+pub struct QueryCache {
+    /// Least recently used first
+    entries: Vec<Entry>,
+    capacity: usize,
+    /// Changes to the index being cached, once [`watch`](Self::watch)ed
+    changes: Option<Receiver<IndexChange>>,
+    /// Generation of the last change to each volume's records
+    volume_changes: HashMap<VolumeId, u64>,
+    /// Generation of the last change to the whole index, which counts as a
+    /// change to every volume
+    reset: u64,
+    /// Latest generation a change was received for
+    heard: u64,
+}
+
+impl QueryCache {
+    /// A cache holding up to `capacity` outcomes (0 disables it).
+    ///
+    /// Until it [`watch`](Self::watch)es an index, any change to the index
+    /// invalidates every outcome.
+    pub fn new(capacity: usize) -> Self {
+        QueryCache {
+            entries: Vec::with_capacity(capacity),
+            capacity,
+            changes: None,
+            volume_changes: HashMap::new(),
+            reset: 0,
+            heard: 0,
+        }
+    }
+
+    /// Start caching outcomes from `index`, dropping those from any other
+    /// and following its changes from now on.
+    pub fn watch(&mut self, index: &Index) {
+        self.entries.clear();
+        self.changes = Some(index.subscribe());
+        self.volume_changes.clear();
+        self.reset = index.generation();
+        self.heard = self.reset;
+    }
+
+    /// The outcome cached for `key`, if none of the volumes it searched has
+    /// changed and no volume was enabled or disabled since.
+    pub fn get(
+        &mut self,
+        index: &Index,
+        query: &SearchQuery,
+        key: &CacheKey,
+    ) -> Option<Arc<SearchOutcome>> {
+        let position = self.entries.iter().position(|e| e.key == *key)?;
+        self.catch_up();
+        let entry = &self.entries[position];
+        if !self.unchanged_since(entry, index.generation())
+            || entry.volumes != searched_volumes(index, query)
+        {
+            self.entries.remove(position);
+            return None;
+        }
+        let entry = self.entries.remove(position);
+        let outcome = Arc::clone(&entry.outcome);
+        self.entries.push(entry);
+        Some(outcome)
+    }
+
+    /// Cache `outcome` as the result of `query` on `index` as it was at
+    /// `generation`, read before the search ran so that changes made
+    /// during it invalidate the outcome.
+    pub fn insert(
+        &mut self,
+        index: &Index,
+        query: &SearchQuery,
+        generation: u64,
+        key: CacheKey,
+        outcome: SearchOutcome,
+    ) -> Arc<SearchOutcome> {
+        let outcome = Arc::new(outcome);
+        if self.capacity == 0 {
+            return outcome;
+        }
+        self.entries.retain(|e| e.key != key);
+        if self.entries.len() >= self.capacity {
+            self.entries.remove(0);
+        }
+        self.entries.push(Entry {
+            key,
+            generation,
+            volumes: searched_volumes(index, query),
+            outcome: Arc::clone(&outcome),
+        });
+        outcome
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Take in the changes received since the last call.
+    fn catch_up(&mut self) {
+        let Some(changes) = &self.changes else {
+            return;
+        };
+        for change in changes.try_iter() {
+            self.heard = self.heard.max(change.generation);
+            match change.volume {
+                Some(volume) => {
+                    self.volume_changes.insert(volume, change.generation);
+                }
+                None => self.reset = self.reset.max(change.generation),
+            }
+        }
+    }
+
+    /// Whether none of the volumes `entry` searched has changed since it
+    /// was computed, with the index now at `current`.
+    fn unchanged_since(&self, entry: &Entry, current: u64) -> bool {
+        if entry.generation == current {
+            return true;
+        }
+        // Changes not received yet could be to any volume
+        if self.changes.is_none() || self.heard < current || self.reset > entry.generation {
+            return false;
+        }
+        entry
+            .volumes
+            .iter()
+            .all(|volume| self.volume_changes.get(volume).copied().unwrap_or(0) <= entry.generation)
+    }
+}
+
+/// The enabled volumes `query` can match records on, sorted by ID.
+fn searched_volumes(index: &Index, query: &SearchQuery) -> Vec<VolumeId> {
+    let mut volumes: Vec<VolumeId> = index
+        .volume_states()
+        .into_iter()
+        .filter(|state| state.enabled && query.may_match_volume(&state.info))
+        .map(|state| state.info.id)
+        .collect();
+    volumes.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    volumes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search;
+    use glint_core::federation::Federation;
+    use glint_core::pins::PinStore;
+    use glint_core::{FileId, FileRecord, SearchFilter, VolumeInfo};
+
+    fn record(volume: &VolumeInfo, id: u64, name: &str) -> FileRecord {
+        FileRecord::new(
+            FileId::new(id),
+            None,
+            volume.id.clone(),
+            name.into(),
+            format!("{}\\{}", volume.mount_point, name),
+            false,
+        )
+    }
+
+    fn key(query: &str) -> CacheKey {
+        CacheKey::new(query, "", None, &SearchOptions::default(), 0)
+    }
+
+    fn cached(cache: &mut QueryCache, index: &Index, query: &SearchQuery, text: &str) -> bool {
+        if cache.get(index, query, &key(text)).is_some() {
+            return true;
+        }
+        let generation = index.generation();
+        let outcome = search::run(
+            index,
+            &Federation::new(),
+            &PinStore::new(),
+            query,
+            &SearchOptions::default(),
+        );
+        cache.insert(index, query, generation, key(text), outcome);
+        false
+    }
+
+    #[test]
+    fn test_cache_invalidated_per_volume() {
+        let c = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        let d = VolumeInfo::new(VolumeId::new("D"), "D:", "NTFS");
+        let index = Index::new();
+        index.add_volume_records(&c, vec![record(&c, 1, "report.txt")]);
+        index.add_volume_records(&d, vec![record(&d, 1, "notes.txt")]);
+
+        let mut cache = QueryCache::new(4);
+        cache.watch(&index);
+        let everywhere = SearchQuery::substring("txt");
+        let on_c = SearchQuery::substring("txt").with_filter(SearchFilter::Volume(c.id.clone()));
+        assert!(!cached(&mut cache, &index, &everywhere, "txt"));
+        assert!(!cached(&mut cache, &index, &on_c, "txt on:C"));
+        assert!(cached(&mut cache, &index, &everywhere, " txt "));
+        assert!(cached(&mut cache, &index, &on_c, "txt on:C"));
+
+        // A change on D only invalidates searches that include D
+        index.add_volume_records(&d, vec![record(&d, 2, "todo.txt")]);
+        assert!(!cached(&mut cache, &index, &everywhere, "txt"));
+        assert!(cached(&mut cache, &index, &on_c, "txt on:C"));
+
+        // So does disabling a volume
+        index.set_volume_enabled(&d.id, false);
+        assert!(!cached(&mut cache, &index, &everywhere, "txt"));
+        assert!(cached(&mut cache, &index, &on_c, "txt on:C"));
+
+        // Clearing the index changes every volume
+        index.clear();
+        assert!(!cached(&mut cache, &index, &on_c, "txt on:C"));
+    }
+
+    #[test]
+    fn test_cache_unwatched() {
+        let c = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        let d = VolumeInfo::new(VolumeId::new("D"), "D:", "NTFS");
+        let index = Index::new();
+        index.add_volume_records(&c, vec![record(&c, 1, "report.txt")]);
+
+        // Without following the index, any change invalidates everything
+        let mut cache = QueryCache::new(4);
+        let on_c = SearchQuery::substring("txt").with_filter(SearchFilter::Volume(c.id.clone()));
+        assert!(!cached(&mut cache, &index, &on_c, "txt on:C"));
+        assert!(cached(&mut cache, &index, &on_c, "txt on:C"));
+        index.add_volume_records(&d, Vec::new());
+        assert!(!cached(&mut cache, &index, &on_c, "txt on:C"));
+    }
+
+    #[test]
+    fn test_cache_change_during_search() {
+        let c = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        let index = Index::new();
+        index.add_volume_records(&c, vec![record(&c, 1, "report.txt")]);
+        let mut cache = QueryCache::new(4);
+        cache.watch(&index);
+
+        // An outcome computed before a change to a volume it searched is
+        // never handed back after it
+        let query = SearchQuery::substring("txt");
+        let generation = index.generation();
+        let outcome = search::run(
+            &index,
+            &Federation::new(),
+            &PinStore::new(),
+            &query,
+            &SearchOptions::default(),
+        );
+        index.add_volume_records(&c, vec![record(&c, 2, "notes.txt")]);
+        cache.insert(&index, &query, generation, key("txt"), outcome);
+        assert!(cache.get(&index, &query, &key("txt")).is_none());
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let index = Index::new();
+        let mut cache = QueryCache::new(2);
+        let query = SearchQuery::substring("a");
+        for text in ["a", "b", "c"] {
+            cached(&mut cache, &index, &query, text);
+        }
+        assert_eq!(cache.len(), 2);
+        assert!(!cached(&mut cache, &index, &query, "a"));
+        assert!(cached(&mut cache, &index, &query, "c"));
+
+        let mut disabled = QueryCache::new(0);
+        assert!(!cached(&mut disabled, &index, &query, "a"));
+        assert!(disabled.is_empty());
+    }
+}
@@ -0,0 +1,250 @@
+//! Formatting numbers, sizes, dates and ages for display.
+//!
+//! Numbers, sizes and dates follow the [`Locale`] taken from the `[ui]`
+//! config, which each frontend applies to the whole process at startup
+//! with [`set_locale`]; until then they are written the US way, with
+//! binary units and ISO dates.
+
+use chrono::{DateTime, Local, Utc};
+use glint_core::clock::{self, Zone};
+use glint_core::config::{DateFormat, NumberFormat, UiConfig};
+use glint_core::ResultTotals;
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// The locale used by the `format_*` functions, for the whole process.
+static LOCALE: RwLock<Locale> = RwLock::new(Locale::DEFAULT);
+
+/// How numbers, sizes and dates are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    pub numbers: NumberFormat,
+    /// Sizes in powers of 1000 (kB, MB) rather than 1024 (KB, MB)
+    pub si_units: bool,
+    pub dates: DateFormat,
+}
+
+impl Locale {
+    /// `1,234.5`, binary units and ISO dates.
+    pub const DEFAULT: Locale = Locale {
+        numbers: NumberFormat::En,
+        si_units: false,
+        dates: DateFormat::Iso,
+    };
+
+    /// The locale set in `[ui]`.
+    pub fn from_config(ui: &UiConfig) -> Self {
+        Locale {
+            numbers: ui.number_format,
+            si_units: ui.si_units,
+            dates: ui.date_format,
+        }
+    }
+
+    /// A count with its digits grouped, e.g. `1,234,567`.
+    pub fn number(&self, n: usize) -> String {
+        let digits = n.to_string();
+        let Some(separator) = self.numbers.group_separator() else {
+            return digits;
+        };
+        let mut result = String::with_capacity(digits.len() + digits.len() / 3 * 2);
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                result.push(separator);
+            }
+            result.push(c);
+        }
+        result
+    }
+
+    /// `value` rounded to `places` decimals, with the locale's decimal
+    /// mark, e.g. `1.5` or `1,5`.
+    pub fn decimal(&self, value: f64, places: usize) -> String {
+        let text = format!("{:.*}", places, value);
+        match self.numbers.decimal_mark() {
+            '.' => text,
+            mark => text.replace('.', &mark.to_string()),
+        }
+    }
+
+    /// A size in bytes, e.g. `1.5 MB`.
+    pub fn size(&self, bytes: u64) -> String {
+        let (unit, names) = match self.si_units {
+            true => (1000, ["kB", "MB", "GB"]),
+            false => (1024, ["KB", "MB", "GB"]),
+        };
+        let mut scaled = None;
+        let mut step = unit;
+        for name in names {
+            if bytes < step {
+                break;
+            }
+            scaled = Some((step, name));
+            step = step.saturating_mul(unit);
+        }
+        match scaled {
+            Some((step, name)) => {
+                format!("{} {}", self.decimal(bytes as f64 / step as f64, 1), name)
+            }
+            None => format!("{} B", bytes),
+        }
+    }
+
+    /// A date and time to the minute, in the zone days are counted in
+    /// (see [`clock::set_zone`]), e.g. `2024-01-31 14:05`.
+    pub fn date_time(&self, at: DateTime<Utc>) -> String {
+        let pattern = format!("{} %H:%M", self.dates.pattern());
+        match clock::zone() {
+            Zone::Local => at.with_timezone(&Local).format(&pattern).to_string(),
+            Zone::Utc => at.format(&pattern).to_string(),
+        }
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::DEFAULT
+    }
+}
+
+/// Write numbers, sizes and dates the way `locale` does from now on.
+pub fn set_locale(locale: Locale) {
+    *LOCALE.write().unwrap_or_else(|e| e.into_inner()) = locale;
+}
+
+/// The locale set with [`set_locale`].
+pub fn locale() -> Locale {
+    *LOCALE.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Format a count with thousands separators, e.g. `1,234,567`.
+pub fn format_number(n: usize) -> String {
+    locale().number(n)
+}
+
+/// Format a size in bytes, e.g. `1.5 MB`.
+pub fn format_size(bytes: u64) -> String {
+    locale().size(bytes)
+}
+
+/// Format a date and time to the minute, e.g. `2024-01-31 14:05`.
+pub fn format_date_time(at: DateTime<Utc>) -> String {
+    locale().date_time(at)
+}
+
+/// Format the count and size of a set of results, e.g. `123 files, 4.2 GB`.
+///
+/// Folders are mentioned only when there are some.
+pub fn format_totals(totals: &ResultTotals) -> String {
+    let files = match totals.files {
+        1 => "1 file".to_string(),
+        n => format!("{} files", format_number(n)),
+    };
+    match totals.dirs {
+        0 => format!("{}, {}", files, format_size(totals.size)),
+        1 => format!("{}, 1 folder, {}", files, format_size(totals.size)),
+        n => format!(
+            "{}, {} folders, {}",
+            files,
+            format_number(n),
+            format_size(totals.size)
+        ),
+    }
+}
+
+/// Format how long ago something happened, e.g. `5 min ago`.
+pub fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    if secs < 60 {
+        "just now".to_string()
+    } else if secs < 60 * 60 {
+        format!("{} min ago", secs / 60)
+    } else if secs < 24 * 60 * 60 {
+        format!("{} h ago", secs / (60 * 60))
+    } else {
+        match secs / (24 * 60 * 60) {
+            1 => "1 day ago".to_string(),
+            days => format!("{} days ago", days),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(0), "0");
+        assert_eq!(format_number(999), "999");
+        assert_eq!(format_number(1000), "1,000");
+        assert_eq!(format_number(1234567), "1,234,567");
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024 / 2), "1.5 GB");
+    }
+
+    #[test]
+    fn test_locale() {
+        let mut locale = Locale {
+            numbers: NumberFormat::De,
+            si_units: false,
+            dates: DateFormat::De,
+        };
+        assert_eq!(locale.number(1234567), "1.234.567");
+        assert_eq!(locale.number(123), "123");
+        assert_eq!(locale.size(1536), "1,5 KB");
+        assert_eq!(locale.decimal(0.25, 2), "0,25");
+
+        locale.numbers = NumberFormat::Fr;
+        assert_eq!(locale.number(1234), "1\u{a0}234");
+        locale.numbers = NumberFormat::Ch;
+        assert_eq!(locale.number(1234567), "1'234'567");
+        locale.numbers = NumberFormat::Plain;
+        assert_eq!(locale.number(1234567), "1234567");
+
+        locale.si_units = true;
+        assert_eq!(locale.size(999), "999 B");
+        assert_eq!(locale.size(1500), "1.5 kB");
+        assert_eq!(locale.size(2_000_000_000), "2.0 GB");
+
+        clock::set_zone(Zone::Utc);
+        let at = DateTime::parse_from_rfc3339("2024-01-31T14:05:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(locale.date_time(at), "31.01.2024 14:05");
+        locale.dates = DateFormat::Us;
+        assert_eq!(locale.date_time(at), "01/31/2024 14:05");
+        assert_eq!(Locale::DEFAULT.date_time(at), "2024-01-31 14:05");
+    }
+
+    #[test]
+    fn test_format_totals() {
+        let totals = ResultTotals {
+            files: 1234,
+            dirs: 0,
+            size: 3 * 1024 * 1024 * 1024 / 2,
+        };
+        assert_eq!(format_totals(&totals), "1,234 files, 1.5 GB");
+        let totals = ResultTotals {
+            files: 1,
+            dirs: 2,
+            size: 512,
+        };
+        assert_eq!(format_totals(&totals), "1 file, 2 folders, 512 B");
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(Duration::from_secs(30)), "just now");
+        assert_eq!(format_age(Duration::from_secs(5 * 60 + 10)), "5 min ago");
+        assert_eq!(format_age(Duration::from_secs(3 * 3600)), "3 h ago");
+        assert_eq!(format_age(Duration::from_secs(86400 + 5)), "1 day ago");
+        assert_eq!(format_age(Duration::from_secs(3 * 86400)), "3 days ago");
+    }
+}
@@ -0,0 +1,219 @@
+//! Completing query tokens as the user types.
+//!
+//! [`complete`] looks at the word before the cursor: a partial token name
+//! such as `ex` completes to the tokens of [`QUERY_TOKENS`], and the value
+//! of a token completes from what the index holds, such as `ext:p` to the
+//! most common extensions starting with `p`.
+
+use glint_core::search::{TokenValue, QUERY_TOKENS};
+use glint_core::{Index, Scopes};
+use std::ops::Range;
+
+/// Most suggestions offered at once.
+pub const MAX_COMPLETIONS: usize = 8;
+
+/// Ages offered for `older-than:` and `newer-than:`.
+const AGES: &[&str] = &["1d", "7d", "30d", "6mo", "1y", "2y"];
+
+/// One suggestion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// Text replacing the word being completed
+    pub text: String,
+    /// Short detail shown next to it, such as a description or count
+    pub detail: String,
+}
+
+/// Suggestions for the word at the cursor.
+#[derive(Debug, Clone, Default)]
+pub struct Completions {
+    /// Byte range of the word in the query
+    pub range: Range<usize>,
+    pub items: Vec<Completion>,
+}
+
+impl Completions {
+    /// The query with the word replaced by `completion`.
+    pub fn apply(&self, query: &str, completion: &Completion) -> String {
+        let mut query = query.to_string();
+        query.replace_range(self.range.clone(), &completion.text);
+        query
+    }
+}
+
+/// Suggestions for the word of `query` ending at byte `cursor`, or `None`
+/// when there is nothing to suggest.
+pub fn complete(index: &Index, scopes: &Scopes, query: &str, cursor: usize) -> Option<Completions> {
+    let before = query.get(..cursor)?;
+    let start = before.rfind(char::is_whitespace).map_or(0, |i| {
+        i + before[i..].chars().next().map_or(1, char::len_utf8)
+    });
+    let word = &before[start..];
+    if word.is_empty() {
+        return None;
+    }
+
+    let items = match word.find(':') {
+        None => token_names(word),
+        Some(colon) => {
+            let (prefix, value) = word.split_at(colon + 1);
+            let token = QUERY_TOKENS.iter().find(|t| t.prefix == prefix)?;
+            values(index, scopes, token.value, value)
+                .into_iter()
+                .map(|(value, detail)| Completion {
+                    text: format!("{}{}", prefix, value),
+                    detail,
+                })
+                .collect()
+        }
+    };
+
+    let items: Vec<Completion> = items
+        .into_iter()
+        .filter(|c| c.text != word)
+        .take(MAX_COMPLETIONS)
+        .collect();
+    (!items.is_empty()).then_some(Completions {
+        range: start..cursor,
+        items,
+    })
+}
+
+/// Tokens whose name starts with `word`.
+fn token_names(word: &str) -> Vec<Completion> {
+    let word = word.to_lowercase();
+    QUERY_TOKENS
+        .iter()
+        .filter(|t| t.prefix.starts_with(&word))
+        .map(|t| Completion {
+            text: t.prefix.to_string(),
+            detail: t.description.to_string(),
+        })
+        .collect()
+}
+
+/// Values of a token starting with `value`, with a detail for each.
+fn values(index: &Index, scopes: &Scopes, kind: TokenValue, value: &str) -> Vec<(String, String)> {
+    let lower = value.to_lowercase();
+    let starts = |candidate: &str| candidate.to_lowercase().starts_with(&lower);
+    match kind {
+        TokenValue::None => Vec::new(),
+        TokenValue::Extensions => {
+            // Only the extension after the last comma is completed
+            let (done, last) = value.rsplit_once(',').unwrap_or(("", value));
+            let last = last.to_lowercase();
+            let done = match done {
+                "" => String::new(),
+                done => format!("{},", done),
+            };
+            index
+                .extension_counts()
+                .into_iter()
+                .filter(|(ext, _)| ext.starts_with(&last))
+                .map(|(ext, count)| (format!("{}{}", done, ext), format!("{} files", count)))
+                .collect()
+        }
+        TokenValue::Folder => index
+            .volume_states()
+            .into_iter()
+            .filter(|state| state.enabled)
+            .map(|state| state.info.display_name().to_string())
+            .filter(|root| starts(root))
+            .map(|root| (root, "Volume".to_string()))
+            .collect(),
+        TokenValue::Age => AGES
+            .iter()
+            .filter(|age| starts(age))
+            .map(|age| (age.to_string(), String::new()))
+            .collect(),
+        TokenValue::Scope => scopes
+            .names()
+            .filter(|name| starts(name))
+            .map(|name| (name.to_string(), "Scope".to_string()))
+            .collect(),
+        TokenValue::OneOf(words) => words
+            .iter()
+            .filter(|word| starts(word))
+            .map(|word| (word.to_string(), String::new()))
+            .collect(),
+        // The volume ID, named by its display name; the file ID is typed
+        TokenValue::FileId => index
+            .volume_states()
+            .into_iter()
+            .map(|state| (format!("{}:", state.info.id), state.info.display_name().to_string()))
+            .filter(|(id, _)| starts(id))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glint_core::{FileId, FileRecord, VolumeId, VolumeInfo};
+
+    fn index() -> Index {
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        let records = ["a.pdf", "b.pdf", "c.png", "d.rs"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                FileRecord::new(
+                    FileId::new(i as u64 + 1),
+                    None,
+                    volume.id.clone(),
+                    name.to_string(),
+                    format!("C:\\{}", name),
+                    false,
+                )
+            })
+            .collect();
+        let index = Index::new();
+        index.add_volume_records(&volume, records);
+        index
+    }
+
+    fn texts(completions: &Option<Completions>) -> Vec<&str> {
+        completions
+            .iter()
+            .flat_map(|c| &c.items)
+            .map(|c| c.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_complete_tokens_and_values() {
+        let index = index();
+        let scopes = Scopes::new();
+
+        let query = "report ex";
+        let completions = complete(&index, &scopes, query, query.len());
+        assert_eq!(texts(&completions), ["ext:"]);
+        let completions = completions.unwrap();
+        assert_eq!(
+            completions.apply(query, &completions.items[0]),
+            "report ext:"
+        );
+
+        // Most common extensions first, completing after the last comma
+        let query = "ext:p";
+        let completions = complete(&index, &scopes, query, query.len());
+        assert_eq!(texts(&completions), ["ext:pdf", "ext:png"]);
+        assert_eq!(completions.unwrap().items[0].detail, "2 files");
+        let query = "ext:rs,p";
+        assert_eq!(
+            texts(&complete(&index, &scopes, query, query.len())),
+            ["ext:rs,pdf", "ext:rs,png"]
+        );
+
+        assert_eq!(texts(&complete(&index, &scopes, "in:c", 4)), ["in:C:"]);
+        assert_eq!(
+            texts(&complete(&index, &scopes, "cloud:", 6)),
+            ["cloud:yes", "cloud:no"]
+        );
+
+        // Nothing for plain words, complete tokens or an empty word
+        assert!(complete(&index, &scopes, "report", 6).is_none());
+        assert!(complete(&index, &scopes, "file:", 5).is_none());
+        assert!(complete(&index, &scopes, "ext ", 4).is_none());
+    }
+}
@@ -0,0 +1,145 @@
+//! What was done to files from a frontend during this session.
+//!
+//! Opening, copying, recycling and renaming are recorded in a [`History`]
+//! so they can be reviewed, and recycled files and renames undone. The
+//! history lives only as long as the frontend; undoing relies on what
+//! survives on disk, the Recycle Bin and the rename journal.
+
+use chrono::{DateTime, Local};
+use glint_core::FileRecord;
+
+/// Most actions kept; older ones are dropped.
+pub const MAX_HISTORY: usize = 500;
+
+/// Something done to files.
+#[derive(Debug, Clone)]
+pub enum HistoryAction {
+    /// A file or folder opened with its default program
+    Opened(String),
+    /// A path copied to the clipboard
+    Copied(String),
+    /// A file sent to the Recycle Bin, kept to put back in the index
+    Recycled(FileRecord),
+    /// A bulk rename of this many files, undone through its journal
+    Renamed(usize),
+}
+
+impl HistoryAction {
+    /// Short description, such as `Opened C:\notes.txt`.
+    pub fn describe(&self) -> String {
+        match self {
+            HistoryAction::Opened(path) => format!("Opened {}", path),
+            HistoryAction::Copied(path) => format!("Copied {}", path),
+            HistoryAction::Recycled(record) => format!("Recycled {}", record.path),
+            HistoryAction::Renamed(1) => "Renamed 1 file".to_string(),
+            HistoryAction::Renamed(count) => format!("Renamed {} files", count),
+        }
+    }
+}
+
+/// One recorded action.
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub at: DateTime<Local>,
+    pub action: HistoryAction,
+    /// Whether the action was undone
+    pub undone: bool,
+}
+
+/// Actions done this session, oldest first.
+#[derive(Debug, Default)]
+pub struct History {
+    entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an action done now.
+    pub fn record(&mut self, action: HistoryAction) {
+        if self.entries.len() == MAX_HISTORY {
+            self.entries.remove(0);
+        }
+        self.entries.push(HistoryEntry {
+            at: Local::now(),
+            action,
+            undone: false,
+        });
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether entry `i` can still be undone. Recycled files can be put
+    /// back until the Recycle Bin is emptied; only the latest rename is
+    /// journaled.
+    pub fn can_undo(&self, i: usize) -> bool {
+        let Some(entry) = self.entries.get(i).filter(|e| !e.undone) else {
+            return false;
+        };
+        match entry.action {
+            HistoryAction::Recycled(_) => true,
+            HistoryAction::Renamed(_) => self.last_rename() == Some(i),
+            HistoryAction::Opened(_) | HistoryAction::Copied(_) => false,
+        }
+    }
+
+    /// The latest rename, if it wasn't undone.
+    pub fn last_rename(&self) -> Option<usize> {
+        let i = self
+            .entries
+            .iter()
+            .rposition(|e| matches!(e.action, HistoryAction::Renamed(_)))?;
+        (!self.entries[i].undone).then_some(i)
+    }
+
+    /// Note that entry `i` was undone.
+    pub fn mark_undone(&mut self, i: usize) {
+        if let Some(entry) = self.entries.get_mut(i) {
+            entry.undone = true;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_rules() {
+        let mut history = History::new();
+        history.record(HistoryAction::Renamed(3));
+        history.record(HistoryAction::Opened("C:\\a.txt".to_string()));
+        history.record(HistoryAction::Renamed(1));
+        history.record(HistoryAction::Copied("C:\\a.txt".to_string()));
+        assert_eq!(history.entries()[2].action.describe(), "Renamed 1 file");
+
+        // Only the latest rename is journaled, and opening can't be undone
+        assert!(!history.can_undo(0));
+        assert!(!history.can_undo(1));
+        assert!(history.can_undo(2));
+        assert!(!history.can_undo(4));
+
+        // Once undone, no earlier rename takes its place
+        history.mark_undone(2);
+        assert!(!history.can_undo(2));
+        assert_eq!(history.last_rename(), None);
+
+        for _ in 0..MAX_HISTORY {
+            history.record(HistoryAction::Opened("C:\\b.txt".to_string()));
+        }
+        assert_eq!(history.entries().len(), MAX_HISTORY);
+        assert!(history.last_rename().is_none());
+    }
+}
@@ -0,0 +1,56 @@
+//! # Glint Frontend
+//!
+//! Logic shared by the frontends (the egui GUI and the terminal UI) that
+//! doesn't depend on how results are drawn:
+//!
+//! - **Search** (`search`): Running a query the way the frontends do, with
+//!   pinned items first, the typo-tolerant fallback and link merging
+//! - **Cache** (`cache`): Reusing outcomes of searches whose volumes have
+//!   not changed
+//! - **Actions** (`actions`): Opening, revealing and copying results
+//! - **Hints** (`hints`): Completing query tokens and their values
+//! - **Syntax** (`syntax`): The query syntax reference
+//! - **History** (`history`): What was done to files this session, for
+//!   review and undo
+//! - **Formatting** (`format`): Numbers and sizes for display
+//!
+//! Each frontend keeps its own input handling and layout; anything that
+//! should behave the same in both belongs here.
+
+pub mod actions;
+pub mod cache;
+pub mod format;
+pub mod hints;
+pub mod history;
+pub mod search;
+pub mod syntax;
+
+use glint_core::exclude::Exclusions;
+use glint_core::{Config, Index, IndexStore};
+use std::sync::Arc;
+
+/// Load the index from the configured index folder, set up as the config
+/// asks. An index that is missing or unreadable loads empty.
+///
+/// With `read_only` set in the config, both the store and the index refuse
+/// changes.
+pub fn load_index(config: &Config) -> glint_core::Result<(IndexStore, Arc<Index>)> {
+    let store = IndexStore::new(&config.index_dir()?).with_read_only(config.general.read_only);
+    let index = Arc::new(store.load_or_new());
+    apply_config(&index, config);
+    Ok((store, index))
+}
+
+/// Apply the settings of `config` to a loaded index: read-only mode, the
+/// lowercase path cache, exclusions, disabled volumes and whether offline
+/// volumes are searched.
+pub fn apply_config(index: &Index, config: &Config) {
+    index.set_read_only(config.general.read_only);
+    index.set_hide_offline(config.general.hide_offline_volumes);
+    index.set_cache_lower_paths(config.performance.cache_lower_paths);
+    index.set_exclusions(Exclusions::new(&config.exclude));
+    for state in index.volume_states() {
+        let enabled = !config.is_volume_disabled(state.info.display_name());
+        index.set_volume_enabled(&state.info.id, enabled);
+    }
+}
@@ -0,0 +1,308 @@
+//! Running a query the way the frontends show it.
+
+use glint_core::federation::Federation;
+use glint_core::pins::PinStore;
+use glint_core::{Index, LargestKind, ResultTotals, SearchQuery, SearchResult};
+
+/// How a frontend runs its searches.
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    /// Most results to return
+    pub max_results: usize,
+    /// List only the pinned items that match instead of searching
+    pub pinned_only: bool,
+    /// List the largest matching files, biggest first, instead of searching
+    pub largest_only: bool,
+    /// Search the files deleted recently instead of the index, as with the
+    /// `deleted:` token
+    pub deleted_only: bool,
+    /// Retry allowing one typo when nothing matches
+    pub typo_tolerance: bool,
+    /// Merge results that are the same file under several paths
+    pub dedup_links: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            max_results: 1000,
+            pinned_only: false,
+            largest_only: false,
+            deleted_only: false,
+            typo_tolerance: true,
+            dedup_links: false,
+        }
+    }
+}
+
+/// Results of [`run`].
+#[derive(Debug, Clone, Default)]
+pub struct SearchOutcome {
+    pub results: Vec<SearchResult>,
+    /// Number of matches, including those beyond `max_results`
+    pub total: usize,
+    /// Files, folders and size of the same matches as `total`
+    pub totals: ResultTotals,
+    /// Plain index results in index order, which matching new records can
+    /// be appended to
+    pub appendable: bool,
+}
+
+/// Search `index` and the indexes attached to it for `query`, with
+/// matching pinned items first.
+///
+/// Pinned and largest-file listings only cover `index`. Searches of
+/// deleted files list them newest first, without pinned items. When
+/// nothing matches and typos are tolerated, the query is retried allowing
+/// one typo; those results are marked `fuzzy`.
+pub fn run(
+    index: &Index,
+    attached: &Federation,
+    pins: &PinStore,
+    query: &SearchQuery,
+    options: &SearchOptions,
+) -> SearchOutcome {
+    if options.pinned_only {
+        let results: Vec<_> = pins
+            .resolve(index)
+            .into_iter()
+            .filter(|r| query.matches(r))
+            .take(options.max_results)
+            .map(|r| SearchResult::new(r, 0))
+            .collect();
+        return SearchOutcome {
+            total: results.len(),
+            totals: ResultTotals::of(results.iter().map(|r| &r.record)),
+            results,
+            appendable: false,
+        };
+    }
+
+    if options.largest_only {
+        let page = index.largest(query, LargestKind::Files, options.max_results);
+        return SearchOutcome {
+            results: page.results,
+            total: page.total,
+            totals: page.totals,
+            appendable: false,
+        };
+    }
+
+    let deleted_query;
+    let query = if options.deleted_only {
+        deleted_query = query.clone().search_deleted(true);
+        &deleted_query
+    } else {
+        query
+    };
+    let deleted = query.searches_deleted();
+
+    let page = attached.search_counted(index, query, options.max_results);
+    let mut total = Some((page.total, page.totals));
+    let mut results = page.results;
+    if !deleted {
+        pins.promote(index, query, &mut results, options.max_results);
+    }
+    if results.is_empty() && options.typo_tolerance {
+        results = attached.search_fuzzy(index, query, options.max_results);
+        total = None;
+    }
+    // Appended records would skip the merge, and are never deleted ones
+    let appendable = total.is_some() && !options.dedup_links && !deleted;
+    if options.dedup_links {
+        results = index.dedup_links(results);
+    }
+    let (total, totals) = total.unwrap_or_else(|| {
+        (
+            results.len(),
+            ResultTotals::of(results.iter().map(|r| &r.record)),
+        )
+    });
+    SearchOutcome {
+        total,
+        totals,
+        results,
+        appendable,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glint_core::{ChangeEvent, FileId, FileRecord, VolumeId, VolumeInfo};
+    use std::sync::Arc;
+
+    fn index() -> Index {
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        let vol = VolumeId::new("C");
+        let records = vec![
+            FileRecord::new(
+                FileId::new(5),
+                None,
+                vol.clone(),
+                "docs".into(),
+                "C:\\docs".into(),
+                true,
+            ),
+            FileRecord::new(
+                FileId::new(10),
+                Some(FileId::new(5)),
+                vol.clone(),
+                "report.txt".into(),
+                "C:\\docs\\report.txt".into(),
+                false,
+            )
+            .with_size(500),
+            FileRecord::new(
+                FileId::new(11),
+                Some(FileId::new(5)),
+                vol,
+                "notes.txt".into(),
+                "C:\\docs\\notes.txt".into(),
+                false,
+            ),
+        ];
+        let index = Index::new();
+        index.add_volume_records(&volume, records);
+        index
+    }
+
+    fn names(outcome: &SearchOutcome) -> Vec<&str> {
+        outcome
+            .results
+            .iter()
+            .map(|r| r.record.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_run_pins_first() {
+        let index = index();
+        let mut pins = PinStore::new();
+        let notes = index.search(&SearchQuery::substring("notes")).remove(0);
+        pins.pin(&notes.record);
+
+        let outcome = run(
+            &index,
+            &Federation::new(),
+            &pins,
+            &SearchQuery::substring(".txt"),
+            &SearchOptions::default(),
+        );
+        assert_eq!(names(&outcome), ["notes.txt", "report.txt"]);
+        assert_eq!(outcome.total, 2);
+        assert!(outcome.appendable);
+
+        let pinned = SearchOptions {
+            pinned_only: true,
+            ..Default::default()
+        };
+        let outcome = run(
+            &index,
+            &Federation::new(),
+            &pins,
+            &SearchQuery::substring(".txt"),
+            &pinned,
+        );
+        assert_eq!(names(&outcome), ["notes.txt"]);
+        assert!(!outcome.appendable);
+    }
+
+    #[test]
+    fn test_run_typo_fallback() {
+        let index = index();
+        let pins = PinStore::new();
+        let query = SearchQuery::substring("reprt");
+
+        let outcome = run(
+            &index,
+            &Federation::new(),
+            &pins,
+            &query,
+            &SearchOptions::default(),
+        );
+        assert_eq!(names(&outcome), ["report.txt"]);
+        assert!(outcome.results[0].fuzzy);
+        assert!(!outcome.appendable);
+
+        let strict = SearchOptions {
+            typo_tolerance: false,
+            ..Default::default()
+        };
+        assert!(run(&index, &Federation::new(), &pins, &query, &strict)
+            .results
+            .is_empty());
+    }
+
+    #[test]
+    fn test_run_largest() {
+        let index = index();
+        let largest = SearchOptions {
+            largest_only: true,
+            ..Default::default()
+        };
+        let outcome = run(
+            &index,
+            &Federation::new(),
+            &PinStore::new(),
+            &SearchQuery::substring(""),
+            &largest,
+        );
+        assert_eq!(names(&outcome), ["report.txt", "notes.txt"]);
+        assert_eq!(outcome.totals.size, 500);
+        assert!(!outcome.appendable);
+    }
+
+    #[test]
+    fn test_run_deleted() {
+        let index = index();
+        let mut pins = PinStore::new();
+        let notes = index.search(&SearchQuery::substring("notes")).remove(0);
+        pins.pin(&notes.record);
+        index.apply_change(ChangeEvent::deleted(
+            VolumeId::new("C"),
+            FileId::new(10),
+            Some(FileId::new(5)),
+            "report.txt".into(),
+            false,
+            1,
+        ));
+
+        let deleted = SearchOptions {
+            deleted_only: true,
+            ..Default::default()
+        };
+        let outcome = run(
+            &index,
+            &Federation::new(),
+            &pins,
+            &SearchQuery::substring(".txt"),
+            &deleted,
+        );
+        assert_eq!(names(&outcome), ["report.txt"]);
+        assert!(outcome.results[0].deleted_at.is_some());
+        assert!(!outcome.appendable);
+    }
+
+    #[test]
+    fn test_run_attached() {
+        let local = index();
+        let mut attached = Federation::new();
+        attached.add("server", Arc::new(index()));
+
+        let outcome = run(
+            &local,
+            &attached,
+            &PinStore::new(),
+            &SearchQuery::substring("report"),
+            &SearchOptions::default(),
+        );
+        let sources: Vec<_> = outcome
+            .results
+            .iter()
+            .map(|r| r.source.as_deref())
+            .collect();
+        assert_eq!(sources, [None, Some("server")]);
+        assert_eq!(outcome.total, 2);
+    }
+}
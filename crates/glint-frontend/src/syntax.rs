@@ -0,0 +1,164 @@
+//! The query syntax reference.
+//!
+//! Built from the parser's own registries, [`QUERY_PATTERNS`] and
+//! [`QUERY_TOKENS`], so it lists exactly what
+//! [`parse_query`](glint_core::search::parse_query) reads. `glint help
+//! syntax` prints it, the terminal UI shows it on F1 and the GUI in its
+//! Query Syntax window; the table in README is checked against [`markdown`].
+
+use glint_core::search::{QUERY_PATTERNS, QUERY_TOKENS};
+
+/// One row of the reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxEntry {
+    /// How it is written, such as `older-than:<age>`
+    pub syntax: String,
+    /// Other spellings of a token
+    pub aliases: &'static [&'static str],
+    pub description: &'static str,
+    /// A query using it
+    pub example: &'static str,
+}
+
+/// A group of rows under a heading.
+#[derive(Debug, Clone)]
+pub struct SyntaxSection {
+    pub title: &'static str,
+    pub entries: Vec<SyntaxEntry>,
+}
+
+/// The search text forms, then the filter tokens.
+pub fn sections() -> Vec<SyntaxSection> {
+    let patterns = QUERY_PATTERNS
+        .iter()
+        .map(|pattern| SyntaxEntry {
+            syntax: pattern.syntax.to_string(),
+            aliases: &[],
+            description: pattern.description,
+            example: pattern.example,
+        })
+        .collect();
+    let tokens = QUERY_TOKENS
+        .iter()
+        .map(|token| SyntaxEntry {
+            syntax: token.usage(),
+            aliases: token.aliases,
+            description: token.description,
+            example: token.example,
+        })
+        .collect();
+    vec![
+        SyntaxSection {
+            title: "Search text",
+            entries: patterns,
+        },
+        SyntaxSection {
+            title: "Filters",
+            entries: tokens,
+        },
+    ]
+}
+
+/// How the token values are read, each once, in token order.
+pub fn notes() -> Vec<&'static str> {
+    let mut notes = Vec::new();
+    for note in QUERY_TOKENS.iter().filter_map(|token| token.value.note()) {
+        if !notes.contains(&note) {
+            notes.push(note);
+        }
+    }
+    notes
+}
+
+/// The description with the aliases of a token, such as
+/// `Only files (also files:)`.
+pub fn describe(entry: &SyntaxEntry) -> String {
+    if entry.aliases.is_empty() {
+        entry.description.to_string()
+    } else {
+        format!("{} (also {})", entry.description, entry.aliases.join(", "))
+    }
+}
+
+/// The reference as lines of plain text, for terminals.
+pub fn plain_text() -> Vec<String> {
+    let sections = sections();
+    let width = sections
+        .iter()
+        .flat_map(|section| &section.entries)
+        .map(|entry| entry.syntax.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = vec![
+        "Parts of a query are separated by spaces. Filter tokens narrow the results;".to_string(),
+        "the other parts are joined into the search text.".to_string(),
+    ];
+    for section in &sections {
+        lines.push(String::new());
+        lines.push(format!("{}:", section.title));
+        for entry in &section.entries {
+            lines.push(format!("  {:<width$}  {}", entry.syntax, describe(entry)));
+            lines.push(format!("  {:<width$}  e.g. {}", "", entry.example));
+        }
+    }
+    lines.push(String::new());
+    lines.extend(notes().into_iter().map(str::to_string));
+    lines
+}
+
+/// The reference as a Markdown table followed by the notes, as in README.
+pub fn markdown() -> String {
+    // A pipe inside a table cell ends the cell, even in a code span
+    let code = |text: &str| format!("`{}`", text.replace('|', "\\|"));
+
+    let mut out = String::from("| Syntax | Description | Example |\n");
+    out.push_str("|--------|-------------|---------|\n");
+    for entry in sections().iter().flat_map(|section| &section.entries) {
+        let mut description = entry.description.to_string();
+        if !entry.aliases.is_empty() {
+            let aliases: Vec<String> = entry.aliases.iter().map(|alias| code(alias)).collect();
+            description = format!("{} (also {})", description, aliases.join(", "));
+        }
+        out.push_str(&format!(
+            "| {} | {} | {} |\n",
+            code(&entry.syntax),
+            description.replace('|', "\\|"),
+            code(entry.example)
+        ));
+    }
+    out.push('\n');
+    for note in notes() {
+        out.push_str(&format!("- {}\n", note));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_lists_every_token() {
+        let sections = sections();
+        assert_eq!(sections[0].entries.len(), QUERY_PATTERNS.len());
+        assert_eq!(sections[1].entries.len(), QUERY_TOKENS.len());
+
+        let text = plain_text().join("\n");
+        for token in QUERY_TOKENS {
+            assert!(text.contains(&token.usage()), "{}", token.prefix);
+        }
+        assert!(text.contains("Only folders (also dirs:, folder:)"));
+        assert!(markdown().contains("| `cloud:[yes\\|no]` |"));
+    }
+
+    #[test]
+    fn test_readme_is_current() {
+        let readme = include_str!("../../../README.md").replace("\r\n", "\n");
+        assert!(
+            readme.contains(&markdown()),
+            "README's query syntax is out of date; replace it with the output of \
+             'glint help syntax --markdown'"
+        );
+    }
+}
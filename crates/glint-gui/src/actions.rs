@@ -0,0 +1,712 @@
+//! Central registry of GUI actions.
+//!
+//! Every user-facing command is an [`ActionId`] with a label, a category and
+//! a handler. Menus, the command palette and keyboard shortcuts all go
+//! through [`execute`], so an action behaves the same wherever it is
+//! triggered from.
+//!
+//! Shortcuts come from each action's default, overridden by the user's
+//! bindings in Settings (see [`Keymap`]).
+
+use crate::app::GlintApp;
+use crate::launcher;
+use eframe::egui;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Identifies a user-facing action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActionId {
+    BuildIndex,
+    ReloadIndex,
+    ShowStatistics,
+    RenameResults,
+    UndoRename,
+    ShowHistory,
+    AttachIndex,
+    DetachIndexes,
+    OpenSettings,
+    Exit,
+    FocusSearch,
+    ClearSearch,
+    OpenSelected,
+    CopyPath,
+    FilterResults,
+    SelectPrevious,
+    SelectNext,
+    PageUp,
+    PageDown,
+    SelectFirst,
+    SelectLast,
+    TogglePin,
+    ToggleFilesOnly,
+    ToggleFoldersOnly,
+    ToggleCaseSensitive,
+    ToggleRegex,
+    TogglePinnedView,
+    ToggleLargestView,
+    ToggleDeletedView,
+    ToggleTypoTolerance,
+    ToggleDarkMode,
+    CommandPalette,
+    ToggleLauncher,
+    ShowQuerySyntax,
+    ShowAbout,
+}
+
+/// A registered action.
+pub struct Action {
+    pub id: ActionId,
+    /// Stable name used when saving key bindings
+    pub key: &'static str,
+    /// Group shown in the palette ("File", "Search", ...)
+    pub category: &'static str,
+    pub label: &'static str,
+    /// Shortcut used unless rebound in Settings
+    pub default_shortcut: Option<&'static str>,
+    handler: fn(&egui::Context, &mut GlintApp),
+    enabled: fn(&GlintApp) -> bool,
+}
+
+impl Action {
+    /// Whether the action can run in the current state.
+    pub fn is_enabled(&self, app: &GlintApp) -> bool {
+        (self.enabled)(app)
+    }
+
+    /// Label with the category, as listed in the palette.
+    pub fn title(&self) -> String {
+        format!("{}: {}", self.category, self.label)
+    }
+}
+
+fn always(_: &GlintApp) -> bool {
+    true
+}
+
+fn has_results(app: &GlintApp) -> bool {
+    !app.search.results.is_empty()
+}
+
+fn has_attached(app: &GlintApp) -> bool {
+    !app.search.attached().is_empty()
+}
+
+/// All actions, in palette order.
+pub static ACTIONS: &[Action] = &[
+    Action {
+        id: ActionId::BuildIndex,
+        key: "build_index",
+        category: "File",
+        label: "Build Index...",
+        default_shortcut: None,
+        handler: |_, app| app.show_index_builder = true,
+        enabled: always,
+    },
+    Action {
+        id: ActionId::ReloadIndex,
+        key: "reload_index",
+        category: "File",
+        label: "Reload Index",
+        default_shortcut: Some("F5"),
+        handler: |_, app| app.reload_index(),
+        enabled: always,
+    },
+    Action {
+        id: ActionId::ShowStatistics,
+        key: "show_statistics",
+        category: "File",
+        label: "Statistics...",
+        default_shortcut: None,
+        handler: |_, app| app.show_stats = true,
+        enabled: always,
+    },
+    Action {
+        id: ActionId::RenameResults,
+        key: "rename_results",
+        category: "File",
+        label: "Rename Results...",
+        default_shortcut: None,
+        handler: |_, app| app.open_rename_dialog(),
+        enabled: has_results,
+    },
+    Action {
+        id: ActionId::UndoRename,
+        key: "undo_rename",
+        category: "File",
+        label: "Undo Last Rename",
+        default_shortcut: None,
+        handler: |_, app| app.undo_rename(),
+        enabled: always,
+    },
+    Action {
+        id: ActionId::ShowHistory,
+        key: "show_history",
+        category: "File",
+        label: "History...",
+        default_shortcut: Some("Ctrl+H"),
+        handler: |_, app| app.show_history = !app.show_history,
+        enabled: always,
+    },
+    Action {
+        id: ActionId::AttachIndex,
+        key: "attach_index",
+        category: "File",
+        label: "Attach Index...",
+        default_shortcut: None,
+        handler: |_, app| app.attach_index(),
+        enabled: always,
+    },
+    Action {
+        id: ActionId::DetachIndexes,
+        key: "detach_indexes",
+        category: "File",
+        label: "Detach All Indexes",
+        default_shortcut: None,
+        handler: |_, app| app.detach_indexes(),
+        enabled: has_attached,
+    },
+    Action {
+        id: ActionId::OpenSettings,
+        key: "open_settings",
+        category: "File",
+        label: "Settings...",
+        default_shortcut: Some("Ctrl+Comma"),
+        handler: |_, app| app.show_settings = !app.show_settings,
+        enabled: always,
+    },
+    Action {
+        id: ActionId::Exit,
+        key: "exit",
+        category: "File",
+        label: "Exit",
+        default_shortcut: None,
+        handler: |ctx, _| ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+        enabled: always,
+    },
+    Action {
+        id: ActionId::FocusSearch,
+        key: "focus_search",
+        category: "Search",
+        label: "Focus Search Box",
+        default_shortcut: Some("Ctrl+L"),
+        handler: |_, app| app.focus_search = true,
+        enabled: always,
+    },
+    Action {
+        id: ActionId::ClearSearch,
+        key: "clear_search",
+        category: "Search",
+        label: "Clear Search",
+        default_shortcut: Some("Escape"),
+        handler: |_, app| {
+            app.search.query.clear();
+            app.search.clear();
+        },
+        enabled: always,
+    },
+    Action {
+        id: ActionId::OpenSelected,
+        key: "open_selected",
+        category: "Results",
+        label: "Open Selected",
+        default_shortcut: Some("Enter"),
+        handler: |_, app| app.search.open_selected(),
+        enabled: has_results,
+    },
+    Action {
+        id: ActionId::CopyPath,
+        key: "copy_path",
+        category: "Results",
+        label: "Copy Path",
+        default_shortcut: Some("Ctrl+C"),
+        handler: |_, app| {
+            app.status_message = match app.search.copy_selected_path(app.config.ui.path_format) {
+                Ok(()) => "Path copied to clipboard".to_string(),
+                Err(e) => format!("Failed to copy: {}", e),
+            };
+        },
+        enabled: has_results,
+    },
+    Action {
+        id: ActionId::FilterResults,
+        key: "filter_results",
+        category: "Results",
+        label: "Filter Results",
+        default_shortcut: Some("Ctrl+Shift+F"),
+        handler: |_, app| {
+            app.search.filter_open = true;
+            app.focus_filter = true;
+        },
+        enabled: always,
+    },
+    Action {
+        id: ActionId::SelectPrevious,
+        key: "select_previous",
+        category: "Results",
+        label: "Select Previous",
+        default_shortcut: Some("Up"),
+        handler: |_, app| app.search.select_previous(),
+        enabled: has_results,
+    },
+    Action {
+        id: ActionId::SelectNext,
+        key: "select_next",
+        category: "Results",
+        label: "Select Next",
+        default_shortcut: Some("Down"),
+        handler: |_, app| app.search.select_next(),
+        enabled: has_results,
+    },
+    Action {
+        id: ActionId::PageUp,
+        key: "page_up",
+        category: "Results",
+        label: "Page Up",
+        default_shortcut: Some("PageUp"),
+        handler: |_, app| app.search.page_up(20),
+        enabled: has_results,
+    },
+    Action {
+        id: ActionId::PageDown,
+        key: "page_down",
+        category: "Results",
+        label: "Page Down",
+        default_shortcut: Some("PageDown"),
+        handler: |_, app| app.search.page_down(20),
+        enabled: has_results,
+    },
+    Action {
+        id: ActionId::SelectFirst,
+        key: "select_first",
+        category: "Results",
+        label: "Select First",
+        default_shortcut: Some("Ctrl+Home"),
+        handler: |_, app| app.search.select_first(),
+        enabled: has_results,
+    },
+    Action {
+        id: ActionId::SelectLast,
+        key: "select_last",
+        category: "Results",
+        label: "Select Last",
+        default_shortcut: Some("Ctrl+End"),
+        handler: |_, app| app.search.select_last(),
+        enabled: has_results,
+    },
+    Action {
+        id: ActionId::TogglePin,
+        key: "toggle_pin",
+        category: "Results",
+        label: "Pin/Unpin Selected",
+        default_shortcut: None,
+        handler: |_, app| app.toggle_pin(app.search.selected),
+        enabled: has_results,
+    },
+    Action {
+        id: ActionId::ToggleFilesOnly,
+        key: "toggle_files_only",
+        category: "Filter",
+        label: "Toggle Files Only",
+        default_shortcut: None,
+        handler: |_, app| {
+            app.search.files_only = !app.search.files_only;
+            app.search.dirs_only &= !app.search.files_only;
+            app.search.mark_dirty();
+        },
+        enabled: always,
+    },
+    Action {
+        id: ActionId::ToggleFoldersOnly,
+        key: "toggle_folders_only",
+        category: "Filter",
+        label: "Toggle Folders Only",
+        default_shortcut: None,
+        handler: |_, app| {
+            app.search.dirs_only = !app.search.dirs_only;
+            app.search.files_only &= !app.search.dirs_only;
+            app.search.mark_dirty();
+        },
+        enabled: always,
+    },
+    Action {
+        id: ActionId::ToggleCaseSensitive,
+        key: "toggle_case_sensitive",
+        category: "Filter",
+        label: "Toggle Case Sensitive",
+        default_shortcut: None,
+        handler: |_, app| {
+            app.search.case_sensitive = !app.search.case_sensitive;
+            app.search.mark_dirty();
+        },
+        enabled: always,
+    },
+    Action {
+        id: ActionId::ToggleRegex,
+        key: "toggle_regex",
+        category: "Filter",
+        label: "Toggle Regex",
+        default_shortcut: None,
+        handler: |_, app| {
+            app.search.use_regex = !app.search.use_regex;
+            app.search.mark_dirty();
+        },
+        enabled: always,
+    },
+    Action {
+        id: ActionId::TogglePinnedView,
+        key: "toggle_pinned_view",
+        category: "Filter",
+        label: "Toggle Pinned Only",
+        default_shortcut: None,
+        handler: |_, app| {
+            app.search.show_pinned = !app.search.show_pinned;
+            app.search.mark_dirty();
+        },
+        enabled: always,
+    },
+    Action {
+        id: ActionId::ToggleLargestView,
+        key: "toggle_largest_view",
+        category: "Filter",
+        label: "Toggle Largest Files",
+        default_shortcut: None,
+        handler: |_, app| {
+            app.search.show_largest = !app.search.show_largest;
+            app.search.mark_dirty();
+        },
+        enabled: always,
+    },
+    Action {
+        id: ActionId::ToggleDeletedView,
+        key: "toggle_deleted_view",
+        category: "Filter",
+        label: "Toggle Recently Deleted",
+        default_shortcut: None,
+        handler: |_, app| {
+            app.search.show_deleted = !app.search.show_deleted;
+            app.search.mark_dirty();
+        },
+        enabled: always,
+    },
+    Action {
+        id: ActionId::ToggleTypoTolerance,
+        key: "toggle_typo_tolerance",
+        category: "Search",
+        label: "Toggle Typo-Tolerant Search",
+        default_shortcut: None,
+        handler: |_, app| {
+            app.config.general.typo_tolerance = !app.config.general.typo_tolerance;
+            app.search.typo_tolerance = app.config.general.typo_tolerance;
+            app.search.mark_dirty();
+            if let Err(e) = app.config.save() {
+                app.toasts.error(format!("Failed to save config: {}", e));
+            }
+        },
+        enabled: always,
+    },
+    Action {
+        id: ActionId::ToggleDarkMode,
+        key: "toggle_dark_mode",
+        category: "View",
+        label: "Toggle Dark Mode",
+        default_shortcut: None,
+        handler: |_, app| app.dark_mode = !app.dark_mode,
+        enabled: always,
+    },
+    Action {
+        id: ActionId::CommandPalette,
+        key: "command_palette",
+        category: "View",
+        label: "Command Palette",
+        default_shortcut: Some("Ctrl+Shift+P"),
+        handler: |_, app| app.palette.open(),
+        enabled: always,
+    },
+    Action {
+        id: ActionId::ToggleLauncher,
+        key: "toggle_launcher",
+        category: "View",
+        label: "Toggle Launcher",
+        default_shortcut: Some("Ctrl+Shift+L"),
+        handler: launcher::toggle,
+        enabled: always,
+    },
+    Action {
+        id: ActionId::ShowQuerySyntax,
+        key: "show_query_syntax",
+        category: "Help",
+        label: "Query Syntax",
+        default_shortcut: Some("F1"),
+        handler: |_, app| app.show_syntax = !app.show_syntax,
+        enabled: always,
+    },
+    Action {
+        id: ActionId::ShowAbout,
+        key: "show_about",
+        category: "Help",
+        label: "About...",
+        default_shortcut: None,
+        handler: |_, app| app.show_about = !app.show_about,
+        enabled: always,
+    },
+];
+
+/// Look up an action.
+pub fn action(id: ActionId) -> &'static Action {
+    ACTIONS
+        .iter()
+        .find(|a| a.id == id)
+        .expect("every ActionId is registered")
+}
+
+/// Run an action if it is enabled.
+pub fn execute(id: ActionId, ctx: &egui::Context, app: &mut GlintApp) {
+    let action = action(id);
+    if action.is_enabled(app) {
+        (action.handler)(ctx, app);
+    }
+}
+
+/// A key with modifiers, e.g. `Ctrl+Shift+P`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub key: egui::Key,
+}
+
+impl Shortcut {
+    /// Parse a shortcut such as `Ctrl+Shift+P` or `F5`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (mods, key) = s.trim().rsplit_once('+').unwrap_or(("", s.trim()));
+        let mut shortcut = Shortcut {
+            ctrl: false,
+            shift: false,
+            alt: false,
+            key: egui::Key::from_name(key)?,
+        };
+        for modifier in mods.split('+').filter(|m| !m.is_empty()) {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "cmd" => shortcut.ctrl = true,
+                "shift" => shortcut.shift = true,
+                "alt" => shortcut.alt = true,
+                _ => return None,
+            }
+        }
+        Some(shortcut)
+    }
+
+    /// Build a shortcut from a key event.
+    pub fn from_event(key: egui::Key, modifiers: egui::Modifiers) -> Self {
+        Shortcut {
+            ctrl: modifiers.command,
+            shift: modifiers.shift,
+            alt: modifiers.alt,
+            key,
+        }
+    }
+
+    /// Check if the shortcut was pressed this frame (modifiers must match exactly).
+    pub fn pressed(&self, input: &egui::InputState) -> bool {
+        input.key_pressed(self.key)
+            && input.modifiers.command == self.ctrl
+            && input.modifiers.shift == self.shift
+            && input.modifiers.alt == self.alt
+    }
+
+    /// The form saved in settings, using key names (`Ctrl+Comma`).
+    pub fn to_setting(self) -> String {
+        self.format(self.key.name())
+    }
+
+    fn format(&self, key: &str) -> String {
+        let mut out = String::new();
+        if self.ctrl {
+            out.push_str("Ctrl+");
+        }
+        if self.shift {
+            out.push_str("Shift+");
+        }
+        if self.alt {
+            out.push_str("Alt+");
+        }
+        out.push_str(key);
+        out
+    }
+}
+
+impl fmt::Display for Shortcut {
+    /// The form shown in menus, using key symbols (`Ctrl+,`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.format(self.key.symbol_or_name()))
+    }
+}
+
+/// Current shortcut of every action.
+pub struct Keymap {
+    bindings: HashMap<ActionId, Option<Shortcut>>,
+}
+
+impl Keymap {
+    /// Default shortcuts with the user's overrides applied.
+    ///
+    /// `overrides` maps action keys to shortcuts; an empty shortcut unbinds
+    /// the action. Unknown actions and unparseable shortcuts are ignored.
+    pub fn new(overrides: &BTreeMap<String, String>) -> Self {
+        let mut keymap = Keymap {
+            bindings: ACTIONS
+                .iter()
+                .map(|a| (a.id, default_shortcut(a)))
+                .collect(),
+        };
+        for action in ACTIONS {
+            match overrides.get(action.key).map(|s| s.trim()) {
+                Some("") => keymap.set(action.id, None),
+                Some(s) => {
+                    if let Some(shortcut) = Shortcut::parse(s) {
+                        keymap.set(action.id, Some(shortcut));
+                    }
+                }
+                None => {}
+            }
+        }
+        keymap
+    }
+
+    /// The shortcut bound to an action.
+    pub fn shortcut(&self, id: ActionId) -> Option<Shortcut> {
+        self.bindings.get(&id).copied().flatten()
+    }
+
+    /// Bind (or with None, unbind) an action.
+    pub fn set(&mut self, id: ActionId, shortcut: Option<Shortcut>) {
+        self.bindings.insert(id, shortcut);
+    }
+
+    /// Restore an action's default shortcut.
+    pub fn reset(&mut self, id: ActionId) {
+        self.set(id, default_shortcut(action(id)));
+    }
+
+    /// Check if an action has its default shortcut.
+    pub fn is_default(&self, id: ActionId) -> bool {
+        self.shortcut(id) == default_shortcut(action(id))
+    }
+
+    /// Bindings that differ from the defaults, for saving in settings.
+    pub fn overrides(&self) -> BTreeMap<String, String> {
+        ACTIONS
+            .iter()
+            .filter(|a| !self.is_default(a.id))
+            .map(|a| {
+                let shortcut = self.shortcut(a.id).map(|s| s.to_setting());
+                (a.key.to_string(), shortcut.unwrap_or_default())
+            })
+            .collect()
+    }
+
+    /// Other actions bound to the same shortcut as `id`.
+    pub fn conflicts(&self, id: ActionId) -> Vec<ActionId> {
+        let Some(shortcut) = self.shortcut(id) else {
+            return Vec::new();
+        };
+        ACTIONS
+            .iter()
+            .filter(|a| a.id != id && self.shortcut(a.id) == Some(shortcut))
+            .map(|a| a.id)
+            .collect()
+    }
+
+    /// Actions whose shortcut was pressed this frame.
+    ///
+    /// If several actions share a shortcut, only the first in registry
+    /// order runs.
+    pub fn pressed(&self, input: &egui::InputState) -> Vec<ActionId> {
+        let mut seen = Vec::new();
+        let mut pressed = Vec::new();
+        for action in ACTIONS {
+            if let Some(shortcut) = self.shortcut(action.id) {
+                if shortcut.pressed(input) && !seen.contains(&shortcut) {
+                    seen.push(shortcut);
+                    pressed.push(action.id);
+                }
+            }
+        }
+        pressed
+    }
+}
+
+fn default_shortcut(action: &Action) -> Option<Shortcut> {
+    action.default_shortcut.and_then(Shortcut::parse)
+}
+
+/// State of the command palette.
+#[derive(Default)]
+pub struct CommandPalette {
+    pub open: bool,
+    pub query: String,
+    pub selected: usize,
+    /// Focus the input on the next frame
+    pub focus: bool,
+}
+
+impl CommandPalette {
+    pub fn open(&mut self) {
+        self.open = true;
+        self.query.clear();
+        self.selected = 0;
+        self.focus = true;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    /// Enabled actions matching the query, best match first.
+    pub fn matches(&self, app: &GlintApp) -> Vec<&'static Action> {
+        let mut scored: Vec<(i32, usize, &'static Action)> = ACTIONS
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| a.id != ActionId::CommandPalette && a.is_enabled(app))
+            .filter_map(|(i, a)| fuzzy_score(&self.query, &a.title()).map(|s| (s, i, a)))
+            .collect();
+        // Highest score first, registry order for ties
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        scored.into_iter().map(|(_, _, a)| a).collect()
+    }
+}
+
+/// Score `text` against `query` as a case-insensitive subsequence match.
+///
+/// Returns None if the query characters don't all appear in order.
+/// Consecutive characters and matches at word starts score higher, so
+/// "tfo" ranks "Toggle Files Only" above "Toggle Folders Only".
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let mut score = 0;
+    let mut chars = text.chars().enumerate();
+    let mut prev_char = ' ';
+    let mut prev_match: Option<usize> = None;
+
+    for q in query.chars().filter(|c| !c.is_whitespace()) {
+        let q = q.to_ascii_lowercase();
+        loop {
+            let (i, c) = chars.next()?;
+            let before = std::mem::replace(&mut prev_char, c);
+            if c.to_ascii_lowercase() != q {
+                continue;
+            }
+            score += 1;
+            if prev_match.is_some_and(|p| p + 1 == i) {
+                score += 5;
+            }
+            if !before.is_alphanumeric() {
+                score += 10;
+            }
+            prev_match = Some(i);
+            break;
+        }
+    }
+    Some(score)
+}
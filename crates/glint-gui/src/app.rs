@@ -1,25 +1,228 @@
 //! Main application state and logic.
 
+use crate::actions::{self, ActionId, CommandPalette, Keymap};
+use crate::browse::BrowseState;
+use crate::exclusions::ExclusionManager;
+use crate::folder_size::FolderSizes;
+use crate::index_watch::{self, IndexWatch};
+use crate::launcher::{self, Launcher};
+use crate::safe_mode::{self, LoadMessage, LoadedIndex, SafeMode};
 use crate::search::SearchState;
-use crate::service::{self, ServiceStatus};
-use crate::settings::Settings;
+use crate::service::{self, ServiceStatus, TaskStatus, TaskTrigger};
+use crate::settings::{Settings, WindowGeometry};
+use crate::theme::{self, SystemPreferences};
+use crate::toasts::Toasts;
 use crate::ui;
 use eframe::egui;
-use glint_core::{Config, Index, IndexStore};
-use glint_core::archive_view::ArchivedView;
-use crossbeam_channel::{unbounded, Receiver};
+use glint_core::{Config, FileRecord, Index, IndexStore, SaveProgress, VolumeId};
+use glint_core::backend::{
+    ChangeEvent, ProgressChannel, ProgressEvent, ScanEstimate, VOLUME_REFRESH_INTERVAL,
+};
+use glint_core::churn::ChurnFolder;
+use glint_core::exclude::{ExcludeRule, Exclusions};
+use glint_core::federation::{AttachedList, Federation};
+use glint_core::manifest::BuildManifest;
+use glint_core::pins::PinStore;
+use glint_core::rename::{RenameJournal, RenameOptions, RenamePlan, RenameTemplate};
+use glint_core::stats::{GroupBy, Statistics};
+use glint_frontend::format::{format_number, Locale};
+use glint_frontend::history::HistoryAction;
+use crossbeam_channel::{unbounded, Receiver, TryRecvError};
 use std::time::{Duration, Instant};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+/// Longest the window stays open after being closed, waiting for an index
+/// save to finish. A save cut short leaves the previous index file intact.
+const CLOSE_SAVE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Scan progress of an index build, read from the build thread's
+/// progress channel.
+pub struct BuildProgress {
+    events: Receiver<ProgressEvent>,
+    /// Volume being scanned
+    volume: Option<String>,
+    /// Files and folders scanned so far on that volume
+    scanned: u64,
+    estimate: Option<ScanEstimate>,
+    /// How each volume was scanned, for the build manifest
+    methods: Vec<(String, &'static str)>,
+}
+
+impl BuildProgress {
+    fn new(events: Receiver<ProgressEvent>) -> Self {
+        BuildProgress {
+            events,
+            volume: None,
+            scanned: 0,
+            estimate: None,
+            methods: Vec::new(),
+        }
+    }
+
+    /// Take in the reports sent since the last frame.
+    fn poll(&mut self) {
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                ProgressEvent::Progress {
+                    volume,
+                    files,
+                    dirs,
+                } => {
+                    self.start(volume);
+                    self.scanned = files + dirs;
+                }
+                ProgressEvent::Estimate { volume, estimate } => {
+                    self.start(volume);
+                    self.estimate = Some(estimate);
+                }
+                ProgressEvent::Method { volume, method } => {
+                    self.methods.retain(|(v, _)| *v != volume);
+                    self.methods.push((volume, method));
+                }
+                ProgressEvent::Complete { .. } => {
+                    self.volume = None;
+                    self.estimate = None;
+                }
+            }
+        }
+    }
+
+    /// Switch to reporting `volume` if it is a new one.
+    fn start(&mut self, volume: String) {
+        if self.volume.as_ref() != Some(&volume) {
+            self.volume = Some(volume);
+            self.scanned = 0;
+            self.estimate = None;
+        }
+    }
+
+    /// Status line for the scan under way, if the backend has reported on it.
+    fn status(&self) -> Option<String> {
+        let volume = self.volume.as_ref()?;
+        let scanned = format_number(self.scanned as usize);
+        Some(match self.estimate {
+            Some(estimate) => format!("Indexing {}... {} ({} entries)", volume, estimate, scanned),
+            None => format!("Indexing {}... {} entries", volume, scanned),
+        })
+    }
+
+    /// Record the scan mode of each scanned volume in a manifest.
+    fn fill_manifest(&self, manifest: &mut BuildManifest) {
+        for (volume, method) in &self.methods {
+            manifest.set_scan_mode(volume, method);
+        }
+    }
+}
+
+/// Report from the thread saving the index.
+enum SaveMessage {
+    Progress(SaveProgress),
+    Finished(Result<(), String>),
+}
+
 /// Information about a volume (for UI selection)
 #[derive(Clone)]
 pub struct VolumeInfo {
-    pub letter: char,
+    /// Drive root (e.g. "C:\\") or, for volumes without a drive letter,
+    /// the folder they are mounted at
+    pub mount_point: String,
     pub label: String,
     pub size: u64,
     pub selected: bool,
 }
 
+impl VolumeInfo {
+    /// The drive letter, if the volume is mounted at a drive root.
+    pub fn letter(&self) -> Option<char> {
+        let mount = self.mount_point.trim_end_matches('\\');
+        match mount.as_bytes() {
+            [letter, b':'] => Some(letter.to_ascii_uppercase() as char),
+            _ => None,
+        }
+    }
+}
+
+/// Check if a mount point from the backend is one of the selected ones.
+#[cfg(windows)]
+fn is_selected_mount(mount_point: &str, selected: &[String]) -> bool {
+    let mount_point = mount_point.trim_end_matches('\\');
+    selected
+        .iter()
+        .any(|s| s.trim_end_matches('\\').eq_ignore_ascii_case(mount_point))
+}
+
+/// Busy folders from the watcher's last report that are neither excluded
+/// nor dismissed.
+fn busy_folders(store: &IndexStore, config: &Config, settings: &Settings) -> Vec<ChurnFolder> {
+    let Some(report) = store.load_churn() else {
+        return Vec::new();
+    };
+    let exclusions = Exclusions::new(&config.exclude);
+    report
+        .folders
+        .into_iter()
+        .filter(|f| !exclusions.excludes_folder(&f.path.to_lowercase()))
+        .filter(|f| !settings.dismissed_busy_folders.contains(&f.path))
+        .collect()
+}
+
+/// A volume being loaded from disk, shown in the status bar
+pub struct VolumeLoadStatus {
+    pub id: VolumeId,
+    pub mount_point: String,
+    pub records: usize,
+    pub ready: bool,
+}
+
+/// State of the bulk rename dialog
+pub struct RenameDialog {
+    pub template: String,
+    pub pattern: String,
+    pub start: u64,
+    pub plan: Option<RenamePlan>,
+    pub error: Option<String>,
+    /// Inputs changed since the preview was built
+    pub dirty: bool,
+}
+
+impl Default for RenameDialog {
+    fn default() -> Self {
+        Self {
+            template: "{name}.{ext}".to_string(),
+            pattern: String::new(),
+            start: 1,
+            plan: None,
+            error: None,
+            dirty: true,
+        }
+    }
+}
+
+/// State of the statistics window
+#[derive(Default)]
+pub struct StatsView {
+    pub group_by: GroupBy,
+    /// Last computed statistics
+    pub stats: Option<Arc<Statistics>>,
+    /// Index generation the statistics were computed for
+    pub generation: u64,
+    /// Pending background aggregation
+    rx: Option<Receiver<(u64, Statistics)>>,
+}
+
+impl StatsView {
+    pub fn is_computing(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    /// Drop results computed for a previous index.
+    fn reset(&mut self) {
+        self.stats = None;
+        self.rx = None;
+    }
+}
+
 /// Main application state
 pub struct GlintApp {
     pub search: SearchState,
@@ -29,24 +232,85 @@ pub struct GlintApp {
     pub settings: Settings,
     pub available_volumes: Vec<VolumeInfo>,
     pub dark_mode: bool,
+    /// Accessibility settings of Windows, followed unless overridden
+    pub system_preferences: SystemPreferences,
     pub show_settings: bool,
     pub show_about: bool,
+    pub show_syntax: bool,
     pub show_index_builder: bool,
+    pub show_rename: bool,
+    pub rename: RenameDialog,
+    pub show_stats: bool,
+    pub show_history: bool,
+    pub stats: StatsView,
+    pub exclusions: ExclusionManager,
+    /// Folder being browsed instead of showing results
+    pub browse: Option<BrowseState>,
+    /// Sizes of the folders shown in the results and browse lists
+    pub folder_sizes: FolderSizes,
+    pub palette: CommandPalette,
+    /// Compact layout brought up by the hotkey
+    pub launcher: Launcher,
+    pub keymap: Keymap,
+    /// Action waiting for a new shortcut in Settings
+    pub rebinding: Option<ActionId>,
+    /// Move keyboard focus to the search box on the next frame
+    pub focus_search: bool,
+    /// Move keyboard focus to the result filter on the next frame
+    pub focus_filter: bool,
     pub status_message: String,
+    /// Outcomes of background work, shown until read
+    pub toasts: Toasts,
+    /// Set when the index could not be loaded; the app runs with an empty index
+    pub safe_mode: Option<SafeMode>,
+    /// Folders the watcher found changing constantly, to suggest excluding
+    pub busy_folders: Vec<ChurnFolder>,
+    /// Notices the index file being rewritten by another program
+    pub index_watch: IndexWatch,
+    /// Volumes of the index being loaded, in load order
+    pub volume_loads: Vec<VolumeLoadStatus>,
     pub service_status: ServiceStatus,
+    pub task_status: TaskStatus,
     pub enable_service_on_index: bool,
 
     // Async index loading
     loading_index: bool,
+    /// Generation of the in-memory index when it last matched the file on disk
+    disk_generation: Option<u64>,
     load_started_at: Instant,
-    load_rx: Option<Receiver<Arc<Index>>>,
+    /// Last time the loader reported progress
+    load_progress_at: Instant,
+    load_rx: Option<Receiver<LoadMessage>>,
 
     // Async index building
     building_index: bool,
     build_started_at: Instant,
     build_rx: Option<Receiver<Result<Arc<Index>, String>>>,
+    /// Index the build is adding scanned batches to
+    build_index: Option<Arc<Index>>,
+    build_progress: Option<BuildProgress>,
+    /// Set to stop the build before its next volume
+    build_cancel: Option<Arc<AtomicBool>>,
     saving_index: bool,
-    save_rx: Option<Receiver<Result<(), String>>>,
+    /// Generation of the index being saved
+    save_generation: u64,
+    save_rx: Option<Receiver<SaveMessage>>,
+    /// Latest progress of the save under way
+    save_progress: Option<SaveProgress>,
+    /// When the window was closed while the index was being saved
+    closing_since: Option<Instant>,
+    /// Window geometry or column widths changed since the settings were saved
+    pub layout_changed: bool,
+
+    /// Last time volume labels and free space were re-read
+    volumes_refreshed_at: Instant,
+    /// Indexed volumes missing from the last re-read, from the refresh thread
+    volume_refresh_rx: Option<Receiver<Vec<String>>>,
+    /// Indexed volumes that were offline at the last re-read
+    offline_volumes: Vec<String>,
+
+    /// Attached indexes being loaded, with the names of any that failed
+    attached_rx: Option<Receiver<(Federation, Vec<String>)>>,
 }
 
 impl GlintApp {
@@ -55,63 +319,338 @@ impl GlintApp {
 
         let config = Config::load().unwrap_or_default();
         let settings = Settings::load().unwrap_or_default();
+        glint_core::clock::set_zone(config.general.timezone);
+        glint_frontend::format::set_locale(Locale::from_config(&config.ui));
 
-        let available_volumes = detect_ntfs_volumes(&settings.indexed_volumes);
+        let available_volumes =
+            detect_ntfs_volumes(&settings.indexed_volumes, &settings.indexed_mount_folders);
 
         let data_dir = config.index_dir().unwrap_or_else(|_| {
             directories::ProjectDirs::from("org", "glint", "glint")
                 .map(|p| p.data_dir().to_path_buf())
                 .unwrap_or_else(|| std::path::PathBuf::from("."))
         });
-        let store = IndexStore::new(&data_dir);
-        // Start with empty index and load asynchronously so UI is instant
+        let store = IndexStore::new(&data_dir).with_read_only(config.general.read_only);
+        // Start with empty index and load asynchronously so UI is instant.
+        // The loader is guarded so a damaged index drops us into safe mode.
         let index = Arc::new(Index::new());
-        let (tx, rx) = unbounded::<Arc<Index>>();
-        let data_dir_clone = data_dir.clone();
-        std::thread::spawn(move || {
-            let s = IndexStore::new(&data_dir_clone);
-            let loaded = s.load_or_new();
-            let _ = tx.send(Arc::new(loaded));
-        });
+        let rx = safe_mode::spawn_load(data_dir.clone(), false);
         let status_message = "Loading index from disk...".to_string();
+        let mut toasts = Toasts::default();
+
+        let mut search = SearchState::new(Arc::clone(&index), cc.egui_ctx.clone());
+        search.typo_tolerance = config.general.typo_tolerance;
+        search.dedup_links = config.general.dedup_links;
+        search.hide_patterns = config.ui.hide_patterns.clone();
+        search.max_results = settings.result_limit;
+        search.debounce = Duration::from_millis(settings.search_debounce_ms);
+        search.min_query_len = settings.min_query_len.max(1);
+        search.scopes = config.scopes.clone();
+        search.remotes = config.server.remotes.clone();
+        search.remote_timeout = config.server.remote_timeout();
+        match PinStore::load(&PinStore::path_in(&data_dir)) {
+            Ok(pins) => search.set_pins(pins),
+            Err(e) => toasts.error(format!("Failed to load pins: {}", e)),
+        }
+        let attached_rx = spawn_attached_load(AttachedList::path_in(&data_dir));
 
         let service_status = service::get_service_status();
+        let task_status = service::get_task_status();
+        let keymap = Keymap::new(&settings.key_bindings);
+        let launcher = Launcher::new(&cc.egui_ctx, &settings.launcher_hotkey);
+        let busy_folders = busy_folders(&store, &config, &settings);
+        let index_watch = IndexWatch::new(&store);
 
         Self {
-            search: SearchState::new(Arc::clone(&index)),
+            search,
             index,
             store,
             config,
             settings,
             available_volumes,
             dark_mode: true,
+            system_preferences: SystemPreferences::detect(),
             show_settings: false,
             show_about: false,
+            show_syntax: false,
             show_index_builder: false,
+            show_rename: false,
+            rename: RenameDialog::default(),
+            show_stats: false,
+            show_history: false,
+            stats: StatsView::default(),
+            exclusions: ExclusionManager::default(),
+            browse: None,
+            folder_sizes: FolderSizes::new(cc.egui_ctx.clone()),
+            palette: CommandPalette::default(),
+            launcher,
+            keymap,
+            rebinding: None,
+            focus_search: false,
+            focus_filter: false,
             status_message,
+            toasts,
+            safe_mode: None,
+            busy_folders,
+            index_watch,
+            volume_loads: Vec::new(),
             service_status,
+            task_status,
             enable_service_on_index: true,
             loading_index: true,
+            disk_generation: None,
             load_started_at: Instant::now(),
+            load_progress_at: Instant::now(),
             load_rx: Some(rx),
             building_index: false,
             build_started_at: Instant::now(),
             build_rx: None,
+            build_cancel: None,
+            build_index: None,
+            build_progress: None,
             saving_index: false,
+            save_generation: 0,
             save_rx: None,
+            save_progress: None,
+            closing_since: None,
+            layout_changed: false,
+            volumes_refreshed_at: Instant::now(),
+            volume_refresh_rx: None,
+            offline_volumes: Vec::new(),
+            attached_rx: Some(attached_rx),
         }
     }
 
     pub fn reload_index(&mut self) {
-        self.index = Arc::new(self.store.load_or_new());
+        self.search.clear();
+        self.start_index_load(false);
+    }
+
+    /// Load the index file again if another program rewrote it. Changes
+    /// made here and not saved yet would be lost, so then the user is asked
+    /// first.
+    fn check_index_file(&mut self, ctx: &egui::Context) {
+        ctx.request_repaint_after(index_watch::CHECK_INTERVAL);
+        if self.saving_index || self.building_index || !self.index_watch.check(&self.store) {
+            return;
+        }
+        if self.disk_generation == Some(self.index.generation()) {
+            self.toasts
+                .info("The index was saved by another program; loading it");
+            self.index_watch.changed = false;
+            // Keep the results up; the query runs again on the new index
+            self.start_index_load(false);
+        } else {
+            self.toasts
+                .warning("The index was saved by another program");
+        }
+    }
+
+    /// Load the index another program saved, dropping unsaved changes.
+    pub fn reload_changed_index(&mut self) {
+        self.index_watch.changed = false;
+        self.start_index_load(false);
+    }
+
+    /// Keep the index in memory and save it over the other program's.
+    pub fn keep_own_index(&mut self) {
+        self.index_watch.set_known(self.store.stamp());
+        if self.store.is_read_only() {
+            return;
+        }
+        if !self.start_index_save() {
+            self.toasts.error("Failed to resolve save path");
+        }
+    }
+
+    /// Replace the index with the backup written by the previous save.
+    pub fn restore_backup(&mut self) {
+        if let Err(e) = self.store.ensure_writable("restore the index from backup") {
+            self.status_message = e.to_string();
+            return;
+        }
+        self.start_index_load(true);
+    }
+
+    /// Load the index file (or its backup) in the background.
+    fn start_index_load(&mut self, from_backup: bool) {
+        if self.loading_index {
+            return;
+        }
+        let data_dir = self
+            .store
+            .index_path()
+            .parent()
+            .map(|p| p.to_path_buf())
+            .unwrap_or_default();
+        self.load_rx = Some(safe_mode::spawn_load(data_dir, from_backup));
+        self.loading_index = true;
+        self.load_started_at = Instant::now();
+        self.load_progress_at = Instant::now();
+        self.volume_loads.clear();
+        self.status_message = if from_backup {
+            "Restoring index from backup...".to_string()
+        } else {
+            "Loading index from disk...".to_string()
+        };
+    }
+
+    /// Open the diagnostics report written when entering safe mode.
+    pub fn open_diagnostics_report(&mut self) {
+        let Some(path) = self.safe_mode.as_ref().and_then(|s| s.report_path.clone()) else {
+            self.status_message = "No diagnostics report was written".to_string();
+            return;
+        };
+        if let Err(e) = glint_frontend::actions::open(&path) {
+            self.status_message = format!("Failed to open {}: {}", path.display(), e);
+        }
+    }
+
+    fn finish_index_load(&mut self, loaded: LoadedIndex) {
+        self.index = loaded.index;
+        self.apply_index_config();
+        self.disk_generation = Some(self.index.generation());
+        self.index_watch.set_known(loaded.stamp);
+        self.stats.reset();
         self.search.set_index(Arc::clone(&self.index));
+        self.refresh_pins();
+        // Zero-copy archived view (if v3 exists)
+        if let Some(view) = loaded.archived {
+            self.search.set_archived_view(view);
+        }
+        self.safe_mode = None;
         let count = self.index.len();
-        self.status_message = format!("Index reloaded: {} files", format_number(count));
-        self.search.clear();
+        self.status_message = if count > 0 {
+            format!("{} files indexed", format_number(count))
+        } else if loaded.fresh {
+            "No index found. Click 'Build Index' to get started.".to_string()
+        } else {
+            "Index is empty. Click 'Build Index' to get started.".to_string()
+        };
+        self.show_index_builder = count == 0;
+
+        for state in self.index.volume_states() {
+            if state.needs_rescan {
+                self.toasts.warning(format!(
+                    "{} needs a rescan (its change journal was reset); rebuild the index to catch up",
+                    state.info.display_name()
+                ));
+            }
+        }
+    }
+
+    fn poll_index_load(&mut self, ctx: &egui::Context) {
+        let Some(rx) = self.load_rx.clone() else {
+            return;
+        };
+        let finished = loop {
+            match rx.try_recv() {
+                Ok(LoadMessage::Started(index, volumes)) => {
+                    // Search the partially loaded index while the rest fills in
+                    self.index = index;
+                    self.apply_index_config();
+                    self.disk_generation = None;
+                    self.stats.reset();
+                    self.search.set_index(Arc::clone(&self.index));
+                    self.volume_loads = volumes
+                        .into_iter()
+                        .map(|v| VolumeLoadStatus {
+                            id: v.id,
+                            mount_point: v.mount_point,
+                            records: v.records,
+                            ready: false,
+                        })
+                        .collect();
+                    self.load_progress_at = Instant::now();
+                }
+                Ok(LoadMessage::VolumeReady(id)) => {
+                    self.apply_index_config();
+                    if let Some(volume) = self.volume_loads.iter_mut().find(|v| v.id == id) {
+                        volume.ready = true;
+                    }
+                    self.load_progress_at = Instant::now();
+                }
+                Ok(LoadMessage::Finished(result)) => break Some(result),
+                Err(TryRecvError::Disconnected) => {
+                    break Some(Err("The index loader stopped unexpectedly".to_string()))
+                }
+                Err(TryRecvError::Empty) => {
+                    if self.load_progress_at.elapsed() >= safe_mode::LOAD_TIMEOUT {
+                        break Some(Err(format!(
+                            "Loading the index made no progress for {} seconds",
+                            safe_mode::LOAD_TIMEOUT.as_secs()
+                        )));
+                    }
+                    break None;
+                }
+            }
+        };
+
+        let Some(result) = finished else {
+            let secs = self.load_started_at.elapsed().as_secs_f32();
+            let ready = self.volume_loads.iter().filter(|v| v.ready).count();
+            self.status_message = if self.volume_loads.is_empty() {
+                format!("Loading index... {:.1}s", secs)
+            } else {
+                format!(
+                    "Loading index... {}/{} volumes, {:.1}s",
+                    ready,
+                    self.volume_loads.len(),
+                    secs
+                )
+            };
+            ctx.request_repaint_after(Duration::from_millis(150));
+            return;
+        };
+
+        // A stalled loader is abandoned along with its receiver
+        self.loading_index = false;
+        self.load_rx = None;
+        let partial = !self.volume_loads.is_empty();
+        self.volume_loads.clear();
+        match result {
+            Ok(loaded) => self.finish_index_load(loaded),
+            Err(reason) => {
+                if partial {
+                    // Drop the volumes that did load rather than show half an index
+                    self.index = Arc::new(Index::new());
+                    self.stats.reset();
+                    self.search.set_index(Arc::clone(&self.index));
+                }
+                self.enter_safe_mode(reason);
+            }
+        }
+    }
+
+    fn enter_safe_mode(&mut self, reason: String) {
+        // Keep whatever index we had; at startup that is the empty one
+        self.index_watch.set_known(self.store.stamp());
+        self.status_message = "Safe mode: the index could not be loaded".to_string();
+        self.safe_mode = Some(SafeMode::new(&self.store, reason));
     }
 
     pub fn refresh_service_status(&mut self) {
         self.service_status = service::get_service_status();
+        self.task_status = service::get_task_status();
+    }
+
+    /// Install or remove the scheduled task (`glint watch --once`).
+    pub fn set_scheduled_task(&mut self, trigger: Option<TaskTrigger>) {
+        let result = match trigger {
+            Some(trigger) => service::install_task(trigger),
+            None => service::uninstall_task(),
+        };
+        match result {
+            Ok(()) => {
+                self.task_status = service::get_task_status();
+                self.toasts
+                    .success(format!("Scheduled task is now {}", self.task_status));
+            }
+            Err(e) => {
+                self.toasts
+                    .error(format!("Scheduled task update failed: {}", e));
+            }
+        }
     }
 
     pub fn toggle_service(&mut self) {
@@ -124,7 +663,8 @@ impl GlintApp {
             };
 
             if let Err(e) = service::request_elevation_for_service(operation) {
-                self.status_message = format!("Failed to request elevation: {}", e);
+                self.toasts
+                    .error(format!("Failed to request elevation: {}", e));
             } else {
                 self.status_message = "Requesting administrator privileges...".to_string();
             }
@@ -132,30 +672,35 @@ impl GlintApp {
             match service::toggle_service() {
                 Ok(new_status) => {
                     self.service_status = new_status;
-                    self.status_message = format!("Service is now {}", new_status);
+                    self.toasts
+                        .success(format!("Service is now {}", new_status));
                 }
                 Err(e) => {
-                    self.status_message = format!("Service toggle failed: {}", e);
+                    self.toasts.error(format!("Service toggle failed: {}", e));
                 }
             }
         }
     }
 
-    /// Index selected volumes (Windows NTFS)
-    pub fn index_volumes(&mut self) {
-        let volumes: Vec<char> = self
-            .available_volumes
+    /// Mount points of the volumes selected for indexing.
+    pub fn selected_mount_points(&self) -> Vec<String> {
+        self.available_volumes
             .iter()
             .filter(|v| v.selected)
-            .map(|v| v.letter)
-            .collect();
+            .map(|v| v.mount_point.clone())
+            .collect()
+    }
+
+    /// Index selected volumes (Windows NTFS)
+    pub fn index_volumes(&mut self) {
+        let volumes = self.selected_mount_points();
 
         if volumes.is_empty() {
             self.status_message = "No volumes selected".to_string();
             return;
         }
 
-        self.status_message = format!("Indexing volumes: {:?}...", volumes);
+        self.status_message = format!("Indexing volumes: {}...", volumes.join(", "));
 
         #[cfg(windows)]
         {
@@ -164,31 +709,24 @@ impl GlintApp {
 
             let backend = NtfsBackend::new();
             let new_index = Index::new();
+            new_index.set_exclusions(Exclusions::new(&self.config.exclude));
             let mut total_records = 0usize;
 
             match backend.list_volumes() {
                 Ok(all_volumes) => {
                     for volume in all_volumes {
-                        let mount_letter = volume
-                            .mount_point
-                            .chars()
-                            .next()
-                            .map(|c| c.to_ascii_uppercase());
-
-                        if let Some(letter) = mount_letter {
-                            if volumes.contains(&letter) {
-                                match backend.full_scan(&volume, None) {
-                                    Ok(records) => {
-                                        total_records += records.len();
-                                        new_index.add_volume_records(&volume, records);
-                                    }
-                                    Err(e) => {
-                                        tracing::warn!(
-                                            volume = %volume.mount_point,
-                                            error = %e,
-                                            "Failed to scan volume"
-                                        );
-                                    }
+                        if is_selected_mount(&volume.mount_point, &volumes) {
+                            match backend.full_scan(&volume, None) {
+                                Ok(records) => {
+                                    total_records += records.len();
+                                    new_index.add_volume_records(&volume, records);
+                                }
+                                Err(e) => {
+                                    tracing::warn!(
+                                        volume = %volume.mount_point,
+                                        error = %e,
+                                        "Failed to scan volume"
+                                    );
                                 }
                             }
                         }
@@ -201,8 +739,11 @@ impl GlintApp {
             }
 
             self.index = Arc::new(new_index);
+            self.apply_index_config();
             self.search.set_index(Arc::clone(&self.index));
-            if let Err(e) = self.store.save(&self.index) {
+            let saved = self.store.save(&self.index);
+            self.index_watch.set_known(self.store.stamp());
+            if let Err(e) = saved {
                 self.status_message = format!(
                     "Indexed {} files but failed to save: {}",
                     format_number(total_records),
@@ -225,57 +766,27 @@ impl eframe::App for GlintApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Poll async search results first
         self.search.poll_results();
+        self.search.poll_index_changes();
+        self.poll_attached();
 
         // Poll async index loader and update status bar with progress
         if self.loading_index {
-            if let Some(rx) = &self.load_rx {
-                match rx.try_recv() {
-                    Ok(new_index) => {
-                        self.index = new_index;
-                        self.search.set_index(Arc::clone(&self.index));
-                        // Try to open zero-copy archived view (if v3 exists)
-                        if let Ok(view) = ArchivedView::open(self.store.index_path()) {
-                            self.search.set_archived_view(Arc::new(view));
-                        }
-                        let count = self.index.len();
-                        self.status_message = if count > 0 {
-                            format!("{} files indexed", format_number(count))
-                        } else {
-                            "No index found. Click 'Build Index' to get started.".to_string()
-                        };
-                        self.show_index_builder = count == 0;
-                        self.loading_index = false;
-                        self.load_rx = None;
-                    }
-                    Err(_) => {
-                        let secs = self.load_started_at.elapsed().as_secs_f32();
-                        self.status_message = format!("Loading index... {:.1}s", secs);
-                        ctx.request_repaint_after(Duration::from_millis(150));
-                    }
-                }
-            }
-        }
-        if self.dark_mode {
-            ctx.set_visuals(egui::Visuals::dark());
+            self.poll_index_load(ctx);
         } else {
-            ctx.set_visuals(egui::Visuals::light());
-        }
-
-        handle_shortcuts(ctx, self);
-
-        ui::menu_bar(ctx, self);
-        ui::top_panel(ctx, self);
-        ui::bottom_panel(ctx, self);
-        ui::central_panel(ctx, self);
-
-        if self.show_settings {
-            ui::settings_window(ctx, self);
+            self.refresh_volume_info();
+            self.check_index_file(ctx);
         }
-        if self.show_about {
-            ui::about_window(ctx, self);
+        ctx.set_visuals(theme::visuals(self.dark_mode, self.high_contrast()));
+        let reduced_motion = self.reduced_motion();
+        if (ctx.style().animation_time == 0.0) != reduced_motion {
+            ctx.style_mut(|style| theme::set_reduced_motion(style, reduced_motion));
         }
-        if self.show_index_builder {
-            ui::index_builder_window(ctx, self);
+
+        launcher::poll_hotkey(ctx, self);
+        if self.launcher.active {
+            launcher::panel(ctx, self);
+        } else {
+            self.full_window(ctx);
         }
 
         // Poll async index build
@@ -283,35 +794,59 @@ impl eframe::App for GlintApp {
             if let Some(rx) = &self.build_rx {
                 match rx.try_recv() {
                     Ok(Ok(new_index)) => {
+                        self.build_index = None;
+                        let mut manifest = BuildManifest::new(
+                            &new_index,
+                            &self.config,
+                            self.build_started_at.elapsed(),
+                        );
+                        if let Some(mut progress) = self.build_progress.take() {
+                            progress.poll();
+                            progress.fill_manifest(&mut manifest);
+                        }
+                        if let Err(e) = self.store.save_manifest(&manifest) {
+                            tracing::warn!(error = %e, "Cannot save index manifest");
+                        }
                         self.index = new_index;
+                        self.apply_index_config();
+                        self.disk_generation = None;
+                        self.stats.reset();
                         self.search.set_index(Arc::clone(&self.index));
+                        self.refresh_pins();
                         let count = self.index.len();
                         self.status_message = format!("Indexed {} files. Saving...", format_number(count));
                         self.building_index = false;
 
-                        // Save asynchronously
-                        let index_for_save = Arc::clone(&self.index);
-                        let base_dir = self.store.index_path().parent().map(|p| p.to_path_buf());
-                        if let Some(dir) = base_dir {
-                            let (stx, srx) = unbounded::<Result<(), String>>();
-                            self.save_rx = Some(srx);
-                            self.saving_index = true;
-                            std::thread::spawn(move || {
-                                let store = IndexStore::new(&dir);
-                                let res = store.save(&index_for_save).map_err(|e| e.to_string());
-                                let _ = stx.send(res);
-                            });
-                        } else {
-                            self.status_message = "Indexed, but failed to resolve save path".to_string();
+                        if !self.start_index_save() {
+                            self.status_message = format!("{} files indexed", format_number(count));
+                            self.toasts
+                                .error("Indexed, but failed to resolve save path");
                         }
                     }
                     Ok(Err(msg)) => {
-                        self.status_message = msg;
+                        self.status_message = "Index build failed".to_string();
+                        self.toasts.error(msg);
                         self.building_index = false;
+                        self.build_index = None;
+                        self.build_progress = None;
                     }
                     Err(_) => {
                         let secs = self.build_started_at.elapsed().as_secs_f32();
-                        self.status_message = format!("Indexing... {:.1}s", secs);
+                        let status = self.build_progress.as_mut().and_then(|progress| {
+                            progress.poll();
+                            progress.status()
+                        });
+                        self.status_message = status.unwrap_or_else(|| {
+                            let files = self
+                                .build_index
+                                .as_ref()
+                                .map_or(0, |i| i.stats().total_files);
+                            format!(
+                                "Indexing... {} files so far ({:.1}s)",
+                                format_number(files as usize),
+                                secs
+                            )
+                        });
                         ctx.request_repaint_after(Duration::from_millis(150));
                     }
                 }
@@ -321,87 +856,869 @@ impl eframe::App for GlintApp {
         // Poll async save
         if self.saving_index {
             if let Some(rx) = &self.save_rx {
-                match rx.try_recv() {
-                    Ok(Ok(())) => {
-                        self.status_message = "Index saved".to_string();
+                let mut finished = None;
+                while let Ok(message) = rx.try_recv() {
+                    match message {
+                        SaveMessage::Progress(progress) => self.save_progress = Some(progress),
+                        SaveMessage::Finished(result) => finished = Some(result),
+                    }
+                }
+                if finished.is_some() {
+                    self.index_watch.set_known(self.store.stamp());
+                }
+                match finished {
+                    Some(Ok(())) => {
+                        self.disk_generation = Some(self.save_generation);
+                        self.status_message =
+                            format!("{} files indexed", format_number(self.index.len()));
+                        self.toasts.success("Index saved");
                         self.saving_index = false;
                         self.save_rx = None;
+                        self.save_progress = None;
                     }
-                    Ok(Err(msg)) => {
-                        self.status_message = format!("Save failed: {}", msg);
+                    Some(Err(msg)) => {
+                        self.status_message =
+                            format!("{} files indexed", format_number(self.index.len()));
+                        self.toasts.error(format!("Save failed: {}", msg));
                         self.saving_index = false;
                         self.save_rx = None;
+                        self.save_progress = None;
                     }
-                    Err(_) => {
+                    None => {
                         // throttle repaint
                         ctx.request_repaint_after(Duration::from_millis(150));
                     }
                 }
             }
         }
+
+        if !self.launcher.active {
+            self.track_window(ctx);
+        }
+        self.handle_close(ctx);
+        ui::toasts(ctx, self);
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if self.layout_changed {
+            if let Err(e) = self.settings.save() {
+                tracing::warn!("Failed to save window layout: {}", e);
+            }
+        }
     }
 }
 
 impl GlintApp {
+    /// The full window: menus, search bar, results and dialogs.
+    fn full_window(&mut self, ctx: &egui::Context) {
+        handle_shortcuts(ctx, self);
+
+        ui::menu_bar(ctx, self);
+        ui::top_panel(ctx, self);
+        ui::safe_mode_banner(ctx, self);
+        ui::index_changed_banner(ctx, self);
+        ui::busy_folders_banner(ctx, self);
+        ui::bottom_panel(ctx, self);
+        ui::central_panel(ctx, self);
+
+        if self.show_settings {
+            ui::settings_window(ctx, self);
+        }
+        if self.show_about {
+            ui::about_window(ctx, self);
+        }
+        if self.show_syntax {
+            ui::syntax_window(ctx, self);
+        }
+        if self.show_index_builder {
+            ui::index_builder_window(ctx, self);
+        }
+        if self.show_rename {
+            ui::rename_window(ctx, self);
+        }
+        if self.show_stats {
+            self.poll_stats(ctx);
+            ui::stats_window(ctx, self);
+        }
+        if self.show_history {
+            ui::history_window(ctx, self);
+        }
+        if self.exclusions.open {
+            ui::exclusions_window(ctx, self);
+        }
+        if self.palette.open {
+            ui::command_palette(ctx, self);
+        }
+    }
+
+    /// Remember the window's size and position for the next launch. While
+    /// maximized, the size to restore to is kept.
+    fn track_window(&mut self, ctx: &egui::Context) {
+        let viewport = ctx.input(|i| i.viewport().clone());
+        let Some(window) = WindowGeometry::of_viewport(&viewport, self.settings.window) else {
+            return;
+        };
+        if self.settings.window != Some(window) {
+            self.settings.window = Some(window);
+            self.layout_changed = true;
+        }
+    }
+
+    /// Whether the index in memory has changes the file on disk lacks and
+    /// may be written over it. Not while another program's save waits for
+    /// the user to choose between the two.
+    fn has_unsaved_changes(&self) -> bool {
+        !self.loading_index
+            && !self.building_index
+            && !self.index_watch.changed
+            && !self.store.is_read_only()
+            && !self.index.is_empty()
+            && self.disk_generation != Some(self.index.generation())
+    }
+
+    /// Save unsaved changes on close and keep the window open while the
+    /// index is being saved, up to [`CLOSE_SAVE_TIMEOUT`]. A build is
+    /// stopped before its next volume.
+    fn handle_close(&mut self, ctx: &egui::Context) {
+        let close_requested = ctx.input(|i| i.viewport().close_requested());
+        if close_requested && self.closing_since.is_none() {
+            if let Some(cancel) = self.build_cancel.as_ref().filter(|_| self.building_index) {
+                cancel.store(true, Ordering::Relaxed);
+                tracing::warn!("Closed during an index build; keeping the index on disk");
+            }
+            if !self.saving_index && self.has_unsaved_changes() && !self.start_index_save() {
+                tracing::warn!("Cannot resolve the index save path; closing without saving");
+            }
+            if self.saving_index {
+                self.closing_since = Some(Instant::now());
+                self.status_message = "Saving index before closing...".to_string();
+            }
+        }
+
+        let Some(since) = self.closing_since else {
+            return;
+        };
+        if self.saving_index && since.elapsed() < CLOSE_SAVE_TIMEOUT {
+            if close_requested {
+                ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            }
+            ctx.request_repaint_after(Duration::from_millis(150));
+        } else if !close_requested {
+            if self.saving_index {
+                tracing::warn!("Index save did not finish in time; closing anyway");
+            }
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+    }
+
+    /// Save the index on a background thread.
+    ///
+    /// Returns false if the save location could not be determined.
+    pub fn start_index_save(&mut self) -> bool {
+        let index_for_save = Arc::clone(&self.index);
+        let Some(dir) = self.store.index_path().parent().map(|p| p.to_path_buf()) else {
+            return false;
+        };
+        let read_only = self.store.is_read_only();
+
+        let (stx, srx) = unbounded();
+        self.save_rx = Some(srx);
+        self.save_progress = None;
+        self.saving_index = true;
+        self.save_generation = self.index.generation();
+        std::thread::spawn(move || {
+            let store = IndexStore::new(&dir).with_read_only(read_only);
+            let res = store
+                .save_with_progress(&index_for_save, |progress| {
+                    let _ = stx.send(SaveMessage::Progress(progress));
+                })
+                .map_err(|e| e.to_string());
+            let _ = stx.send(SaveMessage::Finished(res));
+        });
+        true
+    }
+
+    /// Check if the statistics are missing or were computed for an older index state.
+    pub fn stats_stale(&self) -> bool {
+        self.stats.stats.is_none() || self.stats.generation != self.index.generation()
+    }
+
+    /// Aggregate statistics on a background thread.
+    ///
+    /// With `use_cache`, statistics cached by `glint stats` (or a previous
+    /// run) are used while the in-memory index matches the file on disk.
+    pub fn start_stats_job(&mut self, use_cache: bool) {
+        if self.stats.is_computing() {
+            return;
+        }
+
+        let index = Arc::clone(&self.index);
+        let index_file = self.store.index_path();
+        let cache_path = index_file.parent().map(Statistics::cache_path_in);
+        let from_disk = self.disk_generation == Some(self.index.generation());
+        let read_only = self.store.is_read_only();
+
+        let (tx, rx) = unbounded::<(u64, Statistics)>();
+        self.stats.rx = Some(rx);
+        std::thread::spawn(move || {
+            let generation = index.generation();
+            let cached = cache_path
+                .as_deref()
+                .filter(|_| from_disk && use_cache)
+                .and_then(|path| Statistics::load_cached(path, &index_file));
+            let stats = cached.unwrap_or_else(|| {
+                let stats = Statistics::compute(&index);
+                if let (true, false, Some(path)) = (from_disk, read_only, cache_path.as_deref()) {
+                    if let Err(e) = stats.save_cached(path, &index_file) {
+                        tracing::warn!(error = %e, "Failed to cache statistics");
+                    }
+                }
+                stats
+            });
+            let _ = tx.send((generation, stats));
+        });
+    }
+
+    fn poll_stats(&mut self, ctx: &egui::Context) {
+        let Some(rx) = &self.stats.rx else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok((generation, stats)) => {
+                self.stats.stats = Some(Arc::new(stats));
+                self.stats.generation = generation;
+                self.stats.rx = None;
+            }
+            Err(crossbeam_channel::TryRecvError::Empty) => {
+                ctx.request_repaint_after(Duration::from_millis(150));
+            }
+            Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                self.stats.rx = None;
+            }
+        }
+    }
+
+    fn pins_path(&self) -> Option<std::path::PathBuf> {
+        self.store.index_path().parent().map(PinStore::path_in)
+    }
+
+    /// Pin or unpin a result row and save the pin list.
+    pub fn toggle_pin(&mut self, row: usize) {
+        let Some(result) = self.search.results.get(row) else {
+            return;
+        };
+        // Results from the archived view carry no real file identity, and
+        // pins only cover the local index
+        if result.record.path.is_empty() || result.source.is_some() {
+            self.status_message = "This item cannot be pinned".to_string();
+            return;
+        }
+
+        let mut pins = self.search.pins().clone();
+        let pinned = pins.toggle(&result.record);
+        let name = result.record.name.clone();
+        self.status_message = if pinned {
+            format!("Pinned {}", name)
+        } else {
+            format!("Unpinned {}", name)
+        };
+        self.save_pins(pins);
+    }
+
+    /// Include or leave out an indexed volume in searches, keeping its records.
+    pub fn set_volume_enabled(&mut self, volume: &glint_core::VolumeInfo, enabled: bool) {
+        self.index.set_volume_enabled(&volume.id, enabled);
+        self.search.mark_dirty();
+        let name = volume.display_name();
+        if self.config.set_volume_disabled(name, !enabled) {
+            if let Err(e) = self.config.save() {
+                self.toasts.error(format!("Failed to save config: {}", e));
+                return;
+            }
+        }
+        self.status_message = if enabled {
+            format!("{} enabled", name)
+        } else {
+            format!(
+                "{} disabled; its files stay indexed but are hidden from searches",
+                name
+            )
+        };
+    }
+
+    /// Exclude a busy folder from future builds, by enabling the preset
+    /// that covers it or else by its path.
+    pub fn exclude_busy_folder(&mut self, folder: &ChurnFolder) {
+        let exclude = &mut self.config.exclude;
+        let added = match folder.preset() {
+            Some(preset) => {
+                exclude.presets.push(preset.to_string());
+                format!("Enabled the {} preset", preset)
+            }
+            None => {
+                exclude.paths.push(folder.path.clone());
+                format!("Excluded {}", folder.path)
+            }
+        };
+        if let Err(e) = self.config.save() {
+            self.toasts.error(format!("Failed to save config: {}", e));
+            return;
+        }
+        self.apply_index_config();
+        self.busy_folders = busy_folders(&self.store, &self.config, &self.settings);
+        self.status_message = format!("{}. Re-index to apply.", added);
+    }
+
+    /// Add an exclusion rule, leaving its files out of changes from now on
+    /// and of the next re-index.
+    pub fn add_exclusion(&mut self, rule: ExcludeRule) {
+        if !self.config.exclude.add_rule(rule.clone()) {
+            self.status_message = format!("The {} is already excluded", rule);
+            return;
+        }
+        if let Err(e) = self.config.save() {
+            self.toasts.error(format!("Failed to save config: {}", e));
+            return;
+        }
+        self.apply_index_config();
+        self.status_message = format!("Excluded the {}", rule);
+    }
+
+    /// Remove an exclusion rule. Its files come back with the next
+    /// re-index.
+    pub fn remove_exclusion(&mut self, rule: &ExcludeRule) {
+        if !self.config.exclude.remove_rule(rule) {
+            return;
+        }
+        if let Err(e) = self.config.save() {
+            self.toasts.error(format!("Failed to save config: {}", e));
+            return;
+        }
+        self.apply_index_config();
+        self.status_message = format!("No longer excluding the {}. Re-index to apply.", rule);
+    }
+
+    /// Take the records of `rule`, or of all rules, out of the index now
+    /// rather than at the next re-index, and save it.
+    pub fn remove_excluded_records(&mut self, rule: Option<&ExcludeRule>) {
+        if let Err(e) = self.store.ensure_writable("remove excluded files") {
+            self.status_message = e.to_string();
+            return;
+        }
+        let exclusions = match rule {
+            Some(rule) => rule.exclusions(),
+            None => Exclusions::new(&self.config.exclude),
+        };
+        let removed = self.index.remove_excluded(&exclusions);
+        self.status_message = format!(
+            "Removed {} excluded files from the index",
+            format_number(removed)
+        );
+        if removed > 0 {
+            self.stats.reset();
+            self.start_index_save();
+            self.search.mark_dirty();
+        }
+    }
+
+    /// Stop suggesting exclusions for the current busy folders.
+    pub fn dismiss_busy_folders(&mut self) {
+        self.settings
+            .dismissed_busy_folders
+            .extend(self.busy_folders.drain(..).map(|f| f.path));
+        if let Err(e) = self.settings.save() {
+            self.toasts.error(format!("Failed to save settings: {}", e));
+        }
+    }
+
+    /// Apply the path cache setting and exclusions, and leave volumes disabled
+    /// in the config out of searches.
+    fn apply_index_config(&self) {
+        glint_frontend::apply_config(&self.index, &self.config);
+    }
+
+    /// Re-read volume labels and free space in the background every so often,
+    /// and say when an indexed volume goes offline or comes back.
+    fn refresh_volume_info(&mut self) {
+        if let Some(offline) = self
+            .volume_refresh_rx
+            .as_ref()
+            .and_then(|rx| rx.try_recv().ok())
+        {
+            self.volume_refresh_rx = None;
+            for name in offline.iter().filter(|n| !self.offline_volumes.contains(n)) {
+                self.toasts.warning(format!(
+                    "{} is offline; its files stay searchable but may be out of date",
+                    name
+                ));
+            }
+            for name in self.offline_volumes.iter().filter(|n| !offline.contains(n)) {
+                self.toasts.info(format!("{} is back online", name));
+            }
+            self.offline_volumes = offline;
+        }
+
+        if self.volumes_refreshed_at.elapsed() < VOLUME_REFRESH_INTERVAL {
+            return;
+        }
+        self.volumes_refreshed_at = Instant::now();
+
+        #[cfg(windows)]
+        {
+            use glint_backend_ntfs::NtfsBackend;
+            use glint_core::backend::FileSystemBackend;
+            use glint_core::VolumeAvailability;
+
+            let (tx, rx) = unbounded();
+            self.volume_refresh_rx = Some(rx);
+            let index = Arc::clone(&self.index);
+            std::thread::spawn(move || {
+                if let Ok(current) = NtfsBackend::new().list_volumes() {
+                    index.refresh_volume_info(&current);
+                    index.update_availability(&current);
+                    let offline = index
+                        .volume_states()
+                        .into_iter()
+                        .filter(|state| state.availability == VolumeAvailability::Offline)
+                        .map(|state| state.info.display_name().to_string())
+                        .collect();
+                    let _ = tx.send(offline);
+                }
+            });
+        }
+    }
+
+    /// Update pinned paths after renames and moves picked up by the index.
+    fn refresh_pins(&mut self) {
+        let mut pins = self.search.pins().clone();
+        if pins.refresh(&self.index) {
+            self.save_pins(pins);
+        }
+    }
+
+    fn save_pins(&mut self, pins: PinStore) {
+        if let Some(path) = self.pins_path() {
+            if let Err(e) = pins.save(&path) {
+                self.toasts.error(format!("Failed to save pins: {}", e));
+            }
+        }
+        self.search.set_pins(pins);
+    }
+
+    fn attached_path(&self) -> Option<std::path::PathBuf> {
+        self.store.index_path().parent().map(AttachedList::path_in)
+    }
+
+    /// Pick an index file and search it alongside the local index.
+    pub fn attach_index(&mut self) {
+        let Some(list_path) = self.attached_path() else {
+            return;
+        };
+        let Some(file) = rfd::FileDialog::new()
+            .set_title("Select an index to attach")
+            .add_filter("Glint index", &["idx"])
+            .pick_file()
+        else {
+            return;
+        };
+
+        let attached = AttachedList::load(&list_path).and_then(|mut list| {
+            let dir = glint_core::federation::index_dir(&file)?;
+            let name = dir
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| format!("index {}", list.len() + 1));
+            list.attach(&name, &dir)?;
+            list.save(&list_path)?;
+            Ok(name)
+        });
+        match attached {
+            Ok(name) => {
+                self.status_message = format!("Attaching {}...", name);
+                self.attached_rx = Some(spawn_attached_load(list_path));
+            }
+            Err(e) => self.toasts.error(format!("Failed to attach index: {}", e)),
+        }
+    }
+
+    /// Stop searching all attached indexes.
+    pub fn detach_indexes(&mut self) {
+        let Some(list_path) = self.attached_path() else {
+            return;
+        };
+        if let Err(e) = AttachedList::new().save(&list_path) {
+            self.toasts
+                .error(format!("Failed to detach indexes: {}", e));
+            return;
+        }
+        self.attached_rx = None;
+        self.search.set_attached(Federation::new());
+        self.status_message = "Detached all indexes".to_string();
+    }
+
+    /// Pick up attached indexes once they have loaded.
+    fn poll_attached(&mut self) {
+        let Some(Ok((attached, failed))) = self.attached_rx.as_ref().map(|rx| rx.try_recv()) else {
+            return;
+        };
+        self.attached_rx = None;
+        for name in failed {
+            self.toasts
+                .warning(format!("Attached index '{}' could not be loaded", name));
+        }
+        if !attached.is_empty() {
+            let names: Vec<&str> = attached.sources().iter().map(|s| &*s.name).collect();
+            self.status_message = format!("Also searching {}", names.join(", "));
+        }
+        self.search.set_attached(attached);
+    }
+
+    fn rename_journal_path(&self) -> Option<std::path::PathBuf> {
+        self.store.index_path().parent().map(RenameJournal::path_in)
+    }
+
+    /// Open the bulk rename dialog for the current results.
+    pub fn open_rename_dialog(&mut self) {
+        self.show_rename = true;
+        self.rename.dirty = true;
+    }
+
+    /// Rebuild the rename preview from the dialog inputs and current results.
+    pub fn refresh_rename_preview(&mut self) {
+        self.rename.dirty = false;
+        self.rename.plan = None;
+        self.rename.error = None;
+
+        let options = RenameTemplate::parse(&self.rename.template)
+            .map(|t| RenameOptions::new(t).with_start(self.rename.start))
+            .and_then(|o| {
+                if self.rename.pattern.is_empty() {
+                    Ok(o)
+                } else {
+                    o.with_pattern(&self.rename.pattern)
+                }
+            });
+
+        let records: Vec<_> = self.search.results.iter().map(|r| r.record.clone()).collect();
+        match options.and_then(|o| RenamePlan::build(&records, &o)) {
+            Ok(plan) => self.rename.plan = Some(plan),
+            Err(e) => self.rename.error = Some(e.to_string()),
+        }
+    }
+
+    /// Perform the previewed renames and record them for undo.
+    pub fn execute_rename(&mut self) {
+        if let Err(e) = self.store.ensure_writable("rename files") {
+            self.status_message = e.to_string();
+            return;
+        }
+        let Some(plan) = self.rename.plan.take() else {
+            return;
+        };
+
+        let journal = plan.execute();
+        self.finish_rename(&journal);
+        if !journal.entries.is_empty() {
+            self.search
+                .history
+                .record(HistoryAction::Renamed(journal.entries.len()));
+        }
+
+        if let Some(path) = self.rename_journal_path() {
+            if let Err(e) = journal.save(&path) {
+                self.status_message = format!("Renamed, but failed to save undo journal: {}", e);
+                return;
+            }
+        }
+
+        self.status_message = if journal.failures.is_empty() {
+            format!("Renamed {} files", format_number(journal.entries.len()))
+        } else {
+            format!(
+                "Renamed {} files, {} failed",
+                format_number(journal.entries.len()),
+                journal.failures.len()
+            )
+        };
+    }
+
+    /// Revert the last bulk rename.
+    pub fn undo_rename(&mut self) {
+        if let Err(e) = self.store.ensure_writable("undo a rename") {
+            self.status_message = e.to_string();
+            return;
+        }
+        let Some(path) = self.rename_journal_path() else {
+            return;
+        };
+
+        let journal = match RenameJournal::load(&path) {
+            Ok(Some(journal)) if !journal.entries.is_empty() => journal,
+            Ok(_) => {
+                self.status_message = "Nothing to undo".to_string();
+                return;
+            }
+            Err(e) => {
+                self.status_message = format!("Failed to read undo journal: {}", e);
+                return;
+            }
+        };
+
+        let undone = journal.undo();
+        self.finish_rename(&undone);
+        let _ = std::fs::remove_file(&path);
+        if let Some(i) = self.search.history.last_rename() {
+            self.search.history.mark_undone(i);
+        }
+
+        self.status_message = format!("Restored {} files", format_number(undone.entries.len()));
+    }
+
+    /// Send a result to the Recycle Bin and drop it from the index.
+    #[cfg(windows)]
+    pub fn recycle_result(&mut self, row: usize) {
+        if let Err(e) = self.store.ensure_writable("recycle files") {
+            self.status_message = e.to_string();
+            return;
+        }
+        let Some(record) = self.search.results.get(row).map(|r| r.record.clone()) else {
+            return;
+        };
+        let path = std::path::Path::new(&record.path);
+        if let Err(e) = glint_backend_ntfs::recycle_bin::send(path) {
+            self.status_message = format!("Failed to recycle {}: {}", record.path, e);
+            return;
+        }
+        self.index.apply_change(ChangeEvent::deleted(
+            record.volume_id.clone(),
+            record.id,
+            record.parent_id,
+            record.name.clone(),
+            record.is_dir,
+            0,
+        ));
+        self.status_message = format!("Moved {} to the Recycle Bin", record.name);
+        self.search.history.record(HistoryAction::Recycled(record));
+        self.start_index_save();
+        self.search.mark_dirty();
+    }
+
+    /// Copy or move the results an action on `row` applies to into a
+    /// folder the user picks. Moves within a volume show in the index right
+    /// away; copies and moves to another volume appear once its watcher
+    /// sees them.
+    #[cfg(windows)]
+    pub fn transfer_results(&mut self, row: usize, mode: crate::shell::Transfer) {
+        use crate::shell::Transfer;
+
+        let records = self.search.targets(row);
+        let verb = match mode {
+            Transfer::Copy => "Copy",
+            Transfer::Move => "Move",
+        };
+        if mode == Transfer::Move {
+            if let Err(e) = self.store.ensure_writable("move files") {
+                self.status_message = e.to_string();
+                return;
+            }
+        }
+        let Some(folder) = rfd::FileDialog::new()
+            .set_title(format!("{} {} items to", verb, records.len()))
+            .pick_folder()
+        else {
+            return;
+        };
+        let destination = folder.to_string_lossy().into_owned();
+        let paths: Vec<&str> = records.iter().map(|r| r.path.as_str()).collect();
+
+        let complete = match crate::shell::transfer(&paths, &destination, mode) {
+            Ok(complete) => complete,
+            Err(e) => {
+                self.status_message = format!("{} failed: {}", verb, e);
+                return;
+            }
+        };
+        if mode == Transfer::Move {
+            apply_moves(&self.index, &records, &folder);
+            self.start_index_save();
+        }
+        self.search.marked.clear();
+        self.search.mark_dirty();
+
+        let done = match mode {
+            Transfer::Copy => "Copied",
+            Transfer::Move => "Moved",
+        };
+        self.status_message = match complete {
+            true => format!("{} {} items to {}", done, records.len(), destination),
+            false => format!("{} to {} was cancelled or skipped items", verb, destination),
+        };
+    }
+
+    /// Undo entry `i` of the history: put a recycled file back, or revert
+    /// the last rename.
+    pub fn undo_history(&mut self, i: usize) {
+        if !self.search.history.can_undo(i) {
+            return;
+        }
+        match self.search.history.entries()[i].action.clone() {
+            HistoryAction::Renamed(_) => self.undo_rename(),
+            HistoryAction::Recycled(record) => {
+                if let Err(e) = self.store.ensure_writable("restore files") {
+                    self.status_message = e.to_string();
+                    return;
+                }
+                if let Err(e) = restore_recycled(&record) {
+                    self.status_message = format!("Failed to restore {}: {}", record.path, e);
+                    return;
+                }
+                self.index.apply_change(ChangeEvent::created(
+                    record.volume_id.clone(),
+                    record.id,
+                    record.parent_id,
+                    record.name.clone(),
+                    record.is_dir,
+                    0,
+                ));
+                self.search.history.mark_undone(i);
+                self.status_message = format!("Restored {}", record.path);
+                self.start_index_save();
+                self.search.mark_dirty();
+            }
+            HistoryAction::Opened(_) | HistoryAction::Copied(_) => {}
+        }
+    }
+
+    fn finish_rename(&mut self, journal: &RenameJournal) {
+        for (path, error) in &journal.failures {
+            tracing::warn!(path = %path, error = %error, "Rename failed");
+        }
+
+        journal.apply_to_index(&self.index);
+        self.refresh_pins();
+        self.start_index_save();
+        self.search.mark_dirty();
+        self.rename.dirty = true;
+    }
+
+    /// How far the scan of the volume being indexed has got, if known.
+    pub fn build_estimate(&self) -> Option<ScanEstimate> {
+        self.build_progress.as_ref()?.estimate
+    }
+
+    /// Share of the index file written by the save under way.
+    pub fn save_fraction(&self) -> Option<f32> {
+        Some(self.save_progress?.fraction() as f32)
+    }
+
     /// Start building index asynchronously for selected volumes
     pub fn start_index_build(&mut self) {
-        let volumes: Vec<char> = self
-            .available_volumes
-            .iter()
-            .filter(|v| v.selected)
-            .map(|v| v.letter)
-            .collect();
-        if volumes.is_empty() {
-            self.status_message = "Please select at least one volume".to_string();
+        if let Err(e) = self.store.ensure_writable("rebuild the index") {
+            self.status_message = e.to_string();
+            return;
+        }
+        let volumes = self.selected_mount_points();
+        let folders = self.config.volumes.folders.clone();
+        if volumes.is_empty() && folders.is_empty() {
+            self.status_message = "Please select at least one volume or folder".to_string();
             return;
         }
 
         // Persist selected volumes
-        self.settings.indexed_volumes = volumes.clone();
+        let selected: Vec<&VolumeInfo> = self
+            .available_volumes
+            .iter()
+            .filter(|v| v.selected)
+            .collect();
+        self.settings.indexed_volumes = selected.iter().filter_map(|v| v.letter()).collect();
+        self.settings.indexed_mount_folders = selected
+            .iter()
+            .filter(|v| v.letter().is_none())
+            .map(|v| v.mount_point.clone())
+            .collect();
         if let Err(e) = self.settings.save() {
-            self.status_message = format!("Failed to save settings: {}", e);
+            self.toasts.error(format!("Failed to save settings: {}", e));
         }
 
         let (tx, rx) = unbounded::<Result<Arc<Index>, String>>();
         self.build_rx = Some(rx);
         self.building_index = true;
         self.build_started_at = Instant::now();
-        self.status_message = format!("Indexing volumes: {:?}...", volumes);
+
+        // With nothing indexed yet, search the new index while it fills up;
+        // otherwise keep searching the old one until the new one is complete
+        let new_index = Arc::new(Index::new());
+        new_index.set_exclusions(Exclusions::new(&self.config.exclude));
+        if self.index.is_empty() {
+            self.index = Arc::clone(&new_index);
+            self.apply_index_config();
+            self.search.set_index(Arc::clone(&self.index));
+        }
+        self.build_index = Some(Arc::clone(&new_index));
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.build_cancel = Some(Arc::clone(&cancel));
+        let (progress, events) = ProgressChannel::new("");
+        self.build_progress = Some(BuildProgress::new(events));
+        self.status_message = if folders.is_empty() {
+            format!("Indexing volumes: {}...", volumes.join(", "))
+        } else {
+            format!(
+                "Indexing volumes: {} and {} folder(s)...",
+                volumes.join(", "),
+                folders.len()
+            )
+        };
 
         std::thread::spawn(move || {
             #[cfg(windows)]
             {
                 use glint_backend_ntfs::NtfsBackend;
-                use glint_core::{backend::FileSystemBackend, Index};
+                use glint_core::backend::{folder_volume, FileSystemBackend, ScanProgress};
 
                 let backend = NtfsBackend::new();
-                let new_index = Index::new();
                 match backend.list_volumes() {
                     Ok(all) => {
-                        for volume in all {
-                            let mount_letter = volume
-                                .mount_point
-                                .chars()
-                                .next()
-                                .map(|c| c.to_ascii_uppercase());
-                            if let Some(letter) = mount_letter {
-                                if volumes.contains(&letter) {
-                                    match backend.full_scan(&volume, None) {
-                                        Ok(records) => {
-                                            new_index.add_volume_records(&volume, records);
-                                        }
-                                        Err(e) => {
-                                            let _ = tx.send(Err(format!(
-                                                "Failed to scan {}: {}",
-                                                volume.mount_point, e
-                                            )));
-                                            return;
-                                        }
-                                    }
+                        let mut selected: Vec<_> = all
+                            .iter()
+                            .filter(|volume| is_selected_mount(&volume.mount_point, &volumes))
+                            .cloned()
+                            .collect();
+                        // Folders are indexed as folder volumes
+                        for folder in &folders {
+                            match folder_volume(&all, folder) {
+                                Some(volume) => selected.push(volume),
+                                None => {
+                                    let _ = tx.send(Err(format!(
+                                        "No indexable volume contains {}",
+                                        folder
+                                    )));
+                                    return;
                                 }
                             }
                         }
-                        let _ = tx.send(Ok(Arc::new(new_index)));
+                        for volume in selected {
+                            if cancel.load(Ordering::Relaxed) {
+                                let _ = tx.send(Err("Index build cancelled".to_string()));
+                                return;
+                            }
+                            // Searches pick up each batch as it's added
+                            new_index.add_volume_records(&volume, Vec::new());
+                            let scan_progress: Arc<dyn ScanProgress> =
+                                Arc::new(progress.for_volume(volume.display_name()));
+                            let scanned =
+                                backend.scan_batches(&volume, Some(scan_progress), &mut |batch| {
+                                    new_index.append_volume_records(&volume, batch);
+                                });
+                            if let Err(e) = scanned {
+                                let _ = tx.send(Err(format!(
+                                    "Failed to scan {}: {}",
+                                    volume.display_name(),
+                                    e
+                                )));
+                                return;
+                            }
+                        }
+                        let _ = tx.send(Ok(new_index));
                     }
                     Err(e) => {
                         let _ = tx.send(Err(format!("Failed to enumerate volumes: {}", e)));
@@ -410,6 +1727,7 @@ impl GlintApp {
             }
             #[cfg(not(windows))]
             {
+                drop(progress);
                 let _ = tx.send(Err("NTFS indexing only available on Windows".to_string()));
             }
         });
@@ -426,51 +1744,78 @@ fn configure_fonts(ctx: &egui::Context) {
     ctx.set_style(style);
 }
 
+/// Run the actions whose shortcuts were pressed this frame.
 fn handle_shortcuts(ctx: &egui::Context, app: &mut GlintApp) {
-    if ctx.input(|i| i.key_pressed(egui::Key::F5)) {
-        app.reload_index();
-    }
-    if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
-        if !app.search.query.is_empty() {
-            app.search.query.clear();
-            app.search.clear();
-        }
+    // The palette handles its own keys while open, and the settings
+    // window takes the next key press while rebinding a shortcut
+    if app.palette.open || app.rebinding.is_some() {
+        return;
     }
-    if ctx.input(|i| i.modifiers.ctrl && i.key_pressed(egui::Key::Comma)) {
-        app.show_settings = !app.show_settings;
+    let pressed = ctx.input(|i| app.keymap.pressed(i));
+    for id in pressed {
+        actions::execute(id, ctx, app);
     }
 }
 
-pub fn format_number(n: usize) -> String {
-    let s = n.to_string();
-    let mut result = String::with_capacity(s.len() + s.len() / 3);
-    for (i, c) in s.chars().rev().enumerate() {
-        if i > 0 && i % 3 == 0 {
-            result.push(',');
+impl GlintApp {
+    /// Whether a result row is a folder that can be browsed.
+    pub fn can_browse(&self, row: usize) -> bool {
+        // Results from the archived view carry no real file identity
+        self.search
+            .results
+            .get(row)
+            .is_some_and(|r| r.record.is_dir && !r.record.path.is_empty())
+    }
+
+    /// Browse a folder result in place, or open anything else.
+    pub fn open_or_browse(&mut self, row: usize) {
+        self.search.selected = row;
+        if self.can_browse(row) {
+            let folder = self.search.results[row].record.clone();
+            self.browse = Some(BrowseState::open(&self.index, folder));
+        } else {
+            self.search.open_selected();
         }
-        result.push(c);
     }
-    result.chars().rev().collect()
-}
 
-pub fn format_size(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
+    /// Save the search settings after they were changed in Settings.
+    pub fn save_search_settings(&mut self) {
+        self.settings.result_limit = self.search.max_results;
+        self.settings.search_debounce_ms = self.search.debounce.as_millis() as u64;
+        self.settings.min_query_len = self.search.min_query_len;
+        if let Err(e) = self.settings.save() {
+            self.toasts.error(format!("Failed to save settings: {}", e));
+        }
+    }
+
+    /// Whether the high-contrast colors are in use.
+    pub fn high_contrast(&self) -> bool {
+        self.settings
+            .high_contrast
+            .unwrap_or(self.system_preferences.high_contrast)
+    }
+
+    /// Whether animations and smooth scrolling are off.
+    pub fn reduced_motion(&self) -> bool {
+        self.settings
+            .reduced_motion
+            .unwrap_or(self.system_preferences.reduced_motion)
+    }
 
-    if bytes >= GB {
-        format!("{:.2} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.1} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.1} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
+    /// Save the key bindings after they were changed in Settings.
+    pub fn save_key_bindings(&mut self) {
+        self.settings.key_bindings = self.keymap.overrides();
+        if let Err(e) = self.settings.save() {
+            self.toasts.error(format!("Failed to save settings: {}", e));
+        }
     }
 }
 
 #[cfg(windows)]
-fn detect_ntfs_volumes(previously_selected: &[char]) -> Vec<VolumeInfo> {
+fn detect_ntfs_volumes(
+    previously_selected: &[char],
+    previous_mount_folders: &[String],
+) -> Vec<VolumeInfo> {
     use std::ffi::OsStr;
     use std::os::windows::ffi::OsStrExt;
     use windows::Win32::Storage::FileSystem::{GetDiskFreeSpaceExW, GetDriveTypeW, GetVolumeInformationW};
@@ -537,7 +1882,7 @@ fn detect_ntfs_volumes(previously_selected: &[char]) -> Vec<VolumeInfo> {
             };
 
             volumes.push(VolumeInfo {
-                letter,
+                mount_point: format!("{}:\\", letter),
                 label,
                 size: total_bytes,
                 selected: previously_selected.is_empty() || previously_selected.contains(&letter),
@@ -545,10 +1890,167 @@ fn detect_ntfs_volumes(previously_selected: &[char]) -> Vec<VolumeInfo> {
         }
     }
 
+    // Volumes mounted only in a folder have no letter to find them by
+    {
+        use glint_backend_ntfs::NtfsBackend;
+        use glint_core::backend::FileSystemBackend;
+
+        let mounted = NtfsBackend::new().list_volumes().unwrap_or_default();
+        for volume in mounted {
+            let mount = volume.mount_point.trim_end_matches('\\');
+            if mount.len() <= 2 {
+                continue;
+            }
+            volumes.push(VolumeInfo {
+                selected: (previously_selected.is_empty() && previous_mount_folders.is_empty())
+                    || previous_mount_folders
+                        .iter()
+                        .any(|f| f.trim_end_matches('\\').eq_ignore_ascii_case(mount)),
+                label: volume.label.unwrap_or_else(|| "Mounted Volume".to_string()),
+                size: volume.total_bytes.unwrap_or(0),
+                mount_point: volume.mount_point,
+            });
+        }
+    }
+
     volumes
 }
 
 #[cfg(not(windows))]
-fn detect_ntfs_volumes(_previously_selected: &[char]) -> Vec<VolumeInfo> {
+fn detect_ntfs_volumes(
+    _previously_selected: &[char],
+    _previous_mount_folders: &[String],
+) -> Vec<VolumeInfo> {
     Vec::new()
 }
+
+#[cfg(windows)]
+fn restore_recycled(record: &FileRecord) -> std::io::Result<()> {
+    glint_backend_ntfs::recycle_bin::restore(std::path::Path::new(&record.path))
+}
+
+/// Nothing is recycled elsewhere, so there is nothing to restore.
+#[cfg(not(windows))]
+fn restore_recycled(_record: &FileRecord) -> std::io::Result<()> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+/// Update the index for `records` moved into `folder`: a file moved
+/// within its volume keeps its ID, so it is re-parented, and anything
+/// else is dropped until the destination's watcher reports it.
+#[cfg(windows)]
+fn apply_moves(index: &Index, records: &[FileRecord], folder: &std::path::Path) {
+    let target = index.find_by_path(&folder.to_string_lossy());
+    for record in records {
+        if std::path::Path::new(&record.path).exists() {
+            // Skipped or cancelled
+            continue;
+        }
+        let same_volume = target
+            .as_ref()
+            .filter(|t| t.volume_id == record.volume_id && folder.join(&record.name).exists());
+        let event = match same_volume {
+            Some(target) => ChangeEvent::renamed(
+                record.volume_id.clone(),
+                record.id,
+                record.parent_id,
+                record.name.clone(),
+                record.name.clone(),
+                Some(target.id),
+                record.is_dir,
+                0,
+            ),
+            None => ChangeEvent::deleted(
+                record.volume_id.clone(),
+                record.id,
+                record.parent_id,
+                record.name.clone(),
+                record.is_dir,
+                0,
+            ),
+        };
+        index.apply_change(event);
+    }
+}
+
+/// Load the indexes listed at `list_path` on a background thread.
+fn spawn_attached_load(list_path: std::path::PathBuf) -> Receiver<(Federation, Vec<String>)> {
+    let (tx, rx) = unbounded();
+    std::thread::spawn(move || {
+        let list = AttachedList::load(&list_path).unwrap_or_default();
+        let (attached, failures) = Federation::open(&list);
+        let failed = failures.into_iter().map(|(name, _)| name).collect();
+        let _ = tx.send((attached, failed));
+    });
+    rx
+}
+
+#[cfg(all(test, windows))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_moves() {
+        use glint_core::{FileId, VolumeInfo};
+        use std::fs;
+
+        let dir = tempfile::tempdir().unwrap();
+        let (src, dest, other) = (
+            dir.path().join("src"),
+            dir.path().join("dest"),
+            dir.path().join("other"),
+        );
+        for folder in [&src, &dest, &other] {
+            fs::create_dir(folder).unwrap();
+        }
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            fs::write(src.join(name), name).unwrap();
+        }
+
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        let record = |id: u64, parent: Option<u64>, path: &std::path::Path, is_dir: bool| {
+            FileRecord::new(
+                FileId::new(id),
+                parent.map(FileId::new),
+                volume.id.clone(),
+                path.file_name().unwrap().to_string_lossy().into_owned(),
+                path.to_string_lossy().into_owned(),
+                is_dir,
+            )
+        };
+        let index = Index::new();
+        index.add_volume_records(
+            &volume,
+            vec![
+                record(1, None, dir.path(), true),
+                record(2, Some(1), &src, true),
+                record(3, Some(1), &dest, true),
+                record(10, Some(2), &src.join("a.txt"), false),
+                record(11, Some(2), &src.join("b.txt"), false),
+                record(12, Some(2), &src.join("c.txt"), false),
+            ],
+        );
+        let find = |path: std::path::PathBuf| index.find_by_path(&path.to_string_lossy());
+        let records = |names: &[&str]| -> Vec<FileRecord> {
+            names
+                .iter()
+                .map(|name| find(src.join(name)).unwrap())
+                .collect()
+        };
+
+        // a.txt moved into an indexed folder; b.txt was skipped
+        let moved = records(&["a.txt", "b.txt"]);
+        fs::rename(src.join("a.txt"), dest.join("a.txt")).unwrap();
+        apply_moves(&index, &moved, &dest);
+        assert_eq!(find(dest.join("a.txt")).unwrap().id, FileId::new(10));
+        assert!(find(src.join("a.txt")).is_none());
+        assert!(find(src.join("b.txt")).is_some());
+
+        // c.txt moved somewhere the index doesn't know
+        let moved = records(&["c.txt"]);
+        fs::rename(src.join("c.txt"), other.join("c.txt")).unwrap();
+        apply_moves(&index, &moved, &other);
+        assert!(find(src.join("c.txt")).is_none());
+        assert!(find(other.join("c.txt")).is_none());
+    }
+}
@@ -0,0 +1,165 @@
+//! Folder browsing backed by the index.
+//!
+//! Double-clicking a folder in the results opens it here. Listings come
+//! from [`Index::get_children`], so browsing never touches the disk and
+//! works for folders on drives that aren't even connected.
+
+use glint_core::{FileRecord, Index};
+
+/// Column the listing is sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrowseSort {
+    Name,
+    Size,
+    Modified,
+}
+
+impl BrowseSort {
+    pub const ALL: [BrowseSort; 3] = [BrowseSort::Name, BrowseSort::Size, BrowseSort::Modified];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BrowseSort::Name => "Name",
+            BrowseSort::Size => "Size",
+            BrowseSort::Modified => "Modified",
+        }
+    }
+}
+
+/// The folder being browsed and how its listing is shown.
+pub struct BrowseState {
+    /// Folders from the volume root down to the current one
+    pub trail: Vec<FileRecord>,
+    /// Children of the current folder, sorted and filtered
+    pub entries: Vec<FileRecord>,
+    /// Only list names containing this (case-insensitive)
+    pub filter: String,
+    pub sort: BrowseSort,
+    pub descending: bool,
+    pub selected: usize,
+    /// Unfiltered children of the current folder
+    children: Vec<FileRecord>,
+    /// Index generation the listing was read at
+    generation: u64,
+}
+
+/// Deepest folder chain followed, in case of a parent loop.
+const MAX_DEPTH: usize = 256;
+
+impl BrowseState {
+    /// Browse `folder`, which must be a directory record.
+    pub fn open(index: &Index, folder: FileRecord) -> Self {
+        let mut state = BrowseState {
+            trail: Vec::new(),
+            entries: Vec::new(),
+            filter: String::new(),
+            sort: BrowseSort::Name,
+            descending: false,
+            selected: 0,
+            children: Vec::new(),
+            generation: 0,
+        };
+        state.trail = ancestors(index, &folder);
+        state.trail.push(folder);
+        state.reload(index);
+        state
+    }
+
+    /// The folder being listed.
+    pub fn current(&self) -> &FileRecord {
+        self.trail
+            .last()
+            .expect("trail always holds the current folder")
+    }
+
+    /// Open a child folder of the current one.
+    pub fn enter(&mut self, index: &Index, folder: FileRecord) {
+        self.trail.push(folder);
+        self.filter.clear();
+        self.reload(index);
+    }
+
+    /// Go back to the folder at `depth` in the trail (0 = outermost).
+    pub fn go_to(&mut self, index: &Index, depth: usize) {
+        if depth + 1 < self.trail.len() {
+            self.trail.truncate(depth + 1);
+            self.filter.clear();
+            self.reload(index);
+        }
+    }
+
+    /// Go to the parent folder, if there is one.
+    pub fn go_up(&mut self, index: &Index) {
+        if self.trail.len() > 1 {
+            self.go_to(index, self.trail.len() - 2);
+        }
+    }
+
+    /// Re-read the listing if the index changed since it was read.
+    pub fn refresh(&mut self, index: &Index) {
+        if index.generation() != self.generation {
+            self.reload(index);
+        }
+    }
+
+    fn reload(&mut self, index: &Index) {
+        let current = self.current();
+        self.children = index.get_children(&current.volume_id, current.id);
+        self.generation = index.generation();
+        self.apply_view();
+    }
+
+    /// Sort and filter the children again, after changing `sort` or `filter`.
+    pub fn apply_view(&mut self) {
+        let filter = self.filter.to_lowercase();
+        self.entries = self
+            .children
+            .iter()
+            .filter(|r| filter.is_empty() || r.name_lower.contains(&filter))
+            .cloned()
+            .collect();
+
+        let sort = self.sort;
+        let descending = self.descending;
+        self.entries.sort_by(|a, b| {
+            // Folders first, whichever way the column sorts
+            let order = match sort {
+                BrowseSort::Name => a.name_lower.cmp(&b.name_lower),
+                BrowseSort::Size => a.size.cmp(&b.size),
+                BrowseSort::Modified => a.modified.cmp(&b.modified),
+            };
+            let order = if descending { order.reverse() } else { order };
+            b.is_dir.cmp(&a.is_dir).then(order)
+        });
+        self.selected = self.selected.min(self.entries.len().saturating_sub(1));
+    }
+
+    /// Sort by `sort`, or flip the direction if already sorted by it.
+    pub fn sort_by(&mut self, sort: BrowseSort) {
+        if self.sort == sort {
+            self.descending = !self.descending;
+        } else {
+            self.sort = sort;
+            self.descending = false;
+        }
+        self.apply_view();
+    }
+}
+
+/// Folders above `record`, outermost first.
+fn ancestors(index: &Index, record: &FileRecord) -> Vec<FileRecord> {
+    let mut chain = Vec::new();
+    let mut parent = record.parent_id.filter(|&id| id != record.id);
+    while let Some(id) = parent {
+        if chain.len() >= MAX_DEPTH {
+            break;
+        }
+        let Some(folder) = index.get(&record.volume_id, id) else {
+            break;
+        };
+        parent = folder.parent_id.filter(|&pid| pid != folder.id);
+        chain.push(folder);
+    }
+    chain.reverse();
+    chain
+}
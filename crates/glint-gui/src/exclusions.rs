@@ -0,0 +1,150 @@
+//! The exclusions manager.
+//!
+//! Lists the `[exclude]` rules with how many indexed records each one
+//! leaves out, tests a sample path against them as it is typed, and takes
+//! a rule's records out of the index right away instead of waiting for a
+//! re-index. Counting walks the whole index, so it runs on a background
+//! thread and again whenever the index or the rules change.
+
+use crossbeam_channel::{unbounded, Receiver, TryRecvError};
+use eframe::egui;
+use glint_core::exclude::{count_by_rule, ExcludeRule};
+use glint_core::Index;
+use std::sync::Arc;
+
+/// Column the rules are sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleSort {
+    Rule,
+    Kind,
+    Records,
+}
+
+impl RuleSort {
+    pub const ALL: [RuleSort; 3] = [RuleSort::Rule, RuleSort::Kind, RuleSort::Records];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RuleSort::Rule => "Rule",
+            RuleSort::Kind => "Kind",
+            RuleSort::Records => "Records",
+        }
+    }
+}
+
+/// Records left out by each rule, for an index generation.
+struct Counts {
+    generation: u64,
+    rules: Vec<ExcludeRule>,
+    counts: Vec<usize>,
+}
+
+/// State of the exclusions manager window.
+pub struct ExclusionManager {
+    pub open: bool,
+    /// Rule being typed
+    pub new_rule: String,
+    /// Path tested against the rules
+    pub sample: String,
+    /// Only list rules containing this (case-insensitive)
+    pub filter: String,
+    pub sort: RuleSort,
+    pub descending: bool,
+    /// Latest counts, kept while newer ones are computed
+    counts: Option<Counts>,
+    rx: Option<Receiver<Counts>>,
+}
+
+impl Default for ExclusionManager {
+    fn default() -> Self {
+        ExclusionManager {
+            open: false,
+            new_rule: String::new(),
+            sample: String::new(),
+            filter: String::new(),
+            sort: RuleSort::Records,
+            descending: true,
+            counts: None,
+            rx: None,
+        }
+    }
+}
+
+impl ExclusionManager {
+    /// Records `rule` leaves out, once counted.
+    pub fn count(&self, rule: &ExcludeRule) -> Option<usize> {
+        let counts = self.counts.as_ref()?;
+        let i = counts.rules.iter().position(|r| r == rule)?;
+        Some(counts.counts[i])
+    }
+
+    pub fn is_counting(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    /// Pick up finished counts, and count again if the index or the rules
+    /// changed since.
+    pub fn refresh(&mut self, index: &Arc<Index>, rules: &[ExcludeRule], repaint: &egui::Context) {
+        if let Some(rx) = &self.rx {
+            match rx.try_recv() {
+                Ok(counts) => {
+                    self.counts = Some(counts);
+                    self.rx = None;
+                }
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => self.rx = None,
+            }
+        }
+
+        let generation = index.generation();
+        let current = self
+            .counts
+            .as_ref()
+            .is_some_and(|c| c.generation == generation && c.rules == rules);
+        if current {
+            return;
+        }
+        let (tx, rx) = unbounded();
+        self.rx = Some(rx);
+        let (index, rules, repaint) = (Arc::clone(index), rules.to_vec(), repaint.clone());
+        std::thread::spawn(move || {
+            let counts = count_by_rule(&index, &rules);
+            let _ = tx.send(Counts {
+                generation,
+                rules,
+                counts,
+            });
+            repaint.request_repaint();
+        });
+    }
+
+    /// The rules to list, filtered and sorted. Rules not counted yet sort
+    /// as if they left out nothing.
+    pub fn view(&self, rules: &[ExcludeRule]) -> Vec<ExcludeRule> {
+        let filter = self.filter.to_lowercase();
+        let mut view: Vec<ExcludeRule> = rules
+            .iter()
+            .filter(|rule| rule.text().to_lowercase().contains(&filter))
+            .cloned()
+            .collect();
+        match self.sort {
+            RuleSort::Rule => view.sort_by_key(|rule| rule.text().to_lowercase()),
+            RuleSort::Kind => view.sort_by_key(|rule| rule.kind()),
+            RuleSort::Records => view.sort_by_key(|rule| self.count(rule).unwrap_or(0)),
+        }
+        if self.descending {
+            view.reverse();
+        }
+        view
+    }
+
+    /// Sort by `column`, or flip the order if already sorted by it.
+    pub fn sort_by(&mut self, column: RuleSort) {
+        if self.sort == column {
+            self.descending = !self.descending;
+        } else {
+            self.sort = column;
+            self.descending = column == RuleSort::Records;
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Folder sizes for the results and browse lists.
+//!
+//! Folder records carry no size of their own. When a folder row is drawn
+//! its size is summed from the index on a background thread, with
+//! [`Index::folder_totals`], and kept until something on that volume
+//! changes. Rows show a spinner until the first result arrives and the
+//! previous size while a changed folder is summed again.
+
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use eframe::egui;
+use glint_core::{FileId, FileRecord, Index, VolumeId};
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+use std::thread;
+
+type FolderKey = (VolumeId, FileId);
+
+/// A folder to sum, with the index generation when asked.
+struct Job {
+    index: Arc<Index>,
+    key: FolderKey,
+    generation: u64,
+}
+
+/// A summed folder.
+struct Done {
+    index: Weak<Index>,
+    key: FolderKey,
+    generation: u64,
+    size: u64,
+}
+
+/// What is known about one folder's size.
+struct Entry {
+    /// Last size computed, if any
+    size: Option<u64>,
+    /// Index generation `size` was computed at
+    generation: u64,
+    /// A job for this folder is queued or running
+    pending: bool,
+}
+
+/// Sizes of the folders shown, computed as they are needed.
+pub struct FolderSizes {
+    entries: HashMap<FolderKey, Entry>,
+    /// Index the entries belong to; a reloaded index starts over
+    index: Weak<Index>,
+    job_tx: Sender<Job>,
+    done_rx: Receiver<Done>,
+}
+
+impl FolderSizes {
+    pub fn new(repaint: egui::Context) -> Self {
+        let (job_tx, job_rx) = unbounded::<Job>();
+        let (done_tx, done_rx) = unbounded::<Done>();
+        thread::spawn(move || {
+            while let Ok(job) = job_rx.recv() {
+                let (volume_id, folder_id) = &job.key;
+                let size = job.index.folder_totals(volume_id, *folder_id).size;
+                if done_tx
+                    .send(Done {
+                        index: Arc::downgrade(&job.index),
+                        key: job.key,
+                        generation: job.generation,
+                        size,
+                    })
+                    .is_err()
+                {
+                    break;
+                }
+                repaint.request_repaint();
+            }
+        });
+        FolderSizes {
+            entries: HashMap::new(),
+            index: Weak::new(),
+            job_tx,
+            done_rx,
+        }
+    }
+
+    /// The size of the folder `record`, or None until it has been summed.
+    ///
+    /// Queues the folder if it hasn't been summed since the index last
+    /// changed.
+    pub fn get(&mut self, index: &Arc<Index>, record: &FileRecord) -> Option<u64> {
+        self.poll(index);
+        let key = (record.volume_id.clone(), record.id);
+        let generation = index.generation();
+        let entry = self.entries.entry(key.clone()).or_insert(Entry {
+            size: None,
+            generation,
+            pending: false,
+        });
+        let stale = entry.size.is_none() || entry.generation != generation;
+        if stale && !entry.pending {
+            entry.pending = true;
+            let _ = self.job_tx.send(Job {
+                index: Arc::clone(index),
+                key,
+                generation,
+            });
+        }
+        entry.size
+    }
+
+    /// Take in the sizes summed since the last call.
+    fn poll(&mut self, index: &Arc<Index>) {
+        if !Weak::ptr_eq(&self.index, &Arc::downgrade(index)) {
+            self.entries.clear();
+            self.index = Arc::downgrade(index);
+        }
+        while let Ok(done) = self.done_rx.try_recv() {
+            // Sizes still arriving from a replaced index are dropped
+            if !Weak::ptr_eq(&done.index, &self.index) {
+                continue;
+            }
+            if let Some(entry) = self.entries.get_mut(&done.key) {
+                entry.size = Some(done.size);
+                entry.generation = done.generation;
+                entry.pending = false;
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Noticing when another program rewrites the index file.
+//!
+//! `glint index`, `glint watch` or a second Glint window can save glint.idx
+//! while this window runs, which would otherwise keep searching the records
+//! it loaded. The file's modification time and size are compared every few
+//! seconds with how this process last loaded or saved it. Saves replace the
+//! file by renaming, so a new stamp is always a complete index; it is still
+//! only reported once it stays the same for two checks in a row, so a burst
+//! of saves is taken as one.
+
+use glint_core::{IndexStamp, IndexStore};
+use std::time::{Duration, Instant};
+
+/// How often the index file is looked at.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(3);
+
+pub struct IndexWatch {
+    /// The file as this process last loaded or saved it
+    known: Option<IndexStamp>,
+    /// The file at the previous check
+    seen: Option<IndexStamp>,
+    checked_at: Instant,
+    /// Set when another program changed the file and the user has not yet
+    /// reloaded it or kept the index in memory
+    pub changed: bool,
+}
+
+impl IndexWatch {
+    pub fn new(store: &IndexStore) -> Self {
+        let stamp = store.stamp();
+        IndexWatch {
+            known: stamp,
+            seen: stamp,
+            checked_at: Instant::now(),
+            changed: false,
+        }
+    }
+
+    /// Take the file as it is now, or as `stamp` says, to be this
+    /// process's own.
+    pub fn set_known(&mut self, stamp: Option<IndexStamp>) {
+        self.known = stamp;
+        self.seen = stamp;
+        self.changed = false;
+    }
+
+    /// Look at the file if it is time to. Returns true once when a change
+    /// made elsewhere has settled.
+    pub fn check(&mut self, store: &IndexStore) -> bool {
+        if self.changed || self.checked_at.elapsed() < CHECK_INTERVAL {
+            return false;
+        }
+        self.checked_at = Instant::now();
+        let stamp = store.stamp();
+        let settled = stamp == self.seen;
+        self.seen = stamp;
+        self.changed = settled && stamp != self.known;
+        self.changed
+    }
+}
@@ -5,6 +5,7 @@
 //! - Start Menu shortcut creation
 //! - Windows Registry entries for Add/Remove Programs
 //! - Self-update when running a newer version
+//! - Installing the background service executable shipped alongside
 
 #[cfg(windows)]
 mod windows_installer {
@@ -21,6 +22,9 @@ mod windows_installer {
     const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
     const UNINSTALL_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Uninstall\Glint";
 
+    /// File name of the background service executable, installed next to the GUI
+    pub const SERVICE_EXE: &str = "glint-svc.exe";
+
     /// Installation paths
     pub struct InstallPaths {
         pub install_dir: PathBuf,
@@ -140,6 +144,15 @@ mod windows_installer {
         fs::copy(&current_exe, &paths.exe_path)?;
         info!("Copied executable to {:?}", paths.exe_path);
 
+        // Bring the service executable along if it was shipped with us. A
+        // running service keeps its exe locked until it is restarted.
+        let service_exe = current_exe.with_file_name(SERVICE_EXE);
+        if service_exe.exists() {
+            if let Err(e) = fs::copy(&service_exe, paths.install_dir.join(SERVICE_EXE)) {
+                warn!("Could not copy {}: {}", SERVICE_EXE, e);
+            }
+        }
+
         // Create Start Menu shortcut
         if let Err(e) = create_shortcut(&paths) {
             warn!("Failed to create Start Menu shortcut: {}", e);
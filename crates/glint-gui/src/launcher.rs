@@ -0,0 +1,340 @@
+//! Compact launcher layout for opening a file fast.
+//!
+//! The launcher turns the main window into a small borderless box on top of
+//! other windows, with just the search input and the best few results. On
+//! Windows a global hotkey (`launcher_hotkey` in the settings) brings it up
+//! from anywhere; Esc or opening a result hides it again. The full window
+//! comes back with the "Toggle Launcher" action.
+
+use crate::actions::{ActionId, Shortcut};
+use crate::app::GlintApp;
+use crossbeam_channel::Receiver;
+use eframe::egui::{self, Color32, RichText, Sense, ViewportCommand};
+
+/// Results shown in the launcher.
+pub const LAUNCHER_RESULTS: usize = 8;
+
+/// Size of the launcher window, in points.
+const LAUNCHER_SIZE: [f32; 2] = [640.0, 300.0];
+
+/// Whether the launcher is showing, and the hotkey that brings it up.
+pub struct Launcher {
+    pub active: bool,
+    /// Focus the input on the next frame
+    focus: bool,
+    hotkey: Option<Receiver<()>>,
+}
+
+impl Launcher {
+    /// A launcher brought up by `hotkey`, such as `Ctrl+Alt+Space`. An
+    /// empty or unparseable hotkey leaves only the action.
+    pub fn new(ctx: &egui::Context, hotkey: &str) -> Self {
+        let hotkey = Shortcut::parse(hotkey).and_then(|shortcut| register_hotkey(shortcut, ctx));
+        Launcher {
+            active: false,
+            focus: false,
+            hotkey,
+        }
+    }
+
+    /// Whether the hotkey was pressed since the last frame.
+    fn hotkey_pressed(&self) -> bool {
+        self.hotkey
+            .as_ref()
+            .is_some_and(|rx| rx.try_iter().count() > 0)
+    }
+}
+
+/// Show, hide or switch to the launcher when the hotkey was pressed.
+pub fn poll_hotkey(ctx: &egui::Context, app: &mut GlintApp) {
+    if !app.launcher.hotkey_pressed() {
+        return;
+    }
+    let showing = ctx.input(|i| {
+        let viewport = i.viewport();
+        viewport.focused.unwrap_or(false) && !viewport.minimized.unwrap_or(false)
+    });
+    if !app.launcher.active {
+        enter(ctx, app);
+    } else if showing {
+        hide(ctx);
+    } else {
+        ctx.send_viewport_cmd(ViewportCommand::Minimized(false));
+        ctx.send_viewport_cmd(ViewportCommand::Focus);
+        app.launcher.focus = true;
+    }
+}
+
+/// Switch between the launcher and the full window.
+pub fn toggle(ctx: &egui::Context, app: &mut GlintApp) {
+    if app.launcher.active {
+        leave(ctx, app);
+    } else {
+        enter(ctx, app);
+    }
+}
+
+/// Shrink the window to the launcher, centered near the top of the screen.
+fn enter(ctx: &egui::Context, app: &mut GlintApp) {
+    app.launcher.active = true;
+    app.launcher.focus = true;
+    app.search.selected = 0;
+
+    let [width, height] = LAUNCHER_SIZE;
+    ctx.send_viewport_cmd(ViewportCommand::Maximized(false));
+    ctx.send_viewport_cmd(ViewportCommand::Decorations(false));
+    ctx.send_viewport_cmd(ViewportCommand::WindowLevel(egui::WindowLevel::AlwaysOnTop));
+    ctx.send_viewport_cmd(ViewportCommand::InnerSize(egui::vec2(width, height)));
+    if let Some(monitor) = ctx.input(|i| i.viewport().monitor_size) {
+        let position = egui::pos2((monitor.x - width) / 2.0, monitor.y / 4.0);
+        ctx.send_viewport_cmd(ViewportCommand::OuterPosition(position));
+    }
+    ctx.send_viewport_cmd(ViewportCommand::Minimized(false));
+    ctx.send_viewport_cmd(ViewportCommand::Focus);
+}
+
+/// Restore the full window where it was before the launcher.
+fn leave(ctx: &egui::Context, app: &mut GlintApp) {
+    app.launcher.active = false;
+    app.focus_search = true;
+
+    ctx.send_viewport_cmd(ViewportCommand::Decorations(true));
+    ctx.send_viewport_cmd(ViewportCommand::WindowLevel(egui::WindowLevel::Normal));
+    let (size, position, maximized) = match app.settings.window {
+        Some(window) => (
+            egui::vec2(window.width, window.height),
+            Some(egui::pos2(window.x, window.y)),
+            window.maximized,
+        ),
+        None => (egui::vec2(1000.0, 700.0), None, false),
+    };
+    ctx.send_viewport_cmd(ViewportCommand::InnerSize(size));
+    if let Some(position) = position {
+        ctx.send_viewport_cmd(ViewportCommand::OuterPosition(position));
+    }
+    ctx.send_viewport_cmd(ViewportCommand::Maximized(maximized));
+    ctx.send_viewport_cmd(ViewportCommand::Minimized(false));
+    ctx.send_viewport_cmd(ViewportCommand::Focus);
+}
+
+/// Hide the launcher until the hotkey is pressed again.
+fn hide(ctx: &egui::Context) {
+    // Minimizing rather than hiding keeps the window updating, so the
+    // hotkey is noticed
+    ctx.send_viewport_cmd(ViewportCommand::Minimized(true));
+}
+
+/// The launcher: the search input and the first [`LAUNCHER_RESULTS`].
+pub fn panel(ctx: &egui::Context, app: &mut GlintApp) {
+    let toggle_shortcut = app.keymap.shortcut(ActionId::ToggleLauncher);
+    let (escape, up, down, enter, toggle_pressed) = ctx.input(|i| {
+        (
+            i.key_pressed(egui::Key::Escape),
+            i.key_pressed(egui::Key::ArrowUp),
+            i.key_pressed(egui::Key::ArrowDown),
+            i.key_pressed(egui::Key::Enter),
+            toggle_shortcut.is_some_and(|s| s.pressed(i)),
+        )
+    });
+    if toggle_pressed {
+        leave(ctx, app);
+        return;
+    }
+    if escape {
+        hide(ctx);
+    }
+    let shown = app.search.results.len().min(LAUNCHER_RESULTS);
+    app.search.selected = move_selection(app.search.selected, shown, up, down);
+    let mut open = enter && shown > 0;
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        // Without a title bar, dragging the background moves the window
+        let background = ui.interact(ui.max_rect(), ui.id().with("launcher_drag"), Sense::drag());
+        if background.drag_started() {
+            ctx.send_viewport_cmd(ViewportCommand::StartDrag);
+        }
+
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut app.search.query)
+                .hint_text("Open a file...")
+                .font(egui::TextStyle::Heading)
+                .desired_width(f32::INFINITY),
+        );
+        if app.launcher.focus {
+            response.request_focus();
+            app.launcher.focus = false;
+        }
+        if response.changed() {
+            app.search.selected = 0;
+            app.search.mark_dirty();
+        }
+        if app.search.should_search(app.index.generation()) {
+            app.search.search();
+        }
+
+        ui.separator();
+        if shown == 0 {
+            let hint = match app.search.query.is_empty() {
+                true => "Enter opens, Esc hides",
+                false => "No results found",
+            };
+            ui.label(RichText::new(hint).color(Color32::GRAY));
+            return;
+        }
+        let mut clicked = None;
+        for (row, result) in app.search.results.iter().take(shown).enumerate() {
+            let record = &result.record;
+            let folder = parent_folder(&record.path);
+            ui.horizontal(|ui| {
+                let label = ui.selectable_label(row == app.search.selected, &record.name);
+                ui.label(RichText::new(folder).small().color(Color32::GRAY));
+                if label.clicked() {
+                    clicked = Some(row);
+                }
+                open |= label.double_clicked();
+            });
+        }
+        if let Some(row) = clicked {
+            app.search.selected = row;
+        }
+    });
+
+    if open {
+        app.search.open_selected();
+        hide(ctx);
+    }
+}
+
+/// The selected row after Up or Down, kept within the `shown` rows.
+fn move_selection(selected: usize, shown: usize, up: bool, down: bool) -> usize {
+    let mut selected = selected;
+    if up {
+        selected = selected.saturating_sub(1);
+    }
+    if down && selected + 1 < shown {
+        selected += 1;
+    }
+    selected.min(shown.saturating_sub(1))
+}
+
+/// The folder shown next to a result's name.
+fn parent_folder(path: &str) -> &str {
+    path.rsplit_once(['\\', '/'])
+        .map_or("", |(folder, _)| folder)
+}
+
+/// Report presses of `shortcut` anywhere in Windows, waking the UI.
+#[cfg(windows)]
+fn register_hotkey(shortcut: Shortcut, ctx: &egui::Context) -> Option<Receiver<()>> {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        RegisterHotKey, MOD_ALT, MOD_CONTROL, MOD_NOREPEAT, MOD_SHIFT,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{GetMessageW, MSG, WM_HOTKEY};
+
+    let key = virtual_key(shortcut.key)?;
+    let mut modifiers = MOD_NOREPEAT;
+    for (held, modifier) in [
+        (shortcut.ctrl, MOD_CONTROL),
+        (shortcut.shift, MOD_SHIFT),
+        (shortcut.alt, MOD_ALT),
+    ] {
+        if held {
+            modifiers |= modifier;
+        }
+    }
+
+    let (tx, rx) = crossbeam_channel::unbounded();
+    let ctx = ctx.clone();
+    std::thread::spawn(move || {
+        // The hotkey belongs to this thread, which receives WM_HOTKEY
+        if let Err(e) = unsafe { RegisterHotKey(HWND::default(), 1, modifiers, key) } {
+            tracing::warn!("Could not register the launcher hotkey {}: {}", shortcut, e);
+            return;
+        }
+        let mut msg = MSG::default();
+        while unsafe { GetMessageW(&mut msg, HWND::default(), 0, 0) }.0 > 0 {
+            if msg.message == WM_HOTKEY {
+                if tx.send(()).is_err() {
+                    break;
+                }
+                ctx.request_repaint();
+            }
+        }
+    });
+    Some(rx)
+}
+
+/// Hotkeys need a system-wide keyboard hook elsewhere; only the action
+/// toggles the launcher.
+#[cfg(not(windows))]
+fn register_hotkey(_shortcut: Shortcut, _ctx: &egui::Context) -> Option<Receiver<()>> {
+    None
+}
+
+/// The Windows virtual-key code of a letter, digit, function key or Space.
+#[cfg(windows)]
+fn virtual_key(key: egui::Key) -> Option<u32> {
+    let name = key.name();
+    match name.as_bytes() {
+        [c @ (b'A'..=b'Z' | b'0'..=b'9')] => Some(u32::from(*c)),
+        _ if key == egui::Key::Space => Some(0x20),
+        _ => {
+            let n: u32 = name.strip_prefix('F')?.parse().ok()?;
+            (1..=24).contains(&n).then_some(0x6F + n)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_selection() {
+        assert_eq!(move_selection(0, 8, false, true), 1);
+        assert_eq!(move_selection(7, 8, false, true), 7);
+        assert_eq!(move_selection(3, 8, true, false), 2);
+        assert_eq!(move_selection(0, 8, true, false), 0);
+        // Fewer results than before leave the selection on the last one
+        assert_eq!(move_selection(6, 2, false, false), 1);
+        assert_eq!(move_selection(4, 0, false, true), 0);
+    }
+
+    #[test]
+    fn test_parent_folder() {
+        assert_eq!(parent_folder("C:\\docs\\report.pdf"), "C:\\docs");
+        assert_eq!(parent_folder("/home/me/notes.txt"), "/home/me");
+        assert_eq!(parent_folder("notes.txt"), "");
+    }
+
+    #[test]
+    fn test_hotkey_pressed() {
+        let ctx = egui::Context::default();
+        assert!(!Launcher::new(&ctx, "").hotkey_pressed());
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let launcher = Launcher {
+            active: false,
+            focus: false,
+            hotkey: Some(rx),
+        };
+        assert!(!launcher.hotkey_pressed());
+        // Presses since the last frame count once
+        tx.send(()).unwrap();
+        tx.send(()).unwrap();
+        assert!(launcher.hotkey_pressed());
+        assert!(!launcher.hotkey_pressed());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_virtual_key() {
+        assert_eq!(virtual_key(egui::Key::A), Some(0x41));
+        assert_eq!(virtual_key(egui::Key::Num5), Some(0x35));
+        assert_eq!(virtual_key(egui::Key::Space), Some(0x20));
+        assert_eq!(virtual_key(egui::Key::F1), Some(0x70));
+        assert_eq!(virtual_key(egui::Key::F12), Some(0x7B));
+        assert_eq!(virtual_key(egui::Key::Escape), None);
+    }
+}
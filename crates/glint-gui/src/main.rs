@@ -12,15 +12,27 @@
 //! - Registers in Add/Remove Programs
 //! - Running a newer version automatically updates
 
+mod actions;
 mod app;
+mod browse;
+mod exclusions;
+mod folder_size;
+mod index_watch;
 mod installer;
+mod launcher;
+mod safe_mode;
 mod search;
 mod service;
 mod settings;
+#[cfg(windows)]
+mod shell;
+mod theme;
+mod toasts;
 mod ui;
 
 use app::GlintApp;
 use eframe::egui;
+use settings::Settings;
 use std::env;
 
 fn main() -> eframe::Result<()> {
@@ -82,6 +94,13 @@ fn main() -> eframe::Result<()> {
                 }
                 std::process::exit(0);
             }
+            safe_mode::CHECK_INDEX_ARG => {
+                // Trial load run by the app itself; a crash shows in the exit status
+                let data_dir = args.get(2).map(std::path::PathBuf::from).unwrap_or_default();
+                let from_backup = args.get(3).is_some_and(|arg| arg == "--backup");
+                safe_mode::check_index(&data_dir, from_backup);
+                std::process::exit(0);
+            }
             "--help" | "-h" => {
                 println!("Glint - Fast File Search");
                 println!();
@@ -121,13 +140,27 @@ fn main() -> eframe::Result<()> {
 
     tracing::info!("Starting Glint GUI");
 
-    // Configure native options
+    // Configure native options, reopening the window where it was closed
+    let mut viewport = egui::ViewportBuilder::default()
+        .with_inner_size([1000.0, 700.0])
+        .with_min_inner_size([600.0, 400.0])
+        .with_title("Glint - Fast File Search")
+        .with_icon(load_icon());
+    if let Some(window) = Settings::load().ok().and_then(|s| s.window) {
+        viewport = viewport
+            .with_inner_size([window.width, window.height])
+            .with_maximized(window.maximized);
+        match desktop_bounds() {
+            Some(desktop) if window.on_screen(desktop) => {
+                viewport = viewport.with_position([window.x, window.y]);
+            }
+            _ => tracing::debug!("Not restoring the window position"),
+        }
+    }
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([1000.0, 700.0])
-            .with_min_inner_size([600.0, 400.0])
-            .with_title("Glint - Fast File Search")
-            .with_icon(load_icon()),
+        viewport,
+        // Geometry is kept in the settings file instead
+        persist_window: false,
         ..Default::default()
     };
 
@@ -139,6 +172,32 @@ fn main() -> eframe::Result<()> {
     )
 }
 
+/// Edges of the desktop across all monitors, in pixels.
+#[cfg(windows)]
+fn desktop_bounds() -> Option<[i32; 4]> {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetSystemMetrics, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN, SM_XVIRTUALSCREEN,
+        SM_YVIRTUALSCREEN,
+    };
+
+    let (left, top, width, height) = unsafe {
+        (
+            GetSystemMetrics(SM_XVIRTUALSCREEN),
+            GetSystemMetrics(SM_YVIRTUALSCREEN),
+            GetSystemMetrics(SM_CXVIRTUALSCREEN),
+            GetSystemMetrics(SM_CYVIRTUALSCREEN),
+        )
+    };
+    (width > 0 && height > 0).then_some([left, top, left + width, top + height])
+}
+
+/// Monitor layouts aren't known before the window opens elsewhere, so the
+/// window manager places it.
+#[cfg(not(windows))]
+fn desktop_bounds() -> Option<[i32; 4]> {
+    None
+}
+
 /// Load application icon (returns default if not found)
 fn load_icon() -> egui::IconData {
     // Default icon data (a simple magnifying glass shape encoded as RGBA)
@@ -0,0 +1,340 @@
+//! Guarded index loading and safe mode.
+//!
+//! A damaged index file can make deserialization panic, or a damaged
+//! archive can make the zero-copy view fault, and a stuck disk can make
+//! loading hang. Release builds abort on panic, and a fault is no panic in
+//! any build, so neither can be caught in the app's own process. Loading
+//! is therefore first tried in a child process (`glint-gui --check-index`)
+//! whose exit status tells whether it crashed, and the app gives up on it
+//! after [`LOAD_TIMEOUT`]. Either way the app starts in safe mode with an
+//! empty index instead of dying, and offers to restore the backup, rebuild,
+//! or open a diagnostics report.
+//!
+//! The index fills in one volume at a time, so the app can search the
+//! first volume while the rest are still loading.
+
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use glint_core::archive_scan;
+use glint_core::archive_view::ArchivedView;
+use glint_core::{
+    GlintError, Index, IndexStamp, IndexStore, LoadProgress, SearchQuery, VolumeId, VolumeLoad,
+};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How long loading may go without progress before the app starts in safe mode.
+pub const LOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Command-line option running a trial load in a child process.
+pub const CHECK_INDEX_ARG: &str = "--check-index";
+
+/// File name of the diagnostics report inside the index directory.
+const REPORT_FILE: &str = "glint-diagnostics.txt";
+
+/// An index loaded from disk.
+pub struct LoadedIndex {
+    pub index: Arc<Index>,
+    /// Zero-copy view of the same file, if it is a v3 archive
+    pub archived: Option<Arc<ArchivedView>>,
+    /// True if there was no index file yet
+    pub fresh: bool,
+    /// The file as it was read
+    pub stamp: Option<IndexStamp>,
+}
+
+/// Messages from the loader thread.
+pub enum LoadMessage {
+    /// The index will fill in with these volumes, in order. It can be
+    /// searched right away.
+    Started(Arc<Index>, Vec<VolumeLoad>),
+    /// A volume's records are in the index
+    VolumeReady(VolumeId),
+    /// Loading finished, failed or panicked
+    Finished(Result<LoadedIndex, String>),
+}
+
+/// Why the app is in safe mode.
+pub struct SafeMode {
+    pub reason: String,
+    /// Diagnostics report, if it could be written
+    pub report_path: Option<PathBuf>,
+}
+
+impl SafeMode {
+    /// Enter safe mode, writing a diagnostics report next to the index.
+    pub fn new(store: &IndexStore, reason: String) -> Self {
+        tracing::error!(reason = %reason, "Starting in safe mode");
+        let report_path = write_report(store, &reason)
+            .map_err(|e| tracing::warn!(error = %e, "Failed to write diagnostics report"))
+            .ok();
+        SafeMode {
+            reason,
+            report_path,
+        }
+    }
+}
+
+/// Load the index (or with `from_backup`, restore the backup) on a worker thread.
+///
+/// The index is first loaded in a child process, and only loaded here if
+/// that one exits normally. The receiver reports each volume as it becomes
+/// ready, then the loaded index or a description of what went wrong if
+/// loading failed, crashed or hung.
+pub fn spawn_load(data_dir: PathBuf, from_backup: bool) -> Receiver<LoadMessage> {
+    let (tx, rx) = unbounded();
+    std::thread::spawn(move || {
+        let result = trial_load(&data_dir, from_backup).and_then(|()| {
+            panic::catch_unwind(AssertUnwindSafe(|| load(&data_dir, from_backup, &tx)))
+                .unwrap_or_else(|payload| {
+                    Err(format!(
+                        "Index loading crashed: {}",
+                        panic_message(&*payload)
+                    ))
+                })
+        });
+        let _ = tx.send(LoadMessage::Finished(result));
+    });
+    rx
+}
+
+/// Load the index the way [`spawn_load`] does, for `glint-gui
+/// --check-index`, reading every name of the archive.
+///
+/// Returns normally whether or not the index loads; a damaged index that
+/// crashes loading ends the process instead.
+pub fn check_index(data_dir: &Path, from_backup: bool) {
+    let (tx, _rx) = unbounded();
+    if let Ok(LoadedIndex {
+        archived: Some(view),
+        ..
+    }) = load(data_dir, from_backup, &tx)
+    {
+        // Matches nothing, but compares against every name
+        let _ = archive_scan::search(&view, &SearchQuery::substring("\u{fffd}\u{fffd}"), 1);
+    }
+}
+
+/// Run [`check_index`] in a child process and fail if it crashes or takes
+/// longer than [`LOAD_TIMEOUT`].
+///
+/// If the child can't be started, the index is loaded unguarded.
+fn trial_load(data_dir: &Path, from_backup: bool) -> Result<(), String> {
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            tracing::warn!(error = %e, "Cannot find the executable to check the index with");
+            return Ok(());
+        }
+    };
+    let mut command = Command::new(exe);
+    command.arg(CHECK_INDEX_ARG).arg(data_dir);
+    if from_backup {
+        command.arg("--backup");
+    }
+    run_trial(command, LOAD_TIMEOUT)
+}
+
+/// Run `command`, waiting up to `timeout` for it to exit successfully.
+fn run_trial(mut command: Command, timeout: Duration) -> Result<(), String> {
+    let mut child = match command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            tracing::warn!(error = %e, "Cannot start the index check");
+            return Ok(());
+        }
+    };
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => return Err(format!("Index loading crashed ({})", status)),
+            Ok(None) if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "Loading the index did not finish within {} seconds",
+                    timeout.as_secs()
+                ));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(20)),
+            Err(e) => return Err(format!("The index check failed: {}", e)),
+        }
+    }
+}
+
+fn load(
+    data_dir: &Path,
+    from_backup: bool,
+    tx: &Sender<LoadMessage>,
+) -> Result<LoadedIndex, String> {
+    let store = IndexStore::new(data_dir);
+    let mut stamp = store.stamp();
+    let (index, fresh) = if from_backup {
+        let index = store
+            .restore_from_backup()
+            .map_err(|e| format!("The backup could not be restored: {}", e))?;
+        stamp = store.stamp();
+        (Arc::new(index), false)
+    } else {
+        let index = Arc::new(Index::new());
+        let result = store.load_progressive(&index, |event| {
+            let _ = tx.send(match event {
+                LoadProgress::Started(volumes) => LoadMessage::Started(Arc::clone(&index), volumes),
+                LoadProgress::VolumeReady(id) => LoadMessage::VolumeReady(id),
+            });
+        });
+        match result {
+            Ok(()) => (index, false),
+            Err(GlintError::IndexNotFound { .. }) => (index, true),
+            Err(e) => return Err(format!("The index could not be loaded: {}", e)),
+        }
+    };
+    let archived = if fresh {
+        None
+    } else {
+        ArchivedView::open(store.index_path()).ok().map(Arc::new)
+    };
+    Ok(LoadedIndex {
+        index,
+        archived,
+        fresh,
+        stamp,
+    })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Write a plain-text report describing the failure and the index files.
+fn write_report(store: &IndexStore, reason: &str) -> std::io::Result<PathBuf> {
+    let index_path = store.index_path();
+    let dir = index_path.parent().unwrap_or(Path::new("."));
+    std::fs::create_dir_all(dir)?;
+
+    let describe = |path: &Path| match std::fs::metadata(path) {
+        Ok(meta) => {
+            let modified = meta
+                .modified()
+                .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339())
+                .unwrap_or_else(|_| "unknown".to_string());
+            format!(
+                "{} ({} bytes, modified {})",
+                path.display(),
+                meta.len(),
+                modified
+            )
+        }
+        Err(e) => format!("{} ({})", path.display(), e),
+    };
+
+    let report = format!(
+        "Glint diagnostics report\n\
+         ========================\n\
+         \n\
+         Time:     {}\n\
+         Version:  {}\n\
+         Platform: {} {}\n\
+         \n\
+         Problem:\n  {}\n\
+         \n\
+         Index:    {}\n\
+         Backup:   {}\n\
+         \n\
+         The application started in safe mode with an empty index.\n\
+         Restore the backup or rebuild the index to continue.\n",
+        chrono::Utc::now().to_rfc3339(),
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        reason,
+        describe(&index_path),
+        describe(&store.backup_path()),
+    );
+
+    let path = dir.join(REPORT_FILE);
+    std::fs::write(&path, report)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glint_core::{FileId, FileRecord, VolumeInfo};
+
+    /// Tells [`child_load`], run in a child process, how to end.
+    const CHILD_ENV: &str = "GLINT_TEST_CHILD_LOAD";
+
+    /// Run by [`child`] in a child process. With `abort` it crashes the way
+    /// loading does in a release build, where `panic = "abort"` ends the
+    /// process from the panic hook; with `hang` it never finishes.
+    #[test]
+    #[ignore]
+    fn child_load() {
+        match std::env::var(CHILD_ENV).as_deref() {
+            Ok("abort") => {
+                panic::set_hook(Box::new(|_| std::process::abort()));
+                panic!("damaged index");
+            }
+            Ok("hang") => std::thread::sleep(Duration::from_secs(60)),
+            _ => {}
+        }
+    }
+
+    fn child(end: &str) -> Command {
+        let mut command = Command::new(std::env::current_exe().unwrap());
+        command
+            .args(["--exact", "safe_mode::tests::child_load", "--ignored"])
+            .env(CHILD_ENV, end);
+        command
+    }
+
+    #[test]
+    fn test_trial_load() {
+        assert_eq!(run_trial(child("exit"), LOAD_TIMEOUT), Ok(()));
+
+        let crashed = run_trial(child("abort"), LOAD_TIMEOUT).unwrap_err();
+        assert!(crashed.starts_with("Index loading crashed"), "{}", crashed);
+
+        let hung = run_trial(child("hang"), Duration::from_millis(200)).unwrap_err();
+        assert!(hung.contains("did not finish"), "{}", hung);
+    }
+
+    #[test]
+    fn test_check_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        let index = Index::new();
+        index.add_volume_records(
+            &volume,
+            vec![FileRecord::new(
+                FileId::new(5),
+                None,
+                volume.id.clone(),
+                "report.txt".into(),
+                "C:\\report.txt".into(),
+                false,
+            )],
+        );
+        IndexStore::new(dir.path()).save(&index).unwrap();
+
+        // Loads and reads the archive, or finds no index, without crashing
+        check_index(dir.path(), false);
+        check_index(&dir.path().join("missing"), false);
+    }
+}
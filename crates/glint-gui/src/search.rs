@@ -1,8 +1,24 @@
 //! GUI search state wrapper around glint_core search.
 
-use glint_core::{Index, SearchQuery};
+use eframe::egui;
+use glint_core::{
+    Index, IndexChange, PathFormat, ResultTotals, Scopes, SearchPage, SearchQuery, VolumeId,
+};
+use glint_core::archive_scan;
 use glint_core::archive_view::ArchivedView;
+use glint_core::config::RemoteServer;
+use glint_core::federation::{self, Federation};
+use glint_core::ipc::QueryRequest;
+use glint_core::pins::PinStore;
 use glint_core::search::SearchResult;
+use glint_core::types::FileRecord;
+use glint_frontend::actions;
+use glint_frontend::cache::{CacheKey, QueryCache};
+use glint_frontend::hints::{self, Completions};
+use glint_frontend::history::{History, HistoryAction};
+use glint_frontend::search::{self, SearchOptions, SearchOutcome};
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Range;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use crossbeam_channel::{unbounded, Receiver, Sender};
@@ -12,14 +28,60 @@ use arc_swap::ArcSwap;
 struct SearchRequest {
     id: u64,
     query: SearchQuery,
-    max_results: usize,
+    options: SearchOptions,
     archived: Option<Arc<ArchivedView>>,
+    pins: Arc<PinStore>,
+    attached: Arc<Federation>,
+    /// What to ask remote servers, unless only local results make sense
+    remote: Option<QueryRequest>,
+    remotes: Vec<RemoteServer>,
+    remote_timeout: Duration,
+    /// Limited to scope folders, which the archived view can't check
+    scoped: bool,
+    /// The query without the volume selection, to count matches per volume
+    count_query: SearchQuery,
+    cache_key: CacheKey,
+}
+
+/// Parts of `query` that don't parse, with why. `scope:` tokens are
+/// checked against `scopes`.
+pub fn query_problems(scopes: &Scopes, query: &str) -> Vec<(Range<usize>, String)> {
+    glint_core::search::inspect_query(query)
+        .into_iter()
+        .filter_map(|part| {
+            let error = match query[part.range.clone()].strip_prefix("scope:") {
+                Some(name) => match scopes.resolve(name) {
+                    Ok(_) => return None,
+                    Err(glint_core::GlintError::InvalidPattern { reason, .. }) => reason,
+                    Err(e) => e.to_string(),
+                },
+                None => part.error?,
+            };
+            Some((part.range, error))
+        })
+        .collect()
+}
+
+/// Matches per volume for the search with the same id.
+struct VolumeCounts {
+    id: u64,
+    counts: HashMap<VolumeId, usize>,
 }
 
 struct SearchDone {
     id: u64,
+    query: SearchQuery,
     results: Vec<SearchResult>,
+    /// Matches in the whole index, including those beyond `max_results`
+    total: usize,
+    totals: ResultTotals,
     took: Duration,
+    /// Index generation the search started from
+    generation: u64,
+    /// Plain index results, which new matches can be appended to
+    appendable: bool,
+    /// Remote servers that failed or didn't answer in time
+    unreachable: Vec<String>,
 }
 
 pub struct SearchState {
@@ -28,83 +90,253 @@ pub struct SearchState {
     pub dirs_only: bool,
     pub case_sensitive: bool,
     pub use_regex: bool,
+    /// Show only pinned items instead of search results
+    pub show_pinned: bool,
+    /// Show the largest matching files, biggest first, instead of search
+    /// results
+    pub show_largest: bool,
+    /// Show the files deleted recently, newest first, instead of search
+    /// results
+    pub show_deleted: bool,
+    /// Retry allowing one typo when nothing matches
+    pub typo_tolerance: bool,
+    /// Merge results that are the same file under several paths
+    pub dedup_links: bool,
+    /// Globs of results to leave out, from the config
+    pub hide_patterns: Vec<String>,
+    /// Show the results matching `hide_patterns` anyway
+    pub show_hidden_patterns: bool,
+    /// Selected scope (None = everywhere)
+    pub scope: Option<String>,
+    /// Scopes defined in the config
+    pub scopes: Scopes,
+    /// Other machines' servers searched alongside the local index
+    pub remotes: Vec<RemoteServer>,
+    /// How long to wait for each of them
+    pub remote_timeout: Duration,
+    /// Remote servers that failed or didn't answer in the last search
+    pub unreachable: Vec<String>,
+    /// Selected volume (None = all volumes)
+    pub volume: Option<VolumeId>,
+    /// Matches on each volume, ignoring the selected volume; known once a
+    /// search has run with more than one volume indexed
+    pub volume_counts: Option<HashMap<VolumeId, usize>>,
+    /// Only search inside this folder, picked from a result's path
+    pub folder: Option<String>,
     pub max_results: usize,
+    /// Wait this long after the last keystroke before searching
+    pub debounce: Duration,
+    /// Shortest query that starts a search
+    pub min_query_len: usize,
+    /// Results shown: the retrieved results that pass `result_filter`
     pub results: Vec<SearchResult>,
+    /// Narrows the retrieved results without searching again
+    pub result_filter: String,
+    /// Show the result filter box
+    pub filter_open: bool,
+    /// Suggestions for the query token being typed
+    pub completions: Option<Completions>,
+    /// Total number of matches; may exceed `results.len()`
+    pub total_matches: usize,
+    /// Files, folders and size of all matches
+    pub totals: ResultTotals,
     pub selected: usize,
+    /// Paths of the results picked with Ctrl+click and Shift+click
+    pub marked: BTreeSet<String>,
     pub search_time: Duration,
     pub scroll_to_selected: bool,
     pub error: Option<String>,
+    /// What was done to results this session
+    pub history: History,
     shared_index: Arc<ArcSwap<Arc<Index>>>,
     archived_view: Option<Arc<ArchivedView>>,
+    pins: Arc<PinStore>,
+    pins_generation: u64,
+    /// Indexes searched alongside the local one
+    attached: Arc<Federation>,
+    attached_generation: u64,
 
     // Change detection and debounce
     dirty: bool,
     last_input_at: Instant,
-    debounce: Duration,
     last_query: String,
     last_files_only: bool,
     last_dirs_only: bool,
     last_use_regex: bool,
+    last_show_pinned: bool,
+    last_show_largest: bool,
+    last_show_deleted: bool,
+    last_typo_tolerance: bool,
+    last_dedup_links: bool,
+    last_show_hidden_patterns: bool,
+    last_scope: Option<String>,
+    last_volume: Option<VolumeId>,
+    last_folder: Option<String>,
+    last_max_results: usize,
     last_index_generation: u64,
+    last_pins_generation: u64,
+    last_attached_generation: u64,
 
     // Async search worker
     req_tx: Sender<SearchRequest>,
     done_rx: Receiver<SearchDone>,
+    counts_rx: Receiver<VolumeCounts>,
     in_flight: bool,
     last_request_id: u64,
     latest_applied_id: u64,
 
-    // Incremental narrowing cache
+    // Incremental narrowing cache; also the retrieved results that
+    // `result_filter` narrows
     prev_query: String,
     prev_results: Vec<SearchResult>,
+    result_filter_query: Option<SearchQuery>,
+
+    // Live refresh when the index changes under the displayed query
+    repaint: egui::Context,
+    index_changes: Receiver<IndexChange>,
+    refreshing: bool,
+    /// When the results were last re-run because the index changed
+    pub updated_at: Option<Instant>,
+    /// Query behind the displayed results
+    displayed_query: Option<SearchQuery>,
+    /// Whether matching new files can be appended to the results
+    appendable: bool,
+}
+
+/// Searches whose results the worker keeps for when they are run again.
+const QUERY_CACHE_SIZE: usize = 16;
+
+/// How long results show the "updated" indicator after a live refresh.
+pub const UPDATED_INDICATOR: Duration = Duration::from_secs(3);
+
+/// Forward index changes and wake the UI for each one.
+///
+/// The thread ends once the index is dropped or the receiver is replaced.
+fn watch_index(index: &Index, repaint: egui::Context) -> Receiver<IndexChange> {
+    let changes = index.subscribe();
+    let (tx, rx) = unbounded();
+    thread::spawn(move || {
+        for change in changes {
+            if tx.send(change).is_err() {
+                break;
+            }
+            repaint.request_repaint();
+        }
+    });
+    rx
 }
 
 impl SearchState {
-    pub fn new(index: Arc<Index>) -> Self {
+    pub fn new(index: Arc<Index>, repaint: egui::Context) -> Self {
         // Spawn background search worker
         let (req_tx, req_rx) = unbounded::<SearchRequest>();
         let (done_tx, done_rx) = unbounded::<SearchDone>();
+        let (counts_tx, counts_rx) = unbounded::<VolumeCounts>();
+        let index_changes = watch_index(&index, repaint.clone());
         let shared_index = Arc::new(ArcSwap::from(Arc::new(Arc::clone(&index))));
         let worker_shared = Arc::clone(&shared_index);
+        let worker_repaint = repaint.clone();
         thread::spawn(move || {
+            let mut cache = QueryCache::new(QUERY_CACHE_SIZE);
+            // A reloaded index starts its generations over
+            let mut cached_index = std::sync::Weak::new();
+            // Attaching or detaching an index changes every outcome
+            let mut cached_attached = Arc::new(Federation::new());
             while let Ok(req) = req_rx.recv() {
                 let start = Instant::now();
                 // Load the current index snapshot
                 let idx = worker_shared.load_full();
-                // Prefer archived view if provided
-                let mut results = if let Some(view) = req.archived.clone() {
-                    // Unsafe root reference lives as long as mmap
-                    let root = unsafe { view.root() };
-                    let mut out = Vec::with_capacity(req.max_results);
-                    // Simple linear scan over names (lowercased)
-                    for i in 0..root.is_dir.len() {
-                        let noff = root.name_offsets[i] as usize;
-                        // read cstr from names_blob
-                        let name = cstr_from_bytes_local(&root.names_blob[noff..]);
-                        // Build minimal record for matcher
-                        let rec = glint_core::types::FileRecord::new(
-                            glint_core::types::FileId::new(i as u64 + 1),
-                            None,
-                            glint_core::types::VolumeId::new("V"),
-                            name.to_string(),
-                            String::new(),
-                            root.is_dir[i] != 0,
-                        );
-                        if req.query.matches(&rec) {
-                            out.push(glint_core::search::SearchResult::new(rec, 0));
-                            if out.len() >= req.max_results { break; }
+                let generation = idx.generation();
+                if !std::sync::Weak::ptr_eq(&cached_index, &Arc::downgrade(&*idx)) {
+                    cache.watch(&idx);
+                    cached_index = Arc::downgrade(&*idx);
+                }
+                if !Arc::ptr_eq(&cached_attached, &req.attached) {
+                    cache.clear();
+                    cached_attached = Arc::clone(&req.attached);
+                }
+                // Archived view if provided, otherwise the in-memory index
+                let mut run_local = || {
+                    if let Some(view) = req
+                        .archived
+                        .clone()
+                        // The archived view can't tell volumes or folders apart
+                        .filter(|_| {
+                            !req.options.pinned_only
+                                && !req.options.largest_only
+                                && !req.query.searches_deleted()
+                                && !req.scoped
+                                && !idx.has_disabled_volumes()
+                                && req.attached.is_empty()
+                        })
+                    {
+                        let out = archive_scan::search(&view, &req.query, req.options.max_results);
+                        SearchOutcome {
+                            total: out.len(),
+                            totals: ResultTotals::of(out.iter().map(|r| &r.record)),
+                            results: out,
+                            appendable: false,
                         }
+                    } else if let Some(outcome) = cache.get(&idx, &req.query, &req.cache_key) {
+                        SearchOutcome::clone(&outcome)
+                    } else {
+                        let outcome =
+                            search::run(&idx, &req.attached, &req.pins, &req.query, &req.options);
+                        let key = req.cache_key.clone();
+                        let outcome = cache.insert(&idx, &req.query, generation, key, outcome);
+                        SearchOutcome::clone(&outcome)
+                    }
+                };
+                // Remote servers are asked while the local index is searched
+                let (mut outcome, remote) = match &req.remote {
+                    Some(request) => {
+                        federation::fan_out(&req.remotes, request, req.remote_timeout, run_local)
                     }
-                    out
-                } else {
-                    // Default path: use in-memory index
-                    idx.search_limited(&req.query, req.max_results)
+                    None => (run_local(), Vec::new()),
                 };
+                let mut unreachable = Vec::new();
+                if !remote.is_empty() {
+                    let limit = req.options.max_results;
+                    let mut page = SearchPage {
+                        results: outcome.results,
+                        total: outcome.total,
+                        totals: outcome.totals,
+                    };
+                    for (name, _) in federation::merge_remote(&mut page, remote, limit) {
+                        unreachable.push(name.to_string());
+                    }
+                    if !req.query.searches_deleted() {
+                        req.pins.promote(&idx, &req.query, &mut page.results, limit);
+                    }
+                    outcome = SearchOutcome {
+                        results: page.results,
+                        total: page.total,
+                        totals: page.totals,
+                        appendable: false,
+                    };
+                }
                 let _ = done_tx.send(SearchDone {
                     id: req.id,
-                    results,
+                    query: req.query,
+                    total: outcome.total,
+                    totals: outcome.totals,
+                    results: outcome.results,
                     took: start.elapsed(),
+                    generation,
+                    appendable: outcome.appendable,
+                    unreachable,
                 });
+
+                // Counted after the results are out, so they don't wait
+                if !req.options.pinned_only
+                    && idx.volume_states().iter().filter(|v| v.enabled).count() > 1
+                {
+                    let _ = counts_tx.send(VolumeCounts {
+                        id: req.id,
+                        counts: idx.count_by_volume(&req.count_query),
+                    });
+                    worker_repaint.request_repaint();
+                }
             }
         });
 
@@ -114,33 +346,81 @@ impl SearchState {
             dirs_only: false,
             case_sensitive: false,
             use_regex: false,
+            show_pinned: false,
+            show_largest: false,
+            show_deleted: false,
+            typo_tolerance: true,
+            dedup_links: false,
+            hide_patterns: Vec::new(),
+            show_hidden_patterns: false,
+            scope: None,
+            scopes: Scopes::new(),
+            remotes: Vec::new(),
+            remote_timeout: Duration::ZERO,
+            unreachable: Vec::new(),
+            volume: None,
+            volume_counts: None,
+            folder: None,
             max_results: 5000,
+            debounce: Duration::from_millis(120),
+            min_query_len: 2,
             results: Vec::new(),
+            result_filter: String::new(),
+            completions: None,
+            filter_open: false,
+            total_matches: 0,
+            totals: ResultTotals::default(),
             selected: 0,
+            marked: BTreeSet::new(),
             search_time: Duration::from_millis(0),
             scroll_to_selected: false,
             error: None,
             shared_index,
             dirty: false,
             last_input_at: Instant::now(),
-            debounce: Duration::from_millis(120),
             last_query: String::new(),
             last_files_only: false,
             last_dirs_only: false,
             last_use_regex: false,
+            last_show_pinned: false,
+            last_show_largest: false,
+            last_show_deleted: false,
+            last_typo_tolerance: true,
+            last_dedup_links: false,
+            last_show_hidden_patterns: false,
+            last_scope: None,
+            last_volume: None,
+            last_folder: None,
+            last_max_results: 5000,
             last_index_generation: 0,
+            last_pins_generation: 0,
+            last_attached_generation: 0,
             req_tx,
             done_rx,
+            counts_rx,
             in_flight: false,
             last_request_id: 0,
             latest_applied_id: 0,
             prev_query: String::new(),
             prev_results: Vec::new(),
+            result_filter_query: None,
+            repaint,
+            index_changes,
+            refreshing: false,
+            updated_at: None,
+            displayed_query: None,
+            appendable: false,
             archived_view: None,
+            history: History::new(),
+            pins: Arc::new(PinStore::new()),
+            pins_generation: 0,
+            attached: Arc::new(Federation::new()),
+            attached_generation: 0,
         }
     }
 
     pub fn set_index(&mut self, index: Arc<Index>) {
+        self.index_changes = watch_index(&index, self.repaint.clone());
         self.shared_index.store(Arc::new(index));
         self.mark_dirty();
     }
@@ -150,24 +430,155 @@ impl SearchState {
         self.mark_dirty();
     }
 
-    fn current_generation(&self) -> u64 {
-        self.shared_index.load().generation()
+    pub fn pins(&self) -> &PinStore {
+        &self.pins
+    }
+
+    /// Replace the pin list, re-running the search so pinned items move.
+    pub fn set_pins(&mut self, pins: PinStore) {
+        self.pins = Arc::new(pins);
+        self.pins_generation += 1;
+        self.mark_dirty();
+    }
+
+    pub fn attached(&self) -> &Federation {
+        &self.attached
+    }
+
+    /// Replace the attached indexes, re-running the search to include them.
+    pub fn set_attached(&mut self, attached: Federation) {
+        self.attached = Arc::new(attached);
+        self.attached_generation += 1;
+        self.mark_dirty();
+    }
+
+    /// True if the results came from the typo-tolerant fallback.
+    pub fn is_fuzzy(&self) -> bool {
+        self.results.first().is_some_and(|r| r.fuzzy)
     }
 
     pub fn is_in_flight(&self) -> bool {
         self.in_flight
     }
 
+    /// Limit the search to a folder, or lift the limit.
+    pub fn set_folder(&mut self, folder: Option<String>) {
+        if self.folder != folder {
+            self.folder = folder;
+            self.mark_dirty();
+        }
+    }
+
     pub fn mark_dirty(&mut self) {
         self.dirty = true;
         self.last_input_at = Instant::now();
+        self.refreshing = false;
+    }
+
+    /// Bring the displayed results up to date with index changes.
+    ///
+    /// Changes on volumes the query can't match are skipped, and files
+    /// created elsewhere are appended if they match. Anything else re-runs
+    /// the query. While a search is pending, changes wait for its results.
+    pub fn poll_index_changes(&mut self) {
+        let displayed = self.show_pinned
+            || self.show_largest
+            || self.show_deleted
+            || !self.last_query.is_empty();
+        if !displayed {
+            self.index_changes.try_iter().for_each(drop);
+            return;
+        }
+        if self.dirty {
+            return;
+        }
+
+        let changes: Vec<IndexChange> = self
+            .index_changes
+            .try_iter()
+            .filter(|c| c.generation > self.last_index_generation)
+            .collect();
+        if changes.is_empty() {
+            return;
+        }
+        let Some(query) = self.displayed_query.clone() else {
+            return self.refresh();
+        };
+
+        let index = self.shared_index.load_full();
+        let volumes = index.volume_states();
+        let mut added = false;
+        for change in &changes {
+            let Some(volume_id) = &change.volume else {
+                return self.refresh();
+            };
+            // A removed volume may still have results on screen
+            let Some(volume) = volumes.iter().find(|v| &v.info.id == volume_id) else {
+                return self.refresh();
+            };
+            if !volume.enabled || !query.may_match_volume(&volume.info) {
+                continue;
+            }
+
+            let created = change
+                .created
+                .filter(|_| self.appendable)
+                .and_then(|id| index.get(volume_id, id));
+            let Some(record) = created else {
+                return self.refresh();
+            };
+            let shown = self
+                .results
+                .iter()
+                .any(|r| r.record.id == record.id && r.record.volume_id == record.volume_id);
+            if !shown && query.matches(&record) {
+                self.total_matches += 1;
+                self.totals.add(&record);
+                if let Some(counts) = &mut self.volume_counts {
+                    *counts.entry(record.volume_id.clone()).or_default() += 1;
+                }
+                if self.prev_results.len() < self.max_results {
+                    let result = SearchResult::new(record, 0);
+                    if self.passes_result_filter(&result.record) {
+                        self.results.push(result.clone());
+                    }
+                    self.prev_results.push(result);
+                }
+                added = true;
+            }
+        }
+
+        if let Some(change) = changes.last() {
+            self.last_index_generation = change.generation;
+        }
+        if added {
+            self.updated_at = Some(Instant::now());
+        }
+    }
+
+    /// Re-run the displayed query, showing the "updated" indicator after.
+    fn refresh(&mut self) {
+        // No debounce: the query itself hasn't changed
+        self.dirty = true;
+        self.refreshing = true;
+    }
+
+    /// True while the "updated" indicator should show.
+    pub fn recently_updated(&self) -> bool {
+        self.updated_at.is_some_and(|at| at.elapsed() < UPDATED_INDICATOR)
     }
 
     pub fn should_search(&self, index_generation: u64) -> bool {
         if self.in_flight {
             return false;
         }
-        if self.query.len() < 2 {
+        // The pinned, largest and deleted views are short enough to list
+        // without a query
+        if self.query.len() < self.min_query_len
+            && !self.show_pinned
+            && !self.show_largest
+            && !self.show_deleted
+        {
             return false;
         }
 
@@ -179,8 +590,12 @@ impl SearchState {
             return false;
         }
 
-        // If the index changed since last run, allow search
-        if index_generation != self.last_index_generation {
+        // If the index, pins or attached indexes changed since last run,
+        // allow search
+        if index_generation != self.last_index_generation
+            || self.pins_generation != self.last_pins_generation
+            || self.attached_generation != self.last_attached_generation
+        {
             return true;
         }
 
@@ -189,6 +604,16 @@ impl SearchState {
             || self.files_only != self.last_files_only
             || self.dirs_only != self.last_dirs_only
             || self.use_regex != self.last_use_regex
+            || self.show_pinned != self.last_show_pinned
+            || self.show_largest != self.last_show_largest
+            || self.show_deleted != self.last_show_deleted
+            || self.typo_tolerance != self.last_typo_tolerance
+            || self.dedup_links != self.last_dedup_links
+            || self.show_hidden_patterns != self.last_show_hidden_patterns
+            || self.scope != self.last_scope
+            || self.volume != self.last_volume
+            || self.folder != self.last_folder
+            || self.max_results != self.last_max_results
         {
             return true;
         }
@@ -199,27 +624,24 @@ impl SearchState {
     pub fn search(&mut self) {
         self.error = None;
 
-        // Build query
-        let mut query = if self.use_regex {
-            match glint_core::search::parse_query(&format!("r/{}/", self.query)) {
-                Ok(q) => q,
-                Err(e) => {
-                    self.error = Some(format!("Invalid regex: {}", e));
-                    self.results.clear();
-                    return;
-                }
+        // Folders of the selected scope and any scope: tokens in the query
+        let (pattern, folders) = match self.scope_folders() {
+            Ok(split) => split,
+            Err(e) => {
+                self.error = Some(e.to_string());
+                self.results.clear();
+                return;
             }
-        } else if self.query.contains('*') || self.query.contains('?') {
-            match SearchQuery::wildcard(&self.query) {
-                Ok(q) => q,
-                Err(e) => {
-                    self.error = Some(format!("Invalid pattern: {}", e));
-                    self.results.clear();
-                    return;
-                }
+        };
+
+        // Build query
+        let mut query = match self.pattern_query(&pattern) {
+            Ok(q) => q,
+            Err(e) => {
+                self.error = Some(e);
+                self.results.clear();
+                return;
             }
-        } else {
-            SearchQuery::substring(&self.query)
         };
 
         if self.files_only {
@@ -228,6 +650,35 @@ impl SearchState {
         if self.dirs_only {
             query = query.with_filter(glint_core::search::SearchFilter::DirsOnly);
         }
+        if !self.show_hidden_patterns {
+            for pattern in &self.hide_patterns {
+                match glint_core::search::SearchFilter::exclude_glob(pattern) {
+                    Ok(filter) => query = query.with_filter(filter),
+                    Err(e) => {
+                        self.error = Some(format!("Invalid hide pattern: {}", e));
+                        self.results.clear();
+                        return;
+                    }
+                }
+            }
+        }
+        if self.show_deleted {
+            query = query.search_deleted(true);
+        }
+        let scope_filter =
+            (!folders.is_empty()).then(|| glint_core::search::SearchFilter::in_folders(&folders));
+        if let Some(filter) = &scope_filter {
+            query = query.with_filter(filter.clone());
+        }
+        if let Some(folder) = &self.folder {
+            query = query.with_filter(glint_core::search::SearchFilter::in_folders(
+                std::slice::from_ref(folder),
+            ));
+        }
+        let count_query = query.clone();
+        if let Some(volume) = &self.volume {
+            query = query.with_filter(glint_core::search::SearchFilter::Volume(volume.clone()));
+        }
 
         // If the new query is a simple extension of the previous query and filters are unchanged,
         // try incremental narrowing by filtering previous results on the UI thread for snappy feedback.
@@ -237,32 +688,31 @@ impl SearchState {
             && self.files_only == self.last_files_only
             && self.dirs_only == self.last_dirs_only
             && self.use_regex == self.last_use_regex
+            && self.show_pinned == self.last_show_pinned
+            && self.show_largest == self.last_show_largest
+            && self.show_deleted == self.last_show_deleted
+            && self.show_hidden_patterns == self.last_show_hidden_patterns
+            && self.scope == self.last_scope
+            && self.volume == self.last_volume
+            && self.folder == self.last_folder
         {
             let start = Instant::now();
             // Build matcher for the new query
-            let narrowed_query = if self.use_regex {
-                match glint_core::search::parse_query(&format!("r/{}/", self.query)) {
-                    Ok(q) => q,
-                    Err(e) => {
-                        self.error = Some(format!("Invalid regex: {}", e));
-                        return;
-                    }
-                }
-            } else if self.query.contains('*') || self.query.contains('?') {
-                match SearchQuery::wildcard(&self.query) {
-                    Ok(q) => q,
-                    Err(e) => {
-                        self.error = Some(format!("Invalid pattern: {}", e));
-                        return;
-                    }
+            let mut narrowed_query = match self.pattern_query(&pattern) {
+                Ok(q) => q,
+                Err(e) => {
+                    self.error = Some(e);
+                    return;
                 }
-            } else {
-                SearchQuery::substring(&self.query)
             };
+            // A scope: token typed since the last search narrows by folder
+            if let Some(filter) = scope_filter {
+                narrowed_query = narrowed_query.with_filter(filter);
+            }
 
             let mut filtered = Vec::with_capacity(self.max_results.min(self.prev_results.len()));
             for r in self.prev_results.iter() {
-                if narrowed_query.matches(&r.record) {
+                if narrowed_query.matches(&r.record) && self.passes_result_filter(&r.record) {
                     filtered.push(r.clone());
                     if filtered.len() >= self.max_results { break; }
                 }
@@ -276,49 +726,282 @@ impl SearchState {
         // Dispatch async search request (authoritative)
         self.last_request_id = self.last_request_id.wrapping_add(1);
         let id = self.last_request_id;
-        let max_results = self.max_results;
+        let options = SearchOptions {
+            max_results: self.max_results,
+            pinned_only: self.show_pinned,
+            largest_only: self.show_largest,
+            deleted_only: self.show_deleted,
+            typo_tolerance: self.typo_tolerance,
+            dedup_links: self.dedup_links,
+        };
         let archived = self.archived_view.clone();
-        if self.req_tx.send(SearchRequest { id, query, max_results, archived }).is_ok() {
+        let pins = Arc::clone(&self.pins);
+        let attached = Arc::clone(&self.attached);
+        // A selected volume or folder is on this machine
+        let remote = (!self.remotes.is_empty()
+            && !self.show_pinned
+            && !self.show_largest
+            && !self.show_deleted
+            && self.volume.is_none()
+            && self.folder.is_none())
+        .then(|| {
+            let pattern = match self.use_regex {
+                true => format!("r/{}/", pattern),
+                false => pattern.clone(),
+            };
+            let mut request = QueryRequest::new(pattern, self.max_results);
+            request.files_only = self.files_only;
+            request.dirs_only = self.dirs_only;
+            request.scope = folders.clone();
+            if !self.show_hidden_patterns {
+                request.exclude = self.hide_patterns.clone();
+            }
+            request
+        });
+        let scoped = !folders.is_empty() || self.volume.is_some() || self.folder.is_some();
+        let filters = format!(
+            "{:?}",
+            (
+                self.files_only,
+                self.dirs_only,
+                self.use_regex,
+                (!self.show_hidden_patterns).then_some(&self.hide_patterns),
+                &self.volume,
+                &self.folder,
+                &folders
+            )
+        );
+        let cache_key = CacheKey::new(
+            &self.query,
+            filters,
+            self.scope.as_deref(),
+            &options,
+            self.pins_generation,
+        );
+        if self
+            .req_tx
+            .send(SearchRequest {
+                id,
+                query,
+                options,
+                archived,
+                pins,
+                attached,
+                remote,
+                remotes: self.remotes.clone(),
+                remote_timeout: self.remote_timeout,
+                scoped,
+                count_query,
+                cache_key,
+            })
+            .is_ok()
+        {
             self.in_flight = true;
         }
     }
-}
 
-fn cstr_from_bytes_local(bytes: &[u8]) -> &str {
-    let mut end = 0;
-    while end < bytes.len() && bytes[end] != 0 { end += 1; }
-    std::str::from_utf8(&bytes[..end]).unwrap_or("")
+    /// The query for `pattern`: the whole of it as a regex in regex mode,
+    /// otherwise read with the query syntax, tokens and all.
+    fn pattern_query(&self, pattern: &str) -> Result<SearchQuery, String> {
+        if self.use_regex {
+            glint_core::search::parse_query(&format!("r/{}/", pattern))
+                .map_err(|e| format!("Invalid regex: {}", e))
+        } else {
+            glint_core::search::parse_query(pattern).map_err(|e| format!("Invalid query: {}", e))
+        }
+    }
+
+    /// Parts of the query to underline, with why each is wrong. A regex is
+    /// checked when searching instead.
+    pub fn query_problems(&self) -> Vec<(Range<usize>, String)> {
+        if self.use_regex {
+            return Vec::new();
+        }
+        query_problems(&self.scopes, &self.query)
+    }
+
+    /// Suggestions for the query token at byte `cursor`.
+    pub fn complete(&self, index: &Index, cursor: usize) -> Option<Completions> {
+        if self.use_regex {
+            return None;
+        }
+        hints::complete(index, &self.scopes, &self.query, cursor)
+    }
+
+    /// Split `scope:` tokens off the query and collect the folders to search.
+    fn scope_folders(&self) -> glint_core::Result<(String, Vec<String>)> {
+        let (pattern, mut folders) = self.scopes.split_query(&self.query)?;
+        if let Some(name) = &self.scope {
+            folders.extend_from_slice(self.scopes.resolve(name)?);
+        }
+        Ok((pattern, folders))
+    }
 }
 
 impl SearchState {
     pub fn poll_results(&mut self) {
         while let Ok(done) = self.done_rx.try_recv() {
             if done.id >= self.latest_applied_id {
-                self.results = done.results;
+                self.prev_results = done.results;
+                self.results = self.filter_retrieved();
+                self.total_matches = done.total;
+                self.totals = done.totals;
+                self.volume_counts = None;
                 self.selected = 0.min(self.results.len().saturating_sub(1));
                 self.search_time = done.took;
                 self.latest_applied_id = done.id;
                 self.in_flight = false;
                 self.prev_query = self.last_query.clone();
-                self.prev_results = self.results.clone();
 
                 // Update last-run snapshot
                 self.last_query = self.query.clone();
                 self.last_files_only = self.files_only;
                 self.last_dirs_only = self.dirs_only;
                 self.last_use_regex = self.use_regex;
-                self.last_index_generation = self.current_generation();
+                self.last_show_pinned = self.show_pinned;
+                self.last_show_largest = self.show_largest;
+                self.last_show_deleted = self.show_deleted;
+                self.last_typo_tolerance = self.typo_tolerance;
+                self.last_dedup_links = self.dedup_links;
+                self.last_show_hidden_patterns = self.show_hidden_patterns;
+                self.last_scope = self.scope.clone();
+                self.last_volume = self.volume.clone();
+                self.last_folder = self.folder.clone();
+                self.last_max_results = self.max_results;
+                self.last_index_generation = done.generation;
+                self.displayed_query = Some(done.query);
+                self.appendable = done.appendable;
+                self.last_pins_generation = self.pins_generation;
+                self.last_attached_generation = self.attached_generation;
+                self.unreachable = done.unreachable;
                 self.dirty = false;
+                if self.refreshing {
+                    self.updated_at = Some(Instant::now());
+                    self.refreshing = false;
+                }
             }
         }
+        while let Ok(counts) = self.counts_rx.try_recv() {
+            if counts.id == self.latest_applied_id {
+                self.volume_counts = Some(counts.counts);
+            }
+        }
+    }
+
+    /// Narrow the retrieved results to those matching `result_filter`,
+    /// without searching the index again.
+    ///
+    /// The filter takes the same syntax as the search box (`ext:pdf`,
+    /// `*.log`, `r/regex/`, `path:`); an invalid one shows an error and
+    /// hides nothing.
+    pub fn filter_results(&mut self) {
+        self.error = None;
+        self.result_filter_query = match self.result_filter.trim() {
+            "" => None,
+            filter => match glint_core::search::parse_query(filter) {
+                Ok(query) => Some(query),
+                Err(e) => {
+                    self.error = Some(format!("Invalid filter: {}", e));
+                    None
+                }
+            },
+        };
+        self.results = self.filter_retrieved();
+        self.selected = 0;
+        self.scroll_to_selected = true;
+    }
+
+    /// Close the result filter and show all retrieved results again.
+    pub fn close_filter(&mut self) {
+        self.filter_open = false;
+        self.result_filter.clear();
+        self.filter_results();
+    }
+
+    /// Number of results retrieved, before `result_filter` narrows them.
+    pub fn retrieved(&self) -> usize {
+        self.prev_results.len()
+    }
+
+    fn passes_result_filter(&self, record: &FileRecord) -> bool {
+        self.result_filter_query
+            .as_ref()
+            .map_or(true, |query| query.matches(record))
+    }
+
+    fn filter_retrieved(&self) -> Vec<SearchResult> {
+        match &self.result_filter_query {
+            Some(query) => self
+                .prev_results
+                .iter()
+                .filter(|r| query.matches(&r.record))
+                .cloned()
+                .collect(),
+            None => self.prev_results.clone(),
+        }
     }
 
     pub fn clear(&mut self) {
         self.results.clear();
+        self.prev_results.clear();
+        self.total_matches = 0;
+        self.totals = ResultTotals::default();
+        self.volume_counts = None;
         self.selected = 0;
+        self.marked.clear();
         self.error = None;
     }
 
+    /// Select a clicked row. With `toggle` (Ctrl) the row is added to or
+    /// taken from the marked results, with `extend` (Shift) the rows from
+    /// the selection to it are marked, and otherwise the marks are cleared.
+    pub fn click_row(&mut self, row: usize, toggle: bool, extend: bool) {
+        let path = |row: usize| self.results.get(row).map(|r| r.record.path.clone());
+        if extend {
+            let rows = self.selected.min(row)..=self.selected.max(row);
+            let paths: Vec<String> = rows.filter_map(path).collect();
+            self.marked.extend(paths);
+        } else if toggle {
+            // The first Ctrl+click keeps the row selected before it
+            let selected = path(self.selected).filter(|_| self.marked.is_empty());
+            let clicked = path(row);
+            self.marked.extend(selected);
+            if let Some(clicked) = clicked {
+                if !self.marked.remove(&clicked) {
+                    self.marked.insert(clicked);
+                }
+            }
+        } else {
+            self.marked.clear();
+        }
+        self.selected = row;
+    }
+
+    pub fn is_marked(&self, row: usize) -> bool {
+        self.results
+            .get(row)
+            .is_some_and(|r| self.marked.contains(&r.record.path))
+    }
+
+    /// Results an action on `row` applies to: all marked results if `row`
+    /// is one of them, otherwise just `row`.
+    #[cfg(windows)]
+    pub fn targets(&self, row: usize) -> Vec<FileRecord> {
+        if self.is_marked(row) {
+            self.results
+                .iter()
+                .filter(|r| self.marked.contains(&r.record.path))
+                .map(|r| r.record.clone())
+                .collect()
+        } else {
+            self.results
+                .get(row)
+                .map(|r| r.record.clone())
+                .into_iter()
+                .collect()
+        }
+    }
+
     pub fn select_previous(&mut self) {
         if self.selected > 0 {
             self.selected -= 1;
@@ -355,21 +1038,162 @@ impl SearchState {
         }
     }
 
-    pub fn open_selected(&self) {
+    pub fn open_selected(&mut self) {
         if let Some(result) = self.results.get(self.selected) {
-            let _ = open::that(&result.record.path);
+            let path = result.record.path.clone();
+            if actions::open(&path).is_ok() {
+                self.history.record(HistoryAction::Opened(path));
+            }
         }
     }
 
-    pub fn copy_selected_path(&self) -> Result<(), String> {
-        if let Some(result) = self.results.get(self.selected) {
-            let mut clipboard = arboard::Clipboard::new().map_err(|e| e.to_string())?;
-            clipboard
-                .set_text(result.record.path.clone())
-                .map_err(|e| e.to_string())?;
-            Ok(())
-        } else {
-            Err("No selection".into())
+    pub fn copy_selected_path(&mut self, format: PathFormat) -> Result<(), String> {
+        let Some(result) = self.results.get(self.selected) else {
+            return Err("No selection".into());
+        };
+        let path = result.record.path.clone();
+        actions::copy_path(&path, format)?;
+        self.history.record(HistoryAction::Copied(path));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glint_core::{FileId, VolumeInfo};
+
+    fn make_state(names: &[&str]) -> SearchState {
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS");
+        let records = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                FileRecord::new(
+                    FileId::new(10 + i as u64),
+                    None,
+                    volume.id.clone(),
+                    name.to_string(),
+                    format!("C:\\{}", name),
+                    false,
+                )
+            })
+            .collect();
+        let index = Arc::new(Index::new());
+        index.add_volume_records(&volume, records);
+        SearchState::new(index, egui::Context::default())
+    }
+
+    /// Search for `query` and wait for the results.
+    fn search_for(state: &mut SearchState, query: &str) {
+        state.query = query.to_string();
+        state.search();
+        wait(state);
+    }
+
+    fn wait(state: &mut SearchState) {
+        let start = Instant::now();
+        while state.is_in_flight() {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(5));
+            state.poll_results();
         }
     }
+
+    fn names(state: &SearchState) -> Vec<&str> {
+        let mut names: Vec<&str> = state
+            .results
+            .iter()
+            .map(|r| r.record.name.as_str())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_filter_results() {
+        let mut state = make_state(&["report.pdf", "report.txt", "report_2.pdf", "notes.pdf"]);
+        search_for(&mut state, "report");
+        assert_eq!(names(&state), ["report_2.pdf", "report.pdf", "report.txt"]);
+
+        state.result_filter = "ext:pdf".to_string();
+        state.filter_results();
+        assert_eq!(names(&state), ["report_2.pdf", "report.pdf"]);
+        assert_eq!(state.retrieved(), 3);
+
+        // Typing on narrows within the filter, and so does the search
+        // that follows
+        state.query = "report_".to_string();
+        state.search();
+        assert_eq!(names(&state), ["report_2.pdf"]);
+        wait(&mut state);
+        assert_eq!(names(&state), ["report_2.pdf"]);
+        search_for(&mut state, "report");
+        assert_eq!(names(&state), ["report_2.pdf", "report.pdf"]);
+
+        // An invalid filter hides nothing
+        state.result_filter = "r/(/".to_string();
+        state.filter_results();
+        let error = state.error.as_deref().unwrap_or_default();
+        assert!(error.starts_with("Invalid filter"));
+        assert_eq!(state.results.len(), 3);
+
+        state.result_filter = "*.txt".to_string();
+        state.filter_results();
+        assert_eq!(names(&state), ["report.txt"]);
+        state.close_filter();
+        assert!(!state.filter_open && state.result_filter.is_empty());
+        assert_eq!(state.results.len(), 3);
+        assert_eq!(state.error, None);
+    }
+
+    #[test]
+    fn test_click_row() {
+        let mut state = make_state(&["a.txt", "b.txt", "c.txt", "d.txt"]);
+        search_for(&mut state, "txt");
+        let marked = |state: &SearchState| -> Vec<usize> {
+            (0..state.results.len())
+                .filter(|&row| state.is_marked(row))
+                .collect()
+        };
+
+        // Ctrl+click marks the selected row too, and unmarks a marked one
+        state.click_row(1, false, false);
+        state.click_row(3, true, false);
+        assert_eq!(marked(&state), [1, 3]);
+        state.click_row(1, true, false);
+        assert_eq!(marked(&state), [3]);
+
+        // Shift+click marks the rows from the selected one
+        state.click_row(0, false, true);
+        assert_eq!(marked(&state), [0, 1, 3]);
+        assert_eq!(state.selected, 0);
+
+        state.click_row(2, false, false);
+        assert!(marked(&state).is_empty());
+        assert_eq!(state.selected, 2);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_targets() {
+        let mut state = make_state(&["a.txt", "b.txt", "c.txt"]);
+        search_for(&mut state, "txt");
+        let path: Vec<String> = state
+            .results
+            .iter()
+            .map(|r| r.record.path.clone())
+            .collect();
+        let paths = |records: Vec<FileRecord>| -> Vec<String> {
+            records.into_iter().map(|r| r.path).collect()
+        };
+
+        state.click_row(0, false, false);
+        state.click_row(2, true, false);
+        // An action on a marked row applies to all marked rows, on any
+        // other row to that row alone
+        assert_eq!(paths(state.targets(2)), [&*path[0], &*path[2]]);
+        assert_eq!(paths(state.targets(1)), [&*path[1]]);
+        assert!(state.targets(5).is_empty());
+    }
 }
@@ -4,8 +4,11 @@
 //! - Installing/uninstalling the Glint background service
 //! - Starting/stopping the service
 //! - Checking service status
+//! - Installing/removing the scheduled task alternative
 //!
-//! The service monitors USN journals for real-time index updates.
+//! The service (`glint-svc.exe`) monitors USN journals for real-time index
+//! updates. The scheduled task instead runs `glint watch --once` periodically
+//! or at logon, and can be installed without administrator privileges.
 
 #[cfg(windows)]
 mod windows_service {
@@ -90,17 +93,28 @@ mod windows_service {
         }
     }
 
-    /// Get the service executable path
-    fn get_service_exe_path() -> io::Result<PathBuf> {
-        let install_dir = std::env::var("LOCALAPPDATA")
+    fn get_install_dir() -> PathBuf {
+        std::env::var("LOCALAPPDATA")
             .map(PathBuf::from)
             .unwrap_or_else(|_| PathBuf::from("."))
             .join("Programs")
-            .join("Glint");
+            .join("Glint")
+    }
 
-        // For now, use the CLI exe for the service
-        // In the future, we might have a dedicated service exe
-        Ok(install_dir.join("glint.exe"))
+    /// Get the service executable path: the installed `glint-svc.exe`, or
+    /// the one next to this executable when running uninstalled.
+    fn get_service_exe_path() -> io::Result<PathBuf> {
+        let installed = get_install_dir().join(crate::installer::SERVICE_EXE);
+        if installed.exists() {
+            return Ok(installed);
+        }
+        let beside = std::env::current_exe()?.with_file_name(crate::installer::SERVICE_EXE);
+        Ok(if beside.exists() { beside } else { installed })
+    }
+
+    /// Get the CLI executable path, used by the scheduled task
+    fn get_cli_exe_path() -> io::Result<PathBuf> {
+        Ok(get_install_dir().join("glint.exe"))
     }
 
     /// Get current service status
@@ -164,8 +178,16 @@ mod windows_service {
             ));
         }
 
-        // Service command: glint.exe watch --service
-        let service_command = format!("\"{}\" watch --service", exe_path.to_string_lossy());
+        // The service runs as LocalSystem, so point it at this user's config
+        // and index rather than its own profile
+        let config = glint_core::Config::load().unwrap_or_default();
+        let mut service_command = format!("\"{}\"", exe_path.to_string_lossy());
+        if let Ok(path) = glint_core::Config::default_config_path() {
+            service_command.push_str(&format!(" --config \"{}\"", path.to_string_lossy()));
+        }
+        if let Ok(dir) = config.index_dir() {
+            service_command.push_str(&format!(" --data-dir \"{}\"", dir.to_string_lossy()));
+        }
 
         unsafe {
             let sc_manager = OpenSCManagerW(
@@ -361,6 +383,33 @@ mod windows_service {
         }
     }
 
+    pub use glint_backend_ntfs::scheduled_task::{TaskStatus, TaskTrigger};
+
+    /// Get current scheduled task status
+    pub fn get_task_status() -> TaskStatus {
+        glint_backend_ntfs::scheduled_task::status()
+    }
+
+    /// Install the scheduled task running `glint.exe watch --once`
+    pub fn install_task(trigger: TaskTrigger) -> io::Result<()> {
+        let exe_path = get_cli_exe_path()?;
+        if !exe_path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Glint executable not found: {:?}", exe_path),
+            ));
+        }
+
+        glint_backend_ntfs::scheduled_task::install(&exe_path, trigger)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))
+    }
+
+    /// Remove the scheduled task
+    pub fn uninstall_task() -> io::Result<()> {
+        glint_backend_ntfs::scheduled_task::uninstall()
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))
+    }
+
     /// Request elevation and restart for service operations
     pub fn request_elevation_for_service(operation: &str) -> io::Result<()> {
         use std::process::Command;
@@ -467,3 +516,45 @@ pub fn request_elevation_for_service(_operation: &str) -> std::io::Result<()> {
         "Service not supported on this platform",
     ))
 }
+
+#[cfg(not(windows))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskStatus {
+    NotInstalled,
+    Ready,
+    Running,
+    Disabled,
+    Unknown,
+}
+
+#[cfg(not(windows))]
+impl std::fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Not supported on this platform")
+    }
+}
+
+#[cfg(not(windows))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskTrigger {
+    Every(u32),
+    AtLogon,
+}
+
+#[cfg(not(windows))]
+pub fn get_task_status() -> TaskStatus {
+    TaskStatus::NotInstalled
+}
+
+#[cfg(not(windows))]
+pub fn install_task(_trigger: TaskTrigger) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Scheduled task not supported on this platform",
+    ))
+}
+
+#[cfg(not(windows))]
+pub fn uninstall_task() -> std::io::Result<()> {
+    Ok(())
+}
@@ -1,25 +1,118 @@
 //! Application settings persistence.
 
+use eframe::egui;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+/// Default width of the name column in the results
+pub const NAME_COLUMN_WIDTH: f32 = 230.0;
+
 /// Application settings
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
     /// Volumes to index
     pub indexed_volumes: Vec<char>,
+    /// Volumes without a drive letter to index, by mount folder
+    pub indexed_mount_folders: Vec<String>,
     /// Maximum search results to display
-    pub max_results: usize,
+    pub result_limit: usize,
+    /// Delay after the last keystroke before searching, in milliseconds
+    pub search_debounce_ms: u64,
+    /// Shortest query that starts a search
+    pub min_query_len: usize,
     /// Enable real-time monitoring service
     pub service_enabled: bool,
+    /// Shortcut overrides by action name (empty = unbound)
+    pub key_bindings: BTreeMap<String, String>,
+    /// Busy folders the user chose not to exclude
+    pub dismissed_busy_folders: Vec<String>,
+    /// Main window size and position when it was last closed
+    pub window: Option<WindowGeometry>,
+    /// Width of the name column in the results, in points
+    pub name_column_width: f32,
+    /// Shortcut bringing up the launcher from anywhere (empty = none)
+    pub launcher_hotkey: String,
+    /// Draw with the high-contrast colors (None = follow Windows)
+    pub high_contrast: Option<bool>,
+    /// Scroll and open panels without animation (None = follow Windows)
+    pub reduced_motion: Option<bool>,
+}
+
+/// Size and position of the main window, in points.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    /// Left edge of the window frame
+    pub x: f32,
+    /// Top edge of the window frame
+    pub y: f32,
+    /// Width of the window contents
+    pub width: f32,
+    /// Height of the window contents
+    pub height: f32,
+    pub maximized: bool,
+    /// Pixels per point on the window's monitor
+    pub pixels_per_point: f32,
+}
+
+impl WindowGeometry {
+    /// Whether enough of the title bar lies on a desktop spanning `desktop`
+    /// (left, top, right, bottom, in pixels) to grab the window. A monitor
+    /// that has since been unplugged can leave it entirely off screen.
+    pub fn on_screen(&self, desktop: [i32; 4]) -> bool {
+        let [left, top, right, bottom] = desktop.map(|edge| edge as f32);
+        let ppp = self.pixels_per_point;
+        let (x, y, width) = (self.x * ppp, self.y * ppp, self.width * ppp);
+        let visible = (x + width).min(right) - x.max(left);
+        visible >= 100.0 && y >= top && y + 30.0 <= bottom
+    }
+
+    /// The geometry to remember for a window in `viewport`, or `None`
+    /// while it is minimized or not yet placed. A maximized window keeps
+    /// the `previous` size and position, to restore to.
+    pub fn of_viewport(
+        viewport: &egui::ViewportInfo,
+        previous: Option<WindowGeometry>,
+    ) -> Option<Self> {
+        if viewport.minimized.unwrap_or(false) {
+            return None;
+        }
+        let maximized = viewport.maximized.unwrap_or(false);
+        if let (true, Some(previous)) = (maximized, previous) {
+            return Some(WindowGeometry {
+                maximized,
+                ..previous
+            });
+        }
+        let (outer, inner) = (viewport.outer_rect?, viewport.inner_rect?);
+        Some(WindowGeometry {
+            x: outer.min.x,
+            y: outer.min.y,
+            width: inner.width(),
+            height: inner.height(),
+            maximized,
+            pixels_per_point: viewport.native_pixels_per_point.unwrap_or(1.0),
+        })
+    }
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
             indexed_volumes: Vec::new(),
-            max_results: 100,
+            indexed_mount_folders: Vec::new(),
+            result_limit: 5000,
+            search_debounce_ms: 120,
+            min_query_len: 2,
             service_enabled: true,
+            key_bindings: BTreeMap::new(),
+            dismissed_busy_folders: Vec::new(),
+            window: None,
+            name_column_width: NAME_COLUMN_WIDTH,
+            launcher_hotkey: "Ctrl+Alt+Space".to_string(),
+            high_contrast: None,
+            reduced_motion: None,
         }
     }
 }
@@ -55,3 +148,109 @@ impl Settings {
         Ok(dirs.config_dir().join("settings.json"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(x: f32, y: f32) -> WindowGeometry {
+        WindowGeometry {
+            x,
+            y,
+            width: 800.0,
+            height: 600.0,
+            maximized: false,
+            pixels_per_point: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_window_on_screen() {
+        let desktop = [0, 0, 1920, 1080];
+        assert!(window(100.0, 100.0).on_screen(desktop));
+        // Mostly off to the left, with the title bar still reachable
+        assert!(window(-650.0, 100.0).on_screen(desktop));
+        assert!(!window(-750.0, 100.0).on_screen(desktop));
+        assert!(!window(100.0, -10.0).on_screen(desktop));
+        assert!(!window(100.0, 1060.0).on_screen(desktop));
+
+        // On a second monitor, left of the main one, since unplugged
+        let left_monitor = window(-1500.0, 100.0);
+        assert!(left_monitor.on_screen([-1920, 0, 1920, 1080]));
+        assert!(!left_monitor.on_screen(desktop));
+
+        // Positions are in points, the desktop in pixels
+        let scaled = WindowGeometry {
+            pixels_per_point: 2.0,
+            ..window(1000.0, 100.0)
+        };
+        assert!(!scaled.on_screen(desktop));
+        assert!(scaled.on_screen([0, 0, 3840, 2160]));
+    }
+
+    #[test]
+    fn test_window_of_viewport() {
+        let mut viewport = egui::ViewportInfo {
+            outer_rect: Some(egui::Rect::from_min_size(
+                egui::pos2(50.0, 40.0),
+                egui::vec2(820.0, 640.0),
+            )),
+            inner_rect: Some(egui::Rect::from_min_size(
+                egui::pos2(60.0, 70.0),
+                egui::vec2(800.0, 600.0),
+            )),
+            native_pixels_per_point: Some(1.5),
+            ..Default::default()
+        };
+        let placed = WindowGeometry::of_viewport(&viewport, None).unwrap();
+        assert_eq!(
+            placed,
+            WindowGeometry {
+                x: 50.0,
+                y: 40.0,
+                pixels_per_point: 1.5,
+                ..window(0.0, 0.0)
+            }
+        );
+
+        // Maximized, the size to restore to is kept
+        viewport.maximized = Some(true);
+        viewport.inner_rect = Some(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(1920.0, 1040.0),
+        ));
+        let maximized = WindowGeometry::of_viewport(&viewport, Some(placed)).unwrap();
+        assert_eq!(
+            maximized,
+            WindowGeometry {
+                maximized: true,
+                ..placed
+            }
+        );
+
+        viewport.minimized = Some(true);
+        assert_eq!(WindowGeometry::of_viewport(&viewport, Some(placed)), None);
+        assert_eq!(
+            WindowGeometry::of_viewport(&egui::ViewportInfo::default(), None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_settings_without_layout() {
+        // Settings saved before the layout was remembered
+        let settings: Settings = serde_json::from_str(r#"{"result_limit": 500}"#).unwrap();
+        assert_eq!(settings.window, None);
+        assert_eq!(settings.name_column_width, NAME_COLUMN_WIDTH);
+
+        let settings = Settings {
+            window: Some(window(10.0, 20.0)),
+            name_column_width: 300.0,
+            ..settings
+        };
+        let json = serde_json::to_string(&settings).unwrap();
+        let loaded: Settings = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.window, settings.window);
+        assert_eq!(loaded.name_column_width, 300.0);
+    }
+}
@@ -0,0 +1,187 @@
+//! Windows shell integration for search results.
+//!
+//! This module handles:
+//! - Showing the "Open with" dialog for a file
+//! - Hosting the native Explorer context menu for a file, so every shell
+//!   extension (Send To, archivers, version control, ...) is available
+//!   from the results list
+//! - Copying and moving files with Explorer's progress and conflict dialogs
+//!
+//! All require a foreground window to own the dialog or menu, and must be
+//! called from the UI thread. The module is only compiled on Windows.
+
+use std::ffi::OsStr;
+use std::io;
+use std::os::windows::ffi::OsStrExt;
+use windows::core::{PCSTR, PCWSTR};
+use windows::Win32::Foundation::{HWND, POINT};
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_APARTMENTTHREADED,
+};
+use windows::Win32::UI::Shell::{
+    BHID_SFUIObject, FileOperation, IContextMenu, IFileOperation, IShellItem,
+    SHCreateItemFromParsingName, SHOpenWithDialog, CMF_NORMAL, CMINVOKECOMMANDINFO,
+    FOF_ALLOWUNDO, FOF_NOCONFIRMMKDIR, OAIF_ALLOW_REGISTRATION, OAIF_EXEC, OAIF_REGISTER_EXT,
+    OPENASINFO,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    CreatePopupMenu, DestroyMenu, GetCursorPos, GetForegroundWindow, TrackPopupMenuEx,
+    SW_SHOWNORMAL, TPM_RETURNCMD, TPM_RIGHTBUTTON,
+};
+
+/// First command ID handed to the shell; 0 means "menu dismissed".
+const FIRST_COMMAND: u32 = 1;
+const LAST_COMMAND: u32 = 0x7FFF;
+
+/// Convert string to wide null-terminated
+fn to_wide(s: &str) -> Vec<u16> {
+    OsStr::new(s)
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect()
+}
+
+fn check_path(path: &str) -> io::Result<()> {
+    if path.is_empty() || !std::path::Path::new(path).exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("File not found: {}", path),
+        ));
+    }
+    Ok(())
+}
+
+/// Show the Windows "Open with" dialog for a file.
+pub fn open_with(path: &str) -> io::Result<()> {
+    check_path(path)?;
+    let file = to_wide(path);
+
+    let info = OPENASINFO {
+        pcszFile: PCWSTR(file.as_ptr()),
+        pcszClass: PCWSTR::null(),
+        oaifInFlags: OAIF_ALLOW_REGISTRATION | OAIF_REGISTER_EXT | OAIF_EXEC,
+    };
+
+    unsafe {
+        SHOpenWithDialog(GetForegroundWindow(), &info)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))
+    }
+}
+
+/// Show the native Explorer context menu for a file at the cursor position
+/// and run the chosen command.
+///
+/// Returns `Ok(false)` if the menu was dismissed without a selection.
+/// Submenus that shell extensions populate lazily through
+/// `IContextMenu2`/`IContextMenu3` window messages (e.g. "Send to") may
+/// appear empty, since egui owns the window procedure.
+pub fn show_context_menu(path: &str) -> io::Result<bool> {
+    check_path(path)?;
+    let file = to_wide(path);
+    let to_io = |e: windows::core::Error| io::Error::new(io::ErrorKind::Other, e.to_string());
+
+    unsafe {
+        // The UI thread is normally already in an STA (winit uses OLE for
+        // drag and drop); this is a no-op in that case.
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+
+        let item: IShellItem =
+            SHCreateItemFromParsingName(PCWSTR(file.as_ptr()), None).map_err(to_io)?;
+        let context_menu: IContextMenu =
+            item.BindToHandler(None, &BHID_SFUIObject).map_err(to_io)?;
+
+        let menu = CreatePopupMenu().map_err(to_io)?;
+        let result = (|| {
+            context_menu
+                .QueryContextMenu(menu, 0, FIRST_COMMAND, LAST_COMMAND, CMF_NORMAL)
+                .map_err(to_io)?;
+
+            let hwnd: HWND = GetForegroundWindow();
+            let mut cursor = POINT::default();
+            GetCursorPos(&mut cursor).map_err(to_io)?;
+
+            let command = TrackPopupMenuEx(
+                menu,
+                (TPM_RETURNCMD | TPM_RIGHTBUTTON).0,
+                cursor.x,
+                cursor.y,
+                hwnd,
+                None,
+            )
+            .0 as u32;
+
+            if command < FIRST_COMMAND {
+                return Ok(false);
+            }
+
+            // Commands are passed as an offset via MAKEINTRESOURCE
+            let invoke = CMINVOKECOMMANDINFO {
+                cbSize: std::mem::size_of::<CMINVOKECOMMANDINFO>() as u32,
+                hwnd,
+                lpVerb: PCSTR((command - FIRST_COMMAND) as usize as *const u8),
+                nShow: SW_SHOWNORMAL.0,
+                ..Default::default()
+            };
+            context_menu.InvokeCommand(&invoke).map_err(to_io)?;
+            Ok(true)
+        })();
+
+        let _ = DestroyMenu(menu);
+        result
+    }
+}
+
+/// Whether [`transfer`] copies or moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transfer {
+    Copy,
+    Move,
+}
+
+/// Copy or move files and folders into `destination` the way Explorer
+/// does, with its progress dialog and its questions about name conflicts.
+///
+/// Runs a modal loop until the operation finishes. Returns `Ok(false)` if
+/// the user cancelled or skipped some of the items.
+pub fn transfer(paths: &[&str], destination: &str, mode: Transfer) -> io::Result<bool> {
+    check_path(destination)?;
+    let to_io = |e: windows::core::Error| io::Error::new(io::ErrorKind::Other, e.to_string());
+
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+
+        let operation: IFileOperation =
+            CoCreateInstance(&FileOperation, None, CLSCTX_ALL).map_err(to_io)?;
+        operation
+            .SetOwnerWindow(GetForegroundWindow())
+            .map_err(to_io)?;
+        operation
+            .SetOperationFlags(FOF_ALLOWUNDO | FOF_NOCONFIRMMKDIR)
+            .map_err(to_io)?;
+
+        let destination = to_wide(destination);
+        let folder: IShellItem =
+            SHCreateItemFromParsingName(PCWSTR(destination.as_ptr()), None).map_err(to_io)?;
+        for path in paths {
+            let file = to_wide(path);
+            let item: IShellItem =
+                SHCreateItemFromParsingName(PCWSTR(file.as_ptr()), None).map_err(to_io)?;
+            match mode {
+                Transfer::Copy => operation.CopyItem(&item, &folder, PCWSTR::null(), None),
+                Transfer::Move => operation.MoveItem(&item, &folder, PCWSTR::null(), None),
+            }
+            .map_err(to_io)?;
+        }
+
+        // Cancelling fails the whole call, though items already done stay
+        let performed = operation.PerformOperations();
+        let aborted = operation
+            .GetAnyOperationsAborted()
+            .is_ok_and(|aborted| aborted.as_bool());
+        match performed {
+            Ok(()) => Ok(!aborted),
+            Err(_) if aborted => Ok(false),
+            Err(e) => Err(to_io(e)),
+        }
+    }
+}
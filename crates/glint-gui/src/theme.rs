@@ -0,0 +1,279 @@
+//! Window and result row colors, the high-contrast preset and the
+//! reduced-motion style.
+//!
+//! Both accessibility options follow Windows' own settings unless they are
+//! chosen in Settings.
+
+use eframe::egui::{self, style::ScrollAnimation, Color32, Stroke};
+
+/// Accessibility preferences of the operating system, read at startup.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemPreferences {
+    /// Windows' high-contrast mode is on
+    pub high_contrast: bool,
+    /// Windows' "Show animations" is off
+    pub reduced_motion: bool,
+}
+
+impl SystemPreferences {
+    #[cfg(windows)]
+    pub fn detect() -> Self {
+        use windows::Win32::Foundation::BOOL;
+        use windows::Win32::UI::Accessibility::{HCF_HIGHCONTRASTON, HIGHCONTRASTW};
+        use windows::Win32::UI::WindowsAndMessaging::{
+            SystemParametersInfoW, SPI_GETCLIENTAREAANIMATION, SPI_GETHIGHCONTRAST,
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+        };
+
+        let mut contrast = HIGHCONTRASTW {
+            cbSize: std::mem::size_of::<HIGHCONTRASTW>() as u32,
+            ..Default::default()
+        };
+        let mut animations = BOOL(1);
+        let no_update = SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0);
+        unsafe {
+            let high_contrast = SystemParametersInfoW(
+                SPI_GETHIGHCONTRAST,
+                contrast.cbSize,
+                Some(&mut contrast as *mut _ as *mut _),
+                no_update,
+            )
+            .is_ok()
+                && contrast.dwFlags.0 & HCF_HIGHCONTRASTON.0 != 0;
+            let reduced_motion = SystemParametersInfoW(
+                SPI_GETCLIENTAREAANIMATION,
+                0,
+                Some(&mut animations as *mut _ as *mut _),
+                no_update,
+            )
+            .is_ok()
+                && !animations.as_bool();
+            SystemPreferences {
+                high_contrast,
+                reduced_motion,
+            }
+        }
+    }
+
+    /// Other systems don't tell us; both options stay off until chosen.
+    #[cfg(not(windows))]
+    pub fn detect() -> Self {
+        Self::default()
+    }
+}
+
+/// Window colors for the light or dark theme, or their high-contrast
+/// counterparts: black on white or white on black, with outlined widgets.
+pub fn visuals(dark: bool, high_contrast: bool) -> egui::Visuals {
+    if !high_contrast {
+        return if dark {
+            egui::Visuals::dark()
+        } else {
+            egui::Visuals::light()
+        };
+    }
+
+    let (mut visuals, fg, bg, accent) = if dark {
+        (
+            egui::Visuals::dark(),
+            Color32::WHITE,
+            Color32::BLACK,
+            Color32::YELLOW,
+        )
+    } else {
+        let accent = Color32::from_rgb(0, 0, 160);
+        (
+            egui::Visuals::light(),
+            Color32::BLACK,
+            Color32::WHITE,
+            accent,
+        )
+    };
+    visuals.override_text_color = Some(fg);
+    visuals.panel_fill = bg;
+    visuals.window_fill = bg;
+    visuals.extreme_bg_color = bg;
+    visuals.faint_bg_color = bg;
+    visuals.code_bg_color = bg;
+    visuals.window_stroke = Stroke::new(2.0, fg);
+    visuals.hyperlink_color = accent;
+    visuals.selection.bg_fill = accent;
+    visuals.selection.stroke = Stroke::new(1.5, bg);
+    let widgets = &mut visuals.widgets;
+    for widget in [
+        &mut widgets.noninteractive,
+        &mut widgets.inactive,
+        &mut widgets.hovered,
+        &mut widgets.active,
+        &mut widgets.open,
+    ] {
+        widget.bg_fill = bg;
+        widget.weak_bg_fill = bg;
+        widget.bg_stroke = Stroke::new(1.0, fg);
+        widget.fg_stroke = Stroke::new(1.5, fg);
+    }
+    widgets.hovered.bg_stroke = Stroke::new(2.0, accent);
+    widgets.active.bg_stroke = Stroke::new(2.0, accent);
+    visuals
+}
+
+/// Turn animations and smooth scrolling off, or back to egui's defaults.
+pub fn set_reduced_motion(style: &mut egui::Style, reduced: bool) {
+    if reduced {
+        style.animation_time = 0.0;
+        style.scroll_animation = ScrollAnimation::none();
+    } else {
+        let default = egui::Style::default();
+        style.animation_time = default.animation_time;
+        style.scroll_animation = default.scroll_animation;
+    }
+}
+
+/// Colors of the rows of the result and browse lists.
+#[derive(Clone, Copy)]
+pub struct RowColors {
+    even: Color32,
+    odd: Color32,
+    selected: Color32,
+    marked: Color32,
+    text: Color32,
+    offline_text: Color32,
+    secondary: Color32,
+    /// Text on a selected or marked row
+    highlighted_text: Color32,
+    highlighted_secondary: Color32,
+}
+
+impl RowColors {
+    pub fn new(dark: bool, high_contrast: bool) -> Self {
+        match (high_contrast, dark) {
+            (false, _) => RowColors {
+                even: Color32::from_gray(30),
+                odd: Color32::from_gray(35),
+                selected: Color32::from_rgb(0, 120, 212),
+                marked: Color32::from_rgb(0, 84, 150),
+                text: Color32::from_gray(200),
+                offline_text: Color32::from_gray(120),
+                secondary: Color32::from_gray(128),
+                highlighted_text: Color32::WHITE,
+                highlighted_secondary: Color32::from_gray(220),
+            },
+            (true, true) => RowColors {
+                even: Color32::BLACK,
+                odd: Color32::BLACK,
+                selected: Color32::YELLOW,
+                marked: Color32::from_rgb(0, 255, 255),
+                text: Color32::WHITE,
+                offline_text: Color32::from_gray(170),
+                secondary: Color32::from_gray(230),
+                highlighted_text: Color32::BLACK,
+                highlighted_secondary: Color32::BLACK,
+            },
+            (true, false) => RowColors {
+                even: Color32::WHITE,
+                odd: Color32::WHITE,
+                selected: Color32::from_rgb(0, 0, 160),
+                marked: Color32::from_rgb(96, 0, 128),
+                text: Color32::BLACK,
+                offline_text: Color32::from_gray(90),
+                secondary: Color32::from_gray(40),
+                highlighted_text: Color32::WHITE,
+                highlighted_secondary: Color32::WHITE,
+            },
+        }
+    }
+
+    pub fn background(&self, row: usize, selected: bool, marked: bool) -> Color32 {
+        if selected {
+            self.selected
+        } else if marked {
+            self.marked
+        } else if row % 2 == 0 {
+            self.even
+        } else {
+            self.odd
+        }
+    }
+
+    /// Names: greyed out for results of offline volumes.
+    pub fn text(&self, highlighted: bool, offline: bool) -> Color32 {
+        if highlighted {
+            self.highlighted_text
+        } else if offline {
+            self.offline_text
+        } else {
+            self.text
+        }
+    }
+
+    /// Folders, sizes and dates.
+    pub fn secondary(&self, highlighted: bool) -> Color32 {
+        if highlighted {
+            self.highlighted_secondary
+        } else {
+            self.secondary
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_high_contrast_visuals() {
+        assert_eq!(visuals(true, false), egui::Visuals::dark());
+        assert_eq!(visuals(false, false), egui::Visuals::light());
+
+        let dark = visuals(true, true);
+        assert!(dark.dark_mode);
+        assert_eq!(dark.override_text_color, Some(Color32::WHITE));
+        assert_eq!(dark.panel_fill, Color32::BLACK);
+        assert_eq!(dark.widgets.inactive.bg_stroke.color, Color32::WHITE);
+
+        let light = visuals(false, true);
+        assert!(!light.dark_mode);
+        assert_eq!(light.override_text_color, Some(Color32::BLACK));
+        assert_eq!(light.panel_fill, Color32::WHITE);
+    }
+
+    #[test]
+    fn test_high_contrast_rows() {
+        for dark in [true, false] {
+            let colors = RowColors::new(dark, true);
+            // No striping, and highlighted rows keep their text readable
+            assert_eq!(
+                colors.background(0, false, false),
+                colors.background(1, false, false)
+            );
+            for (selected, marked) in [(true, false), (false, true)] {
+                assert_ne!(
+                    colors.background(0, selected, marked),
+                    colors.text(true, false)
+                );
+            }
+            assert_ne!(colors.background(0, false, false), colors.text(false, true));
+        }
+
+        let colors = RowColors::new(true, false);
+        assert_ne!(
+            colors.background(0, false, false),
+            colors.background(1, false, false)
+        );
+        assert_eq!(colors.background(3, true, true), colors.selected);
+        assert_eq!(colors.secondary(true), colors.highlighted_secondary);
+    }
+
+    #[test]
+    fn test_reduced_motion() {
+        let mut style = egui::Style::default();
+        set_reduced_motion(&mut style, true);
+        assert_eq!(style.animation_time, 0.0);
+        assert_eq!(style.scroll_animation, ScrollAnimation::none());
+
+        set_reduced_motion(&mut style, false);
+        let default = egui::Style::default();
+        assert_eq!(style.animation_time, default.animation_time);
+        assert_eq!(style.scroll_animation, default.scroll_animation);
+    }
+}
@@ -0,0 +1,111 @@
+//! Toast notifications.
+//!
+//! Outcomes of background work (an index saved, a build failed, a volume
+//! gone offline) are shown as toasts in the corner of the window instead of
+//! in the status bar, where the next message would replace them before they
+//! were read. Toasts stack, fade out on their own after a while, and can be
+//! dismissed by clicking them.
+
+use std::time::{Duration, Instant};
+
+/// How long informational toasts stay up.
+const SHORT: Duration = Duration::from_secs(4);
+
+/// How long warnings and errors stay up.
+const LONG: Duration = Duration::from_secs(10);
+
+/// Toasts shown at once; older ones are dropped first.
+const MAX_TOASTS: usize = 5;
+
+/// What a toast reports, which sets its icon and colour.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToastKind {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl ToastKind {
+    pub fn icon(self) -> &'static str {
+        match self {
+            ToastKind::Info => "ℹ",
+            ToastKind::Success => "✓",
+            ToastKind::Warning => "⚠",
+            ToastKind::Error => "✕",
+        }
+    }
+
+    fn lifetime(self) -> Duration {
+        match self {
+            ToastKind::Info | ToastKind::Success => SHORT,
+            ToastKind::Warning | ToastKind::Error => LONG,
+        }
+    }
+}
+
+/// A single notification.
+#[derive(Clone, Debug)]
+pub struct Toast {
+    pub id: u64,
+    pub kind: ToastKind,
+    pub text: String,
+    shown_at: Instant,
+}
+
+impl Toast {
+    /// Time left before the toast goes away.
+    pub fn remaining(&self) -> Duration {
+        self.kind.lifetime().saturating_sub(self.shown_at.elapsed())
+    }
+}
+
+/// The toasts currently shown, oldest first.
+#[derive(Default)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+    next_id: u64,
+}
+
+impl Toasts {
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.push(ToastKind::Info, text.into());
+    }
+
+    pub fn success(&mut self, text: impl Into<String>) {
+        self.push(ToastKind::Success, text.into());
+    }
+
+    pub fn warning(&mut self, text: impl Into<String>) {
+        self.push(ToastKind::Warning, text.into());
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.push(ToastKind::Error, text.into());
+    }
+
+    fn push(&mut self, kind: ToastKind, text: String) {
+        // The same message again just restarts its timer
+        self.toasts.retain(|t| t.text != text);
+        if self.toasts.len() >= MAX_TOASTS {
+            self.toasts.remove(0);
+        }
+        self.toasts.push(Toast {
+            id: self.next_id,
+            kind,
+            text,
+            shown_at: Instant::now(),
+        });
+        self.next_id += 1;
+    }
+
+    /// Drop expired toasts and return the rest.
+    pub fn current(&mut self) -> &[Toast] {
+        self.toasts.retain(|t| !t.remaining().is_zero());
+        &self.toasts
+    }
+
+    pub fn dismiss(&mut self, id: u64) {
+        self.toasts.retain(|t| t.id != id);
+    }
+}
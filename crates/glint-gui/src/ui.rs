@@ -1,37 +1,387 @@
 //! UI components for the Glint GUI.
 
-use crate::app::{format_number, format_size, GlintApp};
-use crate::service::ServiceStatus;
-use eframe::egui::{self, Color32, RichText, Sense};
+use crate::actions::{self, ActionId, Shortcut};
+use crate::app::GlintApp;
+use crate::browse::BrowseSort;
+use crate::exclusions::RuleSort;
+use crate::search;
+use crate::service::{ServiceStatus, TaskStatus, TaskTrigger};
+use crate::settings::NAME_COLUMN_WIDTH;
+use crate::theme::RowColors;
+use crate::toasts::ToastKind;
+use eframe::egui::text::LayoutJob;
+use eframe::egui::{self, Color32, RichText, Sense, TextFormat};
+use glint_core::config::{DateFormat, NumberFormat};
+use glint_core::exclude::{preset, ExcludePreset, ExcludeRule, PRESETS};
+use glint_core::index::{VolumeAvailability, VolumeIndexState};
+use glint_core::rename::RenameStatus;
+use glint_core::stats::GroupBy;
+use glint_core::{PathFormat, VolumeId};
+use glint_frontend::format::{
+    format_age, format_date_time, format_number, format_size, format_totals, set_locale, Locale,
+};
+use glint_frontend::hints::Completions;
+use glint_frontend::syntax;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
 
 // Local helper function
 fn format_volume_size(bytes: u64) -> String {
     format_size(bytes)
 }
 
+/// Dropdown entry for a volume: its name with label, match count and
+/// free-space badges, highlighted when the volume is low on space.
+fn volume_badge(info: &glint_core::VolumeInfo, matches: Option<usize>) -> RichText {
+    let mut text = info.display_name().to_string();
+    if let Some(label) = &info.label {
+        text.push_str(&format!("  [{}]", label));
+    }
+    if let Some(matches) = matches {
+        text.push_str(&format!("  ({})", format_number(matches)));
+    }
+    if let Some(free) = info.free_bytes {
+        text.push_str(&format!("  {} free", format_volume_size(free)));
+    }
+    if info.is_low_on_space() {
+        RichText::new(format!("{}  ⚠", text)).color(Color32::from_rgb(255, 150, 0))
+    } else {
+        RichText::new(text)
+    }
+}
+
+/// Spinner in a folder's size column, right-aligned at `pos`, while its
+/// size is being summed; an ellipsis that stays still with reduced motion.
+fn folder_size_spinner(ui: &egui::Ui, pos: egui::Pos2, reduced_motion: bool, color: Color32) {
+    if reduced_motion {
+        let font = egui::FontId::proportional(12.0);
+        ui.painter()
+            .text(pos, egui::Align2::RIGHT_CENTER, "…", font, color);
+        return;
+    }
+    let rect = egui::Rect::from_center_size(pos - egui::vec2(6.0, 0.0), egui::vec2(12.0, 12.0));
+    egui::Spinner::new().size(12.0).paint_at(ui, rect);
+}
+
+/// Everything known about a result, for when its row is hovered: the
+/// whole path, which narrow windows cut short, then its size, dates,
+/// attributes and volume.
+fn result_tooltip(
+    ui: &mut egui::Ui,
+    result: &glint_core::SearchResult,
+    size: Option<u64>,
+    volume: Option<&glint_core::VolumeInfo>,
+    path_format: PathFormat,
+) {
+    let record = &result.record;
+    ui.set_max_width(480.0);
+    ui.label(RichText::new(path_format.apply(&record.path)).strong());
+    ui.add_space(4.0);
+
+    let mut attributes = file_attributes(ui, &record.path);
+    if record.cloud {
+        attributes.insert(0, "Online-only");
+    }
+
+    egui::Grid::new("result_tooltip")
+        .num_columns(2)
+        .spacing([12.0, 2.0])
+        .show(ui, |ui| {
+            for (label, value) in tooltip_rows(result, size, volume, &attributes) {
+                ui.label(RichText::new(label).color(Color32::GRAY));
+                ui.label(value);
+                ui.end_row();
+            }
+        });
+}
+
+/// The labelled rows under the path of a result's tooltip.
+fn tooltip_rows(
+    result: &glint_core::SearchResult,
+    size: Option<u64>,
+    volume: Option<&glint_core::VolumeInfo>,
+    attributes: &[&str],
+) -> Vec<(&'static str, String)> {
+    let record = &result.record;
+    let size = match (size, record.is_dir) {
+        (Some(size), true) => format!("{} in total", format_size(size)),
+        (Some(size), false) => format!(
+            "{} ({} bytes)",
+            format_size(size),
+            format_number(size as usize)
+        ),
+        (None, true) => "Being summed...".to_string(),
+        (None, false) => "-".to_string(),
+    };
+    let date = |at: Option<chrono::DateTime<chrono::Utc>>| {
+        at.map(format_date_time).unwrap_or_else(|| "-".to_string())
+    };
+
+    let mut rows = vec![
+        (if record.is_dir { "Folder" } else { "Size" }, size),
+        ("Modified", date(record.modified)),
+        ("Created", date(record.created)),
+    ];
+    if let Some(deleted) = result.deleted_at {
+        rows.push(("Deleted", format_date_time(deleted)));
+    }
+    if !attributes.is_empty() {
+        rows.push(("Attributes", attributes.join(", ")));
+    }
+    match (&result.source, volume) {
+        (Some(source), _) => rows.push(("Index", source.to_string())),
+        (None, Some(info)) => rows.push((
+            "Volume",
+            match &info.label {
+                Some(label) => format!("{} [{}]", info.display_name(), label),
+                None => info.display_name().to_string(),
+            },
+        )),
+        (None, None) => {}
+    }
+    rows
+}
+
+/// Names of the attributes set on a file, read once while its row is
+/// hovered rather than on every frame of the tooltip.
+fn file_attributes(ui: &egui::Ui, path: &str) -> Vec<&'static str> {
+    let id = egui::Id::new(("file_attributes", path));
+    if let Some(attributes) = ui.data(|data| data.get_temp::<Vec<&'static str>>(id)) {
+        return attributes;
+    }
+    let attributes = read_attributes(path);
+    ui.data_mut(|data| data.insert_temp(id, attributes.clone()));
+    attributes
+}
+
+#[cfg(windows)]
+fn read_attributes(path: &str) -> Vec<&'static str> {
+    use std::os::windows::fs::MetadataExt;
+
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return Vec::new();
+    };
+    attribute_names(metadata.file_attributes())
+}
+
+/// The names of the Windows file attributes set in `attributes`.
+#[cfg(any(windows, test))]
+fn attribute_names(attributes: u32) -> Vec<&'static str> {
+    const NAMES: [(u32, &str); 7] = [
+        (0x1, "Read-only"),
+        (0x2, "Hidden"),
+        (0x4, "System"),
+        (0x20, "Archive"),
+        (0x400, "Link"),
+        (0x800, "Compressed"),
+        (0x4000, "Encrypted"),
+    ];
+    NAMES
+        .iter()
+        .filter(|(flag, _)| attributes & flag != 0)
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+/// Only Windows has these attributes.
+#[cfg(not(windows))]
+fn read_attributes(_path: &str) -> Vec<&'static str> {
+    Vec::new()
+}
+
+/// "Updated 2 min ago" for a volume in the settings, or a warning once it
+/// has gone longer than `stale_after_hours` without an update.
+fn update_age_label(ui: &mut egui::Ui, app: &GlintApp, state: &VolumeIndexState) {
+    let Some(updated) = state.last_updated() else {
+        return;
+    };
+    let age = format_age((chrono::Utc::now() - updated).to_std().unwrap_or_default());
+    let stale = app
+        .config
+        .stale_after()
+        .is_some_and(|max_age| state.is_stale(max_age));
+    let text = if stale {
+        RichText::new(format!("⚠ Last updated {}", age))
+            .small()
+            .color(Color32::from_rgb(230, 160, 60))
+    } else {
+        RichText::new(format!("Updated {}", age))
+            .small()
+            .color(Color32::GRAY)
+    };
+    let mut hover = String::new();
+    if let Some(scan) = state.last_full_scan {
+        hover.push_str(&format!(
+            "Last full scan: {}",
+            format_date_time(scan)
+        ));
+    }
+    if let Some(event) = state.last_event_applied {
+        hover.push_str(&format!(
+            "\nLast change applied: {}",
+            format_date_time(event)
+        ));
+    }
+    ui.label(text).on_hover_text(hover.trim_start());
+}
+
+/// One piece of the path column: a folder name, separator or "…".
+struct Crumb {
+    galley: Arc<egui::Galley>,
+    rect: egui::Rect,
+    /// Folder a click on this piece leads to
+    folder: Option<String>,
+}
+
+/// Split the folder part of a path into names and the folders they lead to.
+fn folder_segments(path: &str) -> Vec<(String, String)> {
+    let dir = match path.rfind(['\\', '/']) {
+        Some(end) => &path[..end],
+        None => return Vec::new(),
+    };
+    let unc = dir.starts_with("\\\\");
+    let mut folder = String::new();
+    dir.split(['\\', '/'])
+        .filter(|name| !name.is_empty())
+        .enumerate()
+        .map(|(i, name)| {
+            if i == 0 {
+                folder = if unc {
+                    format!("\\\\{}", name)
+                } else {
+                    name.to_string()
+                };
+            } else {
+                folder = format!("{}\\{}", folder, name);
+            }
+            // A bare drive letter means the current directory on that drive
+            let target = if i == 0 && !unc {
+                format!("{}\\", folder)
+            } else {
+                folder.clone()
+            };
+            (name.to_string(), target)
+        })
+        .collect()
+}
+
+/// Lay out a result's folder as breadcrumbs inside `rect`.
+///
+/// When the path doesn't fit, folders after the root are replaced by "…"
+/// so the innermost ones stay visible.
+fn breadcrumbs(ui: &egui::Ui, rect: egui::Rect, path: &str, color: Color32) -> Vec<Crumb> {
+    let font = egui::FontId::proportional(12.0);
+    let layout = |text: &str| {
+        ui.painter()
+            .layout_no_wrap(text.to_string(), font.clone(), color)
+    };
+    let segments: Vec<_> = folder_segments(path)
+        .into_iter()
+        .map(|(name, folder)| (layout(&name), folder))
+        .collect();
+    if segments.is_empty() {
+        return Vec::new();
+    }
+    let separator = layout("\\");
+    let ellipsis = layout("…");
+
+    let sep_width = separator.size().x;
+    let width_from = |start: usize| -> f32 {
+        segments[start..]
+            .iter()
+            .map(|(galley, _)| galley.size().x + sep_width)
+            .sum()
+    };
+    let mut skip_to = 1;
+    if segments[0].0.size().x + sep_width + width_from(1) > rect.width() {
+        let prefix = segments[0].0.size().x + sep_width + ellipsis.size().x + sep_width;
+        while skip_to + 1 < segments.len() && prefix + width_from(skip_to) > rect.width() {
+            skip_to += 1;
+        }
+    }
+
+    let mut pieces = vec![(segments[0].0.clone(), Some(segments[0].1.clone()))];
+    if skip_to > 1 {
+        pieces.push((separator.clone(), None));
+        pieces.push((ellipsis, None));
+    }
+    for (galley, folder) in &segments[skip_to..] {
+        pieces.push((separator.clone(), None));
+        pieces.push((galley.clone(), Some(folder.clone())));
+    }
+
+    let mut x = rect.left();
+    let mut crumbs = Vec::with_capacity(pieces.len());
+    for (galley, folder) in pieces {
+        let size = galley.size();
+        if x + size.x > rect.right() {
+            break;
+        }
+        let min = egui::pos2(x, rect.center().y - size.y / 2.0);
+        x += size.x;
+        crumbs.push(Crumb {
+            galley,
+            rect: egui::Rect::from_min_size(min, size),
+            folder,
+        });
+    }
+    crumbs
+}
+
+/// A menu entry that runs a registered action.
+fn menu_action(ui: &mut egui::Ui, ctx: &egui::Context, app: &mut GlintApp, id: ActionId) {
+    let action = actions::action(id);
+    let mut button = egui::Button::new(action.label);
+    if let Some(shortcut) = app.keymap.shortcut(id) {
+        button = button.shortcut_text(shortcut.to_string());
+    }
+    if ui.add_enabled(action.is_enabled(app), button).clicked() {
+        actions::execute(id, ctx, app);
+        ui.close_menu();
+    }
+}
+
+/// Action label with its current shortcut, for tooltips.
+fn action_hint(app: &GlintApp, id: ActionId) -> String {
+    let label = actions::action(id).label.trim_end_matches("...");
+    match app.keymap.shortcut(id) {
+        Some(shortcut) => format!("{} ({})", label, shortcut),
+        None => label.to_string(),
+    }
+}
+
 /// Menu bar at the top of the window
 pub fn menu_bar(ctx: &egui::Context, app: &mut GlintApp) {
     egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
         egui::menu::bar(ui, |ui| {
             // File menu
             ui.menu_button("File", |ui| {
-                if ui.button("Build Index...").clicked() {
-                    app.show_index_builder = true;
-                    ui.close_menu();
-                }
-                if ui.button("Reload Index (F5)").clicked() {
-                    app.reload_index();
-                    ui.close_menu();
-                }
+                menu_action(ui, ctx, app, ActionId::BuildIndex);
+                menu_action(ui, ctx, app, ActionId::ReloadIndex);
+                menu_action(ui, ctx, app, ActionId::ShowStatistics);
                 ui.separator();
-                if ui.button("Settings...").clicked() {
-                    app.show_settings = true;
-                    ui.close_menu();
-                }
+                menu_action(ui, ctx, app, ActionId::RenameResults);
+                menu_action(ui, ctx, app, ActionId::UndoRename);
+                menu_action(ui, ctx, app, ActionId::ShowHistory);
                 ui.separator();
-                if ui.button("Exit").clicked() {
-                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
-                }
+                menu_action(ui, ctx, app, ActionId::AttachIndex);
+                menu_action(ui, ctx, app, ActionId::DetachIndexes);
+                ui.separator();
+                menu_action(ui, ctx, app, ActionId::OpenSettings);
+                ui.separator();
+                menu_action(ui, ctx, app, ActionId::Exit);
+            });
+
+            // View menu
+            ui.menu_button("View", |ui| {
+                menu_action(ui, ctx, app, ActionId::CommandPalette);
+                ui.separator();
+                menu_action(ui, ctx, app, ActionId::ToggleFilesOnly);
+                menu_action(ui, ctx, app, ActionId::ToggleFoldersOnly);
+                menu_action(ui, ctx, app, ActionId::TogglePinnedView);
+                ui.separator();
+                menu_action(ui, ctx, app, ActionId::ToggleDarkMode);
             });
 
             // Service menu
@@ -72,6 +422,33 @@ pub fn menu_bar(ctx: &egui::Context, app: &mut GlintApp) {
                     }
                 }
 
+                // Scheduled task alternative, for when the service can't be installed
+                ui.separator();
+                let task_status = app.task_status;
+                let task_text = match task_status {
+                    TaskStatus::NotInstalled => "⚪ Not Installed",
+                    TaskStatus::Ready => "🟢 Ready",
+                    TaskStatus::Running => "🟢 Running",
+                    TaskStatus::Disabled => "🔴 Disabled",
+                    TaskStatus::Unknown => "❓ Unknown",
+                };
+                ui.label(format!("Scheduled task: {}", task_text))
+                    .on_hover_text("Runs 'glint watch --once' to catch up on changes");
+
+                if task_status == TaskStatus::NotInstalled {
+                    if ui.button("Install Task (Every 15 Minutes)").clicked() {
+                        app.set_scheduled_task(Some(TaskTrigger::Every(15)));
+                        ui.close_menu();
+                    }
+                    if ui.button("Install Task (At Logon)").clicked() {
+                        app.set_scheduled_task(Some(TaskTrigger::AtLogon));
+                        ui.close_menu();
+                    }
+                } else if ui.button("Remove Scheduled Task").clicked() {
+                    app.set_scheduled_task(None);
+                    ui.close_menu();
+                }
+
                 ui.separator();
                 if ui.button("Refresh Status").clicked() {
                     app.refresh_service_status();
@@ -81,10 +458,8 @@ pub fn menu_bar(ctx: &egui::Context, app: &mut GlintApp) {
 
             // Help menu
             ui.menu_button("Help", |ui| {
-                if ui.button("About...").clicked() {
-                    app.show_about = true;
-                    ui.close_menu();
-                }
+                menu_action(ui, ctx, app, ActionId::ShowQuerySyntax);
+                menu_action(ui, ctx, app, ActionId::ShowAbout);
             });
         });
     });
@@ -99,18 +474,78 @@ pub fn top_panel(ctx: &egui::Context, app: &mut GlintApp) {
             // Search icon
             ui.label(RichText::new("🔍").size(18.0));
 
-            // Search input
-            let response = ui.add_sized(
-                [ui.available_width() - 150.0, 28.0],
-                egui::TextEdit::singleline(&mut app.search.query)
-                    .hint_text("Search files... (type at least 2 characters)")
-                    .font(egui::TextStyle::Heading),
-            );
+            // Search input, with parts that don't parse underlined
+            let scopes = &app.search.scopes;
+            let use_regex = app.search.use_regex;
+            let mut layouter = |ui: &egui::Ui, text: &str, wrap_width: f32| {
+                let problems = match use_regex {
+                    true => Vec::new(),
+                    false => search::query_problems(scopes, text),
+                };
+                let job = underlined_job(ui, text, &problems, wrap_width);
+                ui.fonts(|f| f.layout_job(job))
+            };
+            // Tab completes instead of moving on while suggestions show
+            let completing = app.search.completions.is_some();
+            let output = ui
+                .allocate_ui_with_layout(
+                    egui::vec2(ui.available_width() - 150.0, 28.0),
+                    egui::Layout::centered_and_justified(ui.layout().main_dir()),
+                    |ui| {
+                        egui::TextEdit::singleline(&mut app.search.query)
+                            .hint_text("Search files... (type at least 2 characters)")
+                            .font(egui::TextStyle::Heading)
+                            .layouter(&mut layouter)
+                            .lock_focus(completing)
+                            .show(ui)
+                    },
+                )
+                .inner;
+            let problems = app.search.query_problems();
+            let mut response = output.response.clone();
+            if !problems.is_empty() {
+                let reasons: Vec<String> = problems
+                    .iter()
+                    .map(|(range, reason)| {
+                        format!("{}: {}", &app.search.query[range.clone()], reason)
+                    })
+                    .collect();
+                response = response.on_hover_text(reasons.join("\n"));
+            }
 
             if response.changed() {
                 app.search.mark_dirty();
             }
 
+            // Suggest tokens and their values for the word at the cursor,
+            // also in the frame that focus leaves for a clicked suggestion
+            let entering = ui.input(|i| i.key_pressed(egui::Key::Enter));
+            let cursor = output
+                .state
+                .cursor
+                .char_range()
+                .filter(|range| range.primary.index == range.secondary.index)
+                .filter(|_| (response.has_focus() || response.lost_focus()) && !entering)
+                .map(|range| char_to_byte(&app.search.query, range.primary.index));
+            app.search.completions = cursor.and_then(|c| app.search.complete(&app.index, c));
+            if let Some(completions) = app.search.completions.clone() {
+                let tab = response.has_focus() && ui.input(|i| i.key_pressed(egui::Key::Tab));
+                let chosen = completion_popup(ui, &response, &completions).or(tab.then_some(0));
+                if let Some(item) = chosen {
+                    let item = &completions.items[item];
+                    app.search.query = completions.apply(&app.search.query, item);
+                    let end = completions.range.start + item.text.len();
+                    let end = app.search.query[..end].chars().count();
+                    let mut state = output.state.clone();
+                    let end = egui::text::CCursorRange::one(egui::text::CCursor::new(end));
+                    state.cursor.set_char_range(Some(end));
+                    state.store(ui.ctx(), response.id);
+                    response.request_focus();
+                    app.search.completions = None;
+                    app.search.mark_dirty();
+                }
+            }
+
             // Focus search box on startup or Ctrl+L
             if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
                 app.search.search();
@@ -121,20 +556,29 @@ pub fn top_panel(ctx: &egui::Context, app: &mut GlintApp) {
                 app.search.search();
             }
 
-            // Request focus with Ctrl+L
-            if ui.input(|i| i.modifiers.ctrl && i.key_pressed(egui::Key::L)) {
+            // Focus requested by shortcut (Ctrl+L) or the command palette
+            if app.focus_search {
                 response.request_focus();
+                app.focus_search = false;
             }
 
             // Clear button
-            if ui.button("✕").on_hover_text("Clear search (Esc)").clicked() {
+            if ui
+                .button("✕")
+                .on_hover_text(action_hint(app, ActionId::ClearSearch))
+                .clicked()
+            {
                 app.search.query.clear();
                 app.search.clear();
             }
 
             // Settings button
-            if ui.button("⚙").on_hover_text("Settings (Ctrl+,)").clicked() {
-                app.show_settings = !app.show_settings;
+            if ui
+                .button("⚙")
+                .on_hover_text(action_hint(app, ActionId::OpenSettings))
+                .clicked()
+            {
+                actions::execute(ActionId::OpenSettings, ctx, app);
             }
 
             // About button
@@ -172,21 +616,419 @@ pub fn top_panel(ctx: &egui::Context, app: &mut GlintApp) {
                 app.search.mark_dirty();
             }
 
+            ui.separator();
+
+            if ui
+                .checkbox(&mut app.search.show_pinned, "⭐ Pinned")
+                .on_hover_text("Show only pinned items")
+                .changed()
+            {
+                app.search.mark_dirty();
+            }
+            if ui
+                .checkbox(&mut app.search.show_largest, "📊 Largest")
+                .on_hover_text("Show the largest matching files, biggest first")
+                .changed()
+            {
+                app.search.mark_dirty();
+            }
+            if ui
+                .checkbox(&mut app.search.show_deleted, "🗑 Deleted")
+                .on_hover_text("Show files deleted recently, newest first")
+                .changed()
+            {
+                app.search.mark_dirty();
+            }
+
+            // Shown once hide_patterns are set in the config
+            if !app.search.hide_patterns.is_empty()
+                && ui
+                    .checkbox(&mut app.search.show_hidden_patterns, "Show hidden patterns")
+                    .on_hover_text(format!(
+                        "Also show results matching {}",
+                        app.search.hide_patterns.join(", ")
+                    ))
+                    .changed()
+            {
+                app.search.mark_dirty();
+            }
+
+            // Scope dropdown, shown once scopes are defined in the config
+            if !app.search.scopes.is_empty() {
+                ui.separator();
+                let names: Vec<String> = app.search.scopes.names().map(str::to_string).collect();
+                let before = app.search.scope.clone();
+                egui::ComboBox::from_id_salt("scope")
+                    .selected_text(before.as_deref().unwrap_or("Everywhere"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut app.search.scope, None, "Everywhere");
+                        for name in names {
+                            let label = name.clone();
+                            ui.selectable_value(&mut app.search.scope, Some(name), label);
+                        }
+                    })
+                    .response
+                    .on_hover_text("Only search the folders of a scope");
+                if app.search.scope != before {
+                    app.search.mark_dirty();
+                }
+            }
+
+            // Volume dropdown, shown once more than one volume is indexed
+            let mut volumes = app.index.volume_states();
+            volumes.retain(|v| v.enabled);
+            if let Some(selected) = &app.search.volume {
+                if !volumes.iter().any(|v| &v.info.id == selected) {
+                    app.search.volume = None;
+                    app.search.mark_dirty();
+                }
+            }
+            if volumes.len() > 1 {
+                ui.separator();
+                volumes.sort_by(|a, b| a.info.display_name().cmp(b.info.display_name()));
+                let before = app.search.volume.clone();
+                let selected_name = volumes
+                    .iter()
+                    .find(|v| Some(&v.info.id) == before.as_ref())
+                    .map_or("All volumes", |v| v.info.display_name());
+                let counts = app.search.volume_counts.as_ref();
+                egui::ComboBox::from_id_salt("volume")
+                    .selected_text(selected_name)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut app.search.volume, None, "All volumes");
+                        for state in &volumes {
+                            let matches =
+                                counts.map(|c| c.get(&state.info.id).copied().unwrap_or(0));
+                            ui.selectable_value(
+                                &mut app.search.volume,
+                                Some(state.info.id.clone()),
+                                volume_badge(&state.info, matches),
+                            );
+                        }
+                    })
+                    .response
+                    .on_hover_text("Only search one volume");
+                if app.search.volume != before {
+                    app.search.mark_dirty();
+                }
+            }
+
+            // Folder picked from a result's path
+            if let Some(folder) = app.search.folder.clone() {
+                ui.separator();
+                if ui
+                    .button(format!("in: {}  ✕", folder))
+                    .on_hover_text("Search everywhere again")
+                    .clicked()
+                {
+                    app.search.set_folder(None);
+                }
+            }
+
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 if !app.search.results.is_empty() {
+                    let shown = app.search.results.len();
+                    let count = if !app.search.result_filter.trim().is_empty() {
+                        format!(
+                            "{} of {} results (filtered)",
+                            format_number(shown),
+                            format_number(app.search.retrieved())
+                        )
+                    } else if app.search.total_matches > shown {
+                        format!(
+                            "{} of {} results",
+                            format_number(shown),
+                            format_number(app.search.total_matches)
+                        )
+                    } else {
+                        format!("{} results", format_number(shown))
+                    };
                     ui.label(format!(
-                        "{} results in {:.1}ms",
-                        format_number(app.search.results.len()),
+                        "{} in {:.1}ms",
+                        count,
                         app.search.search_time.as_secs_f64() * 1000.0
                     ));
                 }
+                if !app.search.unreachable.is_empty() {
+                    ui.label(
+                        RichText::new("⚠ offline servers")
+                            .small()
+                            .color(Color32::GOLD),
+                    )
+                    .on_hover_text(format!(
+                        "Not searched: {}",
+                        app.search.unreachable.join(", ")
+                    ));
+                }
+                if app.search.recently_updated() {
+                    ui.label(RichText::new("↻ updated").small().color(Color32::GRAY))
+                        .on_hover_text("Results were refreshed because the index changed");
+                    ctx.request_repaint_after(crate::search::UPDATED_INDICATOR);
+                }
             });
         });
 
+        // Result filter row (Ctrl+Shift+F)
+        if app.search.filter_open {
+            ui.add_space(4.0);
+            ui.horizontal(|ui| {
+                ui.label("Filter results:");
+                let response = ui.add_sized(
+                    [ui.available_width() - 40.0, 22.0],
+                    egui::TextEdit::singleline(&mut app.search.result_filter)
+                        .hint_text("Narrow these results, e.g. ext:pdf or path: 2024"),
+                );
+                if response.changed() {
+                    app.search.filter_results();
+                }
+                if app.focus_filter {
+                    response.request_focus();
+                    app.focus_filter = false;
+                }
+                if ui
+                    .button("✕")
+                    .on_hover_text("Show all results again")
+                    .clicked()
+                {
+                    app.search.close_filter();
+                }
+            });
+        }
+
         ui.add_space(4.0);
     });
 }
 
+/// Banner shown while running in safe mode after the index failed to load.
+pub fn safe_mode_banner(ctx: &egui::Context, app: &mut GlintApp) {
+    let Some(safe_mode) = &app.safe_mode else {
+        return;
+    };
+    let reason = safe_mode.reason.clone();
+    let has_report = safe_mode.report_path.is_some();
+    let has_backup = app.store.backup_path().exists();
+
+    egui::TopBottomPanel::top("safe_mode_banner").show(ctx, |ui| {
+        ui.add_space(6.0);
+        ui.label(
+            RichText::new("⚠ Safe mode: the index could not be loaded")
+                .strong()
+                .color(Color32::from_rgb(230, 80, 80)),
+        );
+        ui.label(&reason);
+        ui.label(
+            RichText::new("Glint started with an empty index. Your index file has not been changed.")
+                .weak(),
+        );
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(has_backup, egui::Button::new("Restore Backup"))
+                .on_hover_text("Replace the index with the copy kept from the previous save")
+                .on_disabled_hover_text("No backup index was found")
+                .clicked()
+            {
+                app.restore_backup();
+            }
+            if ui.button("Rebuild Index").clicked() {
+                app.show_index_builder = true;
+            }
+            if ui
+                .add_enabled(has_report, egui::Button::new("Open Diagnostics Report"))
+                .clicked()
+            {
+                app.open_diagnostics_report();
+            }
+            if ui.button("Dismiss").clicked() {
+                app.safe_mode = None;
+            }
+        });
+        ui.add_space(6.0);
+    });
+}
+
+/// Banner suggesting exclusions for folders that change constantly.
+pub fn busy_folders_banner(ctx: &egui::Context, app: &mut GlintApp) {
+    if app.busy_folders.is_empty() {
+        return;
+    }
+    let folders = app.busy_folders.clone();
+
+    egui::TopBottomPanel::top("busy_folders_banner").show(ctx, |ui| {
+        ui.add_space(6.0);
+        ui.label(
+            RichText::new("⚠ Some folders change constantly")
+                .strong()
+                .color(Color32::from_rgb(230, 160, 60)),
+        );
+        ui.label(
+            RichText::new("Excluding them keeps the index small and the watcher idle.").weak(),
+        );
+        for folder in &folders {
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "📁 {} ({} changes a minute)",
+                    folder.path,
+                    format_number(folder.per_minute as usize)
+                ));
+                let hover = match folder.preset() {
+                    Some(preset) => format!("Enable the {} exclusion preset", preset),
+                    None => "Add the folder to the excluded folders".to_string(),
+                };
+                if ui.small_button("Exclude").on_hover_text(hover).clicked() {
+                    app.exclude_busy_folder(folder);
+                }
+            });
+        }
+        if ui.button("Dismiss").clicked() {
+            app.dismiss_busy_folders();
+        }
+        ui.add_space(6.0);
+    });
+}
+
+/// Banner shown when another program saved the index while changes made
+/// here were not saved yet.
+pub fn index_changed_banner(ctx: &egui::Context, app: &mut GlintApp) {
+    if !app.index_watch.changed {
+        return;
+    }
+
+    egui::TopBottomPanel::top("index_changed_banner").show(ctx, |ui| {
+        ui.add_space(6.0);
+        ui.label(
+            RichText::new("⚠ The index was saved by another program")
+                .strong()
+                .color(Color32::from_rgb(230, 160, 60)),
+        );
+        ui.label(
+            RichText::new(
+                "Changes made here are not saved yet. Reload to search the new index, \
+                 or keep this one and save it over the other.",
+            )
+            .weak(),
+        );
+        ui.horizontal(|ui| {
+            if ui.button("Reload").clicked() {
+                app.reload_changed_index();
+            }
+            let keep = if app.store.is_read_only() {
+                "Keep searching the index loaded here; the file is left as it is"
+            } else {
+                "Save the index loaded here over the other program's"
+            };
+            if ui.button("Keep Mine").on_hover_text(keep).clicked() {
+                app.keep_own_index();
+            }
+        });
+        ui.add_space(6.0);
+    });
+}
+
+/// A layout of `text` in the heading font with the `problems` ranges
+/// underlined in red.
+fn underlined_job(
+    ui: &egui::Ui,
+    text: &str,
+    problems: &[(Range<usize>, String)],
+    wrap_width: f32,
+) -> LayoutJob {
+    let plain = TextFormat {
+        font_id: egui::TextStyle::Heading.resolve(ui.style()),
+        color: ui.visuals().text_color(),
+        ..Default::default()
+    };
+    let wrong = TextFormat {
+        underline: egui::Stroke::new(1.5, ui.visuals().error_fg_color),
+        ..plain.clone()
+    };
+    let mut job = LayoutJob::default();
+    let mut at = 0;
+    for (range, _) in problems {
+        job.append(&text[at..range.start], 0.0, plain.clone());
+        job.append(&text[range.clone()], 0.0, wrong.clone());
+        at = range.end;
+    }
+    job.append(&text[at..], 0.0, plain);
+    job.wrap.max_width = wrap_width;
+    job
+}
+
+/// Byte offset of the character at `index` in `text`.
+fn char_to_byte(text: &str, index: usize) -> usize {
+    text.char_indices()
+        .nth(index)
+        .map_or(text.len(), |(i, _)| i)
+}
+
+/// Suggestions for the query token being typed, below the search box.
+/// Returns the one clicked.
+fn completion_popup(
+    ui: &egui::Ui,
+    below: &egui::Response,
+    completions: &Completions,
+) -> Option<usize> {
+    let mut chosen = None;
+    egui::Area::new(below.id.with("completions"))
+        .fixed_pos(below.rect.left_bottom())
+        .order(egui::Order::Foreground)
+        .show(ui.ctx(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                for (i, item) in completions.items.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.selectable_label(i == 0, &item.text).clicked() {
+                            chosen = Some(i);
+                        }
+                        ui.label(RichText::new(&item.detail).weak());
+                    });
+                }
+                ui.label(RichText::new("Tab completes the first").small().weak());
+            });
+        });
+    chosen
+}
+
+/// Toast notifications in the bottom-right corner, above the status bar.
+pub fn toasts(ctx: &egui::Context, app: &mut GlintApp) {
+    let toasts = app.toasts.current().to_vec();
+    let Some(next_expiry) = toasts.iter().map(|t| t.remaining()).min() else {
+        return;
+    };
+
+    let mut dismissed = None;
+    egui::Area::new(egui::Id::new("toasts"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -32.0))
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            for toast in &toasts {
+                let color = match toast.kind {
+                    ToastKind::Info => Color32::from_rgb(0, 120, 212),
+                    ToastKind::Success => Color32::from_rgb(80, 180, 80),
+                    ToastKind::Warning => Color32::from_rgb(230, 160, 60),
+                    ToastKind::Error => Color32::from_rgb(230, 80, 80),
+                };
+                let response = egui::Frame::popup(ui.style())
+                    .show(ui, |ui| {
+                        ui.set_max_width(360.0);
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(toast.kind.icon()).strong().color(color));
+                            ui.label(&toast.text);
+                        });
+                    })
+                    .response
+                    .interact(Sense::click())
+                    .on_hover_text("Click to dismiss");
+                if response.clicked() {
+                    dismissed = Some(toast.id);
+                }
+            }
+        });
+
+    if let Some(id) = dismissed {
+        app.toasts.dismiss(id);
+    }
+    ctx.request_repaint_after(next_expiry);
+}
+
 /// Bottom status bar.
 pub fn bottom_panel(ctx: &egui::Context, app: &mut GlintApp) {
     egui::TopBottomPanel::bottom("bottom_panel")
@@ -195,51 +1037,149 @@ pub fn bottom_panel(ctx: &egui::Context, app: &mut GlintApp) {
             ui.horizontal(|ui| {
                 // Status message
                 ui.label(&app.status_message);
+                if let Some(estimate) = app.build_estimate() {
+                    ui.add(
+                        egui::ProgressBar::new(estimate.fraction() as f32)
+                            .desired_width(120.0)
+                            .show_percentage(),
+                    );
+                }
+                if let Some(fraction) = app.save_fraction() {
+                    ui.add(
+                        egui::ProgressBar::new(fraction)
+                            .desired_width(120.0)
+                            .show_percentage(),
+                    );
+                }
+
+                // Per-volume load state; ready volumes can already be searched
+                for volume in &app.volume_loads {
+                    let hover = format!(
+                        "{} files{}",
+                        format_number(volume.records),
+                        if volume.ready { ", searchable" } else { ", loading" }
+                    );
+                    if volume.ready {
+                        ui.label(
+                            RichText::new(format!("✓ {}", volume.mount_point))
+                                .small()
+                                .color(Color32::from_rgb(80, 180, 80)),
+                        )
+                        .on_hover_text(hover);
+                    } else {
+                        ui.add(egui::Spinner::new().size(12.0)).on_hover_text(&hover);
+                        ui.label(RichText::new(&volume.mount_point).small())
+                            .on_hover_text(hover);
+                    }
+                }
+
+                if !app.search.results.is_empty() {
+                    ui.separator();
+                    ui.label(format_totals(&app.search.totals))
+                        .on_hover_text("All matches, including those not listed");
+                }
+
+                let stale: Vec<String> = app
+                    .config
+                    .stale_after()
+                    .map(|max_age| {
+                        app.index
+                            .volume_states()
+                            .iter()
+                            .filter(|state| state.enabled && state.is_stale(max_age))
+                            .map(|state| state.info.display_name().to_string())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                if !stale.is_empty() {
+                    ui.separator();
+                    ui.label(
+                        RichText::new(format!("⚠ {} not updated recently", stale.join(", ")))
+                            .small()
+                            .color(Color32::from_rgb(230, 160, 60)),
+                    )
+                    .on_hover_text("Start the watcher or rescan to bring the index up to date");
+                }
 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let hints: Vec<String> = [
+                        ActionId::ReloadIndex,
+                        ActionId::FocusSearch,
+                        ActionId::OpenSelected,
+                        ActionId::CopyPath,
+                        ActionId::CommandPalette,
+                    ]
+                    .into_iter()
+                    .filter_map(|id| {
+                        let shortcut = app.keymap.shortcut(id)?;
+                        Some(format!("{}: {}", shortcut, actions::action(id).label))
+                    })
+                    .collect();
                     ui.label(
-                        RichText::new(
-                            "F5: Reload | Ctrl+L: Focus | Enter: Open | Ctrl+C: Copy Path",
-                        )
-                        .small()
-                        .color(Color32::GRAY),
+                        RichText::new(hints.join(" | "))
+                            .small()
+                            .color(Color32::GRAY),
                     );
                 });
             });
         });
 }
 
+/// Column titles above the results. Dragging the line after Name resizes
+/// the name column; double-clicking it restores the default width.
+fn results_header(ui: &mut egui::Ui, app: &mut GlintApp) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), 18.0), Sense::hover());
+    let name_width = app.settings.name_column_width;
+    let color = Color32::from_gray(128);
+    let font = egui::FontId::proportional(11.0);
+    let (left, right) = (egui::Align2::LEFT_CENTER, egui::Align2::RIGHT_CENTER);
+    // Deleted files show when they went instead
+    let results = &app.search.results;
+    let deleted = results.first().is_some_and(|r| r.deleted_at.is_some());
+    let date = if deleted { "Deleted" } else { "Modified" };
+    for (x, align, title) in [
+        (rect.min.x + 50.0, left, "Name"),
+        (rect.min.x + 60.0 + name_width, left, "Folder"),
+        (rect.max.x - 120.0, right, "Size"),
+        (rect.max.x - 8.0, right, date),
+    ] {
+        let pos = egui::pos2(x, rect.center().y);
+        ui.painter().text(pos, align, title, font.clone(), color);
+    }
+
+    let handle = egui::Rect::from_center_size(
+        egui::pos2(rect.min.x + 55.0 + name_width, rect.center().y),
+        egui::vec2(8.0, rect.height()),
+    );
+    let response = ui.interact(handle, ui.id().with("name_column"), Sense::click_and_drag());
+    if response.hovered() || response.dragged() {
+        ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeHorizontal);
+    }
+    ui.painter().vline(
+        handle.center().x,
+        handle.y_range(),
+        egui::Stroke::new(1.0, color),
+    );
+
+    // Leave room for the folder, size and date columns
+    let max_width = (rect.width() - 400.0).max(80.0);
+    let width = if response.double_clicked() {
+        NAME_COLUMN_WIDTH
+    } else {
+        (name_width + response.drag_delta().x).clamp(80.0, max_width)
+    };
+    if response.dragged() || response.double_clicked() {
+        app.settings.name_column_width = width;
+        app.layout_changed = true;
+    }
+}
+
 /// Central panel with search results.
 pub fn central_panel(ctx: &egui::Context, app: &mut GlintApp) {
     egui::CentralPanel::default().show(ctx, |ui| {
-        // Handle keyboard navigation
-        if ui.input(|i| i.key_pressed(egui::Key::ArrowUp)) {
-            app.search.select_previous();
-        }
-        if ui.input(|i| i.key_pressed(egui::Key::ArrowDown)) {
-            app.search.select_next();
-        }
-        if ui.input(|i| i.key_pressed(egui::Key::PageUp)) {
-            app.search.page_up(20);
-        }
-        if ui.input(|i| i.key_pressed(egui::Key::PageDown)) {
-            app.search.page_down(20);
-        }
-        if ui.input(|i| i.key_pressed(egui::Key::Home) && i.modifiers.ctrl) {
-            app.search.select_first();
-        }
-        if ui.input(|i| i.key_pressed(egui::Key::End) && i.modifiers.ctrl) {
-            app.search.select_last();
-        }
-        if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-            app.search.open_selected();
-        }
-        if ui.input(|i| i.modifiers.ctrl && i.key_pressed(egui::Key::C)) {
-            if let Err(e) = app.search.copy_selected_path() {
-                app.status_message = format!("Failed to copy: {}", e);
-            } else {
-                app.status_message = "Path copied to clipboard".to_string();
-            }
+        if app.browse.is_some() {
+            browse_panel(ui, app);
+            return;
         }
 
         // Show error if any
@@ -257,18 +1197,39 @@ pub fn central_panel(ctx: &egui::Context, app: &mut GlintApp) {
                             .size(18.0)
                             .color(Color32::GRAY),
                     );
-                } else if app.search.query.is_empty() {
+                } else if app.search.show_pinned {
                     ui.label(
-                        RichText::new("Start typing to search files...")
+                        RichText::new("Nothing pinned here. Click ⭐ on a result to pin it.")
+                            .size(18.0)
+                            .color(Color32::GRAY),
+                    );
+                } else if app.search.show_largest {
+                    ui.label(
+                        RichText::new("No files match.")
                             .size(18.0)
                             .color(Color32::GRAY),
                     );
-                } else if app.search.query.len() < 2 {
+                } else if app.search.show_deleted {
                     ui.label(
-                        RichText::new("Type at least 2 characters to search")
+                        RichText::new("No matching files were deleted recently.")
                             .size(18.0)
                             .color(Color32::GRAY),
                     );
+                } else if app.search.query.is_empty() {
+                    ui.label(
+                        RichText::new("Start typing to search files...")
+                            .size(18.0)
+                            .color(Color32::GRAY),
+                    );
+                } else if app.search.query.len() < app.search.min_query_len {
+                    ui.label(
+                        RichText::new(format!(
+                            "Type at least {} characters to search",
+                            app.search.min_query_len
+                        ))
+                        .size(18.0)
+                        .color(Color32::GRAY),
+                    );
                 } else {
                     ui.label(
                         RichText::new("No results found")
@@ -280,9 +1241,32 @@ pub fn central_panel(ctx: &egui::Context, app: &mut GlintApp) {
             return;
         }
 
+        if app.search.is_fuzzy() {
+            ui.label(
+                RichText::new(format!(
+                    "No exact matches for \"{}\". Did you mean…",
+                    app.search.query
+                ))
+                .italics()
+                .color(Color32::from_rgb(255, 200, 0)),
+            );
+        }
+
+        results_header(ui, app);
+
         // Results list with virtual scrolling
         let row_height = 24.0;
         let total_rows = app.search.results.len();
+        let name_width = app.settings.name_column_width;
+        let colors = RowColors::new(app.dark_mode, app.high_contrast());
+        let reduced_motion = app.reduced_motion();
+        // Results from disconnected volumes are greyed out
+        let volumes = app.index.volume_states();
+        let offline: Vec<&VolumeId> = volumes
+            .iter()
+            .filter(|state| state.availability == VolumeAvailability::Offline)
+            .map(|state| &state.info.id)
+            .collect();
 
         egui::ScrollArea::vertical()
             .auto_shrink([false, false])
@@ -296,27 +1280,14 @@ pub fn central_panel(ctx: &egui::Context, app: &mut GlintApp) {
                     if let Some(result) = app.search.results.get(row) {
                         let record = &result.record;
                         let is_selected = row == app.search.selected;
+                        let is_marked = app.search.is_marked(row);
+                        let is_pinned = app.search.pins().is_pinned(record);
+                        let is_offline =
+                            result.source.is_none() && offline.contains(&&record.volume_id);
 
-                        // Row background
-                        let bg_color = if is_selected {
-                            Color32::from_rgb(0, 120, 212) // Blue selection
-                        } else if row % 2 == 0 {
-                            Color32::from_gray(30)
-                        } else {
-                            Color32::from_gray(35)
-                        };
-
-                        let text_color = if is_selected {
-                            Color32::WHITE
-                        } else {
-                            Color32::from_gray(200)
-                        };
-
-                        let secondary_color = if is_selected {
-                            Color32::from_gray(220)
-                        } else {
-                            Color32::from_gray(128)
-                        };
+                        let bg_color = colors.background(row, is_selected, is_marked);
+                        let text_color = colors.text(is_selected || is_marked, is_offline);
+                        let secondary_color = colors.secondary(is_selected || is_marked);
 
                         // Draw row
                         let (rect, response) = ui.allocate_exact_size(
@@ -324,13 +1295,69 @@ pub fn central_panel(ctx: &egui::Context, app: &mut GlintApp) {
                             Sense::click(),
                         );
 
+                        // Pin star, clickable
+                        let star_rect = egui::Rect::from_min_size(
+                            rect.min + egui::vec2(4.0, 2.0),
+                            egui::vec2(20.0, 20.0),
+                        );
+                        let star_clicked = response.clicked()
+                            && response
+                                .interact_pointer_pos()
+                                .is_some_and(|pos| star_rect.contains(pos));
+
+                        // Folder breadcrumbs; a click searches in that folder
+                        let path_rect = egui::Rect::from_min_max(
+                            egui::pos2(rect.min.x + 60.0 + name_width, rect.min.y),
+                            egui::pos2(rect.max.x - 200.0, rect.max.y),
+                        );
+                        let crumbs = breadcrumbs(ui, path_rect, &record.path, secondary_color);
+                        let crumb_at = |pos: Option<egui::Pos2>| {
+                            let pos = pos?;
+                            crumbs
+                                .iter()
+                                .find(|c| c.rect.contains(pos))
+                                .and_then(|c| c.folder.clone())
+                        };
+                        let hovered_crumb = crumb_at(response.hover_pos());
+                        let clicked_crumb = if response.clicked() {
+                            crumb_at(response.interact_pointer_pos())
+                        } else {
+                            None
+                        };
+
+                        let size = if record.is_dir {
+                            app.folder_sizes.get(&app.index, record)
+                        } else {
+                            record.size
+                        };
+
                         if ui.is_rect_visible(rect) {
                             ui.painter().rect_filled(rect, 0.0, bg_color);
 
+                            // Star (dimmed on hover when not pinned)
+                            if is_pinned || response.hovered() {
+                                let star_color = if is_pinned {
+                                    Color32::from_rgb(255, 200, 0)
+                                } else {
+                                    secondary_color
+                                };
+                                ui.painter().text(
+                                    star_rect.center(),
+                                    egui::Align2::CENTER_CENTER,
+                                    "⭐",
+                                    egui::FontId::proportional(12.0),
+                                    star_color,
+                                );
+                            }
+
                             // Icon
-                            let icon = if record.is_dir { "📁" } else { "📄" };
+                            let icon = match (record.is_dir, record.cloud) {
+                                (true, _) => "📁",
+                                (false, true) => "☁",
+                                (false, false) => "📄",
+                            };
                             let icon_rect = egui::Rect::from_min_size(
-                                rect.min + egui::vec2(8.0, 2.0),
+                                rect.min + egui::vec2(26.0, 2.0),
                                 egui::vec2(20.0, 20.0),
                             );
                             ui.painter().text(
@@ -343,53 +1370,93 @@ pub fn central_panel(ctx: &egui::Context, app: &mut GlintApp) {
 
                             // Filename
                             let name_rect = egui::Rect::from_min_max(
-                                rect.min + egui::vec2(32.0, 0.0),
-                                egui::pos2(rect.min.x + 280.0, rect.max.y),
-                            );
-                            ui.painter().text(
-                                name_rect.left_center(),
-                                egui::Align2::LEFT_CENTER,
-                                &record.name,
-                                egui::FontId::proportional(13.0),
-                                text_color,
+                                rect.min + egui::vec2(50.0, 0.0),
+                                egui::pos2(rect.min.x + 50.0 + name_width, rect.max.y),
                             );
+                            let name_end = ui
+                                .painter()
+                                .text(
+                                    name_rect.left_center(),
+                                    egui::Align2::LEFT_CENTER,
+                                    &record.name,
+                                    egui::FontId::proportional(13.0),
+                                    text_color,
+                                )
+                                .right();
+
+                            // Badges for a file found under several paths, for
+                            // the attached index a result comes from and for
+                            // a volume that is offline
+                            let mut badges = Vec::new();
+                            if result.links > 1 {
+                                badges.push(format!("{} links", result.links));
+                            }
+                            if let Some(source) = &result.source {
+                                badges.push(format!("[{}]", source));
+                            }
+                            if is_offline {
+                                badges.push("offline".to_string());
+                            }
+                            if !badges.is_empty() {
+                                ui.painter().text(
+                                    egui::pos2(name_end + 6.0, rect.center().y),
+                                    egui::Align2::LEFT_CENTER,
+                                    badges.join("  "),
+                                    egui::FontId::proportional(10.0),
+                                    secondary_color,
+                                );
+                            }
 
-                            // Path (directory part)
-                            let path_dir = std::path::Path::new(&record.path)
-                                .parent()
-                                .map(|p| p.to_string_lossy().to_string())
-                                .unwrap_or_default();
-                            let path_rect = egui::Rect::from_min_max(
-                                egui::pos2(rect.min.x + 290.0, rect.min.y),
-                                egui::pos2(rect.max.x - 200.0, rect.max.y),
-                            );
-                            ui.painter().text(
-                                path_rect.left_center(),
-                                egui::Align2::LEFT_CENTER,
-                                &path_dir,
-                                egui::FontId::proportional(12.0),
-                                secondary_color,
-                            );
-
-                            // Size (for files)
-                            if !record.is_dir {
-                                if let Some(size) = record.size {
-                                    let size_rect = egui::Rect::from_min_max(
-                                        egui::pos2(rect.max.x - 190.0, rect.min.y),
-                                        egui::pos2(rect.max.x - 120.0, rect.max.y),
+                            // Path (directory part), hovered folder underlined
+                            for crumb in &crumbs {
+                                let hovered = crumb.folder.is_some()
+                                    && response
+                                        .hover_pos()
+                                        .is_some_and(|pos| crumb.rect.contains(pos));
+                                if hovered {
+                                    ui.painter().galley_with_override_text_color(
+                                        crumb.rect.min,
+                                        crumb.galley.clone(),
+                                        text_color,
+                                    );
+                                    ui.painter().hline(
+                                        crumb.rect.x_range(),
+                                        crumb.rect.bottom(),
+                                        egui::Stroke::new(1.0, text_color),
                                     );
-                                    ui.painter().text(
-                                        size_rect.right_center(),
-                                        egui::Align2::RIGHT_CENTER,
-                                        format_size(size),
-                                        egui::FontId::proportional(12.0),
+                                } else {
+                                    ui.painter().galley(
+                                        crumb.rect.min,
+                                        crumb.galley.clone(),
                                         secondary_color,
                                     );
                                 }
                             }
 
-                            // Modified date
-                            if let Some(modified) = record.modified {
+                            // Size, summed in the background for folders
+                            let size_rect = egui::Rect::from_min_max(
+                                egui::pos2(rect.max.x - 190.0, rect.min.y),
+                                egui::pos2(rect.max.x - 120.0, rect.max.y),
+                            );
+                            if let Some(size) = size {
+                                ui.painter().text(
+                                    size_rect.right_center(),
+                                    egui::Align2::RIGHT_CENTER,
+                                    format_size(size),
+                                    egui::FontId::proportional(12.0),
+                                    secondary_color,
+                                );
+                            } else if record.is_dir {
+                                folder_size_spinner(
+                                    ui,
+                                    size_rect.right_center(),
+                                    reduced_motion,
+                                    secondary_color,
+                                );
+                            }
+
+                            // Modified date, or when a deleted file went
+                            if let Some(modified) = result.deleted_at.or(record.modified) {
                                 let date_rect = egui::Rect::from_min_max(
                                     egui::pos2(rect.max.x - 110.0, rect.min.y),
                                     egui::pos2(rect.max.x - 8.0, rect.max.y),
@@ -397,24 +1464,54 @@ pub fn central_panel(ctx: &egui::Context, app: &mut GlintApp) {
                                 ui.painter().text(
                                     date_rect.right_center(),
                                     egui::Align2::RIGHT_CENTER,
-                                    modified.format("%Y-%m-%d %H:%M").to_string(),
+                                    format_date_time(modified),
                                     egui::FontId::proportional(12.0),
                                     secondary_color,
                                 );
                             }
                         }
 
+                        // Copy the name for use in context menu (avoids borrow issues)
+                        let record_name = record.name.clone();
+                        let file_id = format!("fileid:{}:{}", record.volume_id, record.id);
+
+                        let response = if hovered_crumb.is_some() {
+                            ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+                            response.on_hover_text("Search in this folder (Ctrl+click to open it)")
+                        } else {
+                            let volume = volumes
+                                .iter()
+                                .find(|state| {
+                                    result.source.is_none() && state.info.id == record.volume_id
+                                })
+                                .map(|state| &state.info);
+                            let path_format = app.config.ui.path_format;
+                            response.on_hover_ui(|ui| {
+                                result_tooltip(ui, result, size, volume, path_format)
+                            })
+                        };
+
                         // Handle clicks
-                        if response.clicked() {
-                            app.search.selected = row;
+                        if star_clicked {
+                            app.toggle_pin(row);
+                        } else if let Some(folder) = clicked_crumb {
+                            if ui.input(|i| i.modifiers.command) {
+                                if let Err(e) = glint_frontend::actions::open(&folder) {
+                                    app.status_message =
+                                        format!("Failed to open {}: {}", folder, e);
+                                }
+                            } else {
+                                app.search.set_folder(Some(folder));
+                            }
+                        } else if response.clicked() {
+                            let modifiers = ui.input(|i| i.modifiers);
+                            app.search
+                                .click_row(row, modifiers.command, modifiers.shift);
                         }
-                        if response.double_clicked() {
-                            app.search.open_selected();
+                        if response.double_clicked() && hovered_crumb.is_none() {
+                            app.open_or_browse(row);
                         }
 
-                        // Copy the name for use in context menu (avoids borrow issues)
-                        let record_name = record.name.clone();
-
                         // Context menu
                         response.context_menu(|ui| {
                             if ui.button("Open in Explorer").clicked() {
@@ -422,23 +1519,108 @@ pub fn central_panel(ctx: &egui::Context, app: &mut GlintApp) {
                                 app.search.open_selected();
                                 ui.close_menu();
                             }
+                            if app.can_browse(row) && ui.button("Browse Folder").clicked() {
+                                app.open_or_browse(row);
+                                ui.close_menu();
+                            }
+                            #[cfg(windows)]
+                            if ui.button("Open With...").clicked() {
+                                app.search.selected = row;
+                                if let Some(result) = app.search.results.get(row) {
+                                    if let Err(e) = crate::shell::open_with(&result.record.path) {
+                                        app.status_message = format!("Failed to open: {}", e);
+                                    }
+                                }
+                                ui.close_menu();
+                            }
                             if ui.button("Copy Path").clicked() {
                                 app.search.selected = row;
-                                if let Err(e) = app.search.copy_selected_path() {
+                                if let Err(e) =
+                                    app.search.copy_selected_path(app.config.ui.path_format)
+                                {
                                     app.status_message = format!("Failed to copy: {}", e);
                                 } else {
                                     app.status_message = "Path copied to clipboard".to_string();
                                 }
                                 ui.close_menu();
                             }
+                            ui.menu_button("Copy Path As", |ui| {
+                                for format in PathFormat::ALL {
+                                    if ui.button(format.label()).clicked() {
+                                        app.search.selected = row;
+                                        if let Err(e) = app.search.copy_selected_path(format) {
+                                            app.status_message = format!("Failed to copy: {}", e);
+                                        } else {
+                                            app.status_message = format!(
+                                                "Path copied to clipboard ({})",
+                                                format.label()
+                                            );
+                                        }
+                                        ui.close_menu();
+                                    }
+                                }
+                            });
                             ui.separator();
+                            let pin_label = if is_pinned { "Unpin" } else { "Pin" };
+                            if ui.button(pin_label).clicked() {
+                                app.toggle_pin(row);
+                                ui.close_menu();
+                            }
                             if ui.button("Copy Name").clicked() {
-                                if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                                    let _ = clipboard.set_text(&record_name);
+                                if glint_frontend::actions::copy_text(&record_name).is_ok() {
                                     app.status_message = "Name copied to clipboard".to_string();
                                 }
                                 ui.close_menu();
                             }
+                            if ui.button("Copy File ID").on_hover_text(&file_id).clicked() {
+                                if glint_frontend::actions::copy_text(&file_id).is_ok() {
+                                    app.status_message = format!("{} copied to clipboard", file_id);
+                                }
+                                ui.close_menu();
+                            }
+                            ui.separator();
+                            if ui.button("Rename Results...").clicked() {
+                                app.open_rename_dialog();
+                                ui.close_menu();
+                            }
+                            #[cfg(windows)]
+                            {
+                                let count = app.search.targets(row).len();
+                                let items = match count {
+                                    1 => String::new(),
+                                    n => format!(" {} Items", n),
+                                };
+                                if ui.button(format!("Copy{} to...", items)).clicked() {
+                                    app.transfer_results(row, crate::shell::Transfer::Copy);
+                                    ui.close_menu();
+                                }
+                                if ui.button(format!("Move{} to...", items)).clicked() {
+                                    app.transfer_results(row, crate::shell::Transfer::Move);
+                                    ui.close_menu();
+                                }
+                            }
+                            #[cfg(windows)]
+                            if ui.button("Move to Recycle Bin").clicked() {
+                                app.recycle_result(row);
+                                ui.close_menu();
+                            }
+                            #[cfg(windows)]
+                            {
+                                ui.separator();
+                                if ui.button("Show More Options").clicked() {
+                                    app.search.selected = row;
+                                    ui.close_menu();
+                                    // Runs a modal menu loop until the user picks or dismisses
+                                    if let Some(result) = app.search.results.get(row) {
+                                        if let Err(e) =
+                                            crate::shell::show_context_menu(&result.record.path)
+                                        {
+                                            app.status_message =
+                                                format!("Shell menu failed: {}", e);
+                                        }
+                                    }
+                                }
+                            }
                         });
                     }
                 }
@@ -447,6 +1629,243 @@ pub fn central_panel(ctx: &egui::Context, app: &mut GlintApp) {
 }
 
 /// Settings window.
+/// Index-backed listing of one folder, shown instead of the results.
+fn browse_panel(ui: &mut egui::Ui, app: &mut GlintApp) {
+    let index = Arc::clone(&app.index);
+    let colors = RowColors::new(app.dark_mode, app.high_contrast());
+    let reduced_motion = app.reduced_motion();
+    let Some(browse) = app.browse.as_mut() else {
+        return;
+    };
+    browse.refresh(&index);
+
+    // Breadcrumbs and filter
+    let mut go_to = None;
+    let mut close = false;
+    ui.horizontal(|ui| {
+        if ui
+            .button("← Results")
+            .on_hover_text("Back to the search results (Esc)")
+            .clicked()
+        {
+            close = true;
+        }
+        ui.separator();
+        let last = browse.trail.len() - 1;
+        for (depth, folder) in browse.trail.iter().enumerate() {
+            let label = if folder.name.is_empty() {
+                &folder.path
+            } else {
+                &folder.name
+            };
+            if depth > 0 {
+                ui.label(RichText::new("›").color(Color32::GRAY));
+            }
+            if ui.selectable_label(depth == last, label).clicked() {
+                go_to = Some(depth);
+            }
+        }
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .add(
+                    egui::TextEdit::singleline(&mut browse.filter)
+                        .hint_text("Filter")
+                        .desired_width(160.0),
+                )
+                .changed()
+            {
+                browse.apply_view();
+            }
+        });
+    });
+    ui.separator();
+
+    // Column headers sort the listing
+    let mut sort = None;
+    ui.horizontal(|ui| {
+        for column in BrowseSort::ALL {
+            let arrow = match (browse.sort == column, browse.descending) {
+                (false, _) => "",
+                (true, false) => " ⏶",
+                (true, true) => " ⏷",
+            };
+            if ui
+                .selectable_label(
+                    browse.sort == column,
+                    format!("{}{}", column.label(), arrow),
+                )
+                .clicked()
+            {
+                sort = Some(column);
+            }
+        }
+        ui.label(
+            RichText::new(format!("{} items", format_number(browse.entries.len())))
+                .small()
+                .color(Color32::GRAY),
+        );
+    });
+    if let Some(column) = sort {
+        browse.sort_by(column);
+    }
+
+    // Keyboard: arrows select, Enter opens, Alt+Up goes up, Esc closes
+    let (up, down, enter, parent, escape) = ui.input(|i| {
+        (
+            i.key_pressed(egui::Key::ArrowUp) && !i.modifiers.alt,
+            i.key_pressed(egui::Key::ArrowDown),
+            i.key_pressed(egui::Key::Enter),
+            i.key_pressed(egui::Key::ArrowUp) && i.modifiers.alt,
+            i.key_pressed(egui::Key::Escape),
+        )
+    });
+    let mut scroll_to_selected = false;
+    if up && browse.selected > 0 {
+        browse.selected -= 1;
+        scroll_to_selected = true;
+    }
+    if down && browse.selected + 1 < browse.entries.len() {
+        browse.selected += 1;
+        scroll_to_selected = true;
+    }
+    let mut activate = enter.then_some(browse.selected);
+    close |= escape;
+
+    if browse.entries.is_empty() {
+        ui.centered_and_justified(|ui| {
+            let text = if browse.filter.is_empty() {
+                "This folder is empty"
+            } else {
+                "No items match the filter"
+            };
+            ui.label(RichText::new(text).size(18.0).color(Color32::GRAY));
+        });
+    } else {
+        let row_height = 24.0;
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show_rows(ui, row_height, browse.entries.len(), |ui, row_range| {
+                for row in row_range {
+                    let record = &browse.entries[row];
+                    let is_selected = row == browse.selected;
+                    let text_color = colors.text(is_selected, false);
+                    let secondary_color = colors.secondary(is_selected);
+                    let bg_color = colors.background(row, is_selected, false);
+
+                    let (rect, response) = ui.allocate_exact_size(
+                        egui::vec2(ui.available_width(), row_height),
+                        Sense::click(),
+                    );
+                    if is_selected && scroll_to_selected {
+                        response.scroll_to_me(None);
+                    }
+                    if ui.is_rect_visible(rect) {
+                        let painter = ui.painter();
+                        painter.rect_filled(rect, 0.0, bg_color);
+                        painter.text(
+                            rect.min + egui::vec2(16.0, row_height / 2.0),
+                            egui::Align2::CENTER_CENTER,
+                            if record.is_dir { "📁" } else { "📄" },
+                            egui::FontId::proportional(14.0),
+                            text_color,
+                        );
+                        painter.text(
+                            rect.min + egui::vec2(32.0, row_height / 2.0),
+                            egui::Align2::LEFT_CENTER,
+                            &record.name,
+                            egui::FontId::proportional(13.0),
+                            text_color,
+                        );
+                        let size_pos = egui::pos2(rect.max.x - 120.0, rect.center().y);
+                        let size = if record.is_dir {
+                            app.folder_sizes.get(&app.index, record)
+                        } else {
+                            record.size
+                        };
+                        if let Some(size) = size {
+                            painter.text(
+                                size_pos,
+                                egui::Align2::RIGHT_CENTER,
+                                format_size(size),
+                                egui::FontId::proportional(12.0),
+                                secondary_color,
+                            );
+                        } else if record.is_dir {
+                            folder_size_spinner(ui, size_pos, reduced_motion, secondary_color);
+                        }
+                        if let Some(modified) = record.modified {
+                            painter.text(
+                                egui::pos2(rect.max.x - 8.0, rect.center().y),
+                                egui::Align2::RIGHT_CENTER,
+                                format_date_time(modified),
+                                egui::FontId::proportional(12.0),
+                                secondary_color,
+                            );
+                        }
+                    }
+
+                    if response.clicked() {
+                        browse.selected = row;
+                    }
+                    if response.double_clicked() {
+                        activate = Some(row);
+                    }
+                }
+            });
+    }
+
+    // Folders open in place, files with their default program
+    if let Some(record) = activate.and_then(|row| browse.entries.get(row).cloned()) {
+        if record.is_dir {
+            browse.enter(&index, record);
+            browse.selected = 0;
+        } else if let Err(e) = glint_frontend::actions::open(&record.path) {
+            app.status_message = format!("Failed to open {}: {}", record.path, e);
+        }
+    } else if let Some(depth) = go_to {
+        browse.go_to(&index, depth);
+    } else if parent {
+        browse.go_up(&index);
+    }
+    if close {
+        app.browse = None;
+    }
+}
+
+/// A row choosing an accessibility option: on, off, or whatever the
+/// system says (`None`), which the label shows.
+fn follow_system_choice(
+    ui: &mut egui::Ui,
+    label: &str,
+    choice: &mut Option<bool>,
+    system: bool,
+) -> egui::Response {
+    let on_off = |on: bool| if on { "On" } else { "Off" };
+    let follow = format!("As in Windows ({})", on_off(system).to_lowercase());
+    let text = |choice: Option<bool>| match choice {
+        None => follow.clone(),
+        Some(on) => on_off(on).to_string(),
+    };
+    let before = *choice;
+    let mut response = ui
+        .horizontal(|ui| {
+            ui.label(label);
+            egui::ComboBox::from_id_salt(label)
+                .selected_text(text(*choice))
+                .show_ui(ui, |ui| {
+                    for option in [None, Some(true), Some(false)] {
+                        ui.selectable_value(choice, option, text(option));
+                    }
+                });
+        })
+        .response;
+    if *choice != before {
+        response.mark_changed();
+    }
+    response
+}
+
 pub fn settings_window(ctx: &egui::Context, app: &mut GlintApp) {
     let mut show = app.show_settings;
     egui::Window::new("Settings")
@@ -457,19 +1876,174 @@ pub fn settings_window(ctx: &egui::Context, app: &mut GlintApp) {
             egui::ScrollArea::vertical().show(ui, |ui| {
                 ui.heading("Appearance");
                 ui.checkbox(&mut app.dark_mode, "Dark mode");
+                let system = app.system_preferences;
+                let high_contrast = follow_system_choice(
+                    ui,
+                    "High contrast:",
+                    &mut app.settings.high_contrast,
+                    system.high_contrast,
+                );
+                let reduced_motion = follow_system_choice(
+                    ui,
+                    "Reduce motion:",
+                    &mut app.settings.reduced_motion,
+                    system.reduced_motion,
+                )
+                .on_hover_text("Scroll and open panels without animation");
+                if high_contrast.changed() || reduced_motion.changed() {
+                    if let Err(e) = app.settings.save() {
+                        app.toasts.error(format!("Failed to save settings: {}", e));
+                    }
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Copy paths as:");
+                    let before = app.config.ui.path_format;
+                    egui::ComboBox::from_id_salt("path_format")
+                        .selected_text(before.label())
+                        .show_ui(ui, |ui| {
+                            for format in PathFormat::ALL {
+                                ui.selectable_value(
+                                    &mut app.config.ui.path_format,
+                                    format,
+                                    format.label(),
+                                );
+                            }
+                        });
+                    if app.config.ui.path_format != before {
+                        if let Err(e) = app.config.save() {
+                            app.toasts.error(format!("Failed to save config: {}", e));
+                        }
+                    }
+                });
+                let locale_before = Locale::from_config(&app.config.ui);
+                ui.horizontal(|ui| {
+                    ui.label("Numbers:");
+                    egui::ComboBox::from_id_salt("number_format")
+                        .selected_text(app.config.ui.number_format.label())
+                        .show_ui(ui, |ui| {
+                            for format in NumberFormat::ALL {
+                                ui.selectable_value(
+                                    &mut app.config.ui.number_format,
+                                    format,
+                                    format.label(),
+                                );
+                            }
+                        });
+                    ui.label("Dates:");
+                    egui::ComboBox::from_id_salt("date_format")
+                        .selected_text(app.config.ui.date_format.label())
+                        .show_ui(ui, |ui| {
+                            for format in DateFormat::ALL {
+                                ui.selectable_value(
+                                    &mut app.config.ui.date_format,
+                                    format,
+                                    format.label(),
+                                );
+                            }
+                        });
+                });
+                ui.checkbox(&mut app.config.ui.si_units, "Sizes in kB/MB of 1000 bytes")
+                    .on_hover_text("Count sizes in powers of 1000, as drive makers do, not 1024");
+                let locale = Locale::from_config(&app.config.ui);
+                if locale != locale_before {
+                    set_locale(locale);
+                    if let Err(e) = app.config.save() {
+                        app.toasts.error(format!("Failed to save config: {}", e));
+                    }
+                }
 
                 ui.add_space(10.0);
                 ui.separator();
 
                 ui.heading("Search");
+                // Applied while dragging, saved once the value is settled
+                let mut save = false;
                 ui.horizontal(|ui| {
                     ui.label("Max results:");
-                    ui.add(
+                    let response = ui.add(
                         egui::DragValue::new(&mut app.search.max_results)
                             .range(100..=100000)
                             .speed(100),
                     );
+                    if response.changed() {
+                        app.search.mark_dirty();
+                    }
+                    save |= response.drag_stopped() || response.lost_focus();
                 });
+                ui.horizontal(|ui| {
+                    ui.label("Search delay:");
+                    let mut millis = app.search.debounce.as_millis() as u64;
+                    let response = ui
+                        .add(
+                            egui::DragValue::new(&mut millis)
+                                .range(0..=1000)
+                                .speed(5)
+                                .suffix(" ms"),
+                        )
+                        .on_hover_text("How long to wait after typing before searching");
+                    if response.changed() {
+                        app.search.debounce = Duration::from_millis(millis);
+                    }
+                    save |= response.drag_stopped() || response.lost_focus();
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Minimum query length:");
+                    let response = ui
+                        .add(egui::DragValue::new(&mut app.search.min_query_len).range(1..=5))
+                        .on_hover_text("Shorter queries don't start a search");
+                    if response.changed() {
+                        app.search.mark_dirty();
+                    }
+                    save |= response.drag_stopped() || response.lost_focus();
+                });
+                if save {
+                    app.save_search_settings();
+                }
+                if ui
+                    .checkbox(
+                        &mut app.config.general.typo_tolerance,
+                        "Typo-tolerant search",
+                    )
+                    .on_hover_text("When nothing matches, show results with one typo")
+                    .changed()
+                {
+                    app.search.typo_tolerance = app.config.general.typo_tolerance;
+                    app.search.mark_dirty();
+                    if let Err(e) = app.config.save() {
+                        app.toasts.error(format!("Failed to save config: {}", e));
+                    }
+                }
+                if ui
+                    .checkbox(
+                        &mut app.config.general.hide_offline_volumes,
+                        "Hide offline volumes",
+                    )
+                    .on_hover_text(
+                        "Leave out files of disconnected drives instead of greying them out",
+                    )
+                    .changed()
+                {
+                    app.index
+                        .set_hide_offline(app.config.general.hide_offline_volumes);
+                    app.search.mark_dirty();
+                    if let Err(e) = app.config.save() {
+                        app.toasts.error(format!("Failed to save config: {}", e));
+                    }
+                }
+                if ui
+                    .checkbox(&mut app.config.general.dedup_links, "Merge duplicate paths")
+                    .on_hover_text(
+                        "Show a file indexed under several paths (e.g. a folder volume \
+                         inside an indexed drive) once, with a \"links\" badge",
+                    )
+                    .changed()
+                {
+                    app.search.dedup_links = app.config.general.dedup_links;
+                    app.search.mark_dirty();
+                    if let Err(e) = app.config.save() {
+                        app.toasts.error(format!("Failed to save config: {}", e));
+                    }
+                }
 
                 ui.add_space(10.0);
                 ui.separator();
@@ -488,52 +2062,58 @@ pub fn settings_window(ctx: &egui::Context, app: &mut GlintApp) {
 
                 ui.add_space(10.0);
 
-                if ui.button("Reload Index (F5)").clicked() {
-                    app.reload_index();
+                if ui.button(action_hint(app, ActionId::ReloadIndex)).clicked() {
+                    actions::execute(ActionId::ReloadIndex, ctx, app);
                 }
 
                 ui.add_space(10.0);
                 ui.separator();
 
-                ui.heading("Excluded Folders");
-                ui.label("These folders will be skipped during indexing:");
-
-                // Show current exclusions
-                let mut to_remove: Option<usize> = None;
-                for (i, path) in app.config.exclude.paths.iter().enumerate() {
+                ui.heading("Indexed Volumes");
+                ui.label("Unchecked volumes keep their data but are hidden from searches:");
+                let mut volumes = app.index.volume_states();
+                volumes.sort_by(|a, b| a.info.display_name().cmp(b.info.display_name()));
+                if volumes.is_empty() {
+                    ui.label("No volumes indexed yet.");
+                }
+                for state in volumes {
+                    let mut enabled = state.enabled;
+                    let label = format!(
+                        "{} ({} entries)",
+                        state.info.display_name(),
+                        format_number(state.record_count as usize)
+                    );
                     ui.horizontal(|ui| {
-                        ui.label(format!("📁 {}", path));
-                        if ui.small_button("✕").on_hover_text("Remove").clicked() {
-                            to_remove = Some(i);
+                        if ui.checkbox(&mut enabled, label).changed() {
+                            app.set_volume_enabled(&state.info, enabled);
                         }
+                        update_age_label(ui, app, &state);
                     });
                 }
 
-                // Remove if requested
-                if let Some(idx) = to_remove {
-                    app.config.exclude.paths.remove(idx);
-                    if let Err(e) = app.config.save() {
-                        app.status_message = format!("Failed to save config: {}", e);
-                    }
-                }
+                ui.add_space(10.0);
+                ui.separator();
 
-                // Add folder button with native picker
-                if ui.button("➕ Add Excluded Folder...").clicked() {
-                    if let Some(folder) = rfd::FileDialog::new()
-                        .set_title("Select folder to exclude from indexing")
-                        .pick_folder()
-                    {
-                        let path_str = folder.to_string_lossy().to_string();
-                        if !app.config.exclude.paths.contains(&path_str) {
-                            app.config.exclude.paths.push(path_str);
-                            if let Err(e) = app.config.save() {
-                                app.status_message = format!("Failed to save config: {}", e);
-                            } else {
-                                app.status_message =
-                                    "Folder added to exclusions. Re-index to apply.".to_string();
-                            }
-                        }
-                    }
+                ui.heading("Excluded Folders");
+                let exclude = &app.config.exclude;
+                let presets_on = PRESETS
+                    .iter()
+                    .filter(|p| {
+                        exclude
+                            .presets
+                            .iter()
+                            .any(|n| n.eq_ignore_ascii_case(p.name))
+                    })
+                    .count();
+                ui.label(format!(
+                    "Skipped during indexing: {} folders, {} name patterns and {} of {} presets.",
+                    exclude.paths.len(),
+                    exclude.patterns.len(),
+                    presets_on,
+                    PRESETS.len()
+                ));
+                if ui.button("Manage Exclusions...").clicked() {
+                    app.exclusions.open = true;
                 }
 
                 ui.add_space(10.0);
@@ -552,12 +2132,111 @@ pub fn settings_window(ctx: &egui::Context, app: &mut GlintApp) {
                         .on_hover_text("Open in Explorer")
                         .clicked()
                     {
-                        let _ = open::that(&index_path);
+                        let _ = glint_frontend::actions::open(&index_path);
                     }
                 });
+
+                ui.add_space(10.0);
+                ui.separator();
+
+                ui.heading("Keyboard Shortcuts");
+                keyboard_shortcuts(ui, app);
             });
         });
     app.show_settings = show;
+    if !show {
+        app.rebinding = None;
+    }
+}
+
+/// Editable list of action shortcuts, with conflicts highlighted.
+fn keyboard_shortcuts(ui: &mut egui::Ui, app: &mut GlintApp) {
+    // Take the next key press for the action being rebound
+    if let Some(id) = app.rebinding {
+        let pressed = ui.input(|i| {
+            i.events.iter().find_map(|e| match e {
+                egui::Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } => Some((*key, *modifiers)),
+                _ => None,
+            })
+        });
+        if let Some((key, modifiers)) = pressed {
+            // A bare Escape cancels
+            if key != egui::Key::Escape || modifiers.any() {
+                app.keymap
+                    .set(id, Some(Shortcut::from_event(key, modifiers)));
+                app.save_key_bindings();
+            }
+            app.rebinding = None;
+        }
+    }
+
+    ui.label("Click a shortcut, then press the new key combination (Esc cancels).");
+    let mut changed = false;
+    if ui.button("Restore Defaults").clicked() {
+        for action in actions::ACTIONS {
+            app.keymap.reset(action.id);
+        }
+        changed = true;
+    }
+
+    egui::Grid::new("key_bindings")
+        .num_columns(3)
+        .striped(true)
+        .spacing([8.0, 4.0])
+        .show(ui, |ui| {
+            for action in actions::ACTIONS {
+                ui.label(action.title());
+                ui.horizontal(|ui| {
+                    let text = if app.rebinding == Some(action.id) {
+                        "Press keys...".to_string()
+                    } else {
+                        app.keymap
+                            .shortcut(action.id)
+                            .map(|s| s.to_string())
+                            .unwrap_or_else(|| "—".to_string())
+                    };
+                    if ui.button(text).clicked() {
+                        app.rebinding = Some(action.id);
+                    }
+                    if app.keymap.shortcut(action.id).is_some()
+                        && ui.small_button("✕").on_hover_text("Remove shortcut").clicked()
+                    {
+                        app.keymap.set(action.id, None);
+                        changed = true;
+                    }
+                    if !app.keymap.is_default(action.id)
+                        && ui.small_button("↺").on_hover_text("Restore default").clicked()
+                    {
+                        app.keymap.reset(action.id);
+                        changed = true;
+                    }
+                });
+
+                let conflicts = app.keymap.conflicts(action.id);
+                if conflicts.is_empty() {
+                    ui.label("");
+                } else {
+                    let names: Vec<&str> = conflicts
+                        .iter()
+                        .map(|id| actions::action(*id).label)
+                        .collect();
+                    ui.colored_label(
+                        Color32::from_rgb(255, 150, 0),
+                        format!("⚠ Also used by {}", names.join(", ")),
+                    );
+                }
+                ui.end_row();
+            }
+        });
+
+    if changed {
+        app.save_key_bindings();
+    }
 }
 
 /// About window.
@@ -584,6 +2263,603 @@ pub fn about_window(ctx: &egui::Context, app: &mut GlintApp) {
     app.show_about = show;
 }
 
+/// The query syntax reference, generated from the parser's tokens.
+pub fn syntax_window(ctx: &egui::Context, app: &mut GlintApp) {
+    let mut show = app.show_syntax;
+    egui::Window::new("Query Syntax")
+        .open(&mut show)
+        .default_width(620.0)
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.label(
+                    "Parts of a query are separated by spaces. Filter tokens narrow the \
+                     results; the other parts are joined into the search text.",
+                );
+                for section in syntax::sections() {
+                    ui.add_space(8.0);
+                    ui.strong(section.title);
+                    egui::Grid::new(section.title)
+                        .num_columns(3)
+                        .spacing([16.0, 4.0])
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for entry in &section.entries {
+                                ui.monospace(&entry.syntax);
+                                ui.label(syntax::describe(entry));
+                                ui.label(RichText::new(entry.example).monospace().weak());
+                                ui.end_row();
+                            }
+                        });
+                }
+                ui.add_space(8.0);
+                for note in syntax::notes() {
+                    ui.label(RichText::new(note).weak());
+                }
+            });
+        });
+    app.show_syntax = show;
+}
+
+/// Command palette listing all actions, filtered as you type.
+pub fn command_palette(ctx: &egui::Context, app: &mut GlintApp) {
+    let matches = app.palette.matches(app);
+    let mut run: Option<ActionId> = None;
+
+    let (escape, up, down, enter) = ctx.input(|i| {
+        (
+            i.key_pressed(egui::Key::Escape),
+            i.key_pressed(egui::Key::ArrowUp),
+            i.key_pressed(egui::Key::ArrowDown),
+            i.key_pressed(egui::Key::Enter),
+        )
+    });
+    if escape {
+        app.palette.close();
+        return;
+    }
+    if up {
+        app.palette.selected = app.palette.selected.saturating_sub(1);
+    }
+    if down && app.palette.selected + 1 < matches.len() {
+        app.palette.selected += 1;
+    }
+    app.palette.selected = app.palette.selected.min(matches.len().saturating_sub(1));
+    if enter {
+        run = matches.get(app.palette.selected).map(|a| a.id);
+    }
+
+    egui::Window::new("Command Palette")
+        .title_bar(false)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 60.0])
+        .fixed_size([480.0, 0.0])
+        .show(ctx, |ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut app.palette.query)
+                    .hint_text("Type a command...")
+                    .desired_width(f32::INFINITY),
+            );
+            if app.palette.focus {
+                response.request_focus();
+                app.palette.focus = false;
+            }
+            if response.changed() {
+                app.palette.selected = 0;
+            }
+
+            ui.separator();
+            if matches.is_empty() {
+                ui.label(RichText::new("No matching commands").color(Color32::GRAY));
+                return;
+            }
+
+            egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                for (i, action) in matches.iter().enumerate() {
+                    let selected = i == app.palette.selected;
+                    ui.horizontal(|ui| {
+                        let label = ui.selectable_label(selected, action.title());
+                        if selected {
+                            label.scroll_to_me(None);
+                        }
+                        if label.clicked() {
+                            run = Some(action.id);
+                        }
+                        if let Some(shortcut) = app.keymap.shortcut(action.id) {
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    ui.label(
+                                        RichText::new(shortcut.to_string())
+                                            .small()
+                                            .color(Color32::GRAY),
+                                    );
+                                },
+                            );
+                        }
+                    });
+                }
+            });
+        });
+
+    if let Some(id) = run {
+        app.palette.close();
+        actions::execute(id, ctx, app);
+    }
+}
+
+/// Bulk rename window operating on the current results.
+pub fn rename_window(ctx: &egui::Context, app: &mut GlintApp) {
+    let mut show = app.show_rename;
+    egui::Window::new("Rename Results")
+        .open(&mut show)
+        .resizable(true)
+        .default_width(600.0)
+        .show(ctx, |ui| {
+            egui::Grid::new("rename_inputs")
+                .num_columns(2)
+                .spacing([8.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label("New name:");
+                    if ui
+                        .add(
+                            egui::TextEdit::singleline(&mut app.rename.template)
+                                .hint_text("photo_{n:3}.{ext}")
+                                .desired_width(f32::INFINITY),
+                        )
+                        .changed()
+                    {
+                        app.rename.dirty = true;
+                    }
+                    ui.end_row();
+
+                    ui.label("Match regex:");
+                    if ui
+                        .add(
+                            egui::TextEdit::singleline(&mut app.rename.pattern)
+                                .hint_text("optional, provides {1}, {2}, ...")
+                                .desired_width(f32::INFINITY),
+                        )
+                        .changed()
+                    {
+                        app.rename.dirty = true;
+                    }
+                    ui.end_row();
+
+                    ui.label("Counter starts at:");
+                    if ui
+                        .add(egui::DragValue::new(&mut app.rename.start).range(0..=u64::MAX))
+                        .changed()
+                    {
+                        app.rename.dirty = true;
+                    }
+                    ui.end_row();
+                });
+
+            ui.label(
+                RichText::new("Tokens: {name} {ext} {file} {n} {n:3} {1}..{9}")
+                    .small()
+                    .weak(),
+            );
+
+            if app.rename.dirty {
+                app.refresh_rename_preview();
+            }
+
+            ui.separator();
+
+            if let Some(error) = &app.rename.error {
+                ui.colored_label(Color32::RED, error);
+            }
+
+            let mut ready = 0;
+            if let Some(plan) = &app.rename.plan {
+                ready = plan.ready_count();
+                ui.label(format!(
+                    "{} to rename, {} conflicts, {} total",
+                    format_number(ready),
+                    format_number(plan.conflict_count()),
+                    format_number(plan.entries.len())
+                ));
+
+                egui::ScrollArea::vertical()
+                    .max_height(320.0)
+                    .auto_shrink([false, true])
+                    .show_rows(ui, 20.0, plan.entries.len(), |ui, row_range| {
+                        for entry in &plan.entries[row_range] {
+                            ui.horizontal(|ui| {
+                                let (color, note) = match &entry.status {
+                                    RenameStatus::Ready => (Color32::from_gray(200), String::new()),
+                                    RenameStatus::Unchanged => {
+                                        (Color32::GRAY, "unchanged".to_string())
+                                    }
+                                    RenameStatus::Skipped => (Color32::GRAY, "no match".to_string()),
+                                    RenameStatus::Conflict(reason) => {
+                                        (Color32::from_rgb(230, 80, 80), reason.clone())
+                                    }
+                                };
+                                ui.label(RichText::new(&entry.record.name).color(color));
+                                ui.label(RichText::new("→").color(Color32::GRAY));
+                                ui.label(RichText::new(&entry.new_name).color(color));
+                                if !note.is_empty() {
+                                    ui.label(RichText::new(format!("({})", note)).small().color(color));
+                                }
+                            });
+                        }
+                    });
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        ready > 0,
+                        egui::Button::new(format!("Rename {} files", format_number(ready))),
+                    )
+                    .clicked()
+                {
+                    app.execute_rename();
+                }
+                if ui.button("Undo Last Rename").clicked() {
+                    app.undo_rename();
+                }
+                if ui.button("Close").clicked() {
+                    app.show_rename = false;
+                }
+            });
+        });
+    app.show_rename &= show;
+}
+
+/// Statistics window: file counts and sizes by extension, folder, volume and age.
+/// The exclusions manager: rules with the records each leaves out, a
+/// path tester, and removal of excluded records without a re-index.
+pub fn exclusions_window(ctx: &egui::Context, app: &mut GlintApp) {
+    let rules = app.config.exclude.rules();
+    app.exclusions.refresh(&app.index, &rules, ctx);
+    let reduced_motion = app.reduced_motion();
+
+    let mut show = app.exclusions.open;
+    let mut add = None;
+    let mut remove = None;
+    let mut remove_records: Option<Option<ExcludeRule>> = None;
+    let mut sort = None;
+    egui::Window::new("Exclusions")
+        .open(&mut show)
+        .resizable(true)
+        .default_width(600.0)
+        .show(ctx, |ui| {
+            // A typed rule: folder path, name pattern or preset name
+            ui.horizontal(|ui| {
+                ui.label("Add:");
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut app.exclusions.new_rule)
+                        .hint_text(r"D:\Build, *.tmp or node_modules")
+                        .desired_width(320.0),
+                );
+                let entered =
+                    response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.button("Add").clicked() || entered {
+                    add = ExcludeRule::parse(&app.exclusions.new_rule);
+                }
+                if ui.button("📁 Folder...").clicked() {
+                    if let Some(folder) = rfd::FileDialog::new()
+                        .set_title("Select folder to exclude from indexing")
+                        .pick_folder()
+                    {
+                        add = Some(ExcludeRule::Path(folder.to_string_lossy().into_owned()));
+                    }
+                }
+            });
+            if let Some(rule) = ExcludeRule::parse(&app.exclusions.new_rule) {
+                ui.label(
+                    RichText::new(format!("Adds the {}", rule))
+                        .small()
+                        .color(Color32::GRAY),
+                );
+            }
+
+            // The rule a sample path falls under, as it is typed
+            ui.horizontal(|ui| {
+                ui.label("Test:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut app.exclusions.sample)
+                        .hint_text(r"C:\Users\me\AppData\Local\Temp\setup.log")
+                        .desired_width(320.0),
+                );
+            });
+            let sample = app.exclusions.sample.trim();
+            let matched = (!sample.is_empty())
+                .then(|| app.config.exclude.matching_rule(sample))
+                .flatten();
+            if !sample.is_empty() {
+                match &matched {
+                    Some(rule) => ui.label(
+                        RichText::new(format!("Excluded by the {}", rule))
+                            .color(Color32::from_rgb(230, 160, 60)),
+                    ),
+                    None => ui
+                        .label(RichText::new("Not excluded").color(Color32::from_rgb(80, 180, 80))),
+                };
+            }
+            ui.separator();
+
+            // Filter and sortable column headers
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut app.exclusions.filter)
+                        .hint_text("Filter rules")
+                        .desired_width(160.0),
+                );
+                for column in RuleSort::ALL {
+                    let sorted = app.exclusions.sort == column;
+                    let arrow = match (sorted, app.exclusions.descending) {
+                        (false, _) => "",
+                        (true, false) => " ⏶",
+                        (true, true) => " ⏷",
+                    };
+                    if ui
+                        .selectable_label(sorted, format!("{}{}", column.label(), arrow))
+                        .clicked()
+                    {
+                        sort = Some(column);
+                    }
+                }
+                if app.exclusions.is_counting() && !reduced_motion {
+                    ui.spinner();
+                }
+            });
+
+            let view = app.exclusions.view(&rules);
+            egui::ScrollArea::vertical()
+                .max_height(320.0)
+                .auto_shrink([false, true])
+                .show(ui, |ui| {
+                    egui::Grid::new("exclusion_rules")
+                        .num_columns(4)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for rule in &view {
+                                let text = match rule {
+                                    ExcludeRule::Preset(name) => preset(name)
+                                        .map_or(name.clone(), |p| p.description.to_string()),
+                                    _ => rule.text().to_string(),
+                                };
+                                let text = if matched.as_ref() == Some(rule) {
+                                    RichText::new(text).strong()
+                                } else {
+                                    RichText::new(text)
+                                };
+                                ui.label(text);
+                                ui.label(RichText::new(rule.kind()).color(Color32::GRAY));
+                                let count = app.exclusions.count(rule);
+                                ui.label(count.map_or("…".to_string(), format_number));
+                                ui.horizontal(|ui| {
+                                    if count.unwrap_or(0) > 0
+                                        && ui
+                                            .small_button("Remove files")
+                                            .on_hover_text(
+                                                "Take the files it leaves out of the index now",
+                                            )
+                                            .clicked()
+                                    {
+                                        remove_records = Some(Some(rule.clone()));
+                                    }
+                                    if ui
+                                        .small_button("✕")
+                                        .on_hover_text("Delete the rule")
+                                        .clicked()
+                                    {
+                                        remove = Some(rule.clone());
+                                    }
+                                });
+                                ui.end_row();
+                            }
+                        });
+                });
+
+            // Presets that are off, to turn on with a click
+            let enabled = |p: &ExcludePreset| {
+                rules.iter().any(
+                    |r| matches!(r, ExcludeRule::Preset(name) if name.eq_ignore_ascii_case(p.name)),
+                )
+            };
+            let off: Vec<_> = PRESETS.iter().filter(|p| !enabled(p)).collect();
+            if !off.is_empty() {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("Presets:");
+                    for p in off {
+                        let button = ui.button(format!("➕ {}", p.name));
+                        if button.on_hover_text(p.description).clicked() {
+                            add = Some(ExcludeRule::Preset(p.name.to_string()));
+                        }
+                    }
+                });
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Remove All Excluded Files")
+                    .on_hover_text("Take everything the rules leave out of the index now")
+                    .clicked()
+                {
+                    remove_records = Some(None);
+                }
+                ui.label(
+                    RichText::new("A deleted rule's files come back at the next re-index.")
+                        .small()
+                        .color(Color32::GRAY),
+                );
+            });
+        });
+
+    if let Some(rule) = add {
+        app.add_exclusion(rule);
+        app.exclusions.new_rule.clear();
+    }
+    if let Some(rule) = remove {
+        app.remove_exclusion(&rule);
+    }
+    if let Some(rule) = remove_records {
+        app.remove_excluded_records(rule.as_ref());
+    }
+    if let Some(column) = sort {
+        app.exclusions.sort_by(column);
+    }
+    app.exclusions.open &= show;
+}
+
+pub fn stats_window(ctx: &egui::Context, app: &mut GlintApp) {
+    const MAX_ROWS: usize = 100;
+
+    if app.stats_stale() && !app.stats.is_computing() && !app.index.is_empty() {
+        app.start_stats_job(true);
+    }
+
+    let mut show = app.show_stats;
+    egui::Window::new("Statistics")
+        .open(&mut show)
+        .resizable(true)
+        .default_width(560.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                for group_by in GroupBy::ALL {
+                    ui.selectable_value(&mut app.stats.group_by, group_by, group_by.label());
+                }
+                if app.stats.is_computing() {
+                    ui.spinner();
+                }
+            });
+            ui.separator();
+
+            let Some(stats) = app.stats.stats.clone() else {
+                ui.label(if app.index.is_empty() {
+                    "The index is empty."
+                } else {
+                    "Computing statistics..."
+                });
+                return;
+            };
+
+            ui.label(format!(
+                "{} files, {} total",
+                format_number(stats.total_files as usize),
+                format_size(stats.total_size)
+            ));
+
+            let rows = stats.rows(app.stats.group_by);
+            let max_size = rows.iter().map(|r| r.total_size).max().unwrap_or(0).max(1);
+
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .auto_shrink([false, true])
+                .show(ui, |ui| {
+                    egui::Grid::new("stats_table")
+                        .num_columns(4)
+                        .striped(true)
+                        .spacing([12.0, 4.0])
+                        .show(ui, |ui| {
+                            ui.strong(app.stats.group_by.label());
+                            ui.strong("Files");
+                            ui.strong("Size");
+                            ui.label("");
+                            ui.end_row();
+
+                            for row in rows.iter().take(MAX_ROWS) {
+                                ui.label(&row.key).on_hover_text(&row.key);
+                                ui.label(format_number(row.count as usize));
+                                ui.label(format_size(row.total_size));
+                                let share = if stats.total_size > 0 {
+                                    row.total_size as f32 / stats.total_size as f32
+                                } else {
+                                    0.0
+                                };
+                                ui.add(
+                                    egui::ProgressBar::new(row.total_size as f32 / max_size as f32)
+                                        .desired_width(160.0)
+                                        .text(format!("{:.1}%", share * 100.0)),
+                                );
+                                ui.end_row();
+                            }
+                        });
+                    if rows.len() > MAX_ROWS {
+                        ui.label(
+                            RichText::new(format!("… {} more", rows.len() - MAX_ROWS))
+                                .small()
+                                .weak(),
+                        );
+                    }
+                });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new(format!(
+                        "Computed {}",
+                        stats.computed_at.format("%Y-%m-%d %H:%M UTC")
+                    ))
+                    .small()
+                    .weak(),
+                );
+                if ui
+                    .add_enabled(!app.stats.is_computing(), egui::Button::new("Refresh"))
+                    .clicked()
+                {
+                    app.start_stats_job(false);
+                }
+            });
+        });
+    app.show_stats &= show;
+}
+
+/// What was done to results this session, newest first, with undo for
+/// recycled files and the last rename.
+pub fn history_window(ctx: &egui::Context, app: &mut GlintApp) {
+    let mut show = app.show_history;
+    let mut undo = None;
+    egui::Window::new("History")
+        .open(&mut show)
+        .resizable(true)
+        .default_width(560.0)
+        .show(ctx, |ui| {
+            let history = &app.search.history;
+            if history.is_empty() {
+                ui.label(RichText::new("Nothing done yet this session").color(Color32::GRAY));
+                return;
+            }
+            egui::ScrollArea::vertical()
+                .max_height(360.0)
+                .show(ui, |ui| {
+                    for (i, entry) in history.entries().iter().enumerate().rev() {
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                RichText::new(entry.at.format("%H:%M:%S").to_string())
+                                    .small()
+                                    .weak(),
+                            );
+                            let mut text = RichText::new(entry.action.describe());
+                            if entry.undone {
+                                text = text.strikethrough();
+                            }
+                            ui.label(text);
+                            if history.can_undo(i) && ui.small_button("Undo").clicked() {
+                                undo = Some(i);
+                            }
+                        });
+                    }
+                });
+            ui.separator();
+            if ui.button("Clear").clicked() {
+                app.search.history.clear();
+            }
+        });
+    app.show_history &= show;
+    if let Some(i) = undo {
+        app.undo_history(i);
+    }
+}
+
 /// Index builder window for first run or rebuilding index.
 pub fn index_builder_window(ctx: &egui::Context, app: &mut GlintApp) {
     let mut show = app.show_index_builder;
@@ -608,7 +2884,7 @@ pub fn index_builder_window(ctx: &egui::Context, app: &mut GlintApp) {
                             ui.checkbox(&mut volume.selected, "");
                             ui.label(format!(
                                 "{} ({}) - {}",
-                                volume.letter,
+                                volume.mount_point.trim_end_matches('\\'),
                                 volume.label,
                                 format_size(volume.size)
                             ));
@@ -616,6 +2892,41 @@ pub fn index_builder_window(ctx: &egui::Context, app: &mut GlintApp) {
                     }
                 });
 
+            ui.add_space(10.0);
+            ui.label("Or index just some folders:");
+            ui.add_space(5.0);
+
+            let mut to_remove: Option<usize> = None;
+            for (i, folder) in app.config.volumes.folders.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("📁 {}", folder));
+                    if ui.small_button("✕").on_hover_text("Remove").clicked() {
+                        to_remove = Some(i);
+                    }
+                });
+            }
+            if let Some(idx) = to_remove {
+                app.config.volumes.folders.remove(idx);
+                if let Err(e) = app.config.save() {
+                    app.toasts.error(format!("Failed to save config: {}", e));
+                }
+            }
+
+            if ui.button("➕ Add Folder...").clicked() {
+                if let Some(folder) = rfd::FileDialog::new()
+                    .set_title("Select folder to index")
+                    .pick_folder()
+                {
+                    let path_str = folder.to_string_lossy().to_string();
+                    if !app.config.volumes.folders.contains(&path_str) {
+                        app.config.volumes.folders.push(path_str);
+                        if let Err(e) = app.config.save() {
+                            app.toasts.error(format!("Failed to save config: {}", e));
+                        }
+                    }
+                }
+            }
+
             ui.add_space(10.0);
             ui.separator();
             ui.add_space(10.0);
@@ -639,14 +2950,9 @@ pub fn index_builder_window(ctx: &egui::Context, app: &mut GlintApp) {
             ui.horizontal(|ui| {
                 if ui.button("Build Index").clicked() {
                     // Collect selected volumes
-                    let selected: Vec<char> = app
-                        .available_volumes
-                        .iter()
-                        .filter(|v| v.selected)
-                        .map(|v| v.letter)
-                        .collect();
+                    let selected = app.selected_mount_points();
 
-                    if !selected.is_empty() {
+                    if !selected.is_empty() || !app.config.volumes.folders.is_empty() {
                         // Trigger async index rebuild (non-blocking)
                         app.start_index_build();
 
@@ -669,7 +2975,8 @@ pub fn index_builder_window(ctx: &egui::Context, app: &mut GlintApp) {
 
                         app.show_index_builder = false;
                     } else {
-                        app.status_message = "Please select at least one volume".to_string();
+                        app.status_message =
+                            "Please select at least one volume or folder".to_string();
                     }
                 }
 
@@ -680,3 +2987,69 @@ pub fn index_builder_window(ctx: &egui::Context, app: &mut GlintApp) {
         });
     app.show_index_builder = show;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glint_core::{FileId, FileRecord, SearchResult, VolumeInfo};
+
+    fn result(path: &str, is_dir: bool) -> SearchResult {
+        let name = path.rsplit('\\').next().unwrap().to_string();
+        let record = FileRecord::new(
+            FileId::new(7),
+            None,
+            VolumeId::new("C"),
+            name,
+            path.to_string(),
+            is_dir,
+        );
+        SearchResult::new(record, 0)
+    }
+
+    #[test]
+    fn test_tooltip_rows() {
+        let volume = VolumeInfo::new(VolumeId::new("C"), "C:", "NTFS").with_label("System");
+        let modified = chrono::Utc::now();
+        let mut file = result("C:\\docs\\report.pdf", false);
+        file.record.modified = Some(modified);
+
+        let rows = tooltip_rows(&file, Some(2048), Some(&volume), &["Hidden", "Archive"]);
+        assert_eq!(
+            rows,
+            [
+                (
+                    "Size",
+                    format!("{} ({} bytes)", format_size(2048), format_number(2048))
+                ),
+                ("Modified", format_date_time(modified)),
+                ("Created", "-".to_string()),
+                ("Attributes", "Hidden, Archive".to_string()),
+                ("Volume", format!("{} [System]", volume.display_name())),
+            ]
+        );
+
+        // Folders whose size is still being summed, deleted files, and
+        // results of attached indexes
+        let rows = tooltip_rows(&result("C:\\docs", true), None, None, &[]);
+        assert_eq!(rows[0], ("Folder", "Being summed...".to_string()));
+        assert_eq!(rows.len(), 3);
+
+        let mut deleted = result("C:\\old.txt", false);
+        deleted.deleted_at = Some(modified);
+        deleted.source = Some("laptop".into());
+        let rows = tooltip_rows(&deleted, None, Some(&volume), &[]);
+        assert_eq!(rows[0], ("Size", "-".to_string()));
+        assert_eq!(rows[3], ("Deleted", format_date_time(modified)));
+        assert_eq!(rows[4], ("Index", "laptop".to_string()));
+    }
+
+    #[test]
+    fn test_attribute_names() {
+        assert!(attribute_names(0).is_empty());
+        assert_eq!(attribute_names(0x1 | 0x20), ["Read-only", "Archive"]);
+        assert_eq!(
+            attribute_names(0x4000 | 0x2 | 0x10),
+            ["Hidden", "Encrypted"]
+        );
+    }
+}
@@ -0,0 +1,684 @@
+//! The work of the service: keep the index current until told to stop.
+
+use crossbeam_channel::Receiver;
+use glint_backend_ntfs::NtfsBackend;
+use glint_core::backend::{
+    rebuild_due, rebuild_index, ChangeHandler, ChangeHandlerMessage, ChannelChangeHandler,
+    VolumeRefresher, VolumeResumer, WatchHandle, VOLUME_REFRESH_INTERVAL,
+};
+use glint_core::churn::ChurnReport;
+use glint_core::exclude::Exclusions;
+use glint_core::ipc::IpcServer;
+use glint_core::opensearch::OpenSearchServer;
+use glint_core::report::ReportScheduler;
+use glint_core::tasks::{self, CancellationToken};
+use glint_core::{
+    Config, FileSystemBackend, GlintError, Index, IndexStore, SaveProgress, VolumeAvailability,
+    VolumeId,
+};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio::task::JoinSet;
+use tracing::{error, info, warn};
+
+/// How long to wait for a change before checking the stop flag.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often a changed index is saved.
+const SAVE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// How often to look for folders that change constantly.
+const CHURN_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often to check whether the index is due for a rebuild, and so how
+/// soon a failed rebuild is tried again.
+const REBUILD_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Least time between compactions of the index.
+const COMPACT_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Most changes since the last rebuild check for the index to count as
+/// quiet enough to compact.
+const COMPACT_QUIET_CHANGES: u64 = 100;
+
+/// Deleted-file records worth compacting the index for.
+const COMPACT_MIN_TOMBSTONES: usize = 10_000;
+
+/// Longest wait for the watchers to hand over their last changes when
+/// stopping; the index is saved after that either way.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Changes applied between progress reports while stopping.
+const DRAIN_REPORT_EVERY: usize = 10_000;
+
+/// How the service runs its work.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Runtime {
+    /// A thread for each watcher, server and timer
+    #[default]
+    Threads,
+    /// Tasks on a tokio runtime, stopped together
+    Tokio,
+}
+
+impl std::str::FromStr for Runtime {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "threads" => Ok(Runtime::Threads),
+            "tokio" => Ok(Runtime::Tokio),
+            _ => anyhow::bail!("Unknown runtime '{}' (expected threads or tokio)", s),
+        }
+    }
+}
+
+/// Watch the indexed volumes and apply their changes until `stop` is set or
+/// every watcher has stopped, then save the index.
+///
+/// `on_started` is called once the watchers are running, and `on_stopping`
+/// now and then while the last changes are applied and the index is saved.
+pub fn run(
+    runtime: Runtime,
+    config: &Config,
+    stop: &AtomicBool,
+    on_started: impl FnOnce(),
+    on_stopping: impl Fn(),
+) -> anyhow::Result<()> {
+    match runtime {
+        Runtime::Threads => run_threads(config, stop, on_started, on_stopping),
+        Runtime::Tokio => run_tokio(config, stop, on_started, on_stopping),
+    }
+}
+
+/// The loaded index with its volumes being watched.
+struct Watching {
+    store: IndexStore,
+    index: Arc<Index>,
+    backend: Arc<NtfsBackend>,
+    receiver: Receiver<ChangeHandlerMessage>,
+    handles: Vec<WatchHandle>,
+    /// Watches volumes that come back; handed to the volume refresher
+    resumer: Option<VolumeResumer>,
+}
+
+impl Watching {
+    /// Load the index and start watching its enabled volumes.
+    fn start(config: &Config) -> anyhow::Result<Self> {
+        if config.general.read_only {
+            return Err(GlintError::read_only("watch for changes").into());
+        }
+        let store = IndexStore::new(&config.index_dir()?);
+        let index = Arc::new(store.load_or_new());
+        if index.is_empty() {
+            anyhow::bail!("Index is empty; build it with the GUI or 'glint index' first");
+        }
+        index.set_cache_lower_paths(config.performance.cache_lower_paths);
+        index.set_exclusions(Exclusions::new(&config.exclude));
+
+        let backend = Arc::new(
+            NtfsBackend::new().ignoring_maintenance(config.general.ignore_maintenance_changes),
+        );
+        let capabilities = backend.capabilities();
+        index.set_capabilities(capabilities);
+        index.set_hide_offline(config.general.hide_offline_volumes);
+        // Volumes not mounted now are watched once they are back
+        match backend.list_volumes() {
+            Ok(present) => {
+                index.update_availability(&present);
+            }
+            Err(e) => warn!(error = %e, "Cannot list volumes"),
+        }
+        let (handler, receiver) = ChannelChangeHandler::new();
+        let handler: Arc<dyn ChangeHandler> = Arc::new(handler);
+
+        let mut handles = Vec::new();
+        for state in index.volume_states() {
+            let name = state.info.display_name();
+            if config.is_volume_disabled(name) {
+                index.set_volume_enabled(&state.info.id, false);
+                info!(volume = %name, "Not watching disabled volume");
+                continue;
+            }
+            if !capabilities.supports_change_journal {
+                // Scheduled rebuilds keep the index current instead
+                continue;
+            }
+            if state.availability == VolumeAvailability::Offline {
+                info!(volume = %name, "Volume is offline; watching it once it is back");
+                continue;
+            }
+
+            let mut volume_info = state.info.clone();
+            volume_info.journal_state = state.journal_state.clone();
+            match backend.watch_changes(volume_info, Arc::clone(&handler)) {
+                Ok(handle) => {
+                    info!(volume = %name, "Watching volume");
+                    handles.push(handle);
+                }
+                Err(e) => warn!(volume = %name, error = %e, "Cannot watch volume"),
+            }
+        }
+        let resumer = capabilities
+            .supports_change_journal
+            .then(|| VolumeResumer::new(handler));
+        let offline = index
+            .volume_states()
+            .iter()
+            .any(|state| state.enabled && state.availability == VolumeAvailability::Offline);
+        if !capabilities.supports_change_journal {
+            info!(
+                every = ?capabilities.rebuild_after(config.rebuild_after()),
+                "No change journal; rebuilding the index on a schedule instead"
+            );
+        } else if handles.is_empty() && !offline {
+            anyhow::bail!("No volumes could be watched");
+        }
+
+        Ok(Watching {
+            store,
+            index,
+            backend,
+            receiver,
+            handles,
+            resumer,
+        })
+    }
+
+    /// Stop the watchers, apply the changes they had already sent, and save
+    /// the index with their journal positions if it changed since
+    /// `saved_generation`.
+    fn finish(mut self, saved_generation: u64, on_stopping: impl Fn()) {
+        for handle in &mut self.handles {
+            handle.stop();
+        }
+        self.resumer = None;
+        on_stopping();
+
+        // The channel closes once every watcher thread has exited
+        let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+        let mut drained = 0usize;
+        loop {
+            match self.receiver.recv_deadline(deadline) {
+                Ok(message) => {
+                    message.apply(&self.index);
+                    drained += 1;
+                    if drained % DRAIN_REPORT_EVERY == 0 {
+                        on_stopping();
+                    }
+                }
+                Err(e) if e.is_timeout() => {
+                    warn!(
+                        pending = self.receiver.len(),
+                        "Watchers did not stop in time; saving without their last changes"
+                    );
+                    break;
+                }
+                Err(_) => break,
+            }
+        }
+        if drained > 0 {
+            info!(changes = drained, "Applied changes queued before stopping");
+        }
+
+        save_if_changed(&self.index, &self.store, saved_generation, |_| {
+            on_stopping()
+        });
+        info!("Service stopped");
+    }
+}
+
+/// Run scheduled reports, if any are configured.
+fn spawn_reports(config: &Config, watching: &Watching) -> anyhow::Result<Option<ReportScheduler>> {
+    if config.reports.jobs.is_empty() {
+        return Ok(None);
+    }
+    Ok(ReportScheduler::spawn(
+        config.reports.jobs.clone(),
+        config.reports_dir()?,
+        Arc::clone(&watching.index),
+        watching.store.manifest_path(),
+    )
+    .map_err(|e| warn!(error = %e, "Cannot schedule reports"))
+    .ok())
+}
+
+/// Answer searches from Explorer's search box, if configured.
+fn spawn_explorer(config: &Config, index: &Arc<Index>) -> Option<OpenSearchServer> {
+    let addr = config.server.explorer_listen.as_deref()?;
+    OpenSearchServer::spawn(addr, Arc::clone(index), config.server.query_limits())
+        .map_err(|e| warn!(listen = %addr, error = %e, "Cannot start Explorer search endpoint"))
+        .ok()
+}
+
+fn run_threads(
+    config: &Config,
+    stop: &AtomicBool,
+    on_started: impl FnOnce(),
+    on_stopping: impl Fn(),
+) -> anyhow::Result<()> {
+    let mut watching = Watching::start(config)?;
+    let index = Arc::clone(&watching.index);
+
+    let _ipc_server = if config.server.enabled {
+        config
+            .server
+            .server_tls()
+            .and_then(|tls| {
+                IpcServer::spawn(
+                    &config.server.listen,
+                    Arc::clone(&index),
+                    config.server.query_limits(),
+                    config.server.token.clone(),
+                    tls,
+                )
+            })
+            .map_err(
+                |e| warn!(listen = %config.server.listen, error = %e, "Cannot start query server"),
+            )
+            .ok()
+    } else {
+        None
+    };
+
+    let _explorer_server = spawn_explorer(config, &index);
+    let _report_scheduler = spawn_reports(config, &watching)?;
+
+    let backend: Arc<dyn FileSystemBackend> = watching.backend.clone();
+    let volume_refresher = match watching.resumer.take() {
+        Some(resumer) => VolumeRefresher::spawn_resuming(
+            backend,
+            Arc::clone(&index),
+            VOLUME_REFRESH_INTERVAL,
+            resumer,
+        ),
+        None => VolumeRefresher::spawn(backend, Arc::clone(&index), VOLUME_REFRESH_INTERVAL),
+    }
+    .map_err(|e| warn!(error = %e, "Cannot refresh volume information"))
+    .ok();
+
+    let stop_rebuilds = Arc::new(AtomicBool::new(false));
+    spawn_rebuilds(config, &watching, Arc::clone(&stop_rebuilds));
+
+    on_started();
+    info!(volumes = watching.handles.len(), "Service started");
+
+    let store = &watching.store;
+    let mut saved_generation = index.generation();
+    let mut last_save = Instant::now();
+    let mut last_churn_check = Instant::now();
+    let mut flagged = HashSet::new();
+
+    while !stop.load(Ordering::Relaxed) {
+        match watching.receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(message) => message.apply(&index),
+            Err(e) if e.is_timeout() => {}
+            Err(_) => {
+                warn!("All watchers stopped");
+                break;
+            }
+        }
+
+        if last_churn_check.elapsed() >= CHURN_CHECK_INTERVAL {
+            last_churn_check = Instant::now();
+            check_churn(&index, store, config.exclude.churn_threshold, &mut flagged);
+        }
+        if last_save.elapsed() >= SAVE_INTERVAL {
+            last_save = Instant::now();
+            saved_generation = save_if_changed(&index, store, saved_generation, |_| {});
+        }
+    }
+
+    // A rebuild in progress is abandoned at the next volume
+    stop_rebuilds.store(true, Ordering::Relaxed);
+    // The refresher holds on to the change channel for resumed volumes
+    drop(volume_refresher);
+    watching.finish(saved_generation, on_stopping);
+    Ok(())
+}
+
+/// Like [`run_threads`], with the change loop, query server, timers and
+/// volume refresh as tasks on one tokio runtime. When `stop` is set they
+/// are all cancelled together and awaited before the final save.
+fn run_tokio(
+    config: &Config,
+    stop: &AtomicBool,
+    on_started: impl FnOnce(),
+    on_stopping: impl Fn(),
+) -> anyhow::Result<()> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .thread_name("glint-svc")
+        .build()?;
+    let mut watching = Watching::start(config)?;
+    let _report_scheduler = spawn_reports(config, &watching)?;
+    let index = Arc::clone(&watching.index);
+    let _explorer_server = spawn_explorer(config, &index);
+    let store = Arc::new(IndexStore::new(&config.index_dir()?));
+    let saved_generation = Arc::new(AtomicU64::new(index.generation()));
+
+    runtime.block_on(async {
+        let cancel = CancellationToken::new();
+        let mut tasks = JoinSet::new();
+
+        if config.server.enabled {
+            let listener = match config.server.server_tls() {
+                Ok(tls) => TcpListener::bind(&config.server.listen)
+                    .await
+                    .map(|listener| (listener, tls))
+                    .map_err(anyhow::Error::from),
+                Err(e) => Err(e.into()),
+            };
+            match listener {
+                Ok((listener, tls)) => {
+                    info!(listen = %config.server.listen, "IPC server listening");
+                    tasks.spawn(tasks::serve_ipc(
+                        listener,
+                        Arc::clone(&index),
+                        config.server.query_limits(),
+                        config.server.token.clone(),
+                        tls,
+                        cancel.clone(),
+                    ));
+                }
+                Err(e) => {
+                    warn!(listen = %config.server.listen, error = %e, "Cannot start query server")
+                }
+            }
+        }
+
+        tasks.spawn(tasks::refresh_volumes(
+            watching.backend.clone(),
+            Arc::clone(&index),
+            VOLUME_REFRESH_INTERVAL,
+            watching.resumer.take(),
+            cancel.clone(),
+        ));
+
+        let (churn_index, churn_store) = (Arc::clone(&index), Arc::clone(&store));
+        let threshold = config.exclude.churn_threshold;
+        let mut flagged = HashSet::new();
+        tasks.spawn(tasks::every(
+            CHURN_CHECK_INTERVAL,
+            cancel.clone(),
+            move || check_churn(&churn_index, &churn_store, threshold, &mut flagged),
+        ));
+
+        let (rebuild_target, rebuild_backend) = (Arc::clone(&index), watching.backend.clone());
+        let rebuild_config = config.clone();
+        let rebuild_cancel = cancel.clone();
+        let mut compaction = Compaction::default();
+        tasks.spawn(tasks::every(
+            REBUILD_CHECK_INTERVAL,
+            cancel.clone(),
+            move || {
+                rebuild_if_due(&rebuild_target, &rebuild_backend, &rebuild_config, || {
+                    rebuild_cancel.is_cancelled()
+                });
+                compact_if_due(
+                    &rebuild_target,
+                    &rebuild_backend,
+                    &rebuild_config,
+                    &mut compaction,
+                );
+            },
+        ));
+
+        let (save_index, save_store) = (Arc::clone(&index), Arc::clone(&store));
+        let saved = Arc::clone(&saved_generation);
+        tasks.spawn(tasks::every(SAVE_INTERVAL, cancel.clone(), move || {
+            let generation = save_if_changed(
+                &save_index,
+                &save_store,
+                saved.load(Ordering::Relaxed),
+                |_| {},
+            );
+            saved.store(generation, Ordering::Relaxed);
+        }));
+
+        let mut changes = tokio::spawn(tasks::apply_changes(
+            Arc::clone(&index),
+            watching.receiver.clone(),
+            cancel.clone(),
+        ));
+
+        on_started();
+        info!(volumes = watching.handles.len(), "Service started");
+
+        let watchers_stopped = tokio::select! {
+            _ = &mut changes => true,
+            _ = stop_requested(stop) => false,
+        };
+        cancel.cancel();
+        if !watchers_stopped {
+            let _ = changes.await;
+        }
+        while tasks.join_next().await.is_some() {}
+    });
+
+    watching.finish(saved_generation.load(Ordering::Relaxed), on_stopping);
+    Ok(())
+}
+
+/// Check every [`REBUILD_CHECK_INTERVAL`] on a thread of its own whether
+/// the index is due for a rebuild, until `stop` is set.
+fn spawn_rebuilds(config: &Config, watching: &Watching, stop: Arc<AtomicBool>) {
+    let index = Arc::clone(&watching.index);
+    let backend = Arc::clone(&watching.backend);
+    let config = config.clone();
+    let spawned = std::thread::Builder::new()
+        .name("glint-rebuild".to_string())
+        .spawn(move || {
+            let mut last_check = Instant::now();
+            let mut compaction = Compaction::default();
+            while !stop.load(Ordering::Relaxed) {
+                std::thread::sleep(POLL_INTERVAL);
+                if last_check.elapsed() >= REBUILD_CHECK_INTERVAL {
+                    last_check = Instant::now();
+                    rebuild_if_due(&index, &backend, &config, || stop.load(Ordering::Relaxed));
+                    compact_if_due(&index, &backend, &config, &mut compaction);
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        warn!(error = %e, "Cannot schedule index rebuilds");
+    }
+}
+
+/// Rebuild the index if a volume needs a rescan or the configured time
+/// since the last full scan has passed. Searches and changes go to the
+/// old records until the new ones are swapped in; the next periodic save
+/// writes them out.
+fn rebuild_if_due(
+    index: &Index,
+    backend: &NtfsBackend,
+    config: &Config,
+    stopped: impl Fn() -> bool,
+) {
+    let rebuild_after = backend.capabilities().rebuild_after(config.rebuild_after());
+    if !rebuild_due(index, rebuild_after) {
+        return;
+    }
+    info!("Rebuilding the index in the background");
+    let start = Instant::now();
+    match rebuild_index(backend, index, Exclusions::new(&config.exclude), stopped) {
+        Ok(()) => info!(records = index.len(), elapsed = ?start.elapsed(), "Index rebuilt"),
+        Err(e) => warn!(error = %e, "Cannot rebuild the index"),
+    }
+}
+
+/// What [`compact_if_due`] remembers between checks.
+#[derive(Debug, Default)]
+struct Compaction {
+    /// Index generation at the previous check
+    checked_generation: Option<u64>,
+    /// When the index was last compacted
+    last_run: Option<Instant>,
+}
+
+/// Compact the index at a quiet time: when few changes came in since the
+/// previous check, at most once every [`COMPACT_INTERVAL`], and only if
+/// there are enough deleted-file records or volumes disconnected for
+/// longer than configured to drop. The next periodic save writes the
+/// smaller index.
+fn compact_if_due(
+    index: &Index,
+    backend: &NtfsBackend,
+    config: &Config,
+    compaction: &mut Compaction,
+) {
+    let generation = index.generation();
+    let quiet = compaction
+        .checked_generation
+        .replace(generation)
+        .is_some_and(|checked| generation - checked < COMPACT_QUIET_CHANGES);
+    let recent = compaction
+        .last_run
+        .is_some_and(|run| run.elapsed() < COMPACT_INTERVAL);
+    if !quiet || recent {
+        return;
+    }
+
+    let absent = match config.drop_absent_after() {
+        Some(absent_for) => match backend.list_volumes() {
+            Ok(present) => index.absent_volumes(&present, absent_for),
+            Err(e) => {
+                warn!(error = %e, "Cannot list volumes to find disconnected ones");
+                Vec::new()
+            }
+        },
+        None => Vec::new(),
+    };
+    if absent.is_empty() && index.tombstone_count() < COMPACT_MIN_TOMBSTONES {
+        return;
+    }
+    compaction.last_run = Some(Instant::now());
+    let volumes: Vec<VolumeId> = absent.into_iter().map(|state| state.info.id).collect();
+    index.compact(&volumes);
+}
+
+/// Resolve once `stop` is set.
+async fn stop_requested(stop: &AtomicBool) {
+    while !stop.load(Ordering::Relaxed) {
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Save the index if it changed since `saved_generation`; returns the
+/// generation now on disk.
+fn save_if_changed(
+    index: &Index,
+    store: &IndexStore,
+    saved_generation: u64,
+    progress: impl FnMut(SaveProgress),
+) -> u64 {
+    let generation = index.generation();
+    if generation == saved_generation {
+        return saved_generation;
+    }
+    match store.save_with_progress(index, progress) {
+        Ok(()) => generation,
+        Err(e) => {
+            error!(error = %e, "Cannot save index");
+            saved_generation
+        }
+    }
+}
+
+/// Save the folders changing faster than `threshold` for `glint doctor` and
+/// the GUI, and log the ones not seen before.
+fn check_churn(index: &Index, store: &IndexStore, threshold: u32, flagged: &mut HashSet<String>) {
+    let folders = index.churning_folders(threshold);
+    if folders.is_empty() {
+        return;
+    }
+    for folder in folders.iter().filter(|f| flagged.insert(f.path.clone())) {
+        warn!(folder = %folder.path, per_minute = folder.per_minute, "Folder is changing constantly");
+    }
+    if let Err(e) = store.save_churn(&ChurnReport::new(threshold, folders)) {
+        warn!(error = %e, "Cannot save churn report");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glint_core::backend::{ChangeEvent, JournalState};
+    use glint_core::mock::{MockBackend, MockVolume};
+    use glint_core::{FileId, SearchQuery};
+    use std::cell::Cell;
+
+    #[test]
+    fn test_finish_applies_queued_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = MockBackend::new().with_volume(
+            MockVolume::new("C")
+                .with_dir(5, None, "src")
+                .then_delay(Duration::from_secs(60)),
+        );
+        let volume = backend.list_volumes().unwrap().remove(0);
+        let index = Arc::new(Index::new());
+        index.add_volume_records(&volume, backend.full_scan(&volume, None).unwrap());
+        let store = IndexStore::new(dir.path());
+        store.save(&index).unwrap();
+        let saved_generation = index.generation();
+
+        // Sent by the watchers before they were told to stop
+        let (handler, receiver) = ChannelChangeHandler::new();
+        handler.on_change(ChangeEvent::created(
+            volume.id.clone(),
+            FileId::new(11),
+            Some(FileId::new(5)),
+            "lib.rs".to_string(),
+            false,
+            1,
+        ));
+        handler.on_position(volume.id.clone(), JournalState::new(1, 1));
+        // A watcher still waiting mid-script
+        let handle = backend.watch_changes(volume, Arc::new(handler)).unwrap();
+
+        let watching = Watching {
+            store,
+            index: Arc::clone(&index),
+            backend: Arc::new(NtfsBackend::new()),
+            receiver,
+            handles: vec![handle],
+            resumer: None,
+        };
+        let reports = Cell::new(0);
+        let started = Instant::now();
+        watching.finish(saved_generation, || reports.set(reports.get() + 1));
+        assert!(started.elapsed() < SHUTDOWN_TIMEOUT);
+        assert!(reports.get() > 0);
+
+        let saved = IndexStore::new(dir.path()).load().unwrap();
+        assert_eq!(saved.search(&SearchQuery::substring("lib.rs")).len(), 1);
+        assert_eq!(
+            saved.volume_states()[0].journal_state,
+            Some(JournalState::new(1, 1))
+        );
+    }
+
+    #[test]
+    fn test_save_if_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = IndexStore::new(dir.path());
+        let index = Index::new();
+        let generation = index.generation();
+
+        assert_eq!(
+            save_if_changed(&index, &store, generation, |_| {}),
+            generation
+        );
+        assert!(store.load().is_err());
+
+        assert_eq!(
+            save_if_changed(&index, &store, generation + 1, |_| {}),
+            generation
+        );
+        assert!(store.load().is_ok());
+    }
+}
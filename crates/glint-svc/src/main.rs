@@ -0,0 +1,177 @@
+//! Glint background service.
+//!
+//! A small executable that keeps the index current while nobody is looking:
+//! it loads the index, watches the change journals of the indexed volumes,
+//! serves queries over IPC, runs scheduled reports and saves the index now
+//! and then. It leaves out the CLI's argument parser and TUI and the GUI's
+//! windowing, so the resident service stays light.
+//!
+//! On Windows it runs under the Service Control Manager; the GUI installs it
+//! as `GlintIndexService`. With `--console` (and always on other platforms)
+//! it runs in the foreground until Ctrl+C, which stops it the way the SCM
+//! does: queued changes are applied and the index and journal positions
+//! saved. A second Ctrl+C ends the process at once.
+//!
+//! ```text
+//! glint-svc [--console] [--config <file>] [--data-dir <dir>] [--runtime threads|tokio]
+//!           [--set <key>=<value>]...
+//! ```
+//!
+//! `--runtime tokio` runs the watchers, query server and timers as tasks on
+//! a tokio runtime instead of a thread each. `--set` overrides a config key,
+//! as `GLINT_*` environment variables do.
+//!
+//! The service runs as LocalSystem, whose profile holds no Glint config, so
+//! the GUI passes the installing user's config file and index folder.
+
+mod engine;
+#[cfg(windows)]
+mod scm;
+
+use engine::Runtime;
+use glint_core::Config;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::info;
+
+/// Command-line options.
+#[derive(Debug, Default)]
+struct Options {
+    console: bool,
+    config: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+    runtime: Runtime,
+    set: Vec<String>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> anyhow::Result<Self> {
+        let mut options = Options::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--console" => options.console = true,
+                "--config" => options.config = Some(value(&arg, args.next())?),
+                "--data-dir" => options.data_dir = Some(value(&arg, args.next())?),
+                "--runtime" => {
+                    options.runtime = value(&arg, args.next())?.to_string_lossy().parse()?
+                }
+                "--set" => options
+                    .set
+                    .push(value(&arg, args.next())?.to_string_lossy().into_owned()),
+                _ => anyhow::bail!("Unknown option: {}", arg),
+            }
+        }
+        Ok(options)
+    }
+
+    /// Load the config, with the index folder overridden if one was given.
+    fn load_config(&self) -> anyhow::Result<Config> {
+        let path = match &self.config {
+            Some(path) => path.clone(),
+            None => Config::default_config_path()?,
+        };
+        let mut config = Config::load_layered(&path, &self.set)?;
+        if let Some(dir) = &self.data_dir {
+            config.general.index_path = Some(dir.clone());
+        }
+        Ok(config)
+    }
+}
+
+fn value(option: &str, value: Option<String>) -> anyhow::Result<PathBuf> {
+    match value {
+        Some(value) => Ok(PathBuf::from(value)),
+        None => anyhow::bail!("{} needs a value", option),
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let options = Options::parse(std::env::args().skip(1))?;
+    let config = options.load_config()?;
+    init_logging(&config);
+    glint_core::clock::set_zone(config.general.timezone);
+
+    #[cfg(windows)]
+    if !options.console {
+        return scm::run(config, options.runtime);
+    }
+
+    static STOP: AtomicBool = AtomicBool::new(false);
+    stop_on_ctrl_c(&STOP)?;
+    engine::run(options.runtime, &config, &STOP, || {}, || {})
+}
+
+/// Set `stop` on the first Ctrl+C and end the process on the second.
+fn stop_on_ctrl_c(stop: &'static AtomicBool) -> anyhow::Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    std::thread::Builder::new()
+        .name("ctrl-c".to_string())
+        .spawn(move || {
+            runtime.block_on(async {
+                if tokio::signal::ctrl_c().await.is_err() {
+                    return;
+                }
+                info!("Stopping; press Ctrl+C again to quit without saving");
+                stop.store(true, Ordering::Relaxed);
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    std::process::exit(130);
+                }
+            })
+        })?;
+    Ok(())
+}
+
+/// Log to `glint-svc.log` in the index folder; a service has no console.
+fn init_logging(config: &Config) {
+    let level = config.general.log_level.as_str();
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(level)),
+        )
+        .with_ansi(false);
+
+    let file = config.index_dir().ok().and_then(|dir| {
+        std::fs::create_dir_all(&dir).ok()?;
+        std::fs::File::options()
+            .create(true)
+            .append(true)
+            .open(dir.join("glint-svc.log"))
+            .ok()
+    });
+    match file {
+        Some(file) => builder.with_writer(std::sync::Mutex::new(file)).init(),
+        None => builder.init(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> anyhow::Result<Options> {
+        Options::parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_options() {
+        let options = parse(&["--console", "--data-dir", r"C:\Glint"]).unwrap();
+        assert!(options.console);
+        assert_eq!(options.data_dir, Some(PathBuf::from(r"C:\Glint")));
+        assert!(options.config.is_none());
+        assert_eq!(options.runtime, Runtime::Threads);
+
+        let options = parse(&["--runtime", "tokio"]).unwrap();
+        assert_eq!(options.runtime, Runtime::Tokio);
+
+        let options =
+            parse(&["--set", "server.enabled=false", "--set", "ui.page_size=20"]).unwrap();
+        assert_eq!(options.set, ["server.enabled=false", "ui.page_size=20"]);
+        assert!(parse(&["--runtime", "async"]).is_err());
+
+        assert!(parse(&["--config"]).is_err());
+        assert!(parse(&["watch"]).is_err());
+    }
+}
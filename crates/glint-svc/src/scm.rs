@@ -0,0 +1,144 @@
+//! Running under the Windows Service Control Manager.
+//!
+//! The SCM calls [`service_main`] on a thread of its own and sends stop and
+//! shutdown requests to [`control_handler`], which sets the flag the engine
+//! polls. Progress is reported back with `SetServiceStatus`, including while
+//! the last changes are applied and the index is saved on the way out.
+
+use crate::engine::{self, Runtime};
+use glint_core::Config;
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tracing::error;
+use windows::core::{PCWSTR, PWSTR};
+use windows::Win32::Foundation::{
+    ERROR_CALL_NOT_IMPLEMENTED, ERROR_SERVICE_SPECIFIC_ERROR, NO_ERROR,
+};
+use windows::Win32::System::Services::{
+    RegisterServiceCtrlHandlerExW, SetServiceStatus, StartServiceCtrlDispatcherW,
+    SERVICE_ACCEPT_SHUTDOWN, SERVICE_ACCEPT_STOP, SERVICE_CONTROL_INTERROGATE,
+    SERVICE_CONTROL_SHUTDOWN, SERVICE_CONTROL_STOP, SERVICE_RUNNING, SERVICE_START_PENDING,
+    SERVICE_STATUS, SERVICE_STATUS_CURRENT_STATE, SERVICE_STATUS_HANDLE, SERVICE_STOPPED,
+    SERVICE_STOP_PENDING, SERVICE_TABLE_ENTRYW, SERVICE_WIN32_OWN_PROCESS,
+};
+
+/// Name the GUI installs the service under.
+const SERVICE_NAME: &str = "GlintIndexService";
+
+/// How long the SCM should wait between reports while starting or stopping,
+/// in milliseconds. Loading a large index takes a while.
+const WAIT_HINT_MS: u32 = 60_000;
+
+static CONFIG: OnceLock<(Config, Runtime)> = OnceLock::new();
+static STOP: AtomicBool = AtomicBool::new(false);
+static CHECKPOINT: AtomicU32 = AtomicU32::new(0);
+static STATUS_HANDLE: Mutex<Option<StatusHandle>> = Mutex::new(None);
+
+struct StatusHandle(SERVICE_STATUS_HANDLE);
+
+// The handle is only passed back to SetServiceStatus, which any thread may call
+unsafe impl Send for StatusHandle {}
+
+/// Hand the process over to the SCM; returns once the service has stopped.
+pub fn run(config: Config, runtime: Runtime) -> anyhow::Result<()> {
+    let _ = CONFIG.set((config, runtime));
+    let mut name = to_wide(SERVICE_NAME);
+    let table = [
+        SERVICE_TABLE_ENTRYW {
+            lpServiceName: PWSTR(name.as_mut_ptr()),
+            lpServiceProc: Some(service_main),
+        },
+        SERVICE_TABLE_ENTRYW::default(),
+    ];
+    unsafe { StartServiceCtrlDispatcherW(table.as_ptr()) }.map_err(|e| {
+        anyhow::anyhow!(
+            "Not started by the Service Control Manager ({}); use --console to run in the foreground",
+            e
+        )
+    })
+}
+
+unsafe extern "system" fn service_main(_argc: u32, _argv: *mut PWSTR) {
+    let name = to_wide(SERVICE_NAME);
+    match RegisterServiceCtrlHandlerExW(PCWSTR(name.as_ptr()), Some(control_handler), None) {
+        Ok(handle) => {
+            *STATUS_HANDLE.lock().unwrap_or_else(|e| e.into_inner()) = Some(StatusHandle(handle));
+        }
+        Err(e) => {
+            error!(error = %e, "Cannot register service control handler");
+            return;
+        }
+    }
+
+    set_status(SERVICE_START_PENDING, NO_ERROR.0);
+    let result = match CONFIG.get() {
+        Some((config, runtime)) => engine::run(
+            *runtime,
+            config,
+            &STOP,
+            || set_status(SERVICE_RUNNING, NO_ERROR.0),
+            // Each report moves the checkpoint on, so the SCM keeps waiting
+            || set_status(SERVICE_STOP_PENDING, NO_ERROR.0),
+        ),
+        None => Err(anyhow::anyhow!("No configuration")),
+    };
+    match result {
+        Ok(()) => set_status(SERVICE_STOPPED, NO_ERROR.0),
+        Err(e) => {
+            error!(error = %e, "Service failed");
+            set_status(SERVICE_STOPPED, ERROR_SERVICE_SPECIFIC_ERROR.0);
+        }
+    }
+}
+
+unsafe extern "system" fn control_handler(
+    control: u32,
+    _event_type: u32,
+    _event_data: *mut c_void,
+    _context: *mut c_void,
+) -> u32 {
+    match control {
+        SERVICE_CONTROL_STOP | SERVICE_CONTROL_SHUTDOWN => {
+            set_status(SERVICE_STOP_PENDING, NO_ERROR.0);
+            STOP.store(true, Ordering::Relaxed);
+            NO_ERROR.0
+        }
+        SERVICE_CONTROL_INTERROGATE => NO_ERROR.0,
+        _ => ERROR_CALL_NOT_IMPLEMENTED.0,
+    }
+}
+
+/// Report the service's state to the SCM.
+fn set_status(state: SERVICE_STATUS_CURRENT_STATE, exit_code: u32) {
+    let handle = STATUS_HANDLE.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(handle) = handle.as_ref() else {
+        return;
+    };
+    let pending = state == SERVICE_START_PENDING || state == SERVICE_STOP_PENDING;
+    let status = SERVICE_STATUS {
+        dwServiceType: SERVICE_WIN32_OWN_PROCESS,
+        dwCurrentState: state,
+        dwControlsAccepted: if state == SERVICE_RUNNING {
+            SERVICE_ACCEPT_STOP | SERVICE_ACCEPT_SHUTDOWN
+        } else {
+            0
+        },
+        dwWin32ExitCode: exit_code,
+        // Any failure is reported as the one service-specific code
+        dwServiceSpecificExitCode: u32::from(exit_code == ERROR_SERVICE_SPECIFIC_ERROR.0),
+        dwCheckPoint: if pending {
+            CHECKPOINT.fetch_add(1, Ordering::Relaxed) + 1
+        } else {
+            0
+        },
+        dwWaitHint: if pending { WAIT_HINT_MS } else { 0 },
+    };
+    if let Err(e) = unsafe { SetServiceStatus(handle.0, &status) } {
+        error!(error = %e, "Cannot report service status");
+    }
+}
+
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}